@@ -0,0 +1,98 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use std::path::Path;
+use std::rc::Rc;
+
+use icarus::css::selector::parse_selector_list;
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::html::serialize::outer_html;
+
+const SELECTORS: &[&str] = &[
+    "p",
+    "pre code",
+    "#main-content .docblock p",
+    "table tr td",
+    "nav ul li a",
+];
+
+fn corpus() -> Vec<(String, String)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/corpus");
+    let mut pages: Vec<(String, String)> = std::fs::read_dir(&dir)
+        .expect("benches/corpus exists")
+        .map(|entry| entry.expect("readable corpus entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let html = std::fs::read_to_string(&path).expect("corpus page is UTF-8");
+            (name, html)
+        })
+        .collect();
+    pages.sort();
+    pages
+}
+
+fn count_matches(root: &Rc<Node>, selectors: &[icarus::css::selector::Selector]) -> usize {
+    let mut count = 0;
+    root.walk_rc(&mut |node| {
+        if node.element_name().is_some() && selectors.iter().any(|s| s.matches(node)) {
+            count += 1;
+        }
+    });
+    count
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, html) in corpus() {
+        group.throughput(Throughput::Bytes(html.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &html, |b, html| {
+            b.iter(|| parse_html(black_box(html)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_tag_name(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_elements_by_tag_name");
+    for (name, html) in corpus() {
+        let document = parse_html(&html);
+        group.bench_function(name, |b| {
+            b.iter(|| document.get_elements_by_tag_name(black_box("a")).len())
+        });
+    }
+    group.finish();
+}
+
+fn bench_selectors(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selector_matching");
+    for (name, html) in corpus() {
+        let document = parse_html(&html);
+        for selector in SELECTORS {
+            let selectors = parse_selector_list(selector).expect("benchmark selector parses");
+            group.bench_function(BenchmarkId::new(&name, selector), |b| {
+                b.iter(|| count_matches(&document.root, black_box(&selectors)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for (name, html) in corpus() {
+        let document = parse_html(&html);
+        group.throughput(Throughput::Bytes(outer_html(&document.root).len() as u64));
+        group.bench_function(name, |b| b.iter(|| outer_html(black_box(&document.root))));
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_tag_name,
+    bench_selectors,
+    bench_serialize
+);
+criterion_main!(benches);
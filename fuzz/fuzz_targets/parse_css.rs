@@ -0,0 +1,19 @@
+#![no_main]
+
+use icarus::css::selector::parse_selector_list;
+use icarus::css::{Origin, Stylesheet};
+use icarus::html::parser::parse_html;
+use icarus::loader::decode;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let source = decode(data, None);
+    parse_selector_list(&source);
+    Stylesheet::parse(&source, Origin::Author);
+
+    let document = parse_html(&format!(
+        "<style>{}</style><div class=a><p id=b>x</p></div>",
+        source
+    ));
+    document.restyle();
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::loader::decode;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let document = parse_html(&decode(data, None));
+    LayoutTree::build(&document, LayoutOptions::default());
+});
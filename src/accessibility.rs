@@ -0,0 +1,380 @@
+use html5ever::ns;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData};
+use crate::style::Display;
+
+const HIDDEN_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "template", "noscript", "meta", "link", "title",
+];
+
+const NAME_FROM_CONTENT_ROLES: &[&str] = &[
+    "button",
+    "cell",
+    "checkbox",
+    "columnheader",
+    "gridcell",
+    "heading",
+    "link",
+    "listitem",
+    "menuitem",
+    "option",
+    "radio",
+    "row",
+    "rowheader",
+    "switch",
+    "tab",
+    "tooltip",
+    "treeitem",
+];
+
+pub struct AccessibleNode {
+    pub role: String,
+    pub name: String,
+    pub properties: Vec<(String, String)>,
+    pub node: Rc<Node>,
+    pub children: Vec<AccessibleNode>,
+}
+
+impl AccessibleNode {
+    pub fn walk<F>(&self, visitor: &mut F)
+    where
+        F: FnMut(&AccessibleNode),
+    {
+        visitor(self);
+        for child in &self.children {
+            child.walk(visitor);
+        }
+    }
+
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        self.dump_node(0, &mut out);
+        out
+    }
+
+    fn dump_node(&self, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&self.role);
+        if !self.name.is_empty() {
+            out.push_str(&format!(" {:?}", self.name));
+        }
+        for (key, value) in &self.properties {
+            out.push_str(&format!(" {}={}", key, value));
+        }
+        out.push('\n');
+
+        for child in &self.children {
+            child.dump_node(depth + 1, out);
+        }
+    }
+}
+
+impl Document {
+    pub fn accessibility_tree(&self) -> AccessibleNode {
+        self.restyle();
+        let mut root = AccessibleNode {
+            role: String::from("document"),
+            name: self
+                .get_elements_by_tag_name_ns(&ns!(html), "title")
+                .first()
+                .map(|title| collapse_whitespace(&title.get_text_content()))
+                .unwrap_or_default(),
+            properties: Vec::new(),
+            node: Rc::clone(&self.root),
+            children: Vec::new(),
+        };
+        for child in self.root.children.borrow().iter() {
+            build(self, child, &mut root.children);
+        }
+        root
+    }
+}
+
+fn build(document: &Document, node: &Rc<Node>, out: &mut Vec<AccessibleNode>) {
+    match &node.data {
+        NodeData::Text { contents } => {
+            let text = collapse_whitespace(&contents.borrow());
+            if !text.is_empty() {
+                out.push(AccessibleNode {
+                    role: String::from("text"),
+                    name: text,
+                    properties: Vec::new(),
+                    node: Rc::clone(node),
+                    children: Vec::new(),
+                });
+            }
+        }
+        NodeData::Element { .. } => {
+            if is_hidden(node) {
+                return;
+            }
+
+            let role = role_of(node);
+            let mut children = Vec::new();
+            for child in node.children.borrow().iter() {
+                build(document, child, &mut children);
+            }
+
+            match role {
+                Some(role) if role != "none" && role != "presentation" => {
+                    let name = accessible_name(document, node, &role);
+                    let properties = properties_of(node, &role);
+                    out.push(AccessibleNode {
+                        role,
+                        name,
+                        properties,
+                        node: Rc::clone(node),
+                        children,
+                    });
+                }
+                _ => out.extend(children),
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn is_hidden(node: &Node) -> bool {
+    let name = node.element_name().unwrap_or("");
+    HIDDEN_ELEMENTS.contains(&name)
+        || node.has_attribute("hidden")
+        || node.get_attribute("aria-hidden").as_deref() == Some("true")
+        || (name == "input"
+            && node
+                .get_attribute("type")
+                .is_some_and(|t| t.eq_ignore_ascii_case("hidden")))
+        || node
+            .style
+            .borrow()
+            .as_ref()
+            .is_some_and(|style| style.display == Display::None || !style.is_visible())
+}
+
+pub fn role_of(node: &Node) -> Option<String> {
+    if let Some(role) = node.get_attribute("role")
+        && let Some(first) = role.split_whitespace().next()
+    {
+        return Some(first.to_ascii_lowercase());
+    }
+    implicit_role(node).map(String::from)
+}
+
+pub fn implicit_role(node: &Node) -> Option<&'static str> {
+    let role = match node.element_name()? {
+        "a" | "area" if node.has_attribute("href") => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" => "button",
+        "dialog" => "dialog",
+        "details" => "group",
+        "fieldset" => "group",
+        "footer" => "contentinfo",
+        "form" => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" => match node.get_attribute("alt") {
+            Some(alt) if alt.is_empty() => "presentation",
+            _ => "img",
+        },
+        "input" => match node
+            .get_attribute("type")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "button" | "submit" | "reset" | "image" => "button",
+            "checkbox" => "checkbox",
+            "radio" => "radio",
+            "range" => "slider",
+            "number" => "spinbutton",
+            "search" => "searchbox",
+            _ => "textbox",
+        },
+        "li" => "listitem",
+        "main" => "main",
+        "math" => "math",
+        "nav" => "navigation",
+        "ol" | "ul" | "menu" => "list",
+        "option" => "option",
+        "p" => "paragraph",
+        "progress" => "progressbar",
+        "section" if node.has_attribute("aria-label") || node.has_attribute("aria-labelledby") => {
+            "region"
+        }
+        "select" if node.has_attribute("multiple") => "listbox",
+        "select" => "combobox",
+        "summary" => "button",
+        "table" => "table",
+        "tbody" | "thead" | "tfoot" => "rowgroup",
+        "td" => "cell",
+        "textarea" => "textbox",
+        "th" => "columnheader",
+        "tr" => "row",
+        _ => return None,
+    };
+    Some(role)
+}
+
+fn accessible_name(document: &Document, node: &Rc<Node>, role: &str) -> String {
+    if let Some(ids) = node.get_attribute("aria-labelledby") {
+        let name: Vec<String> = ids
+            .split_whitespace()
+            .filter_map(|id| document.get_element_by_id(id))
+            .map(|label| collapse_whitespace(&label.get_text_content()))
+            .collect();
+        if !name.is_empty() {
+            return name.join(" ");
+        }
+    }
+
+    if let Some(label) = node.get_attribute("aria-label")
+        && !label.trim().is_empty()
+    {
+        return collapse_whitespace(&label);
+    }
+
+    match node.element_name() {
+        Some("img" | "area") => {
+            if let Some(alt) = node.get_attribute("alt") {
+                return collapse_whitespace(&alt);
+            }
+        }
+        Some("input" | "select" | "textarea") => {
+            if let Some(label) = label_for(document, node) {
+                return label;
+            }
+            if let Some(value) = node.get_attribute("value")
+                && role == "button"
+            {
+                return collapse_whitespace(&value);
+            }
+            if let Some(placeholder) = node.get_attribute("placeholder") {
+                return collapse_whitespace(&placeholder);
+            }
+        }
+        Some("table") => {
+            if let Some(caption) = node
+                .children
+                .borrow()
+                .iter()
+                .find(|child| child.element_name() == Some("caption"))
+            {
+                return collapse_whitespace(&caption.get_text_content());
+            }
+        }
+        Some("fieldset") => {
+            if let Some(legend) = node
+                .children
+                .borrow()
+                .iter()
+                .find(|child| child.element_name() == Some("legend"))
+            {
+                return collapse_whitespace(&legend.get_text_content());
+            }
+        }
+        _ => {}
+    }
+
+    if NAME_FROM_CONTENT_ROLES.contains(&role) {
+        let text = collapse_whitespace(&text_alternative(node));
+        if !text.is_empty() {
+            return text;
+        }
+    }
+
+    node.get_attribute("title")
+        .map(|title| collapse_whitespace(&title))
+        .unwrap_or_default()
+}
+
+fn text_alternative(node: &Node) -> String {
+    match &node.data {
+        NodeData::Text { contents } => contents.borrow().clone(),
+        NodeData::Element { .. } => {
+            if is_hidden(node) {
+                return String::new();
+            }
+            if node.element_name() == Some("img") {
+                return node.get_attribute("alt").unwrap_or_default();
+            }
+            if let Some(label) = node.get_attribute("aria-label") {
+                return label;
+            }
+            node.children
+                .borrow()
+                .iter()
+                .map(|child| text_alternative(child))
+                .collect::<Vec<_>>()
+                .join("")
+        }
+        _ => String::new(),
+    }
+}
+
+fn label_for(document: &Document, node: &Rc<Node>) -> Option<String> {
+    if let Some(id) = node.get_attribute("id") {
+        let label = document
+            .get_elements_by_tag_name("label")
+            .into_iter()
+            .find(|label| label.get_attribute("for").as_deref() == Some(id.as_str()));
+        if let Some(label) = label {
+            return Some(collapse_whitespace(&text_alternative(&label)));
+        }
+    }
+
+    let mut current = node.parent.borrow().upgrade();
+    while let Some(ancestor) = current {
+        if ancestor.element_name() == Some("label") {
+            return Some(collapse_whitespace(&text_alternative(&ancestor)));
+        }
+        current = ancestor.parent.borrow().upgrade();
+    }
+    None
+}
+
+fn properties_of(node: &Node, role: &str) -> Vec<(String, String)> {
+    let mut properties = Vec::new();
+
+    if role == "heading" {
+        let level = node
+            .get_attribute("aria-level")
+            .or_else(|| {
+                node.element_name()
+                    .and_then(|name| name.strip_prefix('h'))
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| String::from("2"));
+        properties.push((String::from("level"), level));
+    }
+
+    if matches!(node.element_name(), Some("input")) && matches!(role, "checkbox" | "radio") {
+        let checked = node.has_attribute("checked");
+        properties.push((String::from("checked"), checked.to_string()));
+    }
+
+    if node.has_attribute("disabled") {
+        properties.push((String::from("disabled"), String::from("true")));
+    }
+
+    for attr in node.attributes() {
+        let name = &attr.name.local;
+        if let Some(state) = name.strip_prefix("aria-")
+            && !matches!(
+                state,
+                "label" | "labelledby" | "hidden" | "level" | "describedby"
+            )
+        {
+            properties.push((state.to_string(), attr.value.clone()));
+        }
+    }
+
+    properties
+}
+
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
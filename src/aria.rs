@@ -0,0 +1,337 @@
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::accessibility;
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::dom::{Document, Node};
+
+const ROLES: &[&str] = &[
+    "alert",
+    "alertdialog",
+    "application",
+    "article",
+    "banner",
+    "blockquote",
+    "button",
+    "caption",
+    "cell",
+    "checkbox",
+    "code",
+    "columnheader",
+    "combobox",
+    "complementary",
+    "contentinfo",
+    "definition",
+    "deletion",
+    "dialog",
+    "directory",
+    "document",
+    "emphasis",
+    "feed",
+    "figure",
+    "form",
+    "generic",
+    "grid",
+    "gridcell",
+    "group",
+    "heading",
+    "img",
+    "insertion",
+    "link",
+    "list",
+    "listbox",
+    "listitem",
+    "log",
+    "main",
+    "marquee",
+    "math",
+    "menu",
+    "menubar",
+    "menuitem",
+    "menuitemcheckbox",
+    "menuitemradio",
+    "meter",
+    "navigation",
+    "none",
+    "note",
+    "option",
+    "paragraph",
+    "presentation",
+    "progressbar",
+    "radio",
+    "radiogroup",
+    "region",
+    "row",
+    "rowgroup",
+    "rowheader",
+    "scrollbar",
+    "search",
+    "searchbox",
+    "separator",
+    "slider",
+    "spinbutton",
+    "status",
+    "strong",
+    "subscript",
+    "superscript",
+    "switch",
+    "tab",
+    "table",
+    "tablist",
+    "tabpanel",
+    "term",
+    "textbox",
+    "time",
+    "timer",
+    "toolbar",
+    "tooltip",
+    "tree",
+    "treegrid",
+    "treeitem",
+];
+
+const ATTRIBUTES: &[&str] = &[
+    "aria-activedescendant",
+    "aria-atomic",
+    "aria-autocomplete",
+    "aria-braillelabel",
+    "aria-brailleroledescription",
+    "aria-busy",
+    "aria-checked",
+    "aria-colcount",
+    "aria-colindex",
+    "aria-colindextext",
+    "aria-colspan",
+    "aria-controls",
+    "aria-current",
+    "aria-describedby",
+    "aria-description",
+    "aria-details",
+    "aria-disabled",
+    "aria-errormessage",
+    "aria-expanded",
+    "aria-flowto",
+    "aria-haspopup",
+    "aria-hidden",
+    "aria-invalid",
+    "aria-keyshortcuts",
+    "aria-label",
+    "aria-labelledby",
+    "aria-level",
+    "aria-live",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-orientation",
+    "aria-owns",
+    "aria-placeholder",
+    "aria-posinset",
+    "aria-pressed",
+    "aria-readonly",
+    "aria-relevant",
+    "aria-required",
+    "aria-roledescription",
+    "aria-rowcount",
+    "aria-rowindex",
+    "aria-rowindextext",
+    "aria-rowspan",
+    "aria-selected",
+    "aria-setsize",
+    "aria-sort",
+    "aria-valuemax",
+    "aria-valuemin",
+    "aria-valuenow",
+    "aria-valuetext",
+];
+
+const REQUIRED_PROPERTIES: &[(&str, &[&str])] = &[
+    ("checkbox", &["aria-checked"]),
+    ("combobox", &["aria-expanded"]),
+    ("heading", &["aria-level"]),
+    ("menuitemcheckbox", &["aria-checked"]),
+    ("menuitemradio", &["aria-checked"]),
+    ("meter", &["aria-valuenow"]),
+    ("radio", &["aria-checked"]),
+    ("scrollbar", &["aria-controls", "aria-valuenow"]),
+    ("slider", &["aria-valuenow"]),
+    ("switch", &["aria-checked"]),
+];
+
+const ID_REFERENCES: &[&str] = &[
+    "aria-activedescendant",
+    "aria-controls",
+    "aria-describedby",
+    "aria-details",
+    "aria-errormessage",
+    "aria-flowto",
+    "aria-labelledby",
+    "aria-owns",
+];
+
+const TRUE_FALSE: &[&str] = &[
+    "aria-atomic",
+    "aria-busy",
+    "aria-disabled",
+    "aria-modal",
+    "aria-multiline",
+    "aria-multiselectable",
+    "aria-readonly",
+    "aria-required",
+];
+
+const TRISTATE: &[&str] = &["aria-checked", "aria-pressed"];
+
+impl Document {
+    pub fn validate_aria(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        self.root.walk_rc(&mut |node| {
+            if node.element_name().is_some() {
+                validate_element(self, node, &mut diagnostics);
+            }
+        });
+        diagnostics
+    }
+}
+
+fn validate_element(document: &Document, node: &Rc<Node>, diagnostics: &mut Vec<Diagnostic>) {
+    let explicit_role = node.get_attribute("role");
+
+    if let Some(roles) = &explicit_role {
+        let roles: Vec<String> = roles
+            .split_whitespace()
+            .map(|role| role.to_ascii_lowercase())
+            .collect();
+        if roles.is_empty() {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    "aria-empty-role",
+                    "empty role attribute".into(),
+                )
+                .at(node),
+            );
+        } else if !roles.iter().any(|role| ROLES.contains(&role.as_str())) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "aria-unknown-role",
+                    format!("unknown role {:?}", roles.join(" ")),
+                )
+                .at(node),
+            );
+        }
+
+        if let Some(role) = roles.iter().find(|role| ROLES.contains(&role.as_str()))
+            && accessibility::implicit_role(node) != Some(role.as_str())
+            && let Some((_, required)) = REQUIRED_PROPERTIES.iter().find(|(r, _)| r == role)
+        {
+            for property in required.iter() {
+                if !node.has_attribute(property) {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Error,
+                            "aria-missing-required",
+                            format!("role {:?} requires {}", role, property),
+                        )
+                        .at(node),
+                    );
+                }
+            }
+        }
+    }
+
+    for attr in node.attributes() {
+        let name = &*attr.name.local;
+        if !name.starts_with("aria-") {
+            continue;
+        }
+
+        if !ATTRIBUTES.contains(&name) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "aria-unknown-attribute",
+                    format!("unknown ARIA attribute {}", name),
+                )
+                .at(node),
+            );
+            continue;
+        }
+
+        let value = attr.value.trim();
+        if TRUE_FALSE.contains(&name) && !matches!(value, "true" | "false") {
+            diagnostics.push(invalid_value(node, name, value));
+        }
+        if TRISTATE.contains(&name) && !matches!(value, "true" | "false" | "mixed") {
+            diagnostics.push(invalid_value(node, name, value));
+        }
+        if matches!(name, "aria-hidden" | "aria-expanded" | "aria-selected")
+            && !matches!(value, "true" | "false" | "undefined")
+        {
+            diagnostics.push(invalid_value(node, name, value));
+        }
+
+        if ID_REFERENCES.contains(&name) {
+            for id in value.split_whitespace() {
+                if document.get_element_by_id(id).is_none() {
+                    diagnostics.push(
+                        Diagnostic::new(
+                            Severity::Warning,
+                            "aria-broken-reference",
+                            format!("{} references missing id {:?}", name, id),
+                        )
+                        .at(node),
+                    );
+                }
+            }
+        }
+    }
+
+    if node.get_attribute("aria-hidden").as_deref() == Some("true") {
+        let mut focusable = None;
+        node.walk_rc(&mut |descendant| {
+            if focusable.is_none() && is_focusable(descendant) {
+                focusable = Some(Rc::clone(descendant));
+            }
+        });
+        if let Some(focusable) = focusable {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "aria-hidden-focusable",
+                    "focusable element inside aria-hidden=\"true\"".into(),
+                )
+                .at(&focusable),
+            );
+        }
+    }
+}
+
+fn invalid_value(node: &Rc<Node>, name: &str, value: &str) -> Diagnostic {
+    Diagnostic::new(
+        Severity::Error,
+        "aria-invalid-value",
+        format!("invalid value {:?} for {}", value, name),
+    )
+    .at(node)
+}
+
+pub fn is_focusable(node: &Node) -> bool {
+    if node.has_attribute("disabled") {
+        return false;
+    }
+    if let Some(tabindex) = node.get_attribute("tabindex") {
+        return tabindex.trim().parse::<i32>().is_ok_and(|index| index >= 0);
+    }
+    match node.element_name() {
+        Some("a" | "area") => node.has_attribute("href"),
+        Some("input") => !node
+            .get_attribute("type")
+            .is_some_and(|t| t.eq_ignore_ascii_case("hidden")),
+        Some("button" | "select" | "textarea" | "summary" | "iframe") => true,
+        _ => node
+            .get_attribute("contenteditable")
+            .is_some_and(|v| v != "false"),
+    }
+}
@@ -0,0 +1,543 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+use url::Url;
+
+use crate::dom::{Document, Node, NodeData, NodeId};
+use crate::loader::{self, Load};
+use crate::render;
+use crate::settings::Settings;
+use crate::storage::{Storage, StorageContext};
+
+pub const DEFAULT_PORT: u16 = 9222;
+const VIEWPORT_WIDTH: usize = 1024;
+const VIEWPORT_HEIGHT: usize = 768;
+const TARGET_ID: &str = "icarus-page";
+const FRAME_ID: &str = "icarus-frame";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug)]
+struct ProtocolError {
+    code: i64,
+    message: String,
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+fn invalid_params(message: String) -> anyhow::Error {
+    ProtocolError {
+        code: INVALID_PARAMS,
+        message,
+    }
+    .into()
+}
+
+fn required_str<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| invalid_params(format!("missing {}", name)))
+}
+
+fn error_reply(id: Value, code: i64, message: String) -> Value {
+    json!({ "id": id, "error": { "code": code, "message": message } })
+}
+
+pub struct Session {
+    document: Document,
+    loader_count: usize,
+    settings: Settings,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new(Document::new())
+    }
+}
+
+impl Session {
+    pub fn new(document: Document) -> Self {
+        Session {
+            document,
+            loader_count: 0,
+            settings: Settings::default(),
+        }
+    }
+
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Sets the page's storage; documents it navigates to share it.
+    pub fn with_storage(mut self, storage: StorageContext) -> Self {
+        self.document.storage = storage;
+        self
+    }
+
+    pub fn handle(&mut self, method: &str, params: &Value) -> Result<(Value, Vec<Value>)> {
+        match method {
+            "Page.enable" | "DOM.enable" | "Runtime.enable" | "Network.enable"
+            | "Performance.enable" | "DOMStorage.enable" | "DOMStorage.disable" => {
+                Ok((json!({}), Vec::new()))
+            }
+            "Page.navigate" => self.navigate(params),
+            "Page.captureScreenshot" => self.capture_screenshot(params),
+            "DOM.getDocument" => self.get_document(params),
+            "DOM.describeNode" => self.describe_node(params),
+            "Performance.getMetrics" => Ok((self.metrics(), Vec::new())),
+            "DOMStorage.getDOMStorageItems" => {
+                let entries: Vec<Value> = self.storage(params, |area| {
+                    area.items()
+                        .map(|(key, value)| json!([key, value]))
+                        .collect()
+                })?;
+                Ok((json!({ "entries": entries }), Vec::new()))
+            }
+            "DOMStorage.setDOMStorageItem" => {
+                let key = required_str(&params["key"], "key")?;
+                let value = required_str(&params["value"], "value")?;
+                self.storage(params, |area| area.set_item(key, value))??;
+                Ok((json!({}), Vec::new()))
+            }
+            "DOMStorage.removeDOMStorageItem" => {
+                let key = required_str(&params["key"], "key")?;
+                self.storage(params, |area| area.remove_item(key))?;
+                Ok((json!({}), Vec::new()))
+            }
+            "DOMStorage.clear" => {
+                self.storage(params, Storage::clear)?;
+                Ok((json!({}), Vec::new()))
+            }
+            "Runtime.evaluate" => Ok((
+                json!({
+                    "result": { "type": "undefined" },
+                    "exceptionDetails": {
+                        "exceptionId": 1,
+                        "text": "Uncaught: script evaluation is not supported",
+                        "lineNumber": 0,
+                        "columnNumber": 0,
+                    }
+                }),
+                Vec::new(),
+            )),
+            _ => Err(ProtocolError {
+                code: METHOD_NOT_FOUND,
+                message: format!("'{}' wasn't found", method),
+            }
+            .into()),
+        }
+    }
+
+    // Anything that isn't a ProtocolError came from running the command, so
+    // it's reported as a generic server error.
+    pub fn dispatch(&mut self, text: &str) -> (Value, Vec<Value>) {
+        let request: Value = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(err) => {
+                return (
+                    error_reply(Value::Null, PARSE_ERROR, err.to_string()),
+                    Vec::new(),
+                );
+            }
+        };
+        let id = request["id"].clone();
+        let method = request["method"].as_str().unwrap_or("");
+        match self.handle(method, &request["params"]) {
+            Ok((result, events)) => (json!({ "id": id, "result": result }), events),
+            Err(err) => {
+                let code = err
+                    .downcast_ref::<ProtocolError>()
+                    .map_or(SERVER_ERROR, |err| err.code);
+                (error_reply(id, code, format!("{:#}", err)), Vec::new())
+            }
+        }
+    }
+
+    fn navigate(&mut self, params: &Value) -> Result<(Value, Vec<Value>)> {
+        let url = required_str(&params["url"], "url")?;
+        self.loader_count += 1;
+        let loader_id = format!("loader-{}", self.loader_count);
+
+        match loader::load_with(url, &self.settings) {
+            Ok(Load::Document(mut document)) => {
+                document.storage = self.document.storage.clone();
+                self.document = document;
+            }
+            Ok(Load::Download(_)) => {
+                return Ok((
+                    json!({ "frameId": FRAME_ID, "loaderId": loader_id, "errorText": "net::ERR_ABORTED" }),
+                    Vec::new(),
+                ));
+            }
+            Err(err) => {
+                return Ok((
+                    json!({ "frameId": FRAME_ID, "loaderId": loader_id, "errorText": format!("{:#}", err) }),
+                    Vec::new(),
+                ));
+            }
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let events = vec![
+            json!({ "method": "Page.domContentEventFired", "params": { "timestamp": timestamp } }),
+            json!({ "method": "Page.loadEventFired", "params": { "timestamp": timestamp } }),
+            json!({ "method": "Page.frameStoppedLoading", "params": { "frameId": FRAME_ID } }),
+        ];
+        Ok((
+            json!({ "frameId": FRAME_ID, "loaderId": loader_id }),
+            events,
+        ))
+    }
+
+    fn storage<R>(&self, params: &Value, f: impl FnOnce(&mut Storage) -> R) -> Result<R> {
+        let id = &params["storageId"];
+        let origin = required_str(&id["securityOrigin"], "storageId.securityOrigin")?;
+        let url = Url::parse(origin)
+            .map_err(|_| invalid_params(format!("invalid origin: {}", origin)))?;
+        let storage = &self.document.storage;
+        if id["isLocalStorage"].as_bool().unwrap_or(false) {
+            storage.local(&url, f)
+        } else {
+            storage.session(&url, f)
+        }
+    }
+
+    fn metrics(&self) -> Value {
+        let metrics: Vec<Value> = self
+            .document
+            .performance
+            .metrics()
+            .milestones()
+            .into_iter()
+            .filter_map(|(name, offset)| {
+                Some(json!({ "name": name, "value": offset?.as_secs_f64() }))
+            })
+            .collect();
+        json!({ "metrics": metrics })
+    }
+
+    fn capture_screenshot(&mut self, params: &Value) -> Result<(Value, Vec<Value>)> {
+        if let Some(format) = params["format"].as_str()
+            && format != "png"
+        {
+            return Err(invalid_params(format!(
+                "unsupported screenshot format: {}",
+                format
+            )));
+        }
+        let full_page = params["captureBeyondViewport"].as_bool().unwrap_or(false);
+        let height = if full_page {
+            None
+        } else {
+            Some(VIEWPORT_HEIGHT)
+        };
+        let canvas = render::raster(&self.document, VIEWPORT_WIDTH, height);
+        Ok((
+            json!({ "data": base64_encode(&canvas.to_png()?) }),
+            Vec::new(),
+        ))
+    }
+
+    fn describe(&mut self, node: &Rc<Node>, depth: i64) -> Value {
+        let id = node.id.0;
+        let (node_type, node_name, local_name, node_value) = match &node.data {
+            NodeData::Document { .. } => {
+                (9, String::from("#document"), String::new(), String::new())
+            }
+            NodeData::DocumentFragment | NodeData::ShadowRoot { .. } => (
+                11,
+                String::from("#document-fragment"),
+                String::new(),
+                String::new(),
+            ),
+            NodeData::Element { name, .. } => (
+                1,
+                name.local.as_ref().to_ascii_uppercase(),
+                name.local.to_string(),
+                String::new(),
+            ),
+            NodeData::Text { contents } => (
+                3,
+                String::from("#text"),
+                String::new(),
+                contents.borrow().clone(),
+            ),
+            NodeData::Comment { contents } => (
+                8,
+                String::from("#comment"),
+                String::new(),
+                contents.borrow().clone(),
+            ),
+            NodeData::Doctype { name, .. } => (10, name.clone(), String::new(), String::new()),
+        };
+
+        let children = node.children.borrow().clone();
+        let mut value = json!({
+            "nodeId": id,
+            "backendNodeId": id,
+            "nodeType": node_type,
+            "nodeName": node_name,
+            "localName": local_name,
+            "nodeValue": node_value,
+            "childNodeCount": children.len(),
+        });
+
+        if let NodeData::Element { .. } = node.data {
+            let attributes: Vec<String> = node
+                .attributes()
+                .into_iter()
+                .flat_map(|attr| [attr.name.local.to_string(), attr.value])
+                .collect();
+            value["attributes"] = json!(attributes);
+        }
+        if let NodeData::Document { .. } = node.data {
+            value["documentURL"] = json!(
+                self.document
+                    .url()
+                    .map(|url| url.to_string())
+                    .unwrap_or_else(|| String::from("about:blank"))
+            );
+        }
+        if depth != 0 {
+            let described: Vec<Value> = children
+                .iter()
+                .map(|child| self.describe(child, depth - 1))
+                .collect();
+            value["children"] = json!(described);
+        }
+        value
+    }
+
+    fn get_document(&mut self, params: &Value) -> Result<(Value, Vec<Value>)> {
+        let depth = params["depth"].as_i64().unwrap_or(1);
+        let root = Rc::clone(&self.document.root);
+        Ok((json!({ "root": self.describe(&root, depth) }), Vec::new()))
+    }
+
+    fn describe_node(&mut self, params: &Value) -> Result<(Value, Vec<Value>)> {
+        let id = params["nodeId"]
+            .as_u64()
+            .or_else(|| params["backendNodeId"].as_u64())
+            .ok_or_else(|| invalid_params(String::from("missing nodeId")))?;
+        let node = self
+            .document
+            .node_by_id(NodeId(id))
+            .with_context(|| format!("no node with id {}", id))?;
+        let depth = params["depth"].as_i64().unwrap_or(0);
+        Ok((json!({ "node": self.describe(&node, depth) }), Vec::new()))
+    }
+}
+
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn read_request_head(stream: &mut TcpStream) -> Result<String> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte)? == 0 {
+            bail!("connection closed during handshake");
+        }
+        head.push(byte[0]);
+        if head.len() > 16 * 1024 {
+            bail!("request head too large");
+        }
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn respond_json(stream: &mut TcpStream, body: &Value) -> Result<()> {
+    let body = serde_json::to_string_pretty(body)?;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn respond_forbidden(stream: &mut TcpStream, reason: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 403 Forbidden\r\nContent-Type: text/plain; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        reason.len(),
+        reason
+    )?;
+    Ok(())
+}
+
+fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().skip(1).find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+// Like Chrome, only loopback Host names are served so that DNS rebinding
+// can't reach the endpoint, and a WebSocket opened by a web page (which
+// always carries an Origin) needs that origin to be allowed explicitly.
+pub struct Server {
+    listener: TcpListener,
+    session: Session,
+    allowed_origins: Vec<String>,
+}
+
+impl Server {
+    pub fn bind(port: u16, session: Session) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .with_context(|| format!("binding port {}", port))?;
+        Ok(Server {
+            listener,
+            session,
+            allowed_origins: Vec::new(),
+        })
+    }
+
+    /// Origins that may open the debugging WebSocket; `*` allows any.
+    pub fn allow_origins(mut self, origins: Vec<String>) -> Self {
+        self.allowed_origins = origins;
+        self
+    }
+
+    fn host_allowed(&self, host: Option<&str>) -> Result<bool> {
+        let port = self.port()?;
+        Ok(host.is_some_and(|host| {
+            host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port)
+        }))
+    }
+
+    fn origin_allowed(&self, origin: Option<&str>) -> bool {
+        origin.is_none_or(|origin| {
+            self.allowed_origins
+                .iter()
+                .any(|allowed| allowed == "*" || allowed.trim_end_matches('/') == origin)
+        })
+    }
+
+    pub fn port(&self) -> Result<u16> {
+        Ok(self.listener.local_addr()?.port())
+    }
+
+    fn websocket_url(&self) -> Result<String> {
+        Ok(format!(
+            "ws://127.0.0.1:{}/devtools/page/{}",
+            self.port()?,
+            TARGET_ID
+        ))
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept()?;
+            if let Err(err) = self.serve(stream) {
+                log::warn!("devtools connection failed: {:#}", err);
+            }
+        }
+    }
+
+    fn serve(&mut self, mut stream: TcpStream) -> Result<()> {
+        let head = read_request_head(&mut stream)?;
+        let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+        if !self.host_allowed(header(&head, "Host"))? {
+            return respond_forbidden(&mut stream, "Host header is not a loopback address");
+        }
+        let key = header(&head, "Sec-WebSocket-Key").map(str::to_string);
+
+        let Some(key) = key else {
+            let url = self
+                .session
+                .document
+                .url()
+                .map(|url| url.to_string())
+                .unwrap_or_else(|| String::from("about:blank"));
+            let target = json!({
+                "id": TARGET_ID,
+                "type": "page",
+                "title": url,
+                "url": url,
+                "webSocketDebuggerUrl": self.websocket_url()?,
+            });
+            let body = match path.trim_end_matches('/') {
+                "/json/version" => json!({
+                    "Browser": concat!("Icarus/", env!("CARGO_PKG_VERSION")),
+                    "Protocol-Version": "1.3",
+                    "webSocketDebuggerUrl": self.websocket_url()?,
+                }),
+                _ => json!([target]),
+            };
+            return respond_json(&mut stream, &body);
+        };
+
+        if !self.origin_allowed(header(&head, "Origin")) {
+            return respond_forbidden(
+                &mut stream,
+                "Origin is not allowed; start with --remote-allow-origins",
+            );
+        }
+
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            tungstenite::handshake::derive_accept_key(key.as_bytes())
+        )?;
+        let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+        loop {
+            let message = match socket.read() {
+                Ok(message) => message,
+                Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            };
+            let text = match message {
+                Message::Text(text) => text,
+                Message::Close(_) => return Ok(()),
+                _ => continue,
+            };
+
+            let (reply, events) = self.session.dispatch(&text);
+            socket.send(Message::text(reply.to_string()))?;
+            for event in events {
+                socket.send(Message::text(event.to_string()))?;
+            }
+        }
+    }
+}
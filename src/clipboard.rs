@@ -0,0 +1,36 @@
+use anyhow::{Context, Result};
+
+use crate::dom::Document;
+
+pub fn copy_text(text: &str) -> Result<()> {
+    let mut clipboard = arboard::Clipboard::new().context("opening the system clipboard")?;
+    clipboard
+        .set_text(text)
+        .context("writing to the system clipboard")
+}
+
+#[cfg(target_os = "linux")]
+pub fn copy_text_and_wait(text: &str) -> Result<()> {
+    use arboard::SetExtLinux;
+
+    let mut clipboard = arboard::Clipboard::new().context("opening the system clipboard")?;
+    clipboard
+        .set()
+        .wait()
+        .text(text)
+        .context("writing to the system clipboard")
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn copy_text_and_wait(text: &str) -> Result<()> {
+    copy_text(text)
+}
+
+pub fn copy_selection(document: &Document) -> Result<bool> {
+    let text = document.selected_text();
+    if text.is_empty() {
+        return Ok(false);
+    }
+    copy_text(&text)?;
+    Ok(true)
+}
@@ -0,0 +1,532 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::string::String;
+use std::time::{Instant, SystemTime};
+use std::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConsoleLevel {
+    Debug,
+    Log,
+    Info,
+    Warn,
+    Error,
+}
+
+impl ConsoleLevel {
+    pub fn color(&self) -> &'static str {
+        match self {
+            ConsoleLevel::Debug => "\x1b[2m",
+            ConsoleLevel::Log => "",
+            ConsoleLevel::Info => "\x1b[36m",
+            ConsoleLevel::Warn => "\x1b[33m",
+            ConsoleLevel::Error => "\x1b[31m",
+        }
+    }
+}
+
+impl fmt::Display for ConsoleLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<ConsoleValue>),
+    Object(Vec<(String, ConsoleValue)>),
+}
+
+impl From<&str> for ConsoleValue {
+    fn from(value: &str) -> Self {
+        ConsoleValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ConsoleValue {
+    fn from(value: String) -> Self {
+        ConsoleValue::String(value)
+    }
+}
+
+impl From<f64> for ConsoleValue {
+    fn from(value: f64) -> Self {
+        ConsoleValue::Number(value)
+    }
+}
+
+impl From<i64> for ConsoleValue {
+    fn from(value: i64) -> Self {
+        ConsoleValue::Number(value as f64)
+    }
+}
+
+impl From<bool> for ConsoleValue {
+    fn from(value: bool) -> Self {
+        ConsoleValue::Bool(value)
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.is_nan() {
+        String::from("NaN")
+    } else if value.is_infinite() {
+        String::from(if value > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if value.fract() == 0.0 && value.abs() < 1e21 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+impl ConsoleValue {
+    pub fn to_display_string(&self) -> String {
+        match self {
+            ConsoleValue::String(value) => value.clone(),
+            other => other.inspect(),
+        }
+    }
+
+    pub fn inspect(&self) -> String {
+        match self {
+            ConsoleValue::Undefined => String::from("undefined"),
+            ConsoleValue::Null => String::from("null"),
+            ConsoleValue::Bool(value) => value.to_string(),
+            ConsoleValue::Number(value) => format_number(*value),
+            ConsoleValue::String(value) => format!("'{}'", value),
+            ConsoleValue::Array(items) => format!(
+                "[ {} ]",
+                items
+                    .iter()
+                    .map(|item| item.inspect())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ConsoleValue::Object(entries) if entries.is_empty() => String::from("{}"),
+            ConsoleValue::Object(entries) => format!(
+                "{{ {} }}",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value.inspect()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+
+    fn to_number(&self) -> f64 {
+        match self {
+            ConsoleValue::Number(value) => *value,
+            ConsoleValue::String(value) => {
+                let value = value.trim();
+                let end = value
+                    .char_indices()
+                    .find(|(i, c)| {
+                        !(c.is_ascii_digit() || *c == '.' || (*i == 0 && (*c == '-' || *c == '+')))
+                    })
+                    .map(|(i, _)| i)
+                    .unwrap_or(value.len());
+                value[..end].parse().unwrap_or(f64::NAN)
+            }
+            _ => f64::NAN,
+        }
+    }
+
+    fn entries(&self) -> Vec<(String, ConsoleValue)> {
+        match self {
+            ConsoleValue::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| (index.to_string(), item.clone()))
+                .collect(),
+            ConsoleValue::Object(entries) => entries.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    pub text: String,
+    pub styles: Vec<String>,
+    pub group_depth: usize,
+    pub timestamp: SystemTime,
+}
+
+impl ConsoleMessage {
+    pub fn to_colored_string(&self) -> String {
+        let color = self.level.color();
+        let reset = if color.is_empty() { "" } else { "\x1b[0m" };
+        self.indented()
+            .lines()
+            .map(|line| format!("{}{}{}", color, line, reset))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn indented(&self) -> String {
+        let indent = "  ".repeat(self.group_depth);
+        self.text
+            .lines()
+            .map(|line| format!("{}{}", indent, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for ConsoleMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.indented())
+    }
+}
+
+pub fn format_arguments(args: &[ConsoleValue]) -> (String, Vec<String>) {
+    let mut styles = Vec::new();
+    let Some((ConsoleValue::String(format), rest)) = args.split_first() else {
+        return (join_values(args), styles);
+    };
+
+    let mut out = String::new();
+    let mut rest = rest.iter();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let Some(&specifier) = chars.peek() else {
+            out.push(c);
+            break;
+        };
+        if specifier == '%' {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+        if !"sdifoOc".contains(specifier) {
+            out.push(c);
+            continue;
+        }
+        let Some(arg) = rest.next() else {
+            out.push(c);
+            continue;
+        };
+        chars.next();
+        match specifier {
+            's' => out.push_str(&arg.to_display_string()),
+            'd' | 'i' => {
+                let number = arg.to_number();
+                out.push_str(&format_number(if number.is_nan() {
+                    number
+                } else {
+                    number.trunc()
+                }));
+            }
+            'f' => out.push_str(&format_number(arg.to_number())),
+            'o' | 'O' => out.push_str(&arg.inspect()),
+            'c' => styles.push(arg.to_display_string()),
+            _ => {}
+        }
+    }
+
+    for arg in rest {
+        out.push(' ');
+        out.push_str(&arg.to_display_string());
+    }
+    (out, styles)
+}
+
+fn join_values(args: &[ConsoleValue]) -> String {
+    args.iter()
+        .map(|arg| arg.to_display_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn format_table(data: &ConsoleValue, columns: Option<&[String]>) -> Option<String> {
+    let rows = data.entries();
+    if rows.is_empty() {
+        return None;
+    }
+
+    let mut headers = vec![String::from("(index)")];
+    let mut has_values = false;
+    for (_, row) in &rows {
+        match row {
+            ConsoleValue::Array(_) | ConsoleValue::Object(_) => {
+                for (key, _) in row.entries() {
+                    if !headers.contains(&key)
+                        && columns.is_none_or(|columns| columns.contains(&key))
+                    {
+                        headers.push(key);
+                    }
+                }
+            }
+            _ => has_values = true,
+        }
+    }
+    if has_values {
+        headers.push(String::from("Values"));
+    }
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|(index, row)| {
+            let entries = row.entries();
+            headers
+                .iter()
+                .enumerate()
+                .map(|(column, header)| {
+                    if column == 0 {
+                        return index.clone();
+                    }
+                    if header == "Values"
+                        && !matches!(row, ConsoleValue::Array(_) | ConsoleValue::Object(_))
+                    {
+                        return row.inspect();
+                    }
+                    entries
+                        .iter()
+                        .find(|(key, _)| key == header)
+                        .map(|(_, value)| value.inspect())
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            cells
+                .iter()
+                .map(|row| row[column].chars().count())
+                .chain(std::iter::once(header.chars().count()))
+                .max()
+                .unwrap_or(0)
+                + 2
+        })
+        .collect();
+
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|width| "─".repeat(*width)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let row = |values: &[String]| {
+        let padded: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| {
+                let total = width - value.chars().count();
+                let left = total / 2;
+                format!("{}{}{}", " ".repeat(left), value, " ".repeat(total - left))
+            })
+            .collect();
+        format!("│{}│", padded.join("│"))
+    };
+
+    let mut lines = vec![border("┌", "┬", "┐"), row(&headers), border("├", "┼", "┤")];
+    lines.extend(cells.iter().map(|cells| row(cells)));
+    lines.push(border("└", "┴", "┘"));
+    Some(lines.join("\n"))
+}
+
+type Listener = Box<dyn Fn(&ConsoleMessage)>;
+
+#[derive(Default)]
+pub struct Console {
+    messages: RefCell<Vec<ConsoleMessage>>,
+    listeners: RefCell<Vec<Listener>>,
+    group_depth: RefCell<usize>,
+    counters: RefCell<HashMap<String, u64>>,
+    timers: RefCell<HashMap<String, Instant>>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console::default()
+    }
+
+    pub fn subscribe<F>(&self, listener: F)
+    where
+        F: Fn(&ConsoleMessage) + 'static,
+    {
+        self.listeners.borrow_mut().push(Box::new(listener));
+    }
+
+    pub fn messages(&self) -> Vec<ConsoleMessage> {
+        self.messages.borrow().clone()
+    }
+
+    fn emit(&self, level: ConsoleLevel, text: String, styles: Vec<String>) {
+        let message = ConsoleMessage {
+            level,
+            text,
+            styles,
+            group_depth: *self.group_depth.borrow(),
+            timestamp: SystemTime::now(),
+        };
+        for listener in self.listeners.borrow().iter() {
+            listener(&message);
+        }
+        self.messages.borrow_mut().push(message);
+    }
+
+    pub fn print(&self, level: ConsoleLevel, args: &[ConsoleValue]) {
+        let (text, styles) = format_arguments(args);
+        self.emit(level, text, styles);
+    }
+
+    pub fn log(&self, args: &[ConsoleValue]) {
+        self.print(ConsoleLevel::Log, args);
+    }
+
+    pub fn info(&self, args: &[ConsoleValue]) {
+        self.print(ConsoleLevel::Info, args);
+    }
+
+    pub fn warn(&self, args: &[ConsoleValue]) {
+        self.print(ConsoleLevel::Warn, args);
+    }
+
+    pub fn error(&self, args: &[ConsoleValue]) {
+        self.print(ConsoleLevel::Error, args);
+    }
+
+    pub fn debug(&self, args: &[ConsoleValue]) {
+        self.print(ConsoleLevel::Debug, args);
+    }
+
+    pub fn trace(&self, args: &[ConsoleValue]) {
+        let (text, styles) = format_arguments(args);
+        self.emit(ConsoleLevel::Debug, format!("Trace: {}", text), styles);
+    }
+
+    pub fn assert(&self, condition: bool, args: &[ConsoleValue]) {
+        if condition {
+            return;
+        }
+        let (text, styles) = format_arguments(args);
+        let text = if text.is_empty() {
+            String::from("Assertion failed")
+        } else {
+            format!("Assertion failed: {}", text)
+        };
+        self.emit(ConsoleLevel::Error, text, styles);
+    }
+
+    pub fn table(&self, data: &ConsoleValue, columns: Option<&[String]>) {
+        match format_table(data, columns) {
+            Some(table) => self.emit(ConsoleLevel::Log, table, Vec::new()),
+            None => self.log(std::slice::from_ref(data)),
+        }
+    }
+
+    pub fn group(&self, args: &[ConsoleValue]) {
+        if !args.is_empty() {
+            self.log(args);
+        }
+        *self.group_depth.borrow_mut() += 1;
+    }
+
+    pub fn group_collapsed(&self, args: &[ConsoleValue]) {
+        self.group(args);
+    }
+
+    pub fn group_end(&self) {
+        let mut depth = self.group_depth.borrow_mut();
+        *depth = depth.saturating_sub(1);
+    }
+
+    pub fn count(&self, label: Option<&str>) {
+        let label = label.unwrap_or("default").to_string();
+        let count = {
+            let mut counters = self.counters.borrow_mut();
+            let count = counters.entry(label.clone()).or_insert(0);
+            *count += 1;
+            *count
+        };
+        self.emit(
+            ConsoleLevel::Info,
+            format!("{}: {}", label, count),
+            Vec::new(),
+        );
+    }
+
+    pub fn count_reset(&self, label: Option<&str>) {
+        let label = label.unwrap_or("default");
+        if self.counters.borrow_mut().remove(label).is_none() {
+            self.emit(
+                ConsoleLevel::Warn,
+                format!("Count for '{}' does not exist", label),
+                Vec::new(),
+            );
+        }
+    }
+
+    pub fn time(&self, label: Option<&str>) {
+        let label = label.unwrap_or("default").to_string();
+        let exists = self.timers.borrow().contains_key(&label);
+        if exists {
+            self.emit(
+                ConsoleLevel::Warn,
+                format!("Timer '{}' already exists", label),
+                Vec::new(),
+            );
+            return;
+        }
+        self.timers.borrow_mut().insert(label, Instant::now());
+    }
+
+    pub fn time_log(&self, label: Option<&str>, args: &[ConsoleValue]) {
+        self.report_timer(label, args, false);
+    }
+
+    pub fn time_end(&self, label: Option<&str>) {
+        self.report_timer(label, &[], true);
+    }
+
+    fn report_timer(&self, label: Option<&str>, args: &[ConsoleValue], remove: bool) {
+        let label = label.unwrap_or("default");
+        let start = if remove {
+            self.timers.borrow_mut().remove(label)
+        } else {
+            self.timers.borrow().get(label).copied()
+        };
+        let Some(start) = start else {
+            self.emit(
+                ConsoleLevel::Warn,
+                format!("Timer '{}' does not exist", label),
+                Vec::new(),
+            );
+            return;
+        };
+
+        let mut text = format!("{}: {:.3}ms", label, start.elapsed().as_secs_f64() * 1000.0);
+        if !args.is_empty() {
+            text.push(' ');
+            text.push_str(&join_values(args));
+        }
+        self.emit(ConsoleLevel::Info, text, Vec::new());
+    }
+
+    pub fn clear(&self) {
+        self.messages.borrow_mut().clear();
+        *self.group_depth.borrow_mut() = 0;
+    }
+}
@@ -0,0 +1,102 @@
+use std::vec::Vec;
+
+use html5ever::local_name;
+
+use crate::dom::{Node, NodeData};
+
+const KEY_BITS: u32 = 12;
+const KEY_MASK: u32 = (1 << KEY_BITS) - 1;
+const COUNTERS: usize = 1 << KEY_BITS;
+
+pub fn hash_name(name: &str, ignore_case: bool) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in name.bytes() {
+        let byte = if ignore_case {
+            byte.to_ascii_lowercase()
+        } else {
+            byte
+        };
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+pub fn element_hashes(element: &Node) -> Vec<u32> {
+    let NodeData::Element { name, attrs } = &element.data else {
+        return Vec::new();
+    };
+    let mut hashes = vec![hash_name(&name.local, true)];
+    for attr in attrs.borrow().iter() {
+        if attr.name.local == local_name!("id") {
+            hashes.push(hash_name(&attr.value, false));
+        } else if attr.name.local == local_name!("class") {
+            hashes.extend(
+                attr.value
+                    .split_ascii_whitespace()
+                    .map(|class| hash_name(class, false)),
+            );
+        }
+    }
+    hashes
+}
+
+pub struct AncestorFilter {
+    counters: Vec<u8>,
+    stack: Vec<Vec<u32>>,
+}
+
+impl Default for AncestorFilter {
+    fn default() -> Self {
+        AncestorFilter::new()
+    }
+}
+
+impl AncestorFilter {
+    pub fn new() -> Self {
+        AncestorFilter {
+            counters: vec![0; COUNTERS],
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn push(&mut self, element: &Node) {
+        let hashes = element_hashes(element);
+        for &hash in &hashes {
+            for slot in slots(hash) {
+                let counter = &mut self.counters[slot];
+                *counter = counter.saturating_add(1);
+            }
+        }
+        self.stack.push(hashes);
+    }
+
+    pub fn pop(&mut self) {
+        let Some(hashes) = self.stack.pop() else {
+            return;
+        };
+        for hash in hashes {
+            for slot in slots(hash) {
+                let counter = &mut self.counters[slot];
+                if *counter != u8::MAX {
+                    *counter -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn might_contain(&self, hash: u32) -> bool {
+        slots(hash).iter().all(|&slot| self.counters[slot] != 0)
+    }
+}
+
+fn slots(hash: u32) -> [usize; 2] {
+    [
+        (hash & KEY_MASK) as usize,
+        ((hash >> KEY_BITS) & KEY_MASK) as usize,
+    ]
+}
@@ -0,0 +1,95 @@
+use std::string::String;
+use std::vec::Vec;
+
+use super::Declaration;
+use super::selector::split_top_level;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontSource {
+    Url { url: String, format: Option<String> },
+    Local(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFace {
+    pub family: String,
+    pub sources: Vec<FontSource>,
+    pub bold: bool,
+    pub italic: bool,
+}
+
+impl FontFace {
+    pub fn from_declarations(declarations: &[Declaration]) -> Option<FontFace> {
+        let mut face = FontFace {
+            family: String::new(),
+            sources: Vec::new(),
+            bold: false,
+            italic: false,
+        };
+        for declaration in declarations {
+            let value = declaration.value.trim();
+            match declaration.name.as_str() {
+                "font-family" => face.family = unquote(value).to_string(),
+                "src" => face.sources = parse_sources(value),
+                "font-weight" => face.bold = is_bold(value),
+                "font-style" => {
+                    let value = value.to_ascii_lowercase();
+                    face.italic = value.starts_with("italic") || value.starts_with("oblique");
+                }
+                _ => {}
+            }
+        }
+        (!face.family.is_empty() && !face.sources.is_empty()).then_some(face)
+    }
+}
+
+fn parse_sources(value: &str) -> Vec<FontSource> {
+    split_top_level(value, ',')
+        .iter()
+        .filter_map(|source| {
+            let source = source.trim();
+            if let Some(name) = function_argument(source, "local") {
+                return Some(FontSource::Local(unquote(name).to_string()));
+            }
+            let url = function_argument(source, "url")?;
+            let rest = &source[source.find(')')? + 1..];
+            let format = function_argument(rest.trim(), "format")
+                .map(|format| unquote(format).to_ascii_lowercase());
+            Some(FontSource::Url {
+                url: unquote(url).to_string(),
+                format,
+            })
+        })
+        .collect()
+}
+
+fn function_argument<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    let head = value.get(..name.len() + 1)?;
+    if !head[..name.len()].eq_ignore_ascii_case(name) || !head.ends_with('(') {
+        return None;
+    }
+    let end = value.find(')')?;
+    Some(value[name.len() + 1..end].trim())
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+fn is_bold(value: &str) -> bool {
+    let value = value.to_ascii_lowercase();
+    match value.split_whitespace().next() {
+        Some("bold" | "bolder") => true,
+        Some(weight) => weight.parse::<u16>().is_ok_and(|weight| weight >= 600),
+        None => false,
+    }
+}
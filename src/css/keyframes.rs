@@ -0,0 +1,58 @@
+use std::string::String;
+use std::vec::Vec;
+
+use super::Declaration;
+use super::media::{MediaFeatures, MediaQueryList};
+use super::selector::split_top_level;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    pub offset: f32,
+    pub declarations: Vec<Declaration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes {
+    pub name: String,
+    pub frames: Vec<Keyframe>,
+    pub media: Vec<MediaQueryList>,
+}
+
+impl Keyframes {
+    pub fn applies(&self, features: &MediaFeatures) -> bool {
+        self.media.iter().all(|media| media.matches(features))
+    }
+}
+
+pub fn parse_name(prelude: &str) -> Option<String> {
+    let name = prelude.trim();
+    let name = name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+        .or_else(|| {
+            name.strip_prefix('\'')
+                .and_then(|name| name.strip_suffix('\''))
+        })
+        .unwrap_or(name);
+    let valid = !name.is_empty()
+        && !name.eq_ignore_ascii_case("none")
+        && !name.contains(char::is_whitespace);
+    valid.then(|| name.to_string())
+}
+
+pub fn parse_offsets(selector: &str) -> Option<Vec<f32>> {
+    split_top_level(selector, ',')
+        .iter()
+        .map(|offset| {
+            let offset = offset.trim().to_ascii_lowercase();
+            match offset.as_str() {
+                "from" => Some(0.0),
+                "to" => Some(1.0),
+                _ => {
+                    let percent: f32 = offset.strip_suffix('%')?.trim().parse().ok()?;
+                    (0.0..=100.0).contains(&percent).then_some(percent / 100.0)
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,302 @@
+use std::process::Command;
+use std::string::String;
+use std::sync::OnceLock;
+use std::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+    System,
+}
+
+impl ColorScheme {
+    pub fn parse(value: &str) -> Option<ColorScheme> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "light" => Some(ColorScheme::Light),
+            "dark" => Some(ColorScheme::Dark),
+            "system" => Some(ColorScheme::System),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(self) -> ColorScheme {
+        match self {
+            ColorScheme::System => system_color_scheme(),
+            scheme => scheme,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorScheme::Light => "light",
+            ColorScheme::Dark => "dark",
+            ColorScheme::System => "system",
+        }
+    }
+}
+
+fn system_color_scheme() -> ColorScheme {
+    static SYSTEM: OnceLock<ColorScheme> = OnceLock::new();
+    *SYSTEM.get_or_init(|| {
+        if detect_dark() {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        }
+    })
+}
+
+fn detect_dark() -> bool {
+    if let Some(theme) = std::env::var_os("GTK_THEME") {
+        return theme
+            .to_string_lossy()
+            .to_ascii_lowercase()
+            .ends_with(":dark");
+    }
+    let (program, args): (&str, &[&str]) = if cfg!(target_os = "macos") {
+        ("defaults", &["read", "-g", "AppleInterfaceStyle"])
+    } else if cfg!(target_os = "linux") {
+        (
+            "gsettings",
+            &["get", "org.gnome.desktop.interface", "color-scheme"],
+        )
+    } else {
+        return false;
+    };
+    read_system_setting(program, args).is_some_and(|value| value.contains("dark"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReducedMotion {
+    #[default]
+    NoPreference,
+    Reduce,
+    System,
+}
+
+impl ReducedMotion {
+    pub fn parse(value: &str) -> Option<ReducedMotion> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "no-preference" => Some(ReducedMotion::NoPreference),
+            "reduce" => Some(ReducedMotion::Reduce),
+            "system" => Some(ReducedMotion::System),
+            _ => None,
+        }
+    }
+
+    pub fn resolve(self) -> ReducedMotion {
+        match self {
+            ReducedMotion::System => system_reduced_motion(),
+            motion => motion,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReducedMotion::NoPreference => "no-preference",
+            ReducedMotion::Reduce => "reduce",
+            ReducedMotion::System => "system",
+        }
+    }
+}
+
+fn system_reduced_motion() -> ReducedMotion {
+    static SYSTEM: OnceLock<ReducedMotion> = OnceLock::new();
+    *SYSTEM.get_or_init(|| {
+        if detect_reduced_motion() {
+            ReducedMotion::Reduce
+        } else {
+            ReducedMotion::NoPreference
+        }
+    })
+}
+
+fn detect_reduced_motion() -> bool {
+    if cfg!(target_os = "macos") {
+        read_system_setting(
+            "defaults",
+            &["read", "com.apple.universalaccess", "reduceMotion"],
+        )
+        .is_some_and(|value| value.trim() == "1")
+    } else if cfg!(target_os = "linux") {
+        read_system_setting(
+            "gsettings",
+            &["get", "org.gnome.desktop.interface", "enable-animations"],
+        )
+        .is_some_and(|value| value.trim() == "false")
+    } else {
+        false
+    }
+}
+
+fn read_system_setting(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_ascii_lowercase())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MediaType {
+    #[default]
+    Screen,
+    Print,
+}
+
+impl MediaType {
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaType::Screen => "screen",
+            MediaType::Print => "print",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MediaFeatures {
+    pub media_type: MediaType,
+    pub color_scheme: ColorScheme,
+    pub reduced_motion: ReducedMotion,
+    pub scripting: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Type(String),
+    Feature(String, Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct MediaQuery {
+    negated: bool,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQueryList {
+    queries: Vec<MediaQuery>,
+}
+
+impl MediaQueryList {
+    pub fn parse(text: &str) -> MediaQueryList {
+        let queries = text
+            .split(',')
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+            .map(parse_query)
+            .collect();
+        MediaQueryList { queries }
+    }
+
+    pub fn matches(&self, features: &MediaFeatures) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|query| query.matches(features))
+    }
+}
+
+impl MediaQuery {
+    fn matches(&self, features: &MediaFeatures) -> bool {
+        let matched = self
+            .conditions
+            .iter()
+            .all(|condition| condition.matches(features));
+        matched != self.negated
+    }
+}
+
+impl Condition {
+    fn matches(&self, features: &MediaFeatures) -> bool {
+        match self {
+            Condition::Type(media_type) => {
+                media_type == "all" || media_type == features.media_type.name()
+            }
+            Condition::Feature(name, value) => match (name.as_str(), value.as_deref()) {
+                ("prefers-color-scheme", None) => true,
+                ("prefers-color-scheme", Some(value)) => {
+                    ColorScheme::parse(value).is_some_and(|scheme| {
+                        scheme != ColorScheme::System && scheme == features.color_scheme.resolve()
+                    })
+                }
+                ("prefers-reduced-motion", None) => {
+                    features.reduced_motion.resolve() == ReducedMotion::Reduce
+                }
+                ("prefers-reduced-motion", Some(value)) => {
+                    ReducedMotion::parse(value).is_some_and(|motion| {
+                        motion != ReducedMotion::System
+                            && motion == features.reduced_motion.resolve()
+                    })
+                }
+                ("scripting", None) => features.scripting,
+                ("scripting", Some("enabled")) => features.scripting,
+                ("scripting", Some("none")) => !features.scripting,
+                ("color", None) => true,
+                _ => false,
+            },
+        }
+    }
+}
+
+fn parse_query(text: &str) -> MediaQuery {
+    let lower = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase();
+    let mut rest = lower.as_str();
+    let mut negated = false;
+    if let Some(after) = rest.strip_prefix("not ") {
+        negated = true;
+        rest = after.trim_start();
+    } else if let Some(after) = rest.strip_prefix("only ") {
+        rest = after.trim_start();
+    }
+
+    let mut conditions = Vec::new();
+    for part in split_and(rest) {
+        match part
+            .strip_prefix('(')
+            .and_then(|part| part.strip_suffix(')'))
+        {
+            Some(feature) => {
+                let (name, value) = match feature.split_once(':') {
+                    Some((name, value)) => (name.trim(), Some(value.trim().to_string())),
+                    None => (feature.trim(), None),
+                };
+                conditions.push(Condition::Feature(name.to_string(), value));
+            }
+            None => conditions.push(Condition::Type(part.to_string())),
+        }
+    }
+    MediaQuery {
+        negated,
+        conditions,
+    }
+}
+
+fn split_and(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && bytes[i..].starts_with(b" and ") => {
+                parts.push(text[start..i].trim());
+                i += " and ".len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(text[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
@@ -0,0 +1,68 @@
+pub mod bloom;
+pub mod font_face;
+pub mod keyframes;
+pub mod media;
+pub mod parser;
+pub mod selector;
+pub mod values;
+
+use std::string::String;
+use std::vec::Vec;
+
+use font_face::FontFace;
+use keyframes::Keyframes;
+use media::{MediaFeatures, MediaQueryList};
+use selector::Selector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Origin {
+    UserAgent,
+    Author,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub name: String,
+    pub value: String,
+    pub important: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub selectors: Vec<Selector>,
+    pub declarations: Vec<Declaration>,
+    pub media: Vec<MediaQueryList>,
+}
+
+impl Rule {
+    pub fn applies(&self, features: &MediaFeatures) -> bool {
+        self.media.iter().all(|media| media.matches(features))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRule {
+    pub declarations: Vec<Declaration>,
+    pub media: Vec<MediaQueryList>,
+}
+
+impl PageRule {
+    pub fn applies(&self, features: &MediaFeatures) -> bool {
+        self.media.iter().all(|media| media.matches(features))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stylesheet {
+    pub origin: Origin,
+    pub rules: Vec<Rule>,
+    pub font_faces: Vec<FontFace>,
+    pub keyframes: Vec<Keyframes>,
+    pub pages: Vec<PageRule>,
+}
+
+impl Stylesheet {
+    pub fn parse(source: &str, origin: Origin) -> Self {
+        parser::parse_stylesheet(source, origin)
+    }
+}
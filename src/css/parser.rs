@@ -0,0 +1,412 @@
+use std::string::String;
+use std::vec::Vec;
+
+use super::font_face::FontFace;
+use super::keyframes::{self, Keyframe, Keyframes};
+use super::media::MediaQueryList;
+use super::selector::{parse_selector_list, split_top_level};
+use super::{Declaration, Origin, PageRule, Rule, Stylesheet};
+
+pub fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+pub fn parse_rules(source: &str) -> Vec<Rule> {
+    parse_stylesheet(source, Origin::Author).rules
+}
+
+pub fn parse_stylesheet(source: &str, origin: Origin) -> Stylesheet {
+    let source = strip_comments(source);
+    let mut sheet = Stylesheet {
+        origin,
+        rules: Vec::new(),
+        font_faces: Vec::new(),
+        keyframes: Vec::new(),
+        pages: Vec::new(),
+    };
+    parse_rules_into(&source, &[], &mut sheet);
+    sheet
+}
+
+fn parse_rules_into(source: &str, media: &[MediaQueryList], sheet: &mut Stylesheet) {
+    let mut rest = source;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(after) = strip_at_keyword(rest, "@media")
+            && let Some(open) = after.find('{')
+            && !after[..open].contains(';')
+        {
+            let mut nested = media.to_vec();
+            nested.push(MediaQueryList::parse(&after[..open]));
+            let (block, remaining) = take_block(&after[open..]);
+            parse_rules_into(block, &nested, sheet);
+            rest = remaining;
+            continue;
+        }
+        if let Some(after) = strip_at_keyword(rest, "@font-face")
+            && let Some(open) = after.find('{')
+            && after[..open].trim().is_empty()
+        {
+            let (block, remaining) = take_block(&after[open..]);
+            if let Some(face) = FontFace::from_declarations(&parse_declarations(block)) {
+                sheet.font_faces.push(face);
+            }
+            rest = remaining;
+            continue;
+        }
+        if let Some(after) = strip_at_keyword(rest, "@keyframes")
+            .or_else(|| strip_at_keyword(rest, "@-webkit-keyframes"))
+            && let Some(open) = after.find('{')
+            && let Some(name) = keyframes::parse_name(&after[..open])
+        {
+            let (block, remaining) = take_block(&after[open..]);
+            sheet.keyframes.push(Keyframes {
+                name,
+                frames: parse_keyframe_blocks(block),
+                media: media.to_vec(),
+            });
+            rest = remaining;
+            continue;
+        }
+        if let Some(after) = strip_at_keyword(rest, "@page")
+            && let Some(open) = after.find('{')
+            && after[..open].trim().is_empty()
+        {
+            let (block, remaining) = take_block(&after[open..]);
+            sheet.pages.push(PageRule {
+                declarations: parse_declarations(block),
+                media: media.to_vec(),
+            });
+            rest = remaining;
+            continue;
+        }
+        if rest.starts_with('@') {
+            rest = skip_at_rule(rest);
+            continue;
+        }
+
+        let Some(open) = rest.find('{') else {
+            break;
+        };
+        let prelude = &rest[..open];
+        let (block, remaining) = take_block(&rest[open..]);
+        rest = remaining;
+
+        if let Some(selectors) = parse_selector_list(prelude) {
+            sheet.rules.push(Rule {
+                selectors,
+                declarations: parse_declarations(block),
+                media: media.to_vec(),
+            });
+        }
+    }
+}
+
+fn parse_keyframe_blocks(source: &str) -> Vec<Keyframe> {
+    let mut frames = Vec::new();
+    let mut rest = source;
+    while let Some(open) = rest.find('{') {
+        let prelude = &rest[..open];
+        let (block, remaining) = take_block(&rest[open..]);
+        rest = remaining;
+        let Some(offsets) = keyframes::parse_offsets(prelude) else {
+            continue;
+        };
+        let declarations: Vec<Declaration> = parse_declarations(block)
+            .into_iter()
+            .filter(|declaration| !declaration.important)
+            .collect();
+        for offset in offsets {
+            frames.push(Keyframe {
+                offset,
+                declarations: declarations.clone(),
+            });
+        }
+    }
+    frames.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+    frames
+}
+
+fn strip_at_keyword<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = input.get(..keyword.len())?;
+    let after = &input[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword)
+        && after.starts_with(|c: char| c.is_whitespace() || c == '(' || c == '{'))
+    .then_some(after)
+}
+
+fn skip_at_rule(input: &str) -> &str {
+    let block = input.find('{');
+    let semicolon = input.find(';');
+    match (block, semicolon) {
+        (Some(open), Some(end)) if end < open => &input[end + 1..],
+        (Some(open), _) => take_block(&input[open..]).1,
+        (None, Some(end)) => &input[end + 1..],
+        (None, None) => "",
+    }
+}
+
+fn take_block(input: &str) -> (&str, &str) {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    for (i, c) in input.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return (&input[1..i], &input[i + 1..]);
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+    (input.get(1..).unwrap_or(""), "")
+}
+
+pub fn parse_declarations(block: &str) -> Vec<Declaration> {
+    let block = strip_comments(block);
+    let mut declarations = Vec::new();
+
+    for part in split_top_level(&block, ';') {
+        let Some((name, value)) = part.split_once(':') else {
+            continue;
+        };
+        let name = name.trim().to_ascii_lowercase();
+        let mut value = value.trim();
+        let mut important = false;
+        if let Some(index) = value.to_ascii_lowercase().rfind("!important") {
+            value = value[..index].trim_end();
+            important = true;
+        }
+        if name.is_empty() || value.is_empty() {
+            continue;
+        }
+
+        for (name, value) in expand_shorthand(&name, value) {
+            declarations.push(Declaration {
+                name,
+                value,
+                important,
+            });
+        }
+    }
+
+    declarations
+}
+
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+pub fn split_components(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    for (i, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    parts.push(&value[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(start) = start {
+        parts.push(&value[start..]);
+    }
+    parts
+}
+
+fn four_sides(value: &str) -> Option<[String; 4]> {
+    let parts = split_components(value);
+    let [top, right, bottom, left] = match parts.as_slice() {
+        [all] => [*all, *all, *all, *all],
+        [vertical, horizontal] => [*vertical, *horizontal, *vertical, *horizontal],
+        [top, horizontal, bottom] => [*top, *horizontal, *bottom, *horizontal],
+        [top, right, bottom, left] => [*top, *right, *bottom, *left],
+        _ => return None,
+    };
+    Some([
+        top.to_string(),
+        right.to_string(),
+        bottom.to_string(),
+        left.to_string(),
+    ])
+}
+
+fn expand_shorthand(name: &str, value: &str) -> Vec<(String, String)> {
+    let sides = |prefix: &str, suffix: &str| -> Vec<(String, String)> {
+        match four_sides(value) {
+            Some(values) => SIDES
+                .iter()
+                .zip(values)
+                .map(|(side, value)| (format!("{}-{}{}", prefix, side, suffix), value))
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    match name {
+        "margin" | "padding" => sides(name, ""),
+        "inset" => match four_sides(value) {
+            Some(values) => SIDES
+                .iter()
+                .zip(values)
+                .map(|(side, value)| (side.to_string(), value))
+                .collect(),
+            None => Vec::new(),
+        },
+        "border-width" => sides("border", "-width"),
+        "border-style" => sides("border", "-style"),
+        "border-color" => sides("border", "-color"),
+        "border" => SIDES
+            .iter()
+            .flat_map(|side| expand_border_side(&format!("border-{}", side), value))
+            .collect(),
+        "border-top" | "border-right" | "border-bottom" | "border-left" => {
+            expand_border_side(name, value)
+        }
+        "background" => {
+            let parts = split_components(value);
+            let color = parts
+                .iter()
+                .find(|part| super::values::Color::parse(part).is_some())
+                .unwrap_or(&"transparent");
+            let images: Vec<&str> = parts
+                .iter()
+                .copied()
+                .filter(|part| {
+                    let lower = part.to_ascii_lowercase();
+                    lower.starts_with("url(") || lower.contains("gradient(")
+                })
+                .collect();
+            let image = if images.is_empty() {
+                "none".to_string()
+            } else {
+                images.join(" ")
+            };
+            vec![
+                ("background-color".to_string(), color.to_string()),
+                ("background-image".to_string(), image),
+            ]
+        }
+        "animation" => expand_animation(value),
+        _ => vec![(name.to_string(), value.to_string())],
+    }
+}
+
+const ANIMATION_LONGHANDS: [(&str, &str); 8] = [
+    ("animation-name", "none"),
+    ("animation-duration", "0s"),
+    ("animation-timing-function", "ease"),
+    ("animation-delay", "0s"),
+    ("animation-iteration-count", "1"),
+    ("animation-direction", "normal"),
+    ("animation-fill-mode", "none"),
+    ("animation-play-state", "running"),
+];
+
+fn expand_animation(value: &str) -> Vec<(String, String)> {
+    let mut lists: [Vec<String>; 8] = Default::default();
+    for layer in split_top_level(value, ',') {
+        let mut layer_values: [Option<String>; 8] = Default::default();
+        for part in split_components(&layer) {
+            let lower = part.to_ascii_lowercase();
+            let index = match lower.as_str() {
+                _ if is_time(&lower) && layer_values[1].is_none() => 1,
+                _ if is_time(&lower) => 3,
+                "linear" | "ease" | "ease-in" | "ease-out" | "ease-in-out" | "step-start"
+                | "step-end" => 2,
+                _ if lower.starts_with("cubic-bezier(") || lower.starts_with("steps(") => 2,
+                "infinite" => 4,
+                _ if lower.parse::<f32>().is_ok() => 4,
+                "normal" | "reverse" | "alternate" | "alternate-reverse"
+                    if layer_values[5].is_none() =>
+                {
+                    5
+                }
+                "forwards" | "backwards" | "both" => 6,
+                "none" if layer_values[6].is_none() => 6,
+                "running" | "paused" => 7,
+                _ => {
+                    layer_values[0].get_or_insert_with(|| part.to_string());
+                    continue;
+                }
+            };
+            layer_values[index].get_or_insert(lower);
+        }
+        for ((list, value), (_, initial)) in
+            lists.iter_mut().zip(layer_values).zip(ANIMATION_LONGHANDS)
+        {
+            list.push(value.unwrap_or_else(|| initial.to_string()));
+        }
+    }
+    ANIMATION_LONGHANDS
+        .iter()
+        .zip(lists)
+        .map(|((name, _), list)| (name.to_string(), list.join(", ")))
+        .collect()
+}
+
+fn is_time(value: &str) -> bool {
+    value
+        .strip_suffix("ms")
+        .or_else(|| value.strip_suffix('s'))
+        .is_some_and(|number| number.parse::<f32>().is_ok())
+}
+
+fn expand_border_side(prefix: &str, value: &str) -> Vec<(String, String)> {
+    let mut width = "medium".to_string();
+    let mut style = "none".to_string();
+    let mut color = "currentcolor".to_string();
+
+    for part in split_components(value) {
+        let lower = part.to_ascii_lowercase();
+        if BORDER_STYLES.contains(&lower.as_str()) {
+            style = lower;
+        } else if matches!(lower.as_str(), "thin" | "medium" | "thick")
+            || super::values::Length::parse(&lower).is_some()
+        {
+            width = lower;
+        } else {
+            color = part.to_string();
+        }
+    }
+
+    vec![
+        (format!("{}-width", prefix), width),
+        (format!("{}-style", prefix), style),
+        (format!("{}-color", prefix), color),
+    ]
+}
+
+pub const BORDER_STYLES: &[&str] = &[
+    "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
@@ -0,0 +1,823 @@
+use html5ever::{LocalName, Namespace, local_name, ns};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+use string_cache::DefaultAtom as Atom;
+
+use super::bloom::{AncestorFilter, hash_name};
+use crate::dom::{Node, NodeData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nth {
+    pub a: i32,
+    pub b: i32,
+}
+
+impl Nth {
+    pub const FIRST: Nth = Nth { a: 0, b: 1 };
+
+    // Whitespace is only allowed around the whole argument and on either side
+    // of the sign that joins An to B, so "2n + 1" parses but "2 n" doesn't.
+    pub fn parse(input: &str) -> Option<Nth> {
+        let text = input.trim().to_ascii_lowercase();
+        match text.as_str() {
+            "odd" => return Some(Nth { a: 2, b: 1 }),
+            "even" => return Some(Nth { a: 2, b: 0 }),
+            _ => {}
+        }
+        let Some((a, b)) = text.split_once('n') else {
+            return Some(Nth {
+                a: 0,
+                b: parse_integer(&text)?,
+            });
+        };
+        let a = match a {
+            "" | "+" => 1,
+            "-" => -1,
+            a => parse_integer(a)?,
+        };
+        let b = match b.trim_start() {
+            "" => 0,
+            b => {
+                let negative = match b.chars().next() {
+                    Some('+') => false,
+                    Some('-') => true,
+                    _ => return None,
+                };
+                let digits = b[1..].trim_start();
+                if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    return None;
+                }
+                let magnitude = parse_integer(digits)?;
+                if negative { -magnitude } else { magnitude }
+            }
+        };
+        Some(Nth { a, b })
+    }
+
+    pub fn matches(&self, index: i32) -> bool {
+        // Extreme A and B values overflow i32 arithmetic, so work in i64.
+        let (a, offset) = (i64::from(self.a), i64::from(index) - i64::from(self.b));
+        if a == 0 {
+            offset == 0
+        } else {
+            offset % a == 0 && offset / a >= 0
+        }
+    }
+}
+
+fn parse_integer(text: &str) -> Option<i32> {
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PseudoClass {
+    Link,
+    Never,
+    Empty,
+    Nth {
+        nth: Nth,
+        of_type: bool,
+        from_end: bool,
+    },
+    Only {
+        of_type: bool,
+    },
+    Not(Vec<Selector>),
+    Is(Vec<Selector>),
+    Where(Vec<Selector>),
+}
+
+impl PseudoClass {
+    pub fn is_structural(&self) -> bool {
+        matches!(
+            self,
+            PseudoClass::Empty | PseudoClass::Nth { .. } | PseudoClass::Only { .. }
+        )
+    }
+
+    pub fn arguments(&self) -> &[Selector] {
+        match self {
+            PseudoClass::Not(selectors)
+            | PseudoClass::Is(selectors)
+            | PseudoClass::Where(selectors) => selectors,
+            _ => &[],
+        }
+    }
+
+    fn specificity(&self) -> Specificity {
+        match self {
+            PseudoClass::Where(_) => Specificity::default(),
+            PseudoClass::Not(selectors) | PseudoClass::Is(selectors) => selectors
+                .iter()
+                .map(Selector::specificity)
+                .max()
+                .unwrap_or_default(),
+            _ => Specificity(0, 1, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeOperator {
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeSelector {
+    pub name: LocalName,
+    pub value: Option<(AttributeOperator, String)>,
+    pub case_insensitive: bool,
+}
+
+impl AttributeSelector {
+    fn matches(&self, element: &Rc<Node>) -> bool {
+        let NodeData::Element { name, attrs } = &element.data else {
+            return false;
+        };
+        let attrs = attrs.borrow();
+        let Some(attr) = attrs.iter().find(|attr| match name.ns {
+            ns!(html) => attr.name.local.eq_ignore_ascii_case(&self.name),
+            _ => attr.name.local == self.name,
+        }) else {
+            return false;
+        };
+        let Some((operator, expected)) = &self.value else {
+            return true;
+        };
+        let (actual, expected) = if self.case_insensitive {
+            (attr.value.to_lowercase(), expected.to_lowercase())
+        } else {
+            (attr.value.clone(), expected.clone())
+        };
+        match operator {
+            AttributeOperator::Equals => actual == expected,
+            AttributeOperator::Includes => {
+                !expected.is_empty()
+                    && !expected.contains(char::is_whitespace)
+                    && actual.split_ascii_whitespace().any(|word| word == expected)
+            }
+            AttributeOperator::DashMatch => {
+                actual == expected
+                    || actual
+                        .strip_prefix(expected.as_str())
+                        .is_some_and(|rest| rest.starts_with('-'))
+            }
+            AttributeOperator::Prefix => !expected.is_empty() && actual.starts_with(&expected),
+            AttributeOperator::Suffix => !expected.is_empty() && actual.ends_with(&expected),
+            AttributeOperator::Substring => !expected.is_empty() && actual.contains(&expected),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PseudoElement {
+    Before,
+    After,
+}
+
+impl PseudoElement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PseudoElement::Before => "::before",
+            PseudoElement::After => "::after",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Compound {
+    pub namespace: Option<Namespace>,
+    pub tag: Option<LocalName>,
+    pub id: Option<Atom>,
+    pub classes: Vec<Atom>,
+    pub attributes: Vec<AttributeSelector>,
+    pub pseudo_classes: Vec<PseudoClass>,
+    pub pseudo_element: Option<PseudoElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    pub subject: Compound,
+    pub ancestors: Vec<(Combinator, Compound)>,
+    pub ancestor_hashes: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+
+impl Specificity {
+    fn add(self, other: Specificity) -> Specificity {
+        Specificity(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+}
+
+impl Compound {
+    fn specificity(&self) -> Specificity {
+        self.pseudo_classes
+            .iter()
+            .map(PseudoClass::specificity)
+            .fold(
+                Specificity(
+                    self.id.is_some() as u32,
+                    (self.classes.len() + self.attributes.len()) as u32,
+                    self.tag.is_some() as u32 + self.pseudo_element.is_some() as u32,
+                ),
+                Specificity::add,
+            )
+    }
+
+    fn hashes(&self) -> Vec<u32> {
+        let mut hashes = Vec::new();
+        if let Some(tag) = &self.tag {
+            hashes.push(hash_name(tag, true));
+        }
+        if let Some(id) = &self.id {
+            hashes.push(hash_name(id, false));
+        }
+        hashes.extend(self.classes.iter().map(|class| hash_name(class, false)));
+        hashes
+    }
+
+    pub fn matches(&self, element: &Rc<Node>) -> bool {
+        let NodeData::Element { name, .. } = &element.data else {
+            return false;
+        };
+        if let Some(namespace) = &self.namespace
+            && name.ns != *namespace
+        {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            let matched = match name.ns {
+                ns!(html) => name.local.eq_ignore_ascii_case(tag),
+                _ => name.local == *tag,
+            };
+            if !matched {
+                return false;
+            }
+        }
+        if let Some(id) = &self.id
+            && !element.with_attribute(&local_name!("id"), |value| value == Some(&**id))
+        {
+            return false;
+        }
+        if !self.classes.is_empty()
+            && !element.with_attribute(&local_name!("class"), |value| {
+                let value = value.unwrap_or_default();
+                self.classes
+                    .iter()
+                    .all(|class| value.split_ascii_whitespace().any(|c| c == &**class))
+            })
+        {
+            return false;
+        }
+        self.attributes
+            .iter()
+            .all(|attribute| attribute.matches(element))
+            && self
+                .pseudo_classes
+                .iter()
+                .all(|pseudo| matches_pseudo_class(pseudo, element))
+    }
+}
+
+impl Selector {
+    pub fn specificity(&self) -> Specificity {
+        self.ancestors
+            .iter()
+            .map(|(_, compound)| compound.specificity())
+            .fold(self.subject.specificity(), Specificity::add)
+    }
+
+    pub fn pseudo_element(&self) -> Option<PseudoElement> {
+        self.subject.pseudo_element
+    }
+
+    pub fn matches(&self, element: &Rc<Node>) -> bool {
+        self.matches_pseudo(element, None)
+    }
+
+    pub fn matches_pseudo(&self, element: &Rc<Node>, pseudo: Option<PseudoElement>) -> bool {
+        self.subject.pseudo_element == pseudo
+            && self.subject.matches(element)
+            && matches_ancestors(&self.ancestors, element)
+    }
+
+    pub fn rejected_by(&self, filter: &AncestorFilter) -> bool {
+        self.ancestor_hashes
+            .iter()
+            .any(|&hash| !filter.might_contain(hash))
+    }
+}
+
+fn matches_ancestors(ancestors: &[(Combinator, Compound)], element: &Rc<Node>) -> bool {
+    let Some(((combinator, compound), rest)) = ancestors.split_first() else {
+        return true;
+    };
+
+    let matches =
+        |candidate: &Rc<Node>| compound.matches(candidate) && matches_ancestors(rest, candidate);
+    match combinator {
+        Combinator::Descendant | Combinator::Child => {
+            let mut current = parent_element(element);
+            while let Some(candidate) = current {
+                if matches(&candidate) {
+                    return true;
+                }
+                if *combinator == Combinator::Child {
+                    return false;
+                }
+                current = parent_element(&candidate);
+            }
+            false
+        }
+        Combinator::NextSibling => {
+            previous_element_siblings(element, |sibling| Some(matches(sibling))).unwrap_or(false)
+        }
+        Combinator::SubsequentSibling => {
+            previous_element_siblings(element, |sibling| matches(sibling).then_some(true))
+                .unwrap_or(false)
+        }
+    }
+}
+
+fn previous_element_siblings<R>(
+    element: &Rc<Node>,
+    visit: impl FnMut(&Rc<Node>) -> Option<R>,
+) -> Option<R> {
+    let parent = element.parent.borrow().upgrade()?;
+    let children = parent.children.borrow();
+    let position = children
+        .iter()
+        .position(|sibling| Rc::ptr_eq(sibling, element))?;
+    children[..position]
+        .iter()
+        .rev()
+        .filter(|sibling| matches!(sibling.data, NodeData::Element { .. }))
+        .find_map(visit)
+}
+
+pub fn parent_element(node: &Rc<Node>) -> Option<Rc<Node>> {
+    let parent = node.parent.borrow().upgrade()?;
+    match parent.data {
+        NodeData::Element { .. } => Some(parent),
+        _ => None,
+    }
+}
+
+fn matches_pseudo_class(pseudo: &PseudoClass, element: &Rc<Node>) -> bool {
+    match pseudo {
+        PseudoClass::Link => {
+            matches!(element.element_name(), Some("a" | "area")) && element.has_attribute("href")
+        }
+        PseudoClass::Never => false,
+        PseudoClass::Empty => element
+            .children
+            .borrow()
+            .iter()
+            .all(|child| match &child.data {
+                NodeData::Text { contents } => contents.borrow().is_empty(),
+                NodeData::Comment { .. } => true,
+                _ => false,
+            }),
+        PseudoClass::Nth {
+            nth,
+            of_type,
+            from_end,
+        } => sibling_index(element, *of_type, *from_end).is_some_and(|index| nth.matches(index)),
+        PseudoClass::Not(selectors) => !selectors.iter().any(|selector| selector.matches(element)),
+        PseudoClass::Is(selectors) | PseudoClass::Where(selectors) => {
+            selectors.iter().any(|selector| selector.matches(element))
+        }
+        PseudoClass::Only { of_type } => {
+            sibling_index(element, *of_type, false) == Some(1)
+                && sibling_index(element, *of_type, true) == Some(1)
+        }
+    }
+}
+
+fn sibling_index(element: &Rc<Node>, of_type: bool, from_end: bool) -> Option<i32> {
+    let NodeData::Element { name, .. } = &element.data else {
+        return None;
+    };
+    let parent = element.parent.borrow().upgrade()?;
+    let children = parent.children.borrow();
+    let mut siblings = children.iter().filter(|sibling| match &sibling.data {
+        NodeData::Element {
+            name: sibling_name, ..
+        } => !of_type || sibling_name == name,
+        _ => false,
+    });
+    let position = if from_end {
+        siblings
+            .rev()
+            .position(|sibling| Rc::ptr_eq(sibling, element))
+    } else {
+        siblings.position(|sibling| Rc::ptr_eq(sibling, element))
+    };
+    position.map(|position| position as i32 + 1)
+}
+
+pub fn parse_selector_list(input: &str) -> Option<Vec<Selector>> {
+    split_top_level(input, ',')
+        .iter()
+        .map(|part| parse_selector(part))
+        .collect()
+}
+
+pub fn parse_selector(input: &str) -> Option<Selector> {
+    let mut compounds: Vec<Compound> = Vec::new();
+    let mut combinators: Vec<Combinator> = Vec::new();
+    let mut chars = input.trim().chars().peekable();
+    let mut pending: Option<Combinator> = None;
+
+    while chars.peek().is_some() {
+        let c = *chars.peek()?;
+        if c.is_whitespace() {
+            chars.next();
+            if !compounds.is_empty() && pending.is_none() {
+                pending = Some(Combinator::Descendant);
+            }
+            continue;
+        }
+        let combinator = match c {
+            '>' => Some(Combinator::Child),
+            '+' => Some(Combinator::NextSibling),
+            '~' => Some(Combinator::SubsequentSibling),
+            _ => None,
+        };
+        if let Some(combinator) = combinator {
+            chars.next();
+            if compounds.is_empty()
+                || pending.is_some_and(|pending| pending != Combinator::Descendant)
+            {
+                return None;
+            }
+            pending = Some(combinator);
+            continue;
+        }
+
+        let compound = parse_compound(&mut chars)?;
+        if let Some(combinator) = pending.take() {
+            combinators.push(combinator);
+        } else if !compounds.is_empty() {
+            return None;
+        }
+        compounds.push(compound);
+    }
+
+    if pending.is_some_and(|pending| pending != Combinator::Descendant) {
+        return None;
+    }
+    let subject = compounds.pop()?;
+    if compounds
+        .iter()
+        .any(|compound| compound.pseudo_element.is_some())
+    {
+        return None;
+    }
+    let ancestors: Vec<(Combinator, Compound)> = combinators
+        .into_iter()
+        .rev()
+        .zip(compounds.into_iter().rev())
+        .collect();
+    let ancestor_hashes = ancestors
+        .iter()
+        .filter(|(combinator, _)| matches!(combinator, Combinator::Descendant | Combinator::Child))
+        .flat_map(|(_, compound)| compound.hashes())
+        .collect();
+    Some(Selector {
+        subject,
+        ancestors,
+        ancestor_hashes,
+    })
+}
+
+fn parse_compound(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Compound> {
+    let mut compound = Compound::default();
+
+    let prefix = match chars.peek() {
+        Some('*') => {
+            chars.next();
+            Some(String::from("*"))
+        }
+        Some(c) if is_ident_char(*c) => Some(read_ident(chars)),
+        Some('|') => Some(String::new()),
+        _ => None,
+    };
+    if chars.peek() == Some(&'|') {
+        chars.next();
+        compound.namespace = match prefix?.as_str() {
+            "*" => None,
+            prefix => Some(namespace_for_prefix(prefix)?),
+        };
+        match chars.peek() {
+            Some('*') => {
+                chars.next();
+            }
+            Some(c) if is_ident_char(*c) => compound.tag = Some(LocalName::from(read_ident(chars))),
+            _ => return None,
+        }
+    } else if let Some(prefix) = prefix.filter(|prefix| prefix != "*") {
+        compound.tag = Some(LocalName::from(prefix));
+    }
+
+    loop {
+        if compound.pseudo_element.is_some()
+            && chars
+                .peek()
+                .is_some_and(|c| !c.is_whitespace() && !matches!(c, '>' | '+' | '~'))
+        {
+            return None;
+        }
+        match chars.peek() {
+            Some('#') => {
+                chars.next();
+                let id = read_ident(chars);
+                if id.is_empty() {
+                    return None;
+                }
+                compound.id = Some(Atom::from(id));
+            }
+            Some('.') => {
+                chars.next();
+                let class = read_ident(chars);
+                if class.is_empty() {
+                    return None;
+                }
+                compound.classes.push(Atom::from(class));
+            }
+            Some('[') => {
+                chars.next();
+                compound.attributes.push(parse_attribute(chars)?);
+            }
+            Some(':') => {
+                chars.next();
+                let element = chars.next_if_eq(&':').is_some();
+                let name = read_ident(chars).to_ascii_lowercase();
+                if let Some(pseudo) = parse_pseudo_element(&name) {
+                    compound.pseudo_element = Some(pseudo);
+                    continue;
+                }
+                if element {
+                    return None;
+                }
+                if chars.next_if_eq(&'(').is_some() {
+                    let argument = read_argument(chars)?;
+                    let logical = match name.as_str() {
+                        "not" => Some(PseudoClass::Not(parse_argument_list(&argument)?)),
+                        "is" | "matches" | "-webkit-any" => {
+                            Some(PseudoClass::Is(parse_forgiving_list(&argument)))
+                        }
+                        "where" => Some(PseudoClass::Where(parse_forgiving_list(&argument))),
+                        _ => None,
+                    };
+                    if let Some(logical) = logical {
+                        compound.pseudo_classes.push(logical);
+                        continue;
+                    }
+                    let (of_type, from_end) = match name.as_str() {
+                        "nth-child" => (false, false),
+                        "nth-last-child" => (false, true),
+                        "nth-of-type" => (true, false),
+                        "nth-last-of-type" => (true, true),
+                        _ => return None,
+                    };
+                    compound.pseudo_classes.push(PseudoClass::Nth {
+                        nth: Nth::parse(&argument)?,
+                        of_type,
+                        from_end,
+                    });
+                    continue;
+                }
+                let first = |of_type, from_end| PseudoClass::Nth {
+                    nth: Nth::FIRST,
+                    of_type,
+                    from_end,
+                };
+                compound.pseudo_classes.push(match name.as_str() {
+                    "link" | "any-link" => PseudoClass::Link,
+                    "first-child" => first(false, false),
+                    "last-child" => first(false, true),
+                    "first-of-type" => first(true, false),
+                    "last-of-type" => first(true, true),
+                    "only-child" => PseudoClass::Only { of_type: false },
+                    "only-of-type" => PseudoClass::Only { of_type: true },
+                    "empty" => PseudoClass::Empty,
+                    "visited" | "hover" | "active" | "focus" | "focus-visible" | "focus-within" => {
+                        PseudoClass::Never
+                    }
+                    _ => return None,
+                });
+            }
+            Some(c) if c.is_whitespace() || matches!(c, '>' | '+' | '~') => break,
+            None => break,
+            Some(_) => return None,
+        }
+    }
+
+    Some(compound)
+}
+
+fn parse_attribute(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<AttributeSelector> {
+    skip_whitespace(chars);
+    let name = read_ident(chars);
+    if name.is_empty() {
+        return None;
+    }
+    skip_whitespace(chars);
+    let operator = match chars.next()? {
+        ']' => {
+            return Some(AttributeSelector {
+                name: LocalName::from(name),
+                value: None,
+                case_insensitive: false,
+            });
+        }
+        '=' => AttributeOperator::Equals,
+        prefix => {
+            let operator = match prefix {
+                '~' => AttributeOperator::Includes,
+                '|' => AttributeOperator::DashMatch,
+                '^' => AttributeOperator::Prefix,
+                '$' => AttributeOperator::Suffix,
+                '*' => AttributeOperator::Substring,
+                _ => return None,
+            };
+            chars.next_if_eq(&'=')?;
+            operator
+        }
+    };
+    skip_whitespace(chars);
+    let value = match chars.peek() {
+        Some(&quote) if quote == '"' || quote == '\'' => {
+            chars.next();
+            read_string(chars, quote)?
+        }
+        _ => {
+            let value = read_ident(chars);
+            if value.is_empty() {
+                return None;
+            }
+            value
+        }
+    };
+    skip_whitespace(chars);
+    let case_insensitive = match chars.next()? {
+        ']' => return Some(attribute(name, operator, value, false)),
+        'i' | 'I' => true,
+        's' | 'S' => false,
+        _ => return None,
+    };
+    skip_whitespace(chars);
+    chars.next_if_eq(&']')?;
+    Some(attribute(name, operator, value, case_insensitive))
+}
+
+fn attribute(
+    name: String,
+    operator: AttributeOperator,
+    value: String,
+    case_insensitive: bool,
+) -> AttributeSelector {
+    AttributeSelector {
+        name: LocalName::from(name),
+        value: Some((operator, value)),
+        case_insensitive,
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn read_string(chars: &mut std::iter::Peekable<std::str::Chars>, quote: char) -> Option<String> {
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            c if c == quote => return Some(value),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+fn read_argument(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<String> {
+    let mut argument = String::new();
+    let mut depth = 0usize;
+    for c in chars.by_ref() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(argument),
+            ')' => depth -= 1,
+            _ => {}
+        }
+        argument.push(c);
+    }
+    None
+}
+
+fn parse_argument_list(input: &str) -> Option<Vec<Selector>> {
+    let selectors = parse_selector_list(input)?;
+    selectors
+        .iter()
+        .all(|selector| selector.pseudo_element().is_none())
+        .then_some(selectors)
+}
+
+fn parse_forgiving_list(input: &str) -> Vec<Selector> {
+    split_top_level(input, ',')
+        .iter()
+        .filter_map(|part| parse_selector(part))
+        .filter(|selector| selector.pseudo_element().is_none())
+        .collect()
+}
+
+fn parse_pseudo_element(name: &str) -> Option<PseudoElement> {
+    match name {
+        "before" => Some(PseudoElement::Before),
+        "after" => Some(PseudoElement::After),
+        _ => None,
+    }
+}
+
+fn namespace_for_prefix(prefix: &str) -> Option<Namespace> {
+    match prefix.to_ascii_lowercase().as_str() {
+        "" => Some(ns!()),
+        "html" | "xhtml" => Some(ns!(html)),
+        "svg" => Some(ns!(svg)),
+        "math" | "mathml" => Some(ns!(mathml)),
+        _ => None,
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn read_ident(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut ident = String::new();
+    while let Some(&c) = chars.peek() {
+        if c == '\\' {
+            chars.next();
+            if let Some(escaped) = chars.next() {
+                ident.push(escaped);
+            }
+        } else if is_ident_char(c) {
+            ident.push(c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+    ident
+}
+
+pub fn split_top_level(input: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) => {
+                if c == q {
+                    quote = None;
+                }
+                current.push(c);
+                continue;
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '(' || c == '[' => depth += 1,
+            None if c == ')' || c == ']' => depth = depth.saturating_sub(1),
+            None if c == separator && depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            None => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
@@ -0,0 +1,201 @@
+use std::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("aqua", (0, 255, 255)),
+    ("black", (0, 0, 0)),
+    ("blue", (0, 0, 255)),
+    ("brown", (165, 42, 42)),
+    ("cyan", (0, 255, 255)),
+    ("darkblue", (0, 0, 139)),
+    ("darkgray", (169, 169, 169)),
+    ("darkgreen", (0, 100, 0)),
+    ("darkgrey", (169, 169, 169)),
+    ("darkred", (139, 0, 0)),
+    ("fuchsia", (255, 0, 255)),
+    ("gold", (255, 215, 0)),
+    ("gray", (128, 128, 128)),
+    ("green", (0, 128, 0)),
+    ("grey", (128, 128, 128)),
+    ("indigo", (75, 0, 130)),
+    ("lightblue", (173, 216, 230)),
+    ("lightgray", (211, 211, 211)),
+    ("lightgreen", (144, 238, 144)),
+    ("lightgrey", (211, 211, 211)),
+    ("lightyellow", (255, 255, 224)),
+    ("lime", (0, 255, 0)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("navy", (0, 0, 128)),
+    ("olive", (128, 128, 0)),
+    ("orange", (255, 165, 0)),
+    ("pink", (255, 192, 203)),
+    ("purple", (128, 0, 128)),
+    ("red", (255, 0, 0)),
+    ("silver", (192, 192, 192)),
+    ("teal", (0, 128, 128)),
+    ("white", (255, 255, 255)),
+    ("whitesmoke", (245, 245, 245)),
+    ("yellow", (255, 255, 0)),
+];
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const TRANSPARENT: Color = Color {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.a == 0
+    }
+
+    pub fn to_u32(&self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | self.b as u32
+    }
+
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Color> {
+        let value = value.trim().to_ascii_lowercase();
+        if value == "transparent" {
+            return Some(Color::TRANSPARENT);
+        }
+        if let Some(hex) = value.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+        if let Some(args) = value
+            .strip_prefix("rgba(")
+            .or_else(|| value.strip_prefix("rgb("))
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return parse_rgb_function(args);
+        }
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, (r, g, b))| Color::rgb(*r, *g, *b))
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Color::rgb(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+        4 => Some(Color::rgba(
+            digit(0)? * 17,
+            digit(1)? * 17,
+            digit(2)? * 17,
+            digit(3)? * 17,
+        )),
+        6 => Some(Color::rgb(pair(0)?, pair(2)?, pair(4)?)),
+        8 => Some(Color::rgba(pair(0)?, pair(2)?, pair(4)?, pair(6)?)),
+        _ => None,
+    }
+}
+
+fn parse_rgb_function(args: &str) -> Option<Color> {
+    let parts: Vec<&str> = args
+        .split([',', ' ', '/'])
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect();
+    if parts.len() < 3 || parts.len() > 4 {
+        return None;
+    }
+
+    let channel = |part: &str| -> Option<u8> {
+        let value = match part.strip_suffix('%') {
+            Some(percent) => percent.parse::<f32>().ok()? * 2.55,
+            None => part.parse::<f32>().ok()?,
+        };
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+    let alpha = match parts.get(3) {
+        Some(part) => {
+            let value = match part.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                None => part.parse::<f32>().ok()?,
+            };
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        }
+        None => 255,
+    };
+
+    Some(Color::rgba(
+        channel(parts[0])?,
+        channel(parts[1])?,
+        channel(parts[2])?,
+        alpha,
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Px(f32),
+    Em(f32),
+    Rem(f32),
+    Percent(f32),
+    Auto,
+}
+
+impl Length {
+    pub fn parse(value: &str) -> Option<Length> {
+        let value = value.trim().to_ascii_lowercase();
+        if value == "auto" {
+            return Some(Length::Auto);
+        }
+        if value == "0" {
+            return Some(Length::Px(0.0));
+        }
+
+        let number_end = value
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(number_end);
+        let number: f32 = number.parse().ok()?;
+
+        match unit {
+            "px" => Some(Length::Px(number)),
+            "em" => Some(Length::Em(number)),
+            "rem" => Some(Length::Rem(number)),
+            "%" => Some(Length::Percent(number)),
+            "pt" => Some(Length::Px(number * 4.0 / 3.0)),
+            "pc" => Some(Length::Px(number * 16.0)),
+            "in" => Some(Length::Px(number * 96.0)),
+            "cm" => Some(Length::Px(number * 96.0 / 2.54)),
+            "mm" => Some(Length::Px(number * 96.0 / 25.4)),
+            "ch" => Some(Length::Em(number * 0.6)),
+            "ex" => Some(Length::Em(number * 0.5)),
+            _ => None,
+        }
+    }
+}
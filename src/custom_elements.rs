@@ -0,0 +1,183 @@
+use anyhow::{Result, bail};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::Node;
+
+type ElementCallback = Box<dyn Fn(&Rc<Node>)>;
+type AttributeCallback = Box<dyn Fn(&Rc<Node>, &str, Option<&str>, Option<&str>)>;
+
+const RESERVED_NAMES: &[&str] = &[
+    "annotation-xml",
+    "color-profile",
+    "font-face",
+    "font-face-src",
+    "font-face-uri",
+    "font-face-format",
+    "font-face-name",
+    "missing-glyph",
+];
+
+pub struct CustomElementDefinition {
+    pub name: String,
+    pub observed_attributes: Vec<String>,
+    pub connected_callback: Option<ElementCallback>,
+    pub disconnected_callback: Option<ElementCallback>,
+    pub attribute_changed_callback: Option<AttributeCallback>,
+}
+
+impl CustomElementDefinition {
+    pub fn new(name: &str) -> Self {
+        CustomElementDefinition {
+            name: name.to_string(),
+            observed_attributes: Vec::new(),
+            connected_callback: None,
+            disconnected_callback: None,
+            attribute_changed_callback: None,
+        }
+    }
+
+    pub fn observes(&self, attribute: &str) -> bool {
+        self.observed_attributes
+            .iter()
+            .any(|observed| observed == attribute)
+    }
+}
+
+impl std::fmt::Debug for CustomElementDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomElementDefinition")
+            .field("name", &self.name)
+            .field("observed_attributes", &self.observed_attributes)
+            .finish()
+    }
+}
+
+pub fn is_valid_custom_element_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_lowercase = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+
+    starts_lowercase
+        && name.contains('-')
+        && !name.chars().any(|c| c.is_ascii_uppercase())
+        && name.chars().all(|c| {
+            c.is_ascii_lowercase()
+                || c.is_ascii_digit()
+                || matches!(c, '-' | '.' | '_')
+                || !c.is_ascii()
+        })
+        && !RESERVED_NAMES.contains(&name)
+}
+
+#[derive(Default)]
+pub struct CustomElementRegistry {
+    definitions: RefCell<Vec<Rc<CustomElementDefinition>>>,
+}
+
+impl CustomElementRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Rc<CustomElementDefinition>> {
+        self.definitions
+            .borrow()
+            .iter()
+            .find(|definition| definition.name == name)
+            .cloned()
+    }
+
+    pub fn define(&self, root: &Rc<Node>, definition: CustomElementDefinition) -> Result<()> {
+        if !is_valid_custom_element_name(&definition.name) {
+            bail!("{:?} is not a valid custom element name", definition.name);
+        }
+        if self.get(&definition.name).is_some() {
+            bail!("custom element {:?} is already defined", definition.name);
+        }
+
+        let definition = Rc::new(definition);
+        self.definitions.borrow_mut().push(Rc::clone(&definition));
+        self.upgrade(root);
+        Ok(())
+    }
+
+    pub fn upgrade(&self, root: &Rc<Node>) {
+        let mut candidates = Vec::new();
+        collect_shadow_including(root, &mut candidates);
+
+        for element in candidates {
+            if element.custom_definition.borrow().is_some() {
+                continue;
+            }
+            let Some(definition) = element.element_name().and_then(|name| self.get(name)) else {
+                continue;
+            };
+            upgrade_element(&element, definition);
+        }
+    }
+}
+
+fn upgrade_element(element: &Rc<Node>, definition: Rc<CustomElementDefinition>) {
+    *element.custom_definition.borrow_mut() = Some(Rc::clone(&definition));
+
+    if let Some(callback) = &definition.attribute_changed_callback {
+        for attr in element.attributes() {
+            if definition.observes(&attr.name.local) {
+                callback(element, &attr.name.local, None, Some(&attr.value));
+            }
+        }
+    }
+
+    if Node::is_connected(element)
+        && let Some(callback) = &definition.connected_callback
+    {
+        callback(element);
+    }
+}
+
+fn collect_shadow_including(node: &Rc<Node>, out: &mut Vec<Rc<Node>>) {
+    out.push(Rc::clone(node));
+    if let Some(shadow_root) = node.shadow_root.borrow().as_ref() {
+        collect_shadow_including(shadow_root, out);
+    }
+    for child in node.children.borrow().iter() {
+        collect_shadow_including(child, out);
+    }
+}
+
+pub(crate) fn connection_changed(node: &Rc<Node>, connected: bool) {
+    let mut nodes = Vec::new();
+    collect_shadow_including(node, &mut nodes);
+
+    for element in nodes {
+        let definition = element.custom_definition.borrow().clone();
+        let Some(definition) = definition else {
+            continue;
+        };
+        let callback = if connected {
+            &definition.connected_callback
+        } else {
+            &definition.disconnected_callback
+        };
+        if let Some(callback) = callback {
+            callback(&element);
+        }
+    }
+}
+
+pub(crate) fn attribute_changed(
+    element: &Rc<Node>,
+    name: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) {
+    let definition = element.custom_definition.borrow().clone();
+    if let Some(definition) = definition
+        && definition.observes(name)
+        && let Some(callback) = &definition.attribute_changed_callback
+    {
+        callback(element, name, old_value, new_value);
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::rc::Rc;
+use std::string::String;
+
+use crate::dom::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub node: Option<Rc<Node>>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message,
+            node: None,
+        }
+    }
+
+    pub fn at(mut self, node: &Rc<Node>) -> Self {
+        self.node = Some(Rc::clone(node));
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+        if let Some(node) = &self.node
+            && let Some(name) = node.element_name()
+        {
+            write!(f, " (<{}", name)?;
+            if let Some(id) = node.get_attribute("id") {
+                write!(f, " id={:?}", id)?;
+            }
+            write!(f, ">)")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData, NodeId};
+use crate::html::serialize::outer_html;
+
+const MAX_ALIGNMENT_CELLS: usize = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomPatch {
+    Added {
+        path: Vec<usize>,
+        html: String,
+    },
+    Removed {
+        path: Vec<usize>,
+        html: String,
+    },
+    TextChanged {
+        path: Vec<usize>,
+        old: String,
+        new: String,
+    },
+    AttributeAdded {
+        path: Vec<usize>,
+        name: String,
+        value: String,
+    },
+    AttributeRemoved {
+        path: Vec<usize>,
+        name: String,
+        value: String,
+    },
+    AttributeChanged {
+        path: Vec<usize>,
+        name: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl DomPatch {
+    pub fn path(&self) -> &[usize] {
+        match self {
+            DomPatch::Added { path, .. }
+            | DomPatch::Removed { path, .. }
+            | DomPatch::TextChanged { path, .. }
+            | DomPatch::AttributeAdded { path, .. }
+            | DomPatch::AttributeRemoved { path, .. }
+            | DomPatch::AttributeChanged { path, .. } => path,
+        }
+    }
+}
+
+fn format_path(path: &[usize]) -> String {
+    let parts: Vec<String> = path.iter().map(usize::to_string).collect();
+    format!("/{}", parts.join("/"))
+}
+
+impl fmt::Display for DomPatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = format_path(self.path());
+        match self {
+            DomPatch::Added { html, .. } => write!(f, "+ {} {}", path, html),
+            DomPatch::Removed { html, .. } => write!(f, "- {} {}", path, html),
+            DomPatch::TextChanged { old, new, .. } => {
+                write!(f, "~ {} text {:?} -> {:?}", path, old, new)
+            }
+            DomPatch::AttributeAdded { name, value, .. } => {
+                write!(f, "~ {} +{}={:?}", path, name, value)
+            }
+            DomPatch::AttributeRemoved { name, value, .. } => {
+                write!(f, "~ {} -{}={:?}", path, name, value)
+            }
+            DomPatch::AttributeChanged { name, old, new, .. } => {
+                write!(f, "~ {} {}: {:?} -> {:?}", path, name, old, new)
+            }
+        }
+    }
+}
+
+// Patches form an edit script: each path addresses the tree as it is after
+// every earlier patch has been applied, so replaying them in order turns `a`
+// into `b`.
+pub fn diff(a: &Document, b: &Document) -> Vec<DomPatch> {
+    let mut fingerprints = HashMap::new();
+    fingerprint(&a.root, &mut fingerprints);
+    fingerprint(&b.root, &mut fingerprints);
+    let mut patches = Vec::new();
+    diff_children(
+        &a.root,
+        &b.root,
+        &fingerprints,
+        &mut Vec::new(),
+        &mut patches,
+    );
+    patches
+}
+
+#[derive(PartialEq)]
+enum Key<'a> {
+    Element(&'a str, Option<String>),
+    Text,
+    Comment,
+    Doctype(&'a str),
+    Other,
+}
+
+fn key(node: &Node) -> Key<'_> {
+    match &node.data {
+        NodeData::Element { name, .. } => Key::Element(&name.local, node.get_attribute("id")),
+        NodeData::Text { .. } => Key::Text,
+        NodeData::Comment { .. } => Key::Comment,
+        NodeData::Doctype { name, .. } => Key::Doctype(name),
+        _ => Key::Other,
+    }
+}
+
+fn describe(node: &Rc<Node>) -> String {
+    match &node.data {
+        NodeData::Text { contents } => contents.borrow().clone(),
+        _ => outer_html(node),
+    }
+}
+
+fn fingerprint(node: &Rc<Node>, fingerprints: &mut HashMap<NodeId, u64>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match &node.data {
+        NodeData::Element { name, attrs } => {
+            name.local.as_ref().hash(&mut hasher);
+            let mut attrs: Vec<(String, String)> = attrs
+                .borrow()
+                .iter()
+                .map(|attr| (attr.name.local.to_string(), attr.value.clone()))
+                .collect();
+            attrs.sort();
+            attrs.hash(&mut hasher);
+        }
+        NodeData::Text { contents } => ("#text", &*contents.borrow()).hash(&mut hasher),
+        NodeData::Comment { contents } => ("#comment", &*contents.borrow()).hash(&mut hasher),
+        NodeData::Doctype { name, .. } => ("#doctype", name).hash(&mut hasher),
+        _ => "#other".hash(&mut hasher),
+    }
+    if let Some(contents) = node.template_contents.borrow().as_ref() {
+        fingerprint(contents, fingerprints).hash(&mut hasher);
+    }
+    for child in node.children.borrow().iter() {
+        fingerprint(child, fingerprints).hash(&mut hasher);
+    }
+    let value = hasher.finish();
+    fingerprints.insert(node.id, value);
+    value
+}
+
+fn keyed<'a>(nodes: &'a [Rc<Node>], fingerprints: &HashMap<NodeId, u64>) -> Vec<(Key<'a>, u64)> {
+    nodes
+        .iter()
+        .map(|node| (key(node), fingerprints[&node.id]))
+        .collect()
+}
+
+fn align(
+    old: &[Rc<Node>],
+    new: &[Rc<Node>],
+    fingerprints: &HashMap<NodeId, u64>,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    if old.len() * new.len() > MAX_ALIGNMENT_CELLS {
+        let mut pairs = Vec::new();
+        for i in 0..old.len().max(new.len()) {
+            match (old.get(i), new.get(i)) {
+                (Some(a), Some(b)) if key(a) == key(b) => pairs.push((Some(i), Some(i))),
+                (a, b) => {
+                    if a.is_some() {
+                        pairs.push((Some(i), None));
+                    }
+                    if b.is_some() {
+                        pairs.push((None, Some(i)));
+                    }
+                }
+            }
+        }
+        return pairs;
+    }
+
+    let keys_old = keyed(old, fingerprints);
+    let keys_new = keyed(new, fingerprints);
+    let weight = |i: usize, j: usize| match (&keys_old[i], &keys_new[j]) {
+        ((a, x), (b, y)) if a == b && x == y => 2,
+        ((a, _), (b, _)) if a == b => 1,
+        _ => 0,
+    };
+
+    let (n, m) = (old.len(), new.len());
+    let mut scores = vec![0usize; (n + 1) * (m + 1)];
+    let score = |scores: &[usize], i: usize, j: usize| scores[i * (m + 1) + j];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            let skip = score(&scores, i + 1, j).max(score(&scores, i, j + 1));
+            scores[i * (m + 1) + j] = match weight(i, j) {
+                0 => skip,
+                weight => skip.max(score(&scores, i + 1, j + 1) + weight),
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n
+            && j < m
+            && weight(i, j) > 0
+            && score(&scores, i, j) == score(&scores, i + 1, j + 1) + weight(i, j)
+        {
+            pairs.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || score(&scores, i, j + 1) >= score(&scores, i + 1, j)) {
+            pairs.push((None, Some(j)));
+            j += 1;
+        } else {
+            pairs.push((Some(i), None));
+            i += 1;
+        }
+    }
+    pairs
+}
+
+fn diff_children(
+    old: &Rc<Node>,
+    new: &Rc<Node>,
+    fingerprints: &HashMap<NodeId, u64>,
+    path: &mut Vec<usize>,
+    patches: &mut Vec<DomPatch>,
+) {
+    let old_children: Vec<Rc<Node>> = old.children.borrow().iter().map(Rc::clone).collect();
+    let new_children: Vec<Rc<Node>> = new.children.borrow().iter().map(Rc::clone).collect();
+
+    let mut index = 0;
+    for pair in align(&old_children, &new_children, fingerprints) {
+        path.push(index);
+        match pair {
+            (Some(i), None) => patches.push(DomPatch::Removed {
+                path: path.clone(),
+                html: describe(&old_children[i]),
+            }),
+            (None, Some(j)) => {
+                patches.push(DomPatch::Added {
+                    path: path.clone(),
+                    html: describe(&new_children[j]),
+                });
+                index += 1;
+            }
+            (Some(i), Some(j)) => {
+                if fingerprints[&old_children[i].id] != fingerprints[&new_children[j].id] {
+                    diff_node(
+                        &old_children[i],
+                        &new_children[j],
+                        fingerprints,
+                        path,
+                        patches,
+                    );
+                }
+                index += 1;
+            }
+            (None, None) => {}
+        }
+        path.pop();
+    }
+}
+
+fn diff_node(
+    old: &Rc<Node>,
+    new: &Rc<Node>,
+    fingerprints: &HashMap<NodeId, u64>,
+    path: &mut Vec<usize>,
+    patches: &mut Vec<DomPatch>,
+) {
+    match (&old.data, &new.data) {
+        (NodeData::Text { contents: a }, NodeData::Text { contents: b })
+        | (NodeData::Comment { contents: a }, NodeData::Comment { contents: b })
+            if *a.borrow() != *b.borrow() =>
+        {
+            patches.push(DomPatch::TextChanged {
+                path: path.clone(),
+                old: a.borrow().clone(),
+                new: b.borrow().clone(),
+            });
+        }
+        (NodeData::Element { .. }, NodeData::Element { .. }) => {
+            diff_attributes(old, new, path, patches);
+            diff_children(old, new, fingerprints, path, patches);
+        }
+        _ => {}
+    }
+}
+
+fn diff_attributes(old: &Node, new: &Node, path: &[usize], patches: &mut Vec<DomPatch>) {
+    let old_attrs = old.attributes();
+    let new_attrs = new.attributes();
+
+    for attr in &old_attrs {
+        let name = attr.name.local.to_string();
+        match new_attrs.iter().find(|other| other.name == attr.name) {
+            None => patches.push(DomPatch::AttributeRemoved {
+                path: path.to_vec(),
+                name,
+                value: attr.value.clone(),
+            }),
+            Some(other) if other.value != attr.value => patches.push(DomPatch::AttributeChanged {
+                path: path.to_vec(),
+                name,
+                old: attr.value.clone(),
+                new: other.value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for attr in &new_attrs {
+        if !old_attrs.iter().any(|other| other.name == attr.name) {
+            patches.push(DomPatch::AttributeAdded {
+                path: path.to_vec(),
+                name: attr.name.local.to_string(),
+                value: attr.value.clone(),
+            });
+        }
+    }
+}
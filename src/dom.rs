@@ -1,16 +1,47 @@
-use html5ever::{LocalName, Namespace};
-use std::cell::RefCell;
+use html5ever::{LocalName, Namespace, Prefix, local_name, ns};
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::rc::{Rc, Weak};
 use std::string::String;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::vec::Vec;
+use url::Url;
+
+use crate::console::Console;
+use crate::css::media::MediaType;
+use crate::css::selector::{Selector, parent_element, parse_selector_list};
+use crate::custom_elements::{self, CustomElementDefinition, CustomElementRegistry};
+use crate::html::escape::{CharacterReferences, escape_attribute};
+use crate::image::Image;
+use crate::layout::{self, LayoutDirty};
+use crate::memory::MemoryStats;
+use crate::mutation::{self, Registration};
+use crate::net::ResponseInfo;
+use crate::net::websocket::PageWebSocket;
+use crate::performance::Performance;
+use crate::range::Selection;
+use crate::settings::Settings;
+use crate::storage::{Storage, StorageContext};
+use crate::style::animation::{AnimationState, Timeline};
+use crate::style::invalidation::{self, PendingRestyle};
+use crate::style::{ComputedStyle, Display, Stylist, WhiteSpace, style_of};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QualName {
-    pub prefix: Option<String>,
-    pub ns: String,
-    pub local: String,
-    pub ns_atom: Namespace,
-    pub local_atom: LocalName,
+    pub prefix: Option<Prefix>,
+    pub ns: Namespace,
+    pub local: LocalName,
+}
+
+impl QualName {
+    pub fn new(prefix: Option<Prefix>, ns: Namespace, local: LocalName) -> Self {
+        QualName { prefix, ns, local }
+    }
+
+    pub fn html_attribute(local: LocalName) -> Self {
+        QualName::new(None, Namespace::from(""), local)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -19,18 +50,31 @@ pub struct Attribute {
     pub value: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShadowRootMode {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone)]
 pub enum NodeData {
-    Document,
+    Document {
+        generations: DocumentGenerations,
+    },
+    DocumentFragment,
+    ShadowRoot {
+        mode: ShadowRootMode,
+        host: Weak<Node>,
+    },
     Element {
         name: QualName,
-        attrs: Vec<Attribute>,
+        attrs: RefCell<Vec<Attribute>>,
     },
     Text {
-        contents: String,
+        contents: RefCell<String>,
     },
     Comment {
-        contents: String,
+        contents: RefCell<String>,
     },
     Doctype {
         name: String,
@@ -39,40 +83,549 @@ pub enum NodeData {
     },
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct DocumentGenerations {
+    pub base: Cell<u64>,
+    pub stylesheets: Cell<u64>,
+}
+
+static NEXT_NODE_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u64);
+
+impl NodeId {
+    fn next() -> Self {
+        NodeId(NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 pub struct Node {
+    pub id: NodeId,
     pub data: NodeData,
     pub parent: RefCell<Weak<Node>>,
     pub children: RefCell<Vec<Rc<Node>>>,
+    pub shadow_root: RefCell<Option<Rc<Node>>>,
+    pub template_contents: RefCell<Option<Rc<Node>>>,
+    pub custom_definition: RefCell<Option<Rc<CustomElementDefinition>>>,
+    pub registered_observers: RefCell<Vec<Registration>>,
+    pub style: RefCell<Option<Rc<ComputedStyle>>>,
+    pub restyle: RefCell<PendingRestyle>,
+    pub layout_dirty: Cell<LayoutDirty>,
+    pub image: RefCell<Option<Arc<Image>>>,
+    pub content_document: RefCell<Option<Rc<Document>>>,
+    pub pseudo_before: RefCell<Option<Rc<Node>>>,
+    pub pseudo_after: RefCell<Option<Rc<Node>>>,
+    pub animations: RefCell<Vec<AnimationState>>,
 }
 
+const SHADOW_HOST_ELEMENTS: &[&str] = &[
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "div",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "main",
+    "nav",
+    "p",
+    "section",
+    "span",
+];
+
 impl Node {
     pub fn new(data: NodeData) -> Rc<Self> {
         Rc::new(Node {
+            id: NodeId::next(),
             data,
             parent: RefCell::new(Weak::new()),
             children: RefCell::new(Vec::new()),
+            shadow_root: RefCell::new(None),
+            template_contents: RefCell::new(None),
+            custom_definition: RefCell::new(None),
+            registered_observers: RefCell::new(Vec::new()),
+            style: RefCell::new(None),
+            restyle: RefCell::new(PendingRestyle::default()),
+            layout_dirty: Cell::new(LayoutDirty::default()),
+            image: RefCell::new(None),
+            content_document: RefCell::new(None),
+            pseudo_before: RefCell::new(None),
+            pseudo_after: RefCell::new(None),
+            animations: RefCell::new(Vec::new()),
         })
     }
 
+    pub fn new_fragment() -> Rc<Self> {
+        Node::new(NodeData::DocumentFragment)
+    }
+
+    pub fn template_content(&self) -> Option<Rc<Node>> {
+        self.template_contents.borrow().clone()
+    }
+
+    /// Copies `node`, and with `deep` its children, shadow root and template
+    /// contents. Copies start out as plain elements: custom element state
+    /// isn't carried over, so they're upgraded again once connected.
+    pub fn clone_node(node: &Rc<Node>, deep: bool) -> Rc<Node> {
+        let copy = Node::new(node.data.clone());
+        *copy.image.borrow_mut() = node.image.borrow().clone();
+        if let Some(contents) = node.template_content() {
+            let contents = match deep {
+                true => Node::clone_node(&contents, true),
+                false => Node::new_fragment(),
+            };
+            *copy.template_contents.borrow_mut() = Some(contents);
+        }
+        if !deep {
+            return copy;
+        }
+        if let Some(shadow_root) = node.shadow_root.borrow().as_ref()
+            && let NodeData::ShadowRoot { mode, .. } = shadow_root.data
+        {
+            let shadow_copy = Node::new(NodeData::ShadowRoot {
+                mode,
+                host: Rc::downgrade(&copy),
+            });
+            for child in shadow_root.children.borrow().iter() {
+                Node::append_child(&shadow_copy, Node::clone_node(child, true));
+            }
+            *copy.shadow_root.borrow_mut() = Some(shadow_copy);
+        }
+        for child in node.children.borrow().iter() {
+            Node::append_child(&copy, Node::clone_node(child, true));
+        }
+        copy
+    }
+
+    pub fn clone_subtree(self: &Rc<Self>) -> Rc<Node> {
+        Node::clone_node(self, true)
+    }
+
+    pub fn attach_shadow(host: &Rc<Node>, mode: ShadowRootMode) -> Option<Rc<Node>> {
+        let name = host.element_name()?;
+        let is_custom = custom_elements::is_valid_custom_element_name(name);
+        if !is_custom && !SHADOW_HOST_ELEMENTS.contains(&name) {
+            return None;
+        }
+        if host.shadow_root.borrow().is_some() {
+            return None;
+        }
+
+        let shadow_root = Node::new(NodeData::ShadowRoot {
+            mode,
+            host: Rc::downgrade(host),
+        });
+        *host.shadow_root.borrow_mut() = Some(Rc::clone(&shadow_root));
+        Some(shadow_root)
+    }
+
+    pub fn open_shadow_root(&self) -> Option<Rc<Node>> {
+        let shadow_root = self.shadow_root.borrow();
+        match shadow_root.as_ref().map(|root| &root.data) {
+            Some(NodeData::ShadowRoot {
+                mode: ShadowRootMode::Open,
+                ..
+            }) => shadow_root.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn host(&self) -> Option<Rc<Node>> {
+        match &self.data {
+            NodeData::ShadowRoot { host, .. } => host.upgrade(),
+            _ => None,
+        }
+    }
+
+    pub fn containing_shadow_root(node: &Rc<Node>) -> Option<Rc<Node>> {
+        let mut current = Rc::clone(node);
+        loop {
+            if let NodeData::ShadowRoot { .. } = current.data {
+                return Some(current);
+            }
+            let parent = current.parent.borrow().upgrade()?;
+            current = parent;
+        }
+    }
+
+    pub fn flat_tree_parent(node: &Rc<Node>) -> Option<Rc<Node>> {
+        if let Some(host) = node.host() {
+            return Some(host);
+        }
+        let parent = node.parent.borrow().upgrade()?;
+        if parent.shadow_root.borrow().is_some()
+            && let Some(slot) = Node::assigned_slot(node)
+        {
+            return Some(slot);
+        }
+        Some(parent)
+    }
+
+    pub fn flat_tree_children(node: &Rc<Node>) -> Vec<Rc<Node>> {
+        let shadow_root = node.shadow_root.borrow().clone();
+        let children = match &shadow_root {
+            Some(shadow_root) => shadow_root.children.borrow().clone(),
+            None => node.children.borrow().clone(),
+        };
+        let mut flattened = Vec::new();
+        for child in children {
+            if child.element_name() == Some("slot")
+                && Node::containing_shadow_root(&child).is_some()
+            {
+                let assigned = Node::assigned_nodes(&child);
+                if assigned.is_empty() {
+                    flattened.extend(Node::flat_tree_children(&child));
+                } else {
+                    flattened.extend(assigned);
+                }
+            } else {
+                flattened.push(child);
+            }
+        }
+        flattened
+    }
+
+    pub fn assigned_nodes(slot: &Rc<Node>) -> Vec<Rc<Node>> {
+        if slot.element_name() != Some("slot") {
+            return Vec::new();
+        }
+        let Some(host) = Node::containing_shadow_root(slot).and_then(|root| root.host()) else {
+            return Vec::new();
+        };
+
+        let slot_name = slot.get_attribute("name").unwrap_or_default();
+        host.children
+            .borrow()
+            .iter()
+            .filter(|child| match &child.data {
+                NodeData::Element { .. } => {
+                    child.get_attribute("slot").unwrap_or_default() == slot_name
+                }
+                NodeData::Text { .. } => slot_name.is_empty(),
+                _ => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn assigned_slot(node: &Rc<Node>) -> Option<Rc<Node>> {
+        let host = node.parent.borrow().upgrade()?;
+        let shadow_root = host.shadow_root.borrow().clone()?;
+
+        let slot_name = match &node.data {
+            NodeData::Element { .. } => node.get_attribute("slot").unwrap_or_default(),
+            NodeData::Text { .. } => String::new(),
+            _ => return None,
+        };
+
+        let mut found = None;
+        shadow_root.walk_rc(&mut |candidate| {
+            if found.is_none()
+                && candidate.element_name() == Some("slot")
+                && candidate.get_attribute("name").unwrap_or_default() == slot_name
+            {
+                found = Some(Rc::clone(candidate));
+            }
+        });
+        found
+    }
+
     pub fn append_child(parent: &Rc<Node>, child: Rc<Node>) {
+        if let NodeData::DocumentFragment = child.data {
+            Node::append_fragment(parent, &child);
+            return;
+        }
+        Node::detach(&child);
         *child.parent.borrow_mut() = Rc::downgrade(parent);
-        parent.children.borrow_mut().push(child);
+        let previous_sibling = {
+            let mut children = parent.children.borrow_mut();
+            let last = children.last().cloned();
+            children.push(Rc::clone(&child));
+            last
+        };
+
+        mutation::queue_child_list(
+            parent,
+            std::slice::from_ref(&child),
+            &[],
+            previous_sibling.as_ref(),
+            None,
+        );
+        Node::inserted(parent, &child);
+    }
+
+    pub fn append_fragment(parent: &Rc<Node>, fragment: &Rc<Node>) {
+        Node::insert_fragment(parent, fragment, None);
+    }
+
+    /// Moves all of `fragment`'s children into `parent` before `reference`
+    /// (or at the end), leaving the fragment empty.
+    fn insert_fragment(parent: &Rc<Node>, fragment: &Rc<Node>, reference: Option<&Rc<Node>>) {
+        let children = std::mem::take(&mut *fragment.children.borrow_mut());
+        if children.is_empty() {
+            return;
+        }
+        for child in &children {
+            *child.parent.borrow_mut() = Rc::downgrade(parent);
+        }
+        let (previous_sibling, next_sibling) = {
+            let mut existing = parent.children.borrow_mut();
+            let pos = reference
+                .and_then(|reference| existing.iter().position(|n| Rc::ptr_eq(n, reference)))
+                .unwrap_or(existing.len());
+            existing.splice(pos..pos, children.iter().cloned());
+            let previous = pos.checked_sub(1).map(|i| Rc::clone(&existing[i]));
+            (previous, existing.get(pos + children.len()).cloned())
+        };
+
+        mutation::queue_child_list(fragment, &[], &children, None, None);
+        mutation::queue_child_list(
+            parent,
+            &children,
+            &[],
+            previous_sibling.as_ref(),
+            next_sibling.as_ref(),
+        );
+        for child in &children {
+            Node::inserted(parent, child);
+        }
     }
 
     pub fn insert_before(parent: &Rc<Node>, child: Rc<Node>, reference: &Rc<Node>) {
+        if let NodeData::DocumentFragment = child.data {
+            Node::insert_fragment(parent, &child, Some(reference));
+            return;
+        }
+        Node::detach(&child);
         *child.parent.borrow_mut() = Rc::downgrade(parent);
-        let mut children = parent.children.borrow_mut();
+        let (previous_sibling, next_sibling) = {
+            let mut children = parent.children.borrow_mut();
+            match children.iter().position(|n| Rc::ptr_eq(n, reference)) {
+                Some(pos) => {
+                    children.insert(pos, Rc::clone(&child));
+                    let previous = pos.checked_sub(1).map(|i| Rc::clone(&children[i]));
+                    (previous, Some(Rc::clone(reference)))
+                }
+                None => {
+                    let last = children.last().cloned();
+                    children.push(Rc::clone(&child));
+                    (last, None)
+                }
+            }
+        };
 
-        if let Some(pos) = children.iter().position(|n| Rc::ptr_eq(n, reference)) {
-            children.insert(pos, child);
-        } else {
-            children.push(child);
-        }
+        mutation::queue_child_list(
+            parent,
+            std::slice::from_ref(&child),
+            &[],
+            previous_sibling.as_ref(),
+            next_sibling.as_ref(),
+        );
+        Node::inserted(parent, &child);
     }
 
     pub fn remove_child(parent: &Rc<Node>, child: &Rc<Node>) {
-        let mut children = parent.children.borrow_mut();
-        children.retain(|n| !Rc::ptr_eq(n, child));
+        let was_connected = Node::has_custom_elements(child) && Node::is_connected(parent);
+        let (previous_sibling, next_sibling) = {
+            let mut children = parent.children.borrow_mut();
+            let Some(pos) = children.iter().position(|n| Rc::ptr_eq(n, child)) else {
+                return;
+            };
+            children.remove(pos);
+            let previous = pos.checked_sub(1).map(|i| Rc::clone(&children[i]));
+            (previous, children.get(pos).cloned())
+        };
+        *child.parent.borrow_mut() = Weak::new();
+        layout::mark_dirty(parent);
+        invalidation::children_changed(parent);
+        invalidation::child_list_changed(parent, child);
+        Node::base_changed(parent, child);
+
+        mutation::queue_child_list(
+            parent,
+            &[],
+            std::slice::from_ref(child),
+            previous_sibling.as_ref(),
+            next_sibling.as_ref(),
+        );
+        if was_connected {
+            custom_elements::connection_changed(child, false);
+        }
+    }
+
+    pub fn previous_sibling(node: &Rc<Node>) -> Option<Rc<Node>> {
+        let parent = node.parent.borrow().upgrade()?;
+        let children = parent.children.borrow();
+        let pos = children.iter().position(|n| Rc::ptr_eq(n, node))?;
+        pos.checked_sub(1).map(|i| Rc::clone(&children[i]))
+    }
+
+    pub fn next_sibling(node: &Rc<Node>) -> Option<Rc<Node>> {
+        let parent = node.parent.borrow().upgrade()?;
+        let children = parent.children.borrow();
+        let pos = children.iter().position(|n| Rc::ptr_eq(n, node))?;
+        children.get(pos + 1).cloned()
+    }
+
+    pub fn index_path(node: &Rc<Node>) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = Rc::clone(node);
+        loop {
+            let Some(parent) = current.parent.borrow().upgrade() else {
+                break;
+            };
+            let index = parent
+                .children
+                .borrow()
+                .iter()
+                .position(|child| Rc::ptr_eq(child, &current));
+            let Some(index) = index else {
+                break;
+            };
+            path.push(index);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    pub fn path(self: &Rc<Self>) -> String {
+        let mut parts = Vec::new();
+        let mut current = Rc::clone(self);
+        loop {
+            let Some(parent) = current.parent.borrow().upgrade() else {
+                break;
+            };
+            let siblings = parent.children.borrow();
+            let part = match current.element_name() {
+                Some(name) => {
+                    let elements: Vec<&Rc<Node>> = siblings
+                        .iter()
+                        .filter(|sibling| sibling.element_name().is_some())
+                        .collect();
+                    let same_name = elements
+                        .iter()
+                        .filter(|sibling| sibling.element_name() == Some(name))
+                        .count();
+                    let index = elements
+                        .iter()
+                        .position(|sibling| Rc::ptr_eq(sibling, &current))
+                        .unwrap_or_default();
+                    if same_name > 1 {
+                        format!("{}:nth-child({})", name, index + 1)
+                    } else {
+                        name.to_string()
+                    }
+                }
+                None => {
+                    let kind = match current.data {
+                        NodeData::Text { .. } => "#text",
+                        NodeData::Comment { .. } => "#comment",
+                        NodeData::Doctype { .. } => "#doctype",
+                        _ => "#node",
+                    };
+                    let index = siblings
+                        .iter()
+                        .position(|sibling| Rc::ptr_eq(sibling, &current))
+                        .unwrap_or_default();
+                    format!("{}[{}]", kind, index)
+                }
+            };
+            parts.push(part);
+            drop(siblings);
+            if let NodeData::ShadowRoot { .. } = parent.data {
+                parts.push(String::from("#shadow-root"));
+                match parent.host() {
+                    Some(host) => current = host,
+                    None => break,
+                }
+                continue;
+            }
+            current = parent;
+        }
+        parts.reverse();
+        parts.join(" > ")
+    }
+
+    pub fn detach(node: &Rc<Node>) {
+        let parent = node.parent.borrow().upgrade();
+        if let Some(parent) = parent {
+            Node::remove_child(&parent, node);
+        }
+    }
+
+    fn inserted(parent: &Rc<Node>, child: &Rc<Node>) {
+        invalidation::node_inserted(child);
+        invalidation::children_changed(parent);
+        invalidation::child_list_changed(parent, child);
+        Node::base_changed(parent, child);
+        layout::mark_dirty(child);
+        if Node::has_custom_elements(child) && Node::is_connected(parent) {
+            custom_elements::connection_changed(child, true);
+        }
+    }
+
+    fn has_custom_elements(node: &Rc<Node>) -> bool {
+        if node.custom_definition.borrow().is_some() {
+            return true;
+        }
+        if let Some(shadow_root) = node.shadow_root.borrow().as_ref()
+            && Node::has_custom_elements(shadow_root)
+        {
+            return true;
+        }
+        node.children.borrow().iter().any(Node::has_custom_elements)
+    }
+
+    pub fn is_connected(node: &Rc<Node>) -> bool {
+        Node::document_root(node).is_some()
+    }
+
+    pub fn document_root(node: &Rc<Node>) -> Option<Rc<Node>> {
+        let mut current = Rc::clone(node);
+        loop {
+            match &current.data {
+                NodeData::Document { .. } => return Some(current),
+                NodeData::ShadowRoot { host, .. } => current = host.upgrade()?,
+                _ => {
+                    let parent = current.parent.borrow().upgrade()?;
+                    current = parent;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn bump_generation(
+        node: &Rc<Node>,
+        generation: fn(&DocumentGenerations) -> &Cell<u64>,
+    ) {
+        if let Some(root) = Node::document_root(node)
+            && let NodeData::Document { generations } = &root.data
+        {
+            let generation = generation(generations);
+            generation.set(generation.get() + 1);
+        }
+    }
+
+    fn base_changed(parent: &Rc<Node>, child: &Node) {
+        let mut has_base = false;
+        child.walk(&mut |node| has_base |= node.element_name() == Some("base"));
+        if has_base {
+            Node::bump_generation(parent, |generations| &generations.base);
+        }
     }
 
     pub fn element_name(&self) -> Option<&str> {
@@ -82,13 +635,179 @@ impl Node {
         }
     }
 
-    pub fn text_content(&self) -> Option<&str> {
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        match &self.data {
+            NodeData::Element { attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .find(|attr| attr.name.local.as_ref().eq_ignore_ascii_case(name))
+                .map(|attr| attr.value.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn attribute_text(&self, name: &str, references: CharacterReferences) -> Option<String> {
+        // The tokenizer has already decoded the value once, so `Decode` returns
+        // it as is. The source spelling is gone after parsing, and `Keep`
+        // returns the value escaped the way the serializer would write it.
+        let value = self.get_attribute(name)?;
+        Some(match references {
+            CharacterReferences::Keep => escape_attribute(&value),
+            CharacterReferences::Decode => value,
+        })
+    }
+
+    pub fn with_attribute<R>(&self, name: &LocalName, f: impl FnOnce(Option<&str>) -> R) -> R {
+        match &self.data {
+            NodeData::Element { attrs, .. } => f(attrs
+                .borrow()
+                .iter()
+                .find(|attr| attr.name.local == *name)
+                .map(|attr| attr.value.as_str())),
+            _ => f(None),
+        }
+    }
+
+    pub fn has_attribute(&self, name: &str) -> bool {
+        match &self.data {
+            NodeData::Element { attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .any(|attr| attr.name.local.as_ref().eq_ignore_ascii_case(name)),
+            _ => false,
+        }
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.with_attribute(&local_name!("class"), |value| {
+            value.is_some_and(|value| value.split_ascii_whitespace().any(|c| c == class))
+        })
+    }
+
+    pub fn matches(self: &Rc<Self>, selectors: &str) -> anyhow::Result<bool> {
+        let selectors = parse_selectors(selectors)?;
+        Ok(self.element_name().is_some() && selectors.iter().any(|s| s.matches(self)))
+    }
+
+    pub fn closest(self: &Rc<Self>, selectors: &str) -> anyhow::Result<Option<Rc<Node>>> {
+        let selectors = parse_selectors(selectors)?;
+        let mut current = Some(Rc::clone(self)).filter(|node| node.element_name().is_some());
+        while let Some(node) = current {
+            if selectors.iter().any(|s| s.matches(&node)) {
+                return Ok(Some(node));
+            }
+            current = parent_element(&node);
+        }
+        Ok(None)
+    }
+
+    pub fn attributes(&self) -> Vec<Attribute> {
+        match &self.data {
+            NodeData::Element { attrs, .. } => attrs.borrow().clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn set_attribute(node: &Rc<Node>, name: &str, value: &str) {
+        let NodeData::Element { attrs, .. } = &node.data else {
+            return;
+        };
+        let name = LocalName::from(name.to_ascii_lowercase());
+
+        let old_value = {
+            let mut attrs = attrs.borrow_mut();
+            match attrs.iter_mut().find(|attr| attr.name.local == name) {
+                Some(attr) => Some(std::mem::replace(&mut attr.value, value.to_string())),
+                None => {
+                    attrs.push(Attribute {
+                        name: QualName::html_attribute(name.clone()),
+                        value: value.to_string(),
+                    });
+                    None
+                }
+            }
+        };
+
+        mutation::queue_attribute(node, &name, old_value.as_deref());
+        invalidation::attribute_changed(node, &name, old_value.as_deref());
+        if &*name == "href" && node.element_name() == Some("base") {
+            Node::bump_generation(node, |generations| &generations.base);
+        }
+        layout::mark_dirty(node);
+        custom_elements::attribute_changed(node, &name, old_value.as_deref(), Some(value));
+    }
+
+    pub fn remove_attribute(node: &Rc<Node>, name: &str) {
+        let NodeData::Element { attrs, .. } = &node.data else {
+            return;
+        };
+        let name = LocalName::from(name.to_ascii_lowercase());
+
+        let old_value = {
+            let mut attrs = attrs.borrow_mut();
+            let Some(pos) = attrs.iter().position(|attr| attr.name.local == name) else {
+                return;
+            };
+            attrs.remove(pos).value
+        };
+
+        mutation::queue_attribute(node, &name, Some(&old_value));
+        invalidation::attribute_changed(node, &name, Some(&old_value));
+        if &*name == "href" && node.element_name() == Some("base") {
+            Node::bump_generation(node, |generations| &generations.base);
+        }
+        layout::mark_dirty(node);
+        custom_elements::attribute_changed(node, &name, Some(&old_value), None);
+    }
+
+    pub fn text_content(&self) -> Option<String> {
         match &self.data {
-            NodeData::Text { contents } => Some(contents),
+            NodeData::Text { contents } => Some(contents.borrow().clone()),
             _ => None,
         }
     }
 
+    pub fn normalize(self: &Rc<Self>) {
+        let children: Vec<Rc<Node>> = self.children.borrow().clone();
+        let mut run: Option<(Rc<Node>, String)> = None;
+        for child in children {
+            let NodeData::Text { contents } = &child.data else {
+                self.flush_text_run(run.take());
+                child.normalize();
+                continue;
+            };
+            let text = contents.borrow().clone();
+            if text.is_empty() {
+                Node::remove_child(self, &child);
+            } else if let Some((_, merged)) = run.as_mut() {
+                merged.push_str(&text);
+                Node::remove_child(self, &child);
+            } else {
+                run = Some((child, text));
+            }
+        }
+        self.flush_text_run(run);
+    }
+
+    fn flush_text_run(&self, run: Option<(Rc<Node>, String)>) {
+        if let Some((first, merged)) = run
+            && first.text_content().as_deref() != Some(merged.as_str())
+        {
+            Node::set_character_data(&first, &merged);
+        }
+    }
+
+    pub fn set_character_data(node: &Rc<Node>, value: &str) {
+        let (NodeData::Text { contents } | NodeData::Comment { contents }) = &node.data else {
+            return;
+        };
+
+        let old_value = std::mem::replace(&mut *contents.borrow_mut(), value.to_string());
+        mutation::queue_character_data(node, &old_value);
+        invalidation::text_changed(node);
+        layout::mark_dirty(node);
+    }
+
     pub fn get_text_content(&self) -> String {
         let mut text = String::new();
         self.collect_text(&mut text);
@@ -97,7 +816,7 @@ impl Node {
 
     fn collect_text(&self, buffer: &mut String) {
         match &self.data {
-            NodeData::Text { contents } => buffer.push_str(contents),
+            NodeData::Text { contents } => buffer.push_str(&contents.borrow()),
             _ => {
                 for child in self.children.borrow().iter() {
                     child.collect_text(buffer);
@@ -106,6 +825,12 @@ impl Node {
         }
     }
 
+    pub fn inner_text(self: &Rc<Self>) -> String {
+        let mut text = InnerText::default();
+        text.collect(self);
+        text.finish()
+    }
+
     pub fn walk<F>(&self, visitor: &mut F)
     where
         F: FnMut(&Node),
@@ -115,40 +840,412 @@ impl Node {
             child.walk(visitor);
         }
     }
+
+    pub fn walk_rc<F>(self: &Rc<Self>, visitor: &mut F)
+    where
+        F: FnMut(&Rc<Node>),
+    {
+        visitor(self);
+        for child in self.children.borrow().iter() {
+            child.walk_rc(visitor);
+        }
+    }
+}
+
+fn find_node_by_id(node: &Rc<Node>, id: NodeId) -> Option<Rc<Node>> {
+    if node.id == id {
+        return Some(Rc::clone(node));
+    }
+    if let Some(shadow_root) = node.shadow_root.borrow().as_ref()
+        && let Some(found) = find_node_by_id(shadow_root, id)
+    {
+        return Some(found);
+    }
+    if let Some(contents) = node.template_content()
+        && let Some(found) = find_node_by_id(&contents, id)
+    {
+        return Some(found);
+    }
+    node.children
+        .borrow()
+        .iter()
+        .find_map(|child| find_node_by_id(child, id))
+}
+
+fn element(local: LocalName) -> Rc<Node> {
+    Node::new(NodeData::Element {
+        name: QualName::new(None, ns!(html), local),
+        attrs: RefCell::new(Vec::new()),
+    })
+}
+
+fn is_inclusive_ancestor(ancestor: &Rc<Node>, node: &Rc<Node>) -> bool {
+    let mut current = Some(Rc::clone(node));
+    while let Some(node) = current {
+        if Rc::ptr_eq(&node, ancestor) {
+            return true;
+        }
+        current = node.parent.borrow().upgrade();
+    }
+    false
+}
+
+#[derive(Default)]
+struct InnerText {
+    out: String,
+    trailing_space: bool,
+}
+
+impl InnerText {
+    fn collect(&mut self, node: &Rc<Node>) {
+        match &node.data {
+            NodeData::Text { contents } => {
+                let style = style_of(node);
+                if style.is_visible() {
+                    self.push_text(&contents.borrow(), style.white_space)
+                }
+            }
+            NodeData::Element { .. } => {
+                let style = style_of(node);
+                match (node.element_name(), style.display) {
+                    (_, Display::None) => {}
+                    (Some("br"), _) => {
+                        self.trim_space();
+                        self.out.push('\n');
+                    }
+                    (_, display) => {
+                        if display == Display::Block {
+                            self.line_break();
+                        }
+                        let children = node.children.borrow().clone();
+                        for child in &children {
+                            self.collect(child);
+                        }
+                        if display == Display::Block {
+                            self.line_break();
+                        }
+                    }
+                }
+            }
+            NodeData::Document { .. } | NodeData::ShadowRoot { .. } => {
+                let children = node.children.borrow().clone();
+                for child in &children {
+                    self.collect(child);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_text(&mut self, text: &str, white_space: WhiteSpace) {
+        if !white_space.collapses_spaces() {
+            self.out.push_str(text);
+            self.trailing_space = false;
+            return;
+        }
+        for c in text.chars() {
+            if c == '\n' && white_space.preserves_newlines() {
+                self.trim_space();
+                self.out.push('\n');
+            } else if c.is_whitespace() {
+                if !self.trailing_space && !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push(' ');
+                    self.trailing_space = true;
+                }
+            } else {
+                self.out.push(c);
+                self.trailing_space = false;
+            }
+        }
+    }
+
+    fn trim_space(&mut self) {
+        if self.trailing_space {
+            self.out.pop();
+            self.trailing_space = false;
+        }
+    }
+
+    fn line_break(&mut self) {
+        self.trim_space();
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.trim_space();
+        let len = self.out.trim_end_matches('\n').len();
+        self.out.truncate(len);
+        self.out
+    }
+}
+
+fn parse_selectors(selectors: &str) -> anyhow::Result<Vec<Selector>> {
+    match parse_selector_list(selectors) {
+        Some(selectors) => Ok(selectors),
+        None => anyhow::bail!("'{}' is not a valid selector", selectors),
+    }
 }
 
 pub struct Document {
     pub root: Rc<Node>,
+    pub response: Option<ResponseInfo>,
+    pub source: Option<Rc<str>>,
+    pub custom_elements: CustomElementRegistry,
+    pub selection: RefCell<Selection>,
+    pub console: Rc<Console>,
+    pub performance: Rc<Performance>,
+    pub stylist: RefCell<Option<Rc<Stylist>>>,
+    pub scripting: bool,
+    pub media_type: Cell<MediaType>,
+    pub settings: Arc<Settings>,
+    pub storage: StorageContext,
+    pub timeline: Timeline,
+    base: RefCell<Option<Box<CachedBase>>>,
+    websockets: RefCell<Vec<Rc<PageWebSocket>>>,
+}
+
+struct CachedBase {
+    generation: u64,
+    fallback: Option<Url>,
+    base: Option<Url>,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Document {
     pub fn new() -> Self {
         Document {
-            root: Node::new(NodeData::Document),
+            root: Node::new(NodeData::Document {
+                generations: DocumentGenerations::default(),
+            }),
+            response: None,
+            source: None,
+            custom_elements: CustomElementRegistry::new(),
+            selection: RefCell::new(Selection::new()),
+            console: Rc::new(Console::new()),
+            performance: Rc::new(Performance::default()),
+            stylist: RefCell::new(None),
+            scripting: true,
+            media_type: Cell::new(MediaType::Screen),
+            settings: Arc::new(Settings::default()),
+            storage: StorageContext::default(),
+            timeline: Timeline::default(),
+            base: RefCell::new(None),
+            websockets: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<Rc<Node>> {
-        let mut results = Vec::new();
-        self.collect_elements_by_tag_name(&self.root, tag_name, &mut results);
-        results
+    pub fn define_custom_element(&self, definition: CustomElementDefinition) -> anyhow::Result<()> {
+        self.custom_elements.define(&self.root, definition)
+    }
+
+    pub fn selected_text(&self) -> String {
+        self.selection.borrow().to_text()
+    }
+
+    pub fn url(&self) -> Option<&Url> {
+        self.response.as_ref().map(|info| &info.url)
+    }
+
+    pub fn base_url(&self) -> Option<Url> {
+        let NodeData::Document { generations } = &self.root.data else {
+            return self.url().cloned();
+        };
+        let generation = generations.base.get();
+        let fallback = self.url();
+        if let Some(cached) = self.base.borrow().as_ref()
+            && cached.generation == generation
+            && cached.fallback.as_ref() == fallback
+        {
+            return cached.base.clone();
+        }
+        let base = self.find_base_url();
+        *self.base.borrow_mut() = Some(Box::new(CachedBase {
+            generation,
+            fallback: fallback.cloned(),
+            base: base.clone(),
+        }));
+        base
+    }
+
+    fn find_base_url(&self) -> Option<Url> {
+        let fallback = self.url();
+        let mut href = None;
+        self.root.walk(&mut |node| {
+            if href.is_none() && node.element_name() == Some("base") {
+                href = node.get_attribute("href");
+            }
+        });
+        let base = href.and_then(|href| match fallback {
+            Some(fallback) => fallback.join(href.trim()).ok(),
+            None => Url::parse(href.trim()).ok(),
+        });
+        match base {
+            Some(base) if !matches!(base.scheme(), "data" | "javascript") => Some(base),
+            _ => fallback.cloned(),
+        }
+    }
+
+    pub fn resolve_url(&self, relative: &str) -> Option<Url> {
+        let relative = relative.trim();
+        match self.base_url() {
+            Some(base) => base.join(relative).ok(),
+            None => Url::parse(relative).ok(),
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.response.as_ref()?.header(name)
     }
 
-    fn collect_elements_by_tag_name(
+    pub fn open_websocket(
         &self,
-        node: &Rc<Node>,
-        tag_name: &str,
-        results: &mut Vec<Rc<Node>>,
-    ) {
-        if let Some(name) = node.element_name() {
-            if name.eq_ignore_ascii_case(tag_name) {
-                results.push(Rc::clone(node));
+        url: &str,
+        protocols: &[&str],
+    ) -> anyhow::Result<Rc<PageWebSocket>> {
+        let base = self.base_url();
+        let socket = Rc::new(PageWebSocket::open(
+            base.as_ref(),
+            url,
+            protocols,
+            &self.settings,
+        )?);
+        self.websockets.borrow_mut().push(Rc::clone(&socket));
+        Ok(socket)
+    }
+
+    /// Runs `f` on the document's `localStorage` area.
+    pub fn local_storage<R>(&self, f: impl FnOnce(&mut Storage) -> R) -> anyhow::Result<R> {
+        self.storage.local(self.storage_url()?, f)
+    }
+
+    /// Runs `f` on the document's `sessionStorage` area.
+    pub fn session_storage<R>(&self, f: impl FnOnce(&mut Storage) -> R) -> anyhow::Result<R> {
+        self.storage.session(self.storage_url()?, f)
+    }
+
+    fn storage_url(&self) -> anyhow::Result<&Url> {
+        self.url()
+            .ok_or_else(|| anyhow::anyhow!("SecurityError: the document has no storage origin"))
+    }
+
+    pub fn has_websockets(&self) -> bool {
+        !self.websockets.borrow().is_empty()
+    }
+
+    pub fn dispatch_websocket_events(&self) -> bool {
+        let sockets = self.websockets.borrow().clone();
+        let dispatched = sockets
+            .iter()
+            .fold(0, |count, socket| count + socket.dispatch_events());
+        self.websockets
+            .borrow_mut()
+            .retain(|socket| !socket.is_finished());
+        dispatched > 0
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats::measure(&self.root)
+    }
+
+    pub fn get_element_by_id(&self, id: &str) -> Option<Rc<Node>> {
+        let mut found = None;
+        self.root.walk_rc(&mut |node| {
+            if found.is_none() && node.get_attribute("id").as_deref() == Some(id) {
+                found = Some(Rc::clone(node));
             }
+        });
+        found
+    }
+
+    pub fn node_by_id(&self, id: NodeId) -> Option<Rc<Node>> {
+        find_node_by_id(&self.root, id)
+    }
+
+    pub fn node_at_path(&self, path: &[usize]) -> Option<Rc<Node>> {
+        let mut current = Rc::clone(&self.root);
+        for index in path {
+            let child = current.children.borrow().get(*index).cloned()?;
+            current = child;
         }
+        Some(current)
+    }
 
-        for child in node.children.borrow().iter() {
-            self.collect_elements_by_tag_name(child, tag_name, results);
+    pub fn query_selector_all(&self, selectors: &str) -> anyhow::Result<Vec<Rc<Node>>> {
+        let selectors = parse_selectors(selectors)?;
+        let mut results = Vec::new();
+        self.root.walk_rc(&mut |node| {
+            if node.element_name().is_some() && selectors.iter().any(|s| s.matches(node)) {
+                results.push(Rc::clone(node));
+            }
+        });
+        Ok(results)
+    }
+
+    pub fn query_selector(&self, selectors: &str) -> anyhow::Result<Option<Rc<Node>>> {
+        Ok(self.query_selector_all(selectors)?.into_iter().next())
+    }
+
+    pub fn extract(&self, selectors: &str) -> anyhow::Result<Document> {
+        let matches = self.query_selector_all(selectors)?;
+        let mut document = Document::new();
+        document.response = self.response.clone();
+        document.scripting = self.scripting;
+        document.settings = Arc::clone(&self.settings);
+        document.storage = self.storage.clone();
+
+        let html = element(local_name!("html"));
+        let head = element(local_name!("head"));
+        let body = element(local_name!("body"));
+        Node::append_child(&document.root, Rc::clone(&html));
+        Node::append_child(&html, head);
+        Node::append_child(&html, Rc::clone(&body));
+
+        let mut taken: Vec<Rc<Node>> = Vec::new();
+        for node in matches {
+            let nested = taken
+                .iter()
+                .any(|ancestor| is_inclusive_ancestor(ancestor, &node));
+            if !nested {
+                Node::append_child(&body, node.clone_subtree());
+                taken.push(node);
+            }
         }
+        Ok(document)
+    }
+
+    pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<Rc<Node>> {
+        let lowercase = tag_name.to_ascii_lowercase();
+        self.collect_elements(|name| match tag_name {
+            "*" => true,
+            _ if name.ns == ns!(html) => *name.local == *lowercase,
+            _ => *name.local == *tag_name,
+        })
+    }
+
+    pub fn get_elements_by_tag_name_ns(&self, namespace: &str, local_name: &str) -> Vec<Rc<Node>> {
+        self.collect_elements(|name| {
+            (namespace == "*" || *name.ns == *namespace)
+                && (local_name == "*" || *name.local == *local_name)
+        })
+    }
+
+    fn collect_elements(&self, predicate: impl Fn(&QualName) -> bool) -> Vec<Rc<Node>> {
+        let mut results = Vec::new();
+        self.root.walk_rc(&mut |node| {
+            if let NodeData::Element { name, .. } = &node.data
+                && predicate(name)
+            {
+                results.push(Rc::clone(node));
+            }
+        });
+        results
     }
 
     pub fn print_tree(&self) {
@@ -158,9 +1255,12 @@ impl Document {
     fn print_node(&self, node: &Rc<Node>, depth: usize) {
         let indent = "  ".repeat(depth);
         match &node.data {
-            NodeData::Document => println!("{}Document", indent),
+            NodeData::Document { .. } => println!("{}Document", indent),
+            NodeData::DocumentFragment => println!("{}DocumentFragment", indent),
+            NodeData::ShadowRoot { mode, .. } => println!("{}ShadowRoot ({:?})", indent, mode),
             NodeData::Element { name, .. } => println!("{}Element: {}", indent, name.local),
             NodeData::Text { contents } => {
+                let contents = contents.borrow();
                 let trimmed = contents.trim();
                 if !trimmed.is_empty() {
                     println!("{}Text: {:?}", indent, trimmed);
@@ -170,6 +1270,13 @@ impl Document {
             NodeData::Doctype { name, .. } => println!("{}Doctype: {}", indent, name),
         }
 
+        if let Some(shadow_root) = node.shadow_root.borrow().as_ref() {
+            self.print_node(shadow_root, depth + 1);
+        }
+        if let Some(contents) = node.template_content() {
+            self.print_node(&contents, depth + 1);
+        }
+
         for child in node.children.borrow().iter() {
             self.print_node(child, depth + 1);
         }
@@ -0,0 +1,372 @@
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Text(String),
+    Url(String),
+    Item(Item),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Item {
+    pub types: Vec<String>,
+    pub id: Option<String>,
+    pub properties: Vec<(String, PropertyValue)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraphImage {
+    pub url: String,
+    pub secure_url: Option<String>,
+    pub mime_type: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub alt: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenGraph {
+    pub title: Option<String>,
+    pub kind: Option<String>,
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub site_name: Option<String>,
+    pub locale: Option<String>,
+    pub images: Vec<OpenGraphImage>,
+    pub properties: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TwitterCard {
+    pub card: Option<String>,
+    pub site: Option<String>,
+    pub creator: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub image_alt: Option<String>,
+    pub properties: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StructuredData {
+    pub json_ld: Vec<Value>,
+    pub microdata: Vec<Item>,
+    pub open_graph: OpenGraph,
+    pub twitter: TwitterCard,
+}
+
+impl Item {
+    pub fn get(&self, name: &str) -> Option<&PropertyValue> {
+        self.properties
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_all(&self, name: &str) -> Vec<&PropertyValue> {
+        self.properties
+            .iter()
+            .filter(|(property, _)| property == name)
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        if !self.types.is_empty() {
+            object.insert(String::from("type"), Value::from(self.types.clone()));
+        }
+        if let Some(id) = &self.id {
+            object.insert(String::from("id"), Value::from(id.as_str()));
+        }
+        let mut properties = Map::new();
+        for (name, value) in &self.properties {
+            let value = match value {
+                PropertyValue::Text(text) | PropertyValue::Url(text) => Value::from(text.as_str()),
+                PropertyValue::Item(item) => item.to_json(),
+            };
+            match properties.get_mut(name) {
+                Some(Value::Array(values)) => values.push(value),
+                _ => {
+                    properties.insert(name.clone(), Value::Array(vec![value]));
+                }
+            }
+        }
+        object.insert(String::from("properties"), Value::Object(properties));
+        Value::Object(object)
+    }
+}
+
+impl PropertyValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::Text(text) | PropertyValue::Url(text) => Some(text),
+            PropertyValue::Item(_) => None,
+        }
+    }
+}
+
+impl OpenGraph {
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    fn set(&mut self, name: &str, content: &str) {
+        self.properties
+            .push((name.to_string(), content.to_string()));
+        let value = Some(content.to_string());
+        match name {
+            "title" => self.title = value,
+            "type" => self.kind = value,
+            "url" => self.url = value,
+            "description" => self.description = value,
+            "site_name" => self.site_name = value,
+            "locale" => self.locale = value,
+            "image" | "image:url" => self.images.push(OpenGraphImage {
+                url: content.to_string(),
+                ..OpenGraphImage::default()
+            }),
+            _ => {
+                let Some(field) = name.strip_prefix("image:") else {
+                    return;
+                };
+                let Some(image) = self.images.last_mut() else {
+                    return;
+                };
+                match field {
+                    "secure_url" => image.secure_url = value,
+                    "type" => image.mime_type = value,
+                    "width" => image.width = content.trim().parse().ok(),
+                    "height" => image.height = content.trim().parse().ok(),
+                    "alt" => image.alt = value,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+impl TwitterCard {
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    fn set(&mut self, name: &str, content: &str) {
+        self.properties
+            .push((name.to_string(), content.to_string()));
+        let value = Some(content.to_string());
+        match name {
+            "card" => self.card = value,
+            "site" => self.site = value,
+            "creator" => self.creator = value,
+            "title" => self.title = value,
+            "description" => self.description = value,
+            "image" | "image:src" => self.image = value,
+            "image:alt" => self.image_alt = value,
+            _ => {}
+        }
+    }
+}
+
+impl StructuredData {
+    pub fn is_empty(&self) -> bool {
+        self.json_ld.is_empty()
+            && self.microdata.is_empty()
+            && self.open_graph.is_empty()
+            && self.twitter.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        let pairs = |properties: &[(String, String)]| {
+            Value::Array(
+                properties
+                    .iter()
+                    .map(|(name, value)| Value::from(vec![name.as_str(), value.as_str()]))
+                    .collect(),
+            )
+        };
+        let mut object = Map::new();
+        object.insert(String::from("jsonLd"), Value::Array(self.json_ld.clone()));
+        object.insert(
+            String::from("microdata"),
+            Value::Array(self.microdata.iter().map(Item::to_json).collect()),
+        );
+        object.insert(
+            String::from("openGraph"),
+            pairs(&self.open_graph.properties),
+        );
+        object.insert(String::from("twitter"), pairs(&self.twitter.properties));
+        Value::Object(object)
+    }
+}
+
+impl Document {
+    pub fn structured_data(&self) -> StructuredData {
+        let mut data = StructuredData {
+            json_ld: self.json_ld(),
+            microdata: self.microdata(),
+            ..StructuredData::default()
+        };
+        for meta in self.get_elements_by_tag_name("meta") {
+            let Some(content) = meta.get_attribute("content") else {
+                continue;
+            };
+            let names = [meta.get_attribute("property"), meta.get_attribute("name")];
+            for name in names.iter().flatten() {
+                let name = name.trim().to_ascii_lowercase();
+                if let Some(name) = name.strip_prefix("og:") {
+                    data.open_graph.set(name, &content);
+                    break;
+                }
+                if let Some(name) = name.strip_prefix("twitter:") {
+                    data.twitter.set(name, &content);
+                    break;
+                }
+            }
+        }
+        data
+    }
+
+    pub fn json_ld(&self) -> Vec<Value> {
+        let mut values = Vec::new();
+        for script in self.get_elements_by_tag_name("script") {
+            let is_json_ld = script.get_attribute("type").is_some_and(|kind| {
+                kind.split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .eq_ignore_ascii_case("application/ld+json")
+            });
+            if !is_json_ld {
+                continue;
+            }
+            match serde_json::from_str(&script.get_text_content()) {
+                Ok(Value::Array(items)) => values.extend(items),
+                Ok(value) => values.push(value),
+                Err(err) => log::debug!("skipping invalid JSON-LD block: {}", err),
+            }
+        }
+        values
+    }
+
+    pub fn microdata(&self) -> Vec<Item> {
+        let mut roots = Vec::new();
+        let mut positions = HashMap::new();
+        self.root.walk_rc(&mut |node| {
+            positions.insert(Rc::as_ptr(node), positions.len());
+            if node.has_attribute("itemscope") && !node.has_attribute("itemprop") {
+                roots.push(Rc::clone(node));
+            }
+        });
+        let mut extractor = Microdata {
+            document: self,
+            positions,
+            visited: HashSet::new(),
+        };
+        roots.iter().map(|root| extractor.item(root)).collect()
+    }
+
+    fn absolute_url(&self, value: &str) -> String {
+        self.resolve_url(value)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| value.trim().to_string())
+    }
+}
+
+struct Microdata<'a> {
+    document: &'a Document,
+    positions: HashMap<*const Node, usize>,
+    visited: HashSet<*const Node>,
+}
+
+impl Microdata<'_> {
+    fn item(&mut self, root: &Rc<Node>) -> Item {
+        self.visited.insert(Rc::as_ptr(root));
+        let types = root
+            .get_attribute("itemtype")
+            .map(|types| types.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        let id = root
+            .get_attribute("itemid")
+            .map(|id| self.document.absolute_url(&id));
+
+        let mut pending: Vec<Rc<Node>> = root.children.borrow().iter().cloned().collect();
+        if let Some(references) = root.get_attribute("itemref") {
+            pending.extend(
+                references
+                    .split_whitespace()
+                    .filter_map(|id| self.document.get_element_by_id(id)),
+            );
+        }
+
+        let mut elements = Vec::new();
+        let mut seen = HashSet::new();
+        while let Some(node) = pending.pop() {
+            if Rc::ptr_eq(&node, root)
+                || node.element_name().is_none()
+                || !seen.insert(Rc::as_ptr(&node))
+            {
+                continue;
+            }
+            if !node.has_attribute("itemscope") {
+                pending.extend(node.children.borrow().iter().cloned());
+            }
+            if node.has_attribute("itemprop") {
+                elements.push(node);
+            }
+        }
+        elements.sort_by_key(|node| self.positions.get(&Rc::as_ptr(node)).copied());
+
+        let mut properties = Vec::new();
+        for element in elements {
+            let names = element.get_attribute("itemprop").unwrap_or_default();
+            let value = if element.has_attribute("itemscope") {
+                if self.visited.contains(&Rc::as_ptr(&element)) {
+                    continue;
+                }
+                PropertyValue::Item(self.item(&element))
+            } else {
+                self.property_value(&element)
+            };
+            for name in names.split_whitespace() {
+                properties.push((name.to_string(), value.clone()));
+            }
+        }
+        self.visited.remove(&Rc::as_ptr(root));
+        Item {
+            types,
+            id,
+            properties,
+        }
+    }
+
+    fn property_value(&self, element: &Rc<Node>) -> PropertyValue {
+        let url = |attribute: &str| {
+            PropertyValue::Url(
+                element
+                    .get_attribute(attribute)
+                    .map(|value| self.document.absolute_url(&value))
+                    .unwrap_or_default(),
+            )
+        };
+        let attribute = |attribute: &str| {
+            PropertyValue::Text(element.get_attribute(attribute).unwrap_or_default())
+        };
+        match element.element_name().unwrap_or_default() {
+            "meta" => attribute("content"),
+            "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => url("src"),
+            "a" | "area" | "link" => url("href"),
+            "object" => url("data"),
+            "data" | "meter" => attribute("value"),
+            "time" if element.has_attribute("datetime") => attribute("datetime"),
+            _ => PropertyValue::Text(element.get_text_content()),
+        }
+    }
+}
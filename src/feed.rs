@@ -0,0 +1,200 @@
+use anyhow::{Context, Result, bail};
+use roxmltree::{Node, ParsingOptions};
+use std::io::Read;
+use std::string::String;
+use std::vec::Vec;
+use url::Url;
+
+use crate::dom::Document;
+use crate::net::{self, mime_essence};
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    Rss,
+    Atom,
+}
+
+impl FeedKind {
+    pub fn of(content_type: &str) -> Option<FeedKind> {
+        match mime_essence(content_type)?.as_str() {
+            "application/rss+xml" | "application/rdf+xml" => Some(FeedKind::Rss),
+            "application/atom+xml" => Some(FeedKind::Atom),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeedKind::Rss => "RSS",
+            FeedKind::Atom => "Atom",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedLink {
+    pub url: Url,
+    pub title: Option<String>,
+    pub kind: FeedKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub id: Option<String>,
+    pub author: Option<String>,
+    pub published: Option<String>,
+    pub updated: Option<String>,
+    pub summary: Option<String>,
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    pub kind: FeedKind,
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub description: Option<String>,
+    pub updated: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Document {
+    pub fn feed_links(&self) -> Vec<FeedLink> {
+        self.get_elements_by_tag_name("link")
+            .iter()
+            .filter(|link| {
+                link.get_attribute("rel").is_some_and(|rel| {
+                    rel.split_ascii_whitespace()
+                        .any(|token| token.eq_ignore_ascii_case("alternate"))
+                })
+            })
+            .filter_map(|link| {
+                Some(FeedLink {
+                    kind: FeedKind::of(&link.get_attribute("type")?)?,
+                    url: self.resolve_url(&link.get_attribute("href")?)?,
+                    title: link
+                        .get_attribute("title")
+                        .map(|title| title.trim().to_string())
+                        .filter(|title| !title.is_empty()),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Feed {
+    pub fn fetch(url: &Url, settings: &Settings) -> Result<Feed> {
+        let mut response = net::fetch(url, settings)?;
+        if response.status >= 400 {
+            bail!("{} answered with status {}", url, response.status);
+        }
+        let mut text = String::new();
+        response
+            .body
+            .read_to_string(&mut text)
+            .with_context(|| format!("reading {}", url))?;
+        Feed::parse(&text, Some(&response.url))
+    }
+
+    pub fn parse(text: &str, base: Option<&Url>) -> Result<Feed> {
+        let options = ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        };
+        let xml = roxmltree::Document::parse_with_options(text, options)
+            .context("feed is not well-formed XML")?;
+        let root = xml.root_element();
+        let resolve = |link: String| match base.and_then(|base| base.join(&link).ok()) {
+            Some(url) => url.to_string(),
+            None => link,
+        };
+        match root.tag_name().name() {
+            "rss" => {
+                let channel = child(root, "channel").context("RSS feed has no channel")?;
+                Ok(rss_feed(channel, channel, &resolve))
+            }
+            "RDF" => {
+                let channel = child(root, "channel").context("RSS feed has no channel")?;
+                Ok(rss_feed(channel, root, &resolve))
+            }
+            "feed" => Ok(atom_feed(root, &resolve)),
+            name => bail!("<{}> is not an RSS or Atom feed", name),
+        }
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn text(node: Node, name: &str) -> Option<String> {
+    let text: String = child(node, name)?
+        .descendants()
+        .filter(|node| node.is_text())
+        .filter_map(|node| node.text())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn rss_feed(channel: Node, items: Node, resolve: &dyn Fn(String) -> String) -> Feed {
+    let entries = items
+        .children()
+        .filter(|node| node.is_element() && node.tag_name().name() == "item")
+        .map(|item| FeedEntry {
+            title: text(item, "title"),
+            link: text(item, "link").map(resolve),
+            id: text(item, "guid"),
+            author: text(item, "author").or_else(|| text(item, "creator")),
+            published: text(item, "pubDate").or_else(|| text(item, "date")),
+            updated: None,
+            summary: text(item, "description"),
+            content: text(item, "encoded"),
+        })
+        .collect();
+    Feed {
+        kind: FeedKind::Rss,
+        title: text(channel, "title"),
+        link: text(channel, "link").map(resolve),
+        description: text(channel, "description"),
+        updated: text(channel, "lastBuildDate").or_else(|| text(channel, "date")),
+        entries,
+    }
+}
+
+fn atom_link(node: Node) -> Option<String> {
+    node.children()
+        .filter(|child| child.is_element() && child.tag_name().name() == "link")
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|link| link.attribute("href"))
+        .map(str::to_string)
+}
+
+fn atom_feed(feed: Node, resolve: &dyn Fn(String) -> String) -> Feed {
+    let entries = feed
+        .children()
+        .filter(|node| node.is_element() && node.tag_name().name() == "entry")
+        .map(|entry| FeedEntry {
+            title: text(entry, "title"),
+            link: atom_link(entry).map(resolve),
+            id: text(entry, "id"),
+            author: child(entry, "author").and_then(|author| text(author, "name")),
+            published: text(entry, "published"),
+            updated: text(entry, "updated"),
+            summary: text(entry, "summary"),
+            content: text(entry, "content"),
+        })
+        .collect();
+    Feed {
+        kind: FeedKind::Atom,
+        title: text(feed, "title"),
+        link: atom_link(feed).map(resolve),
+        description: text(feed, "subtitle"),
+        updated: text(feed, "updated"),
+        entries,
+    }
+}
@@ -0,0 +1,165 @@
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData};
+use crate::range::{BoundaryPoint, Range};
+use crate::style::{Display, style_of};
+
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "template", "noscript"];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FindOptions {
+    pub case_sensitive: bool,
+}
+
+struct TextRun {
+    chars: Vec<char>,
+    positions: Vec<(Rc<Node>, usize)>,
+}
+
+impl Document {
+    pub fn find(&self, query: &str, options: FindOptions) -> Vec<Range> {
+        let needle: Vec<char> = normalize(query, options);
+        if needle.is_empty() {
+            return Vec::new();
+        }
+
+        self.restyle();
+        let mut runs = vec![TextRun {
+            chars: Vec::new(),
+            positions: Vec::new(),
+        }];
+        collect_text(&self.root, &mut runs);
+
+        let mut matches = Vec::new();
+        for run in &runs {
+            let haystack: Vec<char> = normalize(&run.chars.iter().collect::<String>(), options);
+            let mut i = 0;
+            while i + needle.len() <= haystack.len() {
+                if haystack[i..i + needle.len()] == needle[..] {
+                    let (start_node, start_offset) = &run.positions[i];
+                    let (end_node, end_offset) = &run.positions[i + needle.len() - 1];
+                    matches.push(Range::from_points(
+                        BoundaryPoint::new(start_node, *start_offset),
+                        BoundaryPoint::new(end_node, end_offset + 1),
+                    ));
+                    i += needle.len();
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        matches
+    }
+}
+
+pub struct Finder {
+    pub query: String,
+    pub options: FindOptions,
+    pub matches: Vec<Range>,
+    current: Option<usize>,
+}
+
+impl Finder {
+    pub fn new(document: &Document, query: &str, options: FindOptions) -> Self {
+        Finder {
+            query: query.to_string(),
+            options,
+            matches: document.find(query, options),
+            current: None,
+        }
+    }
+
+    pub fn current(&self) -> Option<(usize, &Range)> {
+        let index = self.current?;
+        Some((index, &self.matches[index]))
+    }
+
+    pub fn next(&mut self, document: &Document) -> Option<&Range> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let index = match self.current {
+            Some(index) => (index + 1) % self.matches.len(),
+            None => 0,
+        };
+        self.select(document, index)
+    }
+
+    pub fn previous(&mut self, document: &Document) -> Option<&Range> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let index = match self.current {
+            Some(0) | None => self.matches.len() - 1,
+            Some(index) => index - 1,
+        };
+        self.select(document, index)
+    }
+
+    fn select(&mut self, document: &Document, index: usize) -> Option<&Range> {
+        self.current = Some(index);
+        let range = &self.matches[index];
+        document.selection.borrow_mut().set_range(range.clone());
+        Some(range)
+    }
+}
+
+fn normalize(text: &str, options: FindOptions) -> Vec<char> {
+    text.chars()
+        .map(|c| {
+            let c = if c.is_whitespace() { ' ' } else { c };
+            if options.case_sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        })
+        .collect()
+}
+
+fn break_run(runs: &mut Vec<TextRun>) {
+    if runs.last().is_some_and(|run| !run.chars.is_empty()) {
+        runs.push(TextRun {
+            chars: Vec::new(),
+            positions: Vec::new(),
+        });
+    }
+}
+
+fn collect_text(node: &Rc<Node>, runs: &mut Vec<TextRun>) {
+    match &node.data {
+        NodeData::Text { contents } => {
+            let Some(run) = runs.last_mut() else {
+                return;
+            };
+            for (offset, c) in contents.borrow().chars().enumerate() {
+                run.chars.push(c);
+                run.positions.push((Rc::clone(node), offset));
+            }
+        }
+        NodeData::Element { .. } => {
+            let name = node.element_name().unwrap_or_default();
+            if SKIPPED_ELEMENTS.contains(&name) {
+                return;
+            }
+            let block = name == "br" || style_of(node).display == Display::Block;
+            if block {
+                break_run(runs);
+            }
+            for child in node.children.borrow().iter() {
+                collect_text(child, runs);
+            }
+            if block {
+                break_run(runs);
+            }
+        }
+        NodeData::Document { .. } => {
+            for child in node.children.borrow().iter() {
+                collect_text(child, runs);
+            }
+        }
+        _ => {}
+    }
+}
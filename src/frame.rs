@@ -0,0 +1,152 @@
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+use url::Url;
+
+use crate::dom::{Document, Node};
+use crate::html::parser::parse_html_with;
+use crate::loader::{self, Load};
+
+const MAX_FRAME_DEPTH: usize = 8;
+
+pub struct Frame {
+    pub document: Rc<Document>,
+    pub element: Option<Rc<Node>>,
+    pub children: Vec<Frame>,
+}
+
+impl Frame {
+    pub fn new(document: Document) -> Self {
+        Frame {
+            document: Rc::new(document),
+            element: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn url(&self) -> Option<&Url> {
+        self.document.url()
+    }
+
+    pub fn load_subframes(&mut self) {
+        self.children = load_subframes(&self.document);
+    }
+
+    pub fn frame_for_element(&self, element: &Rc<Node>) -> Option<&Frame> {
+        for child in &self.children {
+            if child
+                .element
+                .as_ref()
+                .is_some_and(|e| Rc::ptr_eq(e, element))
+            {
+                return Some(child);
+            }
+            if let Some(found) = child.frame_for_element(element) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    pub fn frame_for_node(&self, node: &Rc<Node>) -> Option<&Frame> {
+        let mut root = Rc::clone(node);
+        loop {
+            let parent = root.parent.borrow().upgrade();
+            match parent {
+                Some(parent) => root = parent,
+                None => break,
+            }
+        }
+        self.find(|frame| Rc::ptr_eq(&frame.document.root, &root))
+    }
+
+    fn find<F>(&self, predicate: F) -> Option<&Frame>
+    where
+        F: Fn(&Frame) -> bool + Copy,
+    {
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(predicate))
+    }
+
+    pub fn print_tree(&self) {
+        self.print_frame(0);
+    }
+
+    fn print_frame(&self, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let url = self
+            .url()
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| String::from("about:blank"));
+        println!("{}Frame: {}", indent, url);
+
+        for child in &self.children {
+            child.print_frame(depth + 1);
+        }
+    }
+}
+
+pub fn load_subframes(document: &Document) -> Vec<Frame> {
+    load_children(document, &mut Vec::new())
+}
+
+fn load_children(document: &Document, ancestors: &mut Vec<Url>) -> Vec<Frame> {
+    if ancestors.len() >= MAX_FRAME_DEPTH {
+        return Vec::new();
+    }
+    if let Some(url) = document.url() {
+        ancestors.push(url.clone());
+    }
+
+    let mut children = Vec::new();
+    for iframe in document.get_elements_by_tag_name("iframe") {
+        let child = Rc::new(load_iframe(document, &iframe, ancestors).unwrap_or_default());
+        *iframe.content_document.borrow_mut() = Some(Rc::clone(&child));
+        children.push(Frame {
+            children: load_children(&child, ancestors),
+            document: child,
+            element: Some(iframe),
+        });
+    }
+
+    if document.url().is_some() {
+        ancestors.pop();
+    }
+    children
+}
+
+fn load_iframe(parent: &Document, iframe: &Rc<Node>, ancestors: &[Url]) -> Option<Document> {
+    let settings = &parent.settings;
+    if let Some(srcdoc) = iframe.get_attribute("srcdoc") {
+        let mut document = parse_html_with(&srcdoc, settings.parse_options());
+        document.settings = Arc::clone(settings);
+        document.storage = parent.storage.clone();
+        return Some(document);
+    }
+
+    let src = iframe.get_attribute("src")?;
+    let src = src.trim();
+    if src.is_empty() || src.eq_ignore_ascii_case("about:blank") {
+        return None;
+    }
+
+    let url = parent.resolve_url(src)?;
+    if ancestors.contains(&url) {
+        return None;
+    }
+
+    match loader::load_with(url.as_str(), settings) {
+        Ok(Load::Document(mut document)) => {
+            document.storage = parent.storage.clone();
+            Some(document)
+        }
+        Ok(Load::Download(_)) => None,
+        Err(err) => {
+            log::warn!("failed to load iframe {}: {:#}", url, err);
+            None
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::string::String;
+use std::vec::Vec;
+use url::Url;
+
+pub const MAX_STATE_SIZE: usize = 640 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub scroll_y: f32,
+    pub state: Option<Value>,
+    document: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Traversal {
+    Load,
+    PopState { url: String, state: Option<Value> },
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionHistory {
+    entries: Vec<HistoryEntry>,
+    index: usize,
+    next_document: u64,
+}
+
+impl SessionHistory {
+    pub fn new(url: &str) -> Self {
+        SessionHistory {
+            entries: vec![HistoryEntry {
+                url: url.to_string(),
+                scroll_y: 0.0,
+                state: None,
+                document: 0,
+            }],
+            index: 0,
+            next_document: 1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn current(&self) -> &HistoryEntry {
+        &self.entries[self.index]
+    }
+
+    pub fn current_mut(&mut self) -> &mut HistoryEntry {
+        &mut self.entries[self.index]
+    }
+
+    pub fn state(&self) -> Option<&Value> {
+        self.current().state.as_ref()
+    }
+
+    pub fn can_go(&self, delta: isize) -> bool {
+        self.index
+            .checked_add_signed(delta)
+            .is_some_and(|index| index < self.entries.len())
+    }
+
+    pub fn navigate(&mut self, url: &str) {
+        let document = self.next_document;
+        self.next_document += 1;
+        self.push(HistoryEntry {
+            url: url.to_string(),
+            scroll_y: 0.0,
+            state: None,
+            document,
+        });
+    }
+
+    pub fn navigate_fragment(&mut self, url: &str) {
+        let document = self.current().document;
+        self.push(HistoryEntry {
+            url: url.to_string(),
+            scroll_y: 0.0,
+            state: None,
+            document,
+        });
+    }
+
+    pub fn replace(&mut self, url: &str) {
+        let document = self.next_document;
+        self.next_document += 1;
+        *self.current_mut() = HistoryEntry {
+            url: url.to_string(),
+            scroll_y: 0.0,
+            state: None,
+            document,
+        };
+    }
+
+    pub fn push_state(&mut self, state: Value, url: Option<&str>) -> Result<String> {
+        let url = self.state_url(&state, url)?;
+        let document = self.current().document;
+        self.push(HistoryEntry {
+            url: url.clone(),
+            scroll_y: 0.0,
+            state: Some(state),
+            document,
+        });
+        Ok(url)
+    }
+
+    pub fn replace_state(&mut self, state: Value, url: Option<&str>) -> Result<String> {
+        let url = self.state_url(&state, url)?;
+        let entry = self.current_mut();
+        entry.url = url.clone();
+        entry.state = Some(state);
+        Ok(url)
+    }
+
+    pub fn go(&mut self, delta: isize) -> Option<Traversal> {
+        if delta == 0 || !self.can_go(delta) {
+            return None;
+        }
+        let from = self.current().document;
+        self.index = self.index.checked_add_signed(delta)?;
+        let entry = self.current();
+        if entry.document != from {
+            return Some(Traversal::Load);
+        }
+        Some(Traversal::PopState {
+            url: entry.url.clone(),
+            state: entry.state.clone(),
+        })
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        self.entries.truncate(self.index + 1);
+        self.entries.push(entry);
+        self.index = self.entries.len() - 1;
+    }
+
+    fn state_url(&self, state: &Value, url: Option<&str>) -> Result<String> {
+        let size = serde_json::to_string(state)?.len();
+        if size > MAX_STATE_SIZE {
+            bail!(
+                "DataCloneError: history state is {} bytes, more than {}",
+                size,
+                MAX_STATE_SIZE
+            );
+        }
+        let current = &self.current().url;
+        let Some(url) = url else {
+            return Ok(current.clone());
+        };
+        let base = Url::parse(current)
+            .with_context(|| format!("SecurityError: {} can't be changed", current))?;
+        let target = base
+            .join(url.trim())
+            .with_context(|| format!("SecurityError: invalid URL {}", url))?;
+        let same_origin = target.scheme() == base.scheme()
+            && target.username() == base.username()
+            && target.password() == base.password()
+            && target.host() == base.host()
+            && target.port_or_known_default() == base.port_or_known_default();
+        if !same_origin || target.cannot_be_a_base() != base.cannot_be_a_base() {
+            bail!(
+                "SecurityError: {} can't be used as a history entry for {}",
+                target,
+                current
+            );
+        }
+        Ok(target.to_string())
+    }
+}
@@ -0,0 +1,142 @@
+use html5ever::data::{C1_REPLACEMENTS, NAMED_ENTITIES};
+use std::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CharacterReferences {
+    Keep,
+    #[default]
+    Decode,
+}
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn escape_text(text: &str) -> String {
+    escape_with(text, false, |c| matches!(c, '<' | '>'))
+}
+
+pub fn escape_attribute(value: &str) -> String {
+    escape_with(value, false, |c| matches!(c, '"' | '<' | '>'))
+}
+
+/// Text content for XML output such as SVG. XML has no `&nbsp;` and can't
+/// carry most control characters, so those become a numeric reference and
+/// are dropped respectively.
+pub fn escape_xml_text(text: &str) -> String {
+    escape_with(text, true, |c| matches!(c, '<' | '>'))
+}
+
+fn escape_with(text: &str, xml: bool, special: impl Fn(char) -> bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' if xml => out.push_str("&#160;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c if xml && c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            '"' if special(c) => out.push_str("&quot;"),
+            '<' if special(c) => out.push_str("&lt;"),
+            '>' if special(c) => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+pub fn unescape(text: &str) -> String {
+    decode(text, true)
+}
+
+pub fn unescape_numeric(text: &str) -> String {
+    decode(text, false)
+}
+
+fn decode(text: &str, named: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = match rest[1..].strip_prefix('#') {
+            Some(number) => numeric_reference(number).map(|(c, len)| (c.to_string(), len + 2)),
+            None if named => named_reference(&rest[1..]).map(|(value, len)| (value, len + 1)),
+            None => None,
+        };
+        match decoded {
+            Some((value, len)) => {
+                out.push_str(&value);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn numeric_reference(text: &str) -> Option<(char, usize)> {
+    let (radix, prefix) = match text.as_bytes().first() {
+        Some(b'x' | b'X') => (16, 1),
+        _ => (10, 0),
+    };
+    let digits = text[prefix..]
+        .bytes()
+        .take_while(|b| (*b as char).is_digit(radix))
+        .count();
+    if digits == 0 {
+        return None;
+    }
+    let mut len = prefix + digits;
+    if text[len..].starts_with(';') {
+        len += 1;
+    }
+    let code = u32::from_str_radix(&text[prefix..prefix + digits], radix).unwrap_or(u32::MAX);
+    let c = match code {
+        0x80..=0x9f => C1_REPLACEMENTS[(code - 0x80) as usize]
+            .unwrap_or_else(|| char::from_u32(code).unwrap_or('\u{fffd}')),
+        0 => '\u{fffd}',
+        code => char::from_u32(code).unwrap_or('\u{fffd}'),
+    };
+    Some((c, len))
+}
+
+fn named_reference(text: &str) -> Option<(String, usize)> {
+    let name_len = text
+        .bytes()
+        .take_while(|b| b.is_ascii_alphanumeric())
+        .count();
+    let with_semicolon = text[name_len..].starts_with(';');
+    let candidates = (1..=name_len).rev().map(|len| {
+        let semicolon = with_semicolon && len == name_len;
+        (len, semicolon)
+    });
+    for (len, semicolon) in candidates {
+        let key = &text[..len + semicolon as usize];
+        if let Some(&(first, second)) = NAMED_ENTITIES.get(key)
+            && first != 0
+        {
+            let value = [first, second]
+                .into_iter()
+                .filter(|code| *code != 0)
+                .filter_map(char::from_u32)
+                .collect();
+            return Some((value, key.len()));
+        }
+    }
+    None
+}
@@ -0,0 +1,314 @@
+use html5ever::{local_name, ns};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::escape::{escape_attribute, escape_text};
+use super::serialize::{RAW_TEXT_ELEMENTS, VOID_ELEMENTS, outer_html};
+use crate::dom::{Attribute, Document, Node, NodeData, QualName};
+
+const PREFORMATTED_ELEMENTS: &[&str] = &["listing", "pre", "textarea"];
+
+const WHITESPACE_INSENSITIVE: &[&str] = &[
+    "colgroup", "datalist", "dl", "head", "html", "ol", "optgroup", "select", "table", "tbody",
+    "tfoot", "thead", "tr", "ul",
+];
+
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "acronym", "audio", "b", "bdi", "bdo", "big", "br", "button", "canvas", "cite",
+    "code", "data", "del", "dfn", "em", "embed", "font", "i", "iframe", "img", "input", "ins",
+    "kbd", "label", "mark", "meter", "object", "output", "picture", "progress", "q", "ruby", "s",
+    "samp", "select", "small", "span", "strike", "strong", "sub", "sup", "svg", "textarea", "time",
+    "tt", "u", "var", "video", "wbr",
+];
+
+const UNQUOTED_FORBIDDEN: &[char] = &[' ', '\t', '\n', '\x0c', '\r', '"', '\'', '=', '<', '>', '`'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SerializeMode {
+    #[default]
+    Exact,
+    Minified,
+    Pretty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub mode: SerializeMode,
+    pub indent: usize,
+    pub comments: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        SerializeOptions {
+            mode: SerializeMode::Exact,
+            indent: 2,
+            comments: true,
+        }
+    }
+}
+
+impl SerializeOptions {
+    pub fn minified() -> Self {
+        SerializeOptions {
+            mode: SerializeMode::Minified,
+            comments: false,
+            ..SerializeOptions::default()
+        }
+    }
+
+    pub fn pretty() -> Self {
+        SerializeOptions {
+            mode: SerializeMode::Pretty,
+            ..SerializeOptions::default()
+        }
+    }
+}
+
+impl Document {
+    pub fn serialize_with(&self, options: SerializeOptions) -> String {
+        serialize_with(&self.root, options)
+    }
+}
+
+pub fn serialize_with(node: &Rc<Node>, options: SerializeOptions) -> String {
+    let mut formatter = Formatter {
+        options,
+        out: String::new(),
+    };
+    match options.mode {
+        SerializeMode::Exact => return outer_html(node),
+        SerializeMode::Minified => formatter.inline(node, false, true),
+        SerializeMode::Pretty => formatter.block(node, 0),
+    }
+    formatter.out
+}
+
+struct Formatter {
+    options: SerializeOptions,
+    out: String,
+}
+
+impl Formatter {
+    fn inline(&mut self, node: &Rc<Node>, preserve: bool, unquoted: bool) {
+        match &node.data {
+            NodeData::Document { .. }
+            | NodeData::DocumentFragment
+            | NodeData::ShadowRoot { .. } => {
+                for child in node.children.borrow().iter() {
+                    self.inline(child, preserve, unquoted);
+                }
+            }
+            NodeData::Doctype { name, .. } => {
+                self.out.push_str("<!DOCTYPE ");
+                self.out.push_str(name);
+                self.out.push('>');
+            }
+            NodeData::Comment { contents } => {
+                if self.options.comments {
+                    self.out.push_str("<!--");
+                    self.out.push_str(&contents.borrow());
+                    self.out.push_str("-->");
+                }
+            }
+            NodeData::Text { contents } => {
+                let contents = contents.borrow();
+                let parent = node.parent.borrow().upgrade();
+                let parent_name = parent.as_ref().and_then(|parent| html_name(parent));
+                if parent_name.is_some_and(|name| RAW_TEXT_ELEMENTS.contains(&name)) {
+                    self.out.push_str(&contents);
+                } else if preserve {
+                    self.out.push_str(&escape_text(&contents));
+                } else if !contents.trim().is_empty() || !droppable(node) {
+                    self.out.push_str(&escape_text(&collapse(&contents)));
+                }
+            }
+            NodeData::Element { name, attrs } => {
+                self.start_tag(name, &attrs.borrow(), unquoted);
+                let local = html_name(node);
+                if local.is_some_and(|local| VOID_ELEMENTS.contains(&local)) {
+                    return;
+                }
+                let preformatted =
+                    local.is_some_and(|local| PREFORMATTED_ELEMENTS.contains(&local));
+                if preformatted
+                    && let Some(first) = node.children.borrow().first()
+                    && let NodeData::Text { contents } = &first.data
+                    && contents.borrow().starts_with('\n')
+                {
+                    self.out.push('\n');
+                }
+                let preserve = preserve || preformatted;
+                for child in &children(node) {
+                    self.inline(child, preserve, unquoted);
+                }
+                self.out.push_str("</");
+                self.out.push_str(&name.local);
+                self.out.push('>');
+            }
+        }
+    }
+
+    fn block(&mut self, node: &Rc<Node>, depth: usize) {
+        let indent = " ".repeat(depth * self.options.indent);
+        match &node.data {
+            NodeData::Document { .. }
+            | NodeData::DocumentFragment
+            | NodeData::ShadowRoot { .. } => {
+                for child in node.children.borrow().iter() {
+                    self.block(child, depth);
+                }
+            }
+            NodeData::Text { contents } if contents.borrow().trim().is_empty() => {}
+            NodeData::Comment { .. } if !self.options.comments => {}
+            NodeData::Element { name, attrs } if !has_inline_content(node) => {
+                self.out.push_str(&indent);
+                self.start_tag(name, &attrs.borrow(), false);
+                if html_name(node).is_some_and(|local| VOID_ELEMENTS.contains(&local)) {
+                    self.out.push('\n');
+                    return;
+                }
+                let children = children(node);
+                let empty = children.iter().all(|child| match &child.data {
+                    NodeData::Text { contents } => contents.borrow().trim().is_empty(),
+                    NodeData::Comment { .. } => !self.options.comments,
+                    _ => false,
+                });
+                if !empty {
+                    self.out.push('\n');
+                    for child in &children {
+                        self.block(child, depth + 1);
+                    }
+                    self.out.push_str(&indent);
+                }
+                self.out.push_str("</");
+                self.out.push_str(&name.local);
+                self.out.push_str(">\n");
+            }
+            _ => {
+                self.out.push_str(&indent);
+                let start = self.out.len();
+                self.inline(node, false, false);
+                let trimmed = self.out[start..].trim().len();
+                let leading = self.out[start..].len() - self.out[start..].trim_start().len();
+                self.out.drain(start..start + leading);
+                self.out.truncate(start + trimmed);
+                self.out.push('\n');
+            }
+        }
+    }
+
+    fn start_tag(&mut self, name: &QualName, attrs: &[Attribute], unquoted: bool) {
+        self.out.push('<');
+        self.out.push_str(&name.local);
+        for attr in attrs {
+            self.out.push(' ');
+            match attr.name.ns {
+                ns!(xml) => self.out.push_str("xml:"),
+                ns!(xmlns) if attr.name.local != local_name!("xmlns") => {
+                    self.out.push_str("xmlns:")
+                }
+                ns!(xlink) => self.out.push_str("xlink:"),
+                _ => {
+                    if let Some(prefix) = &attr.name.prefix {
+                        self.out.push_str(prefix);
+                        self.out.push(':');
+                    }
+                }
+            }
+            self.out.push_str(&attr.name.local);
+            let value = escape_attribute(&attr.value);
+            if unquoted && value.is_empty() {
+                continue;
+            }
+            self.out.push('=');
+            if unquoted && !value.contains(UNQUOTED_FORBIDDEN) {
+                self.out.push_str(&value);
+            } else {
+                self.out.push('"');
+                self.out.push_str(&value);
+                self.out.push('"');
+            }
+        }
+        self.out.push('>');
+    }
+}
+
+fn children(node: &Rc<Node>) -> Vec<Rc<Node>> {
+    let node = node.template_content().unwrap_or_else(|| Rc::clone(node));
+    node.children.borrow().clone()
+}
+
+fn html_name(node: &Node) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } if name.ns == ns!(html) => Some(&name.local),
+        _ => None,
+    }
+}
+
+fn is_inline(node: &Node) -> bool {
+    match &node.data {
+        NodeData::Text { .. } => true,
+        NodeData::Element { name, .. } => {
+            name.ns == ns!(html) && INLINE_ELEMENTS.contains(&&*name.local)
+        }
+        _ => false,
+    }
+}
+
+fn has_inline_content(node: &Rc<Node>) -> bool {
+    if html_name(node).is_some_and(|local| {
+        PREFORMATTED_ELEMENTS.contains(&local) || RAW_TEXT_ELEMENTS.contains(&local)
+    }) {
+        return true;
+    }
+    children(node).iter().any(|child| match &child.data {
+        NodeData::Text { contents } => !contents.borrow().trim().is_empty(),
+        _ => is_inline(child),
+    })
+}
+
+fn droppable(text: &Rc<Node>) -> bool {
+    let Some(parent) = text.parent.borrow().upgrade() else {
+        return true;
+    };
+    match &parent.data {
+        NodeData::Document { .. } | NodeData::DocumentFragment | NodeData::ShadowRoot { .. } => {
+            return true;
+        }
+        NodeData::Element { .. } => {}
+        _ => return false,
+    }
+    if html_name(&parent).is_some_and(|name| WHITESPACE_INSENSITIVE.contains(&name)) {
+        return true;
+    }
+    if is_inline(&parent) {
+        return false;
+    }
+    let blockish = |sibling: Option<Rc<Node>>| {
+        sibling.is_none_or(|sibling| match &sibling.data {
+            NodeData::Comment { .. } => true,
+            NodeData::Element { .. } => !is_inline(&sibling),
+            _ => false,
+        })
+    };
+    blockish(Node::previous_sibling(text)) && blockish(Node::next_sibling(text))
+}
+
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !space {
+                out.push(' ');
+            }
+            space = true;
+        } else {
+            out.push(c);
+            space = false;
+        }
+    }
+    out
+}
@@ -1 +1,7 @@
+pub mod escape;
+pub mod format;
 pub mod parser;
+pub mod serialize;
+pub mod visitor;
+
+pub use escape::{escape, unescape};
@@ -1,17 +1,69 @@
-use html5ever::tendril::TendrilSink;
+use html5ever::driver::Parser;
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::tree_builder::TreeBuilderOpts;
 use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
 use html5ever::{Attribute as Html5Attribute, ExpandedName, QualName as Html5QualName};
 use html5ever::{ParseOpts, parse_document};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::string::String;
 use std::vec::Vec;
 
-use crate::dom::{Attribute, Document, Node, NodeData, QualName};
+use crate::dom::{Attribute, Document, Node, NodeData, NodeId, QualName, ShadowRootMode};
+use crate::layout;
+use crate::style::invalidation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterAction {
+    Keep,
+    Skip,
+    Descend,
+}
+
+type FilterFn = dyn FnMut(&Rc<Node>, &Rc<Node>) -> FilterAction;
+
+pub struct ParseFilter(Box<FilterFn>);
+
+impl ParseFilter {
+    pub fn new(filter: impl FnMut(&Rc<Node>, &Rc<Node>) -> FilterAction + 'static) -> Self {
+        ParseFilter(Box::new(filter))
+    }
+
+    pub fn skip(tags: &[&str]) -> Self {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_ascii_lowercase()).collect();
+        ParseFilter::new(move |element, _| match element.element_name() {
+            Some(name) if tags.iter().any(|tag| tag == name) => FilterAction::Skip,
+            _ => FilterAction::Keep,
+        })
+    }
+
+    pub fn only(tags: &[&str]) -> Self {
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_ascii_lowercase()).collect();
+        ParseFilter::new(move |element, parent| {
+            if !matches!(parent.data, NodeData::Document { .. }) {
+                return FilterAction::Keep;
+            }
+            match element.element_name() {
+                Some(name) if tags.iter().any(|tag| tag == name) => FilterAction::Keep,
+                _ => FilterAction::Descend,
+            }
+        })
+    }
+}
+
+enum Target {
+    Kept(Rc<Node>),
+    Descended(Rc<Node>),
+    Skipped,
+}
 
 pub struct DomSink {
     document: RefCell<Document>,
     quirks_mode: RefCell<QuirksMode>,
+    template_contents: RefCell<Vec<(Rc<Node>, Rc<Node>)>>,
+    filter: RefCell<Option<ParseFilter>>,
+    dropped: RefCell<HashMap<NodeId, Option<Rc<Node>>>>,
 }
 
 impl DomSink {
@@ -19,19 +71,100 @@ impl DomSink {
         DomSink {
             document: RefCell::new(Document::new()),
             quirks_mode: RefCell::new(QuirksMode::NoQuirks),
+            template_contents: RefCell::new(Vec::new()),
+            filter: RefCell::new(None),
+            dropped: RefCell::new(HashMap::new()),
         }
     }
 
-    fn convert_qualname(name: &Html5QualName) -> QualName {
-        QualName {
-            prefix: name.prefix.as_ref().map(|p| p.to_string()),
-            ns: name.ns.to_string(),
-            local: name.local.to_string(),
-            ns_atom: name.ns.clone(),
-            local_atom: name.local.clone(),
+    pub fn with_filter(filter: ParseFilter) -> Self {
+        let sink = DomSink::new();
+        *sink.filter.borrow_mut() = Some(filter);
+        sink
+    }
+
+    fn target(&self, parent: &Rc<Node>) -> Target {
+        match self.dropped.borrow().get(&parent.id) {
+            None => Target::Kept(Rc::clone(parent)),
+            Some(Some(ancestor)) => Target::Descended(Rc::clone(ancestor)),
+            Some(None) => Target::Skipped,
         }
     }
 
+    fn admit(&self, parent: &Rc<Node>, child: &NodeOrText<Handle>) -> Option<Rc<Node>> {
+        let mut filter = self.filter.borrow_mut();
+        let Some(filter) = filter.as_mut() else {
+            return Some(Rc::clone(parent));
+        };
+        let element = match child {
+            NodeOrText::AppendNode(node) if matches!(node.0.data, NodeData::Element { .. }) => {
+                Some(&node.0)
+            }
+            _ => None,
+        };
+        if let Some(element) = element
+            && self.dropped.borrow().contains_key(&element.id)
+        {
+            return None;
+        }
+        let (target, descended) = match self.target(parent) {
+            Target::Kept(target) => (target, false),
+            Target::Descended(target) => (target, true),
+            Target::Skipped => {
+                if let Some(element) = element {
+                    self.dropped.borrow_mut().insert(element.id, None);
+                }
+                return None;
+            }
+        };
+        let Some(element) = element else {
+            return (!descended).then_some(target);
+        };
+        if element.parent.borrow().upgrade().is_some() {
+            return Some(target);
+        }
+        match (filter.0)(element, &target) {
+            FilterAction::Keep => Some(target),
+            FilterAction::Skip => {
+                self.dropped.borrow_mut().insert(element.id, None);
+                None
+            }
+            FilterAction::Descend => {
+                self.dropped.borrow_mut().insert(element.id, Some(target));
+                None
+            }
+        }
+    }
+
+    fn attach(&self, parent: &Rc<Node>, child: NodeOrText<Handle>) {
+        match child {
+            NodeOrText::AppendNode(node) => {
+                Node::append_child(parent, Rc::clone(&node.0));
+            }
+            NodeOrText::AppendText(text) => {
+                let children = parent.children.borrow();
+                if let Some(last) = children.last()
+                    && let NodeData::Text { contents } = &last.data
+                {
+                    contents.borrow_mut().push_str(&text);
+                    invalidation::text_changed(last);
+                    layout::mark_dirty(last);
+                    return;
+                }
+                drop(children);
+
+                let text_node = Node::new(NodeData::Text {
+                    contents: RefCell::new(text.to_string()),
+                });
+                Node::append_child(parent, text_node);
+            }
+        }
+    }
+
+    fn convert_qualname(name: &Html5QualName) -> QualName {
+        QualName::new(name.prefix.clone(), name.ns.clone(), name.local.clone())
+    }
+
     fn convert_attrs(attrs: &[Html5Attribute]) -> Vec<Attribute> {
         attrs
             .iter()
@@ -43,6 +176,12 @@ impl DomSink {
     }
 }
 
+impl Default for DomSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone)]
 pub struct Handle(Rc<Node>);
 
@@ -65,8 +204,8 @@ impl TreeSink for DomSink {
     fn elem_name<'a>(&'a self, target: &'a Self::Handle) -> Self::ElemName<'a> {
         match &target.0.data {
             NodeData::Element { name, .. } => ExpandedName {
-                ns: &name.ns_atom,
-                local: &name.local_atom,
+                ns: &name.ns,
+                local: &name.local,
             },
             _ => panic!("elem_name called on non-element"),
         }
@@ -76,17 +215,21 @@ impl TreeSink for DomSink {
         &self,
         name: Html5QualName,
         attrs: Vec<Html5Attribute>,
-        _flags: ElementFlags,
+        flags: ElementFlags,
     ) -> Self::Handle {
-        Handle(Node::new(NodeData::Element {
+        let element = Node::new(NodeData::Element {
             name: Self::convert_qualname(&name),
-            attrs: Self::convert_attrs(&attrs),
-        }))
+            attrs: RefCell::new(Self::convert_attrs(&attrs)),
+        });
+        if flags.template {
+            *element.template_contents.borrow_mut() = Some(Node::new_fragment());
+        }
+        Handle(element)
     }
 
     fn create_comment(&self, text: html5ever::tendril::StrTendril) -> Self::Handle {
         Handle(Node::new(NodeData::Comment {
-            contents: text.to_string(),
+            contents: RefCell::new(text.to_string()),
         }))
     }
 
@@ -96,49 +239,27 @@ impl TreeSink for DomSink {
         _data: html5ever::tendril::StrTendril,
     ) -> Self::Handle {
         Handle(Node::new(NodeData::Comment {
-            contents: String::new(),
+            contents: RefCell::new(String::new()),
         }))
     }
 
     fn append(&self, parent: &Self::Handle, child: NodeOrText<Self::Handle>) {
-        match child {
-            NodeOrText::AppendNode(node) => {
-                Node::append_child(&parent.0, Rc::clone(&node.0));
-            }
-            NodeOrText::AppendText(text) => {
-                let mut children = parent.0.children.borrow_mut();
-                if let Some(last) = children.last() {
-                    if let NodeData::Text { contents } = &last.data {
-                        let mut new_contents = contents.clone();
-                        new_contents.push_str(&text);
-
-                        children.pop();
-                        drop(children);
-
-                        let new_text_node = Node::new(NodeData::Text {
-                            contents: new_contents,
-                        });
-                        Node::append_child(&parent.0, new_text_node);
-                        return;
-                    }
-                }
-                drop(children);
-
-                let text_node = Node::new(NodeData::Text {
-                    contents: text.to_string(),
-                });
-                Node::append_child(&parent.0, text_node);
-            }
+        if let Some(target) = self.admit(&parent.0, &child) {
+            self.attach(&target, child);
         }
     }
 
     fn append_based_on_parent_node(
         &self,
         element: &Self::Handle,
-        _prev_element: &Self::Handle,
+        prev_element: &Self::Handle,
         child: NodeOrText<Self::Handle>,
     ) {
-        self.append(element, child);
+        if element.0.parent.borrow().upgrade().is_some() {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
     }
 
     fn append_doctype_to_document(
@@ -157,7 +278,40 @@ impl TreeSink for DomSink {
     }
 
     fn get_template_contents(&self, target: &Self::Handle) -> Self::Handle {
-        target.clone()
+        self.template_contents
+            .borrow()
+            .iter()
+            .find(|(template, _)| Rc::ptr_eq(template, &target.0))
+            .map(|(_, contents)| Rc::clone(contents))
+            .or_else(|| target.0.template_content())
+            .map(Handle)
+            .unwrap_or_else(|| target.clone())
+    }
+
+    fn attach_declarative_shadow(
+        &self,
+        location: &Self::Handle,
+        template: &Self::Handle,
+        attrs: &[Html5Attribute],
+    ) -> bool {
+        let mode = attrs
+            .iter()
+            .find(|attr| &*attr.name.local == "shadowrootmode")
+            .map(|attr| match &*attr.value {
+                "closed" => ShadowRootMode::Closed,
+                _ => ShadowRootMode::Open,
+            })
+            .unwrap_or(ShadowRootMode::Open);
+
+        match Node::attach_shadow(&location.0, mode) {
+            Some(shadow_root) => {
+                self.template_contents
+                    .borrow_mut()
+                    .push((Rc::clone(&template.0), shadow_root));
+                true
+            }
+            None => false,
+        }
     }
 
     fn same_node(&self, x: &Self::Handle, y: &Self::Handle) -> bool {
@@ -169,55 +323,167 @@ impl TreeSink for DomSink {
     }
 
     fn append_before_sibling(&self, sibling: &Self::Handle, new_node: NodeOrText<Self::Handle>) {
-        let parent = sibling
-            .0
-            .parent
-            .borrow()
-            .upgrade()
-            .expect("append_before_sibling: no parent");
+        let parent = sibling.0.parent.borrow().upgrade();
+        let Some(parent) = parent else {
+            assert!(
+                self.filter.borrow().is_some(),
+                "append_before_sibling: no parent"
+            );
+            if let NodeOrText::AppendNode(node) = &new_node {
+                self.dropped.borrow_mut().insert(node.0.id, None);
+            }
+            return;
+        };
+        if self.admit(&parent, &new_node).is_none() {
+            return;
+        }
 
         match new_node {
             NodeOrText::AppendNode(node) => {
                 Node::insert_before(&parent, Rc::clone(&node.0), &sibling.0);
             }
             NodeOrText::AppendText(text) => {
+                let children = parent.children.borrow();
+                let index = children
+                    .iter()
+                    .position(|child| Rc::ptr_eq(child, &sibling.0))
+                    .expect("append_before_sibling: sibling not in parent");
+                if let Some(previous) = index.checked_sub(1).map(|i| &children[i])
+                    && let NodeData::Text { contents } = &previous.data
+                {
+                    contents.borrow_mut().push_str(&text);
+                    invalidation::text_changed(previous);
+                    layout::mark_dirty(previous);
+                    return;
+                }
+                drop(children);
+
                 let text_node = Node::new(NodeData::Text {
-                    contents: text.to_string(),
+                    contents: RefCell::new(text.to_string()),
                 });
                 Node::insert_before(&parent, text_node, &sibling.0);
             }
         }
     }
 
-    fn add_attrs_if_missing(&self, _target: &Self::Handle, _attrs: Vec<Html5Attribute>) {}
+    fn add_attrs_if_missing(&self, target: &Self::Handle, attrs: Vec<Html5Attribute>) {
+        let NodeData::Element {
+            attrs: existing, ..
+        } = &target.0.data
+        else {
+            return;
+        };
 
-    fn remove_from_parent(&self, target: &Self::Handle) {
-        if let Some(parent) = target.0.parent.borrow().upgrade() {
-            Node::remove_child(&parent, &target.0);
+        let mut existing = existing.borrow_mut();
+        for attr in Self::convert_attrs(&attrs) {
+            if !existing.iter().any(|e| e.name == attr.name) {
+                existing.push(attr);
+            }
         }
     }
 
+    fn remove_from_parent(&self, target: &Self::Handle) {
+        Node::detach(&target.0);
+    }
+
     fn reparent_children(&self, node: &Self::Handle, new_parent: &Self::Handle) {
-        let children: Vec<_> = node
-            .0
-            .children
-            .borrow()
-            .iter()
-            .map(|c| Rc::clone(c))
-            .collect();
+        let children: Vec<_> = node.0.children.borrow().iter().map(Rc::clone).collect();
 
         node.0.children.borrow_mut().clear();
 
-        for child in children {
-            Node::append_child(&new_parent.0, child);
+        match self.target(&new_parent.0) {
+            Target::Kept(target) | Target::Descended(target) => {
+                for child in children {
+                    Node::append_child(&target, child);
+                }
+            }
+            Target::Skipped => {
+                let mut dropped = self.dropped.borrow_mut();
+                for child in children {
+                    dropped.insert(child.id, None);
+                }
+            }
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub scripting: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { scripting: true }
+    }
+}
+
 pub fn parse_html(html: &str) -> Document {
-    let sink = DomSink::new();
-    parse_document(sink, ParseOpts::default())
-        .from_utf8()
-        .read_from(&mut html.as_bytes())
-        .unwrap()
+    parse_html_with(html, ParseOptions::default())
+}
+
+pub fn parse_html_with_filter(html: &str, filter: ParseFilter) -> Document {
+    let mut parser = StreamingParser::with_filter(ParseOptions::default(), filter);
+    parser.feed(html);
+    parser.finish()
+}
+
+pub fn parse_html_with(html: &str, options: ParseOptions) -> Document {
+    let span =
+        tracing::info_span!("parse", bytes = html.len(), nodes = tracing::field::Empty).entered();
+    let mut parser = StreamingParser::new(options);
+    parser.feed(html);
+    let document = parser.finish();
+
+    if !span.is_disabled() {
+        let mut nodes = 0;
+        document.root.walk(&mut |_| nodes += 1);
+        span.record("nodes", nodes);
+    }
+    document
+}
+
+pub struct StreamingParser {
+    parser: Parser<DomSink>,
+    scripting: bool,
+}
+
+impl StreamingParser {
+    pub fn new(options: ParseOptions) -> Self {
+        StreamingParser::with_sink(options, DomSink::new())
+    }
+
+    pub fn with_filter(options: ParseOptions, filter: ParseFilter) -> Self {
+        StreamingParser::with_sink(options, DomSink::with_filter(filter))
+    }
+
+    fn with_sink(options: ParseOptions, sink: DomSink) -> Self {
+        let opts = ParseOpts {
+            tree_builder: TreeBuilderOpts {
+                scripting_enabled: options.scripting,
+                ..TreeBuilderOpts::default()
+            },
+            ..ParseOpts::default()
+        };
+        StreamingParser {
+            parser: parse_document(sink, opts),
+            scripting: options.scripting,
+        }
+    }
+
+    pub fn root(&self) -> Rc<Node> {
+        Rc::clone(&self.parser.tokenizer.sink.sink.document.borrow().root)
+    }
+
+    pub fn feed(&mut self, html: &str) {
+        if !html.is_empty() {
+            self.parser.process(StrTendril::from_slice(html));
+        }
+    }
+
+    pub fn finish(self) -> Document {
+        let mut document = self.parser.finish();
+        document.scripting = self.scripting;
+        document
+    }
 }
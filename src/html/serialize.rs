@@ -0,0 +1,217 @@
+use html5ever::serialize::{AttrRef, Serializer, TraversalScope};
+use html5ever::{local_name, ns};
+use std::io;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::escape::{escape_attribute, escape_text};
+use crate::dom::{Node, NodeData, QualName};
+
+pub(crate) const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img", "input",
+    "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+pub(crate) const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe",
+    "noembed",
+    "noframes",
+    "noscript",
+    "plaintext",
+    "script",
+    "style",
+    "xmp",
+];
+
+struct HtmlWriter {
+    out: String,
+    stack: Vec<(Option<html5ever::LocalName>, bool)>,
+}
+
+struct SerializableNode<'a>(&'a Rc<Node>);
+
+fn markup_name(name: &QualName) -> html5ever::QualName {
+    html5ever::QualName::new(name.prefix.clone(), name.ns.clone(), name.local.clone())
+}
+
+fn serialize_children<S: Serializer>(node: &Rc<Node>, serializer: &mut S) -> io::Result<()> {
+    let node = node.template_content().unwrap_or_else(|| Rc::clone(node));
+    let children: Vec<_> = node.children.borrow().iter().map(Rc::clone).collect();
+    for child in &children {
+        html5ever::serialize::Serialize::serialize(
+            &SerializableNode(child),
+            serializer,
+            TraversalScope::IncludeNode,
+        )?;
+    }
+    Ok(())
+}
+
+impl html5ever::serialize::Serialize for SerializableNode<'_> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: &mut S,
+        traversal_scope: TraversalScope,
+    ) -> io::Result<()> {
+        let node = self.0;
+        if let TraversalScope::ChildrenOnly(_) = traversal_scope {
+            return serialize_children(node, serializer);
+        }
+
+        match &node.data {
+            NodeData::Document { .. }
+            | NodeData::DocumentFragment
+            | NodeData::ShadowRoot { .. } => serialize_children(node, serializer),
+            NodeData::Element { name, attrs } => {
+                let name = markup_name(name);
+                let attrs = attrs.borrow().clone();
+                let names: Vec<_> = attrs.iter().map(|attr| markup_name(&attr.name)).collect();
+                serializer.start_elem(
+                    name.clone(),
+                    names
+                        .iter()
+                        .zip(&attrs)
+                        .map(|(name, attr)| (name, attr.value.as_str())),
+                )?;
+                serialize_children(node, serializer)?;
+                serializer.end_elem(name)
+            }
+            NodeData::Text { contents } => serializer.write_text(&contents.borrow()),
+            NodeData::Comment { contents } => serializer.write_comment(&contents.borrow()),
+            NodeData::Doctype { name, .. } => serializer.write_doctype(name),
+        }
+    }
+}
+
+impl HtmlWriter {
+    fn new(parent: Option<&html5ever::QualName>) -> Self {
+        let parent = parent.filter(|name| name.ns == ns!(html));
+        HtmlWriter {
+            out: String::new(),
+            stack: vec![(parent.map(|name| name.local.clone()), false)],
+        }
+    }
+
+    fn ignoring_children(&self) -> bool {
+        self.stack.last().is_some_and(|(_, ignore)| *ignore)
+    }
+}
+
+impl Serializer for HtmlWriter {
+    fn start_elem<'a, AttrIter>(
+        &mut self,
+        name: html5ever::QualName,
+        attrs: AttrIter,
+    ) -> io::Result<()>
+    where
+        AttrIter: Iterator<Item = AttrRef<'a>>,
+    {
+        let html_name = (name.ns == ns!(html)).then(|| name.local.clone());
+        if self.ignoring_children() {
+            self.stack.push((html_name, true));
+            return Ok(());
+        }
+
+        self.out.push('<');
+        self.out.push_str(&name.local);
+        for (name, value) in attrs {
+            self.out.push(' ');
+            match name.ns {
+                ns!(xml) => self.out.push_str("xml:"),
+                ns!(xmlns) if name.local != local_name!("xmlns") => self.out.push_str("xmlns:"),
+                ns!(xlink) => self.out.push_str("xlink:"),
+                _ => {
+                    if let Some(prefix) = &name.prefix {
+                        self.out.push_str(prefix);
+                        self.out.push(':');
+                    }
+                }
+            }
+            self.out.push_str(&name.local);
+            self.out.push_str("=\"");
+            self.out.push_str(&escape_attribute(value));
+            self.out.push('"');
+        }
+        self.out.push('>');
+
+        let void = html_name
+            .as_ref()
+            .is_some_and(|local| VOID_ELEMENTS.contains(&&**local));
+        self.stack.push((html_name, void));
+        Ok(())
+    }
+
+    fn end_elem(&mut self, name: html5ever::QualName) -> io::Result<()> {
+        if let Some((_, true)) = self.stack.pop() {
+            return Ok(());
+        }
+        self.out.push_str("</");
+        self.out.push_str(&name.local);
+        self.out.push('>');
+        Ok(())
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        let raw = self
+            .stack
+            .last()
+            .and_then(|(name, _)| name.as_ref())
+            .is_some_and(|local| RAW_TEXT_ELEMENTS.contains(&&**local));
+        if raw {
+            self.out.push_str(text);
+        } else {
+            self.out.push_str(&escape_text(text));
+        }
+        Ok(())
+    }
+
+    fn write_comment(&mut self, text: &str) -> io::Result<()> {
+        self.out.push_str("<!--");
+        self.out.push_str(text);
+        self.out.push_str("-->");
+        Ok(())
+    }
+
+    fn write_doctype(&mut self, name: &str) -> io::Result<()> {
+        self.out.push_str("<!DOCTYPE ");
+        self.out.push_str(name);
+        self.out.push('>');
+        Ok(())
+    }
+
+    fn write_processing_instruction(&mut self, target: &str, data: &str) -> io::Result<()> {
+        self.out.push_str("<?");
+        self.out.push_str(target);
+        self.out.push(' ');
+        self.out.push_str(data);
+        self.out.push('>');
+        Ok(())
+    }
+}
+
+fn to_string(node: &Rc<Node>, traversal_scope: TraversalScope) -> String {
+    let mut writer = HtmlWriter::new(match &traversal_scope {
+        TraversalScope::ChildrenOnly(parent) => parent.as_ref(),
+        TraversalScope::IncludeNode => None,
+    });
+    html5ever::serialize::Serialize::serialize(
+        &SerializableNode(node),
+        &mut writer,
+        traversal_scope,
+    )
+    .expect("writing to a String cannot fail");
+    writer.out
+}
+
+pub fn outer_html(node: &Rc<Node>) -> String {
+    to_string(node, TraversalScope::IncludeNode)
+}
+
+pub fn inner_html(node: &Rc<Node>) -> String {
+    let parent = match &node.data {
+        NodeData::Element { name, .. } => Some(markup_name(name)),
+        _ => None,
+    };
+    to_string(node, TraversalScope::ChildrenOnly(parent))
+}
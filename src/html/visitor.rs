@@ -0,0 +1,362 @@
+use html5ever::driver::Parser;
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeBuilderOpts, TreeSink};
+use html5ever::{Attribute as Html5Attribute, ExpandedName, QualName as Html5QualName};
+use html5ever::{ParseOpts, local_name, ns, parse_document};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::parser::ParseOptions;
+use super::serialize::VOID_ELEMENTS;
+use crate::dom::{Attribute, QualName};
+
+pub struct StartTag {
+    pub name: QualName,
+    pub attributes: Vec<Attribute>,
+    pub depth: usize,
+}
+
+impl StartTag {
+    pub fn local_name(&self) -> &str {
+        &self.name.local
+    }
+
+    pub fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| &*attribute.name.local == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+}
+
+pub struct EndTag {
+    pub name: QualName,
+    pub depth: usize,
+}
+
+impl EndTag {
+    pub fn local_name(&self) -> &str {
+        &self.name.local
+    }
+}
+
+type Callback<'a, T> = Option<Box<dyn FnMut(&T) + 'a>>;
+
+#[derive(Default)]
+struct Callbacks<'a> {
+    start_tag: Callback<'a, StartTag>,
+    end_tag: Callback<'a, EndTag>,
+    text: Callback<'a, str>,
+    comment: Callback<'a, str>,
+    doctype: Callback<'a, str>,
+}
+
+pub struct ParseVisitor<'a> {
+    callbacks: Callbacks<'a>,
+    options: ParseOptions,
+}
+
+impl<'a> ParseVisitor<'a> {
+    pub fn new() -> Self {
+        ParseVisitor {
+            callbacks: Callbacks::default(),
+            options: ParseOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn on_start_tag(mut self, callback: impl FnMut(&StartTag) + 'a) -> Self {
+        self.callbacks.start_tag = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_end_tag(mut self, callback: impl FnMut(&EndTag) + 'a) -> Self {
+        self.callbacks.end_tag = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_text(mut self, callback: impl FnMut(&str) + 'a) -> Self {
+        self.callbacks.text = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_comment(mut self, callback: impl FnMut(&str) + 'a) -> Self {
+        self.callbacks.comment = Some(Box::new(callback));
+        self
+    }
+
+    pub fn on_doctype(mut self, callback: impl FnMut(&str) + 'a) -> Self {
+        self.callbacks.doctype = Some(Box::new(callback));
+        self
+    }
+
+    pub fn parse(self, html: &str) {
+        let mut parser = self.start();
+        parser.feed(html);
+        parser.finish();
+    }
+
+    pub fn start(self) -> VisitingParser<'a> {
+        let opts = ParseOpts {
+            tree_builder: TreeBuilderOpts {
+                scripting_enabled: self.options.scripting,
+                ..TreeBuilderOpts::default()
+            },
+            ..ParseOpts::default()
+        };
+        let sink = EventSink {
+            callbacks: RefCell::new(self.callbacks),
+            document: Handle::new(Kind::Document),
+            open: RefCell::new(Vec::new()),
+            text: RefCell::new(String::new()),
+        };
+        VisitingParser {
+            parser: parse_document(sink, opts),
+        }
+    }
+}
+
+impl Default for ParseVisitor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct VisitingParser<'a> {
+    parser: Parser<EventSink<'a>>,
+}
+
+impl VisitingParser<'_> {
+    pub fn feed(&mut self, html: &str) {
+        if !html.is_empty() {
+            self.parser.process(StrTendril::from_slice(html));
+        }
+    }
+
+    pub fn finish(self) {
+        self.parser.finish()
+    }
+}
+
+enum Kind {
+    Document,
+    Element(Vec<Attribute>),
+    Comment(String),
+    Other,
+}
+
+#[derive(Clone)]
+struct Handle(Rc<SinkNode>);
+
+struct SinkNode {
+    name: Html5QualName,
+    kind: Kind,
+    started: Cell<bool>,
+}
+
+impl Handle {
+    fn new(kind: Kind) -> Self {
+        Handle::named(Html5QualName::new(None, ns!(), local_name!("")), kind)
+    }
+
+    fn named(name: Html5QualName, kind: Kind) -> Self {
+        Handle(Rc::new(SinkNode {
+            name,
+            kind,
+            started: Cell::new(false),
+        }))
+    }
+}
+
+struct EventSink<'a> {
+    callbacks: RefCell<Callbacks<'a>>,
+    document: Handle,
+    open: RefCell<Vec<Handle>>,
+    text: RefCell<String>,
+}
+
+fn convert_qualname(name: &Html5QualName) -> QualName {
+    QualName::new(name.prefix.clone(), name.ns.clone(), name.local.clone())
+}
+
+impl EventSink<'_> {
+    fn flush_text(&self) {
+        let text = std::mem::take(&mut *self.text.borrow_mut());
+        if text.is_empty() {
+            return;
+        }
+        if let Some(callback) = self.callbacks.borrow_mut().text.as_mut() {
+            callback(&text);
+        }
+    }
+
+    fn close_until(&self, target: &Handle, inclusive: bool) {
+        let position = self
+            .open
+            .borrow()
+            .iter()
+            .rposition(|open| Rc::ptr_eq(&open.0, &target.0));
+        let Some(position) = position else {
+            return;
+        };
+        let keep = if inclusive { position } else { position + 1 };
+        while self.open.borrow().len() > keep {
+            self.flush_text();
+            let Some(node) = self.open.borrow_mut().pop() else {
+                break;
+            };
+            let depth = self.open.borrow().len();
+            if let Some(callback) = self.callbacks.borrow_mut().end_tag.as_mut() {
+                callback(&EndTag {
+                    name: convert_qualname(&node.0.name),
+                    depth,
+                });
+            }
+        }
+    }
+
+    fn insert(&self, parent: Option<&Handle>, child: NodeOrText<Handle>) {
+        if let Some(parent) = parent {
+            self.close_until(parent, false);
+        }
+        match child {
+            NodeOrText::AppendText(text) => self.text.borrow_mut().push_str(&text),
+            NodeOrText::AppendNode(node) => {
+                if node.0.started.replace(true) {
+                    return;
+                }
+                self.flush_text();
+                match &node.0.kind {
+                    Kind::Element(attributes) => {
+                        let depth = self.open.borrow().len();
+                        if let Some(callback) = self.callbacks.borrow_mut().start_tag.as_mut() {
+                            callback(&StartTag {
+                                name: convert_qualname(&node.0.name),
+                                attributes: attributes.clone(),
+                                depth,
+                            });
+                        }
+                        let void = node.0.name.ns == ns!(html)
+                            && VOID_ELEMENTS.contains(&&*node.0.name.local);
+                        self.open.borrow_mut().push(node.clone());
+                        if void {
+                            self.close_until(&node, true);
+                        }
+                    }
+                    Kind::Comment(text) => {
+                        if let Some(callback) = self.callbacks.borrow_mut().comment.as_mut() {
+                            callback(text);
+                        }
+                    }
+                    Kind::Document | Kind::Other => {}
+                }
+            }
+        }
+    }
+}
+
+impl TreeSink for EventSink<'_> {
+    type Handle = Handle;
+    type Output = ();
+    type ElemName<'b>
+        = ExpandedName<'b>
+    where
+        Self: 'b;
+
+    fn finish(self) -> Self::Output {
+        self.close_until(&self.document, false);
+        while let Some(node) = self.open.borrow().first().cloned() {
+            self.close_until(&node, true);
+        }
+        self.flush_text();
+    }
+
+    fn parse_error(&self, _msg: Cow<'static, str>) {}
+
+    fn get_document(&self) -> Self::Handle {
+        self.document.clone()
+    }
+
+    fn elem_name<'b>(&'b self, target: &'b Self::Handle) -> Self::ElemName<'b> {
+        target.0.name.expanded()
+    }
+
+    fn create_element(
+        &self,
+        name: Html5QualName,
+        attrs: Vec<Html5Attribute>,
+        _flags: ElementFlags,
+    ) -> Self::Handle {
+        let attributes = attrs
+            .iter()
+            .map(|attr| Attribute {
+                name: convert_qualname(&attr.name),
+                value: attr.value.to_string(),
+            })
+            .collect();
+        Handle::named(name, Kind::Element(attributes))
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Self::Handle {
+        Handle::new(Kind::Comment(text.to_string()))
+    }
+
+    fn create_pi(&self, _target: StrTendril, _data: StrTendril) -> Self::Handle {
+        Handle::new(Kind::Other)
+    }
+
+    fn append(&self, parent: &Self::Handle, child: NodeOrText<Self::Handle>) {
+        self.insert(Some(parent), child);
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Self::Handle,
+        _prev_element: &Self::Handle,
+        child: NodeOrText<Self::Handle>,
+    ) {
+        self.insert(Some(element), child);
+    }
+
+    fn append_doctype_to_document(
+        &self,
+        name: StrTendril,
+        _public_id: StrTendril,
+        _system_id: StrTendril,
+    ) {
+        if let Some(callback) = self.callbacks.borrow_mut().doctype.as_mut() {
+            callback(&name);
+        }
+    }
+
+    fn pop(&self, node: &Self::Handle) {
+        self.close_until(node, true);
+    }
+
+    fn get_template_contents(&self, target: &Self::Handle) -> Self::Handle {
+        target.clone()
+    }
+
+    fn same_node(&self, x: &Self::Handle, y: &Self::Handle) -> bool {
+        Rc::ptr_eq(&x.0, &y.0)
+    }
+
+    fn set_quirks_mode(&self, _mode: QuirksMode) {}
+
+    fn append_before_sibling(&self, _sibling: &Self::Handle, new_node: NodeOrText<Self::Handle>) {
+        self.insert(None, new_node);
+    }
+
+    fn add_attrs_if_missing(&self, _target: &Self::Handle, _attrs: Vec<Html5Attribute>) {}
+
+    fn remove_from_parent(&self, _target: &Self::Handle) {}
+
+    fn reparent_children(&self, _node: &Self::Handle, _new_parent: &Self::Handle) {}
+}
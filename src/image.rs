@@ -0,0 +1,316 @@
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::vec::Vec;
+use url::Url;
+
+use crate::css::values::Color;
+use crate::dom::{Document, Node, NodeId};
+use crate::layout::inline::FragmentKind;
+use crate::layout::{self, LayoutBox, LayoutTree, Rect};
+use crate::net;
+use crate::settings::Settings;
+use crate::style::Display;
+
+pub const LAZY_LOAD_MARGIN: f32 = 1250.0;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Loading {
+    #[default]
+    Eager,
+    Lazy,
+}
+
+impl Loading {
+    pub fn of(node: &Node, default: Loading) -> Loading {
+        match node.get_attribute("loading") {
+            Some(value) if value.trim().eq_ignore_ascii_case("lazy") => Loading::Lazy,
+            Some(value) if value.trim().eq_ignore_ascii_case("eager") => Loading::Eager,
+            _ => default,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Image({}x{})", self.width, self.height)
+    }
+}
+
+pub fn can_decode(mime: &str) -> bool {
+    matches!(
+        mime,
+        "image/png" | "image/x-icon" | "image/vnd.microsoft.icon"
+    )
+}
+
+impl Image {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(PNG_SIGNATURE) {
+            Image::decode_png(bytes)
+        } else if bytes.starts_with(&[0, 0, 1, 0]) {
+            Image::decode_ico(bytes)
+        } else {
+            anyhow::bail!("unsupported image format")
+        }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| [pixel.r, pixel.g, pixel.b, pixel.a])
+            .collect();
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(bytes)
+    }
+
+    fn decode_png(bytes: &[u8]) -> Result<Self> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut data = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut data)?;
+        let data = &data[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => data
+                .chunks_exact(4)
+                .map(|p| Color::rgba(p[0], p[1], p[2], p[3]))
+                .collect(),
+            png::ColorType::Rgb => data
+                .chunks_exact(3)
+                .map(|p| Color::rgb(p[0], p[1], p[2]))
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .map(|p| Color::rgba(p[0], p[0], p[0], p[1]))
+                .collect(),
+            png::ColorType::Grayscale => data.iter().map(|&v| Color::rgb(v, v, v)).collect(),
+            other => anyhow::bail!("unsupported PNG color type {:?}", other),
+        };
+        Ok(Image {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    fn decode_ico(bytes: &[u8]) -> Result<Self> {
+        let count = read_u16(bytes, 4)? as usize;
+        let (offset, size) = (0..count)
+            .map(|index| {
+                let entry = 6 + index * 16;
+                let width = *bytes.get(entry).context("truncated ICO directory")?;
+                let size = read_u32(bytes, entry + 8)? as usize;
+                let offset = read_u32(bytes, entry + 12)? as usize;
+                let width = if width == 0 { 256 } else { width as usize };
+                Ok((width, offset, size))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .max_by_key(|(width, _, _)| *width)
+            .map(|(_, offset, size)| (offset, size))
+            .context("ICO file has no images")?;
+        let data = bytes
+            .get(offset..offset.saturating_add(size))
+            .context("truncated ICO image")?;
+        if data.starts_with(PNG_SIGNATURE) {
+            Image::decode_png(data)
+        } else {
+            Image::decode_dib(data)
+        }
+    }
+
+    fn decode_dib(data: &[u8]) -> Result<Self> {
+        let header = read_u32(data, 0)? as usize;
+        let width = read_u32(data, 4)? as i32;
+        let height = read_u32(data, 8)? as i32 / 2;
+        let bits = read_u16(data, 14)?;
+        let compression = read_u32(data, 16)?;
+        if width <= 0 || height <= 0 || compression != 0 || !matches!(bits, 24 | 32) {
+            anyhow::bail!("unsupported ICO bitmap ({} bpp)", bits);
+        }
+        let (width, height) = (width as usize, height as usize);
+        let stride = (width * bits as usize / 8).next_multiple_of(4);
+        let mask_stride = width.div_ceil(32) * 4;
+        let pixels_at = header;
+        let mask_at = pixels_at + stride * height;
+        if data.len() < mask_at {
+            anyhow::bail!("truncated ICO bitmap");
+        }
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = height - 1 - y;
+            for x in 0..width {
+                let p = &data[pixels_at + row * stride + x * bits as usize / 8..];
+                let alpha = if bits == 32 {
+                    p[3]
+                } else {
+                    let mask = data.get(mask_at + row * mask_stride + x / 8).copied();
+                    match mask {
+                        Some(mask) if mask & (0x80 >> (x % 8)) != 0 => 0,
+                        _ => 255,
+                    }
+                };
+                pixels.push(Color::rgba(p[2], p[1], p[0], alpha));
+            }
+        }
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let x = ((u * self.width as f32) as usize).min(self.width.saturating_sub(1));
+        let y = ((v * self.height as f32) as usize).min(self.height.saturating_sub(1));
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16> {
+    let bytes = bytes.get(at..at + 2).context("truncated image header")?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32> {
+    let bytes = bytes.get(at..at + 4).context("truncated image header")?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[derive(Default)]
+pub struct ImageLoader {
+    requested: HashSet<NodeId>,
+    cache: HashMap<Url, Option<Arc<Image>>>,
+}
+
+impl ImageLoader {
+    pub fn new() -> Self {
+        ImageLoader::default()
+    }
+
+    pub fn load_visible(
+        &mut self,
+        document: &Document,
+        tree: &LayoutTree,
+        viewport: Rect,
+    ) -> usize {
+        let span = tracing::info_span!("images", loaded = tracing::field::Empty).entered();
+        let near = Rect::new(
+            viewport.x,
+            viewport.y - LAZY_LOAD_MARGIN,
+            viewport.width,
+            viewport.height + 2.0 * LAZY_LOAD_MARGIN,
+        );
+        let positions = positions(tree);
+
+        let mut loaded = 0;
+        for image in document.get_elements_by_tag_name("img") {
+            if self.requested.contains(&image.id) {
+                continue;
+            }
+            if Loading::of(&image, document.settings.images) == Loading::Lazy {
+                if layout::display_of(&image) == Display::None {
+                    continue;
+                }
+                let Some(rect) = position_of(&image, &positions) else {
+                    continue;
+                };
+                if !rect.intersects(&near) {
+                    continue;
+                }
+            }
+            self.requested.insert(image.id);
+            if let Some(decoded) = self.fetch(document, &image) {
+                *image.image.borrow_mut() = Some(decoded);
+                layout::mark_dirty(&image);
+                loaded += 1;
+            }
+        }
+        span.record("loaded", loaded);
+        loaded
+    }
+
+    fn fetch(&mut self, document: &Document, image: &Rc<Node>) -> Option<Arc<Image>> {
+        let url = document.resolve_url(&image.get_attribute("src")?)?;
+        self.cache
+            .entry(url)
+            .or_insert_with_key(|url| match fetch_image(url, &document.settings) {
+                Ok(image) => Some(Arc::new(image)),
+                Err(err) => {
+                    log::warn!("failed to load image {}: {:#}", url, err);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+pub fn fetch_image(url: &Url, settings: &Settings) -> Result<Image> {
+    let response = net::fetch(url, settings)?;
+    if !response.info().is_success() {
+        anyhow::bail!("HTTP {}", response.status);
+    }
+    if let Some(mime) = response.content_type()
+        && !settings.content_sniffing
+        && !mime.starts_with("image/")
+    {
+        anyhow::bail!("{} is not an image", mime);
+    }
+    Image::decode(&response.read_body()?)
+}
+
+fn positions(tree: &LayoutTree) -> HashMap<NodeId, Rect> {
+    let mut positions = HashMap::new();
+    collect_positions(&tree.root, &mut positions);
+    positions
+}
+
+fn collect_positions(layout_box: &LayoutBox, positions: &mut HashMap<NodeId, Rect>) {
+    if let Some(node) = &layout_box.node {
+        positions.insert(node.id, layout_box.border_box());
+    }
+    for fragment in layout_box.lines.iter().flat_map(|line| &line.fragments) {
+        if matches!(
+            fragment.kind,
+            FragmentKind::ImagePlaceholder { .. } | FragmentKind::Image { .. }
+        ) {
+            positions.insert(fragment.node.id, fragment.rect);
+        }
+    }
+    for child in &layout_box.children {
+        collect_positions(child, positions);
+    }
+}
+
+fn position_of(node: &Rc<Node>, positions: &HashMap<NodeId, Rect>) -> Option<Rect> {
+    let mut current = Some(Rc::clone(node));
+    while let Some(node) = current {
+        if let Some(rect) = positions.get(&node.id) {
+            return Some(*rect);
+        }
+        current = node.parent.borrow().upgrade();
+    }
+    None
+}
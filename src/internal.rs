@@ -0,0 +1,400 @@
+use anyhow::{Context, Result, bail};
+use std::fmt::Write;
+use std::io::{Cursor, Read};
+use std::string::String;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+use std::vec::Vec;
+use url::Url;
+
+use crate::accessibility::collapse_whitespace;
+use crate::css::media::{ColorScheme, ReducedMotion};
+use crate::feed::Feed;
+use crate::html::escape;
+use crate::html::parser::parse_html;
+use crate::net::error::LoadError;
+use crate::net::{Response, Timing};
+use crate::settings::{DEFAULT_USER_AGENT, Settings};
+
+pub const SCHEME: &str = "icarus";
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const MAX_VISITS: usize = 500;
+const MAX_SUMMARY_CHARS: usize = 400;
+const STYLE: &str = "body { font-family: sans-serif; margin: 24px; color: #202124 }\n\
+    h1 { font-size: 22px }\n\
+    th { text-align: left; padding-right: 24px }\n\
+    td { padding-right: 24px }\n\
+    .message { color: #5f6368 }\n\
+    .retry { margin-top: 24px }\n";
+
+#[derive(Debug, Clone)]
+pub struct Visit {
+    pub url: Url,
+    pub title: Option<String>,
+    pub visited_at: SystemTime,
+}
+
+static HISTORY: Mutex<Vec<Visit>> = Mutex::new(Vec::new());
+
+pub fn record_visit(url: &Url, title: Option<String>) {
+    if is_internal(url) {
+        return;
+    }
+    let mut history = HISTORY.lock().unwrap_or_else(|err| err.into_inner());
+    if history.len() >= MAX_VISITS {
+        history.remove(0);
+    }
+    history.push(Visit {
+        url: url.clone(),
+        title,
+        visited_at: SystemTime::now(),
+    });
+}
+
+pub fn visits() -> Vec<Visit> {
+    HISTORY
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+pub fn is_internal(url: &Url) -> bool {
+    matches!(url.scheme(), SCHEME | "about")
+}
+
+pub fn error_url(url: &str, kind: LoadError, message: &str) -> Url {
+    let mut error = Url::parse("icarus://error").expect("valid internal URL");
+    error
+        .query_pairs_mut()
+        .append_pair("url", url)
+        .append_pair("kind", &kind.code())
+        .append_pair("message", message);
+    error
+}
+
+pub fn fetch(url: &Url, settings: &Settings) -> Result<Response> {
+    let fetch_start = Instant::now();
+    let html = page(url, settings)?;
+    Ok(Response {
+        url: url.clone(),
+        status: 200,
+        headers: vec![
+            (
+                "Content-Type".to_string(),
+                "text/html; charset=utf-8".to_string(),
+            ),
+            ("Content-Length".to_string(), html.len().to_string()),
+        ],
+        timing: Timing::started(fetch_start),
+        body: Box::new(Cursor::new(html.into_bytes())),
+    })
+}
+
+pub fn page(url: &Url, settings: &Settings) -> Result<String> {
+    let name = match url.scheme() {
+        "about" => url.path(),
+        _ => url.host_str().unwrap_or_default(),
+    };
+    let param = |key: &str| {
+        url.query_pairs()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.into_owned())
+            .unwrap_or_default()
+    };
+    match name {
+        "blank" => Ok(String::new()),
+        "version" => Ok(version_page()),
+        "history" => Ok(history_page()),
+        "config" => Ok(config_page(settings)),
+        "feed" => Ok(fetch_feed_page(&param("url"), settings)),
+        "error" => Ok(error_page(
+            &param("url"),
+            LoadError::from_code(&param("kind")),
+            &param("message"),
+        )),
+        _ => bail!("unknown internal page: {}", url),
+    }
+}
+
+pub fn feed_url(url: &Url) -> Url {
+    let mut feed = Url::parse("icarus://feed").expect("valid internal URL");
+    feed.query_pairs_mut().append_pair("url", url.as_str());
+    feed
+}
+
+pub fn feed_response(mut response: Response) -> Result<Response> {
+    let mut text = String::new();
+    response
+        .body
+        .read_to_string(&mut text)
+        .with_context(|| format!("reading {}", response.url))?;
+    let feed = Feed::parse(&text, Some(&response.url))?;
+    let html = feed_page(response.url.as_str(), &feed);
+    response.headers.retain(|(name, _)| {
+        !name.eq_ignore_ascii_case("Content-Type") && !name.eq_ignore_ascii_case("Content-Length")
+    });
+    response.headers.push((
+        "Content-Type".to_string(),
+        "text/html; charset=utf-8".to_string(),
+    ));
+    response
+        .headers
+        .push(("Content-Length".to_string(), html.len().to_string()));
+    response.body = Box::new(Cursor::new(html.into_bytes()));
+    Ok(response)
+}
+
+fn document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        escape(title),
+        body
+    )
+}
+
+fn version_page() -> String {
+    let mut body = String::from("<table>\n");
+    let rows = [
+        ("Version", VERSION),
+        ("User agent", DEFAULT_USER_AGENT),
+        ("Target", std::env::consts::ARCH),
+        ("Platform", std::env::consts::OS),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(
+            body,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(name),
+            escape(value)
+        );
+    }
+    body.push_str("</table>\n");
+    document(&format!("Icarus {}", VERSION), &body)
+}
+
+fn history_page() -> String {
+    let visits = visits();
+    if visits.is_empty() {
+        return document(
+            "History",
+            "<p class=\"message\">No pages visited yet.</p>\n",
+        );
+    }
+    let now = SystemTime::now();
+    let mut body = String::from("<ul>\n");
+    for visit in visits.iter().rev() {
+        let ago = now
+            .duration_since(visit.visited_at)
+            .unwrap_or_default()
+            .as_secs();
+        let url = escape(visit.url.as_str());
+        let title = visit.title.as_deref().map(escape).unwrap_or(url.clone());
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\">{}</a> <span class=\"message\">{} &middot; {}</span></li>",
+            url,
+            title,
+            url,
+            elapsed(ago)
+        );
+    }
+    body.push_str("</ul>\n");
+    document("History", &body)
+}
+
+fn elapsed(seconds: u64) -> String {
+    match seconds {
+        0..60 => String::from("just now"),
+        60..3600 => format!("{} min ago", seconds / 60),
+        _ => format!("{} h ago", seconds / 3600),
+    }
+}
+
+fn config_page(settings: &Settings) -> String {
+    let palette = settings.palette();
+    let mut body = String::from("<table>\n");
+    let rows = [
+        (
+            "Config file",
+            settings
+                .config
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| String::from("(defaults)")),
+        ),
+        ("User agent", settings.user_agent.clone()),
+        ("JavaScript", on_off(settings.javascript)),
+        (
+            "Images",
+            format!("{:?}", settings.images).to_ascii_lowercase(),
+        ),
+        ("Content sniffing", on_off(settings.content_sniffing)),
+        (
+            "Font",
+            settings
+                .fonts
+                .regular
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| String::from("(system)")),
+        ),
+        ("Font size", format!("{}px", settings.fonts.size)),
+        (
+            "Color scheme",
+            match settings.color_scheme {
+                ColorScheme::System => {
+                    format!("system ({})", settings.color_scheme.resolve().name())
+                }
+                scheme => String::from(scheme.name()),
+            },
+        ),
+        (
+            "Reduced motion",
+            match settings.reduced_motion {
+                ReducedMotion::System => {
+                    format!("system ({})", settings.reduced_motion.resolve().name())
+                }
+                motion => String::from(motion.name()),
+            },
+        ),
+        ("Text color", palette.text.to_hex()),
+        ("Background color", palette.background.to_hex()),
+        (
+            "Link color",
+            palette
+                .link
+                .map(|color| color.to_hex())
+                .unwrap_or_else(|| String::from("(inherit)")),
+        ),
+        (
+            "Proxy",
+            settings
+                .proxy
+                .as_ref()
+                .map(|proxy| proxy.to_string())
+                .unwrap_or_else(|| String::from("(none)")),
+        ),
+        (
+            "Working directory",
+            std::env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default(),
+        ),
+        ("Schemes", String::from("http, file, about, icarus")),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(
+            body,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            escape(name),
+            escape(&value)
+        );
+    }
+    body.push_str("</table>\n");
+    document("Configuration", &body)
+}
+
+fn on_off(enabled: bool) -> String {
+    String::from(if enabled { "on" } else { "off" })
+}
+
+fn fetch_feed_page(url: &str, settings: &Settings) -> String {
+    let feed = Url::parse(url)
+        .with_context(|| format!("invalid feed URL: {}", url))
+        .and_then(|url| Feed::fetch(&url, settings));
+    match feed {
+        Ok(feed) => feed_page(url, &feed),
+        Err(err) => document(
+            "Feed unavailable",
+            &format!(
+                "<p class=\"message\">{}</p>\n",
+                escape(&format!("{:#}", err))
+            ),
+        ),
+    }
+}
+
+pub fn feed_page(url: &str, feed: &Feed) -> String {
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p class=\"message\">{} feed &middot; <a href=\"{}\">{}</a></p>",
+        feed.kind.name(),
+        escape(url),
+        escape(url)
+    );
+    if let Some(description) = &feed.description {
+        let _ = writeln!(body, "<p>{}</p>", escape(&plain_text(description)));
+    }
+    if feed.entries.is_empty() {
+        body.push_str("<p class=\"message\">This feed has no entries.</p>\n");
+    }
+    for entry in &feed.entries {
+        let title = entry.title.as_deref().map(plain_text);
+        let title = escape(title.as_deref().unwrap_or("(untitled)"));
+        match &entry.link {
+            Some(link) => {
+                let _ = writeln!(body, "<h2><a href=\"{}\">{}</a></h2>", escape(link), title);
+            }
+            None => {
+                let _ = writeln!(body, "<h2>{}</h2>", title);
+            }
+        }
+        let byline: Vec<&str> = [
+            entry.published.as_deref().or(entry.updated.as_deref()),
+            entry.author.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !byline.is_empty() {
+            let _ = writeln!(
+                body,
+                "<p class=\"message\">{}</p>",
+                escape(&byline.join(" \u{b7} "))
+            );
+        }
+        if let Some(summary) = entry.summary.as_deref().or(entry.content.as_deref()) {
+            let _ = writeln!(body, "<p>{}</p>", escape(&truncate(&plain_text(summary))));
+        }
+    }
+    let title = feed.title.as_deref().map(plain_text);
+    document(title.as_deref().unwrap_or("Feed"), &body)
+}
+
+fn plain_text(markup: &str) -> String {
+    if !markup.contains('<') && !markup.contains('&') {
+        return collapse_whitespace(markup);
+    }
+    collapse_whitespace(&parse_html(markup).root.get_text_content())
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MAX_SUMMARY_CHARS) {
+        Some((end, _)) => format!("{}\u{2026}", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+fn error_page(url: &str, kind: LoadError, message: &str) -> String {
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p>Icarus couldn't load <a href=\"{}\">{}</a>.</p>",
+        escape(url),
+        escape(url)
+    );
+    let _ = writeln!(body, "<p>{}</p>", escape(kind.advice()));
+    for line in message.lines().filter(|line| !line.trim().is_empty()) {
+        let _ = writeln!(body, "<p class=\"message\">{}</p>", escape(line));
+    }
+    let _ = writeln!(
+        body,
+        "<p class=\"retry\"><a href=\"{}\">Try again</a> <span class=\"message\">or press F5</span></p>",
+        escape(url)
+    );
+    document(&kind.title(), &body)
+}
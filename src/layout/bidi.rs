@@ -0,0 +1,314 @@
+use std::string::String;
+use std::vec::Vec;
+
+use crate::style::Direction;
+
+const ARABIC_FORMS_START: u32 = 0xfe80;
+
+const ARABIC_FORM_COUNTS: [u8; 42] = [
+    1, 2, 2, 2, 2, 4, 2, 4, 2, 4, 4, 4, 4, 4, 2, 2, 2, 2, 4, 4, 4, 4, 4, 4, 4, 4, 0, 0, 0, 0, 0, 0,
+    4, 4, 4, 4, 4, 4, 4, 2, 2, 4,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidiClass {
+    L,
+    R,
+    AL,
+    EN,
+    ES,
+    ET,
+    AN,
+    CS,
+    NSM,
+    B,
+    S,
+    WS,
+    ON,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joining {
+    Dual,
+    Right,
+    Causing,
+    Transparent,
+    None,
+}
+
+pub fn bidi_class(c: char) -> BidiClass {
+    use BidiClass::*;
+    match c as u32 {
+        0x0a | 0x0d | 0x1c..=0x1e | 0x85 | 0x2029 => B,
+        0x09 | 0x0b | 0x1f => S,
+        0x0c | 0x20 | 0x1680 | 0x2000..=0x200a | 0x2028 | 0x205f | 0x3000 => WS,
+        0x30..=0x39 | 0xb2 | 0xb3 | 0xb9 | 0x6f0..=0x6f9 | 0x2070 | 0x2074..=0x2079 => EN,
+        0x2080..=0x2089 | 0xff10..=0xff19 => EN,
+        0x2b | 0x2d | 0x207a | 0x207b | 0x208a | 0x208b | 0x2212 | 0xfe62 | 0xfe63 => ES,
+        0xff0b | 0xff0d => ES,
+        0x23..=0x25 | 0xa2..=0xa5 | 0xb0 | 0xb1 | 0x609 | 0x60a | 0x66a | 0x2030..=0x2034 => ET,
+        0x20a0..=0x20cf | 0x2213 => ET,
+        0x2c | 0x2e | 0x2f | 0x3a | 0xa0 | 0x60c | 0x202f | 0x2044 | 0xff0c | 0xff0e | 0xff1a => CS,
+        0x600..=0x605 | 0x660..=0x669 | 0x66b | 0x66c | 0x6dd => AN,
+        0x300..=0x36f | 0x483..=0x489 | 0x591..=0x5bd | 0x5bf | 0x5c1 | 0x5c2 | 0x5c4 | 0x5c5 => {
+            NSM
+        }
+        0x5c7 | 0x610..=0x61a | 0x64b..=0x65f | 0x670 | 0x6d6..=0x6dc | 0x6df..=0x6e4 => NSM,
+        0x6e7 | 0x6e8 | 0x6ea..=0x6ed | 0x200b..=0x200d | 0x20d0..=0x20ff | 0xfe00..=0xfe0f => NSM,
+        0x200e => L,
+        0x200f | 0x590..=0x5ff | 0x7c0..=0x85f | 0xfb1d..=0xfb4f | 0x10800..=0x10fff => R,
+        0x61c | 0x600..=0x7bf | 0x860..=0x8ff | 0xfb50..=0xfdff | 0xfe70..=0xfeff => AL,
+        _ if c.is_alphabetic() => L,
+        _ => ON,
+    }
+}
+
+pub fn is_rtl(c: char) -> bool {
+    matches!(bidi_class(c), BidiClass::R | BidiClass::AL | BidiClass::AN)
+}
+
+pub fn first_strong(text: &str) -> Option<Direction> {
+    text.chars().find_map(|c| match bidi_class(c) {
+        BidiClass::L => Some(Direction::Ltr),
+        BidiClass::R | BidiClass::AL => Some(Direction::Rtl),
+        _ => None,
+    })
+}
+
+pub fn resolve_levels(text: &str, base: u8) -> Vec<u8> {
+    use BidiClass::*;
+    let original: Vec<BidiClass> = text.chars().map(bidi_class).collect();
+    let mut classes = original.clone();
+    let n = classes.len();
+    let sos = if base % 2 == 1 { R } else { L };
+
+    let mut previous = sos;
+    for class in &mut classes {
+        if *class == NSM {
+            *class = previous;
+        }
+        previous = *class;
+    }
+
+    let mut strong = sos;
+    for class in &mut classes {
+        match *class {
+            L | R | AL => strong = *class,
+            EN if strong == AL => *class = AN,
+            _ => {}
+        }
+        if *class == AL {
+            *class = R;
+        }
+    }
+
+    for i in 1..n.saturating_sub(1) {
+        let (before, after) = (classes[i - 1], classes[i + 1]);
+        match classes[i] {
+            ES if before == EN && after == EN => classes[i] = EN,
+            CS if before == after && matches!(before, EN | AN) => classes[i] = before,
+            _ => {}
+        }
+    }
+
+    for i in 0..n {
+        if classes[i] != EN {
+            continue;
+        }
+        let mut j = i;
+        while j > 0 && classes[j - 1] == ET {
+            j -= 1;
+            classes[j] = EN;
+        }
+        let mut j = i + 1;
+        while j < n && classes[j] == ET {
+            classes[j] = EN;
+            j += 1;
+        }
+    }
+
+    let mut strong = sos;
+    for class in &mut classes {
+        match *class {
+            ES | ET | CS => *class = ON,
+            L | R => strong = *class,
+            EN if strong == L => *class = L,
+            _ => {}
+        }
+    }
+
+    let direction = |class: BidiClass| if class == L { L } else { R };
+    let mut i = 0;
+    while i < n {
+        if !matches!(classes[i], B | S | WS | ON) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < n && matches!(classes[i], B | S | WS | ON) {
+            i += 1;
+        }
+        let before = if start == 0 {
+            sos
+        } else {
+            direction(classes[start - 1])
+        };
+        let after = if i == n { sos } else { direction(classes[i]) };
+        let resolved = if before == after { before } else { sos };
+        classes[start..i].fill(resolved);
+    }
+
+    let mut levels: Vec<u8> = classes
+        .iter()
+        .map(|class| match (base % 2, class) {
+            (0, R) => base + 1,
+            (0, AN | EN) => base + 2,
+            (0, _) => base,
+            (_, L | EN | AN) => base + 1,
+            _ => base,
+        })
+        .collect();
+
+    let mut trailing = true;
+    for i in (0..n).rev() {
+        match original[i] {
+            B | S => {
+                levels[i] = base;
+                trailing = true;
+            }
+            WS if trailing => levels[i] = base,
+            _ => trailing = false,
+        }
+    }
+    levels
+}
+
+pub fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let Some(&highest) = levels.iter().max() else {
+        return order;
+    };
+    let lowest_odd = levels
+        .iter()
+        .copied()
+        .filter(|level| level % 2 == 1)
+        .min()
+        .unwrap_or(highest + 1);
+    for level in (lowest_odd..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < order.len() && levels[order[i]] >= level {
+                i += 1;
+            }
+            order[start..i].reverse();
+        }
+    }
+    order
+}
+
+pub fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        '≤' => '≥',
+        '≥' => '≤',
+        c => c,
+    }
+}
+
+fn arabic_forms(c: char) -> Option<(u32, u8)> {
+    let index = (c as u32).checked_sub(0x621)? as usize;
+    let count = *ARABIC_FORM_COUNTS.get(index)?;
+    if count == 0 {
+        return None;
+    }
+    let offset: u32 = ARABIC_FORM_COUNTS[..index]
+        .iter()
+        .map(|count| *count as u32)
+        .sum();
+    Some((ARABIC_FORMS_START + offset, count))
+}
+
+fn joining(c: char) -> Joining {
+    match c as u32 {
+        0x640 | 0x200d => Joining::Causing,
+        0x610..=0x61a | 0x64b..=0x65f | 0x670 | 0x6d6..=0x6dc | 0x6df..=0x6e4 => {
+            Joining::Transparent
+        }
+        0x6e7 | 0x6e8 | 0x6ea..=0x6ed => Joining::Transparent,
+        _ => match arabic_forms(c) {
+            Some((_, 4)) => Joining::Dual,
+            Some((_, 2)) => Joining::Right,
+            _ => Joining::None,
+        },
+    }
+}
+
+fn lam_alef(alef: char) -> Option<u32> {
+    match alef {
+        '\u{622}' => Some(0xfef5),
+        '\u{623}' => Some(0xfef7),
+        '\u{625}' => Some(0xfef9),
+        '\u{627}' => Some(0xfefb),
+        _ => None,
+    }
+}
+
+pub fn shape_arabic(text: &str) -> String {
+    if !text.chars().any(|c| arabic_forms(c).is_some()) {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let neighbour = |indices: &mut dyn Iterator<Item = usize>| {
+        indices
+            .map(|index| (index, joining(chars[index])))
+            .find(|(_, joining)| *joining != Joining::Transparent)
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut ligated = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if ligated == Some(i) {
+            continue;
+        }
+        let Some((base, count)) = arabic_forms(c) else {
+            out.push(c);
+            continue;
+        };
+        let before = neighbour(&mut (0..i).rev())
+            .is_some_and(|(_, joining)| matches!(joining, Joining::Dual | Joining::Causing));
+        let next = neighbour(&mut (i + 1..chars.len()));
+        if c == '\u{644}'
+            && let Some((index, _)) = next
+            && let Some(ligature) = lam_alef(chars[index])
+        {
+            out.extend(char::from_u32(ligature + before as u32));
+            ligated = Some(index);
+            continue;
+        }
+        let after = count == 4 && next.is_some_and(|(_, joining)| joining != Joining::None);
+        let offset = match (before && count > 1, after) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        };
+        out.push(char::from_u32(base + offset).unwrap_or(c));
+    }
+    out
+}
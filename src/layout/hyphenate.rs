@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::string::String;
+use std::sync::OnceLock;
+use std::vec::Vec;
+
+const DICTIONARIES: &[&str] = &[
+    "/usr/share/hyphen/hyph_en_US.dic",
+    "/usr/share/hyphen/hyph_en_GB.dic",
+    "/usr/share/hyph-utf8/patterns/txt/hyph-en-us.pat.txt",
+    "/usr/share/texlive/texmf-dist/tex/generic/hyph-utf8/patterns/txt/hyph-en-us.pat.txt",
+];
+
+pub struct Hyphenator {
+    patterns: HashMap<String, Vec<u8>>,
+    exceptions: HashMap<String, Vec<usize>>,
+    longest: usize,
+    pub left_min: usize,
+    pub right_min: usize,
+}
+
+pub fn dictionary() -> Option<&'static Hyphenator> {
+    static DICTIONARY: OnceLock<Option<Hyphenator>> = OnceLock::new();
+    DICTIONARY
+        .get_or_init(|| {
+            let path = std::env::var_os("ICARUS_HYPHENATION")
+                .map(PathBuf::from)
+                .or_else(|| {
+                    DICTIONARIES
+                        .iter()
+                        .map(PathBuf::from)
+                        .find(|path| path.exists())
+                })?;
+            match std::fs::read_to_string(&path) {
+                Ok(text) => Some(Hyphenator::parse(&text)),
+                Err(err) => {
+                    log::warn!(
+                        "failed to load hyphenation patterns {}: {}",
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+impl Hyphenator {
+    pub fn parse(text: &str) -> Self {
+        let mut hyphenator = Hyphenator {
+            patterns: HashMap::new(),
+            exceptions: HashMap::new(),
+            longest: 0,
+            left_min: 2,
+            right_min: 3,
+        };
+        let mut directive: Option<&str> = None;
+        for line in text.lines() {
+            let line = line.split('%').next().unwrap_or_default();
+            for token in line.split_whitespace() {
+                if let Some(name) = directive.take() {
+                    let value = token.parse().unwrap_or(0);
+                    match name {
+                        "LEFTHYPHENMIN" => hyphenator.left_min = value.max(1),
+                        _ => hyphenator.right_min = value.max(1),
+                    }
+                    continue;
+                }
+                match token {
+                    "LEFTHYPHENMIN" | "RIGHTHYPHENMIN" => directive = Some(token),
+                    _ if token.contains('/')
+                        || token.starts_with('\\')
+                        || token.eq_ignore_ascii_case("utf-8")
+                        || token.starts_with("ISO") => {}
+                    _ => hyphenator.add(token.trim_matches(|c| c == '{' || c == '}')),
+                }
+            }
+        }
+        hyphenator
+    }
+
+    fn add(&mut self, token: &str) {
+        if token.is_empty() {
+            return;
+        }
+        if token.contains('-') && !token.contains(|c: char| c.is_ascii_digit()) {
+            let mut positions = Vec::new();
+            let mut word = String::new();
+            for c in token.chars() {
+                if c == '-' {
+                    positions.push(word.chars().count());
+                } else {
+                    word.push(c);
+                }
+            }
+            self.exceptions.insert(word.to_lowercase(), positions);
+            return;
+        }
+        let mut letters = String::new();
+        let mut values = vec![0];
+        for c in token.chars() {
+            match c.to_digit(10) {
+                Some(digit) => {
+                    if let Some(value) = values.last_mut() {
+                        *value = digit as u8;
+                    }
+                }
+                None => {
+                    letters.push(c);
+                    values.push(0);
+                }
+            }
+        }
+        self.longest = self.longest.max(values.len() - 1);
+        self.patterns.insert(letters, values);
+    }
+
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let lower: Vec<char> = word.to_lowercase().chars().collect();
+        let length = lower.len();
+        if length < self.left_min + self.right_min || lower.len() != word.chars().count() {
+            return Vec::new();
+        }
+        let key: String = lower.iter().collect();
+        if let Some(positions) = self.exceptions.get(&key) {
+            return positions.clone();
+        }
+
+        let padded: Vec<char> = std::iter::once('.')
+            .chain(lower.iter().copied())
+            .chain(std::iter::once('.'))
+            .collect();
+        let mut points = vec![0u8; padded.len() + 1];
+        let mut fragment = String::new();
+        for start in 0..padded.len() {
+            fragment.clear();
+            for c in &padded[start..padded.len().min(start + self.longest)] {
+                fragment.push(*c);
+                if let Some(values) = self.patterns.get(&fragment) {
+                    for (offset, value) in values.iter().enumerate() {
+                        let point = &mut points[start + offset];
+                        *point = (*point).max(*value);
+                    }
+                }
+            }
+        }
+        (self.left_min..=length - self.right_min)
+            .filter(|position| points[position + 1] % 2 == 1)
+            .collect()
+    }
+}
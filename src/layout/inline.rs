@@ -0,0 +1,753 @@
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use super::bidi;
+use super::hyphenate;
+use super::math::{self, MathBox};
+use super::{
+    Display, FontMetrics, LayoutOptions, Rect, box_children, char_columns, clamp_size, display_of,
+    fixed_size,
+};
+use crate::dom::{Node, NodeData};
+use crate::image::Image;
+use crate::style::{
+    ComputedStyle, Direction, Hyphens, OverflowWrap, TextAlign, VerticalAlign, WhiteSpace,
+    WordBreak, style_of,
+};
+
+const TAB_SIZE: usize = 8;
+const SOFT_HYPHEN: char = '\u{ad}';
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FragmentKind {
+    Text(String),
+    ImagePlaceholder { alt: String },
+    Image { image: Arc<Image>, alt: String },
+    Math(MathBox),
+}
+
+pub struct Fragment {
+    pub node: Rc<Node>,
+    pub style: Rc<ComputedStyle>,
+    pub rect: Rect,
+    pub kind: FragmentKind,
+    pub level: u8,
+}
+
+pub struct LineBox {
+    pub rect: Rect,
+    pub fragments: Vec<Fragment>,
+}
+
+enum Item {
+    Word(Rc<Node>, String),
+    Space(Rc<Node>),
+    Atomic(Rc<Node>, FragmentKind, f32, f32),
+    Break,
+}
+
+pub fn image_placeholder_label(alt: &str) -> String {
+    format!("[image: {}]", alt)
+}
+
+pub fn image_placeholder_size(node: &Rc<Node>, options: &LayoutOptions) -> Option<(f32, f32)> {
+    let alt = node.get_attribute("alt").unwrap_or_default();
+    let metrics = options.metrics(&style_of(node));
+    let width = node
+        .get_attribute("width")
+        .and_then(|w| parse_dimension(&w));
+    let height = node
+        .get_attribute("height")
+        .and_then(|h| parse_dimension(&h));
+
+    if alt.trim().is_empty() && width.is_none() && height.is_none() {
+        return None;
+    }
+
+    let label_width = metrics.text_width(&image_placeholder_label(alt.trim()));
+    Some((
+        width.unwrap_or(label_width),
+        height
+            .unwrap_or(metrics.line_height)
+            .max(metrics.line_height),
+    ))
+}
+
+pub fn image_size(node: &Rc<Node>, image: &Image, base: f32) -> (f32, f32) {
+    let style = style_of(node);
+    let width = style.width.resolve(base).or_else(|| {
+        node.get_attribute("width")
+            .and_then(|w| parse_dimension(&w))
+    });
+    let height = fixed_size(style.height).or_else(|| {
+        node.get_attribute("height")
+            .and_then(|h| parse_dimension(&h))
+    });
+    let (natural_width, natural_height) = (image.width as f32, image.height as f32);
+    let natural_ratio =
+        (natural_width > 0.0 && natural_height > 0.0).then(|| natural_width / natural_height);
+    let ratio = style.aspect_ratio.preferred(natural_ratio);
+    let (width, height) = match (width, height, ratio) {
+        (Some(width), Some(height), _) => (width, height),
+        (Some(width), None, Some(ratio)) => (width, width / ratio),
+        (None, Some(height), Some(ratio)) => (height * ratio, height),
+        (Some(width), None, None) => (width, natural_height),
+        (None, Some(height), None) => (natural_width, height),
+        (None, None, Some(ratio)) => (natural_width, natural_width / ratio),
+        (None, None, None) => (natural_width, natural_height),
+    };
+    constrain_replaced(&style, width, height, base)
+}
+
+fn constrain_replaced(style: &ComputedStyle, width: f32, height: f32, base: f32) -> (f32, f32) {
+    let ratio = if width > 0.0 { height / width } else { 0.0 };
+    let clamped = clamp_size(
+        width,
+        style.min_width.resolve(base),
+        style.max_width.resolve(base),
+    );
+    let (width, height) = if clamped != width {
+        (clamped, clamped * ratio)
+    } else {
+        (width, height)
+    };
+    let clamped = clamp_size(
+        height,
+        fixed_size(style.min_height),
+        fixed_size(style.max_height),
+    );
+    if clamped != height && ratio > 0.0 {
+        (clamped / ratio, clamped)
+    } else {
+        (width, clamped)
+    }
+}
+
+fn parse_dimension(value: &str) -> Option<f32> {
+    let value = value.trim().trim_end_matches("px");
+    value.parse::<f32>().ok().filter(|v| *v >= 0.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineEnd {
+    Wrap,
+    Break,
+    End,
+}
+
+enum Placement {
+    Baseline(f32),
+    Top,
+    Bottom,
+}
+
+struct LineBuilder<'a> {
+    area: Rect,
+    align: TextAlign,
+    direction: Direction,
+    strut: FontMetrics,
+    options: &'a LayoutOptions,
+    lines: Vec<LineBox>,
+    fragments: Vec<Fragment>,
+    cursor_x: f32,
+    cursor_y: f32,
+    pending_space: Option<(Rc<Node>, u8)>,
+}
+
+impl LineBuilder<'_> {
+    fn finish_line(&mut self, end: LineEnd) {
+        if self.fragments.is_empty() && end != LineEnd::Break {
+            return;
+        }
+        let placements: Vec<Placement> = self
+            .fragments
+            .iter()
+            .map(|fragment| self.placement(fragment))
+            .collect();
+        let (mut above, mut below, mut edges) = (self.strut.baseline(), self.strut.below(), 0.0f32);
+        for (fragment, placement) in self.fragments.iter().zip(&placements) {
+            match placement {
+                Placement::Baseline(ascent) => {
+                    above = above.max(*ascent);
+                    below = below.max(fragment.rect.height - ascent);
+                }
+                Placement::Top | Placement::Bottom => edges = edges.max(fragment.rect.height),
+            }
+        }
+        let height = (above + below).max(edges);
+        let baseline = self.cursor_y + height - below;
+        for (fragment, placement) in self.fragments.iter_mut().zip(placements) {
+            fragment.rect.y = match placement {
+                Placement::Baseline(ascent) => baseline - ascent,
+                Placement::Top => self.cursor_y,
+                Placement::Bottom => self.cursor_y + height - fragment.rect.height,
+            };
+        }
+        self.reorder();
+        self.align_line(end);
+        self.lines.push(LineBox {
+            rect: Rect::new(self.area.x, self.cursor_y, self.area.width, height),
+            fragments: std::mem::take(&mut self.fragments),
+        });
+        self.cursor_y += height;
+        self.cursor_x = self.area.x;
+        self.pending_space = None;
+    }
+
+    fn placement(&self, fragment: &Fragment) -> Placement {
+        let height = fragment.rect.height;
+        let mut ascent = match &fragment.kind {
+            FragmentKind::Text(_) => self.options.metrics(&fragment.style).baseline(),
+            FragmentKind::Math(math) => math.ascent,
+            _ => height,
+        };
+        let mut current = match fragment.node.data {
+            NodeData::Element { .. } => Some(Rc::clone(&fragment.node)),
+            _ => fragment.node.parent.borrow().upgrade(),
+        };
+        while let Some(element) =
+            current.filter(|node| matches!(node.data, NodeData::Element { .. }))
+        {
+            let style = style_of(&element);
+            if style.display != Display::Inline {
+                break;
+            }
+            let parent = element.parent.borrow().upgrade();
+            let parent_style = parent.as_ref().map(style_of).unwrap_or_default();
+            let metrics = self.options.metrics(&parent_style);
+            let font_size = self.options.font_size(&parent_style);
+            match style.vertical_align {
+                VerticalAlign::Baseline => {}
+                VerticalAlign::Sub => ascent -= font_size * 0.2,
+                VerticalAlign::Super => ascent += font_size * 0.35,
+                VerticalAlign::Px(px) => ascent += px,
+                VerticalAlign::Percent(percent) => {
+                    ascent += self.options.metrics(&style).line_height * percent / 100.0
+                }
+                VerticalAlign::TextTop => ascent = metrics.baseline(),
+                VerticalAlign::TextBottom => ascent = height - metrics.below(),
+                VerticalAlign::Middle => ascent = height / 2.0 + font_size * 0.25,
+                VerticalAlign::Top => return Placement::Top,
+                VerticalAlign::Bottom => return Placement::Bottom,
+            }
+            current = parent;
+        }
+        Placement::Baseline(ascent)
+    }
+
+    fn reorder(&mut self) {
+        let base = self.direction.level();
+        if base == 0 && self.fragments.iter().all(|fragment| fragment.level == 0) {
+            return;
+        }
+        for fragment in self.fragments.iter_mut().rev() {
+            match &fragment.kind {
+                FragmentKind::Text(text) if text.trim().is_empty() => fragment.level = base,
+                _ => break,
+            }
+        }
+        let levels: Vec<u8> = self
+            .fragments
+            .iter()
+            .map(|fragment| fragment.level)
+            .collect();
+        let mut logical: Vec<Option<Fragment>> = std::mem::take(&mut self.fragments)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut x = self.area.x;
+        for index in bidi::visual_order(&levels) {
+            let Some(mut fragment) = logical[index].take() else {
+                continue;
+            };
+            fragment.rect.x = x;
+            x += fragment.rect.width;
+            if fragment.level % 2 == 1
+                && let FragmentKind::Text(text) = &mut fragment.kind
+            {
+                *text = text.chars().rev().map(bidi::mirror).collect();
+            }
+            self.fragments.push(fragment);
+        }
+    }
+
+    fn align_line(&mut self, end: LineEnd) {
+        let Some(last) = self.fragments.last() else {
+            return;
+        };
+        let free = self.area.right() - last.rect.right();
+        if free <= 0.0 {
+            return;
+        }
+        let align = match self.align {
+            TextAlign::Justify if end == LineEnd::Wrap => return self.justify(free),
+            TextAlign::Justify => TextAlign::Start,
+            align => align,
+        };
+        let shift = match align.resolve(self.direction) {
+            TextAlign::Right => free,
+            TextAlign::Center => free / 2.0,
+            _ => return,
+        };
+        for fragment in &mut self.fragments {
+            fragment.rect.x += shift;
+        }
+    }
+
+    fn justify(&mut self, free: f32) {
+        let mut pieces: Vec<Fragment> = Vec::new();
+        for fragment in std::mem::take(&mut self.fragments) {
+            let FragmentKind::Text(text) = &fragment.kind else {
+                pieces.push(fragment);
+                continue;
+            };
+            let words = split_after_spaces(text);
+            if words.len() < 2 {
+                pieces.push(fragment);
+                continue;
+            }
+            let mut x = fragment.rect.x;
+            for word in words {
+                let width = self.options.text_width(&fragment.style, word);
+                pieces.push(Fragment {
+                    node: Rc::clone(&fragment.node),
+                    style: Rc::clone(&fragment.style),
+                    rect: Rect::new(x, fragment.rect.y, width, fragment.rect.height),
+                    kind: FragmentKind::Text(word.to_string()),
+                    level: fragment.level,
+                });
+                x += width;
+            }
+        }
+
+        let ends_with_space = |piece: &Fragment| matches!(&piece.kind, FragmentKind::Text(text) if text.ends_with(' '));
+        let gaps = pieces
+            .iter()
+            .take(pieces.len().saturating_sub(1))
+            .filter(|piece| ends_with_space(piece))
+            .count();
+        if gaps > 0 {
+            let extra = free / gaps as f32;
+            let mut shift = 0.0;
+            for piece in &mut pieces {
+                piece.rect.x += shift;
+                if ends_with_space(piece) {
+                    shift += extra;
+                }
+            }
+        }
+        self.fragments = pieces;
+    }
+
+    fn place_word(&mut self, node: &Rc<Node>, word: &str, level: u8) {
+        let style = style_of(node);
+        let text: String = word.chars().filter(|c| *c != SOFT_HYPHEN).collect();
+        let width = self.options.text_width(&style, &text);
+        if !style.white_space.wraps() || width <= self.available() {
+            self.place(node, width, FragmentKind::Text(text), None, level);
+            return;
+        }
+
+        let mut chars: Vec<char> = Vec::new();
+        let mut soft = Vec::new();
+        for c in word.chars() {
+            if c == SOFT_HYPHEN {
+                soft.push(chars.len());
+            } else {
+                chars.push(c);
+            }
+        }
+        let breaks = break_points(&chars, &soft, &style);
+        let mut start = 0;
+        loop {
+            let rest: String = chars[start..].iter().collect();
+            let width = self.options.text_width(&style, &rest);
+            let available = self.available();
+            if width <= available {
+                self.place(node, width, FragmentKind::Text(rest), None, level);
+                return;
+            }
+            let fitting = breaks
+                .iter()
+                .rev()
+                .filter(|(index, _)| *index > start && *index < chars.len())
+                .find_map(|&(index, hyphen)| {
+                    let mut text: String = chars[start..index].iter().collect();
+                    if hyphen {
+                        text.push('-');
+                    }
+                    let width = self.options.text_width(&style, &text);
+                    (width <= available).then_some((index, text, width))
+                });
+            if let Some((index, text, width)) = fitting {
+                self.place(node, width, FragmentKind::Text(text), None, level);
+                self.finish_line(LineEnd::Wrap);
+                start = index;
+                continue;
+            }
+            if !self.fragments.is_empty() {
+                self.finish_line(LineEnd::Wrap);
+                continue;
+            }
+            if style.overflow_wrap == OverflowWrap::Normal
+                && style.word_break != WordBreak::BreakAll
+            {
+                self.place(node, width, FragmentKind::Text(rest), None, level);
+                return;
+            }
+            let mut end = start + 1;
+            while end < chars.len() {
+                let prefix: String = chars[start..=end].iter().collect();
+                if self.options.text_width(&style, &prefix) > available {
+                    break;
+                }
+                end += 1;
+            }
+            let text: String = chars[start..end].iter().collect();
+            let width = self.options.text_width(&style, &text);
+            self.place(node, width, FragmentKind::Text(text), None, level);
+            self.finish_line(LineEnd::Wrap);
+            start = end;
+        }
+    }
+
+    fn available(&self) -> f32 {
+        let space_width = match &self.pending_space {
+            Some((space, _)) if !self.fragments.is_empty() => {
+                self.options.text_width(&style_of(space), " ")
+            }
+            _ => 0.0,
+        };
+        self.area.right() - self.cursor_x - space_width
+    }
+
+    fn place(
+        &mut self,
+        node: &Rc<Node>,
+        width: f32,
+        kind: FragmentKind,
+        height: Option<f32>,
+        level: u8,
+    ) {
+        let space_width = match &self.pending_space {
+            Some((space, _)) => self.options.text_width(&style_of(space), " "),
+            None => 0.0,
+        };
+        if !self.fragments.is_empty()
+            && style_of(node).white_space.wraps()
+            && self.cursor_x + space_width + width > self.area.right()
+        {
+            self.finish_line(LineEnd::Wrap);
+        }
+        if let Some((space, space_level)) = self.pending_space.take() {
+            self.push_text(&space, " ", space_width, space_level);
+        }
+
+        match kind {
+            FragmentKind::Text(text) => self.push_text(node, &text, width, level),
+            kind => {
+                self.fragments.push(Fragment {
+                    node: Rc::clone(node),
+                    style: style_of(node),
+                    rect: Rect::new(self.cursor_x, 0.0, width, height.unwrap_or(0.0)),
+                    kind,
+                    level,
+                });
+                self.cursor_x += width;
+            }
+        }
+    }
+
+    fn push_text(&mut self, node: &Rc<Node>, text: &str, width: f32, level: u8) {
+        self.cursor_x += width;
+        if let Some(last) = self.fragments.last_mut()
+            && Rc::ptr_eq(&last.node, node)
+            && last.level == level
+            && let FragmentKind::Text(contents) = &mut last.kind
+        {
+            contents.push_str(text);
+            last.rect.width += width;
+            return;
+        }
+        let style = style_of(node);
+        let height = self.options.metrics(&style).line_height;
+        self.fragments.push(Fragment {
+            node: Rc::clone(node),
+            style,
+            rect: Rect::new(self.cursor_x - width, 0.0, width, height),
+            kind: FragmentKind::Text(text.to_string()),
+            level,
+        });
+    }
+}
+
+fn break_points(chars: &[char], soft: &[usize], style: &ComputedStyle) -> Vec<(usize, bool)> {
+    let mut points: Vec<(usize, bool)> = Vec::new();
+    if style.hyphens != Hyphens::None {
+        points.extend(soft.iter().map(|index| (*index, true)));
+    }
+    if style.hyphens == Hyphens::Auto
+        && soft.is_empty()
+        && let Some(dictionary) = hyphenate::dictionary()
+    {
+        let start = chars.iter().position(|c| c.is_alphabetic()).unwrap_or(0);
+        let end = chars
+            .iter()
+            .rposition(|c| c.is_alphabetic())
+            .map_or(start, |index| index + 1);
+        let core: String = chars[start..end].iter().collect();
+        if core.chars().all(char::is_alphabetic) {
+            let hyphens = dictionary.hyphenate(&core);
+            points.extend(hyphens.into_iter().map(|index| (start + index, true)));
+        }
+    }
+    for index in 1..chars.len() {
+        let wide = char_columns(chars[index - 1]) == 2 || char_columns(chars[index]) == 2;
+        if style.word_break == WordBreak::BreakAll
+            || (wide && style.word_break != WordBreak::KeepAll)
+        {
+            points.push((index, false));
+        }
+    }
+    points.sort_by_key(|(index, hyphen)| (*index, *hyphen));
+    points
+}
+
+fn split_after_spaces(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        if previous == ' ' && c != ' ' && index > start {
+            words.push(&text[start..index]);
+            start = index;
+        }
+        previous = c;
+    }
+    words.push(&text[start..]);
+    words
+}
+
+pub fn layout_lines(
+    nodes: &[Rc<Node>],
+    area: Rect,
+    style: &ComputedStyle,
+    options: &LayoutOptions,
+) -> Vec<LineBox> {
+    let mut items = Vec::new();
+    for node in nodes {
+        collect_items(node, area.width, options, &mut items);
+    }
+
+    let mut builder = LineBuilder {
+        area,
+        align: style.text_align,
+        direction: style.direction,
+        strut: options.metrics(style),
+        options,
+        lines: Vec::new(),
+        fragments: Vec::new(),
+        cursor_x: area.x,
+        cursor_y: area.y,
+        pending_space: None,
+    };
+
+    for (item, level) in resolve_levels(items, style.direction.level()) {
+        match item {
+            Item::Space(node) => {
+                if !builder.fragments.is_empty() {
+                    builder.pending_space = Some((node, level));
+                }
+            }
+            Item::Break => builder.finish_line(LineEnd::Break),
+            Item::Word(node, word) => builder.place_word(&node, &word, level),
+            Item::Atomic(node, kind, width, height) => {
+                builder.place(&node, width, kind, Some(height), level);
+            }
+        }
+    }
+    builder.finish_line(LineEnd::End);
+
+    builder.lines
+}
+
+fn resolve_levels(items: Vec<Item>, base: u8) -> Vec<(Item, u8)> {
+    let bidirectional = base > 0
+        || items
+            .iter()
+            .any(|item| matches!(item, Item::Word(_, word) if word.chars().any(bidi::is_rtl)));
+    if !bidirectional {
+        return items.into_iter().map(|item| (item, 0)).collect();
+    }
+
+    let mut resolved = Vec::new();
+    let mut paragraph = Vec::new();
+    for item in items {
+        let is_break = matches!(item, Item::Break);
+        paragraph.push(match item {
+            Item::Word(node, word) => Item::Word(node, bidi::shape_arabic(&word)),
+            item => item,
+        });
+        if is_break {
+            resolve_paragraph(std::mem::take(&mut paragraph), base, &mut resolved);
+        }
+    }
+    resolve_paragraph(paragraph, base, &mut resolved);
+    resolved
+}
+
+fn resolve_paragraph(items: Vec<Item>, base: u8, resolved: &mut Vec<(Item, u8)>) {
+    let text: String = items
+        .iter()
+        .map(|item| match item {
+            Item::Word(_, word) => word.as_str(),
+            Item::Space(_) => " ",
+            Item::Atomic(..) => "\u{fffc}",
+            Item::Break => "\u{2029}",
+        })
+        .collect();
+    let levels = bidi::resolve_levels(&text, base);
+    let mut index = 0;
+    for item in items {
+        let Item::Word(node, word) = item else {
+            resolved.push((item, levels[index]));
+            index += 1;
+            continue;
+        };
+        let mut piece = String::new();
+        let mut current = None;
+        for c in word.chars() {
+            let level = levels[index];
+            index += 1;
+            if let Some(current) = current
+                && current != level
+            {
+                resolved.push((
+                    Item::Word(Rc::clone(&node), std::mem::take(&mut piece)),
+                    current,
+                ));
+            }
+            current = Some(level);
+            piece.push(c);
+        }
+        if let Some(level) = current {
+            resolved.push((Item::Word(node, piece), level));
+        }
+    }
+}
+
+fn collect_items(node: &Rc<Node>, base: f32, options: &LayoutOptions, items: &mut Vec<Item>) {
+    if display_of(node) == Display::None {
+        return;
+    }
+
+    match &node.data {
+        NodeData::Text { contents } => {
+            let style = style_of(node);
+            let word_start = !matches!(items.last(), Some(Item::Word(..)));
+            let contents = style.text_transform.apply(&contents.borrow(), word_start);
+            let white_space = style.white_space;
+            if !white_space.preserves_newlines() {
+                collect_words(node, &contents, items);
+                return;
+            }
+            for (i, line) in contents.split('\n').enumerate() {
+                if i > 0 {
+                    items.push(Item::Break);
+                }
+                match white_space {
+                    WhiteSpace::PreLine => collect_words(node, line, items),
+                    WhiteSpace::PreWrap => collect_runs(node, &expand_tabs(line), items),
+                    _ if !line.is_empty() => {
+                        items.push(Item::Word(Rc::clone(node), expand_tabs(line)))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        NodeData::Element { .. } if math::is_math_root(node) => {
+            let font_size = options.font_size(&style_of(node));
+            let math = math::layout_math(node, font_size);
+            let (width, height) = (math.width, math.height());
+            items.push(Item::Atomic(
+                Rc::clone(node),
+                FragmentKind::Math(math),
+                width,
+                height,
+            ));
+        }
+        NodeData::Element { .. } => match node.element_name() {
+            Some("br") => items.push(Item::Break),
+            Some("img") => {
+                let alt = node
+                    .get_attribute("alt")
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                let image = node.image.borrow().clone();
+                if let Some(image) = image {
+                    let (width, height) = image_size(node, &image, base);
+                    let kind = FragmentKind::Image { image, alt };
+                    items.push(Item::Atomic(Rc::clone(node), kind, width, height));
+                } else if let Some((width, height)) = image_placeholder_size(node, options) {
+                    let kind = FragmentKind::ImagePlaceholder { alt };
+                    items.push(Item::Atomic(Rc::clone(node), kind, width, height));
+                }
+            }
+            _ => {
+                for child in &box_children(node) {
+                    collect_items(child, base, options, items);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+fn collect_words(node: &Rc<Node>, text: &str, items: &mut Vec<Item>) {
+    if text.starts_with(char::is_whitespace) {
+        items.push(Item::Space(Rc::clone(node)));
+    }
+    let mut words = text.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        items.push(Item::Word(Rc::clone(node), word.to_string()));
+        if words.peek().is_some() {
+            items.push(Item::Space(Rc::clone(node)));
+        }
+    }
+    if text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+        items.push(Item::Space(Rc::clone(node)));
+    }
+}
+
+fn collect_runs(node: &Rc<Node>, text: &str, items: &mut Vec<Item>) {
+    let mut start = 0;
+    for (i, c) in text.char_indices().skip(1) {
+        let previous = text[..i].ends_with(char::is_whitespace);
+        if c.is_whitespace() != previous {
+            items.push(Item::Word(Rc::clone(node), text[start..i].to_string()));
+            start = i;
+        }
+    }
+    if start < text.len() {
+        items.push(Item::Word(Rc::clone(node), text[start..].to_string()));
+    }
+}
+
+pub fn expand_tabs(line: &str) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_SIZE - column % TAB_SIZE;
+            out.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else if c != '\r' {
+            out.push(c);
+            column += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,314 @@
+use html5ever::ns;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::{FontMetrics, Rect};
+use crate::dom::{Node, NodeData};
+
+const SCRIPT_SCALE: f32 = 0.71;
+const SCRIPT_MIN_SIZE: f32 = 8.0;
+const ASCENT: f32 = 0.8;
+const DESCENT: f32 = 0.2;
+const AXIS: f32 = 0.3;
+
+const SPACED_OPERATORS: &[&str] = &[
+    "+", "-", "\u{2212}", "=", "<", ">", "\u{b1}", "\u{d7}", "\u{f7}", "\u{b7}", "*", "/",
+    "\u{2264}", "\u{2265}", "\u{2260}", "\u{2248}", "\u{2261}", "\u{2192}", "\u{2190}", "\u{21d2}",
+    "\u{21d4}", "\u{2208}", "\u{2209}", "\u{2282}", "\u{2286}", "\u{222a}", "\u{2229}", "\u{2227}",
+    "\u{2228}",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MathRun {
+    pub rect: Rect,
+    pub text: String,
+    pub font_size: f32,
+    pub italic: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MathBox {
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub runs: Vec<MathRun>,
+    pub rules: Vec<Rect>,
+}
+
+impl MathBox {
+    pub fn height(&self) -> f32 {
+        self.ascent + self.descent
+    }
+
+    pub fn text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+
+    fn place(&mut self, other: MathBox, dx: f32, dy: f32) {
+        self.runs.extend(other.runs.into_iter().map(|mut run| {
+            run.rect.x += dx;
+            run.rect.y += dy;
+            run
+        }));
+        self.rules.extend(other.rules.into_iter().map(|mut rule| {
+            rule.x += dx;
+            rule.y += dy;
+            rule
+        }));
+        self.width = self.width.max(dx + other.width);
+        self.ascent = self.ascent.max(other.ascent - dy);
+        self.descent = self.descent.max(other.descent + dy);
+    }
+
+    fn into_top_left(mut self) -> MathBox {
+        for run in &mut self.runs {
+            run.rect.y += self.ascent;
+        }
+        for rule in &mut self.rules {
+            rule.y += self.ascent;
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Context {
+    font_size: f32,
+    display: bool,
+}
+
+impl Context {
+    fn script(self) -> Context {
+        Context {
+            font_size: (self.font_size * SCRIPT_SCALE).max(SCRIPT_MIN_SIZE.min(self.font_size)),
+            display: false,
+        }
+    }
+}
+
+pub fn is_math_root(node: &Node) -> bool {
+    match &node.data {
+        NodeData::Element { name, .. } => name.ns == ns!(mathml) && &*name.local == "math",
+        _ => false,
+    }
+}
+
+pub fn layout_math(node: &Rc<Node>, font_size: f32) -> MathBox {
+    let context = Context {
+        font_size,
+        display: node.get_attribute("display").as_deref() == Some("block"),
+    };
+    layout_row(&element_children(node), context).into_top_left()
+}
+
+fn element_children(node: &Rc<Node>) -> Vec<Rc<Node>> {
+    node.children
+        .borrow()
+        .iter()
+        .filter(|child| matches!(child.data, NodeData::Element { .. }))
+        .cloned()
+        .collect()
+}
+
+fn layout_node(node: &Rc<Node>, context: Context, first: bool) -> MathBox {
+    let children = element_children(node);
+    let name = node.element_name().unwrap_or_default();
+    match name {
+        "mi" => {
+            let text = token_text(node);
+            let italic = text.chars().count() == 1
+                && node.get_attribute("mathvariant").as_deref() != Some("normal");
+            layout_text(&text, context, italic, 0.0, 0.0)
+        }
+        "mn" | "mtext" => layout_text(&token_text(node), context, false, 0.0, 0.0),
+        "ms" => layout_text(
+            &format!("\"{}\"", token_text(node)),
+            context,
+            false,
+            0.0,
+            0.0,
+        ),
+        "mo" => {
+            let text = token_text(node);
+            let space = context.font_size * 0.2;
+            if SPACED_OPERATORS.contains(&text.as_str()) && !first {
+                layout_text(&text, context, false, space, space)
+            } else if matches!(text.as_str(), "," | ";") {
+                layout_text(&text, context, false, 0.0, space)
+            } else {
+                layout_text(&text, context, false, 0.0, 0.0)
+            }
+        }
+        "mspace" => MathBox {
+            width: node
+                .get_attribute("width")
+                .and_then(|width| parse_length(&width, context.font_size))
+                .unwrap_or(0.0),
+            ..MathBox::default()
+        },
+        "mfrac" if children.len() == 2 => layout_fraction(&children[0], &children[1], context),
+        "msup" if children.len() == 2 => {
+            layout_scripts(&children[0], None, Some(&children[1]), context)
+        }
+        "msub" if children.len() == 2 => {
+            layout_scripts(&children[0], Some(&children[1]), None, context)
+        }
+        "msubsup" if children.len() == 3 => layout_scripts(
+            &children[0],
+            Some(&children[1]),
+            Some(&children[2]),
+            context,
+        ),
+        "msqrt" => layout_sqrt(&children, context),
+        "semantics" => children
+            .first()
+            .map(|child| layout_node(child, context, first))
+            .unwrap_or_default(),
+        "annotation" | "annotation-xml" | "none" | "mprescripts" => MathBox::default(),
+        _ => layout_row(&children, context),
+    }
+}
+
+fn token_text(node: &Rc<Node>) -> String {
+    node.get_text_content()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn layout_text(text: &str, context: Context, italic: bool, before: f32, after: f32) -> MathBox {
+    let metrics = FontMetrics::for_size(context.font_size);
+    let width = metrics.text_width(text);
+    let top = metrics.baseline();
+    let mut runs = Vec::new();
+    if !text.is_empty() {
+        runs.push(MathRun {
+            rect: Rect::new(before, -top, width, metrics.line_height),
+            text: text.to_string(),
+            font_size: context.font_size,
+            italic,
+        });
+    }
+    MathBox {
+        width: before + width + after,
+        ascent: context.font_size * ASCENT,
+        descent: context.font_size * DESCENT,
+        runs,
+        rules: Vec::new(),
+    }
+}
+
+fn layout_row(children: &[Rc<Node>], context: Context) -> MathBox {
+    let mut row = MathBox::default();
+    for (index, child) in children.iter().enumerate() {
+        let child = layout_node(child, context, index == 0);
+        let x = row.width;
+        row.place(child, x, 0.0);
+    }
+    row
+}
+
+fn rule_thickness(font_size: f32) -> f32 {
+    (font_size * 0.06).max(1.0)
+}
+
+fn layout_fraction(numerator: &Rc<Node>, denominator: &Rc<Node>, context: Context) -> MathBox {
+    let inner = match context.display {
+        true => Context {
+            display: false,
+            ..context
+        },
+        false => context.script(),
+    };
+    let numerator = layout_node(numerator, inner, true);
+    let denominator = layout_node(denominator, inner, true);
+    let thickness = rule_thickness(context.font_size);
+    let gap = context.font_size * 0.15;
+    let axis = context.font_size * AXIS;
+    let padding = context.font_size * 0.1;
+    let width = numerator.width.max(denominator.width) + 2.0 * padding;
+
+    let mut fraction = MathBox {
+        width,
+        ..MathBox::default()
+    };
+    let bar_top = -axis - thickness / 2.0;
+    fraction
+        .rules
+        .push(Rect::new(0.0, bar_top, width, thickness));
+    fraction.ascent = axis + thickness / 2.0;
+    fraction.descent = thickness / 2.0 - axis;
+
+    let numerator_shift = bar_top - gap - numerator.descent;
+    let denominator_shift = bar_top + thickness + gap + denominator.ascent;
+    let numerator_x = (width - numerator.width) / 2.0;
+    let denominator_x = (width - denominator.width) / 2.0;
+    fraction.place(numerator, numerator_x, numerator_shift);
+    fraction.place(denominator, denominator_x, denominator_shift);
+    fraction
+}
+
+fn layout_scripts(
+    base: &Rc<Node>,
+    subscript: Option<&Rc<Node>>,
+    superscript: Option<&Rc<Node>>,
+    context: Context,
+) -> MathBox {
+    let base = layout_node(base, context, true);
+    let size = context.font_size;
+    let mut scripts = MathBox::default();
+    let x = base.width;
+    let base_ascent = base.ascent;
+    let base_descent = base.descent;
+    scripts.place(base, 0.0, 0.0);
+
+    let mut superscript_bottom = None;
+    if let Some(superscript) = superscript {
+        let superscript = layout_node(superscript, context.script(), true);
+        let shift = (size * 0.45)
+            .max(base_ascent - superscript.ascent * 0.8)
+            .max(superscript.descent + size * 0.25);
+        superscript_bottom = Some(superscript.descent - shift);
+        scripts.place(superscript, x, -shift);
+    }
+    if let Some(subscript) = subscript {
+        let subscript = layout_node(subscript, context.script(), true);
+        let mut shift = (size * 0.25)
+            .max(base_descent)
+            .max(subscript.ascent - size * 0.8);
+        if let Some(bottom) = superscript_bottom {
+            let gap = shift - subscript.ascent - bottom;
+            if gap < size * 0.1 {
+                shift += size * 0.1 - gap;
+            }
+        }
+        scripts.place(subscript, x, shift);
+    }
+    scripts
+}
+
+fn layout_sqrt(children: &[Rc<Node>], context: Context) -> MathBox {
+    let radicand = layout_row(children, context);
+    let radical = layout_text("\u{221a}", context, false, 0.0, 0.0);
+    let thickness = rule_thickness(context.font_size);
+    let gap = context.font_size * 0.1;
+    let x = radical.width;
+    let top = -(radicand.ascent.max(radical.ascent) + gap + thickness);
+
+    let mut root = MathBox::default();
+    let width = radicand.width;
+    root.place(radical, 0.0, 0.0);
+    root.place(radicand, x, 0.0);
+    root.rules.push(Rect::new(x, top, width, thickness));
+    root.ascent = root.ascent.max(-top);
+    root
+}
+
+fn parse_length(value: &str, font_size: f32) -> Option<f32> {
+    let value = value.trim();
+    if let Some(em) = value.strip_suffix("em") {
+        return em.trim().parse::<f32>().ok().map(|em| em * font_size);
+    }
+    value.trim_end_matches("px").trim().parse::<f32>().ok()
+}
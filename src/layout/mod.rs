@@ -0,0 +1,928 @@
+pub mod bidi;
+pub mod hyphenate;
+pub mod inline;
+pub mod math;
+pub mod selection;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData};
+use crate::style::{
+    BoxSizing, ComputedStyle, Dimension, Direction, Display, LineHeight, MAX_LINE_HEIGHT, Sides,
+    style_of,
+};
+use inline::{FragmentKind, LineBox};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let rect = Rect::new(
+            x,
+            y,
+            self.right().min(other.right()) - x,
+            self.bottom().min(other.bottom()) - y,
+        );
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    pub fn expand(&self, edges: &Sides<f32>) -> Rect {
+        Rect::new(
+            self.x - edges.left,
+            self.y - edges.top,
+            self.width + edges.horizontal(),
+            self.height + edges.vertical(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontProportions {
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+impl Default for FontProportions {
+    fn default() -> Self {
+        FontProportions {
+            ascent: 0.8,
+            descent: 0.2,
+            line_gap: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontMetrics {
+    pub char_width: f32,
+    pub line_height: f32,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+impl FontMetrics {
+    pub fn for_size(font_size: f32) -> Self {
+        FontMetrics::new(font_size, FontProportions::default(), None)
+    }
+
+    pub fn new(font_size: f32, proportions: FontProportions, line_height: Option<f32>) -> Self {
+        let normal = proportions.ascent + proportions.descent + proportions.line_gap;
+        FontMetrics {
+            char_width: font_size * 0.6,
+            line_height: line_height.unwrap_or(font_size * normal),
+            ascent: font_size * proportions.ascent,
+            descent: font_size * proportions.descent,
+        }
+    }
+
+    pub fn text_width(&self, text: &str) -> f32 {
+        text.chars().map(char_columns).sum::<usize>() as f32 * self.char_width
+    }
+
+    pub fn baseline(&self) -> f32 {
+        (self.line_height - self.ascent - self.descent) / 2.0 + self.ascent
+    }
+
+    pub fn below(&self) -> f32 {
+        self.line_height - self.baseline()
+    }
+}
+
+pub fn char_columns(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036f | 0x200b..=0x200d | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f
+        | 0x2e80..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+impl Default for FontMetrics {
+    fn default() -> Self {
+        FontMetrics::for_size(16.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutDirty {
+    pub this: bool,
+    pub descendants: bool,
+}
+
+impl LayoutDirty {
+    pub fn is_clean(&self) -> bool {
+        !self.this && !self.descendants
+    }
+}
+
+pub(crate) fn mark_dirty(node: &Rc<Node>) {
+    let mut dirty = node.layout_dirty.get();
+    dirty.this = true;
+    node.layout_dirty.set(dirty);
+
+    let mut current = Node::flat_tree_parent(node);
+    while let Some(ancestor) = current {
+        let mut dirty = ancestor.layout_dirty.get();
+        if dirty.descendants {
+            return;
+        }
+        dirty.descendants = true;
+        ancestor.layout_dirty.set(dirty);
+        current = Node::flat_tree_parent(&ancestor);
+    }
+}
+
+fn clear_dirty(node: &Rc<Node>) {
+    if node.layout_dirty.get().is_clean() {
+        return;
+    }
+    node.layout_dirty.set(LayoutDirty::default());
+    let children = node.children.borrow().clone();
+    let shadow_root = node.shadow_root.borrow().clone();
+    for child in children.iter().chain(&shadow_root) {
+        clear_dirty(child);
+    }
+}
+
+pub fn display_of(node: &Rc<Node>) -> Display {
+    match &node.data {
+        NodeData::Text { .. } => Display::Inline,
+        NodeData::Element { .. } => style_of(node).display,
+        _ => Display::None,
+    }
+}
+
+fn containing_direction(node: &Rc<Node>) -> Direction {
+    node.parent
+        .borrow()
+        .upgrade()
+        .map(|parent| style_of(&parent).direction)
+        .unwrap_or_default()
+}
+
+pub fn box_children(node: &Rc<Node>) -> Vec<Rc<Node>> {
+    let before = node.pseudo_before.borrow().clone();
+    let after = node.pseudo_after.borrow().clone();
+    before
+        .into_iter()
+        .chain(Node::flat_tree_children(node))
+        .chain(after)
+        .collect()
+}
+
+pub struct LayoutBox {
+    pub node: Option<Rc<Node>>,
+    pub style: Rc<ComputedStyle>,
+    pub rect: Rect,
+    pub margin: Sides<f32>,
+    pub border: Sides<f32>,
+    pub padding: Sides<f32>,
+    pub children: Vec<LayoutBox>,
+    pub lines: Vec<LineBox>,
+    pub containing_width: f32,
+    pub frame: Option<Box<LayoutTree>>,
+}
+
+impl LayoutBox {
+    fn new(node: Option<Rc<Node>>, style: Rc<ComputedStyle>) -> Self {
+        LayoutBox {
+            node,
+            style,
+            rect: Rect::default(),
+            margin: Sides::default(),
+            border: Sides::default(),
+            padding: Sides::default(),
+            children: Vec::new(),
+            lines: Vec::new(),
+            containing_width: 0.0,
+            frame: None,
+        }
+    }
+
+    fn translate(&mut self, dx: f32, dy: f32) {
+        self.rect.x += dx;
+        self.rect.y += dy;
+        for line in &mut self.lines {
+            line.rect.x += dx;
+            line.rect.y += dy;
+            for fragment in &mut line.fragments {
+                fragment.rect.x += dx;
+                fragment.rect.y += dy;
+            }
+        }
+        for child in &mut self.children {
+            child.translate(dx, dy);
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match &self.node {
+            Some(node) => node.element_name().unwrap_or("#document").to_string(),
+            None => String::from("(anonymous)"),
+        }
+    }
+
+    pub fn padding_box(&self) -> Rect {
+        self.rect.expand(&self.padding)
+    }
+
+    pub fn border_box(&self) -> Rect {
+        self.padding_box().expand(&self.border)
+    }
+
+    pub fn margin_box(&self) -> Rect {
+        self.border_box().expand(&self.margin)
+    }
+
+    fn dump_box(&self, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        let display = match (&self.node, self.style.display) {
+            (None, _) => "anonymous",
+            (Some(node), _) if node.element_name().is_none() => "block",
+            (Some(_), Display::None) => "none",
+            (Some(_), Display::Block) => "block",
+            (Some(_), Display::Inline) => "inline",
+        };
+        out.push_str(&format!(
+            "{}{} {} {}",
+            indent,
+            self.name(),
+            display,
+            format_rect(&self.rect)
+        ));
+        for (label, sides) in [
+            ("margin", &self.margin),
+            ("border", &self.border),
+            ("padding", &self.padding),
+        ] {
+            if *sides != Sides::default() {
+                out.push_str(&format!(" {}={}", label, format_sides(sides)));
+            }
+        }
+        out.push('\n');
+
+        for line in &self.lines {
+            out.push_str(&format!("{}  line {}\n", indent, format_rect(&line.rect)));
+            for fragment in &line.fragments {
+                let kind = match &fragment.kind {
+                    FragmentKind::Text(text) => format!("text {:?}", text),
+                    FragmentKind::ImagePlaceholder { alt } => format!("placeholder {:?}", alt),
+                    FragmentKind::Image { image, .. } => {
+                        format!("image {}x{}", image.width, image.height)
+                    }
+                    FragmentKind::Math(math) => format!("math {:?}", math.text()),
+                };
+                out.push_str(&format!(
+                    "{}    {} {}\n",
+                    indent,
+                    kind,
+                    format_rect(&fragment.rect)
+                ));
+            }
+        }
+        for child in &self.children {
+            child.dump_box(depth + 1, out);
+        }
+    }
+
+    pub fn count(&self) -> (usize, usize) {
+        let fragments = self.lines.iter().map(|line| line.fragments.len()).sum();
+        self.children
+            .iter()
+            .map(|child| child.count())
+            .fold((1, fragments), |(boxes, fragments), (b, f)| {
+                (boxes + b, fragments + f)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    pub viewport_width: f32,
+    pub scale_fonts: bool,
+    pub font: FontProportions,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            viewport_width: 800.0,
+            scale_fonts: true,
+            font: FontProportions::default(),
+        }
+    }
+}
+
+impl LayoutOptions {
+    pub fn font_size(&self, style: &ComputedStyle) -> f32 {
+        if self.scale_fonts {
+            style.font_size
+        } else {
+            16.0
+        }
+    }
+
+    pub fn metrics(&self, style: &ComputedStyle) -> FontMetrics {
+        let font_size = self.font_size(style);
+        let line_height = match style.line_height {
+            _ if !self.scale_fonts => None,
+            LineHeight::Normal => None,
+            LineHeight::Number(number) => Some((number * font_size).min(MAX_LINE_HEIGHT)),
+            LineHeight::Px(px) => Some(px),
+        };
+        FontMetrics::new(font_size, self.font, line_height)
+    }
+
+    pub fn text_width(&self, style: &ComputedStyle, text: &str) -> f32 {
+        let spaces = text.chars().filter(|c| matches!(c, ' ' | '\u{a0}')).count();
+        self.metrics(style).text_width(text)
+            + text.chars().count() as f32 * style.letter_spacing
+            + spaces as f32 * style.word_spacing
+    }
+}
+
+pub struct Hit {
+    pub node: Rc<Node>,
+    pub rect: Rect,
+    pub margin: Sides<f32>,
+    pub border: Sides<f32>,
+    pub padding: Sides<f32>,
+}
+
+pub struct LayoutTree {
+    pub root: LayoutBox,
+    pub options: LayoutOptions,
+}
+
+#[derive(Default)]
+struct LayoutCache {
+    boxes: HashMap<*const Node, LayoutBox>,
+    reused: usize,
+}
+
+impl LayoutCache {
+    fn open(&mut self, layout_box: LayoutBox) {
+        for child in layout_box.children {
+            if let Some(node) = &child.node {
+                self.boxes.insert(Rc::as_ptr(node), child);
+            }
+        }
+    }
+
+    fn reuse(&mut self, node: &Rc<Node>, containing: Rect, cursor: f32) -> Option<LayoutBox> {
+        let cached = self.boxes.remove(&Rc::as_ptr(node))?;
+        if !node.layout_dirty.get().is_clean() || cached.containing_width != containing.width {
+            self.open(cached);
+            return None;
+        }
+
+        let mut cached = cached;
+        let origin = cached.margin_box();
+        let dx = match containing_direction(node) {
+            Direction::Ltr => containing.x - origin.x,
+            Direction::Rtl => containing.right() - origin.right(),
+        };
+        cached.translate(dx, cursor - origin.y);
+        self.reused += 1;
+        Some(cached)
+    }
+}
+
+impl LayoutTree {
+    pub fn build(document: &Document, options: LayoutOptions) -> Self {
+        LayoutTree::layout(document, options, LayoutCache::default())
+    }
+
+    pub fn update(&mut self, document: &Document, options: LayoutOptions) {
+        let previous = std::mem::replace(
+            &mut self.root,
+            LayoutBox::new(None, Rc::new(ComputedStyle::default())),
+        );
+        let mut cache = LayoutCache::default();
+        if options == self.options {
+            cache.open(previous);
+        }
+        *self = LayoutTree::layout(document, options, cache);
+    }
+
+    fn layout(document: &Document, options: LayoutOptions, mut cache: LayoutCache) -> Self {
+        document.restyle();
+        let span = tracing::info_span!(
+            "layout",
+            boxes = tracing::field::Empty,
+            fragments = tracing::field::Empty,
+            reused = tracing::field::Empty
+        )
+        .entered();
+        let mut root = LayoutBox::new(Some(Rc::clone(&document.root)), style_of(&document.root));
+        root.rect.width = options.viewport_width;
+        root.containing_width = options.viewport_width;
+        layout_block_contents(
+            &mut root,
+            &document.root,
+            &options,
+            &mut cache,
+            false,
+            false,
+        );
+        clear_dirty(&document.root);
+
+        let (boxes, fragments) = root.count();
+        span.record("boxes", boxes);
+        span.record("fragments", fragments);
+        span.record("reused", cache.reused);
+        LayoutTree { root, options }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.root.margin_box().height
+    }
+
+    pub fn hit_test(&self, x: f32, y: f32) -> Option<Hit> {
+        hit_test_box(&self.root, None, x, y)
+    }
+
+    pub fn rect_of(&self, node: &Rc<Node>) -> Option<Rect> {
+        rect_of_box(&self.root, node)
+    }
+
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        self.root.dump_box(0, &mut out);
+        out
+    }
+}
+
+fn format_length(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn format_rect(rect: &Rect) -> String {
+    format!(
+        "({}, {}) {}x{}",
+        format_length(rect.x),
+        format_length(rect.y),
+        format_length(rect.width),
+        format_length(rect.height)
+    )
+}
+
+fn format_sides(sides: &Sides<f32>) -> String {
+    if *sides == Sides::all(sides.top) {
+        return format_length(sides.top);
+    }
+    [sides.top, sides.right, sides.bottom, sides.left]
+        .map(format_length)
+        .join(" ")
+}
+
+fn rect_of_box(layout_box: &LayoutBox, node: &Rc<Node>) -> Option<Rect> {
+    if let Some(own) = &layout_box.node
+        && Rc::ptr_eq(own, node)
+    {
+        return Some(layout_box.border_box());
+    }
+    let fragment = layout_box
+        .lines
+        .iter()
+        .flat_map(|line| &line.fragments)
+        .find(|fragment| is_inclusive_ancestor(node, &fragment.node));
+    if let Some(fragment) = fragment {
+        return Some(fragment.rect);
+    }
+    layout_box
+        .children
+        .iter()
+        .find_map(|child| rect_of_box(child, node))
+}
+
+fn is_inclusive_ancestor(ancestor: &Rc<Node>, node: &Rc<Node>) -> bool {
+    let mut current = Some(Rc::clone(node));
+    while let Some(node) = current {
+        if Rc::ptr_eq(&node, ancestor) {
+            return true;
+        }
+        current = node.parent.borrow().upgrade();
+    }
+    false
+}
+
+fn hit_test_box(layout_box: &LayoutBox, owner: Option<&LayoutBox>, x: f32, y: f32) -> Option<Hit> {
+    let owner = if layout_box.node.is_some() {
+        layout_box
+    } else {
+        owner?
+    };
+    if let Some(frame) = &layout_box.frame
+        && layout_box.rect.contains(x, y)
+        && let Some(mut hit) = frame.hit_test(x - layout_box.rect.x, y - layout_box.rect.y)
+    {
+        hit.rect.x += layout_box.rect.x;
+        hit.rect.y += layout_box.rect.y;
+        return Some(hit);
+    }
+    for child in layout_box.children.iter().rev() {
+        if let Some(hit) = hit_test_box(child, Some(owner), x, y) {
+            return Some(hit);
+        }
+    }
+
+    for line in &layout_box.lines {
+        for fragment in &line.fragments {
+            if !fragment.rect.contains(x, y) || !fragment.style.is_visible() {
+                continue;
+            }
+            let node = match fragment.node.data {
+                NodeData::Element { .. } => Some(Rc::clone(&fragment.node)),
+                _ => fragment.node.parent.borrow().upgrade(),
+            };
+            let owned = match (&node, &owner.node) {
+                (Some(node), Some(owner)) => Rc::ptr_eq(node, owner),
+                _ => false,
+            };
+            if let Some(node) = node
+                && !owned
+            {
+                return Some(Hit {
+                    node,
+                    rect: fragment.rect,
+                    margin: Sides::default(),
+                    border: Sides::default(),
+                    padding: Sides::default(),
+                });
+            }
+        }
+    }
+
+    let node = owner.node.as_ref()?;
+    if !layout_box.border_box().contains(x, y)
+        || node.element_name().is_none()
+        || !owner.style.is_visible()
+    {
+        return None;
+    }
+    Some(Hit {
+        node: Rc::clone(node),
+        rect: owner.rect,
+        margin: owner.margin,
+        border: owner.border,
+        padding: owner.padding,
+    })
+}
+
+fn layout_block(
+    node: &Rc<Node>,
+    containing: Rect,
+    cursor: f32,
+    options: &LayoutOptions,
+    cache: &mut LayoutCache,
+) -> LayoutBox {
+    if let Some(cached) = cache.reuse(node, containing, cursor) {
+        return cached;
+    }
+
+    let style = style_of(node);
+    let mut layout_box = LayoutBox::new(Some(Rc::clone(node)), Rc::clone(&style));
+    let base = containing.width;
+    layout_box.containing_width = base;
+
+    layout_box.margin = style.margin.map(|d| d.resolve_or_zero(base));
+    layout_box.padding = style.padding.map(|d| d.resolve_or_zero(base));
+    layout_box.border = style.border_width;
+
+    let edges = layout_box.margin.horizontal()
+        + layout_box.border.horizontal()
+        + layout_box.padding.horizontal();
+    let (inset_x, inset_y) = match style.box_sizing {
+        BoxSizing::ContentBox => (0.0, 0.0),
+        BoxSizing::BorderBox => (
+            layout_box.border.horizontal() + layout_box.padding.horizontal(),
+            layout_box.border.vertical() + layout_box.padding.vertical(),
+        ),
+    };
+    let content = |size: Option<f32>, inset: f32| size.map(|size| (size - inset).max(0.0));
+    let width = clamp_size(
+        content(style.width.resolve(base), inset_x).unwrap_or(base - edges),
+        content(style.min_width.resolve(base), inset_x),
+        content(style.max_width.resolve(base), inset_x),
+    )
+    .max(0.0);
+    let free = (base - edges - width).max(0.0);
+    match (style.margin.left, style.margin.right) {
+        (Dimension::Auto, Dimension::Auto) => {
+            layout_box.margin.left = free / 2.0;
+            layout_box.margin.right = free / 2.0;
+        }
+        (Dimension::Auto, _) => layout_box.margin.left = free,
+        (_, Dimension::Auto) => layout_box.margin.right = free,
+        _ => {}
+    }
+
+    let x = match containing_direction(node) {
+        Direction::Ltr => {
+            containing.x + layout_box.margin.left + layout_box.border.left + layout_box.padding.left
+        }
+        Direction::Rtl => {
+            containing.right()
+                - layout_box.margin.right
+                - layout_box.border.right
+                - layout_box.padding.right
+                - width
+        }
+    };
+    layout_box.rect = Rect::new(
+        x,
+        cursor + layout_box.margin.top + layout_box.border.top + layout_box.padding.top,
+        width,
+        0.0,
+    );
+    let root = node
+        .parent
+        .borrow()
+        .upgrade()
+        .is_none_or(|parent| !matches!(parent.data, NodeData::Element { .. }));
+    let through_top = !root && layout_box.border.top == 0.0 && layout_box.padding.top == 0.0;
+    let through_bottom = !root
+        && layout_box.border.bottom == 0.0
+        && layout_box.padding.bottom == 0.0
+        && style.height == Dimension::Auto
+        && style.aspect_ratio.preferred(None).is_none()
+        && fixed_size(style.min_height).unwrap_or(0.0) <= 0.0;
+    let adjoining = if node.element_name() == Some("iframe") {
+        let document = node.content_document.borrow().clone();
+        layout_box.frame = document.map(|document| {
+            let options = LayoutOptions {
+                viewport_width: width,
+                ..*options
+            };
+            Box::new(LayoutTree::build(&document, options))
+        });
+        AdjoiningMargins::default()
+    } else {
+        layout_block_contents(
+            &mut layout_box,
+            node,
+            options,
+            cache,
+            through_top,
+            through_bottom,
+        )
+    };
+    let mut top = MarginStrut::default();
+    top.add(layout_box.margin.top);
+    top.merge(adjoining.top);
+    let dy = top.collapsed() - layout_box.margin.top;
+    layout_box.margin.top = top.collapsed();
+    layout_box.translate(0.0, dy);
+    let mut bottom = adjoining.bottom;
+    bottom.add(layout_box.margin.bottom);
+    layout_box.margin.bottom = bottom.collapsed();
+
+    if let Some(height) = content(fixed_size(style.height), inset_y) {
+        layout_box.rect.height = height;
+    } else if let Some(ratio) = style.aspect_ratio.preferred(None) {
+        let sized = match style.box_sizing {
+            BoxSizing::ContentBox => width / ratio,
+            BoxSizing::BorderBox => ((width + inset_x) / ratio - inset_y).max(0.0),
+        };
+        layout_box.rect.height = layout_box.rect.height.max(sized);
+    }
+    layout_box.rect.height = clamp_size(
+        layout_box.rect.height,
+        content(fixed_size(style.min_height), inset_y),
+        content(fixed_size(style.max_height), inset_y),
+    )
+    .max(0.0);
+    layout_box
+}
+
+pub(crate) fn clamp_size(size: f32, min: Option<f32>, max: Option<f32>) -> f32 {
+    let size = max.map_or(size, |max| size.min(max));
+    min.map_or(size, |min| size.max(min))
+}
+
+pub(crate) fn fixed_size(size: Dimension) -> Option<f32> {
+    match size {
+        Dimension::Px(px) => Some(px),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct MarginStrut {
+    positive: f32,
+    negative: f32,
+}
+
+impl MarginStrut {
+    fn add(&mut self, margin: f32) {
+        self.positive = self.positive.max(margin);
+        self.negative = self.negative.min(margin);
+    }
+
+    fn merge(&mut self, other: MarginStrut) {
+        self.add(other.positive);
+        self.add(other.negative);
+    }
+
+    fn collapsed(&self) -> f32 {
+        self.positive + self.negative
+    }
+}
+
+#[derive(Default)]
+struct AdjoiningMargins {
+    top: MarginStrut,
+    bottom: MarginStrut,
+}
+
+fn collapses_through(layout_box: &LayoutBox) -> bool {
+    layout_box.rect.height == 0.0
+        && layout_box.lines.is_empty()
+        && layout_box.border.top == 0.0
+        && layout_box.border.bottom == 0.0
+        && layout_box.padding.top == 0.0
+        && layout_box.padding.bottom == 0.0
+}
+
+fn layout_block_contents(
+    layout_box: &mut LayoutBox,
+    node: &Rc<Node>,
+    options: &LayoutOptions,
+    cache: &mut LayoutCache,
+    through_top: bool,
+    through_bottom: bool,
+) -> AdjoiningMargins {
+    let children: Vec<Rc<Node>> = box_children(node)
+        .into_iter()
+        .filter(|child| display_of(child) != Display::None)
+        .collect();
+
+    let rect = layout_box.rect;
+    let style = Rc::clone(&layout_box.style);
+    let has_blocks = children
+        .iter()
+        .any(|child| display_of(child) == Display::Block);
+
+    let mut adjoining = AdjoiningMargins::default();
+    if !has_blocks {
+        layout_box.lines = inline::layout_lines(&children, rect, &style, options);
+        layout_box.rect.height = layout_box
+            .lines
+            .last()
+            .map(|line| line.rect.bottom() - rect.y)
+            .unwrap_or(0.0);
+        return adjoining;
+    }
+
+    let mut cursor = rect.y;
+    let mut strut = MarginStrut::default();
+    let mut top_adjoining = through_top;
+    let mut pending_inline: Vec<Rc<Node>> = Vec::new();
+
+    let flush = |pending: &mut Vec<Rc<Node>>,
+                 cursor: &mut f32,
+                 strut: &mut MarginStrut,
+                 top_adjoining: &mut bool,
+                 out: &mut Vec<LayoutBox>| {
+        if pending.is_empty() {
+            return;
+        }
+        let top = *cursor + strut.collapsed();
+        let area = Rect::new(rect.x, top, rect.width, 0.0);
+        let lines = inline::layout_lines(pending, area, &style, options);
+        pending.clear();
+        if lines.is_empty() {
+            return;
+        }
+        let mut anonymous = LayoutBox::new(None, Rc::clone(&style));
+        let height = lines
+            .last()
+            .map(|line| line.rect.bottom() - top)
+            .unwrap_or(0.0);
+        anonymous.rect = Rect::new(rect.x, top, rect.width, height);
+        anonymous.lines = lines;
+        *cursor = top + height;
+        *strut = MarginStrut::default();
+        *top_adjoining = false;
+        out.push(anonymous);
+    };
+
+    for child in children {
+        if display_of(&child) != Display::Block {
+            pending_inline.push(child);
+            continue;
+        }
+        flush(
+            &mut pending_inline,
+            &mut cursor,
+            &mut strut,
+            &mut top_adjoining,
+            &mut layout_box.children,
+        );
+        let mut block = layout_block(&child, rect, cursor, options, cache);
+        let through = collapses_through(&block);
+        let target = if top_adjoining {
+            adjoining.top.add(block.margin.top);
+            if through {
+                adjoining.top.add(block.margin.bottom);
+            }
+            cursor
+        } else {
+            let mut before = strut;
+            before.add(block.margin.top);
+            if through {
+                strut.add(block.margin.top);
+                strut.add(block.margin.bottom);
+            }
+            cursor + before.collapsed()
+        };
+        block.translate(0.0, target - block.border_box().y);
+        if !through {
+            cursor = block.border_box().bottom();
+            strut = MarginStrut::default();
+            strut.add(block.margin.bottom);
+            top_adjoining = false;
+        }
+        layout_box.children.push(block);
+    }
+    flush(
+        &mut pending_inline,
+        &mut cursor,
+        &mut strut,
+        &mut top_adjoining,
+        &mut layout_box.children,
+    );
+
+    if through_bottom && !top_adjoining {
+        adjoining.bottom = strut;
+    } else {
+        cursor += strut.collapsed();
+    }
+    layout_box.rect.height = cursor - rect.y;
+    adjoining
+}
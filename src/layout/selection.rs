@@ -0,0 +1,152 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::inline::{Fragment, FragmentKind};
+use super::{LayoutBox, LayoutTree, Rect};
+use crate::dom::{Node, NodeData};
+use crate::range::{BoundaryPoint, Range, node_length};
+
+const SOFT_HYPHEN: char = '\u{ad}';
+
+struct TextRun<'a> {
+    fragment: &'a Fragment,
+    chars: Vec<char>,
+    offsets: Vec<usize>,
+}
+
+impl TextRun<'_> {
+    fn prefix_width(&self, tree: &LayoutTree, count: usize) -> f32 {
+        let prefix: String = self.chars[..count].iter().collect();
+        tree.options.text_width(&self.fragment.style, &prefix)
+    }
+
+    fn distance(&self, x: f32, y: f32) -> (f32, f32) {
+        let rect = self.fragment.rect;
+        let dy = (rect.y - y).max(y - rect.bottom()).max(0.0);
+        let dx = (rect.x - x).max(x - rect.right()).max(0.0);
+        (dy, dx)
+    }
+}
+
+pub fn position_at(tree: &LayoutTree, x: f32, y: f32) -> Option<BoundaryPoint> {
+    let runs = text_runs(tree);
+    let run = runs.iter().min_by(|a, b| {
+        a.distance(x, y)
+            .partial_cmp(&b.distance(x, y))
+            .unwrap_or(Ordering::Equal)
+    })?;
+    let index = (0..=run.chars.len())
+        .min_by(|&a, &b| {
+            let a = (run.fragment.rect.x + run.prefix_width(tree, a) - x).abs();
+            let b = (run.fragment.rect.x + run.prefix_width(tree, b) - x).abs();
+            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+        })
+        .unwrap_or(0);
+    Some(BoundaryPoint::new(&run.fragment.node, run.offsets[index]))
+}
+
+pub fn selection_rects(tree: &LayoutTree, range: &Range) -> Vec<Rect> {
+    if range.collapsed() {
+        return Vec::new();
+    }
+    let mut rects = Vec::new();
+    for run in text_runs(tree) {
+        let node = &run.fragment.node;
+        let length = node_length(node);
+        let from = if Rc::ptr_eq(&range.start.node, node) {
+            range.start.offset
+        } else if range.start.compare(&BoundaryPoint::new(node, 0)) != Ordering::Greater {
+            0
+        } else {
+            continue;
+        };
+        let to = if Rc::ptr_eq(&range.end.node, node) {
+            range.end.offset
+        } else if range.end.compare(&BoundaryPoint::new(node, length)) != Ordering::Less {
+            length
+        } else {
+            continue;
+        };
+        let selected: Vec<usize> = (0..run.chars.len())
+            .filter(|&i| {
+                run.offsets[i] >= from
+                    && run.offsets[i + 1] <= to
+                    && run.offsets[i] < run.offsets[i + 1]
+            })
+            .collect();
+        let (Some(&first), Some(&last)) = (selected.first(), selected.last()) else {
+            continue;
+        };
+        let left = run.fragment.rect.x + run.prefix_width(tree, first);
+        let right = run.fragment.rect.x + run.prefix_width(tree, last + 1);
+        rects.push(Rect::new(
+            left,
+            run.fragment.rect.y,
+            right - left,
+            run.fragment.rect.height,
+        ));
+    }
+    rects
+}
+
+fn text_runs(tree: &LayoutTree) -> Vec<TextRun<'_>> {
+    let mut runs = Vec::new();
+    let mut cursors = HashMap::new();
+    collect_runs(&tree.root, &mut cursors, &mut runs);
+    runs
+}
+
+fn collect_runs<'a>(
+    layout_box: &'a LayoutBox,
+    cursors: &mut HashMap<*const Node, usize>,
+    runs: &mut Vec<TextRun<'a>>,
+) {
+    for fragment in layout_box.lines.iter().flat_map(|line| &line.fragments) {
+        let (FragmentKind::Text(text), NodeData::Text { contents }) =
+            (&fragment.kind, &fragment.node.data)
+        else {
+            continue;
+        };
+        let source: Vec<char> = contents.borrow().chars().collect();
+        let cursor = cursors.entry(Rc::as_ptr(&fragment.node)).or_default();
+        let chars: Vec<char> = text.chars().collect();
+        let offsets = align(&source, cursor, &chars);
+        runs.push(TextRun {
+            fragment,
+            chars,
+            offsets,
+        });
+    }
+    for child in &layout_box.children {
+        collect_runs(child, cursors, runs);
+    }
+}
+
+fn align(source: &[char], cursor: &mut usize, chars: &[char]) -> Vec<usize> {
+    let skippable = |c: char| c.is_whitespace() || c == SOFT_HYPHEN;
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    for &c in chars {
+        if c.is_whitespace() {
+            offsets.push(*cursor);
+            while *cursor < source.len() && source[*cursor].is_whitespace() {
+                *cursor += 1;
+            }
+            continue;
+        }
+        let mut probe = *cursor;
+        while probe < source.len() && skippable(source[probe]) {
+            probe += 1;
+        }
+        if probe < source.len() && source[probe].to_lowercase().eq(c.to_lowercase()) {
+            offsets.push(probe);
+            *cursor = probe + 1;
+        } else {
+            offsets.push(*cursor);
+        }
+    }
+    offsets.push(*cursor);
+    offsets
+}
@@ -1,2 +1,46 @@
+pub mod accessibility;
+pub mod aria;
+pub mod cdp;
+pub mod clipboard;
+pub mod console;
+pub mod css;
+pub mod custom_elements;
+pub mod diagnostics;
+pub mod diff;
 pub mod dom;
+pub mod extract;
+pub mod feed;
+pub mod find;
+pub mod frame;
+pub mod history;
 pub mod html;
+pub mod image;
+pub mod internal;
+pub mod layout;
+pub mod loader;
+pub mod location;
+pub mod memory;
+pub mod metadata;
+pub mod mutation;
+pub mod net;
+pub mod outline;
+pub mod paint;
+pub mod performance;
+pub mod profile;
+pub mod range;
+pub mod reader;
+pub mod refresh;
+pub mod render;
+pub mod sanitize;
+pub mod settings;
+pub mod shell;
+pub mod source;
+pub mod storage;
+pub mod style;
+pub mod text;
+pub mod units;
+pub mod web_font;
+pub mod woff2;
+
+pub use sanitize::sanitize;
+pub use text::{LinkStyle, TableStyle, TextOptions, to_text};
@@ -0,0 +1,412 @@
+use anyhow::{Context, Result};
+use std::io::Read;
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+use std::time::Instant;
+use std::vec::Vec;
+use url::Url;
+
+use crate::dom::Document;
+use crate::feed::FeedKind;
+use crate::html::escape;
+use crate::html::parser::{ParseOptions, StreamingParser};
+use crate::image;
+use crate::internal;
+use crate::metadata::PageMetadata;
+use crate::mutation;
+use crate::net::multipart::{self, Multipart};
+use crate::net::{self, Response, download};
+use crate::performance::Performance;
+use crate::settings::Settings;
+
+// Loads are returned once per navigation, so boxing the document isn't worth
+// the churn at every call site.
+#[allow(clippy::large_enum_variant)]
+pub enum Load {
+    Document(Document),
+    Download(Response),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Html,
+    Text,
+    Image,
+}
+
+impl DocumentKind {
+    pub fn of(content_type: Option<&str>) -> Option<DocumentKind> {
+        match content_type {
+            None | Some("text/html" | "application/xhtml+xml") => Some(DocumentKind::Html),
+            Some(
+                "text/plain"
+                | "text/css"
+                | "text/javascript"
+                | "text/csv"
+                | "text/markdown"
+                | "application/javascript"
+                | "application/json",
+            ) => Some(DocumentKind::Text),
+            Some(mime) if image::can_decode(mime) => Some(DocumentKind::Image),
+            Some(_) => None,
+        }
+    }
+}
+
+pub fn can_render(content_type: Option<&str>) -> bool {
+    DocumentKind::of(content_type).is_some()
+}
+
+const TEXT_PREFIX: &str = "<!DOCTYPE html>\n<html>\n<head>\n<meta name=\"color-scheme\" content=\"light dark\">\n</head>\n<body>\n<pre style=\"white-space: pre-wrap\">";
+const TEXT_SUFFIX: &str = "</pre>\n</body>\n</html>\n";
+const CHUNK_SIZE: usize = 16 * 1024;
+
+pub fn text_document(text: &str) -> String {
+    format!("{}{}{}", TEXT_PREFIX, escape(text), TEXT_SUFFIX)
+}
+
+pub fn image_document(url: &Url) -> String {
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .and_then(download::percent_decode)
+        .unwrap_or_else(|| url.to_string());
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<title>{}</title>\n</head>\n<body style=\"margin: 0; text-align: center\">\n<img src=\"{}\" alt=\"{}\">\n</body>\n</html>\n",
+        escape(&name),
+        escape(url.as_str()),
+        escape(&name)
+    )
+}
+
+fn is_single_byte(charset: Option<&str>) -> bool {
+    matches!(
+        charset.map(|c| c.to_ascii_lowercase()).as_deref(),
+        Some("iso-8859-1") | Some("latin1") | Some("windows-1252") | Some("us-ascii")
+    )
+}
+
+pub fn decode(bytes: &[u8], charset: Option<&str>) -> String {
+    if is_single_byte(charset) {
+        bytes.iter().map(|&b| b as char).collect()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+pub struct Decoder {
+    single_byte: bool,
+    pending: Vec<u8>,
+}
+
+impl Decoder {
+    pub fn new(charset: Option<&str>) -> Self {
+        Decoder {
+            single_byte: is_single_byte(charset),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        if self.single_byte {
+            return bytes.iter().map(|&b| b as char).collect();
+        }
+        self.pending.extend_from_slice(bytes);
+        let split = self.pending.len() - incomplete_tail(&self.pending);
+        let tail = self.pending.split_off(split);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = tail;
+        text
+    }
+
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xc0 == 0x80 {
+            continue;
+        }
+        let needed = match byte {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 1,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Progress {
+    Unchanged,
+    Updated,
+    Replaced,
+}
+
+struct Part {
+    kind: DocumentKind,
+    decoder: Decoder,
+    parser: StreamingParser,
+    source: String,
+}
+
+impl Part {
+    fn new(
+        kind: DocumentKind,
+        charset: Option<&str>,
+        url: Option<&Url>,
+        options: ParseOptions,
+    ) -> Self {
+        let mut part = Part {
+            kind,
+            decoder: Decoder::new(charset),
+            parser: StreamingParser::new(options),
+            source: String::new(),
+        };
+        match (kind, url) {
+            (DocumentKind::Text, _) => part.parser.feed(TEXT_PREFIX),
+            (DocumentKind::Image, Some(url)) => {
+                part.source = image_document(url);
+                part.parser.feed(&part.source);
+            }
+            _ => {}
+        }
+        part
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        if self.kind == DocumentKind::Image {
+            return;
+        }
+        let text = self.decoder.decode(bytes);
+        self.feed(text);
+    }
+
+    fn feed(&mut self, text: String) {
+        match self.kind {
+            DocumentKind::Html => self.parser.feed(&text),
+            DocumentKind::Text => self.parser.feed(&escape(&text)),
+            DocumentKind::Image => return,
+        }
+        self.source.push_str(&text);
+    }
+
+    fn finish(mut self) -> (Document, String) {
+        let text = self.decoder.finish();
+        self.feed(text);
+        if self.kind == DocumentKind::Text {
+            self.parser.feed(TEXT_SUFFIX);
+        }
+        (self.parser.finish(), self.source)
+    }
+}
+
+pub struct DocumentStream {
+    document: Document,
+    part: Option<Part>,
+    multipart: Option<Multipart>,
+    body: Option<Box<dyn Read + Send>>,
+    options: ParseOptions,
+}
+
+impl DocumentStream {
+    fn new(document: Document, options: ParseOptions) -> Self {
+        DocumentStream {
+            document,
+            part: None,
+            multipart: None,
+            body: None,
+            options,
+        }
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.document
+    }
+
+    pub fn take_body(&mut self) -> Option<Box<dyn Read + Send>> {
+        self.body.take()
+    }
+
+    fn start_part(&mut self, kind: DocumentKind, charset: Option<&str>) {
+        let url = self.document.url().cloned();
+        self.part = Some(Part::new(kind, charset, url.as_ref(), self.options));
+    }
+
+    fn adopt_part_root(&mut self) {
+        if let Some(part) = &self.part {
+            self.document.root = part.parser.root();
+        }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) -> Progress {
+        let Some(multipart) = &mut self.multipart else {
+            return match &mut self.part {
+                Some(part) => {
+                    part.push(bytes);
+                    Progress::Updated
+                }
+                None => Progress::Unchanged,
+            };
+        };
+        let parts = multipart.push(bytes);
+        self.push_parts(parts)
+    }
+
+    fn push_parts(&mut self, parts: Vec<multipart::Part>) -> Progress {
+        let mut progress = Progress::Unchanged;
+        for part in parts {
+            match part {
+                multipart::Part::Headers(headers) => {
+                    let content_type = net::find_header(&headers, "Content-Type");
+                    self.part = None;
+                    match DocumentKind::of(content_type.and_then(net::mime_essence).as_deref()) {
+                        Some(kind @ (DocumentKind::Html | DocumentKind::Text)) => {
+                            let charset =
+                                content_type.and_then(|value| net::mime_param(value, "charset"));
+                            self.start_part(kind, charset.as_deref());
+                        }
+                        _ => log::warn!("skipping part of type {:?}", content_type),
+                    }
+                }
+                multipart::Part::Data(bytes) => {
+                    if let Some(part) = &mut self.part {
+                        part.push(&bytes);
+                    }
+                }
+                multipart::Part::End => {
+                    if let Some(part) = self.part.take() {
+                        self.replace(part);
+                        progress = Progress::Replaced;
+                    }
+                }
+            }
+        }
+        progress
+    }
+
+    fn replace(&mut self, part: Part) {
+        let (parsed, source) = part.finish();
+        let mut document = Document::new();
+        document.root = parsed.root;
+        document.scripting = parsed.scripting;
+        document.response = self.document.response.clone();
+        document.performance = Rc::clone(&self.document.performance);
+        document.settings = Arc::clone(&self.document.settings);
+        document.storage = self.document.storage.clone();
+        document.source = Some(Rc::from(source));
+        self.document = document;
+    }
+
+    pub fn read_to_end(mut self) -> Result<Document> {
+        if let Some(mut body) = self.take_body() {
+            let mut buffer = vec![0; CHUNK_SIZE];
+            loop {
+                let read = body.read(&mut buffer).context("reading response body")?;
+                if read == 0 {
+                    break;
+                }
+                self.push(&buffer[..read]);
+            }
+        }
+        Ok(self.finish())
+    }
+
+    pub fn finish(mut self) -> Document {
+        if let Some(multipart) = &mut self.multipart {
+            let parts = multipart.finish();
+            self.push_parts(parts);
+        } else if let Some(part) = self.part.take() {
+            let (parsed, source) = part.finish();
+            self.document.scripting = parsed.scripting;
+            self.document.source = Some(Rc::from(source));
+        }
+
+        let mut document = self.document;
+        if let Some(info) = &mut document.response {
+            info.timing.response_end = Some(Instant::now());
+            document.performance.record_response(&info.timing);
+        }
+        if let Some(url) = document.url() {
+            internal::record_visit(url, PageMetadata::from_document(&document).title);
+        }
+        document.performance.mark_dom_parsed();
+        mutation::notify_observers();
+        document
+    }
+}
+
+pub enum Open {
+    Document(Box<DocumentStream>),
+    Download(Response),
+}
+
+pub fn open(input: &str, settings: &Settings) -> Result<Open> {
+    let navigation_start = Instant::now();
+    let url = net::parse_url(input)?;
+    let options = settings.parse_options();
+    let mut document = Document::new();
+    document.settings = Arc::new(settings.clone());
+    document.performance = Rc::new(Performance::new(navigation_start));
+    if url.as_str() == "about:blank" {
+        let mut stream = DocumentStream::new(document, options);
+        stream.start_part(DocumentKind::Html, None);
+        stream.adopt_part_root();
+        return Ok(Open::Document(Box::new(stream)));
+    }
+    let mut response = net::fetch(&url, settings)?;
+
+    let content_type = response
+        .header("Content-Type")
+        .unwrap_or_default()
+        .to_string();
+    let multipart = Multipart::for_content_type(&content_type);
+    let kind = match multipart {
+        Some(_) => None,
+        None => match DocumentKind::of(response.content_type().as_deref()) {
+            Some(kind) => Some(kind),
+            None if FeedKind::of(&content_type).is_some() => {
+                response = internal::feed_response(response)?;
+                Some(DocumentKind::Html)
+            }
+            None => return Ok(Open::Download(response)),
+        },
+    };
+
+    let info = response.info();
+    let charset = info.charset();
+    let body = std::mem::replace(&mut response.body, Box::new(std::io::empty()));
+    document.response = Some(info);
+    let mut stream = DocumentStream::new(document, options);
+    stream.multipart = multipart;
+    stream.body = Some(body);
+    if let Some(kind) = kind {
+        stream.start_part(kind, charset.as_deref());
+    }
+    stream.adopt_part_root();
+    Ok(Open::Document(Box::new(stream)))
+}
+
+pub fn load(input: &str) -> Result<Load> {
+    load_with(input, &Settings::default())
+}
+
+pub fn load_with(input: &str, settings: &Settings) -> Result<Load> {
+    match open(input, settings)? {
+        Open::Document(stream) => Ok(Load::Document(stream.read_to_end()?)),
+        Open::Download(response) => Ok(Load::Download(response)),
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Context, Result, bail};
+use std::rc::Rc;
+use std::string::String;
+use url::Url;
+
+use crate::dom::{Document, Node};
+use crate::net::download::percent_decode;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Navigation {
+    Load { url: String, replace: bool },
+    Fragment { url: String, fragment: String },
+    Reload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    url: Url,
+}
+
+impl Location {
+    pub fn new(url: Url) -> Self {
+        Location { url }
+    }
+
+    pub fn parse(url: &str) -> Result<Self> {
+        Ok(Location::new(
+            Url::parse(url).with_context(|| format!("invalid URL: {}", url))?,
+        ))
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn href(&self) -> &str {
+        self.url.as_str()
+    }
+
+    pub fn origin(&self) -> String {
+        self.url.origin().ascii_serialization()
+    }
+
+    pub fn protocol(&self) -> String {
+        format!("{}:", self.url.scheme())
+    }
+
+    pub fn host(&self) -> String {
+        match (self.url.host_str(), self.url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => String::new(),
+        }
+    }
+
+    pub fn hostname(&self) -> &str {
+        self.url.host_str().unwrap_or_default()
+    }
+
+    pub fn port(&self) -> String {
+        self.url
+            .port()
+            .map(|port| port.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn pathname(&self) -> &str {
+        self.url.path()
+    }
+
+    pub fn search(&self) -> String {
+        match self.url.query() {
+            Some(query) if !query.is_empty() => format!("?{}", query),
+            _ => String::new(),
+        }
+    }
+
+    pub fn hash(&self) -> String {
+        match self.url.fragment() {
+            Some(fragment) if !fragment.is_empty() => format!("#{}", fragment),
+            _ => String::new(),
+        }
+    }
+
+    pub fn assign(&self, url: &str) -> Result<Navigation> {
+        self.navigate(url, false)
+    }
+
+    pub fn replace(&self, url: &str) -> Result<Navigation> {
+        self.navigate(url, true)
+    }
+
+    pub fn set_href(&self, url: &str) -> Result<Navigation> {
+        self.assign(url)
+    }
+
+    pub fn set_hash(&self, hash: &str) -> Navigation {
+        let mut url = self.url.clone();
+        url.set_fragment(Some(hash.strip_prefix('#').unwrap_or(hash)));
+        Navigation::Fragment {
+            fragment: url.fragment().unwrap_or_default().to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    pub fn reload(&self) -> Navigation {
+        Navigation::Reload
+    }
+
+    fn navigate(&self, url: &str, replace: bool) -> Result<Navigation> {
+        let target = self
+            .url
+            .join(url.trim())
+            .with_context(|| format!("SyntaxError: invalid URL {}", url))?;
+        if target.scheme() == "javascript" {
+            bail!("javascript: URLs can't be navigated to without a script engine");
+        }
+        if let Some(fragment) = target.fragment()
+            && !replace
+            && same_document(&self.url, &target)
+        {
+            return Ok(Navigation::Fragment {
+                fragment: fragment.to_string(),
+                url: target.to_string(),
+            });
+        }
+        Ok(Navigation::Load {
+            url: target.to_string(),
+            replace,
+        })
+    }
+}
+
+pub fn same_document(a: &Url, b: &Url) -> bool {
+    a[..url::Position::AfterQuery] == b[..url::Position::AfterQuery]
+}
+
+pub fn fragment_target(document: &Document, fragment: &str) -> Option<Rc<Node>> {
+    let decoded = percent_decode(fragment);
+    for id in [Some(fragment), decoded.as_deref()].into_iter().flatten() {
+        if id.is_empty() {
+            continue;
+        }
+        if let Some(node) = document.get_element_by_id(id) {
+            return Some(node);
+        }
+        let anchor = document
+            .get_elements_by_tag_name("a")
+            .into_iter()
+            .find(|anchor| anchor.get_attribute("name").as_deref() == Some(id));
+        if anchor.is_some() {
+            return anchor;
+        }
+    }
+    None
+}
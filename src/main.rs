@@ -1,7 +1,750 @@
+use icarus::cdp;
+use icarus::clipboard;
+use icarus::console::Console;
+use icarus::diff;
+use icarus::frame::Frame;
+use icarus::html::escape::CharacterReferences;
+use icarus::html::format::SerializeOptions;
 use icarus::html::parser;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::loader::{self, Load};
+use icarus::metadata::PageMetadata;
+use icarus::net::log::NetworkLog;
+use icarus::net::websocket::{MessageData, ReadyState, WebSocket, WebSocketEvent};
+use icarus::net::{self, Response, download};
+use icarus::outline;
+use icarus::profile::Profiler;
+use icarus::reader;
+use icarus::refresh::Refresh;
+use icarus::render::pdf::{self, PageSize, PrintOptions};
+use icarus::render::{self, svg, tty};
+use icarus::settings::Settings;
+use icarus::shell::{self, inspector};
+use icarus::source;
+use icarus::storage::{LocalStorage, StorageContext};
+use icarus::style::{Sides, style_of};
+use icarus::{LinkStyle, TableStyle, TextOptions};
 use parser::parse_html;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+
+const MAX_REFRESHES: usize = 10;
+const RENDER_WIDTH: usize = 800;
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let profiler = args.iter().any(|arg| arg == "--profile").then(|| {
+        args.retain(|arg| arg != "--profile");
+        let profiler = Profiler::new();
+        profiler.install();
+        profiler
+    });
+
+    let settings = match Settings::from_args(&mut args) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("icarus: {:#}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match args.first().map(|s| s.as_str()) {
+        None => {
+            demo();
+            Ok(())
+        }
+        Some("download") => run_download(&args[1..], &settings),
+        Some("text") => run_text(&args[1..], &settings),
+        Some("render") => run_render(&args[1..], &settings),
+        Some("print") => run_print(&args[1..], &settings),
+        Some("view") => run_view(&args[1..], &settings),
+        Some("source") => run_source(&args[1..], &settings),
+        Some("diff") => run_diff(&args[1..], &settings),
+        Some("diff-render") => run_diff_render(&args[1..], &settings),
+        Some("devtools") => run_devtools(&args[1..], &settings),
+        Some("reader") => run_reader(&args[1..], &settings),
+        Some("accessibility") => run_accessibility(&args[1..], &settings),
+        Some("audit") => run_audit(&args[1..], &settings),
+        Some("metadata") => run_metadata(&args[1..], &settings),
+        Some("extract") => run_extract(&args[1..], &settings),
+        Some("outline") => run_outline(&args[1..], &settings),
+        Some("dump-layout") => run_dump_layout(&args[1..], &settings),
+        Some("dump-style") => run_dump_style(&args[1..], &settings),
+        Some("format") => run_format(&args[1..], &settings),
+        Some("websocket") => run_websocket(&args[1..], &settings),
+        Some(_) => run_open(&args, &settings),
+    };
+
+    if let Some(profiler) = profiler {
+        eprintln!("\nProfile:");
+        eprint!("{}", profiler.report());
+    }
+    if let Err(err) = result {
+        eprintln!("icarus: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+fn print_console(console: &Console) {
+    let messages = console.messages();
+    if messages.is_empty() {
+        return;
+    }
+    let colored = std::io::stdout().is_terminal();
+    println!("\nConsole:");
+    for message in messages {
+        if colored {
+            println!("{}", message.to_colored_string());
+        } else {
+            println!("[{}] {}", message.level, message);
+        }
+    }
+}
+
+fn run_open(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let log_network = args.iter().any(|arg| arg == "--log-network");
+    let show_timing = args.iter().any(|arg| arg == "--timing");
+    let show_memory = args.iter().any(|arg| arg == "--memory");
+    let har_path = args
+        .iter()
+        .position(|arg| arg == "--har")
+        .and_then(|index| args.get(index + 1));
+    let Some(url) = args
+        .iter()
+        .enumerate()
+        .find(|(index, arg)| !arg.starts_with("--") && (*index == 0 || args[index - 1] != "--har"))
+        .map(|(_, arg)| arg)
+    else {
+        anyhow::bail!(
+            "usage: icarus <url> [--no-refresh] [--log-network] [--timing] [--memory] [--har <file>]"
+        );
+    };
+    let mut url = url.clone();
+
+    let network_log = NetworkLog::new();
+    if log_network || har_path.is_some() {
+        network_log.start();
+    }
+
+    for _ in 0..=MAX_REFRESHES {
+        let document = match loader::load_with(&url, settings)? {
+            Load::Document(document) => document,
+            Load::Download(response) => {
+                println!(
+                    "Can't render {}, downloading instead",
+                    response.content_type().unwrap_or_default()
+                );
+                return save_download(response, Path::new("."));
+            }
+        };
+        let mut frame = Frame::new(document);
+        frame.load_subframes();
+        frame.document.performance.mark_load_complete();
+        frame.document.print_tree();
+        if !frame.children.is_empty() {
+            println!("\nFrames:");
+            frame.print_tree();
+        }
+        print_console(&frame.document.console);
+        if show_timing {
+            println!("\nTiming:");
+            print!("{}", frame.document.performance.metrics().summary());
+        }
+        if show_memory {
+            println!("\nMemory:");
+            print!("{}", frame.document.memory_stats().summary());
+        }
+        if log_network {
+            println!("\nNetwork:");
+            print!("{}", network_log.summary());
+        }
+        if let Some(path) = har_path {
+            network_log.save_har(Path::new(path))?;
+            println!("\nWrote HAR to {}", path);
+        }
+        network_log.clear();
+        let document = frame.document;
+
+        if !settings.refresh {
+            return Ok(());
+        }
+        let Some(refresh) = Refresh::from_document(&document) else {
+            return Ok(());
+        };
+        let Some(target) = refresh.url.filter(|target| Some(target) != document.url()) else {
+            return Ok(());
+        };
+
+        println!("\nRefreshing to {} in {:?}...\n", target, refresh.delay);
+        std::thread::sleep(refresh.delay);
+        url = target.to_string();
+    }
+
+    anyhow::bail!("too many refreshes")
+}
+
+fn run_text(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut copy = false;
+    let mut attribute = None;
+    let mut references = CharacterReferences::Decode;
+    let mut format = None;
+    let mut url = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let options = || format.unwrap_or_default();
+        match arg.as_str() {
+            "--copy" => copy = true,
+            "--attr" => attribute = rest.next(),
+            "--keep-refs" => references = CharacterReferences::Keep,
+            "--width" => {
+                let width = rest.next().and_then(|width| width.parse().ok());
+                let Some(width) = width else {
+                    anyhow::bail!("--width expects a number of columns");
+                };
+                format = Some(TextOptions { width, ..options() });
+            }
+            "--links" => {
+                let links = match rest.next().map(String::as_str) {
+                    Some("footnotes") => LinkStyle::Footnotes,
+                    Some("inline") => LinkStyle::Inline,
+                    Some("none") => LinkStyle::Hidden,
+                    _ => anyhow::bail!("--links expects footnotes, inline or none"),
+                };
+                format = Some(TextOptions { links, ..options() });
+            }
+            "--tables" => {
+                let tables = match rest.next().map(String::as_str) {
+                    Some("ascii") => TableStyle::Ascii,
+                    Some("plain") => TableStyle::Plain,
+                    _ => anyhow::bail!("--tables expects ascii or plain"),
+                };
+                format = Some(TextOptions {
+                    tables,
+                    ..options()
+                });
+            }
+            "--emphasis" => {
+                format = Some(TextOptions {
+                    emphasis: true,
+                    ..options()
+                })
+            }
+            _ => url = Some(arg),
+        }
+    }
+    let Some(url) = url else {
+        anyhow::bail!(
+            "usage: icarus text <url> [--copy] [--attr <name> [--keep-refs]] [--width <columns>] [--links footnotes|inline|none] [--tables ascii|plain] [--emphasis]"
+        );
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+
+    let text = match attribute {
+        Some(name) => {
+            let mut values = Vec::new();
+            document.root.walk(&mut |node| {
+                if let Some(value) = node.attribute_text(name, references) {
+                    values.push(value);
+                }
+            });
+            values.join("\n")
+        }
+        None => match format {
+            Some(options) => document.to_text(options).trim_end().to_string(),
+            None => {
+                document.restyle();
+                document.root.inner_text()
+            }
+        },
+    };
+    println!("{}", text);
+
+    if copy {
+        clipboard::copy_text_and_wait(&text)?;
+    }
+    Ok(())
+}
+
+fn run_render(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut width = None;
+    let mut output = None;
+    let mut url = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--width" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--width needs a value");
+                };
+                width = Some(value.parse()?);
+            }
+            "-o" | "--output" => output = rest.next(),
+            _ => url = Some(arg),
+        }
+    }
+    let Some(url) = url else {
+        anyhow::bail!(
+            "usage: icarus render <url> [--width <columns>] [-o <file.svg> [--width <px>]]"
+        );
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let Some(output) = output else {
+        print!(
+            "{}",
+            tty::render(&document, width.unwrap_or(tty::DEFAULT_COLUMNS))
+        );
+        return Ok(());
+    };
+    if !output.ends_with(".svg") {
+        anyhow::bail!("render can only write .svg files");
+    }
+    let svg = svg::render(&document, width.unwrap_or(RENDER_WIDTH), None);
+    std::fs::write(output, svg)?;
+    println!("Saved to {}", output);
+    Ok(())
+}
+
+fn run_print(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut options = PrintOptions::default();
+    let mut output = None;
+    let mut url = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = rest.next(),
+            "--page" => {
+                let page = rest.next().and_then(|page| PageSize::parse(page));
+                let Some(page) = page else {
+                    anyhow::bail!("--page expects a4, a5, letter or legal");
+                };
+                options.page = page;
+            }
+            "--margin" => {
+                let Some(margin) = rest.next() else {
+                    anyhow::bail!("--margin needs a value");
+                };
+                options.margin = Sides::all(margin.parse()?);
+            }
+            _ => url = Some(arg),
+        }
+    }
+    let (Some(url), Some(output)) = (url, output) else {
+        anyhow::bail!(
+            "usage: icarus print <url> -o <file.pdf> [--page a4|a5|letter|legal] [--margin <px>]"
+        );
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    std::fs::write(output, pdf::print(&document, &options))?;
+    println!("Saved to {}", output);
+    Ok(())
+}
+
+fn run_view(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        anyhow::bail!("usage: icarus view <url> [--lazy-images]");
+    };
+
+    shell::open(url, settings.clone())
+}
+
+fn run_source(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus source <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let source = document.source.as_deref().unwrap_or_default();
+    print!(
+        "{}",
+        source::to_ansi(source, std::io::stdout().is_terminal())
+    );
+    Ok(())
+}
+
+fn run_diff(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let [a, b] = args else {
+        anyhow::bail!("usage: icarus diff <url> <url>");
+    };
+
+    let Load::Document(old) = loader::load_with(a, settings)? else {
+        anyhow::bail!("{} is not an HTML document", a);
+    };
+    let Load::Document(new) = loader::load_with(b, settings)? else {
+        anyhow::bail!("{} is not an HTML document", b);
+    };
+    for patch in diff::diff(&old, &new) {
+        println!("{}", patch);
+    }
+    Ok(())
+}
+
+fn run_diff_render(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut width = RENDER_WIDTH;
+    let mut tolerance = 0;
+    let mut output = "diff.png";
+    let mut urls = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                let Some(path) = rest.next() else {
+                    anyhow::bail!("--output needs a value");
+                };
+                output = path;
+            }
+            "--width" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--width needs a value");
+                };
+                width = value.parse()?;
+            }
+            "--tolerance" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--tolerance needs a value");
+                };
+                tolerance = value.parse()?;
+            }
+            _ => urls.push(arg),
+        }
+    }
+    let [a, b] = urls[..] else {
+        anyhow::bail!(
+            "usage: icarus diff-render <url> <url> [-o <diff.png>] [--width <px>] [--tolerance <0-255>]"
+        );
+    };
+
+    let Load::Document(old) = loader::load_with(a, settings)? else {
+        anyhow::bail!("{} is not an HTML document", a);
+    };
+    let Load::Document(new) = loader::load_with(b, settings)? else {
+        anyhow::bail!("{} is not an HTML document", b);
+    };
+    let old = render::raster(&old, width, None);
+    let new = render::raster(&new, width, None);
+    if (old.width, old.height) != (new.width, new.height) {
+        println!(
+            "sizes differ: {}x{} vs {}x{}",
+            old.width, old.height, new.width, new.height
+        );
+    }
+    let diff = old.diff(&new, tolerance);
+    std::fs::write(output, diff.image.to_png()?)?;
+    println!(
+        "similarity: {:.2}% ({} of {} pixels differ, max channel difference {})",
+        diff.similarity() * 100.0,
+        diff.differing,
+        diff.total(),
+        diff.max_difference
+    );
+    println!("Saved to {}", output);
+    Ok(())
+}
+
+fn run_devtools(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut port = cdp::DEFAULT_PORT;
+    let mut url = None;
+    let mut origins = Vec::new();
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--port" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--port needs a value");
+                };
+                port = value.parse()?;
+            }
+            "--remote-allow-origins" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--remote-allow-origins needs a value");
+                };
+                origins.extend(value.split(',').map(|origin| origin.trim().to_string()));
+            }
+            _ => url = Some(arg),
+        }
+    }
+
+    let local = LocalStorage::open_default().unwrap_or_else(|err| {
+        log::warn!("local storage won't be saved: {:#}", err);
+        LocalStorage::in_memory()
+    });
+    let mut session = cdp::Session::default()
+        .with_settings(settings.clone())
+        .with_storage(StorageContext::new(local));
+    if let Some(url) = url {
+        session.handle("Page.navigate", &serde_json::json!({ "url": url }))?;
+    }
+    let mut server = cdp::Server::bind(port, session)?.allow_origins(origins);
+    eprintln!("DevTools listening on ws://127.0.0.1:{}", server.port()?);
+    server.run()
+}
+
+fn run_websocket(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus websocket <ws://url> [protocol...]");
+    };
+    let protocols: Vec<&str> = args[1..].iter().map(|arg| arg.as_str()).collect();
+    let socket = WebSocket::open(url, &protocols, settings)?;
+
+    let (lines, input) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lines().map_while(Result::ok) {
+            if lines.send(line).is_err() {
+                return;
+            }
+        }
+    });
+
+    loop {
+        match input.try_recv() {
+            Ok(line) if socket.ready_state() == ReadyState::Open => socket.send(line)?,
+            Ok(_) => eprintln!("(not connected)"),
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => socket.close(None, "")?,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {}
+        }
+        let Some(event) = socket.wait(std::time::Duration::from_millis(50)) else {
+            continue;
+        };
+        match event {
+            WebSocketEvent::Open { protocol } if protocol.is_empty() => {
+                eprintln!("connected to {}", socket.url())
+            }
+            WebSocketEvent::Open { protocol } => {
+                eprintln!("connected to {} ({})", socket.url(), protocol)
+            }
+            WebSocketEvent::Message(MessageData::Text(text)) => println!("{}", text),
+            WebSocketEvent::Message(MessageData::Binary(bytes)) => {
+                println!("<{} bytes of binary data>", bytes.len())
+            }
+            WebSocketEvent::Error(message) => eprintln!("error: {}", message),
+            WebSocketEvent::Close { code, reason, .. } => {
+                eprintln!("closed ({}) {}", code, reason);
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn run_reader(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus reader <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let Some(article) = reader::extract_article(&document) else {
+        anyhow::bail!("no article content found");
+    };
+
+    println!("{}\n", article.title);
+    for paragraph in article.content.get_elements_by_tag_name("article") {
+        for line in paragraph.get_text_content().lines() {
+            let line = line.trim();
+            if !line.is_empty() {
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_accessibility(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus accessibility <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    print!("{}", document.accessibility_tree().dump());
+    Ok(())
+}
+
+fn run_audit(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus audit <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let diagnostics = document.validate_aria();
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    println!("{} issue(s) found", diagnostics.len());
+    Ok(())
+}
+
+fn run_outline(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus outline <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    print!("{}", outline::dump(&document.outline()));
+    Ok(())
+}
+
+fn run_format(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let minify = args.iter().any(|arg| arg == "--minify");
+    let Some(url) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        anyhow::bail!("usage: icarus format <url> [--minify]");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let options = if minify {
+        SerializeOptions::minified()
+    } else {
+        SerializeOptions::pretty()
+    };
+    let html = document.serialize_with(options);
+    println!("{}", html.trim_end());
+    Ok(())
+}
+
+fn run_dump_layout(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut width = RENDER_WIDTH;
+    let mut url = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--width" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--width needs a value");
+                };
+                width = value.parse()?;
+            }
+            _ => url = Some(arg),
+        }
+    }
+    let Some(url) = url else {
+        anyhow::bail!("usage: icarus dump-layout <url> [--width <px>]");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let tree = LayoutTree::build(
+        &document,
+        LayoutOptions {
+            viewport_width: width as f32,
+            ..LayoutOptions::default()
+        },
+    );
+    print!("{}", tree.dump());
+    Ok(())
+}
+
+fn run_dump_style(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let mut selector = "body";
+    let mut url = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--selector" => {
+                let Some(value) = rest.next() else {
+                    anyhow::bail!("--selector needs a value");
+                };
+                selector = value;
+            }
+            _ => url = Some(arg),
+        }
+    }
+    let Some(url) = url else {
+        anyhow::bail!("usage: icarus dump-style <url> [--selector <selectors>]");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    document.restyle();
+    let elements = document.query_selector_all(selector)?;
+    if elements.is_empty() {
+        anyhow::bail!("no elements match {:?}", selector);
+    }
+    for element in elements {
+        println!("{} {{", inspector::dom_path(&element));
+        for (name, value) in style_of(&element).to_map() {
+            println!("  {}: {};", name, value);
+        }
+        println!("}}");
+    }
+    Ok(())
+}
+
+fn run_metadata(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus metadata <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    print!("{}", PageMetadata::load(&document));
+    Ok(())
+}
+
+fn run_extract(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus extract <url>");
+    };
+
+    let Load::Document(document) = loader::load_with(url, settings)? else {
+        anyhow::bail!("{} is not an HTML document", url);
+    };
+    let data = document.structured_data();
+    println!("{}", serde_json::to_string_pretty(&data.to_json())?);
+    Ok(())
+}
+
+fn run_download(args: &[String], settings: &Settings) -> anyhow::Result<()> {
+    let Some(url) = args.first() else {
+        anyhow::bail!("usage: icarus download <url> [directory]");
+    };
+    let dir = args.get(1).map(|s| s.as_str()).unwrap_or(".");
+
+    let response = net::fetch(&net::parse_url(url)?, settings)?;
+    if response.status >= 400 {
+        anyhow::bail!("server responded with status {}", response.status);
+    }
+    save_download(response, Path::new(dir))
+}
+
+fn save_download(response: Response, dir: &Path) -> anyhow::Result<()> {
+    let path = download::save(response, dir, |received, total| {
+        match total {
+            Some(total) if total > 0 => eprint!(
+                "\r{} / {} bytes ({}%)",
+                received,
+                total,
+                received * 100 / total
+            ),
+            _ => eprint!("\r{} bytes", received),
+        }
+        let _ = std::io::stderr().flush();
+    })?;
+    eprintln!();
+    println!("Saved to {}", path.display());
+    Ok(())
+}
+
+fn demo() {
     println!("Icarus Browser - DOM Test\n");
 
     let html = r#"
@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::size_of;
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+
+use crate::css::values::Color;
+use crate::dom::{Attribute, Node, NodeData};
+use crate::image::Image;
+use crate::style::ComputedStyle;
+use crate::style::animation::AnimationState;
+use crate::units::format_bytes;
+
+const RC_HEADER: usize = 2 * size_of::<usize>();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub documents: usize,
+    pub fragments: usize,
+    pub shadow_roots: usize,
+    pub elements: usize,
+    pub text_nodes: usize,
+    pub comments: usize,
+    pub doctypes: usize,
+    pub attributes: usize,
+    pub text_bytes: usize,
+    pub attribute_bytes: usize,
+    pub computed_styles: usize,
+    pub pseudo_elements: usize,
+    pub images: usize,
+    pub animations: usize,
+    pub heap_bytes: usize,
+}
+
+// Styles and decoded images are shared between nodes, so each is counted
+// once however many nodes point at it.
+#[derive(Default)]
+struct Shared {
+    styles: HashSet<*const ComputedStyle>,
+    images: HashMap<*const Image, usize>,
+}
+
+impl MemoryStats {
+    pub fn measure(root: &Rc<Node>) -> Self {
+        let mut stats = MemoryStats::default();
+        let mut shared = Shared::default();
+        stats.visit(root, &mut shared);
+        stats.computed_styles = shared.styles.len();
+        stats.heap_bytes += shared.styles.len() * (RC_HEADER + size_of::<ComputedStyle>());
+        stats.images = shared.images.len();
+        stats.heap_bytes += shared.images.values().sum::<usize>();
+        stats
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.documents
+            + self.fragments
+            + self.shadow_roots
+            + self.elements
+            + self.text_nodes
+            + self.comments
+            + self.doctypes
+    }
+
+    pub fn summary(&self) -> String {
+        let rows = [
+            ("nodes", self.nodes().to_string()),
+            ("elements", self.elements.to_string()),
+            ("text nodes", self.text_nodes.to_string()),
+            ("comments", self.comments.to_string()),
+            ("shadow roots", self.shadow_roots.to_string()),
+            ("attributes", self.attributes.to_string()),
+            ("styles", self.computed_styles.to_string()),
+            ("pseudo-elements", self.pseudo_elements.to_string()),
+            ("images", self.images.to_string()),
+            ("animations", self.animations.to_string()),
+            ("text", format_bytes(self.text_bytes as u64)),
+            ("attribute data", format_bytes(self.attribute_bytes as u64)),
+            ("heap (est.)", format_bytes(self.heap_bytes as u64)),
+        ];
+        let mut out = String::new();
+        for (name, value) in rows {
+            out.push_str(&format!("{:<15} {:>10}\n", name, value));
+        }
+        out
+    }
+
+    fn visit(&mut self, node: &Rc<Node>, shared: &mut Shared) {
+        self.heap_bytes += RC_HEADER + size_of::<Node>();
+        self.heap_bytes += node.children.borrow().capacity() * size_of::<Rc<Node>>();
+        self.heap_bytes += node.restyle.borrow().attributes.capacity()
+            * size_of::<(html5ever::LocalName, Option<String>)>();
+
+        match &node.data {
+            NodeData::Document { .. } => self.documents += 1,
+            NodeData::DocumentFragment => self.fragments += 1,
+            NodeData::ShadowRoot { .. } => self.shadow_roots += 1,
+            NodeData::Element { attrs, .. } => {
+                self.elements += 1;
+                let attrs = attrs.borrow();
+                self.attributes += attrs.len();
+                self.heap_bytes += attrs.capacity() * size_of::<Attribute>();
+                for attr in attrs.iter() {
+                    self.attribute_bytes += attr.name.local.len() + attr.value.len();
+                    self.heap_bytes += attr.value.capacity();
+                }
+            }
+            NodeData::Text { contents } => {
+                self.text_nodes += 1;
+                let contents = contents.borrow();
+                self.text_bytes += contents.len();
+                self.heap_bytes += contents.capacity();
+            }
+            NodeData::Comment { contents } => {
+                self.comments += 1;
+                let contents = contents.borrow();
+                self.text_bytes += contents.len();
+                self.heap_bytes += contents.capacity();
+            }
+            NodeData::Doctype {
+                name,
+                public_id,
+                system_id,
+            } => {
+                self.doctypes += 1;
+                self.heap_bytes += name.capacity() + public_id.capacity() + system_id.capacity();
+            }
+        }
+
+        if let Some(style) = node.style.borrow().as_ref() {
+            shared.styles.insert(Rc::as_ptr(style));
+        }
+        if let Some(image) = node.image.borrow().as_ref() {
+            shared.images.entry(Arc::as_ptr(image)).or_insert(
+                RC_HEADER + size_of::<Image>() + image.pixels.capacity() * size_of::<Color>(),
+            );
+        }
+        let animations = node.animations.borrow();
+        self.animations += animations.len();
+        self.heap_bytes += animations.capacity() * size_of::<AnimationState>();
+        for animation in animations.iter() {
+            self.heap_bytes += animation.name.capacity();
+        }
+
+        // Generated content only adds to the heap estimate; its element and
+        // text aren't part of the DOM, so they stay out of the node counts.
+        for pseudo in [&node.pseudo_before, &node.pseudo_after] {
+            if let Some(pseudo) = pseudo.borrow().as_ref() {
+                self.pseudo_elements += 1;
+                let mut generated = MemoryStats::default();
+                generated.visit(pseudo, shared);
+                self.heap_bytes += generated.heap_bytes;
+            }
+        }
+        if let Some(shadow_root) = node.shadow_root.borrow().as_ref() {
+            self.visit(shadow_root, shared);
+        }
+        if let Some(contents) = node.template_content() {
+            self.visit(&contents, shared);
+        }
+        for child in node.children.borrow().iter() {
+            self.visit(child, shared);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use html5ever::ns;
+use std::fmt;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+use url::Url;
+
+use crate::dom::Document;
+use crate::feed::FeedLink;
+use crate::image::{self, Image};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconLink {
+    pub url: Url,
+    pub sizes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub icons: Vec<IconLink>,
+    pub feeds: Vec<FeedLink>,
+    pub favicon: Option<(Url, Arc<Image>)>,
+}
+
+impl PageMetadata {
+    pub fn from_document(document: &Document) -> Self {
+        let title = document
+            .get_elements_by_tag_name_ns(&ns!(html), "title")
+            .first()
+            .map(|title| {
+                title
+                    .get_text_content()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|title| !title.is_empty());
+        let description = document
+            .get_elements_by_tag_name("meta")
+            .iter()
+            .find(|meta| {
+                meta.get_attribute("name")
+                    .is_some_and(|name| name.trim().eq_ignore_ascii_case("description"))
+            })
+            .and_then(|meta| meta.get_attribute("content"));
+
+        PageMetadata {
+            title,
+            description,
+            icons: icon_links(document),
+            feeds: document.feed_links(),
+            favicon: None,
+        }
+    }
+
+    pub fn load(document: &Document) -> Self {
+        let mut metadata = PageMetadata::from_document(document);
+        metadata.favicon = metadata
+            .favicon_candidates(document)
+            .into_iter()
+            .find_map(|url| match image::fetch_image(&url, &document.settings) {
+                Ok(icon) => Some((url, Arc::new(icon))),
+                Err(err) => {
+                    log::debug!("favicon {} unavailable: {:#}", url, err);
+                    None
+                }
+            });
+        metadata
+    }
+
+    pub fn favicon_candidates(&self, document: &Document) -> Vec<Url> {
+        let mut candidates: Vec<Url> = self.icons.iter().map(|icon| icon.url.clone()).collect();
+        if let Some(url) = document.url()
+            && matches!(url.scheme(), "http" | "https")
+            && let Ok(fallback) = url.join("/favicon.ico")
+            && !candidates.contains(&fallback)
+        {
+            candidates.push(fallback);
+        }
+        candidates
+    }
+}
+
+impl fmt::Display for PageMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Title: {}", self.title.as_deref().unwrap_or("(none)"))?;
+        if let Some(description) = &self.description {
+            writeln!(f, "Description: {}", description)?;
+        }
+        for icon in &self.icons {
+            match &icon.sizes {
+                Some(sizes) => writeln!(f, "Icon: {} ({})", icon.url, sizes)?,
+                None => writeln!(f, "Icon: {}", icon.url)?,
+            }
+        }
+        for feed in &self.feeds {
+            match &feed.title {
+                Some(title) => writeln!(f, "Feed: {} ({}, {})", feed.url, feed.kind.name(), title)?,
+                None => writeln!(f, "Feed: {} ({})", feed.url, feed.kind.name())?,
+            }
+        }
+        match &self.favicon {
+            Some((url, icon)) => writeln!(f, "Favicon: {} ({}x{})", url, icon.width, icon.height),
+            None => writeln!(f, "Favicon: (none)"),
+        }
+    }
+}
+
+fn icon_links(document: &Document) -> Vec<IconLink> {
+    document
+        .get_elements_by_tag_name("link")
+        .iter()
+        .filter(|link| {
+            link.get_attribute("rel").is_some_and(|rel| {
+                rel.split_ascii_whitespace()
+                    .any(|token| token.eq_ignore_ascii_case("icon"))
+            })
+        })
+        .filter_map(|link| {
+            Some(IconLink {
+                url: document.resolve_url(&link.get_attribute("href")?)?,
+                sizes: link.get_attribute("sizes"),
+            })
+        })
+        .collect()
+}
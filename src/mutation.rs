@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::Node;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationKind {
+    ChildList,
+    Attributes,
+    CharacterData,
+}
+
+#[derive(Clone)]
+pub struct MutationRecord {
+    pub kind: MutationKind,
+    pub target: Rc<Node>,
+    pub added_nodes: Vec<Rc<Node>>,
+    pub removed_nodes: Vec<Rc<Node>>,
+    pub previous_sibling: Option<Rc<Node>>,
+    pub next_sibling: Option<Rc<Node>>,
+    pub attribute_name: Option<String>,
+    pub old_value: Option<String>,
+}
+
+impl MutationRecord {
+    fn new(kind: MutationKind, target: &Rc<Node>) -> Self {
+        MutationRecord {
+            kind,
+            target: Rc::clone(target),
+            added_nodes: Vec::new(),
+            removed_nodes: Vec::new(),
+            previous_sibling: None,
+            next_sibling: None,
+            attribute_name: None,
+            old_value: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MutationObserverInit {
+    pub child_list: bool,
+    pub attributes: bool,
+    pub character_data: bool,
+    pub subtree: bool,
+    pub attribute_old_value: bool,
+    pub character_data_old_value: bool,
+    pub attribute_filter: Option<Vec<String>>,
+}
+
+type MutationCallback = Box<dyn Fn(Vec<MutationRecord>, &MutationObserver)>;
+
+struct ObserverState {
+    callback: MutationCallback,
+    records: RefCell<Vec<MutationRecord>>,
+    targets: RefCell<Vec<Weak<Node>>>,
+}
+
+#[derive(Clone)]
+pub struct MutationObserver(Rc<ObserverState>);
+
+pub struct Registration {
+    observer: Weak<ObserverState>,
+    options: MutationObserverInit,
+}
+
+thread_local! {
+    static PENDING_OBSERVERS: RefCell<Vec<Weak<ObserverState>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl MutationObserver {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(Vec<MutationRecord>, &MutationObserver) + 'static,
+    {
+        MutationObserver(Rc::new(ObserverState {
+            callback: Box::new(callback),
+            records: RefCell::new(Vec::new()),
+            targets: RefCell::new(Vec::new()),
+        }))
+    }
+
+    pub fn observe(&self, target: &Rc<Node>, mut options: MutationObserverInit) {
+        if options.attribute_old_value || options.attribute_filter.is_some() {
+            options.attributes = true;
+        }
+        if options.character_data_old_value {
+            options.character_data = true;
+        }
+
+        let mut registrations = target.registered_observers.borrow_mut();
+        match registrations
+            .iter_mut()
+            .find(|registration| registration.observer.ptr_eq(&Rc::downgrade(&self.0)))
+        {
+            Some(registration) => registration.options = options,
+            None => {
+                registrations.push(Registration {
+                    observer: Rc::downgrade(&self.0),
+                    options,
+                });
+                self.0.targets.borrow_mut().push(Rc::downgrade(target));
+            }
+        }
+    }
+
+    pub fn disconnect(&self) {
+        for target in self.0.targets.borrow_mut().drain(..) {
+            if let Some(target) = target.upgrade() {
+                target
+                    .registered_observers
+                    .borrow_mut()
+                    .retain(|registration| !registration.observer.ptr_eq(&Rc::downgrade(&self.0)));
+            }
+        }
+        self.0.records.borrow_mut().clear();
+    }
+
+    pub fn take_records(&self) -> Vec<MutationRecord> {
+        std::mem::take(&mut *self.0.records.borrow_mut())
+    }
+
+    fn enqueue(&self, record: MutationRecord) {
+        self.0.records.borrow_mut().push(record);
+        PENDING_OBSERVERS.with(|pending| {
+            let mut pending = pending.borrow_mut();
+            let observer = Rc::downgrade(&self.0);
+            if !pending.iter().any(|pending| pending.ptr_eq(&observer)) {
+                pending.push(observer);
+            }
+        });
+    }
+}
+
+pub fn notify_observers() {
+    loop {
+        let observers =
+            PENDING_OBSERVERS.with(|pending| std::mem::take(&mut *pending.borrow_mut()));
+        if observers.is_empty() {
+            return;
+        }
+
+        for observer in observers.iter().filter_map(Weak::upgrade) {
+            let observer = MutationObserver(observer);
+            let records = observer.take_records();
+            if !records.is_empty() {
+                (observer.0.callback)(records, &observer);
+            }
+        }
+    }
+}
+
+fn interested_observers(
+    target: &Rc<Node>,
+    wants: impl Fn(&MutationObserverInit) -> bool,
+) -> Vec<(MutationObserver, MutationObserverInit)> {
+    let mut interested: Vec<(MutationObserver, MutationObserverInit)> = Vec::new();
+    let mut current = Some(Rc::clone(target));
+
+    while let Some(node) = current {
+        let is_target = Rc::ptr_eq(&node, target);
+        for registration in node.registered_observers.borrow().iter() {
+            if !is_target && !registration.options.subtree {
+                continue;
+            }
+            if !wants(&registration.options) {
+                continue;
+            }
+            let Some(observer) = registration.observer.upgrade() else {
+                continue;
+            };
+            if !interested.iter().any(|(o, _)| Rc::ptr_eq(&o.0, &observer)) {
+                interested.push((MutationObserver(observer), registration.options.clone()));
+            }
+        }
+        current = node.parent.borrow().upgrade();
+    }
+
+    interested
+}
+
+pub(crate) fn queue_child_list(
+    target: &Rc<Node>,
+    added_nodes: &[Rc<Node>],
+    removed_nodes: &[Rc<Node>],
+    previous_sibling: Option<&Rc<Node>>,
+    next_sibling: Option<&Rc<Node>>,
+) {
+    for (observer, _) in interested_observers(target, |options| options.child_list) {
+        let mut record = MutationRecord::new(MutationKind::ChildList, target);
+        record.added_nodes = added_nodes.to_vec();
+        record.removed_nodes = removed_nodes.to_vec();
+        record.previous_sibling = previous_sibling.cloned();
+        record.next_sibling = next_sibling.cloned();
+        observer.enqueue(record);
+    }
+}
+
+pub(crate) fn queue_attribute(target: &Rc<Node>, name: &str, old_value: Option<&str>) {
+    let wants = |options: &MutationObserverInit| {
+        options.attributes
+            && options
+                .attribute_filter
+                .as_ref()
+                .is_none_or(|filter| filter.iter().any(|attr| attr == name))
+    };
+
+    for (observer, options) in interested_observers(target, wants) {
+        let mut record = MutationRecord::new(MutationKind::Attributes, target);
+        record.attribute_name = Some(name.to_string());
+        if options.attribute_old_value {
+            record.old_value = old_value.map(|value| value.to_string());
+        }
+        observer.enqueue(record);
+    }
+}
+
+pub(crate) fn queue_character_data(target: &Rc<Node>, old_value: &str) {
+    for (observer, options) in interested_observers(target, |options| options.character_data) {
+        let mut record = MutationRecord::new(MutationKind::CharacterData, target);
+        if options.character_data_old_value {
+            record.old_value = Some(old_value.to_string());
+        }
+        observer.enqueue(record);
+    }
+}
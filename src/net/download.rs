@@ -0,0 +1,184 @@
+use anyhow::{Context, Result, bail};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use super::Response;
+
+const MAX_CANDIDATES: u32 = 10_000;
+
+pub fn filename_from_content_disposition(value: &str) -> Option<String> {
+    let mut plain = None;
+
+    for param in value.split(';').skip(1) {
+        let Some((key, raw)) = param.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let raw = raw.trim();
+
+        if key == "filename*" {
+            let encoded = match raw.splitn(3, '\'').collect::<Vec<_>>().as_slice() {
+                [_charset, _lang, encoded] => *encoded,
+                _ => raw,
+            };
+            if let Some(name) = percent_decode(encoded) {
+                return sanitize_filename(&name);
+            }
+        } else if key == "filename" {
+            plain = Some(raw.trim_matches('"').to_string());
+        }
+    }
+
+    plain.and_then(|name| sanitize_filename(&name))
+}
+
+pub fn suggested_filename(response: &Response) -> String {
+    if let Some(name) = response
+        .header("Content-Disposition")
+        .and_then(filename_from_content_disposition)
+    {
+        return name;
+    }
+
+    response
+        .url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back().map(|s| s.to_string()))
+        .and_then(|segment| percent_decode(&segment))
+        .and_then(|name| sanitize_filename(&name))
+        .unwrap_or_else(|| "download".to_string())
+}
+
+pub fn save<F>(mut response: Response, dir: &Path, mut progress: F) -> Result<PathBuf>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let (path, partial, mut file) = reserve(dir, &suggested_filename(&response))?;
+    let total = response.content_length();
+
+    let result = copy_body(&mut response, &mut file, total, &mut progress).and_then(|()| {
+        fs::rename(&partial, &path).with_context(|| format!("renaming {}", partial.display()))
+    });
+    if let Err(error) = result {
+        let _ = fs::remove_file(&partial);
+        let _ = fs::remove_file(&path);
+        return Err(error);
+    }
+    Ok(path)
+}
+
+fn copy_body<F>(
+    response: &mut Response,
+    file: &mut File,
+    total: Option<u64>,
+    progress: &mut F,
+) -> Result<()>
+where
+    F: FnMut(u64, Option<u64>),
+{
+    let mut buffer = [0u8; 16 * 1024];
+    let mut received = 0u64;
+
+    progress(received, total);
+    loop {
+        let read = response
+            .body
+            .read(&mut buffer)
+            .with_context(|| format!("downloading {}", response.url))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        received += read as u64;
+        progress(received, total);
+    }
+
+    file.flush()?;
+    Ok(())
+}
+
+/// Claims the first free name for `filename` in `dir` and opens a `.part`
+/// file next to it for the body. The final name is created empty up front so
+/// that a concurrent download cannot pick it while this one is in progress.
+fn reserve(dir: &Path, filename: &str) -> Result<(PathBuf, PathBuf, File)> {
+    for candidate in candidate_names(filename) {
+        let path = dir.join(&candidate);
+        match create_new(&path) {
+            Ok(_) => {}
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => {
+                return Err(error).with_context(|| format!("creating {}", path.display()));
+            }
+        }
+
+        let partial = dir.join(format!("{}.part", candidate));
+        match create_new(&partial) {
+            Ok(file) => return Ok((path, partial, file)),
+            Err(error) => {
+                let _ = fs::remove_file(&path);
+                if error.kind() != ErrorKind::AlreadyExists {
+                    return Err(error).with_context(|| format!("creating {}", partial.display()));
+                }
+            }
+        }
+    }
+
+    bail!("no free file name for {} in {}", filename, dir.display())
+}
+
+fn create_new(path: &Path) -> io::Result<File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+fn candidate_names(filename: &str) -> impl Iterator<Item = String> + '_ {
+    let (stem, extension) = match filename.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, Some(ext)),
+        _ => (filename, None),
+    };
+
+    iter::once(filename.to_string()).chain((1..MAX_CANDIDATES).map(move |n| match extension {
+        Some(ext) => format!("{} ({}).{}", stem, n, ext),
+        None => format!("{} ({})", stem, n),
+    }))
+}
+
+fn sanitize_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or("");
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+
+    if cleaned.is_empty() {
+        None
+    } else {
+        Some(cleaned.to_string())
+    }
+}
+
+pub fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit));
+        if let Some(hex) = escape {
+            let hex = std::str::from_utf8(hex).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,128 @@
+use std::io;
+use std::string::String;
+use url::Url;
+
+use super::log::status_text;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadError {
+    NameNotResolved,
+    ConnectionRefused,
+    ConnectionReset,
+    TimedOut,
+    SecureConnection,
+    UnsupportedScheme,
+    FileNotFound,
+    HttpStatus(u16),
+    NotHtml,
+    Other,
+}
+
+impl LoadError {
+    pub fn classify(url: &str, error: &anyhow::Error) -> LoadError {
+        for cause in error.chain() {
+            let Some(io_error) = cause.downcast_ref::<io::Error>() else {
+                continue;
+            };
+            match io_error.kind() {
+                io::ErrorKind::ConnectionRefused => return LoadError::ConnectionRefused,
+                io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted => {
+                    return LoadError::ConnectionReset;
+                }
+                io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+                    return LoadError::TimedOut;
+                }
+                io::ErrorKind::NotFound => return LoadError::FileNotFound,
+                _ => {}
+            }
+            let message = io_error.to_string().to_ascii_lowercase();
+            if message.contains("lookup address")
+                || message.contains("name or service not known")
+                || message.contains("nodename nor servname")
+            {
+                return LoadError::NameNotResolved;
+            }
+        }
+        match Url::parse(url).as_ref().map(Url::scheme) {
+            Ok("https" | "wss") => LoadError::SecureConnection,
+            Ok("http" | "file" | "about" | "icarus") | Err(_) => LoadError::Other,
+            Ok(_) => LoadError::UnsupportedScheme,
+        }
+    }
+
+    pub fn code(&self) -> String {
+        match self {
+            LoadError::NameNotResolved => String::from("name-not-resolved"),
+            LoadError::ConnectionRefused => String::from("connection-refused"),
+            LoadError::ConnectionReset => String::from("connection-reset"),
+            LoadError::TimedOut => String::from("timed-out"),
+            LoadError::SecureConnection => String::from("secure-connection"),
+            LoadError::UnsupportedScheme => String::from("unsupported-scheme"),
+            LoadError::FileNotFound => String::from("file-not-found"),
+            LoadError::HttpStatus(status) => format!("http-{}", status),
+            LoadError::NotHtml => String::from("not-html"),
+            LoadError::Other => String::from("other"),
+        }
+    }
+
+    pub fn from_code(code: &str) -> LoadError {
+        match code {
+            "name-not-resolved" => LoadError::NameNotResolved,
+            "connection-refused" => LoadError::ConnectionRefused,
+            "connection-reset" => LoadError::ConnectionReset,
+            "timed-out" => LoadError::TimedOut,
+            "secure-connection" => LoadError::SecureConnection,
+            "unsupported-scheme" => LoadError::UnsupportedScheme,
+            "file-not-found" => LoadError::FileNotFound,
+            "not-html" => LoadError::NotHtml,
+            _ => code
+                .strip_prefix("http-")
+                .and_then(|status| status.parse().ok())
+                .map_or(LoadError::Other, LoadError::HttpStatus),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            LoadError::NameNotResolved => String::from("Server not found"),
+            LoadError::ConnectionRefused => String::from("Connection refused"),
+            LoadError::ConnectionReset => String::from("Connection was reset"),
+            LoadError::TimedOut => String::from("Connection timed out"),
+            LoadError::SecureConnection => String::from("Secure connection not supported"),
+            LoadError::UnsupportedScheme => String::from("Unsupported address"),
+            LoadError::FileNotFound => String::from("File not found"),
+            LoadError::HttpStatus(status) => match status_text(*status) {
+                "" => format!("HTTP error {}", status),
+                text => format!("{} {}", status, text),
+            },
+            LoadError::NotHtml => String::from("Can't display this page"),
+            LoadError::Other => String::from("Page failed to load"),
+        }
+    }
+
+    pub fn advice(&self) -> &'static str {
+        match self {
+            LoadError::NameNotResolved => {
+                "Check the address for typing errors and make sure you're connected to a network."
+            }
+            LoadError::ConnectionRefused => {
+                "The server isn't accepting connections. It may be down or listening on another port."
+            }
+            LoadError::ConnectionReset => {
+                "The connection was interrupted while the page was loading."
+            }
+            LoadError::TimedOut => "The server took too long to respond.",
+            LoadError::SecureConnection => {
+                "Icarus can't open https:// pages yet. Try the http:// address if the site offers one."
+            }
+            LoadError::UnsupportedScheme => "Icarus doesn't know how to open this kind of address.",
+            LoadError::FileNotFound => "Check the file name and that the file still exists.",
+            LoadError::HttpStatus(400..500) => "The server couldn't find or won't serve this page.",
+            LoadError::HttpStatus(_) => {
+                "The server ran into a problem while answering the request."
+            }
+            LoadError::NotHtml => "The address points to something other than a web page.",
+            LoadError::Other => "Something went wrong while loading the page.",
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result, bail};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::string::String;
+use std::time::{Instant, SystemTime};
+use std::vec::Vec;
+use url::Url;
+
+use super::{Response, Timing};
+use crate::settings::Settings;
+
+const MAX_REDIRECTS: usize = 10;
+
+pub fn get(url: &Url, settings: &Settings) -> Result<Response> {
+    let mut url = url.clone();
+
+    for _ in 0..MAX_REDIRECTS {
+        let started_at = SystemTime::now();
+        let response = request(&url, settings, Instant::now())?;
+
+        if matches!(response.status, 301 | 302 | 303 | 307 | 308)
+            && let Some(location) = response.header("Location")
+        {
+            super::log::record_response(&response, started_at, 0);
+            url = url
+                .join(location)
+                .with_context(|| format!("invalid redirect location: {}", location))?;
+            if url.scheme() != "http" {
+                bail!("unsupported scheme in redirect to {}", url);
+            }
+            continue;
+        }
+
+        return Ok(response);
+    }
+
+    bail!("too many redirects while fetching {}", url)
+}
+
+fn request(url: &Url, settings: &Settings, fetch_start: Instant) -> Result<Response> {
+    let host = url.host_str().context("URL has no host")?;
+    let port = url.port_or_known_default().unwrap_or(80);
+
+    let (mut stream, target) = match &settings.proxy {
+        Some(proxy) => {
+            let proxy_host = proxy.host_str().context("proxy URL has no host")?;
+            let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+            let stream = TcpStream::connect((proxy_host, proxy_port))
+                .with_context(|| format!("connecting to proxy {}", proxy_host))?;
+            let mut target = url.clone();
+            target.set_fragment(None);
+            (stream, target.to_string())
+        }
+        None => {
+            let stream = TcpStream::connect((host, port))
+                .with_context(|| format!("connecting to {}", host))?;
+            let mut target = url.path().to_string();
+            if let Some(query) = url.query() {
+                target.push('?');
+                target.push_str(query);
+            }
+            (stream, target)
+        }
+    };
+
+    let authority = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: {}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        target, authority, settings.user_agent
+    )?;
+
+    let mut reader = BufReader::new(stream);
+
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = parse_status_line(&status_line)
+        .with_context(|| format!("malformed status line: {:?}", status_line.trim_end()))?;
+    let timing = Timing::started(fetch_start);
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let chunked = headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("Transfer-Encoding") && value.eq_ignore_ascii_case("chunked")
+    });
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse::<u64>().ok());
+
+    let body: Box<dyn Read + Send> = if chunked {
+        Box::new(ChunkedReader::new(reader))
+    } else if let Some(length) = length {
+        Box::new(reader.take(length))
+    } else {
+        Box::new(reader)
+    };
+
+    Ok(Response {
+        url: url.clone(),
+        status,
+        headers,
+        timing,
+        body,
+    })
+}
+
+fn parse_status_line(line: &str) -> Option<u16> {
+    let mut parts = line.split_whitespace();
+    let version = parts.next()?;
+    if !version.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+struct ChunkedReader<R> {
+    inner: R,
+    remaining: u64,
+    done: bool,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    fn new(inner: R) -> Self {
+        ChunkedReader {
+            inner,
+            remaining: 0,
+            done: false,
+        }
+    }
+
+    fn next_chunk(&mut self) -> std::io::Result<()> {
+        let mut line = String::new();
+        self.inner.read_line(&mut line)?;
+        let size = line.trim().split(';').next().unwrap_or("");
+        self.remaining = u64::from_str_radix(size, 16).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid chunk size")
+        })?;
+
+        if self.remaining == 0 {
+            self.done = true;
+            loop {
+                let mut trailer = String::new();
+                if self.inner.read_line(&mut trailer)? == 0 || trailer.trim().is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            self.next_chunk()?;
+            if self.done {
+                return Ok(0);
+            }
+        }
+
+        let max = buf.len().min(self.remaining as usize);
+        let read = self.inner.read(&mut buf[..max])?;
+        self.remaining -= read as u64;
+
+        if self.remaining == 0 {
+            let mut crlf = String::new();
+            self.inner.read_line(&mut crlf)?;
+        }
+        Ok(read)
+    }
+}
@@ -0,0 +1,413 @@
+use std::cell::RefCell;
+use std::io::Read;
+use std::rc::{Rc, Weak};
+use std::string::String;
+use std::time::{Duration, Instant, SystemTime};
+use std::vec::Vec;
+use url::Url;
+
+use super::Response;
+use crate::units::format_bytes;
+
+#[derive(Debug, Clone)]
+pub struct RequestEntry {
+    pub url: Url,
+    pub method: String,
+    pub status: Option<u16>,
+    pub headers: Vec<(String, String)>,
+    pub size: u64,
+    pub started_at: SystemTime,
+    pub wait: Duration,
+    pub total: Duration,
+    pub from_cache: bool,
+    pub error: Option<String>,
+}
+
+impl RequestEntry {
+    pub fn content_type(&self) -> Option<String> {
+        let value = self
+            .headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))?;
+        super::mime_essence(&value.1)
+    }
+}
+
+type Entries = RefCell<Vec<RequestEntry>>;
+
+thread_local! {
+    static RECORDERS: RefCell<Vec<Weak<Entries>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Default)]
+pub struct NetworkLog {
+    entries: Rc<Entries>,
+}
+
+impl NetworkLog {
+    pub fn new() -> Self {
+        NetworkLog::default()
+    }
+
+    pub fn start(&self) {
+        RECORDERS.with(|recorders| {
+            let mut recorders = recorders.borrow_mut();
+            recorders.retain(|recorder| recorder.strong_count() > 0);
+            if !recorders
+                .iter()
+                .any(|recorder| recorder.ptr_eq(&Rc::downgrade(&self.entries)))
+            {
+                recorders.push(Rc::downgrade(&self.entries));
+            }
+        });
+    }
+
+    pub fn stop(&self) {
+        RECORDERS.with(|recorders| {
+            recorders
+                .borrow_mut()
+                .retain(|recorder| !recorder.ptr_eq(&Rc::downgrade(&self.entries)));
+        });
+    }
+
+    pub fn entries(&self) -> Vec<RequestEntry> {
+        self.entries.borrow().clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.borrow().iter().map(|entry| entry.size).sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let entries = self.entries.borrow();
+        let mut out = format!(
+            "{:>3}  {:<6} {:<6} {:<24} {:>10} {:>8}  {}\n",
+            "#", "status", "method", "type", "size", "time", "url"
+        );
+
+        for (index, entry) in entries.iter().enumerate() {
+            let status = match (entry.status, &entry.error) {
+                (Some(status), _) => status.to_string(),
+                (None, _) => String::from("failed"),
+            };
+            let size = if entry.from_cache {
+                String::from("(cache)")
+            } else {
+                format_bytes(entry.size)
+            };
+            out.push_str(&format!(
+                "{:>3}  {:<6} {:<6} {:<24} {:>10} {:>6}ms  {}\n",
+                index + 1,
+                status,
+                entry.method,
+                entry.content_type().unwrap_or_else(|| String::from("-")),
+                size,
+                entry.total.as_millis(),
+                entry.url
+            ));
+            if let Some(error) = &entry.error {
+                out.push_str(&format!("     {}\n", error));
+            }
+        }
+
+        let finished = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .started_at
+                    .duration_since(entries[0].started_at)
+                    .unwrap_or_default()
+                    + entry.total
+            })
+            .max()
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{} request(s), {} transferred, finished in {}ms\n",
+            entries.len(),
+            format_bytes(entries.iter().map(|entry| entry.size).sum()),
+            finished.as_millis()
+        ));
+        out
+    }
+}
+
+fn is_recording() -> bool {
+    RECORDERS.with(|recorders| {
+        recorders
+            .borrow()
+            .iter()
+            .any(|recorder| recorder.strong_count() > 0)
+    })
+}
+
+pub(crate) fn record(entry: RequestEntry) {
+    RECORDERS.with(|recorders| {
+        for recorder in recorders.borrow().iter() {
+            if let Some(entries) = recorder.upgrade() {
+                entries.borrow_mut().push(entry.clone());
+            }
+        }
+    });
+}
+
+pub(crate) fn record_response(response: &Response, started_at: SystemTime, size: u64) {
+    if !is_recording() {
+        return;
+    }
+    let now = Instant::now();
+    record(RequestEntry {
+        url: response.url.clone(),
+        method: String::from("GET"),
+        status: Some(response.status),
+        headers: response.headers.clone(),
+        size,
+        started_at,
+        wait: response.timing.time_to_first_byte(),
+        total: now - response.timing.fetch_start,
+        from_cache: false,
+        error: None,
+    });
+}
+
+pub(crate) fn record_failure(url: &Url, started_at: SystemTime, error: &anyhow::Error) {
+    if !is_recording() {
+        return;
+    }
+    record(RequestEntry {
+        url: url.clone(),
+        method: String::from("GET"),
+        status: None,
+        headers: Vec::new(),
+        size: 0,
+        started_at,
+        wait: Duration::ZERO,
+        total: started_at.elapsed().unwrap_or_default(),
+        from_cache: false,
+        error: Some(format!("{:#}", error)),
+    });
+}
+
+struct LoggedBody {
+    inner: Box<dyn Read + Send>,
+    entry: Option<RequestEntry>,
+    fetch_start: Instant,
+}
+
+impl LoggedBody {
+    fn finish(&mut self) {
+        if let Some(mut entry) = self.entry.take() {
+            entry.total = self.fetch_start.elapsed();
+            record(entry);
+        }
+    }
+}
+
+impl Read for LoggedBody {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        match &mut self.entry {
+            Some(entry) if read > 0 => entry.size += read as u64,
+            Some(_) => self.finish(),
+            None => {}
+        }
+        Ok(read)
+    }
+}
+
+impl Drop for LoggedBody {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+pub(crate) fn track(response: Response) -> Response {
+    if !is_recording() {
+        return response;
+    }
+    // Redirect hops are logged as they happen, so this entry starts with the
+    // final hop rather than with the original request.
+    let started_at = SystemTime::now() - response.timing.fetch_start.elapsed();
+    let entry = RequestEntry {
+        url: response.url.clone(),
+        method: String::from("GET"),
+        status: Some(response.status),
+        headers: response.headers.clone(),
+        size: 0,
+        started_at,
+        wait: response.timing.time_to_first_byte(),
+        total: Duration::ZERO,
+        from_cache: false,
+        error: None,
+    };
+    Response {
+        body: Box::new(LoggedBody {
+            inner: response.body,
+            entry: Some(entry),
+            fetch_start: response.timing.fetch_start,
+        }),
+        ..response
+    }
+}
+
+pub(crate) fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+fn iso8601(time: SystemTime) -> String {
+    let since_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl RequestEntry {
+    pub fn to_har(&self) -> serde_json::Value {
+        let status = self.status.unwrap_or(0);
+        let header = |name: &str| {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        };
+        let wait = millis(self.wait);
+        let total = millis(self.total);
+        let query: Vec<serde_json::Value> = self
+            .url
+            .query_pairs()
+            .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+            .collect();
+        let headers: Vec<serde_json::Value> = self
+            .headers
+            .iter()
+            .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+            .collect();
+
+        let mut entry = serde_json::json!({
+            "startedDateTime": iso8601(self.started_at),
+            "time": total,
+            "request": {
+                "method": self.method,
+                "url": self.url.as_str(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": [],
+                "queryString": query,
+                "headersSize": -1,
+                "bodySize": 0,
+            },
+            "response": {
+                "status": status,
+                "statusText": status_text(status),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers,
+                "content": {
+                    "size": self.size,
+                    "mimeType": header("Content-Type").unwrap_or("x-unknown"),
+                },
+                "redirectURL": header("Location").unwrap_or(""),
+                "headersSize": -1,
+                "bodySize": if self.from_cache { 0 } else { self.size as i64 },
+            },
+            "cache": {},
+            "timings": {
+                "blocked": -1,
+                "dns": -1,
+                "connect": -1,
+                "ssl": -1,
+                "send": 0,
+                "wait": wait,
+                "receive": millis(self.total.saturating_sub(self.wait)),
+            },
+        });
+        if let Some(error) = &self.error {
+            entry["_error"] = serde_json::Value::from(error.as_str());
+        }
+        entry
+    }
+}
+
+impl NetworkLog {
+    pub fn to_har(&self) -> serde_json::Value {
+        let entries: Vec<serde_json::Value> = self
+            .entries
+            .borrow()
+            .iter()
+            .map(RequestEntry::to_har)
+            .collect();
+        serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": "Icarus",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": entries,
+            }
+        })
+    }
+
+    pub fn save_har(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(&self.to_har())?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+pub mod download;
+pub mod error;
+pub mod http;
+pub mod log;
+pub mod multipart;
+pub mod sniff;
+pub mod websocket;
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::string::String;
+use std::time::{Duration, Instant, SystemTime};
+use std::vec::Vec;
+use url::Url;
+
+use crate::internal;
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timing {
+    pub fetch_start: Instant,
+    pub response_start: Instant,
+    pub response_end: Option<Instant>,
+}
+
+impl Timing {
+    pub fn started(fetch_start: Instant) -> Self {
+        Timing {
+            fetch_start,
+            response_start: Instant::now(),
+            response_end: None,
+        }
+    }
+
+    pub fn time_to_first_byte(&self) -> Duration {
+        self.response_start - self.fetch_start
+    }
+
+    pub fn total(&self) -> Option<Duration> {
+        self.response_end.map(|end| end - self.fetch_start)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseInfo {
+    pub url: Url,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub timing: Timing,
+}
+
+impl ResponseInfo {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn header_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn content_type(&self) -> Option<String> {
+        mime_essence(self.header("Content-Type")?)
+    }
+
+    pub fn charset(&self) -> Option<String> {
+        mime_param(self.header("Content-Type")?, "charset")
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+pub struct Response {
+    pub url: Url,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub timing: Timing,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl Response {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn content_type(&self) -> Option<String> {
+        mime_essence(self.header("Content-Type")?)
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.header("Content-Length")?.trim().parse().ok()
+    }
+
+    pub fn info(&self) -> ResponseInfo {
+        ResponseInfo {
+            url: self.url.clone(),
+            status: self.status,
+            headers: self.headers.clone(),
+            timing: self.timing,
+        }
+    }
+
+    pub fn read_body(mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.body
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("reading body of {}", self.url))?;
+        Ok(bytes)
+    }
+}
+
+pub fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+pub fn mime_essence(value: &str) -> Option<String> {
+    let essence = value.split(';').next().unwrap_or("").trim();
+    if essence.is_empty() {
+        None
+    } else {
+        Some(essence.to_ascii_lowercase())
+    }
+}
+
+pub fn mime_param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        if key.trim().eq_ignore_ascii_case(name) {
+            Some(value.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+pub fn fetch(url: &Url, settings: &Settings) -> Result<Response> {
+    let _span = tracing::info_span!("fetch", url = url.as_str()).entered();
+    let started_at = SystemTime::now();
+    let result = match url.scheme() {
+        "http" => http::get(url, settings),
+        "file" => fetch_file(url),
+        internal::SCHEME | "about" => internal::fetch(url, settings),
+        scheme => Err(anyhow::anyhow!("unsupported URL scheme: {}", scheme)),
+    };
+    match result {
+        Ok(response) => sniff::apply(log::track(response), settings.content_sniffing),
+        Err(err) => {
+            log::record_failure(url, started_at, &err);
+            Err(err)
+        }
+    }
+}
+
+fn fetch_file(url: &Url) -> Result<Response> {
+    let fetch_start = Instant::now();
+    let path = url
+        .to_file_path()
+        .map_err(|_| anyhow::anyhow!("invalid file URL: {}", url))?;
+    let file = File::open(&path).with_context(|| format!("opening {}", path.display()))?;
+    let length = file.metadata()?.len();
+
+    let content_type = match path.extension().and_then(|e| e.to_str()) {
+        Some("html") | Some("htm") => "text/html",
+        Some("xhtml") => "application/xhtml+xml",
+        Some("txt") => "text/plain",
+        Some("css") => "text/css",
+        Some("rss") => "application/rss+xml",
+        Some("atom") => "application/atom+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        _ => "",
+    };
+
+    let mut headers = vec![("Content-Length".to_string(), length.to_string())];
+    if !content_type.is_empty() {
+        headers.insert(0, ("Content-Type".to_string(), content_type.to_string()));
+    }
+    Ok(Response {
+        url: url.clone(),
+        status: 200,
+        headers,
+        timing: Timing::started(fetch_start),
+        body: Box::new(file),
+    })
+}
+
+pub fn parse_url(input: &str) -> Result<Url> {
+    if let Ok(url) = Url::parse(input) {
+        return Ok(url);
+    }
+
+    let path = std::path::Path::new(input);
+    if path.exists() {
+        let absolute = path.canonicalize()?;
+        return Url::from_file_path(&absolute)
+            .map_err(|_| anyhow::anyhow!("invalid path: {}", input));
+    }
+
+    Url::parse(&format!("http://{}", input)).with_context(|| format!("invalid URL: {}", input))
+}
@@ -0,0 +1,147 @@
+use std::string::String;
+use std::vec::Vec;
+
+use super::{mime_essence, mime_param};
+
+pub const MIXED_REPLACE: &str = "multipart/x-mixed-replace";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Part {
+    Headers(Vec<(String, String)>),
+    Data(Vec<u8>),
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Headers,
+    Body,
+    Done,
+}
+
+pub struct Multipart {
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    state: State,
+}
+
+impl Multipart {
+    pub fn new(boundary: &str) -> Self {
+        Multipart {
+            delimiter: format!("--{}", boundary).into_bytes(),
+            buffer: Vec::new(),
+            state: State::Preamble,
+        }
+    }
+
+    pub fn for_content_type(content_type: &str) -> Option<Self> {
+        if mime_essence(content_type)? != MIXED_REPLACE {
+            return None;
+        }
+        let boundary = mime_param(content_type, "boundary")?;
+        (!boundary.is_empty()).then(|| Multipart::new(&boundary))
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Part> {
+        self.buffer.extend_from_slice(bytes);
+        let mut parts = Vec::new();
+        while self.advance(&mut parts) {}
+        parts
+    }
+
+    pub fn finish(&mut self) -> Vec<Part> {
+        let mut parts = self.push(&[]);
+        if self.state == State::Body {
+            let data = std::mem::take(&mut self.buffer);
+            if !data.is_empty() {
+                parts.push(Part::Data(data));
+            }
+            parts.push(Part::End);
+        }
+        self.state = State::Done;
+        parts
+    }
+
+    fn advance(&mut self, parts: &mut Vec<Part>) -> bool {
+        match self.state {
+            State::Preamble => {
+                let Some(start) = find(&self.buffer, &self.delimiter) else {
+                    let keep = self.buffer.len().min(self.delimiter.len());
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    return false;
+                };
+                let after = start + self.delimiter.len();
+                if self.buffer.len() < after + 2 {
+                    return false;
+                }
+                if self.buffer[after..].starts_with(b"--") {
+                    self.state = State::Done;
+                    self.buffer.clear();
+                    return false;
+                }
+                let Some(end) = find(&self.buffer[after..], b"\n") else {
+                    return false;
+                };
+                self.buffer.drain(..after + end + 1);
+                self.state = State::Headers;
+                true
+            }
+            State::Headers => {
+                let mut headers = Vec::new();
+                let mut consumed = 0;
+                loop {
+                    let Some(end) = find(&self.buffer[consumed..], b"\n") else {
+                        return false;
+                    };
+                    let line = String::from_utf8_lossy(&self.buffer[consumed..consumed + end]);
+                    let line = line.trim_end_matches('\r').to_string();
+                    consumed += end + 1;
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.push((name.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+                self.buffer.drain(..consumed);
+                self.state = State::Body;
+                parts.push(Part::Headers(headers));
+                true
+            }
+            State::Body => {
+                let mut marker = b"\n".to_vec();
+                marker.extend_from_slice(&self.delimiter);
+                if let Some(end) = find(&self.buffer, &marker) {
+                    let mut data: Vec<u8> = self.buffer.drain(..end + 1).collect();
+                    data.pop();
+                    if data.last() == Some(&b'\r') {
+                        data.pop();
+                    }
+                    if !data.is_empty() {
+                        parts.push(Part::Data(data));
+                    }
+                    parts.push(Part::End);
+                    self.state = State::Preamble;
+                    return true;
+                }
+                let keep = marker.len() + 1;
+                if self.buffer.len() > keep {
+                    let data = self.buffer.drain(..self.buffer.len() - keep).collect();
+                    parts.push(Part::Data(data));
+                }
+                false
+            }
+            State::Done => {
+                self.buffer.clear();
+                false
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,299 @@
+use anyhow::{Context, Result};
+use std::io::{Cursor, Read};
+use std::string::String;
+use std::vec::Vec;
+
+use super::{Response, mime_essence, mime_param};
+
+pub const RESOURCE_HEADER_LENGTH: usize = 1445;
+
+const APACHE_TEXT_PLAIN: [&str; 4] = [
+    "text/plain",
+    "text/plain; charset=ISO-8859-1",
+    "text/plain; charset=iso-8859-1",
+    "text/plain; charset=UTF-8",
+];
+
+const WHITESPACE: &[u8] = b"\t\n\x0c\r ";
+
+struct Pattern {
+    bytes: &'static [u8],
+    mask: &'static [u8],
+    skip_whitespace: bool,
+    mime: &'static str,
+}
+
+const fn exact(bytes: &'static [u8], mime: &'static str) -> Pattern {
+    Pattern {
+        bytes,
+        mask: &[],
+        skip_whitespace: false,
+        mime,
+    }
+}
+
+const fn masked(bytes: &'static [u8], mask: &'static [u8], mime: &'static str) -> Pattern {
+    Pattern {
+        bytes,
+        mask,
+        skip_whitespace: false,
+        mime,
+    }
+}
+
+const fn markup(bytes: &'static [u8], mime: &'static str) -> Pattern {
+    Pattern {
+        bytes,
+        mask: &[],
+        skip_whitespace: true,
+        mime,
+    }
+}
+
+const HTML_TAGS: [&[u8]; 17] = [
+    b"<!DOCTYPE HTML",
+    b"<HTML",
+    b"<HEAD",
+    b"<SCRIPT",
+    b"<IFRAME",
+    b"<H1",
+    b"<DIV",
+    b"<FONT",
+    b"<TABLE",
+    b"<A",
+    b"<STYLE",
+    b"<TITLE",
+    b"<B",
+    b"<BODY",
+    b"<BR",
+    b"<P",
+    b"<!--",
+];
+
+const SCRIPTABLE: [Pattern; 2] = [
+    markup(b"<?xml", "text/xml"),
+    exact(b"%PDF-", "application/pdf"),
+];
+
+const TEXT: [Pattern; 4] = [
+    exact(b"%!PS-Adobe-", "application/postscript"),
+    exact(b"\xfe\xff", "text/plain"),
+    exact(b"\xff\xfe", "text/plain"),
+    exact(b"\xef\xbb\xbf", "text/plain"),
+];
+
+const IMAGES: [Pattern; 8] = [
+    exact(b"\x00\x00\x01\x00", "image/x-icon"),
+    exact(b"\x00\x00\x02\x00", "image/x-icon"),
+    exact(b"BM", "image/bmp"),
+    exact(b"GIF87a", "image/gif"),
+    exact(b"GIF89a", "image/gif"),
+    masked(
+        b"RIFF\x00\x00\x00\x00WEBPVP",
+        b"\xff\xff\xff\xff\x00\x00\x00\x00\xff\xff\xff\xff\xff\xff",
+        "image/webp",
+    ),
+    exact(b"\x89PNG\r\n\x1a\n", "image/png"),
+    exact(b"\xff\xd8\xff", "image/jpeg"),
+];
+
+const MEDIA: [Pattern; 6] = [
+    masked(
+        b"FORM\x00\x00\x00\x00AIFF",
+        b"\xff\xff\xff\xff\x00\x00\x00\x00\xff\xff\xff\xff",
+        "audio/aiff",
+    ),
+    exact(b"ID3", "audio/mpeg"),
+    exact(b"OggS\x00", "application/ogg"),
+    exact(b"MThd\x00\x00\x00\x06", "audio/midi"),
+    masked(
+        b"RIFF\x00\x00\x00\x00AVI ",
+        b"\xff\xff\xff\xff\x00\x00\x00\x00\xff\xff\xff\xff",
+        "video/avi",
+    ),
+    masked(
+        b"RIFF\x00\x00\x00\x00WAVE",
+        b"\xff\xff\xff\xff\x00\x00\x00\x00\xff\xff\xff\xff",
+        "audio/wave",
+    ),
+];
+
+const ARCHIVES: [Pattern; 3] = [
+    exact(b"\x1f\x8b\x08", "application/x-gzip"),
+    exact(b"PK\x03\x04", "application/zip"),
+    exact(b"Rar!\x1a\x07\x00", "application/x-rar-compressed"),
+];
+
+impl Pattern {
+    fn matches(&self, resource: &[u8]) -> bool {
+        let resource = if self.skip_whitespace {
+            let start = resource
+                .iter()
+                .position(|byte| !WHITESPACE.contains(byte))
+                .unwrap_or(resource.len());
+            &resource[start..]
+        } else {
+            resource
+        };
+        resource.len() >= self.bytes.len()
+            && self.bytes.iter().enumerate().all(|(i, byte)| {
+                let mask = self.mask.get(i).copied().unwrap_or(0xff);
+                resource[i] & mask == *byte
+            })
+    }
+}
+
+fn find(patterns: &[Pattern], resource: &[u8]) -> Option<&'static str> {
+    patterns
+        .iter()
+        .find(|pattern| pattern.matches(resource))
+        .map(|pattern| pattern.mime)
+}
+
+fn is_html(resource: &[u8]) -> bool {
+    let start = resource
+        .iter()
+        .position(|byte| !WHITESPACE.contains(byte))
+        .unwrap_or(resource.len());
+    let resource = &resource[start..];
+    HTML_TAGS.iter().any(|tag| {
+        resource.len() > tag.len()
+            && resource[..tag.len()].eq_ignore_ascii_case(tag)
+            && matches!(resource[tag.len()], b' ' | b'>')
+    })
+}
+
+fn is_binary(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0b | 0x0e..=0x1a | 0x1c..=0x1f)
+}
+
+fn is_unknown(essence: &str) -> bool {
+    matches!(essence, "unknown/unknown" | "application/unknown" | "*/*")
+}
+
+fn is_xml(essence: &str) -> bool {
+    essence.ends_with("+xml") || matches!(essence, "text/xml" | "application/xml")
+}
+
+pub fn unknown_type(resource: &[u8], sniff_scriptable: bool) -> &'static str {
+    if sniff_scriptable {
+        if is_html(resource) {
+            return "text/html";
+        }
+        if let Some(mime) = find(&SCRIPTABLE, resource) {
+            return mime;
+        }
+    }
+    if let Some(mime) = find(&TEXT, resource) {
+        return mime;
+    }
+    if let Some(mime) = find(&IMAGES, resource)
+        .or_else(|| find(&MEDIA, resource))
+        .or_else(|| find(&ARCHIVES, resource))
+    {
+        return mime;
+    }
+    if resource.iter().copied().any(is_binary) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+fn text_or_binary(resource: &[u8]) -> &'static str {
+    if find(&TEXT[1..], resource).is_some() {
+        return "text/plain";
+    }
+    if !resource.iter().copied().any(is_binary) {
+        return if is_html(resource) {
+            "text/html"
+        } else {
+            "text/plain"
+        };
+    }
+    match unknown_type(resource, false) {
+        "text/plain" => "application/octet-stream",
+        mime => mime,
+    }
+}
+
+pub fn sniff(content_type: Option<&str>, no_sniff: bool, resource: &[u8]) -> String {
+    let resource = &resource[..resource.len().min(RESOURCE_HEADER_LENGTH)];
+    let supplied = content_type.and_then(mime_essence);
+    let Some(supplied) = supplied.filter(|essence| !is_unknown(essence)) else {
+        return String::from(unknown_type(resource, !no_sniff));
+    };
+    if no_sniff {
+        return supplied;
+    }
+    if content_type.is_some_and(|value| APACHE_TEXT_PLAIN.contains(&value.trim())) {
+        return String::from(text_or_binary(resource));
+    }
+    if is_xml(&supplied) || supplied == "text/html" {
+        return supplied;
+    }
+    let sniffed = if supplied.starts_with("image/") {
+        find(&IMAGES, resource)
+    } else if supplied.starts_with("audio/") || supplied.starts_with("video/") {
+        find(&MEDIA, resource)
+    } else {
+        None
+    };
+    sniffed.map(String::from).unwrap_or(supplied)
+}
+
+fn needs_resource(content_type: Option<&str>, no_sniff: bool) -> bool {
+    let Some(supplied) = content_type
+        .and_then(mime_essence)
+        .filter(|essence| !is_unknown(essence))
+    else {
+        return true;
+    };
+    !no_sniff
+        && (content_type.is_some_and(|value| APACHE_TEXT_PLAIN.contains(&value.trim()))
+            || ["image/", "audio/", "video/"]
+                .iter()
+                .any(|prefix| supplied.starts_with(prefix)))
+}
+
+pub fn apply(response: Response, enabled: bool) -> Result<Response> {
+    let content_type = response.header("Content-Type").map(str::to_string);
+    let no_sniff = !enabled
+        || response
+            .header("X-Content-Type-Options")
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("nosniff"));
+    if !needs_resource(content_type.as_deref(), no_sniff) {
+        return Ok(response);
+    }
+
+    let mut resource = Vec::with_capacity(RESOURCE_HEADER_LENGTH);
+    let mut body = response.body;
+    body.by_ref()
+        .take(RESOURCE_HEADER_LENGTH as u64)
+        .read_to_end(&mut resource)
+        .with_context(|| format!("reading body of {}", response.url))?;
+    let body: Box<dyn Read + Send> = Box::new(Cursor::new(resource.clone()).chain(body));
+    let mut response = Response { body, ..response };
+    if resource.is_empty() {
+        return Ok(response);
+    }
+
+    let computed = sniff(content_type.as_deref(), no_sniff, &resource);
+    if content_type.as_deref().and_then(mime_essence).as_deref() == Some(computed.as_str()) {
+        return Ok(response);
+    }
+    let value = match content_type
+        .as_deref()
+        .and_then(|value| mime_param(value, "charset"))
+    {
+        Some(charset) if computed.starts_with("text/") => {
+            format!("{}; charset={}", computed, charset)
+        }
+        _ => computed,
+    };
+    response
+        .headers
+        .retain(|(name, _)| !name.eq_ignore_ascii_case("Content-Type"));
+    response.headers.push((String::from("Content-Type"), value));
+    Ok(response)
+}
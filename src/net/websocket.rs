@@ -0,0 +1,479 @@
+use anyhow::{Context, Result, bail};
+use std::cell::{Cell, RefCell};
+use std::io;
+use std::net::TcpStream;
+use std::string::String;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::Duration;
+use std::vec::Vec;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::HeaderValue;
+use tungstenite::protocol::CloseFrame;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::{Message, WebSocket as Socket};
+use url::Url;
+
+use crate::settings::Settings;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const NORMAL_CLOSURE: u16 = 1000;
+const ABNORMAL_CLOSURE: u16 = 1006;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadyState {
+    Connecting = 0,
+    Open = 1,
+    Closing = 2,
+    Closed = 3,
+}
+
+impl ReadyState {
+    fn from_u8(value: u8) -> ReadyState {
+        match value {
+            0 => ReadyState::Connecting,
+            1 => ReadyState::Open,
+            2 => ReadyState::Closing,
+            _ => ReadyState::Closed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageData {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl MessageData {
+    fn len(&self) -> usize {
+        match self {
+            MessageData::Text(text) => text.len(),
+            MessageData::Binary(bytes) => bytes.len(),
+        }
+    }
+}
+
+impl From<&str> for MessageData {
+    fn from(text: &str) -> Self {
+        MessageData::Text(text.to_string())
+    }
+}
+
+impl From<String> for MessageData {
+    fn from(text: String) -> Self {
+        MessageData::Text(text)
+    }
+}
+
+impl From<Vec<u8>> for MessageData {
+    fn from(bytes: Vec<u8>) -> Self {
+        MessageData::Binary(bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketEvent {
+    Open {
+        protocol: String,
+    },
+    Message(MessageData),
+    Error(String),
+    Close {
+        code: u16,
+        reason: String,
+        was_clean: bool,
+    },
+}
+
+enum Outgoing {
+    Send(MessageData),
+    Close(u16, String),
+}
+
+struct Shared {
+    state: AtomicU8,
+    buffered: AtomicUsize,
+}
+
+pub struct WebSocket {
+    url: Url,
+    shared: Arc<Shared>,
+    outgoing: Sender<Outgoing>,
+    events: Receiver<WebSocketEvent>,
+}
+
+impl WebSocket {
+    pub fn open(url: &str, protocols: &[&str], settings: &Settings) -> Result<Self> {
+        let url = Url::parse(url).with_context(|| format!("invalid WebSocket URL: {}", url))?;
+        match url.scheme() {
+            "ws" => {}
+            "wss" => bail!("secure WebSockets (wss://) are not supported yet"),
+            scheme => bail!("WebSocket URLs must use ws://, not {}://", scheme),
+        }
+        if url.fragment().is_some() {
+            bail!("WebSocket URLs can't contain a fragment: {}", url);
+        }
+        url.host_str().context("WebSocket URL has no host")?;
+
+        let shared = Arc::new(Shared {
+            state: AtomicU8::new(ReadyState::Connecting as u8),
+            buffered: AtomicUsize::new(0),
+        });
+        let (outgoing, outgoing_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let connection = Connection {
+            url: url.clone(),
+            protocols: protocols
+                .iter()
+                .map(|protocol| protocol.to_string())
+                .collect(),
+            user_agent: settings.user_agent.clone(),
+            shared: Arc::clone(&shared),
+            outgoing: outgoing_receiver,
+            events: event_sender,
+        };
+        std::thread::Builder::new()
+            .name(String::from("websocket"))
+            .spawn(move || connection.run())?;
+        Ok(WebSocket {
+            url,
+            shared,
+            outgoing,
+            events,
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn ready_state(&self) -> ReadyState {
+        ReadyState::from_u8(self.shared.state.load(Ordering::SeqCst))
+    }
+
+    pub fn buffered_amount(&self) -> usize {
+        self.shared.buffered.load(Ordering::SeqCst)
+    }
+
+    pub fn send(&self, data: impl Into<MessageData>) -> Result<()> {
+        let data = data.into();
+        match self.ready_state() {
+            ReadyState::Connecting => bail!("WebSocket is still connecting"),
+            ReadyState::Open => {
+                self.shared.buffered.fetch_add(data.len(), Ordering::SeqCst);
+                let _ = self.outgoing.send(Outgoing::Send(data));
+            }
+            ReadyState::Closing | ReadyState::Closed => {
+                self.shared.buffered.fetch_add(data.len(), Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn close(&self, code: Option<u16>, reason: &str) -> Result<()> {
+        if let Some(code) = code
+            && code != NORMAL_CLOSURE
+            && !(3000..=4999).contains(&code)
+        {
+            bail!("invalid close code {}", code);
+        }
+        if reason.len() > 123 {
+            bail!("close reason is longer than 123 bytes");
+        }
+        let state = self.ready_state();
+        if matches!(state, ReadyState::Closing | ReadyState::Closed) {
+            return Ok(());
+        }
+        self.shared
+            .state
+            .store(ReadyState::Closing as u8, Ordering::SeqCst);
+        let _ = self.outgoing.send(Outgoing::Close(
+            code.unwrap_or(NORMAL_CLOSURE),
+            reason.to_string(),
+        ));
+        Ok(())
+    }
+
+    pub fn poll(&self) -> Vec<WebSocketEvent> {
+        self.events.try_iter().collect()
+    }
+
+    pub fn wait(&self, timeout: Duration) -> Option<WebSocketEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        let _ = self.close(None, "");
+    }
+}
+
+type EventHandler = Box<dyn Fn(&PageWebSocket, &WebSocketEvent)>;
+
+#[derive(Default)]
+struct Handlers {
+    open: Option<EventHandler>,
+    message: Option<EventHandler>,
+    error: Option<EventHandler>,
+    close: Option<EventHandler>,
+}
+
+impl Handlers {
+    fn slot(&mut self, event: &WebSocketEvent) -> &mut Option<EventHandler> {
+        match event {
+            WebSocketEvent::Open { .. } => &mut self.open,
+            WebSocketEvent::Message(_) => &mut self.message,
+            WebSocketEvent::Error(_) => &mut self.error,
+            WebSocketEvent::Close { .. } => &mut self.close,
+        }
+    }
+}
+
+pub struct PageWebSocket {
+    socket: WebSocket,
+    handlers: RefCell<Handlers>,
+    finished: Cell<bool>,
+}
+
+impl PageWebSocket {
+    pub fn open(
+        base: Option<&Url>,
+        url: &str,
+        protocols: &[&str],
+        settings: &Settings,
+    ) -> Result<Self> {
+        let mut url = match base {
+            Some(base) => base.join(url.trim()),
+            None => Url::parse(url.trim()),
+        }
+        .with_context(|| format!("invalid WebSocket URL: {}", url))?;
+        let scheme = match url.scheme() {
+            "http" => Some("ws"),
+            "https" => Some("wss"),
+            _ => None,
+        };
+        if let Some(scheme) = scheme {
+            let _ = url.set_scheme(scheme);
+        }
+        Ok(PageWebSocket {
+            socket: WebSocket::open(url.as_str(), protocols, settings)?,
+            handlers: RefCell::new(Handlers::default()),
+            finished: Cell::new(false),
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        self.socket.url()
+    }
+
+    pub fn ready_state(&self) -> ReadyState {
+        self.socket.ready_state()
+    }
+
+    pub fn buffered_amount(&self) -> usize {
+        self.socket.buffered_amount()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.get()
+    }
+
+    pub fn send(&self, data: impl Into<MessageData>) -> Result<()> {
+        self.socket.send(data)
+    }
+
+    pub fn close(&self, code: Option<u16>, reason: &str) -> Result<()> {
+        self.socket.close(code, reason)
+    }
+
+    pub fn set_onopen(&self, handler: impl Fn(&PageWebSocket, &WebSocketEvent) + 'static) {
+        self.handlers.borrow_mut().open = Some(Box::new(handler));
+    }
+
+    pub fn set_onmessage(&self, handler: impl Fn(&PageWebSocket, &WebSocketEvent) + 'static) {
+        self.handlers.borrow_mut().message = Some(Box::new(handler));
+    }
+
+    pub fn set_onerror(&self, handler: impl Fn(&PageWebSocket, &WebSocketEvent) + 'static) {
+        self.handlers.borrow_mut().error = Some(Box::new(handler));
+    }
+
+    pub fn set_onclose(&self, handler: impl Fn(&PageWebSocket, &WebSocketEvent) + 'static) {
+        self.handlers.borrow_mut().close = Some(Box::new(handler));
+    }
+
+    // Returns how many events were dispatched. A handler is taken out of its
+    // slot while it runs, so it can send, close or replace itself.
+    pub fn dispatch_events(&self) -> usize {
+        let events = self.socket.poll();
+        for event in &events {
+            if let WebSocketEvent::Close { .. } = event {
+                self.finished.set(true);
+            }
+            let Some(handler) = self.handlers.borrow_mut().slot(event).take() else {
+                continue;
+            };
+            handler(self, event);
+            self.handlers
+                .borrow_mut()
+                .slot(event)
+                .get_or_insert(handler);
+        }
+        events.len()
+    }
+}
+
+struct Connection {
+    url: Url,
+    protocols: Vec<String>,
+    user_agent: String,
+    shared: Arc<Shared>,
+    outgoing: Receiver<Outgoing>,
+    events: Sender<WebSocketEvent>,
+}
+
+impl Connection {
+    fn run(self) {
+        let (code, reason, was_clean) = match self.connect() {
+            Ok(mut socket) => self.pump(&mut socket),
+            Err(err) => {
+                let _ = self
+                    .events
+                    .send(WebSocketEvent::Error(format!("{:#}", err)));
+                (ABNORMAL_CLOSURE, String::new(), false)
+            }
+        };
+        self.shared
+            .state
+            .store(ReadyState::Closed as u8, Ordering::SeqCst);
+        let _ = self.events.send(WebSocketEvent::Close {
+            code,
+            reason,
+            was_clean,
+        });
+    }
+
+    fn connect(&self) -> Result<Socket<TcpStream>> {
+        let host = self.url.host_str().context("WebSocket URL has no host")?;
+        let port = self.url.port_or_known_default().unwrap_or(80);
+        let stream =
+            TcpStream::connect((host, port)).with_context(|| format!("connecting to {}", host))?;
+
+        let mut request = self.url.as_str().into_client_request()?;
+        let headers = request.headers_mut();
+        headers.insert("User-Agent", HeaderValue::from_str(&self.user_agent)?);
+        if !self.protocols.is_empty() {
+            headers.insert(
+                "Sec-WebSocket-Protocol",
+                HeaderValue::from_str(&self.protocols.join(", "))?,
+            );
+        }
+        let (socket, response) = tungstenite::client(request, stream)
+            .map_err(|err| anyhow::anyhow!("WebSocket handshake failed: {}", err))?;
+        let protocol = response
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        if !protocol.is_empty() && !self.protocols.contains(&protocol) {
+            bail!(
+                "server chose a subprotocol that wasn't offered: {}",
+                protocol
+            );
+        }
+
+        socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+        let _ = self.shared.state.compare_exchange(
+            ReadyState::Connecting as u8,
+            ReadyState::Open as u8,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        let _ = self.events.send(WebSocketEvent::Open { protocol });
+        Ok(socket)
+    }
+
+    fn pump(&self, socket: &mut Socket<TcpStream>) -> (u16, String, bool) {
+        let mut closing = false;
+        loop {
+            loop {
+                let outgoing = match self.outgoing.try_recv() {
+                    Ok(outgoing) => outgoing,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) if closing => break,
+                    Err(TryRecvError::Disconnected) => {
+                        Outgoing::Close(NORMAL_CLOSURE, String::new())
+                    }
+                };
+                let result = match outgoing {
+                    Outgoing::Send(data) => {
+                        let len = data.len();
+                        let message = match data {
+                            MessageData::Text(text) => Message::text(text),
+                            MessageData::Binary(bytes) => Message::binary(bytes),
+                        };
+                        let result = socket.send(message);
+                        self.shared.buffered.fetch_sub(len, Ordering::SeqCst);
+                        result
+                    }
+                    Outgoing::Close(_, _) if closing => continue,
+                    Outgoing::Close(code, reason) => {
+                        closing = true;
+                        self.shared
+                            .state
+                            .store(ReadyState::Closing as u8, Ordering::SeqCst);
+                        socket.close(Some(CloseFrame {
+                            code: CloseCode::from(code),
+                            reason: reason.into(),
+                        }))
+                    }
+                };
+                if let Err(err) = result {
+                    return self.failed(err);
+                }
+            }
+
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    let _ = self
+                        .events
+                        .send(WebSocketEvent::Message(MessageData::Text(text.to_string())));
+                }
+                Ok(Message::Binary(bytes)) => {
+                    let _ = self
+                        .events
+                        .send(WebSocketEvent::Message(MessageData::Binary(bytes.to_vec())));
+                }
+                Ok(Message::Close(frame)) => {
+                    self.shared
+                        .state
+                        .store(ReadyState::Closing as u8, Ordering::SeqCst);
+                    let (code, reason) = frame
+                        .map(|frame| (u16::from(frame.code), frame.reason.to_string()))
+                        .unwrap_or((1005, String::new()));
+                    let _ = socket.flush();
+                    return (code, reason, true);
+                }
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(tungstenite::Error::ConnectionClosed) => {
+                    return (NORMAL_CLOSURE, String::new(), true);
+                }
+                Err(err) => return self.failed(err),
+            }
+        }
+    }
+
+    fn failed(&self, err: tungstenite::Error) -> (u16, String, bool) {
+        let _ = self.events.send(WebSocketEvent::Error(err.to_string()));
+        (ABNORMAL_CLOSURE, String::new(), false)
+    }
+}
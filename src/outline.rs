@@ -0,0 +1,97 @@
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::{self, Vec};
+
+use crate::accessibility::collapse_whitespace;
+use crate::dom::{Document, Node};
+
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "template", "noscript"];
+
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    pub node: Rc<Node>,
+    pub children: Vec<Heading>,
+}
+
+impl Heading {
+    pub fn walk<F>(&self, visitor: &mut F)
+    where
+        F: FnMut(&Heading),
+    {
+        visitor(self);
+        for child in &self.children {
+            child.walk(visitor);
+        }
+    }
+
+    fn dump_heading(&self, depth: usize, out: &mut String) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&format!("h{}", self.level));
+        if !self.text.is_empty() {
+            out.push_str(&format!(" {}", self.text));
+        }
+        out.push('\n');
+        for child in &self.children {
+            child.dump_heading(depth + 1, out);
+        }
+    }
+}
+
+pub fn dump(outline: &[Heading]) -> String {
+    let mut out = String::new();
+    for heading in outline {
+        heading.dump_heading(0, &mut out);
+    }
+    out
+}
+
+impl Document {
+    pub fn outline(&self) -> Vec<Heading> {
+        let mut headings = Vec::new();
+        collect_headings(&self.root, &mut headings);
+        nest(&mut headings.into_iter().peekable(), 0)
+    }
+}
+
+fn heading_level(node: &Node) -> Option<u8> {
+    match node.element_name()? {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn collect_headings(node: &Rc<Node>, headings: &mut Vec<Heading>) {
+    if let Some(name) = node.element_name()
+        && (SKIPPED_ELEMENTS.contains(&name) || node.has_attribute("hidden"))
+    {
+        return;
+    }
+    if let Some(level) = heading_level(node) {
+        headings.push(Heading {
+            level,
+            text: collapse_whitespace(&node.get_text_content()),
+            node: Rc::clone(node),
+            children: Vec::new(),
+        });
+        return;
+    }
+    for child in node.children.borrow().iter() {
+        collect_headings(child, headings);
+    }
+}
+
+fn nest(headings: &mut Peekable<vec::IntoIter<Heading>>, parent_level: u8) -> Vec<Heading> {
+    let mut nested = Vec::new();
+    while let Some(mut heading) = headings.next_if(|heading| heading.level > parent_level) {
+        heading.children = nest(headings, heading.level);
+        nested.push(heading);
+    }
+    nested
+}
@@ -0,0 +1,109 @@
+use std::vec::Vec;
+
+use super::DisplayList;
+use crate::layout::Rect;
+
+const MAX_RECTS: usize = 16;
+
+#[derive(Debug, Clone, Default)]
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+impl Damage {
+    pub fn new() -> Self {
+        Damage::default()
+    }
+
+    pub fn full(width: usize, height: usize) -> Self {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0.0, 0.0, width as f32, height as f32));
+        damage
+    }
+
+    pub fn between(old: &DisplayList, new: &DisplayList) -> Self {
+        let mut damage = Damage::new();
+        let length = old.items.len().max(new.items.len());
+        for index in 0..length {
+            match (old.items.get(index), new.items.get(index)) {
+                (Some(a), Some(b)) if a == b => {}
+                (a, b) => {
+                    for item in a.into_iter().chain(b) {
+                        damage.add(item.bounds());
+                    }
+                }
+            }
+        }
+        damage
+    }
+
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return;
+        }
+        let x = rect.x.floor() - 1.0;
+        let y = rect.y.floor() - 1.0;
+        let mut rect = Rect::new(
+            x,
+            y,
+            rect.right().ceil() + 1.0 - x,
+            rect.bottom().ceil() + 1.0 - y,
+        );
+        while let Some(index) = self.rects.iter().position(|other| other.intersects(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+
+        if self.rects.len() > MAX_RECTS
+            && let Some(bounds) = self.bounds()
+        {
+            self.rects = vec![bounds];
+        }
+    }
+
+    pub fn extend(&mut self, other: &Damage) {
+        for rect in &other.rects {
+            self.add(*rect);
+        }
+    }
+
+    pub fn offset(&self, dy: f32) -> Damage {
+        Damage {
+            rects: self
+                .rects
+                .iter()
+                .map(|rect| Rect::new(rect.x, rect.y + dy, rect.width, rect.height))
+                .collect(),
+        }
+    }
+
+    pub fn clip(&self, viewport: Rect) -> Damage {
+        Damage {
+            rects: self
+                .rects
+                .iter()
+                .filter_map(|rect| rect.intersection(&viewport))
+                .collect(),
+        }
+    }
+
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        self.rects.iter().copied().reduce(|a, b| a.union(&b))
+    }
+
+    pub fn area(&self) -> f32 {
+        self.rects.iter().map(|rect| rect.width * rect.height).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+}
@@ -0,0 +1,283 @@
+use fontdue::Font;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::glyph_cache::GlyphCache;
+use crate::layout::FontProportions;
+use crate::settings::FontSettings;
+
+const FONT_DIRECTORIES: &[&str] = &[
+    "/usr/share/fonts/truetype/dejavu",
+    "/usr/share/fonts/TTF",
+    "/usr/share/fonts/dejavu",
+    "/usr/share/fonts/truetype/noto",
+    "/usr/share/fonts/opentype/noto",
+    "/usr/share/fonts/noto",
+    "/usr/share/fonts/noto-cjk",
+    "/usr/share/fonts/google-noto-cjk",
+    "/usr/share/fonts/truetype/droid",
+    "/usr/share/fonts/truetype/unifont",
+    "/usr/share/fonts/opentype/unifont",
+    "/usr/local/share/fonts",
+    "/Library/Fonts",
+];
+
+const FAMILY: &str = "DejaVuSansMono";
+
+const SYSTEM_FALLBACKS: &[&str] = &[
+    "DejaVuSans.ttf",
+    "NotoSansCJK-Regular.ttc",
+    "NotoSansCJKsc-Regular.otf",
+    "DroidSansFallbackFull.ttf",
+    "NotoSansSymbols-Regular.ttf",
+    "NotoSansSymbols2-Regular.ttf",
+    "NotoEmoji-Regular.ttf",
+    "Symbola.ttf",
+    "unifont.otf",
+    "unifont.ttf",
+];
+
+enum Fallback {
+    Pending(PathBuf),
+    Loaded(u8),
+    Missing,
+}
+
+pub struct FontSet {
+    faces: RefCell<Vec<Rc<Font>>>,
+    bold: Option<u8>,
+    italic: Option<u8>,
+    bold_italic: Option<u8>,
+    families: RefCell<HashMap<(String, bool, bool), Option<u8>>>,
+    fallbacks: RefCell<Vec<Fallback>>,
+    pub cache: RefCell<GlyphCache>,
+}
+
+fn load_font(path: &PathBuf) -> Option<Font> {
+    let bytes = std::fs::read(path).ok()?;
+    match Font::from_bytes(bytes, fontdue::FontSettings::default()) {
+        Ok(font) => Some(font),
+        Err(err) => {
+            log::warn!("failed to load font {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn font_path(file_name: &str) -> Option<PathBuf> {
+    FONT_DIRECTORIES
+        .iter()
+        .map(|dir| PathBuf::from(dir).join(file_name))
+        .find(|path| path.exists())
+}
+
+fn find_font(file_name: &str) -> Option<Font> {
+    font_path(file_name).and_then(|path| load_font(&path))
+}
+
+fn family_path(family: &str, bold: bool, italic: bool) -> Option<PathBuf> {
+    let family: String = family.split_whitespace().collect();
+    let suffixes: &[&str] = match (bold, italic) {
+        (false, false) => &["", "-Regular"],
+        (true, false) => &["-Bold"],
+        (false, true) => &["-Oblique", "-Italic"],
+        (true, true) => &["-BoldOblique", "-BoldItalic"],
+    };
+    let wanted: Vec<String> = suffixes
+        .iter()
+        .flat_map(|suffix| {
+            ["ttf", "otf", "ttc"]
+                .map(|extension| format!("{}{}.{}", family, suffix, extension).to_lowercase())
+        })
+        .collect();
+    FONT_DIRECTORIES
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| wanted.contains(&name.to_lowercase()))
+        })
+}
+
+fn load_regular(settings: &FontSettings) -> Option<Font> {
+    match &settings.regular {
+        Some(path) => load_font(path),
+        None => find_font(&format!("{}.ttf", FAMILY)),
+    }
+}
+
+fn font_proportions(font: &Font) -> FontProportions {
+    match font.horizontal_line_metrics(1.0) {
+        Some(line) if line.ascent > 0.0 => FontProportions {
+            ascent: line.ascent,
+            descent: -line.descent,
+            line_gap: line.line_gap.max(0.0),
+        },
+        _ => FontProportions::default(),
+    }
+}
+
+pub fn proportions(settings: &FontSettings) -> FontProportions {
+    load_regular(settings)
+        .map(|font| font_proportions(&font))
+        .unwrap_or_default()
+}
+
+impl FontSet {
+    pub fn load(settings: &FontSettings) -> Option<Self> {
+        let regular = load_regular(settings)?;
+        let mut faces = vec![Rc::new(regular)];
+        let mut add = |font: Option<Font>| {
+            let font = font?;
+            faces.push(Rc::new(font));
+            Some((faces.len() - 1) as u8)
+        };
+        let bold = add(find_font(&format!("{}-Bold.ttf", FAMILY)));
+        let italic = add(find_font(&format!("{}-Oblique.ttf", FAMILY)));
+        let bold_italic = add(find_font(&format!("{}-BoldOblique.ttf", FAMILY)));
+
+        let fallbacks = settings
+            .fallbacks
+            .iter()
+            .cloned()
+            .chain(SYSTEM_FALLBACKS.iter().filter_map(|file| font_path(file)))
+            .map(Fallback::Pending)
+            .collect();
+        Some(FontSet {
+            faces: RefCell::new(faces),
+            bold,
+            italic,
+            bold_italic,
+            families: RefCell::new(HashMap::new()),
+            fallbacks: RefCell::new(fallbacks),
+            cache: RefCell::new(GlyphCache::new()),
+        })
+    }
+
+    pub fn proportions(&self) -> FontProportions {
+        font_proportions(&self.font(0))
+    }
+
+    pub fn pick(&self, bold: bool, italic: bool) -> Rc<Font> {
+        self.font(self.face(bold, italic))
+    }
+
+    pub fn face(&self, bold: bool, italic: bool) -> u8 {
+        let candidates = match (bold, italic) {
+            (true, true) => [self.bold_italic, self.bold],
+            (true, false) => [self.bold, None],
+            (false, true) => [self.italic, None],
+            (false, false) => [None, None],
+        };
+        candidates.into_iter().flatten().next().unwrap_or(0)
+    }
+
+    pub fn font(&self, face: u8) -> Rc<Font> {
+        let faces = self.faces.borrow();
+        Rc::clone(faces.get(face as usize).unwrap_or(&faces[0]))
+    }
+
+    fn add_face(&self, font: Font) -> Option<u8> {
+        let mut faces = self.faces.borrow_mut();
+        let face = u8::try_from(faces.len()).ok()?;
+        faces.push(Rc::new(font));
+        Some(face)
+    }
+
+    pub fn register(&self, family: &str, bold: bool, italic: bool, data: &[u8]) -> Option<u8> {
+        let font = match Font::from_bytes(data, fontdue::FontSettings::default()) {
+            Ok(font) => font,
+            Err(err) => {
+                log::warn!("failed to load web font {}: {}", family, err);
+                return None;
+            }
+        };
+        let face = self.add_face(font)?;
+        let family = family.to_lowercase();
+        let mut families = self.families.borrow_mut();
+        for (b, i) in [(false, false), (true, false), (false, true), (true, true)] {
+            let slot = families.entry((family.clone(), b, i)).or_insert(None);
+            if slot.is_none() || (b, i) == (bold, italic) {
+                *slot = Some(face);
+            }
+        }
+        Some(face)
+    }
+
+    fn family_face(&self, family: &str, bold: bool, italic: bool) -> Option<u8> {
+        let family = match family.to_ascii_lowercase().as_str() {
+            "monospace" | "ui-monospace" => return Some(self.face(bold, italic)),
+            "serif" | "ui-serif" => String::from("DejaVu Serif"),
+            "sans-serif" | "system-ui" | "ui-sans-serif" => String::from("DejaVu Sans"),
+            _ => family.to_string(),
+        };
+        let key = (family.to_lowercase(), bold, italic);
+        if let Some(face) = self.families.borrow().get(&key) {
+            return *face;
+        }
+        let face = family_path(&family, bold, italic)
+            .or_else(|| family_path(&family, false, false))
+            .and_then(|path| load_font(&path))
+            .and_then(|font| self.add_face(font));
+        self.families.borrow_mut().insert(key, face);
+        face
+    }
+
+    pub fn chain(&self, families: &[String], bold: bool, italic: bool) -> Vec<u8> {
+        let mut chain: Vec<u8> = Vec::new();
+        let faces = families
+            .iter()
+            .filter_map(|family| self.family_face(family, bold, italic))
+            .chain([self.face(bold, italic)]);
+        for face in faces {
+            if !chain.contains(&face) {
+                chain.push(face);
+            }
+        }
+        chain
+    }
+
+    pub fn resolve(&self, chain: &[u8], c: char) -> u8 {
+        let primary = chain.first().copied().unwrap_or(0);
+        if let Some(face) = chain
+            .iter()
+            .copied()
+            .find(|face| self.font(*face).has_glyph(c))
+        {
+            return face;
+        }
+        let count = self.fallbacks.borrow().len();
+        for index in 0..count {
+            let pending = match &self.fallbacks.borrow()[index] {
+                Fallback::Loaded(face) => Ok(*face),
+                Fallback::Missing => continue,
+                Fallback::Pending(path) => Err(path.clone()),
+            };
+            let face = match pending {
+                Ok(face) => face,
+                Err(path) => {
+                    let face = load_font(&path).and_then(|font| self.add_face(font));
+                    self.fallbacks.borrow_mut()[index] = match face {
+                        Some(face) => Fallback::Loaded(face),
+                        None => Fallback::Missing,
+                    };
+                    match face {
+                        Some(face) => face,
+                        None => continue,
+                    }
+                }
+            };
+            if self.font(face).has_glyph(c) {
+                return face;
+            }
+        }
+        primary
+    }
+}
@@ -0,0 +1,244 @@
+use fontdue::Font;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::font::FontSet;
+use crate::layout::{FontMetrics, char_columns};
+
+const ATLAS_WIDTH: usize = 1024;
+const INITIAL_ATLAS_HEIGHT: usize = 256;
+const MAX_ATLAS_HEIGHT: usize = 4096;
+const MAX_RUNS: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    pub face: u8,
+    pub size: u32,
+    pub glyph: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedGlyph {
+    pub xmin: i32,
+    pub ymin: i32,
+    pub width: usize,
+    pub height: usize,
+    source: GlyphSource,
+}
+
+#[derive(Debug, Clone)]
+enum GlyphSource {
+    Atlas { x: usize, y: usize },
+    // Glyphs too big for the atlas keep their own bitmap and are never cached.
+    Bitmap(Rc<[u8]>),
+}
+
+impl CachedGlyph {
+    pub fn is_cached(&self) -> bool {
+        matches!(self.source, GlyphSource::Atlas { .. })
+    }
+}
+
+pub struct GlyphAtlas {
+    pixels: Vec<u8>,
+    height: usize,
+    shelf_x: usize,
+    shelf_y: usize,
+    shelf_height: usize,
+    entries: HashMap<GlyphKey, CachedGlyph>,
+}
+
+impl Default for GlyphAtlas {
+    fn default() -> Self {
+        GlyphAtlas::new()
+    }
+}
+
+impl GlyphAtlas {
+    pub fn new() -> Self {
+        GlyphAtlas {
+            pixels: vec![0; ATLAS_WIDTH * INITIAL_ATLAS_HEIGHT],
+            height: INITIAL_ATLAS_HEIGHT,
+            shelf_x: 0,
+            shelf_y: 0,
+            shelf_height: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &GlyphKey) -> Option<CachedGlyph> {
+        self.entries.get(key).cloned()
+    }
+
+    pub fn insert(&mut self, key: GlyphKey, font: &Font) -> CachedGlyph {
+        let (metrics, bitmap) = font.rasterize_indexed(key.glyph, f32::from_bits(key.size));
+        let fits = metrics.width <= ATLAS_WIDTH && metrics.height <= MAX_ATLAS_HEIGHT;
+        let position = fits
+            .then(|| {
+                self.allocate(metrics.width, metrics.height).or_else(|| {
+                    self.clear();
+                    self.allocate(metrics.width, metrics.height)
+                })
+            })
+            .flatten();
+        let Some((x, y)) = position else {
+            return CachedGlyph {
+                xmin: metrics.xmin,
+                ymin: metrics.ymin,
+                width: metrics.width,
+                height: metrics.height,
+                source: GlyphSource::Bitmap(bitmap.into()),
+            };
+        };
+
+        for row in 0..metrics.height {
+            let start = (y + row) * ATLAS_WIDTH + x;
+            self.pixels[start..start + metrics.width]
+                .copy_from_slice(&bitmap[row * metrics.width..(row + 1) * metrics.width]);
+        }
+        let glyph = CachedGlyph {
+            xmin: metrics.xmin,
+            ymin: metrics.ymin,
+            width: metrics.width,
+            height: metrics.height,
+            source: GlyphSource::Atlas { x, y },
+        };
+        self.entries.insert(key, glyph.clone());
+        glyph
+    }
+
+    pub fn coverage(&self, glyph: &CachedGlyph, column: usize, row: usize) -> u8 {
+        match &glyph.source {
+            GlyphSource::Atlas { x, y } => self.pixels[(y + row) * ATLAS_WIDTH + x + column],
+            GlyphSource::Bitmap(bitmap) => bitmap[row * glyph.width + column],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.shelf_x = 0;
+        self.shelf_y = 0;
+        self.shelf_height = 0;
+    }
+
+    fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        if self.shelf_x + width > ATLAS_WIDTH {
+            self.shelf_y += self.shelf_height;
+            self.shelf_x = 0;
+            self.shelf_height = 0;
+        }
+        while self.shelf_y + height > self.height {
+            if self.height >= MAX_ATLAS_HEIGHT {
+                return None;
+            }
+            self.height = (self.height * 2).min(MAX_ATLAS_HEIGHT);
+            self.pixels.resize(ATLAS_WIDTH * self.height, 0);
+        }
+
+        let position = (self.shelf_x, self.shelf_y);
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+        Some(position)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RunGlyph {
+    pub face: u8,
+    pub glyph: u16,
+    pub x: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextRun {
+    pub glyphs: Vec<RunGlyph>,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+impl TextRun {
+    pub fn baseline(&self, height: f32) -> f32 {
+        (height - (self.ascent - self.descent)) / 2.0 + self.ascent
+    }
+}
+
+#[derive(Default)]
+pub struct GlyphCache {
+    pub atlas: GlyphAtlas,
+    runs: HashMap<(Vec<u8>, u32), HashMap<String, Rc<TextRun>>>,
+    run_count: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl GlyphCache {
+    pub fn new() -> Self {
+        GlyphCache::default()
+    }
+
+    pub fn run(&mut self, fonts: &FontSet, chain: &[u8], text: &str, size: f32) -> Rc<TextRun> {
+        let key = (chain.to_vec(), size.to_bits());
+        if let Some(run) = self.runs.get(&key).and_then(|runs| runs.get(text)) {
+            return Rc::clone(run);
+        }
+
+        let metrics = FontMetrics::for_size(size);
+        let primary = fonts.font(chain.first().copied().unwrap_or(0));
+        let (ascent, descent) = primary
+            .horizontal_line_metrics(size)
+            .map(|line| (line.ascent, line.descent))
+            .unwrap_or((size * 0.8, -size * 0.2));
+        let mut glyphs = Vec::new();
+        let mut column = 0;
+        for c in text.chars() {
+            if !c.is_whitespace() {
+                let face = fonts.resolve(chain, c);
+                glyphs.push(RunGlyph {
+                    face,
+                    glyph: fonts.font(face).lookup_glyph_index(c),
+                    x: column as f32 * metrics.char_width,
+                });
+            }
+            column += char_columns(c);
+        }
+        let run = Rc::new(TextRun {
+            glyphs,
+            ascent,
+            descent,
+        });
+
+        if self.run_count >= MAX_RUNS {
+            self.runs.clear();
+            self.run_count = 0;
+        }
+        self.runs
+            .entry(key)
+            .or_default()
+            .insert(text.to_string(), Rc::clone(&run));
+        self.run_count += 1;
+        run
+    }
+
+    pub fn glyph(&mut self, font: &Font, key: GlyphKey) -> CachedGlyph {
+        match self.atlas.get(&key) {
+            Some(glyph) => {
+                self.hits += 1;
+                glyph
+            }
+            None => {
+                self.misses += 1;
+                self.atlas.insert(key, font)
+            }
+        }
+    }
+}
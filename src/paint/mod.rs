@@ -0,0 +1,464 @@
+pub mod damage;
+pub mod font;
+pub mod glyph_cache;
+pub mod raster;
+
+use std::ops::Range;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::css::values::Color;
+use crate::image::Image;
+use crate::layout::inline::{FragmentKind, LineBox};
+use crate::layout::{LayoutBox, LayoutTree, Rect, char_columns};
+use crate::style::gradient::Gradient;
+use crate::style::{BoxShadow, ComputedStyle, Dimension, ObjectFit, Position, Sides};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayItem {
+    SolidColor {
+        rect: Rect,
+        color: Color,
+    },
+    Text {
+        rect: Rect,
+        text: String,
+        color: Color,
+        font_size: f32,
+        bold: bool,
+        italic: bool,
+        font_family: Vec<String>,
+    },
+    ImagePlaceholder {
+        rect: Rect,
+        alt: String,
+        font_size: f32,
+    },
+    Image {
+        rect: Rect,
+        image: Arc<Image>,
+        alt: String,
+        source: Rect,
+    },
+    BoxShadow {
+        rect: Rect,
+        shadow: BoxShadow,
+    },
+    Gradient {
+        rect: Rect,
+        origin: Rect,
+        gradient: Gradient,
+    },
+    PushClip {
+        rect: Rect,
+    },
+    PopClip,
+}
+
+impl DisplayItem {
+    pub fn bounds(&self) -> Rect {
+        match self {
+            DisplayItem::SolidColor { rect, .. }
+            | DisplayItem::Text { rect, .. }
+            | DisplayItem::ImagePlaceholder { rect, .. }
+            | DisplayItem::Image { rect, .. }
+            | DisplayItem::Gradient { rect, .. }
+            | DisplayItem::PushClip { rect } => *rect,
+            DisplayItem::BoxShadow { rect, shadow } if shadow.inset => *rect,
+            DisplayItem::BoxShadow { rect, shadow } => {
+                shadow_shape(*rect, shadow).expand(&Sides::all(shadow.blur))
+            }
+            DisplayItem::PopClip => Rect::default(),
+        }
+    }
+
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        let shift = |rect: &mut Rect| {
+            rect.x += dx;
+            rect.y += dy;
+        };
+        match self {
+            DisplayItem::SolidColor { rect, .. }
+            | DisplayItem::Text { rect, .. }
+            | DisplayItem::ImagePlaceholder { rect, .. }
+            | DisplayItem::Image { rect, .. }
+            | DisplayItem::BoxShadow { rect, .. }
+            | DisplayItem::PushClip { rect } => shift(rect),
+            DisplayItem::Gradient { rect, origin, .. } => {
+                shift(rect);
+                shift(origin);
+            }
+            DisplayItem::PopClip => {}
+        }
+    }
+}
+
+pub fn shadow_shape(rect: Rect, shadow: &BoxShadow) -> Rect {
+    let spread = if shadow.inset {
+        -shadow.spread
+    } else {
+        shadow.spread
+    };
+    let shape = Rect::new(
+        rect.x + shadow.offset_x,
+        rect.y + shadow.offset_y,
+        rect.width,
+        rect.height,
+    )
+    .expand(&Sides::all(spread));
+    Rect::new(
+        shape.x,
+        shape.y,
+        shape.width.max(0.0),
+        shape.height.max(0.0),
+    )
+}
+
+pub fn fit_image(rect: Rect, image: &Image, fit: ObjectFit) -> Option<(Rect, Rect)> {
+    let (width, height) = (image.width as f32, image.height as f32);
+    if width <= 0.0 || height <= 0.0 {
+        return None;
+    }
+    let contain = (rect.width / width).min(rect.height / height);
+    let (scale_x, scale_y) = match fit {
+        ObjectFit::Fill => (rect.width / width, rect.height / height),
+        ObjectFit::Contain => (contain, contain),
+        ObjectFit::Cover => {
+            let cover = (rect.width / width).max(rect.height / height);
+            (cover, cover)
+        }
+        ObjectFit::None => (1.0, 1.0),
+        ObjectFit::ScaleDown => (contain.min(1.0), contain.min(1.0)),
+    };
+    if scale_x <= 0.0 || scale_y <= 0.0 {
+        return None;
+    }
+    let placed = Rect::new(
+        rect.x + (rect.width - width * scale_x) / 2.0,
+        rect.y + (rect.height - height * scale_y) / 2.0,
+        width * scale_x,
+        height * scale_y,
+    );
+    let visible = placed.intersection(&rect)?;
+    let source = Rect::new(
+        (visible.x - placed.x) / scale_x,
+        (visible.y - placed.y) / scale_y,
+        visible.width / scale_x,
+        visible.height / scale_y,
+    );
+    Some((visible, source))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StickyLayer {
+    pub items: Range<usize>,
+    pub rect: Rect,
+    pub limit: Rect,
+    pub top: Dimension,
+    pub bottom: Dimension,
+}
+
+impl StickyLayer {
+    pub fn offset(&self, scroll_y: f32, viewport_height: f32) -> f32 {
+        if let Some(top) = self.top.resolve(viewport_height) {
+            let shift = (scroll_y + top - self.rect.y)
+                .min(self.limit.bottom() - self.rect.bottom())
+                .max(0.0);
+            if shift > 0.0 {
+                return shift;
+            }
+        }
+        match self.bottom.resolve(viewport_height) {
+            Some(bottom) => (scroll_y + viewport_height - bottom - self.rect.bottom())
+                .max(self.limit.y - self.rect.y)
+                .min(0.0),
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplayList {
+    pub items: Vec<DisplayItem>,
+    pub sticky: Vec<StickyLayer>,
+}
+
+impl DisplayList {
+    pub fn build(tree: &LayoutTree) -> Self {
+        let span = tracing::info_span!("display_list", items = tracing::field::Empty).entered();
+        let mut list = DisplayList::default();
+        let mut deferred = Vec::new();
+        list.paint_box(&tree.root, tree.root.rect, Some(&mut deferred));
+        for (layout_box, containing) in deferred {
+            list.paint_box(layout_box, containing, None);
+        }
+        span.record("items", list.items.len());
+        list
+    }
+
+    pub fn sticky_offsets(&self, scroll_y: f32, viewport_height: f32) -> Vec<f32> {
+        if self.sticky.is_empty() {
+            return Vec::new();
+        }
+        let mut offsets = vec![0.0; self.items.len()];
+        for layer in &self.sticky {
+            let offset = layer.offset(scroll_y, viewport_height);
+            for item in &mut offsets[layer.items.clone()] {
+                *item += offset;
+            }
+        }
+        offsets
+    }
+
+    pub fn sticky_bounds(&self, scroll_y: f32, viewport_height: f32) -> Vec<Rect> {
+        self.sticky
+            .iter()
+            .filter_map(|layer| {
+                let offset = layer.offset(scroll_y, viewport_height);
+                let bounds = self.items[layer.items.clone()]
+                    .iter()
+                    .map(DisplayItem::bounds)
+                    .reduce(|a, b| a.union(&b))?;
+                Some(Rect::new(
+                    bounds.x,
+                    bounds.y + offset - scroll_y,
+                    bounds.width,
+                    bounds.height,
+                ))
+            })
+            .collect()
+    }
+
+    fn paint_box<'a>(
+        &mut self,
+        layout_box: &'a LayoutBox,
+        containing: Rect,
+        deferred: Option<&mut Vec<(&'a LayoutBox, Rect)>>,
+    ) {
+        let sticky = layout_box.node.is_some() && layout_box.style.position == Position::Sticky;
+        let deferred = match deferred {
+            Some(deferred) if sticky => {
+                deferred.push((layout_box, containing));
+                return;
+            }
+            deferred => deferred,
+        };
+        let start = self.items.len();
+        if layout_box.node.is_some() && layout_box.style.is_visible() {
+            self.paint_shadows(layout_box, false);
+            self.paint_background(layout_box);
+            self.paint_shadows(layout_box, true);
+            self.paint_borders(layout_box);
+        }
+        self.paint_lines(&layout_box.lines);
+        if let Some(frame) = &layout_box.frame {
+            self.paint_frame(frame, layout_box.rect);
+        }
+        let mut deferred = if sticky { None } else { deferred };
+        for child in &layout_box.children {
+            self.paint_box(child, layout_box.rect, deferred.as_deref_mut());
+        }
+        if sticky {
+            let inset = layout_box.style.inset;
+            let margin = layout_box.margin;
+            self.sticky.push(StickyLayer {
+                items: start..self.items.len(),
+                rect: layout_box.border_box(),
+                limit: Rect::new(
+                    containing.x,
+                    containing.y + margin.top,
+                    containing.width,
+                    (containing.height - margin.vertical()).max(0.0),
+                ),
+                top: inset.top,
+                bottom: inset.bottom,
+            });
+        }
+    }
+
+    fn paint_frame(&mut self, frame: &LayoutTree, rect: Rect) {
+        self.items.push(DisplayItem::PushClip { rect });
+        for mut item in DisplayList::build(frame).items {
+            item.translate(rect.x, rect.y);
+            self.items.push(item);
+        }
+        self.items.push(DisplayItem::PopClip);
+    }
+
+    fn paint_shadows(&mut self, layout_box: &LayoutBox, inset: bool) {
+        let rect = if inset {
+            layout_box.padding_box()
+        } else {
+            layout_box.border_box()
+        };
+        for shadow in layout_box.style.box_shadow.iter().rev() {
+            if shadow.inset == inset {
+                self.items.push(DisplayItem::BoxShadow {
+                    rect,
+                    shadow: *shadow,
+                });
+            }
+        }
+    }
+
+    fn paint_background(&mut self, layout_box: &LayoutBox) {
+        let color = layout_box.style.background_color;
+        if !color.is_transparent() {
+            self.items.push(DisplayItem::SolidColor {
+                rect: layout_box.border_box(),
+                color,
+            });
+        }
+        for gradient in layout_box.style.background_image.iter().rev() {
+            self.items.push(DisplayItem::Gradient {
+                rect: layout_box.border_box(),
+                origin: layout_box.padding_box(),
+                gradient: gradient.clone(),
+            });
+        }
+    }
+
+    fn paint_borders(&mut self, layout_box: &LayoutBox) {
+        let border = layout_box.border;
+        let colors = layout_box.style.border_color;
+        let b = layout_box.border_box();
+
+        let edges = [
+            (Rect::new(b.x, b.y, b.width, border.top), colors.top),
+            (
+                Rect::new(b.right() - border.right, b.y, border.right, b.height),
+                colors.right,
+            ),
+            (
+                Rect::new(b.x, b.bottom() - border.bottom, b.width, border.bottom),
+                colors.bottom,
+            ),
+            (Rect::new(b.x, b.y, border.left, b.height), colors.left),
+        ];
+        for (rect, color) in edges {
+            if rect.width > 0.0 && rect.height > 0.0 && !color.is_transparent() {
+                self.items.push(DisplayItem::SolidColor { rect, color });
+            }
+        }
+    }
+
+    fn paint_text(&mut self, rect: Rect, text: &str, style: &ComputedStyle) {
+        let item = |rect: Rect, text: String| DisplayItem::Text {
+            rect,
+            text,
+            color: style.color,
+            font_size: style.font_size,
+            bold: style.is_bold(),
+            italic: style.italic,
+            font_family: style.font_family.clone(),
+        };
+        if style.letter_spacing == 0.0 && style.word_spacing == 0.0 {
+            self.items.push(item(rect, text.to_string()));
+            return;
+        }
+
+        let is_space = |c: &char| matches!(c, ' ' | '\u{a0}');
+        let count = text.chars().count() as f32;
+        let columns = text.chars().map(char_columns).sum::<usize>().max(1) as f32;
+        let spaces = text.chars().filter(is_space).count() as f32;
+        let char_width =
+            (rect.width - count * style.letter_spacing - spaces * style.word_spacing) / columns;
+        let mut x = rect.x;
+        for c in text.chars() {
+            let width = char_width * char_columns(c) as f32;
+            if !c.is_whitespace() {
+                let glyph = Rect::new(x, rect.y, width, rect.height);
+                self.items.push(item(glyph, c.to_string()));
+            }
+            x += width + style.letter_spacing;
+            if is_space(&c) {
+                x += style.word_spacing;
+            }
+        }
+    }
+
+    fn paint_decorations(&mut self, rect: Rect, style: &ComputedStyle) {
+        let decoration = style.text_decoration;
+        if decoration.is_none() {
+            return;
+        }
+        let font_size = style.font_size;
+        let baseline = rect.y + rect.height / 2.0 + font_size * 0.3;
+        let thickness = (font_size / 14.0).max(1.0);
+        for (enabled, y) in [
+            (decoration.underline, baseline + thickness),
+            (decoration.overline, baseline - font_size * 0.8),
+            (
+                decoration.line_through,
+                baseline - font_size * 0.3 - thickness / 2.0,
+            ),
+        ] {
+            if enabled {
+                self.items.push(DisplayItem::SolidColor {
+                    rect: Rect::new(rect.x, y, rect.width, thickness),
+                    color: style.color,
+                });
+            }
+        }
+    }
+
+    fn paint_lines(&mut self, lines: &[LineBox]) {
+        for fragment in lines.iter().flat_map(|line| &line.fragments) {
+            let style = &fragment.style;
+            if !style.is_visible() {
+                continue;
+            }
+            match &fragment.kind {
+                FragmentKind::Text(text) => {
+                    self.paint_text(fragment.rect, text, style);
+                    self.paint_decorations(fragment.rect, style);
+                }
+                FragmentKind::ImagePlaceholder { alt } => {
+                    self.items.push(DisplayItem::ImagePlaceholder {
+                        rect: fragment.rect,
+                        alt: alt.clone(),
+                        font_size: style.font_size,
+                    })
+                }
+                FragmentKind::Image { image, alt } => {
+                    let Some((rect, source)) = fit_image(fragment.rect, image, style.object_fit)
+                    else {
+                        continue;
+                    };
+                    self.items.push(DisplayItem::Image {
+                        rect,
+                        image: Arc::clone(image),
+                        alt: alt.clone(),
+                        source,
+                    })
+                }
+                FragmentKind::Math(math) => {
+                    let (x, y) = (fragment.rect.x, fragment.rect.y);
+                    for run in &math.runs {
+                        self.items.push(DisplayItem::Text {
+                            rect: Rect::new(
+                                x + run.rect.x,
+                                y + run.rect.y,
+                                run.rect.width,
+                                run.rect.height,
+                            ),
+                            text: run.text.clone(),
+                            color: style.color,
+                            font_size: run.font_size,
+                            bold: style.is_bold(),
+                            italic: run.italic,
+                            font_family: style.font_family.clone(),
+                        });
+                    }
+                    for rule in &math.rules {
+                        self.items.push(DisplayItem::SolidColor {
+                            rect: Rect::new(x + rule.x, y + rule.y, rule.width, rule.height),
+                            color: style.color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
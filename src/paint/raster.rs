@@ -0,0 +1,487 @@
+use anyhow::Result;
+use std::vec::Vec;
+
+use super::font::FontSet;
+use super::glyph_cache::GlyphKey;
+use super::{DisplayItem, DisplayList, shadow_shape};
+use crate::css::values::Color;
+use crate::image::Image;
+use crate::layout::{FontMetrics, Rect};
+use crate::style::BoxShadow;
+use crate::style::gradient::Gradient;
+
+const PLACEHOLDER_BORDER: Color = Color::rgb(160, 160, 160);
+const PLACEHOLDER_TEXT: Color = Color::rgb(96, 96, 96);
+const DIFF_HIGHLIGHT: Color = Color::rgb(255, 0, 0);
+const DIFF_MISSING: Color = Color::rgb(255, 0, 255);
+
+pub struct VisualDiff {
+    pub image: Canvas,
+    pub differing: usize,
+    pub max_difference: u8,
+}
+
+impl VisualDiff {
+    pub fn total(&self) -> usize {
+        self.image.width * self.image.height
+    }
+
+    pub fn similarity(&self) -> f64 {
+        match self.total() {
+            0 => 1.0,
+            total => 1.0 - self.differing as f64 / total as f64,
+        }
+    }
+}
+
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+    pub clip: Option<Rect>,
+}
+
+fn blend_channel(dst: u32, src: u8, alpha: u32) -> u32 {
+    (src as u32 * alpha + dst * (255 - alpha)) / 255
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![background.to_u32(); width * height],
+            clip: None,
+        }
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+            .collect();
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(bytes)
+    }
+
+    pub fn from_png(bytes: &[u8]) -> Result<Self> {
+        let mut reader = png::Decoder::new(std::io::Cursor::new(bytes)).read_info()?;
+        let mut data = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut data)?;
+        let channels = match info.color_type {
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            other => anyhow::bail!("unsupported PNG color type {:?}", other),
+        };
+        if info.bit_depth != png::BitDepth::Eight {
+            anyhow::bail!("unsupported PNG bit depth {:?}", info.bit_depth);
+        }
+
+        let pixels = data[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| Color::rgb(pixel[0], pixel[1], pixel[2]).to_u32())
+            .collect();
+        Ok(Canvas {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+            clip: None,
+        })
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<u32> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    pub fn diff(&self, other: &Canvas, tolerance: u8) -> VisualDiff {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        let mut image = Canvas::new(width, height, Color::WHITE);
+        let mut differing = 0;
+        let mut max_difference = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let output = &mut image.pixels[y * width + x];
+                let (Some(a), Some(b)) = (self.pixel(x, y), other.pixel(x, y)) else {
+                    differing += 1;
+                    max_difference = u8::MAX;
+                    *output = DIFF_MISSING.to_u32();
+                    continue;
+                };
+                let difference = [16, 8, 0]
+                    .iter()
+                    .map(|shift| ((a >> shift) as u8).abs_diff((b >> shift) as u8))
+                    .max()
+                    .unwrap_or_default();
+                max_difference = max_difference.max(difference);
+                if difference > tolerance {
+                    differing += 1;
+                    *output = DIFF_HIGHLIGHT.to_u32();
+                } else {
+                    let luma = ((a >> 16 & 0xff) * 3 + (a >> 8 & 0xff) * 6 + (a & 0xff)) / 10;
+                    let faded = 255 - (255 - luma) / 4;
+                    *output = faded << 16 | faded << 8 | faded;
+                }
+            }
+        }
+        VisualDiff {
+            image,
+            differing,
+            max_difference,
+        }
+    }
+
+    pub fn clear(&mut self, background: Color) {
+        self.pixels.fill(background.to_u32());
+    }
+
+    pub fn scroll(&mut self, dy: i32, background: Color) {
+        let shift = dy.unsigned_abs() as usize * self.width;
+        if shift >= self.pixels.len() {
+            self.clear(background);
+            return;
+        }
+        let length = self.pixels.len();
+        if dy > 0 {
+            self.pixels.copy_within(shift.., 0);
+            self.pixels[length - shift..].fill(background.to_u32());
+        } else {
+            self.pixels.copy_within(..length - shift, shift);
+            self.pixels[..shift].fill(background.to_u32());
+        }
+    }
+
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: u8) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        if let Some(clip) = self.clip
+            && !clip.contains(x as f32, y as f32)
+        {
+            return;
+        }
+        let alpha = color.a as u32 * coverage as u32 / 255;
+        if alpha == 0 {
+            return;
+        }
+        let index = y as usize * self.width + x as usize;
+        let dst = self.pixels[index];
+        self.pixels[index] = if alpha == 255 {
+            color.to_u32()
+        } else {
+            (blend_channel((dst >> 16) & 0xff, color.r, alpha) << 16)
+                | (blend_channel((dst >> 8) & 0xff, color.g, alpha) << 8)
+                | blend_channel(dst & 0xff, color.b, alpha)
+        };
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let rect = match self.clip {
+            Some(clip) => match rect.intersection(&clip) {
+                Some(rect) => rect,
+                None => return,
+            },
+            None => rect,
+        };
+        let x0 = rect.x.round().max(0.0) as i32;
+        let y0 = rect.y.round().max(0.0) as i32;
+        let x1 = (rect.right().round() as i32).min(self.width as i32);
+        let y1 = (rect.bottom().round() as i32).min(self.height as i32);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                self.blend_pixel(x, y, color, 255);
+            }
+        }
+    }
+
+    pub fn stroke_rect(&mut self, rect: Rect, width: f32, color: Color) {
+        self.fill_rect(Rect::new(rect.x, rect.y, rect.width, width), color);
+        self.fill_rect(
+            Rect::new(rect.x, rect.bottom() - width, rect.width, width),
+            color,
+        );
+        self.fill_rect(Rect::new(rect.x, rect.y, width, rect.height), color);
+        self.fill_rect(
+            Rect::new(rect.right() - width, rect.y, width, rect.height),
+            color,
+        );
+    }
+
+    pub fn draw_image(&mut self, rect: Rect, image: &Image, source: Rect) {
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+        let (image_width, image_height) = (image.width as f32, image.height as f32);
+        let x0 = rect.x.round() as i32;
+        let y0 = rect.y.round() as i32;
+        let width = rect.width.round() as i32;
+        let height = rect.height.round() as i32;
+        let rows = y0.max(0)..(y0 + height).min(self.height as i32);
+        for y in rows {
+            let v = (source.y + (y - y0) as f32 / height as f32 * source.height) / image_height;
+            for x in x0.max(0)..(x0 + width).min(self.width as i32) {
+                let u = (source.x + (x - x0) as f32 / width as f32 * source.width) / image_width;
+                let color = image.sample(u, v);
+                self.blend_pixel(x, y, color, 255);
+            }
+        }
+    }
+
+    pub fn draw_box_shadow(&mut self, rect: Rect, shadow: &BoxShadow) {
+        let shape = shadow_shape(rect, shadow);
+        let area = if shadow.inset {
+            rect
+        } else {
+            DisplayItem::BoxShadow {
+                rect,
+                shadow: *shadow,
+            }
+            .bounds()
+        };
+        let sigma = shadow.blur / 2.0;
+        let y0 = area.y.floor().max(0.0) as i32;
+        let y1 = (area.bottom().ceil() as i32).min(self.height as i32);
+        let x0 = area.x.floor().max(0.0) as i32;
+        let x1 = (area.right().ceil() as i32).min(self.width as i32);
+        for y in y0..y1 {
+            let cy = y as f32 + 0.5;
+            let vertical = blurred_span(shape.y, shape.bottom(), cy, sigma);
+            for x in x0..x1 {
+                let cx = x as f32 + 0.5;
+                if rect.contains(cx, cy) != shadow.inset {
+                    continue;
+                }
+                let inside = vertical * blurred_span(shape.x, shape.right(), cx, sigma);
+                let coverage = if shadow.inset { 1.0 - inside } else { inside };
+                self.blend_pixel(x, y, shadow.color, (coverage * 255.0).round() as u8);
+            }
+        }
+    }
+
+    pub fn draw_gradient(&mut self, rect: Rect, origin: Rect, gradient: &Gradient) {
+        let sampler = gradient.sampler(origin);
+        let y0 = rect.y.round().max(0.0) as i32;
+        let y1 = (rect.bottom().round() as i32).min(self.height as i32);
+        let x0 = rect.x.round().max(0.0) as i32;
+        let x1 = (rect.right().round() as i32).min(self.width as i32);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let color = sampler.color_at(x as f32 + 0.5, y as f32 + 0.5);
+                self.blend_pixel(x, y, color, 255);
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text(
+        &mut self,
+        fonts: &FontSet,
+        x: f32,
+        y: f32,
+        text: &str,
+        font_size: f32,
+        color: Color,
+        bold: bool,
+        italic: bool,
+        clip: Option<Rect>,
+    ) {
+        let chain = fonts.chain(&[], bold, italic);
+        let height = FontMetrics::for_size(font_size).line_height;
+        let origin = Rect::new(x, y, 0.0, height);
+        self.draw_run(fonts, origin, text, font_size, color, &chain, clip);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_run(
+        &mut self,
+        fonts: &FontSet,
+        rect: Rect,
+        text: &str,
+        font_size: f32,
+        color: Color,
+        chain: &[u8],
+        clip: Option<Rect>,
+    ) {
+        let mut cache = fonts.cache.borrow_mut();
+        let run = cache.run(fonts, chain, text, font_size);
+        let (x, baseline) = (rect.x, rect.y + run.baseline(rect.height));
+
+        for run_glyph in &run.glyphs {
+            let key = GlyphKey {
+                face: run_glyph.face,
+                size: font_size.to_bits(),
+                glyph: run_glyph.glyph,
+            };
+            let glyph = cache.glyph(&fonts.font(run_glyph.face), key);
+            let left = ((x + run_glyph.x).round() as i32).saturating_add(glyph.xmin);
+            let top = (baseline.round() as i32)
+                .saturating_sub(glyph.height as i32)
+                .saturating_sub(glyph.ymin);
+
+            for row in 0..glyph.height {
+                let py = top.saturating_add(row as i32);
+                if py < 0 || py as usize >= self.height {
+                    continue;
+                }
+                for column in 0..glyph.width {
+                    let px = left.saturating_add(column as i32);
+                    if let Some(clip) = clip
+                        && !clip.contains(px as f32, py as f32)
+                    {
+                        continue;
+                    }
+                    let coverage = cache.atlas.coverage(&glyph, column, row);
+                    self.blend_pixel(px, py, color, coverage);
+                }
+            }
+        }
+    }
+
+    pub fn paint(&mut self, list: &DisplayList, fonts: Option<&FontSet>, scroll_y: f32) {
+        let span = tracing::info_span!(
+            "raster",
+            items = list.items.len(),
+            glyph_hits = tracing::field::Empty,
+            glyph_misses = tracing::field::Empty
+        )
+        .entered();
+        let before = fonts.map(|fonts| {
+            let cache = fonts.cache.borrow();
+            (cache.hits, cache.misses)
+        });
+        let shifts = list.sticky_offsets(scroll_y, self.height as f32);
+        let page_scroll = scroll_y;
+        let mut clips = Vec::new();
+        for (index, item) in list.items.iter().enumerate() {
+            let scroll_y = page_scroll - shifts.get(index).copied().unwrap_or(0.0);
+            match item {
+                DisplayItem::PushClip { rect } => {
+                    let rect = offset(rect, scroll_y);
+                    clips.push(self.clip);
+                    self.clip = Some(match self.clip {
+                        Some(clip) => clip.intersection(&rect).unwrap_or_default(),
+                        None => rect,
+                    });
+                    continue;
+                }
+                DisplayItem::PopClip => {
+                    self.clip = clips.pop().flatten();
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some(clip) = self.clip
+                && !clip.intersects(&offset(&item.bounds(), scroll_y))
+            {
+                continue;
+            }
+            match item {
+                DisplayItem::SolidColor { rect, color } => {
+                    self.fill_rect(offset(rect, scroll_y), *color)
+                }
+                DisplayItem::Text {
+                    rect,
+                    text,
+                    color,
+                    font_size,
+                    bold,
+                    italic,
+                    font_family,
+                } => {
+                    let rect = offset(rect, scroll_y);
+                    if rect.bottom() < 0.0 || rect.y > self.height as f32 {
+                        continue;
+                    }
+                    match fonts {
+                        Some(fonts) => {
+                            let chain = fonts.chain(font_family, *bold, *italic);
+                            self.draw_run(fonts, rect, text, *font_size, *color, &chain, None)
+                        }
+                        None => self.fill_rect(
+                            Rect::new(rect.x, rect.bottom() - 2.0, rect.width, 1.0),
+                            *color,
+                        ),
+                    }
+                }
+                DisplayItem::Image {
+                    rect,
+                    image,
+                    source,
+                    ..
+                } => self.draw_image(offset(rect, scroll_y), image, *source),
+                DisplayItem::BoxShadow { rect, shadow } => {
+                    self.draw_box_shadow(offset(rect, scroll_y), shadow)
+                }
+                DisplayItem::Gradient {
+                    rect,
+                    origin,
+                    gradient,
+                } => self.draw_gradient(offset(rect, scroll_y), offset(origin, scroll_y), gradient),
+                DisplayItem::PushClip { .. } | DisplayItem::PopClip => {}
+                DisplayItem::ImagePlaceholder {
+                    rect,
+                    alt,
+                    font_size,
+                } => {
+                    let rect = offset(rect, scroll_y);
+                    self.stroke_rect(rect, 1.0, PLACEHOLDER_BORDER);
+                    if let Some(fonts) = fonts {
+                        let inner = Rect::new(
+                            rect.x + 1.0,
+                            rect.y + 1.0,
+                            rect.width - 2.0,
+                            rect.height - 2.0,
+                        );
+                        let padding = FontMetrics::for_size(*font_size).char_width;
+                        self.draw_text(
+                            fonts,
+                            rect.x + padding,
+                            rect.y,
+                            alt,
+                            *font_size,
+                            PLACEHOLDER_TEXT,
+                            false,
+                            true,
+                            Some(inner),
+                        );
+                    }
+                }
+            }
+        }
+
+        if let (Some(fonts), Some((hits, misses))) = (fonts, before) {
+            let cache = fonts.cache.borrow();
+            span.record("glyph_hits", cache.hits - hits);
+            span.record("glyph_misses", cache.misses - misses);
+        }
+    }
+}
+
+fn blurred_span(start: f32, end: f32, position: f32, sigma: f32) -> f32 {
+    if sigma <= 0.0 {
+        return (position >= start && position < end) as u8 as f32;
+    }
+    let scale = std::f32::consts::SQRT_2 * sigma;
+    (0.5 * (erf((position - start) / scale) - erf((position - end) / scale))).clamp(0.0, 1.0)
+}
+
+fn erf(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t
+        * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_7 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    let value = 1.0 - polynomial * (-x * x).exp();
+    value.copysign(x)
+}
+
+fn offset(rect: &Rect, scroll_y: f32) -> Rect {
+    Rect::new(rect.x, rect.y - scroll_y, rect.width, rect.height)
+}
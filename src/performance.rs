@@ -0,0 +1,114 @@
+use std::cell::RefCell;
+use std::string::String;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use crate::net::Timing;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PageMetrics {
+    pub navigation_start: Instant,
+    pub fetch_start: Option<Instant>,
+    pub response_start: Option<Instant>,
+    pub response_end: Option<Instant>,
+    pub dom_parsed: Option<Instant>,
+    pub first_paint: Option<Instant>,
+    pub load_complete: Option<Instant>,
+}
+
+impl PageMetrics {
+    pub fn new(navigation_start: Instant) -> Self {
+        PageMetrics {
+            navigation_start,
+            fetch_start: None,
+            response_start: None,
+            response_end: None,
+            dom_parsed: None,
+            first_paint: None,
+            load_complete: None,
+        }
+    }
+
+    pub fn since_start(&self, instant: Option<Instant>) -> Option<Duration> {
+        instant.map(|instant| instant.saturating_duration_since(self.navigation_start))
+    }
+
+    pub fn milestones(&self) -> Vec<(&'static str, Option<Duration>)> {
+        vec![
+            ("fetchStart", self.since_start(self.fetch_start)),
+            ("responseStart", self.since_start(self.response_start)),
+            ("responseEnd", self.since_start(self.response_end)),
+            ("domParsed", self.since_start(self.dom_parsed)),
+            ("firstPaint", self.since_start(self.first_paint)),
+            ("loadComplete", self.since_start(self.load_complete)),
+        ]
+    }
+
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for (name, offset) in self.milestones() {
+            let value = match offset {
+                Some(offset) => format!("{:.1} ms", offset.as_secs_f64() * 1000.0),
+                None => String::from("-"),
+            };
+            out.push_str(&format!("{:<14} {:>10}\n", name, value));
+        }
+        out
+    }
+}
+
+impl Default for PageMetrics {
+    fn default() -> Self {
+        PageMetrics::new(Instant::now())
+    }
+}
+
+#[derive(Default)]
+pub struct Performance {
+    metrics: RefCell<PageMetrics>,
+}
+
+impl Performance {
+    pub fn new(navigation_start: Instant) -> Self {
+        Performance {
+            metrics: RefCell::new(PageMetrics::new(navigation_start)),
+        }
+    }
+
+    pub fn time_origin(&self) -> Instant {
+        self.metrics.borrow().navigation_start
+    }
+
+    pub fn now(&self) -> f64 {
+        self.time_origin().elapsed().as_secs_f64() * 1000.0
+    }
+
+    pub fn metrics(&self) -> PageMetrics {
+        *self.metrics.borrow()
+    }
+
+    pub fn record_response(&self, timing: &Timing) {
+        let mut metrics = self.metrics.borrow_mut();
+        metrics.fetch_start = Some(timing.fetch_start);
+        metrics.response_start = Some(timing.response_start);
+        metrics.response_end = timing.response_end;
+    }
+
+    pub fn mark_dom_parsed(&self) {
+        self.metrics.borrow_mut().dom_parsed = Some(Instant::now());
+    }
+
+    pub fn mark_first_paint(&self) {
+        let mut metrics = self.metrics.borrow_mut();
+        if metrics.first_paint.is_none() {
+            metrics.first_paint = Some(Instant::now());
+        }
+    }
+
+    pub fn mark_load_complete(&self) {
+        let mut metrics = self.metrics.borrow_mut();
+        if metrics.load_complete.is_none() {
+            metrics.load_complete = Some(Instant::now());
+        }
+    }
+}
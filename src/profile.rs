@@ -0,0 +1,162 @@
+use std::fmt::Debug;
+use std::string::String;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use tracing::Subscriber;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+#[derive(Debug, Clone, Default)]
+pub struct StageStats {
+    pub name: &'static str,
+    pub calls: usize,
+    pub total: Duration,
+    pub counts: Vec<(&'static str, u64)>,
+}
+
+impl StageStats {
+    fn add_count(&mut self, name: &'static str, value: u64) {
+        match self.counts.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, total)) => *total += value,
+            None => self.counts.push((name, value)),
+        }
+    }
+}
+
+struct SpanTiming {
+    started: Instant,
+    counts: Vec<(&'static str, u64)>,
+}
+
+struct CountVisitor<'a>(&'a mut Vec<(&'static str, u64)>);
+
+impl Visit for CountVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match self.0.iter_mut().find(|(key, _)| *key == field.name()) {
+            Some((_, total)) => *total = value,
+            None => self.0.push((field.name(), value)),
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_u64(field, value.max(0) as u64);
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+}
+
+#[derive(Clone, Default)]
+pub struct Profiler {
+    stages: Arc<Mutex<Vec<StageStats>>>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+
+    pub fn install(&self) -> bool {
+        let subscriber = Registry::default().with(self.clone());
+        tracing::subscriber::set_global_default(subscriber).is_ok()
+    }
+
+    pub fn stages(&self) -> Vec<StageStats> {
+        self.stages
+            .lock()
+            .map(|stages| stages.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut stages) = self.stages.lock() {
+            stages.clear();
+        }
+    }
+
+    pub fn report(&self) -> String {
+        let stages = self.stages();
+        let mut out = format!(
+            "{:<14} {:>6} {:>11} {:>11}  counts\n",
+            "stage", "calls", "total ms", "mean ms"
+        );
+        for stage in &stages {
+            let total = stage.total.as_secs_f64() * 1000.0;
+            let counts = stage
+                .counts
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>()
+                .join(" ");
+            out.push_str(&format!(
+                "{:<14} {:>6} {:>11.3} {:>11.3}  {}\n",
+                stage.name,
+                stage.calls,
+                total,
+                total / stage.calls.max(1) as f64,
+                counts
+            ));
+        }
+        out
+    }
+}
+
+impl<S> Layer<S> for Profiler
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut timing = SpanTiming {
+            started: Instant::now(),
+            counts: Vec::new(),
+        };
+        attrs.record(&mut CountVisitor(&mut timing.counts));
+        span.extensions_mut().insert(timing);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+            values.record(&mut CountVisitor(&mut timing.counts));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let Ok(mut stages) = self.stages.lock() else {
+            return;
+        };
+
+        let name = span.name();
+        let index = match stages.iter().position(|stage| stage.name == name) {
+            Some(index) => index,
+            None => {
+                stages.push(StageStats {
+                    name,
+                    ..StageStats::default()
+                });
+                stages.len() - 1
+            }
+        };
+        let stage = &mut stages[index];
+        stage.calls += 1;
+        stage.total += timing.started.elapsed();
+        for (name, value) in timing.counts {
+            stage.add_count(name, value);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Node, NodeData};
+
+#[derive(Clone)]
+pub struct BoundaryPoint {
+    pub node: Rc<Node>,
+    pub offset: usize,
+}
+
+impl BoundaryPoint {
+    pub fn new(node: &Rc<Node>, offset: usize) -> Self {
+        BoundaryPoint {
+            node: Rc::clone(node),
+            offset: offset.min(node_length(node)),
+        }
+    }
+
+    pub fn compare(&self, other: &BoundaryPoint) -> Ordering {
+        let mut a = index_path(&self.node);
+        a.push(self.offset);
+        let mut b = index_path(&other.node);
+        b.push(other.offset);
+        a.cmp(&b)
+    }
+}
+
+#[derive(Clone)]
+pub struct Range {
+    pub start: BoundaryPoint,
+    pub end: BoundaryPoint,
+}
+
+impl Range {
+    pub fn new(node: &Rc<Node>) -> Self {
+        let point = BoundaryPoint::new(node, 0);
+        Range {
+            start: point.clone(),
+            end: point,
+        }
+    }
+
+    pub fn from_points(a: BoundaryPoint, b: BoundaryPoint) -> Self {
+        if a.compare(&b) == Ordering::Greater {
+            Range { start: b, end: a }
+        } else {
+            Range { start: a, end: b }
+        }
+    }
+
+    pub fn set_start(&mut self, node: &Rc<Node>, offset: usize) {
+        self.start = BoundaryPoint::new(node, offset);
+        if self.start.compare(&self.end) == Ordering::Greater || !same_root(node, &self.end.node) {
+            self.end = self.start.clone();
+        }
+    }
+
+    pub fn set_end(&mut self, node: &Rc<Node>, offset: usize) {
+        self.end = BoundaryPoint::new(node, offset);
+        if self.end.compare(&self.start) == Ordering::Less || !same_root(node, &self.start.node) {
+            self.start = self.end.clone();
+        }
+    }
+
+    pub fn select_node(&mut self, node: &Rc<Node>) {
+        let Some(parent) = node.parent.borrow().upgrade() else {
+            return;
+        };
+        let index = child_index(&parent, node).unwrap_or(0);
+        self.start = BoundaryPoint::new(&parent, index);
+        self.end = BoundaryPoint::new(&parent, index + 1);
+    }
+
+    pub fn select_node_contents(&mut self, node: &Rc<Node>) {
+        self.start = BoundaryPoint::new(node, 0);
+        self.end = BoundaryPoint::new(node, node_length(node));
+    }
+
+    pub fn collapse(&mut self, to_start: bool) {
+        if to_start {
+            self.end = self.start.clone();
+        } else {
+            self.start = self.end.clone();
+        }
+    }
+
+    pub fn collapsed(&self) -> bool {
+        Rc::ptr_eq(&self.start.node, &self.end.node) && self.start.offset == self.end.offset
+    }
+
+    pub fn common_ancestor(&self) -> Rc<Node> {
+        let ancestors = inclusive_ancestors(&self.start.node);
+        let mut current = Some(Rc::clone(&self.end.node));
+        while let Some(node) = current {
+            if ancestors.iter().any(|a| Rc::ptr_eq(a, &node)) {
+                return node;
+            }
+            current = node.parent.borrow().upgrade();
+        }
+        Rc::clone(&self.start.node)
+    }
+
+    pub fn contains_point(&self, point: &BoundaryPoint) -> bool {
+        self.start.compare(point) != Ordering::Greater && self.end.compare(point) != Ordering::Less
+    }
+
+    pub fn clone_contents(&self) -> Vec<Rc<Node>> {
+        if self.collapsed() {
+            return Vec::new();
+        }
+        self.process(&self.common_ancestor(), false)
+    }
+
+    pub fn extract_contents(&mut self) -> Vec<Rc<Node>> {
+        if self.collapsed() {
+            return Vec::new();
+        }
+        let extracted = self.process(&self.common_ancestor(), true);
+        self.collapse(true);
+        extracted
+    }
+
+    pub fn delete_contents(&mut self) {
+        self.extract_contents();
+    }
+
+    fn process(&self, node: &Rc<Node>, extract: bool) -> Vec<Rc<Node>> {
+        if let Some(text) = character_data(node) {
+            let start = if Rc::ptr_eq(node, &self.start.node) {
+                self.start.offset
+            } else {
+                0
+            };
+            let end = if Rc::ptr_eq(node, &self.end.node) {
+                self.end.offset
+            } else {
+                text.chars().count()
+            };
+            let (before, middle, after) = split_chars(&text, start, end);
+            if extract {
+                Node::set_character_data(node, &(before + &after));
+            }
+            return vec![Node::new(copy_data(node, middle))];
+        }
+
+        let children: Vec<Rc<Node>> = node.children.borrow().clone();
+        let mut result = Vec::new();
+
+        for (index, child) in children.iter().enumerate() {
+            let before_child = BoundaryPoint {
+                node: Rc::clone(node),
+                offset: index,
+            };
+            let after_child = BoundaryPoint {
+                node: Rc::clone(node),
+                offset: index + 1,
+            };
+
+            let fully_contained = self.start.compare(&before_child) != Ordering::Greater
+                && self.end.compare(&after_child) != Ordering::Less;
+            if fully_contained {
+                if extract {
+                    Node::detach(child);
+                    result.push(Rc::clone(child));
+                } else {
+                    result.push(Node::clone_node(child, true));
+                }
+                continue;
+            }
+
+            let partially_contained = is_inclusive_ancestor(child, &self.start.node)
+                || is_inclusive_ancestor(child, &self.end.node);
+            if !partially_contained {
+                continue;
+            }
+
+            let inner = self.process(child, extract);
+            if character_data(child).is_some() {
+                result.extend(inner);
+            } else {
+                let shallow = Node::clone_node(child, false);
+                for node in inner {
+                    Node::append_child(&shallow, node);
+                }
+                result.push(shallow);
+            }
+        }
+
+        result
+    }
+
+    pub fn to_text(&self) -> String {
+        self.clone_contents()
+            .iter()
+            .map(|node| node.get_text_content())
+            .collect()
+    }
+}
+
+pub struct Selection {
+    anchor: Option<BoundaryPoint>,
+    focus: Option<BoundaryPoint>,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Selection {
+            anchor: None,
+            focus: None,
+        }
+    }
+
+    pub fn anchor(&self) -> Option<&BoundaryPoint> {
+        self.anchor.as_ref()
+    }
+
+    pub fn focus(&self) -> Option<&BoundaryPoint> {
+        self.focus.as_ref()
+    }
+
+    pub fn range(&self) -> Option<Range> {
+        match (&self.anchor, &self.focus) {
+            (Some(anchor), Some(focus)) => Some(Range::from_points(anchor.clone(), focus.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        self.range().is_none_or(|range| range.collapsed())
+    }
+
+    pub fn collapse(&mut self, node: &Rc<Node>, offset: usize) {
+        let point = BoundaryPoint::new(node, offset);
+        self.anchor = Some(point.clone());
+        self.focus = Some(point);
+    }
+
+    pub fn extend(&mut self, node: &Rc<Node>, offset: usize) {
+        if self.anchor.is_none() {
+            self.collapse(node, offset);
+            return;
+        }
+        self.focus = Some(BoundaryPoint::new(node, offset));
+    }
+
+    pub fn set_range(&mut self, range: Range) {
+        self.anchor = Some(range.start);
+        self.focus = Some(range.end);
+    }
+
+    pub fn select_all_children(&mut self, node: &Rc<Node>) {
+        let mut range = Range::new(node);
+        range.select_node_contents(node);
+        self.set_range(range);
+    }
+
+    pub fn remove_all_ranges(&mut self) {
+        self.anchor = None;
+        self.focus = None;
+    }
+
+    pub fn to_text(&self) -> String {
+        self.range()
+            .map(|range| range.to_text())
+            .unwrap_or_default()
+    }
+}
+
+pub fn node_length(node: &Node) -> usize {
+    match &node.data {
+        NodeData::Text { contents } | NodeData::Comment { contents } => {
+            contents.borrow().chars().count()
+        }
+        NodeData::Doctype { .. } => 0,
+        _ => node.children.borrow().len(),
+    }
+}
+
+fn character_data(node: &Node) -> Option<String> {
+    match &node.data {
+        NodeData::Text { contents } | NodeData::Comment { contents } => {
+            Some(contents.borrow().clone())
+        }
+        _ => None,
+    }
+}
+
+fn copy_data(node: &Node, data: String) -> NodeData {
+    match &node.data {
+        NodeData::Comment { .. } => NodeData::Comment {
+            contents: RefCell::new(data),
+        },
+        _ => NodeData::Text {
+            contents: RefCell::new(data),
+        },
+    }
+}
+
+fn split_chars(text: &str, start: usize, end: usize) -> (String, String, String) {
+    let before = text.chars().take(start).collect();
+    let middle = text
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect();
+    let after = text.chars().skip(end).collect();
+    (before, middle, after)
+}
+
+fn child_index(parent: &Rc<Node>, child: &Rc<Node>) -> Option<usize> {
+    parent
+        .children
+        .borrow()
+        .iter()
+        .position(|n| Rc::ptr_eq(n, child))
+}
+
+fn index_path(node: &Rc<Node>) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut current = Rc::clone(node);
+    loop {
+        let parent = current.parent.borrow().upgrade();
+        let Some(parent) = parent else {
+            break;
+        };
+        path.push(child_index(&parent, &current).unwrap_or(0));
+        current = parent;
+    }
+    path.reverse();
+    path
+}
+
+fn inclusive_ancestors(node: &Rc<Node>) -> Vec<Rc<Node>> {
+    let mut ancestors = vec![Rc::clone(node)];
+    let mut current = node.parent.borrow().upgrade();
+    while let Some(parent) = current {
+        current = parent.parent.borrow().upgrade();
+        ancestors.push(parent);
+    }
+    ancestors
+}
+
+fn is_inclusive_ancestor(ancestor: &Rc<Node>, node: &Rc<Node>) -> bool {
+    inclusive_ancestors(node)
+        .iter()
+        .any(|candidate| Rc::ptr_eq(candidate, ancestor))
+}
+
+fn same_root(a: &Rc<Node>, b: &Rc<Node>) -> bool {
+    let root_a = inclusive_ancestors(a).pop();
+    let root_b = inclusive_ancestors(b).pop();
+    match (root_a, root_b) {
+        (Some(x), Some(y)) => Rc::ptr_eq(&x, &y),
+        _ => false,
+    }
+}
@@ -0,0 +1,252 @@
+use html5ever::ns;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData};
+use crate::html::escape::escape_text;
+use crate::html::parser::parse_html;
+use crate::html::serialize::inner_html;
+
+const UNLIKELY_TAGS: &[&str] = &[
+    "script", "style", "noscript", "nav", "aside", "footer", "header", "form", "iframe", "button",
+    "input", "select", "textarea", "svg", "template",
+];
+
+const NEGATIVE_HINTS: &[&str] = &[
+    "comment",
+    "meta",
+    "footer",
+    "footnote",
+    "nav",
+    "sidebar",
+    "sponsor",
+    "ad-",
+    "ads",
+    "advert",
+    "share",
+    "social",
+    "related",
+    "menu",
+    "banner",
+    "promo",
+    "popup",
+    "cookie",
+    "newsletter",
+    "subscribe",
+];
+
+const READER_STYLE: &str = "body { max-width: 40em; margin: 2em auto; padding: 0 1em; \
+    font-size: 18px; line-height: 1.6 } img { max-width: 100% }";
+
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "text", "blog", "story",
+];
+
+pub struct Article {
+    pub title: String,
+    pub content: Document,
+}
+
+impl Article {
+    pub fn text(&self) -> String {
+        self.content.root.get_text_content()
+    }
+
+    pub fn page(&self, source: &Document) -> Document {
+        let body = self
+            .content
+            .get_elements_by_tag_name("article")
+            .first()
+            .map(inner_html)
+            .unwrap_or_default();
+        let title = escape_text(&self.title);
+        let mut page = parse_html(&format!(
+            "<!DOCTYPE html><html><head><title>{}</title><style>{}</style></head>\
+             <body><h1>{}</h1>{}</body></html>",
+            title, READER_STYLE, title, body
+        ));
+        page.response = source.response.clone();
+        page.settings = source.settings.clone();
+        page.storage = source.storage.clone();
+        page
+    }
+}
+
+pub fn extract_article(document: &Document) -> Option<Article> {
+    let title = document
+        .get_elements_by_tag_name_ns(&ns!(html), "title")
+        .first()
+        .map(|title| title.get_text_content().trim().to_string())
+        .unwrap_or_default();
+
+    let mut scores: HashMap<*const Node, (Rc<Node>, f64)> = HashMap::new();
+
+    let mut paragraphs = Vec::new();
+    document.root.walk_rc(&mut |node| {
+        if matches!(node.element_name(), Some("p" | "pre" | "td" | "blockquote")) {
+            paragraphs.push(Rc::clone(node));
+        }
+    });
+
+    for paragraph in &paragraphs {
+        if is_unlikely(paragraph) {
+            continue;
+        }
+        let text = paragraph.get_text_content();
+        let text = text.trim();
+        if text.chars().count() < 25 {
+            continue;
+        }
+
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+
+        let parent = paragraph.parent.borrow().upgrade();
+        if let Some(parent) = parent {
+            add_score(&mut scores, &parent, score);
+            let grandparent = parent.parent.borrow().upgrade();
+            if let Some(grandparent) = grandparent {
+                add_score(&mut scores, &grandparent, score / 2.0);
+            }
+        }
+    }
+
+    let best = scores
+        .values()
+        .map(|(node, score)| (node, score * (1.0 - link_density(node))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(node, _)| Rc::clone(node))?;
+
+    let content = parse_html(
+        "<!DOCTYPE html><html><head><title></title></head><body><article></article></body></html>",
+    );
+    if let Some(head_title) = content.get_elements_by_tag_name("title").first() {
+        Node::append_child(
+            head_title,
+            Node::new(NodeData::Text {
+                contents: RefCell::new(title.clone()),
+            }),
+        );
+    }
+    let article = content
+        .get_elements_by_tag_name("article")
+        .first()
+        .cloned()?;
+
+    let cleaned = Node::clone_node(&best, true);
+    clean(document, &cleaned);
+    let children = cleaned.children.borrow().clone();
+    for child in children {
+        Node::append_child(&article, child);
+    }
+
+    Some(Article { title, content })
+}
+
+fn add_score(scores: &mut HashMap<*const Node, (Rc<Node>, f64)>, node: &Rc<Node>, score: f64) {
+    if node.element_name().is_none() {
+        return;
+    }
+    scores
+        .entry(Rc::as_ptr(node))
+        .or_insert_with(|| (Rc::clone(node), initial_score(node)))
+        .1 += score;
+}
+
+fn initial_score(node: &Node) -> f64 {
+    let tag_score = match node.element_name() {
+        Some("article") => 10.0,
+        Some("main" | "section") => 8.0,
+        Some("div") => 5.0,
+        Some("pre" | "td" | "blockquote") => 3.0,
+        Some("address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form") => -3.0,
+        Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th") => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(node)
+}
+
+fn class_weight(node: &Node) -> f64 {
+    let hints = format!(
+        "{} {}",
+        node.get_attribute("class").unwrap_or_default(),
+        node.get_attribute("id").unwrap_or_default()
+    )
+    .to_ascii_lowercase();
+
+    let mut weight = 0.0;
+    if NEGATIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn is_unlikely(node: &Rc<Node>) -> bool {
+    let mut current = Some(Rc::clone(node));
+    while let Some(node) = current {
+        if let Some(name) = node.element_name() {
+            if UNLIKELY_TAGS.contains(&name) {
+                return true;
+            }
+            if class_weight(&node) < 0.0 && !matches!(name, "body" | "html") {
+                return true;
+            }
+        }
+        current = node.parent.borrow().upgrade();
+    }
+    false
+}
+
+fn link_density(node: &Rc<Node>) -> f64 {
+    let total = node.get_text_content().trim().len();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let mut link_text = 0;
+    node.walk(&mut |n| {
+        if n.element_name() == Some("a") {
+            link_text += n.get_text_content().trim().len();
+        }
+    });
+    (link_text as f64 / total as f64).min(1.0)
+}
+
+fn clean(document: &Document, node: &Rc<Node>) {
+    let children = node.children.borrow().clone();
+    for child in children {
+        let remove = match &child.data {
+            NodeData::Comment { .. } => true,
+            NodeData::Element { .. } => {
+                let name = child.element_name().unwrap_or("");
+                UNLIKELY_TAGS.contains(&name)
+                    || (class_weight(&child) < 0.0 && link_density(&child) > 0.3)
+                    || (matches!(name, "ul" | "ol" | "div" | "table") && link_density(&child) > 0.5)
+            }
+            _ => false,
+        };
+
+        if remove {
+            Node::remove_child(node, &child);
+            continue;
+        }
+
+        if let NodeData::Element { attrs, .. } = &child.data {
+            let mut attrs = attrs.borrow_mut();
+            attrs.retain(|attr| matches!(&*attr.name.local, "href" | "src" | "alt" | "title"));
+            for attr in attrs.iter_mut() {
+                if matches!(&*attr.name.local, "href" | "src")
+                    && let Some(url) = document.resolve_url(&attr.value)
+                {
+                    attr.value = url.into();
+                }
+            }
+        }
+        clean(document, &child);
+    }
+}
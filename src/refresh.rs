@@ -0,0 +1,119 @@
+use std::string::String;
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::dom::Document;
+use crate::location::Navigation;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Refresh {
+    pub delay: Duration,
+    pub url: Option<Url>,
+}
+
+impl Refresh {
+    pub fn from_document(document: &Document) -> Option<Refresh> {
+        let content = document
+            .get_elements_by_tag_name("meta")
+            .iter()
+            .find(|meta| {
+                meta.get_attribute("http-equiv")
+                    .is_some_and(|value| value.trim().eq_ignore_ascii_case("refresh"))
+            })
+            .and_then(|meta| meta.get_attribute("content"))
+            .or_else(|| document.header("Refresh").map(|value| value.to_string()))?;
+
+        let (delay, target) = parse_refresh(&content)?;
+        let url = match target {
+            Some(target) => Some(document.resolve_url(&target)?),
+            None => None,
+        };
+
+        Some(Refresh { delay, url })
+    }
+
+    pub fn navigation(&self, current: &str) -> Navigation {
+        match &self.url {
+            Some(url) if url.as_str() != current => Navigation::Load {
+                url: url.to_string(),
+                replace: true,
+            },
+            _ => Navigation::Reload,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RefreshTimer {
+    pending: Option<(Instant, Refresh)>,
+}
+
+impl RefreshTimer {
+    pub fn schedule(&mut self, refresh: Refresh, now: Instant) {
+        self.pending = Some((now + refresh.delay, refresh));
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn take_due(&mut self, now: Instant) -> Option<Refresh> {
+        match &self.pending {
+            Some((due, _)) if *due <= now => self.pending.take().map(|(_, refresh)| refresh),
+            _ => None,
+        }
+    }
+}
+
+pub fn parse_refresh(content: &str) -> Option<(Duration, Option<String>)> {
+    let content = content.trim_start();
+    let digits_end = content
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(content.len());
+    let number = &content[..digits_end];
+    if number.is_empty() || number.starts_with('.') {
+        return None;
+    }
+    let seconds: u64 = number.split('.').next()?.parse().ok()?;
+    let delay = Duration::from_secs(seconds);
+
+    let rest = content[digits_end..].trim_start();
+    let rest = rest
+        .strip_prefix(';')
+        .or_else(|| rest.strip_prefix(','))
+        .unwrap_or(rest)
+        .trim_start();
+    if rest.is_empty() {
+        return Some((delay, None));
+    }
+
+    let rest = match rest.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("url") => {
+            let after = rest[3..].trim_start();
+            match after.strip_prefix('=') {
+                Some(value) => value.trim_start(),
+                None => rest,
+            }
+        }
+        _ => rest,
+    };
+
+    let url = match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let inner = &rest[1..];
+            inner.split(quote).next().unwrap_or(inner)
+        }
+        _ => rest,
+    }
+    .trim();
+
+    if url.is_empty() {
+        Some((delay, None))
+    } else {
+        Some((delay, Some(url.to_string())))
+    }
+}
@@ -0,0 +1,34 @@
+pub mod pdf;
+pub mod svg;
+pub mod tty;
+
+use crate::dom::Document;
+use crate::layout::{LayoutOptions, LayoutTree};
+use crate::paint::DisplayList;
+use crate::paint::font::FontSet;
+use crate::paint::raster::Canvas;
+use crate::web_font::FontLoader;
+
+pub fn raster(document: &Document, width: usize, height: Option<usize>) -> Canvas {
+    let settings = &document.settings;
+    let fonts = FontSet::load(&settings.fonts);
+    if let Some(fonts) = &fonts {
+        for font in FontLoader::new().load(document) {
+            fonts.register(&font.family, font.bold, font.italic, &font.data);
+        }
+    }
+    let tree = LayoutTree::build(
+        document,
+        LayoutOptions {
+            viewport_width: width as f32,
+            font: fonts.as_ref().map(FontSet::proportions).unwrap_or_default(),
+            ..LayoutOptions::default()
+        },
+    );
+    let height = height.unwrap_or_else(|| tree.height().ceil().max(1.0) as usize);
+    let mut canvas = Canvas::new(width, height, settings.palette().background);
+    canvas.paint(&DisplayList::build(&tree), fonts.as_ref(), 0.0);
+    document.performance.mark_first_paint();
+    document.performance.mark_load_complete();
+    canvas
+}
@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use crate::css::media::MediaType;
+use crate::css::values::Color;
+use crate::dom::Document;
+use crate::image::Image;
+use crate::layout::inline::image_placeholder_label;
+use crate::layout::{LayoutBox, LayoutOptions, LayoutTree, Rect};
+use crate::metadata::PageMetadata;
+use crate::paint::{DisplayItem, DisplayList, shadow_shape};
+use crate::style::{Dimension, Sides};
+
+pub const POINTS_PER_PX: f32 = 0.75;
+
+const FONTS: &[&str] = &[
+    "Courier",
+    "Courier-Bold",
+    "Courier-Oblique",
+    "Courier-BoldOblique",
+];
+
+const PLACEHOLDER_COLOR: Color = Color::rgb(160, 160, 160);
+const SHADOW_STEPS: usize = 4;
+// Content past this many pages is dropped rather than paginated.
+const MAX_PAGES: usize = 10_000;
+
+const WIN_ANSI: &[(char, u8)] = &[
+    ('€', 0x80),
+    ('‚', 0x82),
+    ('„', 0x84),
+    ('…', 0x85),
+    ('‘', 0x91),
+    ('’', 0x92),
+    ('“', 0x93),
+    ('”', 0x94),
+    ('•', 0x95),
+    ('–', 0x96),
+    ('—', 0x97),
+    ('™', 0x99),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PageSize {
+    pub const A4: PageSize = PageSize {
+        width: 793.7,
+        height: 1122.5,
+    };
+    pub const A5: PageSize = PageSize {
+        width: 559.4,
+        height: 793.7,
+    };
+    pub const LETTER: PageSize = PageSize {
+        width: 816.0,
+        height: 1056.0,
+    };
+    pub const LEGAL: PageSize = PageSize {
+        width: 816.0,
+        height: 1344.0,
+    };
+
+    pub fn parse(value: &str) -> Option<PageSize> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "a4" => Some(PageSize::A4),
+            "a5" => Some(PageSize::A5),
+            "letter" => Some(PageSize::LETTER),
+            "legal" => Some(PageSize::LEGAL),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintOptions {
+    pub page: PageSize,
+    pub margin: Sides<f32>,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            page: PageSize::A4,
+            margin: Sides::all(48.0),
+        }
+    }
+}
+
+impl PrintOptions {
+    pub fn content_width(&self) -> f32 {
+        (self.page.width - self.margin.left - self.margin.right).max(1.0)
+    }
+
+    pub fn content_height(&self) -> f32 {
+        (self.page.height - self.margin.top - self.margin.bottom).max(1.0)
+    }
+
+    fn with_page_margin(&self, margin: Sides<Dimension>) -> PrintOptions {
+        let (width, height) = (self.page.width, self.page.height);
+        PrintOptions {
+            page: self.page,
+            margin: Sides {
+                top: margin.top.resolve(height).unwrap_or(self.margin.top),
+                right: margin.right.resolve(width).unwrap_or(self.margin.right),
+                bottom: margin.bottom.resolve(height).unwrap_or(self.margin.bottom),
+                left: margin.left.resolve(width).unwrap_or(self.margin.left),
+            },
+        }
+    }
+}
+
+pub fn print(document: &Document, options: &PrintOptions) -> Vec<u8> {
+    let media_type = document.media_type.replace(MediaType::Print);
+    document.restyle();
+    let margin = document
+        .stylist
+        .borrow()
+        .as_ref()
+        .map(|stylist| stylist.page_margin());
+    let options = margin.map_or(*options, |margin| options.with_page_margin(margin));
+    let tree = LayoutTree::build(
+        document,
+        LayoutOptions {
+            viewport_width: options.content_width(),
+            ..LayoutOptions::default()
+        },
+    );
+    let list = DisplayList::build(&tree);
+    let breaks = page_breaks(&tree, &list, options.content_height());
+    let title = PageMetadata::load(document).title;
+    document.media_type.set(media_type);
+    document.restyle();
+    write_pdf(&list, &breaks, &options, title.as_deref())
+}
+
+pub fn page_breaks(tree: &LayoutTree, list: &DisplayList, page_height: f32) -> Vec<f32> {
+    let mut forced = Vec::new();
+    let mut unbreakable = Vec::new();
+    collect_breaks(&tree.root, &mut forced, &mut unbreakable);
+    forced.sort_by(f32::total_cmp);
+
+    let bottom = list
+        .items
+        .iter()
+        .map(|item| item.bounds().bottom())
+        .fold(tree.height(), f32::max)
+        .min(page_height * MAX_PAGES as f32);
+    let mut starts = vec![0.0];
+    let mut top = 0.0;
+    while starts.len() < MAX_PAGES {
+        let limit = top + page_height;
+        if limit <= top {
+            break;
+        }
+        let next = match forced.iter().find(|y| **y > top + 0.5) {
+            Some(y) if *y <= limit => *y,
+            _ if limit >= bottom => break,
+            _ => avoid_splitting(list, &unbreakable, top, limit),
+        };
+        // Every page must move forward; cut through whatever straddles the
+        // limit when nothing can be kept whole.
+        let next = if next > top { next } else { limit };
+        if next >= bottom {
+            break;
+        }
+        starts.push(next);
+        top = next;
+    }
+    starts
+}
+
+fn collect_breaks(layout_box: &LayoutBox, forced: &mut Vec<f32>, unbreakable: &mut Vec<Rect>) {
+    if layout_box.style.break_before {
+        forced.push(layout_box.margin_box().y);
+    }
+    if layout_box.style.break_inside_avoid {
+        unbreakable.push(layout_box.border_box());
+    }
+    for child in &layout_box.children {
+        collect_breaks(child, forced, unbreakable);
+    }
+    if layout_box.style.break_after {
+        forced.push(layout_box.margin_box().bottom());
+    }
+}
+
+fn avoid_splitting(list: &DisplayList, unbreakable: &[Rect], top: f32, limit: f32) -> f32 {
+    let mut cut = limit;
+    loop {
+        let straddling = list
+            .items
+            .iter()
+            .filter(|item| {
+                !matches!(
+                    item,
+                    DisplayItem::SolidColor { .. }
+                        | DisplayItem::BoxShadow { .. }
+                        | DisplayItem::Gradient { .. }
+                )
+            })
+            .map(DisplayItem::bounds)
+            .chain(unbreakable.iter().copied())
+            .filter(|rect| rect.y > top && rect.y < cut && rect.bottom() > cut)
+            .map(|rect| rect.y)
+            .fold(cut, f32::min);
+        if straddling >= cut {
+            return cut;
+        }
+        cut = straddling;
+    }
+}
+
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        let byte = match c {
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as u8,
+            _ => WIN_ANSI
+                .iter()
+                .find(|(special, _)| *special == c)
+                .map(|(_, byte)| *byte)
+                .unwrap_or(b'?'),
+        };
+        if matches!(byte, b'(' | b')' | b'\\') {
+            bytes.push(b'\\');
+        }
+        bytes.push(byte);
+    }
+    bytes
+}
+
+fn rgb(color: Color) -> String {
+    format!(
+        "{:.3} {:.3} {:.3}",
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0
+    )
+}
+
+struct Page<'a> {
+    options: &'a PrintOptions,
+    top: f32,
+    images: &'a HashMap<*const Image, usize>,
+    out: Vec<u8>,
+}
+
+impl Page<'_> {
+    fn x(&self, x: f32) -> f32 {
+        (x + self.options.margin.left) * POINTS_PER_PX
+    }
+
+    fn y(&self, y: f32) -> f32 {
+        (self.options.page.height - (y - self.top + self.options.margin.top)) * POINTS_PER_PX
+    }
+
+    fn push(&mut self, operators: &str) {
+        self.out.extend_from_slice(operators.as_bytes());
+        self.out.push(b'\n');
+    }
+
+    fn fill(&mut self, rect: Rect, color: Color) {
+        let operators = format!(
+            "{} rg {:.2} {:.2} {:.2} {:.2} re f",
+            rgb(color),
+            self.x(rect.x),
+            self.y(rect.bottom()),
+            rect.width * POINTS_PER_PX,
+            rect.height * POINTS_PER_PX
+        );
+        self.push(&operators);
+    }
+
+    fn text(&mut self, rect: Rect, text: &str, color: Color, font_size: f32, font: usize) {
+        let baseline = rect.y + rect.height / 2.0 + font_size * 0.3;
+        let operators = format!(
+            "BT /F{} {:.2} Tf {} rg {:.2} {:.2} Td (",
+            font + 1,
+            font_size * POINTS_PER_PX,
+            rgb(color),
+            self.x(rect.x),
+            self.y(baseline)
+        );
+        self.out.extend_from_slice(operators.as_bytes());
+        self.out.extend_from_slice(&encode_text(text));
+        self.push(") Tj ET");
+    }
+
+    fn paint(&mut self, item: &DisplayItem) {
+        match item {
+            DisplayItem::SolidColor { rect, color } => self.fill(*rect, *color),
+            DisplayItem::PushClip { rect } => {
+                let operators = format!(
+                    "q {:.2} {:.2} {:.2} {:.2} re W n",
+                    self.x(rect.x),
+                    self.y(rect.bottom()),
+                    rect.width * POINTS_PER_PX,
+                    rect.height * POINTS_PER_PX
+                );
+                self.push(&operators);
+            }
+            DisplayItem::PopClip => self.push("Q"),
+            DisplayItem::Text {
+                rect,
+                text,
+                color,
+                font_size,
+                bold,
+                italic,
+                ..
+            } => {
+                let font = *bold as usize + 2 * *italic as usize;
+                self.text(*rect, text, *color, *font_size, font);
+            }
+            DisplayItem::ImagePlaceholder {
+                rect,
+                alt,
+                font_size,
+            } => {
+                let operators = format!(
+                    "{} RG 0.75 w {:.2} {:.2} {:.2} {:.2} re S",
+                    rgb(PLACEHOLDER_COLOR),
+                    self.x(rect.x),
+                    self.y(rect.bottom()),
+                    rect.width * POINTS_PER_PX,
+                    rect.height * POINTS_PER_PX
+                );
+                self.push(&operators);
+                let label = Rect::new(rect.x + 2.0, rect.y, rect.width, font_size * 1.2);
+                let label_text = image_placeholder_label(alt);
+                self.text(label, &label_text, PLACEHOLDER_COLOR, *font_size, 0);
+            }
+            DisplayItem::BoxShadow { rect, shadow } => {
+                if shadow.inset {
+                    return;
+                }
+                let shape = shadow_shape(*rect, shadow);
+                let alpha = shadow.color.a as f32 / 255.0;
+                for step in 0..SHADOW_STEPS {
+                    let strength = (step + 1) as f32 / SHADOW_STEPS as f32;
+                    let grow = shadow.blur * (1.0 - strength);
+                    let mix = |channel: u8| {
+                        let amount = alpha * strength;
+                        (255.0 + (channel as f32 - 255.0) * amount).round() as u8
+                    };
+                    let color = Color::rgb(
+                        mix(shadow.color.r),
+                        mix(shadow.color.g),
+                        mix(shadow.color.b),
+                    );
+                    self.fill(shape.expand(&Sides::all(grow)), color);
+                }
+            }
+            DisplayItem::Gradient {
+                rect,
+                origin,
+                gradient,
+            } => {
+                let center = gradient.sampler(*origin).color_at(
+                    origin.x + origin.width / 2.0,
+                    origin.y + origin.height / 2.0,
+                );
+                self.fill(*rect, center);
+            }
+            DisplayItem::Image {
+                rect,
+                image,
+                source,
+                ..
+            } => {
+                let Some(index) = self.images.get(&Arc::as_ptr(image)) else {
+                    return;
+                };
+                let (scale_x, scale_y) = (rect.width / source.width, rect.height / source.height);
+                let placed = Rect::new(
+                    rect.x - source.x * scale_x,
+                    rect.y - source.y * scale_y,
+                    image.width as f32 * scale_x,
+                    image.height as f32 * scale_y,
+                );
+                let operators = format!(
+                    "q {:.2} {:.2} {:.2} {:.2} re W n {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{} Do Q",
+                    self.x(rect.x),
+                    self.y(rect.bottom()),
+                    rect.width * POINTS_PER_PX,
+                    rect.height * POINTS_PER_PX,
+                    placed.width * POINTS_PER_PX,
+                    placed.height * POINTS_PER_PX,
+                    self.x(placed.x),
+                    self.y(placed.bottom()),
+                    index + 1
+                );
+                self.push(&operators);
+            }
+        }
+    }
+}
+
+fn stream(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let mut object = format!("<< {} /Length {} >>\nstream\n", dictionary, data.len()).into_bytes();
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
+
+fn write_pdf(
+    list: &DisplayList,
+    breaks: &[f32],
+    options: &PrintOptions,
+    title: Option<&str>,
+) -> Vec<u8> {
+    let mut objects: Vec<Vec<u8>> = vec![Vec::new(), Vec::new()];
+    let fonts: Vec<usize> = FONTS
+        .iter()
+        .map(|font| {
+            objects.push(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font
+                )
+                .into_bytes(),
+            );
+            objects.len()
+        })
+        .collect();
+
+    let mut images = HashMap::new();
+    let mut image_objects = Vec::new();
+    for item in &list.items {
+        let DisplayItem::Image { image, .. } = item else {
+            continue;
+        };
+        if images.contains_key(&Arc::as_ptr(image)) {
+            continue;
+        }
+        let pixels: Vec<u8> = image
+            .pixels
+            .iter()
+            .flat_map(|pixel| [pixel.r, pixel.g, pixel.b])
+            .collect();
+        objects.push(stream(
+            &format!(
+                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8",
+                image.width, image.height
+            ),
+            &pixels,
+        ));
+        images.insert(Arc::as_ptr(image), image_objects.len());
+        image_objects.push(objects.len());
+    }
+
+    let font_resources: Vec<String> = fonts
+        .iter()
+        .enumerate()
+        .map(|(index, id)| format!("/F{} {} 0 R", index + 1, id))
+        .collect();
+    let image_resources: Vec<String> = image_objects
+        .iter()
+        .enumerate()
+        .map(|(index, id)| format!("/Im{} {} 0 R", index + 1, id))
+        .collect();
+    objects.push(
+        format!(
+            "<< /Font << {} >> /XObject << {} >> >>",
+            font_resources.join(" "),
+            image_resources.join(" ")
+        )
+        .into_bytes(),
+    );
+    let resources = objects.len();
+
+    let width = options.page.width * POINTS_PER_PX;
+    let height = options.page.height * POINTS_PER_PX;
+    let mut kids = Vec::new();
+    for (index, top) in breaks.iter().enumerate() {
+        let bottom = breaks.get(index + 1).copied().unwrap_or(f32::INFINITY);
+        let mut page = Page {
+            options,
+            top: *top,
+            images: &images,
+            out: Vec::new(),
+        };
+        let clip = format!(
+            "q {:.2} {:.2} {:.2} {:.2} re W n",
+            options.margin.left * POINTS_PER_PX,
+            options.margin.bottom * POINTS_PER_PX,
+            options.content_width() * POINTS_PER_PX,
+            options.content_height() * POINTS_PER_PX
+        );
+        page.push(&clip);
+        for item in &list.items {
+            let bounds = item.bounds();
+            let clip = matches!(item, DisplayItem::PushClip { .. } | DisplayItem::PopClip);
+            if clip || (bounds.bottom() > *top && bounds.y < bottom) {
+                page.paint(item);
+            }
+        }
+        page.push("Q");
+        objects.push(stream("", &page.out));
+        let contents = objects.len();
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources {} 0 R /Contents {} 0 R >>",
+                width, height, resources, contents
+            )
+            .into_bytes(),
+        );
+        kids.push(format!("{} 0 R", objects.len()));
+    }
+
+    objects[0] = b"<< /Type /Catalog /Pages 2 0 R >>".to_vec();
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        kids.len()
+    )
+    .into_bytes();
+    let mut info = b"<< /Producer (Icarus)".to_vec();
+    if let Some(title) = title {
+        info.extend_from_slice(b" /Title (");
+        info.extend_from_slice(&encode_text(title));
+        info.push(b')');
+    }
+    info.extend_from_slice(b" >>");
+    objects.push(info);
+    let info = objects.len();
+
+    let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    out.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            info,
+            xref
+        )
+        .as_bytes(),
+    );
+    out
+}
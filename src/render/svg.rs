@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::string::String;
+use std::sync::Arc;
+
+use crate::cdp::base64_encode;
+use crate::css::values::Color;
+use crate::dom::Document;
+use crate::html::escape::escape_xml_text;
+use crate::image::Image;
+use crate::layout::inline::image_placeholder_label;
+use crate::layout::{LayoutOptions, LayoutTree, Rect};
+use crate::paint::{DisplayItem, DisplayList, shadow_shape};
+use crate::style::{BoxShadow, Sides};
+
+const PLACEHOLDER_BORDER: Color = Color::rgb(160, 160, 160);
+const PLACEHOLDER_TEXT: Color = Color::rgb(96, 96, 96);
+
+pub fn render(document: &Document, width: usize, height: Option<usize>) -> String {
+    let tree = LayoutTree::build(
+        document,
+        LayoutOptions {
+            viewport_width: width as f32,
+            ..LayoutOptions::default()
+        },
+    );
+    let height = height.unwrap_or_else(|| tree.height().ceil().max(1.0) as usize);
+    let background = document.settings.palette().background;
+    to_svg(&DisplayList::build(&tree), width, height, background)
+}
+
+pub fn to_svg(list: &DisplayList, width: usize, height: usize, background: Color) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    out.push_str(&format!(
+        "<rect width=\"100%\" height=\"100%\"{}/>\n",
+        paint("fill", background)
+    ));
+    let mut images: HashMap<*const Image, String> = HashMap::new();
+    let mut shadows = 0;
+    let mut clips = 0;
+    for item in &list.items {
+        match item {
+            DisplayItem::SolidColor { rect, color } => {
+                out.push_str(&format!(
+                    "<rect{}{}/>\n",
+                    geometry(rect),
+                    paint("fill", *color)
+                ));
+            }
+            DisplayItem::Text {
+                rect,
+                text,
+                color,
+                font_size,
+                bold,
+                italic,
+                ..
+            } => {
+                let mut style = String::new();
+                if *bold {
+                    style.push_str(" font-weight=\"bold\"");
+                }
+                if *italic {
+                    style.push_str(" font-style=\"italic\"");
+                }
+                out.push_str(&text_element(rect, text, *color, *font_size, &style));
+            }
+            DisplayItem::ImagePlaceholder {
+                rect,
+                alt,
+                font_size,
+            } => {
+                out.push_str(&format!(
+                    "<rect{} fill=\"none\"{}/>\n",
+                    geometry(rect),
+                    paint("stroke", PLACEHOLDER_BORDER)
+                ));
+                let label = Rect::new(rect.x + 2.0, rect.y, rect.width, font_size * 1.2);
+                out.push_str(&text_element(
+                    &label,
+                    &image_placeholder_label(alt),
+                    PLACEHOLDER_TEXT,
+                    *font_size,
+                    "",
+                ));
+            }
+            DisplayItem::BoxShadow { rect, shadow } => {
+                out.push_str(&shadow_element(rect, shadow, shadows));
+                shadows += 1;
+            }
+            DisplayItem::Gradient {
+                rect,
+                origin,
+                gradient,
+            } => {
+                let center = gradient.sampler(*origin).color_at(
+                    origin.x + origin.width / 2.0,
+                    origin.y + origin.height / 2.0,
+                );
+                out.push_str(&format!(
+                    "<rect{}{}/>\n",
+                    geometry(rect),
+                    paint("fill", center)
+                ));
+            }
+            DisplayItem::Image {
+                rect,
+                image,
+                alt,
+                source,
+            } => {
+                let href =
+                    images
+                        .entry(Arc::as_ptr(image))
+                        .or_insert_with(|| match image.to_png() {
+                            Ok(png) => format!("data:image/png;base64,{}", base64_encode(&png)),
+                            Err(err) => {
+                                log::debug!("could not encode image for SVG: {}", err);
+                                String::new()
+                            }
+                        });
+                if href.is_empty() {
+                    continue;
+                }
+                let full = Rect::new(0.0, 0.0, image.width as f32, image.height as f32);
+                if *source == full {
+                    out.push_str(&format!(
+                        "<image{} preserveAspectRatio=\"none\" href=\"{}\"><title>{}</title></image>\n",
+                        geometry(rect),
+                        href,
+                        escape_xml_text(alt)
+                    ));
+                    continue;
+                }
+                out.push_str(&format!(
+                    "<svg{} viewBox=\"{} {} {} {}\" preserveAspectRatio=\"none\">\
+                     <image{} href=\"{}\"><title>{}</title></image></svg>\n",
+                    geometry(rect),
+                    number(source.x),
+                    number(source.y),
+                    number(source.width),
+                    number(source.height),
+                    geometry(&full),
+                    href,
+                    escape_xml_text(alt)
+                ));
+            }
+            DisplayItem::PushClip { rect } => {
+                out.push_str(&format!(
+                    "<clipPath id=\"frame-clip-{0}\"><rect{1}/></clipPath>\n\
+                     <g clip-path=\"url(#frame-clip-{0})\">\n",
+                    clips,
+                    geometry(rect)
+                ));
+                clips += 1;
+            }
+            DisplayItem::PopClip => out.push_str("</g>\n"),
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn geometry(rect: &Rect) -> String {
+    format!(
+        " x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+        number(rect.x),
+        number(rect.y),
+        number(rect.width),
+        number(rect.height)
+    )
+}
+
+fn rect_path(rect: &Rect) -> String {
+    format!(
+        "M{} {}h{}v{}h{}Z",
+        number(rect.x),
+        number(rect.y),
+        number(rect.width),
+        number(rect.height),
+        number(-rect.width)
+    )
+}
+
+fn shadow_element(rect: &Rect, shadow: &BoxShadow, index: usize) -> String {
+    let shape = shadow_shape(*rect, shadow);
+    let reach = shadow.blur * 2.0
+        + shadow.spread.abs()
+        + shadow.offset_x.abs()
+        + shadow.offset_y.abs()
+        + 1.0;
+    let frame = rect.union(&shape).expand(&Sides::all(reach));
+    let (clip, body, region) = if shadow.inset {
+        (
+            rect_path(rect),
+            rect_path(&frame) + &rect_path(&shape),
+            frame,
+        )
+    } else {
+        (
+            rect_path(&frame) + &rect_path(rect),
+            rect_path(&shape),
+            shape.expand(&Sides::all(shadow.blur * 2.0)),
+        )
+    };
+    let mut out = format!(
+        "<clipPath id=\"shadow-clip-{}\"><path clip-rule=\"evenodd\" d=\"{}\"/></clipPath>\n",
+        index, clip
+    );
+    let mut filter = String::new();
+    if shadow.blur > 0.0 {
+        out.push_str(&format!(
+            "<filter id=\"shadow-blur-{}\" filterUnits=\"userSpaceOnUse\"{}><feGaussianBlur stdDeviation=\"{}\"/></filter>\n",
+            index,
+            geometry(&region),
+            number(shadow.blur / 2.0)
+        ));
+        filter = format!(" filter=\"url(#shadow-blur-{})\"", index);
+    }
+    out.push_str(&format!(
+        "<g clip-path=\"url(#shadow-clip-{})\"><path fill-rule=\"evenodd\" d=\"{}\"{}{}/></g>\n",
+        index,
+        body,
+        paint("fill", shadow.color),
+        filter
+    ));
+    out
+}
+
+fn number(value: f32) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn paint(attribute: &str, color: Color) -> String {
+    let hex = Color { a: 255, ..color }.to_hex();
+    if color.a == 255 {
+        format!(" {}=\"{}\"", attribute, hex)
+    } else {
+        format!(
+            " {0}=\"{1}\" {0}-opacity=\"{2:.3}\"",
+            attribute,
+            hex,
+            color.a as f32 / 255.0
+        )
+    }
+}
+
+fn text_element(rect: &Rect, text: &str, color: Color, font_size: f32, style: &str) -> String {
+    let baseline = rect.y + rect.height / 2.0 + font_size * 0.3;
+    format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\"{}{} xml:space=\"preserve\">{}</text>\n",
+        number(rect.x),
+        number(baseline),
+        number(font_size),
+        style,
+        paint("fill", color),
+        escape_xml_text(text)
+    )
+}
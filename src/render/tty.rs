@@ -0,0 +1,96 @@
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::Document;
+use crate::layout::inline::image_placeholder_label;
+use crate::layout::{FontMetrics, LayoutOptions, LayoutTree};
+use crate::paint::{DisplayItem, DisplayList};
+
+pub const DEFAULT_COLUMNS: usize = 80;
+
+pub struct Grid {
+    pub columns: usize,
+    rows: Vec<Vec<char>>,
+}
+
+impl Grid {
+    pub fn new(columns: usize) -> Self {
+        Grid {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn put_str(&mut self, column: usize, row: usize, text: &str) {
+        while self.rows.len() <= row {
+            self.rows.push(Vec::new());
+        }
+        let line = &mut self.rows[row];
+        for (offset, c) in text.chars().enumerate() {
+            let column = column + offset;
+            if column >= self.columns {
+                break;
+            }
+            while line.len() <= column {
+                line.push(' ');
+            }
+            line[column] = c;
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let line: String = row.iter().collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub fn layout_for_columns(document: &Document, columns: usize) -> LayoutTree {
+    let cell = FontMetrics::default();
+    LayoutTree::build(
+        document,
+        LayoutOptions {
+            viewport_width: columns as f32 * cell.char_width,
+            scale_fonts: false,
+            ..LayoutOptions::default()
+        },
+    )
+}
+
+pub fn render(document: &Document, columns: usize) -> String {
+    let tree = layout_for_columns(document, columns);
+    let list = DisplayList::build(&tree);
+    let mut grid = Grid::new(columns);
+    paint(&list, &mut grid);
+    document.performance.mark_first_paint();
+    document.performance.mark_load_complete();
+    grid.to_text()
+}
+
+pub fn paint(list: &DisplayList, grid: &mut Grid) {
+    let _span = tracing::info_span!("tty_paint", items = list.items.len()).entered();
+    let cell = FontMetrics::default();
+    let column = |x: f32| (x / cell.char_width).round().max(0.0) as usize;
+    let row = |y: f32| (y / cell.line_height).round().max(0.0) as usize;
+
+    for item in &list.items {
+        match item {
+            DisplayItem::Text { rect, text, .. } => {
+                grid.put_str(column(rect.x), row(rect.bottom() - cell.line_height), text)
+            }
+            DisplayItem::ImagePlaceholder { rect, alt, .. }
+            | DisplayItem::Image { rect, alt, .. } => {
+                grid.put_str(column(rect.x), row(rect.y), &image_placeholder_label(alt))
+            }
+            DisplayItem::SolidColor { .. }
+            | DisplayItem::BoxShadow { .. }
+            | DisplayItem::Gradient { .. }
+            | DisplayItem::PushClip { .. }
+            | DisplayItem::PopClip => {}
+        }
+    }
+}
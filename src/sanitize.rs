@@ -0,0 +1,260 @@
+use html5ever::ns;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Node, NodeData};
+use crate::html::parser::parse_html;
+use crate::html::serialize::inner_html;
+
+const ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+const GLOBAL_ATTRIBUTES: &[&str] = &["dir", "lang", "title"];
+
+const ELEMENT_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("blockquote", &["cite"]),
+    ("col", &["span"]),
+    ("colgroup", &["span"]),
+    ("del", &["cite", "datetime"]),
+    ("details", &["open"]),
+    ("img", &["alt", "height", "src", "width"]),
+    ("ins", &["cite", "datetime"]),
+    ("ol", &["reversed", "start", "type"]),
+    ("q", &["cite"]),
+    ("td", &["colspan", "rowspan"]),
+    ("th", &["colspan", "rowspan", "scope"]),
+    ("time", &["datetime"]),
+];
+
+const URL_ATTRIBUTES: &[&str] = &[
+    "action",
+    "background",
+    "cite",
+    "formaction",
+    "href",
+    "longdesc",
+    "poster",
+    "src",
+];
+
+const SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+const DROPPED_ELEMENTS: &[&str] = &[
+    "embed", "frame", "frameset", "head", "iframe", "math", "noembed", "noframes", "noscript",
+    "object", "script", "select", "style", "svg", "template", "textarea", "title", "xmp",
+];
+
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub elements: HashSet<String>,
+    pub attributes: HashMap<String, HashSet<String>>,
+    pub global_attributes: HashSet<String>,
+    pub url_schemes: HashSet<String>,
+    pub dropped_elements: HashSet<String>,
+    pub allow_comments: bool,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        let set = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        Policy {
+            elements: set(ELEMENTS),
+            attributes: ELEMENT_ATTRIBUTES
+                .iter()
+                .map(|(element, attributes)| (element.to_string(), set(attributes)))
+                .collect(),
+            global_attributes: set(GLOBAL_ATTRIBUTES),
+            url_schemes: set(SCHEMES),
+            dropped_elements: set(DROPPED_ELEMENTS),
+            allow_comments: false,
+        }
+    }
+}
+
+impl Policy {
+    pub fn text_only() -> Self {
+        Policy {
+            elements: HashSet::new(),
+            attributes: HashMap::new(),
+            global_attributes: HashSet::new(),
+            ..Policy::default()
+        }
+    }
+
+    pub fn allow_elements(mut self, elements: &[&str]) -> Self {
+        for element in elements {
+            let element = element.to_ascii_lowercase();
+            self.dropped_elements.remove(&element);
+            self.elements.insert(element);
+        }
+        self
+    }
+
+    pub fn deny_elements(mut self, elements: &[&str]) -> Self {
+        for element in elements {
+            self.elements.remove(&element.to_ascii_lowercase());
+        }
+        self
+    }
+
+    pub fn allow_attributes(mut self, element: &str, attributes: &[&str]) -> Self {
+        let allowed = match element {
+            "*" => &mut self.global_attributes,
+            element => self
+                .attributes
+                .entry(element.to_ascii_lowercase())
+                .or_default(),
+        };
+        allowed.extend(
+            attributes
+                .iter()
+                .map(|attribute| attribute.to_ascii_lowercase()),
+        );
+        self
+    }
+
+    pub fn allow_url_schemes(mut self, schemes: &[&str]) -> Self {
+        self.url_schemes
+            .extend(schemes.iter().map(|scheme| scheme.to_ascii_lowercase()));
+        self
+    }
+
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    fn allows_attribute(&self, element: &str, attribute: &str, value: &str) -> bool {
+        if attribute.starts_with("on") {
+            return false;
+        }
+        let allowed = self.global_attributes.contains(attribute)
+            || self
+                .attributes
+                .get(element)
+                .is_some_and(|attributes| attributes.contains(attribute));
+        allowed && (!URL_ATTRIBUTES.contains(&attribute) || self.allows_url(value))
+    }
+
+    fn allows_url(&self, value: &str) -> bool {
+        let url: String = value
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+            .collect();
+        let scheme_end = url.find(':');
+        let path_start = url.find(['/', '?', '#']);
+        match scheme_end {
+            Some(end) if path_start.is_none_or(|start| end < start) => {
+                self.url_schemes.contains(&url[..end].to_ascii_lowercase())
+            }
+            _ => true,
+        }
+    }
+}
+
+pub fn sanitize(html: &str, policy: &Policy) -> String {
+    let document = parse_html(html);
+    let Some(body) = document.get_elements_by_tag_name("body").into_iter().next() else {
+        return String::new();
+    };
+    sanitize_children(&body, policy);
+    inner_html(&body)
+}
+
+fn sanitize_children(parent: &Rc<Node>, policy: &Policy) {
+    let children: Vec<Rc<Node>> = parent.children.borrow().clone();
+    for child in children {
+        match &child.data {
+            NodeData::Text { .. } => {}
+            NodeData::Comment { .. } if policy.allow_comments => {}
+            NodeData::Element { name, attrs } => {
+                let element: &str = &name.local;
+                if name.ns != ns!(html) || policy.dropped_elements.contains(element) {
+                    Node::remove_child(parent, &child);
+                    continue;
+                }
+                sanitize_children(&child, policy);
+                if policy.elements.contains(element) {
+                    attrs.borrow_mut().retain(|attribute| {
+                        attribute.name.ns.is_empty()
+                            && policy.allows_attribute(
+                                element,
+                                &attribute.name.local,
+                                &attribute.value,
+                            )
+                    });
+                } else {
+                    unwrap(parent, &child);
+                }
+            }
+            _ => Node::remove_child(parent, &child),
+        }
+    }
+}
+
+fn unwrap(parent: &Rc<Node>, element: &Rc<Node>) {
+    let children: Vec<Rc<Node>> = element.children.borrow().clone();
+    for child in children {
+        Node::insert_before(parent, child, element);
+    }
+    Node::remove_child(parent, element);
+}
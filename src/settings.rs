@@ -0,0 +1,335 @@
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+use toml::{Table, Value};
+use url::Url;
+
+use crate::css::media::{ColorScheme, ReducedMotion};
+use crate::css::values::Color;
+use crate::html::parser::ParseOptions;
+use crate::image::Loading;
+
+pub const DEFAULT_USER_AGENT: &str = concat!("Icarus/", env!("CARGO_PKG_VERSION"));
+
+const CONFIG_FILE: &str = "icarus/config.toml";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontSettings {
+    pub regular: Option<PathBuf>,
+    pub fallbacks: Vec<PathBuf>,
+    pub size: f32,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        FontSettings {
+            regular: std::env::var_os("ICARUS_FONT").map(PathBuf::from),
+            fallbacks: Vec::new(),
+            size: 16.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorSettings {
+    pub text: Color,
+    pub background: Color,
+    pub link: Option<Color>,
+}
+
+impl Default for ColorSettings {
+    fn default() -> Self {
+        ColorSettings {
+            text: Color::BLACK,
+            background: Color::WHITE,
+            link: None,
+        }
+    }
+}
+
+impl ColorSettings {
+    pub fn dark() -> Self {
+        ColorSettings {
+            text: Color::rgb(232, 234, 237),
+            background: Color::rgb(32, 33, 36),
+            link: Some(Color::rgb(138, 180, 248)),
+        }
+    }
+
+    pub fn for_scheme(self, scheme: ColorScheme) -> Self {
+        if scheme.resolve() != ColorScheme::Dark {
+            return self;
+        }
+        let light = ColorSettings::default();
+        let dark = ColorSettings::dark();
+        ColorSettings {
+            text: if self.text == light.text {
+                dark.text
+            } else {
+                self.text
+            },
+            background: if self.background == light.background {
+                dark.background
+            } else {
+                self.background
+            },
+            link: self.link.or(dark.link),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub user_agent: String,
+    pub javascript: bool,
+    pub images: Loading,
+    pub content_sniffing: bool,
+    pub refresh: bool,
+    pub fonts: FontSettings,
+    pub colors: ColorSettings,
+    pub color_scheme: ColorScheme,
+    pub reduced_motion: ReducedMotion,
+    pub proxy: Option<Url>,
+    pub config: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            user_agent: String::from(DEFAULT_USER_AGENT),
+            javascript: true,
+            images: Loading::Eager,
+            content_sniffing: true,
+            refresh: true,
+            fonts: FontSettings::default(),
+            colors: ColorSettings::default(),
+            color_scheme: ColorScheme::System,
+            reduced_motion: ReducedMotion::NoPreference,
+            proxy: None,
+            config: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn config_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("ICARUS_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join(CONFIG_FILE))
+    }
+
+    pub fn load() -> Result<Self> {
+        match Settings::config_path() {
+            Some(path) if path.exists() => Settings::load_file(&path),
+            _ => Ok(Settings {
+                reduced_motion: ReducedMotion::System,
+                ..Settings::default()
+            }),
+        }
+    }
+
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let mut settings =
+            Settings::from_toml(&text).with_context(|| format!("in {}", path.display()))?;
+        settings.config = Some(path.to_path_buf());
+        Ok(settings)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let mut settings = Settings {
+            reduced_motion: ReducedMotion::System,
+            ..Settings::default()
+        };
+        for (key, value) in &table {
+            match key.as_str() {
+                "user-agent" => settings.user_agent = string(key, value)?.to_string(),
+                "javascript" => settings.javascript = boolean(key, value)?,
+                "images" => settings.images = loading(string(key, value)?)?,
+                "content-sniffing" => settings.content_sniffing = boolean(key, value)?,
+                "refresh" => settings.refresh = boolean(key, value)?,
+                "color-scheme" => settings.color_scheme = color_scheme(string(key, value)?)?,
+                "reduced-motion" => settings.reduced_motion = reduced_motion(string(key, value)?)?,
+                "proxy" => settings.proxy = Some(proxy(string(key, value)?)?),
+                "fonts" => settings.fonts.apply(table_of(key, value)?)?,
+                "colors" => settings.colors.apply(table_of(key, value)?)?,
+                _ => log::warn!("unknown setting '{}'", key),
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn from_args(args: &mut Vec<String>) -> Result<Self> {
+        let config = take_value(args, "--config")?;
+        let mut settings = match config {
+            Some(path) => Settings::load_file(Path::new(&path))?,
+            None => Settings::load()?,
+        };
+        settings.apply_args(args)?;
+        Ok(settings)
+    }
+
+    pub fn apply_args(&mut self, args: &mut Vec<String>) -> Result<()> {
+        if take_flag(args, "--no-script") {
+            self.javascript = false;
+        }
+        if take_flag(args, "--lazy-images") {
+            self.images = Loading::Lazy;
+        }
+        if take_flag(args, "--no-sniff") {
+            self.content_sniffing = false;
+        }
+        if take_flag(args, "--no-refresh") {
+            self.refresh = false;
+        }
+        if let Some(user_agent) = take_value(args, "--user-agent")? {
+            self.user_agent = user_agent;
+        }
+        if let Some(value) = take_value(args, "--color-scheme")? {
+            self.color_scheme = color_scheme(&value)?;
+        }
+        if let Some(value) = take_value(args, "--reduced-motion")? {
+            self.reduced_motion = reduced_motion(&value)?;
+        }
+        if let Some(value) = take_value(args, "--proxy")? {
+            self.proxy = Some(proxy(&value)?);
+        }
+        Ok(())
+    }
+
+    pub fn palette(&self) -> ColorSettings {
+        self.colors.for_scheme(self.color_scheme)
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            scripting: self.javascript,
+        }
+    }
+}
+
+impl FontSettings {
+    fn apply(&mut self, table: &Table) -> Result<()> {
+        for (key, value) in table {
+            match key.as_str() {
+                "regular" => self.regular = Some(PathBuf::from(string(key, value)?)),
+                "fallbacks" => {
+                    let paths = value
+                        .as_array()
+                        .with_context(|| format!("'fonts.{}' must be an array", key))?;
+                    self.fallbacks = paths
+                        .iter()
+                        .map(|path| string(key, path).map(PathBuf::from))
+                        .collect::<Result<_>>()?;
+                }
+                "size" => {
+                    let size = match value {
+                        Value::Integer(size) => *size as f32,
+                        Value::Float(size) => *size as f32,
+                        _ => bail!("'fonts.size' must be a number"),
+                    };
+                    if size <= 0.0 {
+                        bail!("'fonts.size' must be positive");
+                    }
+                    self.size = size;
+                }
+                _ => log::warn!("unknown setting 'fonts.{}'", key),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ColorSettings {
+    fn apply(&mut self, table: &Table) -> Result<()> {
+        for (key, value) in table {
+            let color = string(key, value)?;
+            let color = Color::parse(color)
+                .with_context(|| format!("'colors.{}' is not a color: {}", key, color))?;
+            match key.as_str() {
+                "text" => self.text = color,
+                "background" => self.background = color,
+                "link" => self.link = Some(color),
+                _ => log::warn!("unknown setting 'colors.{}'", key),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn string<'a>(key: &str, value: &'a Value) -> Result<&'a str> {
+    value
+        .as_str()
+        .with_context(|| format!("'{}' must be a string", key))
+}
+
+fn boolean(key: &str, value: &Value) -> Result<bool> {
+    value
+        .as_bool()
+        .with_context(|| format!("'{}' must be true or false", key))
+}
+
+fn table_of<'a>(key: &str, value: &'a Value) -> Result<&'a Table> {
+    value
+        .as_table()
+        .with_context(|| format!("'{}' must be a table", key))
+}
+
+fn loading(value: &str) -> Result<Loading> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "eager" => Ok(Loading::Eager),
+        "lazy" => Ok(Loading::Lazy),
+        _ => bail!("'images' must be \"eager\" or \"lazy\", not {:?}", value),
+    }
+}
+
+fn color_scheme(value: &str) -> Result<ColorScheme> {
+    ColorScheme::parse(value).with_context(|| {
+        format!(
+            "color scheme must be \"light\", \"dark\" or \"system\", not {:?}",
+            value
+        )
+    })
+}
+
+fn reduced_motion(value: &str) -> Result<ReducedMotion> {
+    ReducedMotion::parse(value).with_context(|| {
+        format!(
+            "reduced motion must be \"no-preference\", \"reduce\" or \"system\", not {:?}",
+            value
+        )
+    })
+}
+
+fn proxy(value: &str) -> Result<Url> {
+    let url = Url::parse(value).with_context(|| format!("invalid proxy URL: {}", value))?;
+    if url.scheme() != "http" || url.host_str().is_none() {
+        bail!("proxy must be an http:// URL: {}", value);
+    }
+    Ok(url)
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != before
+}
+
+fn take_value(args: &mut Vec<String>, flag: &str) -> Result<Option<String>> {
+    let Some(index) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        bail!("{} needs a value", flag);
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Ok(Some(value))
+}
@@ -0,0 +1,109 @@
+use std::string::String;
+use url::Url;
+
+use crate::net;
+
+const SEARCH_URL: &str = "https://duckduckgo.com/html/";
+
+#[derive(Debug, Clone, Default)]
+pub struct AddressBar {
+    pub text: String,
+    pub focused: bool,
+    pub selected: bool,
+    cursor: usize,
+}
+
+impl AddressBar {
+    pub fn focus(&mut self, url: &str) {
+        self.text = url.to_string();
+        self.cursor = self.text.len();
+        self.focused = true;
+        self.selected = true;
+    }
+
+    pub fn blur(&mut self) {
+        self.focused = false;
+        self.selected = false;
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn take_selection(&mut self) -> bool {
+        if !self.selected {
+            return false;
+        }
+        self.selected = false;
+        self.text.clear();
+        self.cursor = 0;
+        true
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.take_selection();
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.take_selection() {
+            return;
+        }
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if !self.take_selection() && self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.selected = false;
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    pub fn move_right(&mut self) {
+        self.selected = false;
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn home(&mut self) {
+        self.selected = false;
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.selected = false;
+        self.cursor = self.text.len();
+    }
+}
+
+pub fn resolve(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+    if input.contains("://") || input.starts_with("about:") {
+        return Url::parse(input).ok().map(|url| url.to_string());
+    }
+    if std::path::Path::new(input).exists() {
+        return net::parse_url(input).ok().map(|url| url.to_string());
+    }
+    let host_like = !input.contains(char::is_whitespace)
+        && (input.contains('.') || input.contains(':') || input.starts_with("localhost"));
+    if host_like && let Ok(url) = Url::parse(&format!("http://{}", input)) {
+        return Some(url.to_string());
+    }
+    Url::parse_with_params(SEARCH_URL, [("q", input)])
+        .ok()
+        .map(|url| url.to_string())
+}
@@ -0,0 +1,227 @@
+use std::string::String;
+
+use super::address_bar::AddressBar;
+use super::find_bar::FindBar;
+use crate::css::values::Color;
+use crate::layout::{FontMetrics, Rect};
+use crate::paint::font::FontSet;
+use crate::paint::raster::Canvas;
+
+pub const TAB_STRIP_HEIGHT: usize = 30;
+pub const TOOLBAR_HEIGHT: usize = 36;
+pub const HEIGHT: usize = TAB_STRIP_HEIGHT + TOOLBAR_HEIGHT;
+
+const MAX_TAB_WIDTH: f32 = 200.0;
+const TAB_PADDING: f32 = 10.0;
+const TAB_FONT_SIZE: f32 = 12.0;
+const STRIP_BACKGROUND: Color = Color::rgb(222, 225, 230);
+const INACTIVE_TAB: Color = Color::rgb(208, 212, 218);
+const ACTIVE_TAB: Color = Color::WHITE;
+const TAB_TEXT: Color = Color::rgb(32, 33, 36);
+const SEPARATOR: Color = Color::rgb(170, 174, 180);
+const ADDRESS_MARGIN: f32 = 6.0;
+const ADDRESS_FONT_SIZE: f32 = 14.0;
+const ADDRESS_BACKGROUND: Color = Color::rgb(241, 243, 244);
+const ADDRESS_FOCUS: Color = Color::rgb(26, 115, 232);
+const ADDRESS_SELECTION: Color = Color::rgb(187, 214, 251);
+const PROGRESS: Color = Color::rgb(26, 115, 232);
+const FIND_WIDTH: f32 = 280.0;
+const FIND_STATUS: Color = Color::rgb(95, 99, 104);
+
+pub fn tab_width(count: usize, width: usize) -> f32 {
+    (width as f32 / count.max(1) as f32).min(MAX_TAB_WIDTH)
+}
+
+pub fn tab_at(x: f32, y: f32, count: usize, width: usize) -> Option<usize> {
+    if y < 0.0 || y >= TAB_STRIP_HEIGHT as f32 || x < 0.0 {
+        return None;
+    }
+    let index = (x / tab_width(count, width)) as usize;
+    (index < count).then_some(index)
+}
+
+pub fn paint_tab_strip(
+    canvas: &mut Canvas,
+    fonts: Option<&FontSet>,
+    labels: &[String],
+    active: usize,
+) {
+    let height = TAB_STRIP_HEIGHT as f32;
+    let tab_width = tab_width(labels.len(), canvas.width);
+    canvas.fill_rect(
+        Rect::new(0.0, 0.0, canvas.width as f32, height),
+        STRIP_BACKGROUND,
+    );
+    canvas.fill_rect(
+        Rect::new(0.0, height - 1.0, canvas.width as f32, 1.0),
+        SEPARATOR,
+    );
+
+    let metrics = FontMetrics::for_size(TAB_FONT_SIZE);
+    for (index, label) in labels.iter().enumerate() {
+        let x = index as f32 * tab_width;
+        let background = if index == active {
+            ACTIVE_TAB
+        } else {
+            INACTIVE_TAB
+        };
+        let tab = Rect::new(x + 1.0, 4.0, tab_width - 2.0, height - 4.0);
+        canvas.fill_rect(tab, background);
+        if index != active {
+            canvas.fill_rect(Rect::new(x, height - 1.0, tab_width, 1.0), SEPARATOR);
+        }
+
+        if let Some(fonts) = fonts {
+            let text = truncate(label, tab_width - 2.0 * TAB_PADDING, &metrics);
+            canvas.draw_text(
+                fonts,
+                x + TAB_PADDING,
+                4.0 + (height - 4.0 - metrics.line_height) / 2.0,
+                &text,
+                TAB_FONT_SIZE,
+                TAB_TEXT,
+                index == active,
+                false,
+                Some(tab),
+            );
+        }
+    }
+}
+
+pub fn address_bounds(width: usize, finding: bool) -> Rect {
+    let find_width = if finding {
+        FIND_WIDTH + ADDRESS_MARGIN
+    } else {
+        0.0
+    };
+    Rect::new(
+        ADDRESS_MARGIN,
+        TAB_STRIP_HEIGHT as f32 + ADDRESS_MARGIN,
+        width as f32 - 2.0 * ADDRESS_MARGIN - find_width,
+        TOOLBAR_HEIGHT as f32 - 2.0 * ADDRESS_MARGIN,
+    )
+}
+
+pub fn find_bounds(width: usize) -> Rect {
+    Rect::new(
+        width as f32 - ADDRESS_MARGIN - FIND_WIDTH,
+        TAB_STRIP_HEIGHT as f32 + ADDRESS_MARGIN,
+        FIND_WIDTH,
+        TOOLBAR_HEIGHT as f32 - 2.0 * ADDRESS_MARGIN,
+    )
+}
+
+pub fn paint_toolbar(
+    canvas: &mut Canvas,
+    fonts: Option<&FontSet>,
+    bar: &AddressBar,
+    find: &FindBar,
+    url: &str,
+    progress: Option<f32>,
+) {
+    let top = TAB_STRIP_HEIGHT as f32;
+    let width = canvas.width as f32;
+    canvas.fill_rect(
+        Rect::new(0.0, top, width, TOOLBAR_HEIGHT as f32),
+        Color::WHITE,
+    );
+    canvas.fill_rect(Rect::new(0.0, HEIGHT as f32 - 1.0, width, 1.0), SEPARATOR);
+
+    let field = address_bounds(canvas.width, find.open);
+    let text = if bar.focused { bar.text.as_str() } else { url };
+    paint_field(canvas, fonts, field, bar, text);
+
+    if find.open {
+        let field = find_bounds(canvas.width);
+        paint_field(canvas, fonts, field, &find.input, find.query());
+        let status = find.status();
+        let metrics = FontMetrics::for_size(ADDRESS_FONT_SIZE);
+        if let Some(fonts) = fonts
+            && !status.is_empty()
+        {
+            canvas.draw_text(
+                fonts,
+                field.right() - TAB_PADDING - metrics.text_width(&status),
+                field.y + (field.height - metrics.line_height) / 2.0,
+                &status,
+                ADDRESS_FONT_SIZE,
+                FIND_STATUS,
+                false,
+                false,
+                Some(field),
+            );
+        }
+    }
+
+    if let Some(progress) = progress {
+        canvas.fill_rect(
+            Rect::new(
+                0.0,
+                HEIGHT as f32 - 2.0,
+                width * progress.clamp(0.0, 1.0),
+                2.0,
+            ),
+            PROGRESS,
+        );
+    }
+}
+
+fn paint_field(
+    canvas: &mut Canvas,
+    fonts: Option<&FontSet>,
+    field: Rect,
+    bar: &AddressBar,
+    text: &str,
+) {
+    canvas.fill_rect(field, ADDRESS_BACKGROUND);
+    if bar.focused {
+        canvas.stroke_rect(field, 2.0, ADDRESS_FOCUS);
+    }
+
+    let metrics = FontMetrics::for_size(ADDRESS_FONT_SIZE);
+    let text_x = field.x + TAB_PADDING;
+    let text_y = field.y + (field.height - metrics.line_height) / 2.0;
+    if bar.selected && !text.is_empty() {
+        canvas.fill_rect(
+            Rect::new(
+                text_x,
+                text_y,
+                metrics.text_width(text),
+                metrics.line_height,
+            ),
+            ADDRESS_SELECTION,
+        );
+    }
+    if let Some(fonts) = fonts {
+        canvas.draw_text(
+            fonts,
+            text_x,
+            text_y,
+            text,
+            ADDRESS_FONT_SIZE,
+            TAB_TEXT,
+            false,
+            false,
+            Some(field),
+        );
+    }
+    if bar.focused && !bar.selected {
+        let caret = text_x + metrics.text_width(&bar.text[..bar.cursor()]);
+        canvas.fill_rect(Rect::new(caret, text_y, 1.0, metrics.line_height), TAB_TEXT);
+    }
+}
+
+pub fn truncate(text: &str, width: f32, metrics: &FontMetrics) -> String {
+    if metrics.text_width(text) <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    for c in text.chars() {
+        if metrics.text_width(&truncated) + 2.0 * metrics.char_width > width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
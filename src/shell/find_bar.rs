@@ -0,0 +1,38 @@
+use std::string::String;
+
+use super::address_bar::AddressBar;
+
+#[derive(Debug, Clone, Default)]
+pub struct FindBar {
+    pub input: AddressBar,
+    pub open: bool,
+    pub current: Option<usize>,
+    pub total: usize,
+}
+
+impl FindBar {
+    pub fn open(&mut self) {
+        let query = self.input.text.clone();
+        self.input.focus(&query);
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.input.blur();
+        self.open = false;
+        self.current = None;
+        self.total = 0;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.input.text
+    }
+
+    pub fn status(&self) -> String {
+        match self.current {
+            Some(index) => format!("{}/{}", index + 1, self.total),
+            None if self.query().is_empty() => String::new(),
+            None => String::from("0/0"),
+        }
+    }
+}
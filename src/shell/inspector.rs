@@ -0,0 +1,193 @@
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::css::values::Color;
+use crate::dom::{Node, NodeData};
+use crate::layout::{FontMetrics, Hit, Rect};
+use crate::paint::font::FontSet;
+use crate::paint::raster::Canvas;
+use crate::style::{Sides, style_of};
+
+const MARGIN_TINT: Color = Color::rgba(246, 178, 107, 110);
+const BORDER_TINT: Color = Color::rgba(255, 229, 153, 140);
+const PADDING_TINT: Color = Color::rgba(147, 196, 125, 110);
+const CONTENT_TINT: Color = Color::rgba(111, 168, 220, 110);
+const LABEL_BACKGROUND: Color = Color::rgb(51, 51, 51);
+const LABEL_TEXT: Color = Color::WHITE;
+const LABEL_FONT_SIZE: f32 = 12.0;
+
+pub fn selector_for(node: &Node) -> String {
+    let Some(name) = node.element_name() else {
+        return String::from("#text");
+    };
+    let mut selector = name.to_string();
+    if let Some(id) = node.get_attribute("id").filter(|id| !id.is_empty()) {
+        selector.push('#');
+        selector.push_str(&id);
+    }
+    if let Some(classes) = node.get_attribute("class") {
+        for class in classes.split_whitespace() {
+            selector.push('.');
+            selector.push_str(class);
+        }
+    }
+    selector
+}
+
+pub fn dom_path(node: &Rc<Node>) -> String {
+    let mut parts = Vec::new();
+    let mut current = Some(Rc::clone(node));
+    while let Some(node) = current {
+        if let NodeData::Element { .. } = node.data {
+            parts.push(selector_for(&node));
+        }
+        current = node.parent.borrow().upgrade();
+    }
+    parts.reverse();
+    parts.join(" > ")
+}
+
+pub fn describe(node: &Rc<Node>) -> String {
+    let mut out = dom_path(node);
+    out.push_str(" {\n");
+    for (name, value) in style_of(node).properties() {
+        out.push_str(&format!("  {}: {};\n", name, value));
+    }
+    out.push('}');
+    out
+}
+
+fn fill_between(canvas: &mut Canvas, outer: Rect, inner: Rect, color: Color) {
+    canvas.fill_rect(
+        Rect::new(outer.x, outer.y, outer.width, inner.y - outer.y),
+        color,
+    );
+    canvas.fill_rect(
+        Rect::new(
+            outer.x,
+            inner.bottom(),
+            outer.width,
+            outer.bottom() - inner.bottom(),
+        ),
+        color,
+    );
+    canvas.fill_rect(
+        Rect::new(outer.x, inner.y, inner.x - outer.x, inner.height),
+        color,
+    );
+    canvas.fill_rect(
+        Rect::new(
+            inner.right(),
+            inner.y,
+            outer.right() - inner.right(),
+            inner.height,
+        ),
+        color,
+    );
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    pub selector: String,
+    pub rect: Rect,
+    pub margin: Sides<f32>,
+    pub border: Sides<f32>,
+    pub padding: Sides<f32>,
+}
+
+impl Highlight {
+    pub fn new(hit: &Hit) -> Self {
+        Highlight {
+            selector: selector_for(&hit.node),
+            rect: hit.rect,
+            margin: hit.margin,
+            border: hit.border,
+            padding: hit.padding,
+        }
+    }
+}
+
+struct Overlay {
+    content: Rect,
+    padding: Rect,
+    border: Rect,
+    margin: Rect,
+    label: String,
+    label_rect: Rect,
+}
+
+impl Overlay {
+    fn new(hit: &Highlight, scroll_y: f32, canvas_width: usize) -> Self {
+        let content = Rect::new(
+            hit.rect.x,
+            hit.rect.y - scroll_y,
+            hit.rect.width,
+            hit.rect.height,
+        );
+        let padding = content.expand(&hit.padding);
+        let border = padding.expand(&hit.border);
+        let margin = border.expand(&hit.margin);
+
+        let label = format!(
+            "{}  {} x {}",
+            hit.selector,
+            border.width.round(),
+            border.height.round()
+        );
+        let metrics = FontMetrics::for_size(LABEL_FONT_SIZE);
+        let width = metrics.text_width(&label) + metrics.char_width;
+        let y = if margin.y >= metrics.line_height {
+            margin.y - metrics.line_height
+        } else {
+            margin.bottom()
+        };
+        let x = border.x.min(canvas_width as f32 - width).max(0.0);
+        Overlay {
+            content,
+            padding,
+            border,
+            margin,
+            label,
+            label_rect: Rect::new(x, y, width, metrics.line_height),
+        }
+    }
+}
+
+pub fn overlay_bounds(hit: &Highlight, scroll_y: f32, canvas_width: usize) -> Rect {
+    let overlay = Overlay::new(hit, scroll_y, canvas_width);
+    overlay.margin.union(&overlay.label_rect)
+}
+
+pub fn paint_overlay(canvas: &mut Canvas, hit: &Highlight, fonts: Option<&FontSet>, scroll_y: f32) {
+    let Overlay {
+        content,
+        padding,
+        border,
+        margin,
+        label,
+        label_rect,
+    } = Overlay::new(hit, scroll_y, canvas.width);
+
+    fill_between(canvas, margin, border, MARGIN_TINT);
+    fill_between(canvas, border, padding, BORDER_TINT);
+    fill_between(canvas, padding, content, PADDING_TINT);
+    canvas.fill_rect(content, CONTENT_TINT);
+
+    let Some(fonts) = fonts else {
+        return;
+    };
+    canvas.fill_rect(label_rect, LABEL_BACKGROUND);
+    let metrics = FontMetrics::for_size(LABEL_FONT_SIZE);
+    canvas.draw_text(
+        fonts,
+        label_rect.x + metrics.char_width / 2.0,
+        label_rect.y,
+        &label,
+        LABEL_FONT_SIZE,
+        LABEL_TEXT,
+        false,
+        false,
+        None,
+    );
+}
@@ -0,0 +1,958 @@
+pub mod address_bar;
+pub mod chrome;
+pub mod find_bar;
+pub mod inspector;
+pub mod pipeline;
+pub mod tab;
+
+use anyhow::Result;
+#[cfg(target_os = "linux")]
+use minifb::Icon;
+use minifb::{InputCallback, Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::string::String;
+use std::sync::Arc;
+use std::time::Instant;
+use std::vec::Vec;
+
+use crate::css::values::Color;
+use crate::image::Image;
+use crate::layout::Rect;
+use crate::location::Navigation;
+use crate::paint::DisplayList;
+use crate::paint::damage::Damage;
+use crate::paint::font::FontSet;
+use crate::paint::raster::Canvas;
+use crate::settings::Settings;
+use crate::source;
+use crate::storage::{LocalStorage, StorageContext};
+use address_bar::AddressBar;
+use find_bar::FindBar;
+use pipeline::{Command, Event};
+use tab::{BLANK_URL, HISTORY_URL, Tab};
+
+const DEFAULT_WIDTH: usize = 1024;
+const DEFAULT_HEIGHT: usize = 768;
+const SCROLL_STEP: f32 = 40.0;
+const SOURCE_FONT_SIZE: f32 = 13.0;
+const SELECTION_TINT: Color = Color::rgba(51, 144, 255, 96);
+const FIND_TINT: Color = Color::rgba(255, 213, 0, 110);
+
+struct TextInput(Rc<RefCell<Vec<char>>>);
+
+impl InputCallback for TextInput {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char).filter(|c| !c.is_control()) {
+            self.0.borrow_mut().push(c);
+        }
+    }
+}
+
+pub struct Shell {
+    window: Window,
+    address_bar: AddressBar,
+    find_bar: FindBar,
+    typed: Rc<RefCell<Vec<char>>>,
+    quit: bool,
+    tabs: Vec<Tab>,
+    active: usize,
+    settings: Arc<Settings>,
+    storage: StorageContext,
+    canvas: Canvas,
+    chrome: Canvas,
+    chrome_dirty: bool,
+    frame: Vec<u32>,
+    fonts: Option<FontSet>,
+    inspecting: bool,
+    mouse_was_down: bool,
+    damage: Damage,
+}
+
+impl Shell {
+    pub fn new(url: &str, settings: Settings) -> Result<Self> {
+        let settings = Arc::new(settings);
+        let content_height = DEFAULT_HEIGHT - chrome::HEIGHT;
+        let viewport = Rect::new(0.0, 0.0, DEFAULT_WIDTH as f32, content_height as f32);
+        let local = LocalStorage::open_default().unwrap_or_else(|err| {
+            log::warn!("local storage won't be saved: {:#}", err);
+            LocalStorage::in_memory()
+        });
+        let storage = StorageContext::new(local);
+        let tab = Tab::open(url, viewport, Arc::clone(&settings), storage.new_tab())?;
+        let mut window = Window::new(
+            &format!("Icarus - {}", url),
+            DEFAULT_WIDTH,
+            DEFAULT_HEIGHT,
+            WindowOptions {
+                resize: true,
+                ..WindowOptions::default()
+            },
+        )?;
+
+        let typed = Rc::new(RefCell::new(Vec::new()));
+        window.set_input_callback(Box::new(TextInput(Rc::clone(&typed))));
+
+        let fonts = FontSet::load(&settings.fonts);
+        if fonts.is_none() {
+            log::warn!("no usable font found, text will not be drawn");
+        }
+
+        Ok(Shell {
+            window,
+            address_bar: AddressBar::default(),
+            find_bar: FindBar::default(),
+            typed,
+            quit: false,
+            tabs: vec![tab],
+            active: 0,
+            canvas: Canvas::new(DEFAULT_WIDTH, content_height, settings.palette().background),
+            settings,
+            storage,
+            chrome: Canvas::new(DEFAULT_WIDTH, chrome::HEIGHT, Color::WHITE),
+            chrome_dirty: true,
+            frame: Vec::new(),
+            fonts,
+            inspecting: false,
+            mouse_was_down: false,
+            damage: Damage::full(DEFAULT_WIDTH, content_height),
+        })
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        self.window.set_target_fps(60);
+
+        while self.window.is_open() && !self.quit && !self.tabs.is_empty() {
+            self.handle_events();
+            self.fire_refreshes();
+            self.handle_resize();
+            self.handle_keys()?;
+            self.handle_text_input();
+            self.handle_mouse();
+            if self.tabs.is_empty() {
+                break;
+            }
+            self.update_throttling();
+
+            if !self.damage.is_empty() || self.chrome_dirty {
+                self.redraw();
+                self.window.update_with_buffer(
+                    &self.frame,
+                    self.canvas.width,
+                    self.canvas.height + chrome::HEIGHT,
+                )?;
+            } else {
+                self.window.update();
+            }
+        }
+        Ok(())
+    }
+
+    fn viewport(&self) -> Rect {
+        let scroll_y = self.tabs.get(self.active).map_or(0.0, |tab| tab.scroll_y);
+        Rect::new(
+            0.0,
+            scroll_y,
+            self.canvas.width as f32,
+            self.canvas.height as f32,
+        )
+    }
+
+    fn handle_events(&mut self) {
+        for index in 0..self.tabs.len() {
+            if self.tabs[index].stopped {
+                continue;
+            }
+            let events = match self.tabs[index].pipeline.poll() {
+                Ok(events) => events,
+                Err(err) => vec![Event::Failed(format!("{:#}", err))],
+            };
+            let mut link = None;
+            for event in events {
+                match event {
+                    Event::Link(url) => link = Some(url),
+                    event => self.handle_event(index, event),
+                }
+            }
+            if let Some(url) = link {
+                self.follow_link(index, &url);
+            }
+        }
+    }
+
+    fn fire_refreshes(&mut self) {
+        let now = Instant::now();
+        for index in 0..self.tabs.len() {
+            let Some(refresh) = self.tabs[index].refresh.take_due(now) else {
+                continue;
+            };
+            let viewport = self.viewport();
+            let tab = &mut self.tabs[index];
+            let navigation = refresh.navigation(tab.url());
+            if let Err(err) = tab.follow(navigation, viewport) {
+                log::warn!("couldn't refresh {}: {:#}", tab.url(), err);
+                continue;
+            }
+            if index == self.active {
+                self.chrome_dirty = true;
+                self.update_window();
+                self.damage_all();
+            }
+        }
+    }
+
+    fn follow_link(&mut self, index: usize, url: &str) {
+        let viewport = self.viewport();
+        let tab = &mut self.tabs[index];
+        let followed = tab
+            .location()
+            .and_then(|location| location.assign(url))
+            .and_then(|navigation| tab.follow(navigation, viewport));
+        if let Err(err) = followed {
+            log::warn!("couldn't follow link to {}: {:#}", url, err);
+            return;
+        }
+        if index == self.active {
+            self.chrome_dirty = true;
+            self.update_window();
+            self.damage_all();
+        }
+    }
+
+    fn handle_event(&mut self, index: usize, event: Event) {
+        let active = index == self.active;
+        let tab = &mut self.tabs[index];
+        match event {
+            Event::Committed(url) => {
+                tab.commit(url);
+                tab.reading = false;
+                self.chrome_dirty |= active;
+                let previous = std::mem::take(&mut tab.selection);
+                let found = std::mem::take(&mut tab.found);
+                if active {
+                    self.damage_selection(&previous);
+                    self.damage_selection(&found);
+                    self.close_find();
+                }
+            }
+            Event::Frame {
+                display_list,
+                height,
+            } if tab.viewing_source => {
+                tab.advance(tab::RENDERED);
+                tab.hidden_view = (display_list, height, tab.hidden_view.2)
+            }
+            Event::Frame {
+                display_list,
+                height,
+            } => {
+                tab.advance(tab::RENDERED);
+                self.chrome_dirty |= active;
+                self.present(index, display_list, height)
+            }
+            Event::Source(html) => {
+                tab.advance(tab::PARSED);
+                self.chrome_dirty |= active;
+                let (display_list, height) = source::display_list(&html, SOURCE_FONT_SIZE);
+                if tab.viewing_source {
+                    self.present(index, display_list, height);
+                } else {
+                    tab.hidden_view = (display_list, height, 0.0);
+                }
+            }
+            Event::Metadata(metadata) => {
+                tab.title = metadata.title;
+                tab.icon = metadata.favicon.map(|(_, icon)| icon);
+                tab.progress = None;
+                self.chrome_dirty = true;
+                if active {
+                    self.update_window();
+                }
+            }
+            Event::Fonts(fonts) => {
+                if let Some(set) = &self.fonts {
+                    for font in fonts {
+                        set.register(&font.family, font.bold, font.italic, &font.data);
+                    }
+                }
+                self.damage_all();
+            }
+            Event::Hovered(hovered) => {
+                if active && hovered != tab.hovered && self.inspecting {
+                    self.damage_overlay();
+                    self.tabs[index].hovered = hovered;
+                    self.damage_overlay();
+                }
+            }
+            Event::Described(description) => println!("{}", description),
+            Event::Selected(rects) => {
+                let previous = std::mem::replace(&mut tab.selection, rects.clone());
+                if active && !tab.viewing_source {
+                    self.damage_selection(&previous);
+                    self.damage_selection(&rects);
+                }
+            }
+            Event::Found {
+                matches,
+                current,
+                total,
+            } => {
+                let previous = std::mem::replace(&mut tab.found, matches.clone());
+                if active && !tab.viewing_source {
+                    self.damage_selection(&previous);
+                    self.damage_selection(&matches);
+                }
+                if active && self.find_bar.open {
+                    self.find_bar.current = current;
+                    self.find_bar.total = total;
+                    self.chrome_dirty = true;
+                }
+            }
+            Event::Reading(reading) => tab.reading = reading,
+            Event::Link(_) => {}
+            Event::Refresh(refresh) => tab.refresh.schedule(refresh, Instant::now()),
+            Event::ScrollTo(y) => {
+                tab.pending_scroll = None;
+                tab.scroll_y = y;
+                tab.clamp_scroll(self.canvas.height as f32);
+                if active {
+                    tab.report_viewport(self.canvas.height as f32);
+                    self.chrome_dirty = true;
+                    self.damage_all();
+                }
+            }
+            Event::Failed(message) => {
+                tab.stopped = true;
+                tab.progress = None;
+                self.chrome_dirty |= active;
+                let (display_list, height) = tab::message_page(
+                    &format!("Couldn't load {}\n\n{}", tab.url(), message),
+                    self.canvas.width as f32,
+                );
+                tab.viewing_source = false;
+                self.present(index, display_list, height);
+            }
+        }
+    }
+
+    fn present(&mut self, index: usize, display_list: DisplayList, height: f32) {
+        let active = index == self.active;
+        if active {
+            self.damage_overlay();
+        }
+        let viewport_height = self.canvas.height as f32;
+        let tab = &mut self.tabs[index];
+        let previous = std::mem::replace(&mut tab.display_list, display_list);
+        tab.page_height = height;
+        tab.hovered = None;
+        tab.pointer = None;
+
+        let scroll_y = tab.scroll_y;
+        if let Some(pending) = tab.pending_scroll
+            && height > viewport_height
+        {
+            tab.scroll_y = pending;
+            tab.pending_scroll = None;
+        }
+        tab.clamp_scroll(viewport_height);
+        if !active {
+            return;
+        }
+        if tab.scroll_y != scroll_y {
+            tab.report_viewport(viewport_height);
+            self.damage_all();
+        } else if !previous.sticky.is_empty() || !tab.display_list.sticky.is_empty() {
+            self.damage_all();
+        } else {
+            let damage = Damage::between(&previous, &tab.display_list).offset(-tab.scroll_y);
+            self.damage.extend(&damage);
+        }
+    }
+
+    fn toggle_source(&mut self) {
+        self.damage_overlay();
+        let viewport_height = self.canvas.height as f32;
+        let tab = &mut self.tabs[self.active];
+        tab.hovered = None;
+        tab.pointer = None;
+        tab.viewing_source = !tab.viewing_source;
+        let (display_list, height, scroll_y) = std::mem::take(&mut tab.hidden_view);
+        tab.hidden_view = (
+            std::mem::replace(&mut tab.display_list, display_list),
+            std::mem::replace(&mut tab.page_height, height),
+            std::mem::replace(&mut tab.scroll_y, scroll_y),
+        );
+        tab.clamp_scroll(viewport_height);
+        tab.report_viewport(viewport_height);
+        self.damage_all();
+    }
+
+    fn open_tab(&mut self, url: &str) -> Result<()> {
+        let viewport = Rect::new(
+            0.0,
+            0.0,
+            self.canvas.width as f32,
+            self.canvas.height as f32,
+        );
+        let tab = Tab::open(
+            url,
+            viewport,
+            Arc::clone(&self.settings),
+            self.storage.new_tab(),
+        )?;
+        self.tabs.push(tab);
+        self.select_tab(self.tabs.len() - 1);
+        Ok(())
+    }
+
+    fn navigate(&mut self, input: &str) -> Result<()> {
+        let Some(url) = address_bar::resolve(input) else {
+            return Ok(());
+        };
+        let viewport = self.viewport();
+        let tab = &mut self.tabs[self.active];
+        let navigation = tab
+            .location()
+            .and_then(|location| location.assign(&url))
+            .unwrap_or(Navigation::Load {
+                url,
+                replace: false,
+            });
+        tab.follow(navigation, viewport)?;
+        self.address_bar.blur();
+        self.chrome_dirty = true;
+        self.update_window();
+        self.damage_all();
+        Ok(())
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        self.tabs.remove(index);
+        if self.tabs.is_empty() {
+            return;
+        }
+        if self.active > index || self.active == self.tabs.len() {
+            self.active -= 1;
+        }
+        self.activated();
+    }
+
+    fn select_tab(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.active {
+            self.close_find();
+            self.active = index;
+            self.activated();
+        }
+    }
+
+    fn activated(&mut self) {
+        self.address_bar.blur();
+        let viewport_height = self.canvas.height as f32;
+        let tab = &mut self.tabs[self.active];
+        tab.hovered = None;
+        tab.pointer = None;
+        tab.clamp_scroll(viewport_height);
+        tab.report_viewport(viewport_height);
+        self.chrome_dirty = true;
+        self.update_window();
+        self.damage_all();
+    }
+
+    fn open_find(&mut self) {
+        self.address_bar.blur();
+        self.find_bar.open();
+        self.chrome_dirty = true;
+    }
+
+    fn close_find(&mut self) {
+        if !self.find_bar.open {
+            return;
+        }
+        self.find_bar.close();
+        self.chrome_dirty = true;
+        if let Some(tab) = self.tabs.get(self.active) {
+            tab.pipeline.send(Command::StopFinding);
+        }
+    }
+
+    fn search(&mut self) {
+        let query = self.find_bar.query().to_string();
+        self.tabs[self.active].pipeline.send(if query.is_empty() {
+            Command::StopFinding
+        } else {
+            Command::Find(query)
+        });
+        self.chrome_dirty = true;
+    }
+
+    fn navigate_history(&mut self, forward: bool) -> Result<()> {
+        let viewport = self.viewport();
+        let tab = &mut self.tabs[self.active];
+        let moved = if forward {
+            tab.go_forward(viewport)?
+        } else {
+            tab.go_back(viewport)?
+        };
+        if moved {
+            let viewport_height = self.canvas.height as f32;
+            tab.clamp_scroll(viewport_height);
+            tab.report_viewport(viewport_height);
+            self.chrome_dirty = true;
+            self.update_window();
+            self.damage_all();
+        }
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let viewport = self.viewport();
+        self.tabs[self.active].reload(viewport)?;
+        self.chrome_dirty = true;
+        self.update_window();
+        self.damage_all();
+        Ok(())
+    }
+
+    fn update_window(&mut self) {
+        let tab = &self.tabs[self.active];
+        self.window.set_title(&format!("Icarus - {}", tab.label()));
+        if let Some(icon) = tab.icon.clone() {
+            self.set_icon(&icon);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_icon(&mut self, icon: &Image) {
+        let mut buffer = vec![icon.width as u64, icon.height as u64];
+        buffer.extend(icon.pixels.iter().map(|color| {
+            (color.a as u64) << 24 | (color.r as u64) << 16 | (color.g as u64) << 8 | color.b as u64
+        }));
+        if let Ok(icon) = Icon::try_from(buffer.as_slice()) {
+            self.window.set_icon(icon);
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn set_icon(&mut self, _icon: &Image) {}
+
+    fn damage_all(&mut self) {
+        self.damage = Damage::full(self.canvas.width, self.canvas.height);
+    }
+
+    fn damage_overlay(&mut self) {
+        if !self.inspecting {
+            return;
+        }
+        let tab = &self.tabs[self.active];
+        if let Some(hit) = &tab.hovered {
+            self.damage.add(inspector::overlay_bounds(
+                hit,
+                tab.scroll_y,
+                self.canvas.width,
+            ));
+        }
+    }
+
+    fn damage_selection(&mut self, rects: &[Rect]) {
+        let scroll_y = self.tabs[self.active].scroll_y;
+        for rect in rects {
+            self.damage.add(Rect::new(
+                rect.x,
+                rect.y - scroll_y,
+                rect.width,
+                rect.height,
+            ));
+        }
+    }
+
+    fn update_throttling(&mut self) {
+        let focused = self.window.is_active();
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            let throttled = !focused || index != self.active;
+            if tab.throttled != throttled {
+                tab.throttled = throttled;
+                tab.pipeline.send(Command::Throttle(throttled));
+            }
+        }
+    }
+
+    fn handle_resize(&mut self) {
+        let (width, height) = self.window.get_size();
+        if width == 0 || height <= chrome::HEIGHT {
+            return;
+        }
+        let height = height - chrome::HEIGHT;
+        if (width, height) == (self.canvas.width, self.canvas.height) {
+            return;
+        }
+        self.canvas = Canvas::new(width, height, self.settings.palette().background);
+        self.chrome = Canvas::new(width, chrome::HEIGHT, Color::WHITE);
+        self.chrome_dirty = true;
+        for tab in &mut self.tabs {
+            tab.pipeline.send(Command::Resize(width));
+        }
+        let tab = &mut self.tabs[self.active];
+        tab.clamp_scroll(height as f32);
+        tab.report_viewport(height as f32);
+        self.damage_all();
+    }
+
+    fn handle_keys(&mut self) -> Result<()> {
+        let page = self.canvas.height as f32 * 0.9;
+        let control =
+            self.window.is_key_down(Key::LeftCtrl) || self.window.is_key_down(Key::RightCtrl);
+        let shift =
+            self.window.is_key_down(Key::LeftShift) || self.window.is_key_down(Key::RightShift);
+        let alt = self.window.is_key_down(Key::LeftAlt) || self.window.is_key_down(Key::RightAlt);
+        for key in self.window.get_keys_pressed(KeyRepeat::Yes) {
+            if self.tabs.is_empty() {
+                break;
+            }
+            if self.address_bar.focused && !control {
+                self.edit_address(key)?;
+                continue;
+            }
+            if self.find_bar.input.focused && !control {
+                self.edit_find(key, shift);
+                continue;
+            }
+            let count = self.tabs.len();
+            let tab = &self.tabs[self.active];
+            match key {
+                Key::Escape if self.find_bar.open => self.close_find(),
+                Key::Q if control => self.quit = true,
+                Key::L if control => {
+                    self.find_bar.input.blur();
+                    self.address_bar.focus(tab.url());
+                    self.chrome_dirty = true;
+                }
+                Key::F if control && !tab.viewing_source => self.open_find(),
+                Key::G if control && self.find_bar.open => {
+                    tab.pipeline.send(Command::FindNext { forward: !shift })
+                }
+                Key::T if control => {
+                    self.open_tab(BLANK_URL)?;
+                    self.address_bar.focus("");
+                }
+                Key::H if control => self.open_tab(HISTORY_URL)?,
+                Key::W if control => self.close_tab(self.active),
+                Key::Tab if control && shift => self.select_tab((self.active + count - 1) % count),
+                Key::Tab if control => self.select_tab((self.active + 1) % count),
+                Key::Key9 if control => self.select_tab(count - 1),
+                key if control && tab_number(key).is_some() => {
+                    self.select_tab(tab_number(key).unwrap_or_default())
+                }
+                Key::Left if alt => self.navigate_history(false)?,
+                Key::Right if alt => self.navigate_history(true)?,
+                Key::U if control => self.toggle_source(),
+                Key::C if control && !tab.viewing_source => tab.pipeline.send(Command::Copy),
+                Key::F9 if !tab.viewing_source => {
+                    self.close_find();
+                    let tab = &self.tabs[self.active];
+                    tab.pipeline.send(Command::Reader(!tab.reading));
+                }
+                Key::F5 => self.reload()?,
+                Key::R if control => self.reload()?,
+                Key::Down => self.scroll_by(SCROLL_STEP),
+                Key::Up => self.scroll_by(-SCROLL_STEP),
+                Key::PageDown | Key::Space => self.scroll_by(page),
+                Key::PageUp => self.scroll_by(-page),
+                Key::Home => self.scroll_by(-tab.scroll_y),
+                Key::End => self.scroll_by(tab.page_height),
+                Key::F12 if !tab.viewing_source => self.toggle_inspector(),
+                Key::I if control && shift && !tab.viewing_source => self.toggle_inspector(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn toggle_inspector(&mut self) {
+        self.damage_overlay();
+        self.inspecting = !self.inspecting;
+        let tab = &mut self.tabs[self.active];
+        tab.hovered = None;
+        tab.pointer = None;
+    }
+
+    fn edit_address(&mut self, key: Key) -> Result<()> {
+        let bar = &mut self.address_bar;
+        match key {
+            Key::Enter | Key::NumPadEnter => {
+                let input = bar.text.clone();
+                self.navigate(&input)?;
+            }
+            Key::Escape => bar.blur(),
+            Key::Backspace => bar.backspace(),
+            Key::Delete => bar.delete(),
+            Key::Left => bar.move_left(),
+            Key::Right => bar.move_right(),
+            Key::Home => bar.home(),
+            Key::End => bar.end(),
+            _ => return Ok(()),
+        }
+        self.chrome_dirty = true;
+        Ok(())
+    }
+
+    fn edit_find(&mut self, key: Key, shift: bool) {
+        let input = &mut self.find_bar.input;
+        match key {
+            Key::Enter | Key::NumPadEnter => {
+                let tab = &self.tabs[self.active];
+                tab.pipeline.send(Command::FindNext { forward: !shift });
+                return;
+            }
+            Key::Escape => {
+                self.close_find();
+                return;
+            }
+            Key::Backspace => input.backspace(),
+            Key::Delete => input.delete(),
+            Key::Left => input.move_left(),
+            Key::Right => input.move_right(),
+            Key::Home => input.home(),
+            Key::End => input.end(),
+            _ => return,
+        }
+        if matches!(key, Key::Backspace | Key::Delete) {
+            self.search();
+        }
+        self.chrome_dirty = true;
+    }
+
+    fn handle_text_input(&mut self) {
+        let typed = std::mem::take(&mut *self.typed.borrow_mut());
+        let control =
+            self.window.is_key_down(Key::LeftCtrl) || self.window.is_key_down(Key::RightCtrl);
+        if typed.is_empty() || control {
+            return;
+        }
+        if self.find_bar.input.focused {
+            for c in typed {
+                self.find_bar.input.insert(c);
+            }
+            self.search();
+            return;
+        }
+        if !self.address_bar.focused {
+            return;
+        }
+        for c in typed {
+            self.address_bar.insert(c);
+        }
+        self.chrome_dirty = true;
+    }
+
+    fn handle_mouse(&mut self) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        if let Some((_, dy)) = self.window.get_scroll_wheel() {
+            self.scroll_by(-dy * SCROLL_STEP / 3.0);
+        }
+
+        let mouse = self.window.get_mouse_pos(MouseMode::Discard);
+        let down = self.window.get_mouse_down(MouseButton::Left);
+        let clicked = down && !self.mouse_was_down;
+        self.mouse_was_down = down;
+        if clicked
+            && let Some((x, y)) = mouse
+            && let Some(index) = chrome::tab_at(x, y, self.tabs.len(), self.canvas.width)
+        {
+            self.select_tab(index);
+            return;
+        }
+        if clicked
+            && let Some((x, y)) = mouse
+            && y < chrome::HEIGHT as f32
+        {
+            if chrome::address_bounds(self.canvas.width, self.find_bar.open).contains(x, y) {
+                let url = self.tabs[self.active].url().to_string();
+                self.find_bar.input.blur();
+                self.address_bar.focus(&url);
+            } else if self.find_bar.open && chrome::find_bounds(self.canvas.width).contains(x, y) {
+                self.address_bar.blur();
+                self.find_bar.open();
+            } else {
+                self.address_bar.blur();
+                self.find_bar.input.blur();
+            }
+            self.chrome_dirty = true;
+            return;
+        }
+
+        let tab = &mut self.tabs[self.active];
+        if tab.viewing_source {
+            return;
+        }
+        if !self.inspecting {
+            let pointer = mouse
+                .filter(|(_, y)| *y >= chrome::HEIGHT as f32)
+                .map(|(x, y)| (x, y - chrome::HEIGHT as f32 + tab.scroll_y));
+            if let Some((x, y)) = pointer {
+                if clicked {
+                    tab.pipeline.send(Command::Click { x, y });
+                    tab.pipeline.send(Command::SelectFrom { x, y });
+                } else if down && pointer != tab.pointer {
+                    tab.pipeline.send(Command::SelectTo { x, y });
+                }
+            }
+            tab.pointer = pointer.filter(|_| down);
+            return;
+        }
+        let pointer = mouse
+            .filter(|(_, y)| *y >= chrome::HEIGHT as f32)
+            .map(|(x, y)| (x, y - chrome::HEIGHT as f32 + tab.scroll_y));
+        if pointer != tab.pointer {
+            tab.pointer = pointer;
+            match pointer {
+                Some((x, y)) => tab.pipeline.send(Command::HitTest { x, y }),
+                None => {
+                    self.damage_overlay();
+                    self.tabs[self.active].hovered = None;
+                }
+            }
+        }
+
+        let tab = &self.tabs[self.active];
+        if clicked && let Some((x, y)) = tab.pointer {
+            tab.pipeline.send(Command::Inspect { x, y });
+        }
+    }
+
+    fn scroll_by(&mut self, delta: f32) {
+        let viewport_height = self.canvas.height as f32;
+        let tab = &mut self.tabs[self.active];
+        let previous = tab.scroll_y;
+        tab.scroll_y += delta;
+        tab.clamp_scroll(viewport_height);
+        let delta = tab.scroll_y - previous;
+        if delta == 0.0 {
+            return;
+        }
+        tab.report_viewport(viewport_height);
+
+        let overlay = tab
+            .hovered
+            .as_ref()
+            .filter(|_| self.inspecting)
+            .map(|hit| inspector::overlay_bounds(hit, previous, self.canvas.width));
+        self.canvas
+            .scroll(delta as i32, self.settings.palette().background);
+        let (width, height) = (self.canvas.width as f32, self.canvas.height as f32);
+        let exposed = if delta > 0.0 {
+            Rect::new(0.0, height - delta, width, delta)
+        } else {
+            Rect::new(0.0, 0.0, width, -delta)
+        };
+        self.damage = self.damage.offset(-delta);
+        self.damage.add(exposed);
+        let list = &tab.display_list;
+        for rect in list.sticky_bounds(previous, height) {
+            self.damage
+                .add(Rect::new(rect.x, rect.y - delta, rect.width, rect.height));
+        }
+        for rect in list.sticky_bounds(tab.scroll_y, height) {
+            self.damage.add(rect);
+        }
+        if let Some(overlay) = overlay {
+            self.damage.add(Rect::new(
+                overlay.x,
+                overlay.y - delta,
+                overlay.width,
+                overlay.height,
+            ));
+            self.damage_overlay();
+        }
+    }
+
+    fn redraw(&mut self) {
+        if self.chrome_dirty {
+            let labels: Vec<String> = self.tabs.iter().map(Tab::label).collect();
+            chrome::paint_tab_strip(&mut self.chrome, self.fonts.as_ref(), &labels, self.active);
+            let tab = &self.tabs[self.active];
+            chrome::paint_toolbar(
+                &mut self.chrome,
+                self.fonts.as_ref(),
+                &self.address_bar,
+                &self.find_bar,
+                tab.url(),
+                tab.progress,
+            );
+            self.chrome_dirty = false;
+        }
+
+        let damage = std::mem::take(&mut self.damage);
+        let viewport = Rect::new(
+            0.0,
+            0.0,
+            self.canvas.width as f32,
+            self.canvas.height as f32,
+        );
+        let damage = damage.clip(viewport);
+        let _span = tracing::info_span!(
+            "repaint",
+            rects = damage.rects().len(),
+            pixels = damage.area() as u64
+        )
+        .entered();
+
+        let tab = &mut self.tabs[self.active];
+        for rect in damage.rects() {
+            self.canvas.clip = Some(*rect);
+            self.canvas
+                .fill_rect(*rect, self.settings.palette().background);
+            self.canvas
+                .paint(&tab.display_list, self.fonts.as_ref(), tab.scroll_y);
+            if !tab.viewing_source {
+                for found in &tab.found {
+                    let found =
+                        Rect::new(found.x, found.y - tab.scroll_y, found.width, found.height);
+                    self.canvas.fill_rect(found, FIND_TINT);
+                }
+                for selected in &tab.selection {
+                    let selected = Rect::new(
+                        selected.x,
+                        selected.y - tab.scroll_y,
+                        selected.width,
+                        selected.height,
+                    );
+                    self.canvas.fill_rect(selected, SELECTION_TINT);
+                }
+            }
+            if self.inspecting
+                && let Some(hit) = &tab.hovered
+            {
+                inspector::paint_overlay(&mut self.canvas, hit, self.fonts.as_ref(), tab.scroll_y);
+            }
+        }
+        self.canvas.clip = None;
+        if !tab.painted && !tab.display_list.items.is_empty() {
+            tab.painted = true;
+            tab.pipeline.send(Command::Painted);
+        }
+
+        self.frame.clear();
+        self.frame.extend_from_slice(&self.chrome.pixels);
+        self.frame.extend_from_slice(&self.canvas.pixels);
+    }
+}
+
+fn tab_number(key: Key) -> Option<usize> {
+    let keys = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+    ];
+    keys.iter().position(|candidate| *candidate == key)
+}
+
+pub fn open(url: &str, settings: Settings) -> Result<()> {
+    Shell::new(url, settings)?.run()
+}
@@ -0,0 +1,746 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::io::{self, Read};
+use std::string::String;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+use url::Url;
+
+use super::inspector::{self, Highlight};
+use crate::clipboard;
+use crate::dom::Document;
+use crate::find::{FindOptions, Finder};
+use crate::frame::{self, Frame};
+use crate::image::ImageLoader;
+use crate::internal;
+use crate::layout::{LayoutOptions, LayoutTree, Rect, selection};
+use crate::loader::{self, DocumentStream, Load, Open, Progress};
+use crate::location;
+use crate::metadata::PageMetadata;
+use crate::mutation;
+use crate::net::error::LoadError;
+use crate::paint::{DisplayList, font};
+use crate::reader;
+use crate::refresh::Refresh;
+use crate::settings::{FontSettings, Settings};
+use crate::storage::StorageContext;
+use crate::web_font::{FontLoader, WebFont};
+
+const CHUNK_SIZE: usize = 16 * 1024;
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+const ANIMATION_INTERVAL: Duration = Duration::from_millis(16);
+
+pub enum Command {
+    Resize(usize),
+    Scroll { y: f32, height: f32 },
+    HitTest { x: f32, y: f32 },
+    Inspect { x: f32, y: f32 },
+    Click { x: f32, y: f32 },
+    SelectFrom { x: f32, y: f32 },
+    SelectTo { x: f32, y: f32 },
+    Copy,
+    Find(String),
+    FindNext { forward: bool },
+    StopFinding,
+    Reader(bool),
+    Painted,
+    Received(Vec<u8>),
+    Finished(Option<String>),
+    UrlChanged(String),
+    ScrollToFragment(String),
+    PopState { url: String, state: Option<Value> },
+    Throttle(bool),
+}
+
+pub enum Event {
+    Frame {
+        display_list: DisplayList,
+        height: f32,
+    },
+    Committed(Option<String>),
+    Source(String),
+    Metadata(PageMetadata),
+    Fonts(Vec<WebFont>),
+    Hovered(Option<Highlight>),
+    Described(String),
+    ScrollTo(f32),
+    Selected(Vec<Rect>),
+    Found {
+        matches: Vec<Rect>,
+        current: Option<usize>,
+        total: usize,
+    },
+    Reading(bool),
+    Link(String),
+    Refresh(Refresh),
+    Failed(String),
+}
+
+pub struct Pipeline {
+    commands: Sender<Command>,
+    events: Receiver<Event>,
+}
+
+impl Pipeline {
+    pub fn spawn(
+        url: &str,
+        viewport: Rect,
+        settings: Arc<Settings>,
+        storage: StorageContext,
+    ) -> Result<Self> {
+        let (commands, command_receiver) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+        let url = url.to_string();
+        let command_sender = commands.clone();
+        std::thread::Builder::new()
+            .name(String::from("pipeline"))
+            .spawn(move || {
+                run_worker(
+                    &url,
+                    viewport,
+                    &settings,
+                    storage,
+                    command_sender,
+                    command_receiver,
+                    event_sender,
+                )
+            })?;
+        Ok(Pipeline { commands, events })
+    }
+
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+
+    pub fn poll(&self) -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        loop {
+            match self.events.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) => return Ok(events),
+                Err(TryRecvError::Disconnected) if events.is_empty() => {
+                    anyhow::bail!("page pipeline stopped unexpectedly")
+                }
+                Err(TryRecvError::Disconnected) => return Ok(events),
+            }
+        }
+    }
+}
+
+pub fn options(width: usize, fonts: &FontSettings) -> LayoutOptions {
+    LayoutOptions {
+        viewport_width: width as f32,
+        font: font::proportions(fonts),
+        ..LayoutOptions::default()
+    }
+}
+
+fn run_worker(
+    url: &str,
+    viewport: Rect,
+    settings: &Settings,
+    storage: StorageContext,
+    command_sender: Sender<Command>,
+    commands: Receiver<Command>,
+    events: Sender<Event>,
+) {
+    let opened = match loader::open(url, settings) {
+        Ok(Open::Document(stream)) => Ok(stream),
+        Ok(Open::Download(_)) => Err((
+            LoadError::NotHtml,
+            format!("{} is not an HTML document", url),
+        )),
+        Err(err) => Err((LoadError::classify(url, &err), format!("{:#}", err))),
+    };
+    let (mut stream, committed) = match opened {
+        Ok(stream) => {
+            let committed = stream.document().url().map(|url| url.to_string());
+            (stream, committed)
+        }
+        Err((kind, message)) => {
+            log::warn!("failed to load {}: {}", url, message);
+            let error = internal::error_url(url, kind, &message);
+            match loader::open(error.as_str(), settings) {
+                Ok(Open::Document(stream)) => (stream, None),
+                _ => {
+                    let _ = events.send(Event::Failed(message));
+                    return;
+                }
+            }
+        }
+    };
+
+    stream.document_mut().storage = storage;
+
+    if events.send(Event::Committed(committed)).is_err() {
+        return;
+    }
+
+    let body = stream.take_body();
+    let mut worker = Worker {
+        tree: LayoutTree::build(
+            stream.document(),
+            options(viewport.width as usize, &settings.fonts),
+        ),
+        page: Page::Loading(stream),
+        frames: Vec::new(),
+        finder: None,
+        settings: settings.clone(),
+        events,
+        images: ImageLoader::new(),
+        fonts: FontLoader::new(),
+        viewport,
+        last_frame: Instant::now(),
+        animating: false,
+        throttled: false,
+        last_tick: Instant::now(),
+    };
+    match body {
+        Some(body) => {
+            if let Err(err) = spawn_reader(body, command_sender) {
+                log::warn!("couldn't start reading {}: {:#}", url, err);
+                return;
+            }
+        }
+        None => {
+            drop(command_sender);
+            if !worker.finish_loading() {
+                return;
+            }
+        }
+    }
+
+    loop {
+        let command = if worker.ticking() {
+            let wait = ANIMATION_INTERVAL.saturating_sub(worker.last_tick.elapsed());
+            match commands.recv_timeout(wait) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        } else {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            }
+        };
+        if let Some(command) = command {
+            let mut batch = vec![command];
+            batch.extend(commands.try_iter());
+            if !worker.handle(batch) {
+                return;
+            }
+        }
+        if worker.ticking() && worker.last_tick.elapsed() >= ANIMATION_INTERVAL && !worker.animate()
+        {
+            return;
+        }
+        // The page has no event loop of its own, so the end of each batch or
+        // frame is where mutation observers get their microtask checkpoint.
+        mutation::notify_observers();
+    }
+}
+
+fn spawn_reader(mut body: Box<dyn Read + Send>, commands: Sender<Command>) -> Result<()> {
+    std::thread::Builder::new()
+        .name(String::from("network"))
+        .spawn(move || {
+            let mut buffer = vec![0; CHUNK_SIZE];
+            loop {
+                let command = match body.read(&mut buffer) {
+                    Ok(0) => Command::Finished(None),
+                    Ok(read) => Command::Received(buffer[..read].to_vec()),
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => Command::Finished(Some(err.to_string())),
+                };
+                let finished = matches!(command, Command::Finished(_));
+                if commands.send(command).is_err() || finished {
+                    return;
+                }
+            }
+        })?;
+    Ok(())
+}
+
+fn http_error(document: &Document) -> Option<u16> {
+    let status = document.response.as_ref()?.status;
+    let empty = document
+        .source
+        .as_deref()
+        .is_none_or(|source| source.trim().is_empty());
+    (status >= 400 && empty).then_some(status)
+}
+
+enum Page {
+    Loading(Box<DocumentStream>),
+    Loaded(Box<Document>),
+    Reading {
+        page: Box<Document>,
+        article: Box<Document>,
+    },
+}
+
+impl Page {
+    fn document(&self) -> &Document {
+        match self {
+            Page::Loading(stream) => stream.document(),
+            Page::Loaded(document) => document,
+            Page::Reading { article, .. } => article,
+        }
+    }
+
+    fn page(&self) -> &Document {
+        match self {
+            Page::Reading { page, .. } => page,
+            _ => self.document(),
+        }
+    }
+
+    fn page_mut(&mut self) -> &mut Document {
+        match self {
+            Page::Loading(stream) => stream.document_mut(),
+            Page::Loaded(document) | Page::Reading { page: document, .. } => document,
+        }
+    }
+
+    fn set_url(&mut self, url: &str) {
+        let Ok(url) = Url::parse(url) else {
+            return;
+        };
+        if let Some(response) = &mut self.page_mut().response {
+            response.url = url;
+        }
+    }
+}
+
+struct Worker {
+    page: Page,
+    settings: Settings,
+    tree: LayoutTree,
+    frames: Vec<Frame>,
+    finder: Option<Finder>,
+    events: Sender<Event>,
+    images: ImageLoader,
+    fonts: FontLoader,
+    viewport: Rect,
+    last_frame: Instant,
+    animating: bool,
+    throttled: bool,
+    last_tick: Instant,
+}
+
+impl Worker {
+    fn handle(&mut self, batch: Vec<Command>) -> bool {
+        let mut progress = Progress::Unchanged;
+        let mut finished = false;
+        for command in &batch {
+            match command {
+                Command::Received(bytes) => {
+                    if let Page::Loading(stream) = &mut self.page {
+                        progress = progress.max(stream.push(bytes));
+                    }
+                }
+                Command::Finished(error) => {
+                    if let Some(error) = error {
+                        log::warn!("connection closed early: {}", error);
+                    }
+                    finished = true;
+                }
+                Command::UrlChanged(url) => self.page.set_url(url),
+                Command::PopState { url, state } => {
+                    self.page.set_url(url);
+                    log::debug!(
+                        "popstate {} with state {}",
+                        url,
+                        state.as_ref().unwrap_or(&Value::Null)
+                    );
+                }
+                Command::Throttle(throttled) => self.throttled = *throttled,
+                _ => {}
+            }
+        }
+        if finished {
+            if !self.finish_loading() {
+                return false;
+            }
+        } else if progress == Progress::Replaced
+            || (progress == Progress::Updated && self.last_frame.elapsed() >= FRAME_INTERVAL)
+        {
+            if progress == Progress::Replaced {
+                self.tree = LayoutTree::build(self.page.document(), self.tree.options);
+            } else {
+                self.tree.update(self.page.document(), self.tree.options);
+            }
+            if !self.send_frame() {
+                return false;
+            }
+        }
+
+        let resize = batch.iter().rev().find_map(|command| match command {
+            Command::Resize(width) => Some(*width),
+            _ => None,
+        });
+        if let Some(width) = resize {
+            self.viewport.width = width as f32;
+            let options = LayoutOptions {
+                viewport_width: width as f32,
+                ..self.tree.options
+            };
+            self.tree.update(self.page.document(), options);
+            if !self.send_frame() {
+                return false;
+            }
+        }
+        let scroll = batch.iter().rev().find_map(|command| match command {
+            Command::Scroll { y, height } => Some((*y, *height)),
+            _ => None,
+        });
+        if let Some((y, height)) = scroll {
+            self.viewport.y = y;
+            self.viewport.height = height;
+        }
+        if !self.load_images() {
+            return false;
+        }
+
+        let last_hit_test = batch
+            .iter()
+            .rposition(|command| matches!(command, Command::HitTest { .. }));
+        let last_select = batch
+            .iter()
+            .rposition(|command| matches!(command, Command::SelectTo { .. }));
+        for (index, command) in batch.into_iter().enumerate() {
+            let event = match command {
+                Command::Resize(_)
+                | Command::Scroll { .. }
+                | Command::Received(_)
+                | Command::Finished(_)
+                | Command::UrlChanged(_)
+                | Command::PopState { .. }
+                | Command::Throttle(_) => continue,
+                Command::HitTest { .. } if Some(index) != last_hit_test => continue,
+                Command::HitTest { x, y } => {
+                    Event::Hovered(self.tree.hit_test(x, y).map(|hit| Highlight::new(&hit)))
+                }
+                Command::Inspect { x, y } => match self.tree.hit_test(x, y) {
+                    Some(hit) => Event::Described(inspector::describe(&hit.node)),
+                    None => continue,
+                },
+                Command::Click { x, y } => match self.link_at(x, y) {
+                    Some(url) => Event::Link(url),
+                    None => continue,
+                },
+                Command::SelectFrom { x, y } => {
+                    let document = self.page.document();
+                    let mut selection = document.selection.borrow_mut();
+                    match selection::position_at(&self.tree, x, y) {
+                        Some(point) => selection.collapse(&point.node, point.offset),
+                        None => selection.remove_all_ranges(),
+                    }
+                    Event::Selected(Vec::new())
+                }
+                Command::SelectTo { .. } if Some(index) != last_select => continue,
+                Command::SelectTo { x, y } => match selection::position_at(&self.tree, x, y) {
+                    Some(point) => {
+                        let document = self.page.document();
+                        document
+                            .selection
+                            .borrow_mut()
+                            .extend(&point.node, point.offset);
+                        Event::Selected(self.selection_rects())
+                    }
+                    None => continue,
+                },
+                Command::ScrollToFragment(fragment) => match self.fragment_offset(&fragment) {
+                    Some(y) => Event::ScrollTo(y),
+                    None => continue,
+                },
+                Command::Copy => {
+                    if let Err(err) = clipboard::copy_selection(self.page.document()) {
+                        log::warn!("couldn't copy the selection: {:#}", err);
+                    }
+                    continue;
+                }
+                Command::Find(query) => {
+                    let document = self.page.document();
+                    self.finder = Some(Finder::new(document, &query, FindOptions::default()));
+                    if !self.step_finder(true) {
+                        return false;
+                    }
+                    continue;
+                }
+                Command::FindNext { forward } => {
+                    if !self.step_finder(forward) {
+                        return false;
+                    }
+                    continue;
+                }
+                Command::Reader(reading) => {
+                    if !self.toggle_reader(reading) {
+                        return false;
+                    }
+                    continue;
+                }
+                Command::StopFinding => {
+                    self.finder = None;
+                    Event::Found {
+                        matches: Vec::new(),
+                        current: None,
+                        total: 0,
+                    }
+                }
+                Command::Painted => {
+                    self.page.page().performance.mark_first_paint();
+                    self.complete_load();
+                    continue;
+                }
+            };
+            if self.events.send(event).is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn finish_loading(&mut self) -> bool {
+        let page = std::mem::replace(&mut self.page, Page::Loaded(Box::default()));
+        let mut document = match page {
+            Page::Loading(stream) => stream.finish(),
+            Page::Loaded(document) | Page::Reading { page: document, .. } => *document,
+        };
+        if let Some(status) = http_error(&document) {
+            let url = document
+                .url()
+                .map(|url| url.to_string())
+                .unwrap_or_default();
+            let message = format!("the server answered {} with no content", status);
+            let error = internal::error_url(&url, LoadError::HttpStatus(status), &message);
+            if let Ok(Load::Document(mut error_page)) =
+                loader::load_with(error.as_str(), &self.settings)
+            {
+                error_page.storage = document.storage.clone();
+                document = error_page;
+            }
+        }
+        self.frames = frame::load_subframes(&document);
+        self.tree = LayoutTree::build(&document, self.tree.options);
+        self.page = Page::Loaded(Box::new(document));
+
+        let source = self
+            .page
+            .document()
+            .source
+            .as_deref()
+            .unwrap_or_default()
+            .to_string();
+        if !self.send_frame() || self.events.send(Event::Source(source)).is_err() {
+            return false;
+        }
+        if !self.load_fonts() || !self.load_images() {
+            return false;
+        }
+        let fragment = self
+            .page
+            .document()
+            .url()
+            .and_then(|url| url.fragment())
+            .map(str::to_string);
+        if let Some(y) = fragment.and_then(|fragment| self.fragment_offset(&fragment))
+            && self.events.send(Event::ScrollTo(y)).is_err()
+        {
+            return false;
+        }
+        self.complete_load();
+        let metadata = PageMetadata::load(self.page.page());
+        if self.events.send(Event::Metadata(metadata)).is_err() {
+            return false;
+        }
+        match Refresh::from_document(self.page.page()) {
+            Some(refresh) if self.settings.refresh => {
+                self.events.send(Event::Refresh(refresh)).is_ok()
+            }
+            _ => true,
+        }
+    }
+
+    fn link_at(&self, x: f32, y: f32) -> Option<String> {
+        let mut node = Some(self.tree.hit_test(x, y)?.node);
+        while let Some(current) = node {
+            if matches!(current.element_name(), Some("a" | "area"))
+                && let Some(href) = current.get_attribute("href")
+            {
+                let url = match self
+                    .frames
+                    .iter()
+                    .find_map(|frame| frame.frame_for_node(&current))
+                {
+                    Some(frame) => frame.document.resolve_url(&href)?,
+                    None => self.page.page().resolve_url(&href)?,
+                };
+                return Some(url.to_string());
+            }
+            node = current.parent.borrow().upgrade();
+        }
+        None
+    }
+
+    fn fragment_offset(&self, fragment: &str) -> Option<f32> {
+        match location::fragment_target(self.page.document(), fragment) {
+            Some(target) => self.tree.rect_of(&target).map(|rect| rect.y),
+            None if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") => Some(0.0),
+            None => None,
+        }
+    }
+
+    fn complete_load(&self) {
+        let performance = &self.page.page().performance;
+        if !matches!(self.page, Page::Loading(_)) && performance.metrics().first_paint.is_some() {
+            performance.mark_load_complete();
+        }
+    }
+
+    fn load_fonts(&mut self) -> bool {
+        let fonts = self.fonts.load(self.page.page());
+        if fonts.is_empty() {
+            return true;
+        }
+        if self.events.send(Event::Fonts(fonts)).is_err() {
+            return false;
+        }
+        self.tree = LayoutTree::build(self.page.document(), self.tree.options);
+        self.send_frame()
+    }
+
+    fn load_images(&mut self) -> bool {
+        let loaded = self
+            .images
+            .load_visible(self.page.document(), &self.tree, self.viewport);
+        if loaded == 0 {
+            return true;
+        }
+        self.tree.update(self.page.document(), self.tree.options);
+        self.send_frame()
+    }
+
+    fn ticking(&self) -> bool {
+        (self.animating || self.page.document().has_websockets()) && !self.throttled
+    }
+
+    fn animate(&mut self) -> bool {
+        self.last_tick = Instant::now();
+        let document = self.page.document();
+        let dispatched = document.dispatch_websocket_events();
+        self.animating = self.animating && document.advance_animations(document.timeline.elapsed());
+        if !self.animating && !dispatched {
+            return true;
+        }
+        self.tree.update(document, self.tree.options);
+        self.send_frame()
+    }
+
+    fn toggle_reader(&mut self, reading: bool) -> bool {
+        let page = std::mem::replace(&mut self.page, Page::Loaded(Box::default()));
+        self.page = match page {
+            Page::Loaded(page) if reading => match reader::extract_article(&page) {
+                Some(article) => Page::Reading {
+                    article: Box::new(article.page(&page)),
+                    page,
+                },
+                None => {
+                    log::warn!("no article content found for reader mode");
+                    Page::Loaded(page)
+                }
+            },
+            Page::Reading { page, .. } if !reading => Page::Loaded(page),
+            page => page,
+        };
+        self.finder = None;
+        self.tree = LayoutTree::build(self.page.document(), self.tree.options);
+        let cleared = Event::Found {
+            matches: Vec::new(),
+            current: None,
+            total: 0,
+        };
+        self.send_frame()
+            && self.events.send(cleared).is_ok()
+            && self.events.send(Event::Selected(Vec::new())).is_ok()
+            && self.events.send(Event::ScrollTo(0.0)).is_ok()
+            && self
+                .events
+                .send(Event::Reading(matches!(self.page, Page::Reading { .. })))
+                .is_ok()
+            && self.load_images()
+    }
+
+    fn step_finder(&mut self, forward: bool) -> bool {
+        let Some(mut finder) = self.finder.take() else {
+            return true;
+        };
+        let range = if forward {
+            finder.next(self.page.document())
+        } else {
+            finder.previous(self.page.document())
+        };
+        let target = range
+            .map(|range| selection::selection_rects(&self.tree, range))
+            .and_then(|rects| rects.first().copied());
+        self.finder = Some(finder);
+        if let Some(rect) = target
+            && (rect.y < self.viewport.y || rect.bottom() > self.viewport.bottom())
+            && self
+                .events
+                .send(Event::ScrollTo(rect.y - self.viewport.height / 3.0))
+                .is_err()
+        {
+            return false;
+        }
+        self.send_found()
+            && self
+                .events
+                .send(Event::Selected(self.selection_rects()))
+                .is_ok()
+    }
+
+    fn send_found(&self) -> bool {
+        let Some(finder) = &self.finder else {
+            return true;
+        };
+        let matches = finder
+            .matches
+            .iter()
+            .flat_map(|range| selection::selection_rects(&self.tree, range))
+            .collect();
+        let event = Event::Found {
+            matches,
+            current: finder.current().map(|(index, _)| index),
+            total: finder.matches.len(),
+        };
+        self.events.send(event).is_ok()
+    }
+
+    fn selection_rects(&self) -> Vec<Rect> {
+        let range = self.page.document().selection.borrow().range();
+        range.map_or_else(Vec::new, |range| {
+            selection::selection_rects(&self.tree, &range)
+        })
+    }
+
+    fn send_frame(&mut self) -> bool {
+        self.last_frame = Instant::now();
+        self.animating = self.page.document().animations_running();
+        let event = Event::Frame {
+            display_list: DisplayList::build(&self.tree),
+            height: self.tree.height(),
+        };
+        if self.events.send(event).is_err() || !self.send_found() {
+            return false;
+        }
+        self.page.document().selection.borrow().is_collapsed()
+            || self
+                .events
+                .send(Event::Selected(self.selection_rects()))
+                .is_ok()
+    }
+}
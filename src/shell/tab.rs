@@ -0,0 +1,257 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use super::inspector::Highlight;
+use super::pipeline::{Command, Pipeline};
+use crate::css::values::Color;
+use crate::history::{SessionHistory, Traversal};
+use crate::image::Image;
+use crate::layout::{FontMetrics, Rect};
+use crate::location::{Location, Navigation};
+use crate::paint::{DisplayItem, DisplayList};
+use crate::refresh::RefreshTimer;
+use crate::settings::Settings;
+use crate::storage::StorageContext;
+
+pub const BLANK_URL: &str = "about:blank";
+pub const HISTORY_URL: &str = "icarus://history";
+
+pub const STARTED: f32 = 0.1;
+pub const COMMITTED: f32 = 0.4;
+pub const PARSED: f32 = 0.5;
+pub const RENDERED: f32 = 0.8;
+
+const MESSAGE_FONT_SIZE: f32 = 14.0;
+const MESSAGE_MARGIN: f32 = 16.0;
+const MESSAGE_COLOR: Color = Color::rgb(96, 96, 96);
+
+pub struct Tab {
+    pub pipeline: Pipeline,
+    pub title: Option<String>,
+    pub icon: Option<Arc<Image>>,
+    pub display_list: DisplayList,
+    pub page_height: f32,
+    pub viewing_source: bool,
+    pub reading: bool,
+    pub hidden_view: (DisplayList, f32, f32),
+    pub scroll_y: f32,
+    pub pending_scroll: Option<f32>,
+    pub hovered: Option<Highlight>,
+    pub pointer: Option<(f32, f32)>,
+    pub selection: Vec<Rect>,
+    pub found: Vec<Rect>,
+    pub painted: bool,
+    pub stopped: bool,
+    pub progress: Option<f32>,
+    pub throttled: bool,
+    pub refresh: RefreshTimer,
+    history: SessionHistory,
+    settings: Arc<Settings>,
+    storage: StorageContext,
+}
+
+impl Tab {
+    pub fn open(
+        url: &str,
+        viewport: Rect,
+        settings: Arc<Settings>,
+        storage: StorageContext,
+    ) -> Result<Self> {
+        Ok(Tab {
+            pipeline: Pipeline::spawn(url, viewport, Arc::clone(&settings), storage.clone())?,
+            title: None,
+            icon: None,
+            display_list: DisplayList::default(),
+            page_height: 0.0,
+            viewing_source: false,
+            reading: false,
+            hidden_view: (DisplayList::default(), 0.0, 0.0),
+            scroll_y: 0.0,
+            pending_scroll: None,
+            hovered: None,
+            pointer: None,
+            selection: Vec::new(),
+            found: Vec::new(),
+            painted: false,
+            stopped: false,
+            progress: Some(STARTED),
+            throttled: false,
+            refresh: RefreshTimer::default(),
+            history: SessionHistory::new(url),
+            settings,
+            storage,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.history.current().url
+    }
+
+    pub fn history(&self) -> &SessionHistory {
+        &self.history
+    }
+
+    pub fn storage(&self) -> &StorageContext {
+        &self.storage
+    }
+
+    pub fn commit(&mut self, url: Option<String>) {
+        if let Some(url) = url {
+            self.history.current_mut().url = url;
+        }
+        self.advance(COMMITTED);
+    }
+
+    pub fn advance(&mut self, progress: f32) {
+        if let Some(current) = self.progress {
+            self.progress = Some(current.max(progress));
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match &self.title {
+            Some(title) => title.clone(),
+            None if self.url() == BLANK_URL => String::from("New Tab"),
+            None => self.url().to_string(),
+        }
+    }
+
+    pub fn navigate(&mut self, url: &str, viewport: Rect) -> Result<()> {
+        self.history.current_mut().scroll_y = self.scroll_y;
+        self.history.navigate(url);
+        self.load(viewport)
+    }
+
+    pub fn location(&self) -> Result<Location> {
+        Location::parse(self.url())
+    }
+
+    pub fn follow(&mut self, navigation: Navigation, viewport: Rect) -> Result<()> {
+        match navigation {
+            Navigation::Load {
+                url,
+                replace: false,
+            } => self.navigate(&url, viewport),
+            Navigation::Load { url, replace: true } => {
+                self.history.replace(&url);
+                self.load(viewport)
+            }
+            Navigation::Fragment { url, fragment } => {
+                self.history.current_mut().scroll_y = self.scroll_y;
+                self.history.navigate_fragment(&url);
+                self.pipeline.send(Command::UrlChanged(url));
+                self.pipeline.send(Command::ScrollToFragment(fragment));
+                Ok(())
+            }
+            Navigation::Reload => self.reload(viewport),
+        }
+    }
+
+    pub fn push_state(&mut self, state: Value, url: Option<&str>) -> Result<()> {
+        self.history.current_mut().scroll_y = self.scroll_y;
+        let url = self.history.push_state(state, url)?;
+        self.pipeline.send(Command::UrlChanged(url));
+        Ok(())
+    }
+
+    pub fn replace_state(&mut self, state: Value, url: Option<&str>) -> Result<()> {
+        let url = self.history.replace_state(state, url)?;
+        self.pipeline.send(Command::UrlChanged(url));
+        Ok(())
+    }
+
+    pub fn go_back(&mut self, viewport: Rect) -> Result<bool> {
+        self.go(-1, viewport)
+    }
+
+    pub fn go_forward(&mut self, viewport: Rect) -> Result<bool> {
+        self.go(1, viewport)
+    }
+
+    pub fn go(&mut self, delta: isize, viewport: Rect) -> Result<bool> {
+        self.history.current_mut().scroll_y = self.scroll_y;
+        let Some(traversal) = self.history.go(delta) else {
+            return Ok(false);
+        };
+        match traversal {
+            Traversal::Load => self.load(viewport)?,
+            Traversal::PopState { url, state } => {
+                self.scroll_y = self.history.current().scroll_y;
+                self.pipeline.send(Command::PopState { url, state });
+            }
+        }
+        Ok(true)
+    }
+
+    pub fn reload(&mut self, viewport: Rect) -> Result<()> {
+        self.history.current_mut().scroll_y = self.scroll_y;
+        self.load(viewport)
+    }
+
+    fn load(&mut self, viewport: Rect) -> Result<()> {
+        let entry = self.history.current();
+        self.pipeline = Pipeline::spawn(
+            &entry.url,
+            viewport,
+            Arc::clone(&self.settings),
+            self.storage.clone(),
+        )?;
+        self.throttled = false;
+        self.refresh.cancel();
+        self.pending_scroll = Some(entry.scroll_y).filter(|scroll_y| *scroll_y > 0.0);
+        self.title = None;
+        self.icon = None;
+        self.display_list = DisplayList::default();
+        self.page_height = 0.0;
+        self.viewing_source = false;
+        self.hidden_view = (DisplayList::default(), 0.0, 0.0);
+        self.scroll_y = 0.0;
+        self.hovered = None;
+        self.pointer = None;
+        self.painted = false;
+        self.stopped = false;
+        self.progress = Some(STARTED);
+        Ok(())
+    }
+
+    pub fn clamp_scroll(&mut self, viewport_height: f32) {
+        let max = (self.page_height - viewport_height).max(0.0);
+        self.scroll_y = self.scroll_y.clamp(0.0, max.round()).round();
+    }
+
+    pub fn report_viewport(&self, viewport_height: f32) {
+        if !self.viewing_source {
+            self.pipeline.send(Command::Scroll {
+                y: self.scroll_y,
+                height: viewport_height,
+            });
+        }
+    }
+}
+
+pub fn message_page(message: &str, width: f32) -> (DisplayList, f32) {
+    let metrics = FontMetrics::for_size(MESSAGE_FONT_SIZE);
+    let mut list = DisplayList::default();
+    let mut y = MESSAGE_MARGIN;
+    for line in message.lines() {
+        list.items.push(DisplayItem::Text {
+            rect: Rect::new(
+                MESSAGE_MARGIN,
+                y,
+                metrics.text_width(line).min(width),
+                metrics.line_height,
+            ),
+            text: line.to_string(),
+            color: MESSAGE_COLOR,
+            font_size: MESSAGE_FONT_SIZE,
+            bold: false,
+            italic: false,
+            font_family: Vec::new(),
+        });
+        y += metrics.line_height;
+    }
+    (list, y + MESSAGE_MARGIN)
+}
@@ -0,0 +1,281 @@
+use std::string::String;
+use std::vec::Vec;
+
+use crate::css::values::Color;
+use crate::layout::{FontMetrics, Rect};
+use crate::paint::{DisplayItem, DisplayList};
+
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+const GUTTER_COLOR: Color = Color::rgb(150, 150, 150);
+const TAB: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Text,
+    Tag,
+    AttributeName,
+    AttributeValue,
+    Comment,
+    Doctype,
+    Entity,
+}
+
+impl TokenKind {
+    pub fn ansi(&self) -> &'static str {
+        match self {
+            TokenKind::Text => "",
+            TokenKind::Tag => "\x1b[35m",
+            TokenKind::AttributeName => "\x1b[33m",
+            TokenKind::AttributeValue => "\x1b[34m",
+            TokenKind::Comment | TokenKind::Doctype => "\x1b[90m",
+            TokenKind::Entity => "\x1b[31m",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            TokenKind::Text => Color::BLACK,
+            TokenKind::Tag => Color::rgb(136, 18, 128),
+            TokenKind::AttributeName => Color::rgb(153, 69, 0),
+            TokenKind::AttributeValue => Color::rgb(26, 26, 166),
+            TokenKind::Comment => Color::rgb(35, 110, 37),
+            TokenKind::Doctype => Color::rgb(128, 128, 128),
+            TokenKind::Entity => Color::rgb(200, 30, 30),
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    source: &'a str,
+    position: usize,
+    tokens: Vec<(TokenKind, &'a str)>,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn emit(&mut self, kind: TokenKind, len: usize) {
+        let end = (self.position + len).min(self.source.len());
+        let text = &self.source[self.position..end];
+        self.position = end;
+        if text.is_empty() {
+            return;
+        }
+        match self.tokens.last_mut() {
+            Some((last_kind, last)) if *last_kind == kind => {
+                let start = last.as_ptr() as usize - self.source.as_ptr() as usize;
+                *last = &self.source[start..end];
+            }
+            _ => self.tokens.push((kind, text)),
+        }
+    }
+
+    fn emit_until(&mut self, kind: TokenKind, terminator: &str) {
+        let len = match self.rest().find(terminator) {
+            Some(index) => index + terminator.len(),
+            None => self.rest().len(),
+        };
+        self.emit(kind, len);
+    }
+
+    fn emit_while<F: Fn(char) -> bool>(&mut self, kind: TokenKind, predicate: F) {
+        let len = self
+            .rest()
+            .find(|c: char| !predicate(c))
+            .unwrap_or(self.rest().len());
+        self.emit(kind, len);
+    }
+
+    fn run(mut self) -> Vec<(TokenKind, &'a str)> {
+        let mut raw_text: Option<String> = None;
+        while self.position < self.source.len() {
+            let rest = self.rest();
+            if let Some(name) = raw_text.take() {
+                let closing = format!("</{}", name);
+                let len = rest
+                    .to_ascii_lowercase()
+                    .find(&closing)
+                    .unwrap_or(rest.len());
+                self.emit(TokenKind::Text, len);
+            } else if rest.starts_with("<!--") {
+                self.emit_until(TokenKind::Comment, "-->");
+            } else if rest.starts_with("<!") {
+                self.emit_until(TokenKind::Doctype, ">");
+            } else if rest.starts_with("<?") {
+                self.emit_until(TokenKind::Comment, ">");
+            } else if let Some(name) = self.tag_name() {
+                let closing = rest.starts_with("</");
+                self.tag();
+                if !closing && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                    raw_text = Some(name);
+                }
+            } else if let Some(reference) = rest.strip_prefix('&') {
+                let len = reference
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+                    .map(|index| index + 1)
+                    .unwrap_or(rest.len());
+                if len == 1 {
+                    self.emit(TokenKind::Text, 1);
+                } else {
+                    let len = if rest[len..].starts_with(';') {
+                        len + 1
+                    } else {
+                        len
+                    };
+                    self.emit(TokenKind::Entity, len);
+                }
+            } else {
+                let len = rest[1..]
+                    .find(['<', '&'])
+                    .map(|index| index + 1)
+                    .unwrap_or(rest.len());
+                self.emit(TokenKind::Text, len);
+            }
+        }
+        self.tokens
+    }
+
+    fn tag_name(&self) -> Option<String> {
+        let rest = self.rest();
+        let after = rest.strip_prefix("</").or_else(|| rest.strip_prefix('<'))?;
+        if !after.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let len = after
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(after.len());
+        Some(after[..len].to_ascii_lowercase())
+    }
+
+    fn tag(&mut self) {
+        self.emit_while(TokenKind::Tag, |c| c == '<' || c == '/');
+        self.emit_while(TokenKind::Tag, |c| {
+            !c.is_whitespace() && c != '/' && c != '>'
+        });
+        loop {
+            self.emit_while(TokenKind::Tag, |c| c.is_whitespace() || c == '/');
+            let rest = self.rest();
+            if rest.is_empty() {
+                return;
+            }
+            if rest.starts_with('>') {
+                self.emit(TokenKind::Tag, 1);
+                return;
+            }
+            self.emit_while(TokenKind::AttributeName, |c| {
+                !c.is_whitespace() && c != '=' && c != '>' && c != '/'
+            });
+            if !self.rest().starts_with('=') {
+                continue;
+            }
+            self.emit(TokenKind::Tag, 1);
+            match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let len = self.rest()[1..]
+                        .find(quote)
+                        .map(|index| index + 2)
+                        .unwrap_or(self.rest().len());
+                    self.emit(TokenKind::AttributeValue, len);
+                }
+                _ => self.emit_while(TokenKind::AttributeValue, |c| {
+                    !c.is_whitespace() && c != '>'
+                }),
+            }
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Vec<(TokenKind, &str)> {
+    Tokenizer {
+        source,
+        position: 0,
+        tokens: Vec::new(),
+    }
+    .run()
+}
+
+pub fn highlight_lines(source: &str) -> Vec<Vec<(TokenKind, &str)>> {
+    let mut lines = vec![Vec::new()];
+    for (kind, text) in tokenize(source) {
+        for (i, segment) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            let segment = segment.strip_suffix('\r').unwrap_or(segment);
+            if !segment.is_empty() {
+                lines.last_mut().unwrap().push((kind, segment));
+            }
+        }
+    }
+    if source.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+fn gutter_width(lines: usize) -> usize {
+    lines.max(1).to_string().len()
+}
+
+pub fn to_ansi(source: &str, colored: bool) -> String {
+    let lines = highlight_lines(source);
+    let width = gutter_width(lines.len());
+    let mut out = String::new();
+    for (number, line) in lines.iter().enumerate() {
+        if colored {
+            out.push_str(&format!("\x1b[90m{:>width$}\x1b[0m  ", number + 1));
+        } else {
+            out.push_str(&format!("{:>width$}  ", number + 1));
+        }
+        for (kind, text) in line {
+            let color = kind.ansi();
+            if colored && !color.is_empty() {
+                out.push_str(&format!("{}{}\x1b[0m", color, text));
+            } else {
+                out.push_str(text);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+pub fn display_list(source: &str, font_size: f32) -> (DisplayList, f32) {
+    let metrics = FontMetrics::for_size(font_size);
+    let lines = highlight_lines(source);
+    let width = gutter_width(lines.len());
+    let margin = metrics.char_width;
+    let mut list = DisplayList::default();
+
+    let mut text_item = |x: f32, y: f32, text: String, color: Color| {
+        if text.trim().is_empty() {
+            return;
+        }
+        let rect = Rect::new(x, y, metrics.text_width(&text), metrics.line_height);
+        list.items.push(DisplayItem::Text {
+            rect,
+            text,
+            color,
+            font_size,
+            bold: false,
+            italic: false,
+            font_family: Vec::new(),
+        });
+    };
+
+    for (number, line) in lines.iter().enumerate() {
+        let y = margin + number as f32 * metrics.line_height;
+        text_item(margin, y, format!("{:>width$}", number + 1), GUTTER_COLOR);
+        let mut x = margin + (width + 2) as f32 * metrics.char_width;
+        for (kind, text) in line {
+            let text = text.replace('\t', TAB);
+            let advance = metrics.text_width(&text);
+            text_item(x, y, text, kind.color());
+            x += advance;
+        }
+    }
+    let height = 2.0 * margin + lines.len() as f32 * metrics.line_height;
+    (list, height)
+}
@@ -0,0 +1,272 @@
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+use url::{Origin, Url};
+
+pub const QUOTA: usize = 5 * 1024 * 1024;
+
+const DATA_DIR: &str = "icarus";
+const LOCAL_STORAGE_FILE: &str = "local-storage.json";
+
+pub fn origin_of(url: &Url) -> Option<String> {
+    match url.origin() {
+        origin @ Origin::Tuple(..) => Some(origin.ascii_serialization()),
+        Origin::Opaque(_) if url.scheme() == "file" => Some(String::from("file://")),
+        Origin::Opaque(_) => None,
+    }
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("ICARUS_DATA_DIR") {
+        return Some(PathBuf::from(path));
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share"))
+        })?;
+    Some(data_home.join(DATA_DIR))
+}
+
+fn size_of(text: &str) -> usize {
+    text.encode_utf16().count() * 2
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Storage {
+    items: Vec<(String, String)>,
+    used: usize,
+}
+
+impl Storage {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    pub fn key(&self, index: usize) -> Option<&str> {
+        self.items.get(index).map(|(key, _)| key.as_str())
+    }
+
+    pub fn get_item(&self, key: &str) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.items
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn set_item(&mut self, key: &str, value: &str) -> Result<()> {
+        let position = self.items.iter().position(|(name, _)| name == key);
+        let old = position.map_or(0, |index| {
+            size_of(&self.items[index].0) + size_of(&self.items[index].1)
+        });
+        let used = self.used - old + size_of(key) + size_of(value);
+        if used > QUOTA {
+            bail!(
+                "QuotaExceededError: setting '{}' would use {} of {} bytes",
+                key,
+                used,
+                QUOTA
+            );
+        }
+        match position {
+            Some(index) => self.items[index].1 = value.to_string(),
+            None => self.items.push((key.to_string(), value.to_string())),
+        }
+        self.used = used;
+        Ok(())
+    }
+
+    pub fn remove_item(&mut self, key: &str) -> bool {
+        let Some(index) = self.items.iter().position(|(name, _)| name == key) else {
+            return false;
+        };
+        let (key, value) = self.items.remove(index);
+        self.used -= size_of(&key) + size_of(&value);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.used = 0;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SessionStorage {
+    areas: HashMap<String, Storage>,
+}
+
+impl SessionStorage {
+    pub fn new() -> Self {
+        SessionStorage::default()
+    }
+
+    pub fn area(&mut self, url: &Url) -> Result<&mut Storage> {
+        let origin = origin_of(url)
+            .with_context(|| format!("SecurityError: {} has no storage origin", url))?;
+        Ok(self.areas.entry(origin).or_default())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LocalStorage {
+    path: Option<PathBuf>,
+    areas: HashMap<String, Storage>,
+}
+
+impl LocalStorage {
+    pub fn in_memory() -> Self {
+        LocalStorage::default()
+    }
+
+    pub fn open_default() -> Result<Self> {
+        match data_dir() {
+            Some(dir) => LocalStorage::open(&dir.join(LOCAL_STORAGE_FILE)),
+            None => Ok(LocalStorage::in_memory()),
+        }
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut storage = LocalStorage {
+            path: Some(path.to_path_buf()),
+            areas: HashMap::new(),
+        };
+        if !path.exists() {
+            return Ok(storage);
+        }
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let origins: Map<String, Value> =
+            serde_json::from_str(&text).with_context(|| format!("in {}", path.display()))?;
+        for (origin, items) in origins {
+            let Value::Array(items) = items else {
+                continue;
+            };
+            let mut area = Storage::default();
+            let loaded = items.iter().try_for_each(|item| {
+                match item.as_array().map(Vec::as_slice).unwrap_or_default() {
+                    [Value::String(key), Value::String(value)] => area.set_item(key, value),
+                    _ => Ok(()),
+                }
+            });
+            match loaded {
+                Ok(()) => {
+                    storage.areas.insert(origin, area);
+                }
+                Err(err) => log::warn!("skipping stored items for {}: {:#}", origin, err),
+            }
+        }
+        Ok(storage)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn origins(&self) -> Vec<&str> {
+        let mut origins: Vec<&str> = self
+            .areas
+            .iter()
+            .filter(|(_, area)| !area.is_empty())
+            .map(|(origin, _)| origin.as_str())
+            .collect();
+        origins.sort_unstable();
+        origins
+    }
+
+    pub fn area(&mut self, url: &Url) -> Result<&mut Storage> {
+        let origin = origin_of(url)
+            .with_context(|| format!("SecurityError: {} has no storage origin", url))?;
+        Ok(self.areas.entry(origin).or_default())
+    }
+
+    pub fn get(&self, origin: &str) -> Option<&Storage> {
+        self.areas.get(origin)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut origins = Map::new();
+        for origin in self.origins() {
+            let items = self.areas[origin]
+                .items()
+                .map(|(key, value)| Value::from(vec![key, value]))
+                .collect();
+            origins.insert(origin.to_string(), Value::Array(items));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let temporary = path.with_extension("json.tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(&origins)?)
+            .with_context(|| format!("writing {}", temporary.display()))?;
+        std::fs::rename(&temporary, path).with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+/// The storage a browsing context hands to its documents: local storage
+/// shared by every tab, and session storage that belongs to this tab alone
+/// and outlives its navigations.
+#[derive(Debug, Clone, Default)]
+pub struct StorageContext {
+    pub local: Arc<Mutex<LocalStorage>>,
+    pub session: Arc<Mutex<SessionStorage>>,
+}
+
+impl StorageContext {
+    pub fn new(local: LocalStorage) -> Self {
+        StorageContext {
+            local: Arc::new(Mutex::new(local)),
+            session: Arc::default(),
+        }
+    }
+
+    /// A context for another tab: the same local storage and an empty
+    /// session storage.
+    pub fn new_tab(&self) -> Self {
+        StorageContext {
+            local: Arc::clone(&self.local),
+            session: Arc::default(),
+        }
+    }
+
+    /// Runs `f` on the local storage area for `url`'s origin, saving it if
+    /// `f` changed anything.
+    pub fn local<R>(&self, url: &Url, f: impl FnOnce(&mut Storage) -> R) -> Result<R> {
+        let mut local = self.local.lock().unwrap_or_else(|err| err.into_inner());
+        let area = local.area(url)?;
+        let before = area.clone();
+        let result = f(area);
+        if *area != before {
+            local.save()?;
+        }
+        Ok(result)
+    }
+
+    /// Runs `f` on this tab's session storage area for `url`'s origin.
+    pub fn session<R>(&self, url: &Url, f: impl FnOnce(&mut Storage) -> R) -> Result<R> {
+        let mut session = self.session.lock().unwrap_or_else(|err| err.into_inner());
+        Ok(f(session.area(url)?))
+    }
+}
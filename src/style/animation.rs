@@ -0,0 +1,645 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::string::String;
+use std::time::Instant;
+use std::vec::Vec;
+
+use super::gradient::mix;
+use crate::css::Declaration;
+use crate::css::keyframes::Keyframes;
+use crate::css::parser::split_components;
+use crate::css::selector::split_top_level;
+use crate::css::values::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepPosition {
+    Start,
+    End,
+    None,
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimingFunction {
+    Linear,
+    CubicBezier(f32, f32, f32, f32),
+    Steps(u32, StepPosition),
+}
+
+const EASE: TimingFunction = TimingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0);
+const EASE_IN: TimingFunction = TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0);
+const EASE_OUT: TimingFunction = TimingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0);
+const EASE_IN_OUT: TimingFunction = TimingFunction::CubicBezier(0.42, 0.0, 0.58, 1.0);
+
+impl TimingFunction {
+    pub fn at(&self, t: f32) -> f32 {
+        match *self {
+            TimingFunction::Linear => t,
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+                if t <= 0.0 || t >= 1.0 {
+                    return t;
+                }
+                let (mut low, mut high, mut s) = (0.0, 1.0, t);
+                for _ in 0..32 {
+                    let x = bezier(x1, x2, s);
+                    if (x - t).abs() < 1e-6 {
+                        break;
+                    }
+                    if x < t {
+                        low = s;
+                    } else {
+                        high = s;
+                    }
+                    s = (low + high) / 2.0;
+                }
+                bezier(y1, y2, s)
+            }
+            TimingFunction::Steps(steps, position) => {
+                let steps = steps as f32;
+                let jumps = match position {
+                    StepPosition::Start | StepPosition::End => steps,
+                    StepPosition::None => steps - 1.0,
+                    StepPosition::Both => steps + 1.0,
+                }
+                .max(1.0);
+                let mut step = (t * steps).floor();
+                if matches!(position, StepPosition::Start | StepPosition::Both) {
+                    step += 1.0;
+                }
+                if (0.0..=1.0).contains(&t) {
+                    step = step.clamp(0.0, jumps);
+                }
+                step / jumps
+            }
+        }
+    }
+}
+
+fn bezier(p1: f32, p2: f32, t: f32) -> f32 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+}
+
+impl std::fmt::Display for TimingFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            TimingFunction::Linear => write!(f, "linear"),
+            EASE => write!(f, "ease"),
+            EASE_IN => write!(f, "ease-in"),
+            EASE_OUT => write!(f, "ease-out"),
+            EASE_IN_OUT => write!(f, "ease-in-out"),
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => {
+                write!(f, "cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2)
+            }
+            TimingFunction::Steps(steps, position) => {
+                let position = match position {
+                    StepPosition::Start => "jump-start",
+                    StepPosition::End => "jump-end",
+                    StepPosition::None => "jump-none",
+                    StepPosition::Both => "jump-both",
+                };
+                write!(f, "steps({}, {})", steps, position)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IterationCount {
+    Count(f32),
+    Infinite,
+}
+
+impl std::fmt::Display for IterationCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IterationCount::Count(count) => write!(f, "{}", count),
+            IterationCount::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationDirection {
+    Normal,
+    Reverse,
+    Alternate,
+    AlternateReverse,
+}
+
+impl std::fmt::Display for AnimationDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AnimationDirection::Normal => write!(f, "normal"),
+            AnimationDirection::Reverse => write!(f, "reverse"),
+            AnimationDirection::Alternate => write!(f, "alternate"),
+            AnimationDirection::AlternateReverse => write!(f, "alternate-reverse"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillMode {
+    None,
+    Forwards,
+    Backwards,
+    Both,
+}
+
+impl std::fmt::Display for FillMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FillMode::None => write!(f, "none"),
+            FillMode::Forwards => write!(f, "forwards"),
+            FillMode::Backwards => write!(f, "backwards"),
+            FillMode::Both => write!(f, "both"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayState {
+    Running,
+    Paused,
+}
+
+impl std::fmt::Display for PlayState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlayState::Running => write!(f, "running"),
+            PlayState::Paused => write!(f, "paused"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub name: String,
+    pub duration: f32,
+    pub timing_function: TimingFunction,
+    pub delay: f32,
+    pub iteration_count: IterationCount,
+    pub direction: AnimationDirection,
+    pub fill_mode: FillMode,
+    pub play_state: PlayState,
+}
+
+impl Animation {
+    fn iterations(&self) -> f32 {
+        match self.iteration_count {
+            IterationCount::Count(count) => count,
+            IterationCount::Infinite => f32::INFINITY,
+        }
+    }
+
+    fn active_duration(&self) -> f32 {
+        if self.duration > 0.0 {
+            self.duration * self.iterations()
+        } else {
+            0.0
+        }
+    }
+
+    pub fn end(&self) -> f32 {
+        self.delay + self.active_duration()
+    }
+
+    pub fn progress(&self, elapsed: f32) -> Option<f32> {
+        let count = self.iterations();
+        let local = elapsed - self.delay;
+        let (iteration, progress) = if local < 0.0 {
+            if !matches!(self.fill_mode, FillMode::Backwards | FillMode::Both) {
+                return None;
+            }
+            (0.0, 0.0)
+        } else if local >= self.active_duration() {
+            if !matches!(self.fill_mode, FillMode::Forwards | FillMode::Both) {
+                return None;
+            }
+            if count == 0.0 {
+                (0.0, 0.0)
+            } else if count.is_infinite() || count.fract() == 0.0 {
+                (count - 1.0, 1.0)
+            } else {
+                (count.floor(), count.fract())
+            }
+        } else {
+            let overall = local / self.duration;
+            (overall.floor(), overall.fract())
+        };
+        let odd = iteration % 2.0 == 1.0;
+        let reversed = match self.direction {
+            AnimationDirection::Normal => false,
+            AnimationDirection::Reverse => true,
+            AnimationDirection::Alternate => odd,
+            AnimationDirection::AlternateReverse => !odd,
+        };
+        Some(if reversed { 1.0 - progress } else { progress })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationList {
+    pub names: Vec<String>,
+    pub durations: Vec<f32>,
+    pub timing_functions: Vec<TimingFunction>,
+    pub delays: Vec<f32>,
+    pub iteration_counts: Vec<IterationCount>,
+    pub directions: Vec<AnimationDirection>,
+    pub fill_modes: Vec<FillMode>,
+    pub play_states: Vec<PlayState>,
+}
+
+impl Default for AnimationList {
+    fn default() -> Self {
+        AnimationList {
+            names: vec![String::from("none")],
+            durations: vec![0.0],
+            timing_functions: vec![EASE],
+            delays: vec![0.0],
+            iteration_counts: vec![IterationCount::Count(1.0)],
+            directions: vec![AnimationDirection::Normal],
+            fill_modes: vec![FillMode::None],
+            play_states: vec![PlayState::Running],
+        }
+    }
+}
+
+impl AnimationList {
+    pub fn is_none(&self) -> bool {
+        self.names.iter().all(|name| name == "none")
+    }
+
+    pub fn animations(&self) -> Vec<Animation> {
+        fn nth<T: Copy>(values: &[T], index: usize) -> T {
+            values[index % values.len()]
+        }
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| *name != "none")
+            .map(|(index, name)| Animation {
+                name: name.clone(),
+                duration: nth(&self.durations, index),
+                timing_function: nth(&self.timing_functions, index),
+                delay: nth(&self.delays, index),
+                iteration_count: nth(&self.iteration_counts, index),
+                direction: nth(&self.directions, index),
+                fill_mode: nth(&self.fill_modes, index),
+                play_state: nth(&self.play_states, index),
+            })
+            .collect()
+    }
+
+    pub fn apply(&mut self, name: &str, value: &str) {
+        let lower = value.to_ascii_lowercase();
+        match name {
+            "animation-name" => set(&mut self.names, parse_list(value, parse_name)),
+            "animation-duration" => set(&mut self.durations, parse_list(&lower, parse_duration)),
+            "animation-timing-function" => set(
+                &mut self.timing_functions,
+                parse_list(&lower, parse_timing_function),
+            ),
+            "animation-delay" => set(&mut self.delays, parse_list(&lower, parse_time)),
+            "animation-iteration-count" => set(
+                &mut self.iteration_counts,
+                parse_list(&lower, parse_iteration_count),
+            ),
+            "animation-direction" => set(
+                &mut self.directions,
+                parse_list(&lower, |value| match value {
+                    "normal" => Some(AnimationDirection::Normal),
+                    "reverse" => Some(AnimationDirection::Reverse),
+                    "alternate" => Some(AnimationDirection::Alternate),
+                    "alternate-reverse" => Some(AnimationDirection::AlternateReverse),
+                    _ => None,
+                }),
+            ),
+            "animation-fill-mode" => set(
+                &mut self.fill_modes,
+                parse_list(&lower, |value| match value {
+                    "none" => Some(FillMode::None),
+                    "forwards" => Some(FillMode::Forwards),
+                    "backwards" => Some(FillMode::Backwards),
+                    "both" => Some(FillMode::Both),
+                    _ => None,
+                }),
+            ),
+            "animation-play-state" => set(
+                &mut self.play_states,
+                parse_list(&lower, |value| match value {
+                    "running" => Some(PlayState::Running),
+                    "paused" => Some(PlayState::Paused),
+                    _ => None,
+                }),
+            ),
+            _ => {}
+        }
+    }
+
+    pub fn copy_from(&mut self, source: &AnimationList, name: &str) {
+        match name {
+            "animation-name" => self.names = source.names.clone(),
+            "animation-duration" => self.durations = source.durations.clone(),
+            "animation-timing-function" => self.timing_functions = source.timing_functions.clone(),
+            "animation-delay" => self.delays = source.delays.clone(),
+            "animation-iteration-count" => self.iteration_counts = source.iteration_counts.clone(),
+            "animation-direction" => self.directions = source.directions.clone(),
+            "animation-fill-mode" => self.fill_modes = source.fill_modes.clone(),
+            "animation-play-state" => self.play_states = source.play_states.clone(),
+            _ => {}
+        }
+    }
+
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        let seconds = |times: &[f32]| {
+            times
+                .iter()
+                .map(|time| format!("{}s", time))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        vec![
+            ("animation-name", self.names.join(", ")),
+            ("animation-duration", seconds(&self.durations)),
+            ("animation-timing-function", join(&self.timing_functions)),
+            ("animation-delay", seconds(&self.delays)),
+            ("animation-iteration-count", join(&self.iteration_counts)),
+            ("animation-direction", join(&self.directions)),
+            ("animation-fill-mode", join(&self.fill_modes)),
+            ("animation-play-state", join(&self.play_states)),
+        ]
+    }
+}
+
+fn join<T: std::fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn set<T>(slot: &mut Vec<T>, list: Option<Vec<T>>) {
+    if let Some(list) = list {
+        *slot = list;
+    }
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    split_top_level(value, ',')
+        .iter()
+        .map(|item| parse(item.trim()))
+        .collect()
+}
+
+fn parse_name(value: &str) -> Option<String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Some(String::from("none"));
+    }
+    crate::css::keyframes::parse_name(value)
+}
+
+pub fn parse_time(value: &str) -> Option<f32> {
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms.parse::<f32>().ok().map(|ms| ms / 1000.0);
+    }
+    value.strip_suffix('s')?.parse().ok()
+}
+
+fn parse_duration(value: &str) -> Option<f32> {
+    parse_time(value).filter(|duration| *duration >= 0.0)
+}
+
+fn parse_iteration_count(value: &str) -> Option<IterationCount> {
+    if value == "infinite" {
+        return Some(IterationCount::Infinite);
+    }
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|count| *count >= 0.0)
+        .map(IterationCount::Count)
+}
+
+pub fn parse_timing_function(value: &str) -> Option<TimingFunction> {
+    match value {
+        "linear" => return Some(TimingFunction::Linear),
+        "ease" => return Some(EASE),
+        "ease-in" => return Some(EASE_IN),
+        "ease-out" => return Some(EASE_OUT),
+        "ease-in-out" => return Some(EASE_IN_OUT),
+        "step-start" => return Some(TimingFunction::Steps(1, StepPosition::Start)),
+        "step-end" => return Some(TimingFunction::Steps(1, StepPosition::End)),
+        _ => {}
+    }
+    let open = value.find('(')?;
+    let arguments: Vec<String> = split_top_level(value[open + 1..].strip_suffix(')')?, ',')
+        .iter()
+        .map(|argument| argument.trim().to_string())
+        .collect();
+    match &value[..open] {
+        "cubic-bezier" => {
+            let numbers: Vec<f32> = arguments
+                .iter()
+                .map(|argument| argument.parse().ok())
+                .collect::<Option<_>>()?;
+            let [x1, y1, x2, y2] = numbers[..] else {
+                return None;
+            };
+            ((0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2))
+                .then_some(TimingFunction::CubicBezier(x1, y1, x2, y2))
+        }
+        "steps" => {
+            let steps: u32 = arguments.first()?.parse().ok().filter(|steps| *steps > 0)?;
+            let position = match arguments.get(1).map(String::as_str) {
+                None | Some("end" | "jump-end") => StepPosition::End,
+                Some("start" | "jump-start") => StepPosition::Start,
+                Some("jump-none") if steps > 1 => StepPosition::None,
+                Some("jump-both") => StepPosition::Both,
+                _ => return None,
+            };
+            (arguments.len() <= 2).then_some(TimingFunction::Steps(steps, position))
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationState {
+    pub name: String,
+    pub start: f64,
+    pub paused_at: Option<f64>,
+    pub finished: bool,
+}
+
+impl AnimationState {
+    pub fn new(name: &str, now: f64) -> Self {
+        AnimationState {
+            name: name.to_string(),
+            start: now,
+            paused_at: None,
+            finished: false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.finished && self.paused_at.is_none()
+    }
+
+    pub fn update(&mut self, animation: &Animation, now: f64) -> f32 {
+        match (animation.play_state, self.paused_at) {
+            (PlayState::Paused, None) => self.paused_at = Some(now),
+            (PlayState::Running, Some(paused)) => {
+                self.start += now - paused;
+                self.paused_at = None;
+            }
+            _ => {}
+        }
+        let elapsed = (self.paused_at.unwrap_or(now) - self.start) as f32;
+        self.finished = elapsed >= animation.end();
+        elapsed
+    }
+}
+
+#[derive(Debug)]
+pub struct Timeline {
+    origin: Instant,
+    current: Cell<f64>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Timeline {
+            origin: Instant::now(),
+            current: Cell::new(0.0),
+        }
+    }
+}
+
+impl Timeline {
+    pub fn elapsed(&self) -> f64 {
+        self.origin.elapsed().as_secs_f64()
+    }
+
+    pub fn current_time(&self) -> f64 {
+        self.current.get()
+    }
+
+    pub fn set_current_time(&self, time: f64) {
+        self.current.set(time);
+    }
+}
+
+pub fn sample(
+    keyframes: &Keyframes,
+    timing: TimingFunction,
+    progress: f32,
+    underlying: &BTreeMap<String, String>,
+) -> Vec<Declaration> {
+    let mut names: Vec<&str> = Vec::new();
+    for frame in &keyframes.frames {
+        for declaration in &frame.declarations {
+            let name = declaration.name.as_str();
+            if !name.starts_with("animation") && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let mut stops: Vec<(f32, &str, TimingFunction)> = keyframes
+                .frames
+                .iter()
+                .filter_map(|frame| {
+                    let find = |name: &str| {
+                        frame
+                            .declarations
+                            .iter()
+                            .rev()
+                            .find(|declaration| declaration.name == name)
+                    };
+                    let value = find(name)?;
+                    let timing = find("animation-timing-function")
+                        .and_then(|declaration| {
+                            parse_timing_function(&declaration.value.to_ascii_lowercase())
+                        })
+                        .unwrap_or(timing);
+                    Some((frame.offset, value.value.as_str(), timing))
+                })
+                .collect();
+            if let Some(base) = underlying.get(name) {
+                if stops.first().is_some_and(|stop| stop.0 > 0.0) {
+                    stops.insert(0, (0.0, base, timing));
+                }
+                if stops.last().is_some_and(|stop| stop.0 < 1.0) {
+                    stops.push((1.0, base, timing));
+                }
+            }
+            let next = stops
+                .iter()
+                .position(|stop| stop.0 > progress)
+                .unwrap_or(stops.len());
+            let value = match (
+                next.checked_sub(1).map(|index| stops[index]),
+                stops.get(next).copied(),
+            ) {
+                (Some((offset, from, timing)), Some((end, to, _))) => {
+                    let local = (progress - offset) / (end - offset);
+                    interpolate(from, to, timing.at(local))
+                }
+                (Some((_, value, _)), None) | (None, Some((_, value, _))) => value.to_string(),
+                (None, None) => return None,
+            };
+            Some(Declaration {
+                name: name.to_string(),
+                value,
+                important: false,
+            })
+        })
+        .collect()
+}
+
+pub fn interpolate(from: &str, to: &str, t: f32) -> String {
+    let discrete = || if t < 0.5 { from } else { to }.to_string();
+    let (from_parts, to_parts) = (split_components(from), split_components(to));
+    if from_parts.len() != to_parts.len() {
+        return discrete();
+    }
+    let parts: Option<Vec<String>> = from_parts
+        .iter()
+        .zip(&to_parts)
+        .map(|(from, to)| interpolate_component(from, to, t))
+        .collect();
+    match parts {
+        Some(parts) => parts.join(" "),
+        None => discrete(),
+    }
+}
+
+fn interpolate_component(from: &str, to: &str, t: f32) -> Option<String> {
+    if from.eq_ignore_ascii_case(to) {
+        return Some(from.to_string());
+    }
+    if let (Some(from), Some(to)) = (Color::parse(from), Color::parse(to)) {
+        return Some(mix(from, to, t).to_hex());
+    }
+    let (from, from_unit) = split_number(from)?;
+    let (to, to_unit) = split_number(to)?;
+    let unit = match (from_unit, to_unit) {
+        (from_unit, to_unit) if from_unit.eq_ignore_ascii_case(to_unit) => from_unit,
+        ("", unit) if from == 0.0 => unit,
+        (unit, "") if to == 0.0 => unit,
+        _ => return None,
+    };
+    Some(format!("{}{}", from + (to - from) * t, unit))
+}
+
+fn split_number(value: &str) -> Option<(f32, &str)> {
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .unwrap_or(value.len());
+    Some((value[..end].parse().ok()?, &value[end..]))
+}
@@ -0,0 +1,515 @@
+use html5ever::{LocalName, ns};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use super::{ComputedStyle, Display, Stylist};
+use crate::css::selector::{PseudoElement, split_top_level};
+use crate::dom::{Node, NodeData, QualName};
+use crate::layout;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterStyle {
+    Decimal,
+    DecimalLeadingZero,
+    LowerRoman,
+    UpperRoman,
+    LowerAlpha,
+    UpperAlpha,
+    LowerGreek,
+    Disc,
+    Circle,
+    Square,
+    None,
+}
+
+impl CounterStyle {
+    pub fn parse(value: &str) -> Option<CounterStyle> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "decimal" => Some(CounterStyle::Decimal),
+            "decimal-leading-zero" => Some(CounterStyle::DecimalLeadingZero),
+            "lower-roman" => Some(CounterStyle::LowerRoman),
+            "upper-roman" => Some(CounterStyle::UpperRoman),
+            "lower-alpha" | "lower-latin" => Some(CounterStyle::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Some(CounterStyle::UpperAlpha),
+            "lower-greek" => Some(CounterStyle::LowerGreek),
+            "disc" => Some(CounterStyle::Disc),
+            "circle" => Some(CounterStyle::Circle),
+            "square" => Some(CounterStyle::Square),
+            "none" => Some(CounterStyle::None),
+            _ => None,
+        }
+    }
+
+    pub fn format(&self, value: i32) -> String {
+        match self {
+            CounterStyle::Decimal => value.to_string(),
+            CounterStyle::DecimalLeadingZero if (0..10).contains(&value) => format!("0{}", value),
+            CounterStyle::DecimalLeadingZero => value.to_string(),
+            CounterStyle::LowerRoman => {
+                roman(value).map_or(value.to_string(), |roman| roman.to_ascii_lowercase())
+            }
+            CounterStyle::UpperRoman => roman(value).unwrap_or(value.to_string()),
+            CounterStyle::LowerAlpha => alphabetic(value, 'a', 26),
+            CounterStyle::UpperAlpha => alphabetic(value, 'A', 26),
+            CounterStyle::LowerGreek => alphabetic(value, '\u{3b1}', 24),
+            CounterStyle::Disc => String::from("\u{2022}"),
+            CounterStyle::Circle => String::from("\u{25e6}"),
+            CounterStyle::Square => String::from("\u{25aa}"),
+            CounterStyle::None => String::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for CounterStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            CounterStyle::Decimal => "decimal",
+            CounterStyle::DecimalLeadingZero => "decimal-leading-zero",
+            CounterStyle::LowerRoman => "lower-roman",
+            CounterStyle::UpperRoman => "upper-roman",
+            CounterStyle::LowerAlpha => "lower-alpha",
+            CounterStyle::UpperAlpha => "upper-alpha",
+            CounterStyle::LowerGreek => "lower-greek",
+            CounterStyle::Disc => "disc",
+            CounterStyle::Circle => "circle",
+            CounterStyle::Square => "square",
+            CounterStyle::None => "none",
+        };
+        f.write_str(name)
+    }
+}
+
+fn roman(value: i32) -> Option<String> {
+    const NUMERALS: [(i32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    if !(1..4000).contains(&value) {
+        return None;
+    }
+    let mut rest = value;
+    let mut out = String::new();
+    for (amount, numeral) in NUMERALS {
+        while rest >= amount {
+            out.push_str(numeral);
+            rest -= amount;
+        }
+    }
+    Some(out)
+}
+
+fn alphabetic(value: i32, first: char, letters: u32) -> String {
+    if value < 1 {
+        return value.to_string();
+    }
+    let mut rest = value as u32;
+    let mut out = Vec::new();
+    while rest > 0 {
+        rest -= 1;
+        out.push(char::from_u32(first as u32 + rest % letters).unwrap_or(first));
+        rest /= letters;
+    }
+    out.iter().rev().collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentItem {
+    String(String),
+    Counter {
+        name: String,
+        style: CounterStyle,
+    },
+    Counters {
+        name: String,
+        separator: String,
+        style: CounterStyle,
+    },
+    Attr(String),
+}
+
+impl std::fmt::Display for ContentItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ContentItem::String(text) => write!(f, "{:?}", text),
+            ContentItem::Counter { name, style } => write!(f, "counter({}, {})", name, style),
+            ContentItem::Counters {
+                name,
+                separator,
+                style,
+            } => write!(f, "counters({}, {:?}, {})", name, separator, style),
+            ContentItem::Attr(name) => write!(f, "attr({})", name),
+        }
+    }
+}
+
+pub fn parse_content(value: &str) -> Option<Vec<ContentItem>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") || value.eq_ignore_ascii_case("normal") {
+        return Some(Vec::new());
+    }
+
+    let mut items = Vec::new();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if rest.starts_with(['"', '\'']) {
+            let (text, remaining) = parse_string(rest)?;
+            items.push(ContentItem::String(text));
+            rest = remaining;
+            continue;
+        }
+
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '(' || c == '"' || c == '\'')
+            .unwrap_or(rest.len());
+        let name = rest[..end].to_ascii_lowercase();
+        rest = &rest[end..];
+        if !rest.starts_with('(') {
+            match name.as_str() {
+                "open-quote" => items.push(ContentItem::String(String::from("\u{201c}"))),
+                "close-quote" => items.push(ContentItem::String(String::from("\u{201d}"))),
+                "no-open-quote" | "no-close-quote" => {}
+                _ => return None,
+            }
+            continue;
+        }
+
+        let close = closing_paren(rest)?;
+        let arguments: Vec<String> = split_top_level(&rest[1..close], ',')
+            .iter()
+            .map(|argument| argument.trim().to_string())
+            .collect();
+        rest = &rest[close + 1..];
+        let style = |index: usize| match arguments.get(index) {
+            Some(style) => CounterStyle::parse(style),
+            None => Some(CounterStyle::Decimal),
+        };
+        items.push(match (name.as_str(), arguments.as_slice()) {
+            ("counter", [name, ..]) if arguments.len() <= 2 && is_ident(name) => {
+                ContentItem::Counter {
+                    name: name.clone(),
+                    style: style(1)?,
+                }
+            }
+            ("counters", [name, separator, ..]) if arguments.len() <= 3 && is_ident(name) => {
+                ContentItem::Counters {
+                    name: name.clone(),
+                    separator: parse_string(separator)
+                        .filter(|(_, rest)| rest.trim().is_empty())?
+                        .0,
+                    style: style(2)?,
+                }
+            }
+            ("attr", [name]) if is_ident(name) => ContentItem::Attr(name.to_ascii_lowercase()),
+            _ => return None,
+        });
+    }
+    Some(items)
+}
+
+fn parse_string(input: &str) -> Option<(String, &str)> {
+    let mut chars = input.char_indices().peekable();
+    let (_, quote) = chars.next()?;
+    let mut text = String::new();
+    while let Some((index, c)) = chars.next() {
+        if c == quote {
+            return Some((text, &input[index + c.len_utf8()..]));
+        }
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        let mut hex = String::new();
+        while hex.len() < 6 {
+            match chars.next_if(|(_, c)| c.is_ascii_hexdigit()) {
+                Some((_, digit)) => hex.push(digit),
+                None => break,
+            }
+        }
+        if hex.is_empty() {
+            match chars.next()? {
+                (_, '\n') => {}
+                (_, escaped) => text.push(escaped),
+            }
+            continue;
+        }
+        chars.next_if(|(_, c)| c.is_whitespace());
+        let code = u32::from_str_radix(&hex, 16).ok()?;
+        text.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+    }
+    None
+}
+
+fn closing_paren(input: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (index, c) in input.char_indices() {
+        match quote {
+            _ if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == ')' => return Some(index),
+            None => {}
+        }
+    }
+    None
+}
+
+fn is_ident(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with(|c: char| c.is_ascii_digit())
+        && value
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii())
+}
+
+pub fn parse_counter_list(value: &str, default: i32) -> Option<Vec<(String, i32)>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") {
+        return Some(Vec::new());
+    }
+    let mut counters: Vec<(String, i32)> = Vec::new();
+    for token in value.split_whitespace() {
+        if let Ok(amount) = token.parse::<i32>() {
+            counters.last_mut()?.1 = amount;
+        } else if is_ident(token) && !token.eq_ignore_ascii_case("none") {
+            counters.push((token.to_string(), default));
+        } else {
+            return None;
+        }
+    }
+    Some(counters)
+}
+
+pub fn format_counter_list(counters: &[(String, i32)]) -> String {
+    if counters.is_empty() {
+        return String::from("none");
+    }
+    counters
+        .iter()
+        .map(|(name, value)| format!("{} {}", name, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct Counter {
+    name: String,
+    value: i32,
+    level: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    stack: Vec<Counter>,
+}
+
+impl Counters {
+    fn innermost(&mut self, name: &str) -> Option<&mut Counter> {
+        self.stack
+            .iter_mut()
+            .rev()
+            .find(|counter| counter.name == name)
+    }
+
+    fn instantiate(&mut self, name: &str, value: i32, level: usize) {
+        self.stack.push(Counter {
+            name: name.to_string(),
+            value,
+            level,
+        });
+    }
+
+    fn apply(&mut self, style: &ComputedStyle, level: usize) {
+        for (name, value) in &style.counter_reset {
+            match self.innermost(name) {
+                Some(counter) if counter.level == level => counter.value = *value,
+                _ => self.instantiate(name, *value, level),
+            }
+        }
+        for (name, amount) in &style.counter_increment {
+            match self.innermost(name) {
+                Some(counter) => counter.value = counter.value.saturating_add(*amount),
+                None => self.instantiate(name, *amount, level),
+            }
+        }
+        for (name, value) in &style.counter_set {
+            match self.innermost(name) {
+                Some(counter) => counter.value = *value,
+                None => self.instantiate(name, *value, level),
+            }
+        }
+    }
+
+    fn values(&mut self, name: &str, level: usize) -> Vec<i32> {
+        if self.innermost(name).is_none() {
+            self.instantiate(name, 0, level);
+        }
+        self.stack
+            .iter()
+            .filter(|counter| counter.name == name)
+            .map(|counter| counter.value)
+            .collect()
+    }
+
+    fn close(&mut self, level: usize) {
+        self.stack.retain(|counter| counter.level <= level);
+    }
+
+    fn resolve(&mut self, content: &[ContentItem], element: &Rc<Node>, level: usize) -> String {
+        let mut text = String::new();
+        for item in content {
+            match item {
+                ContentItem::String(value) => text.push_str(value),
+                ContentItem::Counter { name, style } => {
+                    let value = self.values(name, level).last().copied().unwrap_or(0);
+                    text.push_str(&style.format(value));
+                }
+                ContentItem::Counters {
+                    name,
+                    separator,
+                    style,
+                } => {
+                    let values: Vec<String> = self
+                        .values(name, level)
+                        .into_iter()
+                        .map(|value| style.format(value))
+                        .collect();
+                    text.push_str(&values.join(separator));
+                }
+                ContentItem::Attr(name) => {
+                    text.push_str(&element.get_attribute(name).unwrap_or_default());
+                }
+            }
+        }
+        text
+    }
+}
+
+pub fn generate_content(root: &Rc<Node>, stylist: &Stylist) {
+    let mut counters = Counters::default();
+    visit(root, 0, stylist, &mut counters);
+}
+
+fn visit(node: &Rc<Node>, level: usize, stylist: &Stylist, counters: &mut Counters) {
+    let style = match node.data {
+        NodeData::Element { .. } => node.style.borrow().clone(),
+        NodeData::Document { .. } | NodeData::DocumentFragment => None,
+        _ => return,
+    };
+    if let Some(style) = &style {
+        if style.display == Display::None {
+            update(node, PseudoElement::Before, None);
+            update(node, PseudoElement::After, None);
+            return;
+        }
+        counters.apply(style, level);
+    }
+
+    let before = style.as_deref().and_then(|style| {
+        generate(
+            node,
+            PseudoElement::Before,
+            style,
+            level + 1,
+            stylist,
+            counters,
+        )
+    });
+    update(node, PseudoElement::Before, before);
+    let children = node.children.borrow().clone();
+    for child in &children {
+        visit(child, level + 1, stylist, counters);
+    }
+    let after = style.as_deref().and_then(|style| {
+        generate(
+            node,
+            PseudoElement::After,
+            style,
+            level + 1,
+            stylist,
+            counters,
+        )
+    });
+    update(node, PseudoElement::After, after);
+    counters.close(level);
+}
+
+fn generate(
+    element: &Rc<Node>,
+    pseudo: PseudoElement,
+    parent: &ComputedStyle,
+    level: usize,
+    stylist: &Stylist,
+    counters: &mut Counters,
+) -> Option<(ComputedStyle, String)> {
+    let style = stylist.compute_pseudo(element, pseudo, parent)?;
+    if style.content.is_empty() || style.display == Display::None {
+        return None;
+    }
+    counters.apply(&style, level);
+    let text = counters.resolve(&style.content, element, level);
+    Some((style, text))
+}
+
+fn update(element: &Rc<Node>, pseudo: PseudoElement, generated: Option<(ComputedStyle, String)>) {
+    let slot = match pseudo {
+        PseudoElement::Before => &element.pseudo_before,
+        PseudoElement::After => &element.pseudo_after,
+    };
+    let current = slot.borrow().as_ref().map(|pseudo| {
+        (
+            pseudo.style.borrow().as_deref().cloned(),
+            pseudo.get_text_content(),
+        )
+    });
+    let unchanged = match (&current, &generated) {
+        (None, None) => true,
+        (Some((style, text)), Some((new_style, new_text))) => {
+            style.as_ref() == Some(new_style) && text == new_text
+        }
+        _ => false,
+    };
+    if unchanged {
+        return;
+    }
+    *slot.borrow_mut() = generated.map(|(style, text)| pseudo_node(element, pseudo, style, text));
+    layout::mark_dirty(element);
+}
+
+fn pseudo_node(
+    element: &Rc<Node>,
+    pseudo: PseudoElement,
+    style: ComputedStyle,
+    text: String,
+) -> Rc<Node> {
+    let node = Node::new(NodeData::Element {
+        name: QualName::new(None, ns!(html), LocalName::from(pseudo.name())),
+        attrs: RefCell::new(Vec::new()),
+    });
+    *node.parent.borrow_mut() = Rc::downgrade(element);
+    *node.style.borrow_mut() = Some(Rc::new(style));
+    let text = Node::new(NodeData::Text {
+        contents: RefCell::new(text),
+    });
+    *text.parent.borrow_mut() = Rc::downgrade(&node);
+    node.children.borrow_mut().push(text);
+    node
+}
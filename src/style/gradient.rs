@@ -0,0 +1,487 @@
+use std::string::String;
+use std::vec::Vec;
+
+use super::{Dimension, parse_color, resolve_length};
+use crate::css::selector::split_top_level;
+use crate::css::values::Color;
+use crate::layout::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinearDirection {
+    Angle(f32),
+    Corner { right: bool, bottom: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RadialExtent {
+    ClosestSide,
+    ClosestCorner,
+    FarthestSide,
+    FarthestCorner,
+    Explicit(Dimension, Dimension),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientShape {
+    Linear(LinearDirection),
+    Radial {
+        circle: bool,
+        extent: RadialExtent,
+        center: (Dimension, Dimension),
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub color: Color,
+    pub position: Option<Dimension>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: Vec<ColorStop>,
+    pub repeating: bool,
+}
+
+pub struct GradientSampler {
+    geometry: Geometry,
+    stops: Vec<(f32, Color)>,
+    repeating: bool,
+}
+
+enum Geometry {
+    Linear {
+        center: (f32, f32),
+        direction: (f32, f32),
+        length: f32,
+    },
+    Radial {
+        center: (f32, f32),
+        radius: (f32, f32),
+    },
+}
+
+impl Gradient {
+    pub fn parse(value: &str, font_size: f32, current: Color) -> Option<Gradient> {
+        let open = value.find('(')?;
+        let inner = value[open + 1..].trim_end().strip_suffix(')')?;
+        let (repeating, name) = match value[..open].trim().strip_prefix("repeating-") {
+            Some(name) => (true, name),
+            None => (false, value[..open].trim()),
+        };
+        let mut arguments = split_top_level(inner, ',').into_iter().peekable();
+        let first = arguments.peek()?.trim().to_string();
+        let shape = match name {
+            "linear-gradient" => match parse_linear_direction(&first) {
+                Some(direction) => {
+                    arguments.next();
+                    GradientShape::Linear(direction)
+                }
+                None => GradientShape::Linear(LinearDirection::Angle(180.0)),
+            },
+            "radial-gradient" => match parse_radial_shape(&first, font_size) {
+                Some(shape) => {
+                    arguments.next();
+                    shape
+                }
+                None => GradientShape::Radial {
+                    circle: false,
+                    extent: RadialExtent::FarthestCorner,
+                    center: (Dimension::Percent(50.0), Dimension::Percent(50.0)),
+                },
+            },
+            _ => return None,
+        };
+
+        let mut stops = Vec::new();
+        for argument in arguments {
+            let tokens: Vec<String> = split_top_level(argument.trim(), ' ')
+                .into_iter()
+                .filter(|token| !token.is_empty())
+                .collect();
+            let (color, positions) = tokens.split_first()?;
+            let color = parse_color(color, current)?;
+            if positions.len() > 2 {
+                return None;
+            }
+            if positions.is_empty() {
+                stops.push(ColorStop {
+                    color,
+                    position: None,
+                });
+            }
+            for position in positions {
+                let position = match resolve_length(position, font_size)? {
+                    Dimension::Auto => return None,
+                    position => position,
+                };
+                stops.push(ColorStop {
+                    color,
+                    position: Some(position),
+                });
+            }
+        }
+        if stops.len() < 2 {
+            return None;
+        }
+        Some(Gradient {
+            shape,
+            stops,
+            repeating,
+        })
+    }
+
+    pub fn sampler(&self, rect: Rect) -> GradientSampler {
+        let center = |x: Dimension, y: Dimension| {
+            (
+                rect.x + x.resolve_or_zero(rect.width),
+                rect.y + y.resolve_or_zero(rect.height),
+            )
+        };
+        let (geometry, length) = match self.shape {
+            GradientShape::Linear(direction) => {
+                let (dx, dy) = match direction {
+                    LinearDirection::Angle(degrees) => {
+                        let radians = degrees.to_radians();
+                        (radians.sin(), -radians.cos())
+                    }
+                    LinearDirection::Corner { right, bottom } => {
+                        let (dx, dy) = (
+                            if right { rect.height } else { -rect.height },
+                            if bottom { rect.width } else { -rect.width },
+                        );
+                        let norm = dx.hypot(dy).max(f32::EPSILON);
+                        (dx / norm, dy / norm)
+                    }
+                };
+                let length = (rect.width * dx).abs() + (rect.height * dy).abs();
+                let geometry = Geometry::Linear {
+                    center: center(Dimension::Percent(50.0), Dimension::Percent(50.0)),
+                    direction: (dx, dy),
+                    length,
+                };
+                (geometry, length)
+            }
+            GradientShape::Radial {
+                circle,
+                extent,
+                center: (x, y),
+            } => {
+                let center = center(x, y);
+                let radius = radial_radius(rect, center, circle, extent);
+                (Geometry::Radial { center, radius }, radius.0)
+            }
+        };
+        GradientSampler {
+            geometry,
+            stops: resolve_stops(&self.stops, length),
+            repeating: self.repeating,
+        }
+    }
+}
+
+impl GradientSampler {
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        let mut t = match self.geometry {
+            Geometry::Linear {
+                center,
+                direction,
+                length,
+            } => {
+                if length <= 0.0 {
+                    0.0
+                } else {
+                    ((x - center.0) * direction.0 + (y - center.1) * direction.1) / length + 0.5
+                }
+            }
+            Geometry::Radial { center, radius } => {
+                if radius.0 <= 0.0 || radius.1 <= 0.0 {
+                    1.0
+                } else {
+                    ((x - center.0) / radius.0).hypot((y - center.1) / radius.1)
+                }
+            }
+        };
+        let (first, last) = (self.stops[0], self.stops[self.stops.len() - 1]);
+        if self.repeating && last.0 > first.0 {
+            t = first.0 + (t - first.0).rem_euclid(last.0 - first.0);
+        }
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            if t <= end {
+                if end <= start {
+                    return to;
+                }
+                return mix(from, to, (t - start) / (end - start));
+            }
+        }
+        last.1
+    }
+}
+
+fn radial_radius(rect: Rect, center: (f32, f32), circle: bool, extent: RadialExtent) -> (f32, f32) {
+    let horizontal = [(center.0 - rect.x).abs(), (rect.right() - center.0).abs()];
+    let vertical = [(center.1 - rect.y).abs(), (rect.bottom() - center.1).abs()];
+    let (near_x, far_x) = (
+        horizontal[0].min(horizontal[1]),
+        horizontal[0].max(horizontal[1]),
+    );
+    let (near_y, far_y) = (vertical[0].min(vertical[1]), vertical[0].max(vertical[1]));
+    match (extent, circle) {
+        (RadialExtent::Explicit(x, y), _) => (
+            x.resolve_or_zero(rect.width),
+            y.resolve_or_zero(rect.height),
+        ),
+        (RadialExtent::ClosestSide, true) => (near_x.min(near_y), near_x.min(near_y)),
+        (RadialExtent::FarthestSide, true) => (far_x.max(far_y), far_x.max(far_y)),
+        (RadialExtent::ClosestCorner, true) => {
+            let radius = near_x.hypot(near_y);
+            (radius, radius)
+        }
+        (RadialExtent::FarthestCorner, true) => {
+            let radius = far_x.hypot(far_y);
+            (radius, radius)
+        }
+        (RadialExtent::ClosestSide, false) => (near_x, near_y),
+        (RadialExtent::FarthestSide, false) => (far_x, far_y),
+        (RadialExtent::ClosestCorner, false) => (
+            near_x * std::f32::consts::SQRT_2,
+            near_y * std::f32::consts::SQRT_2,
+        ),
+        (RadialExtent::FarthestCorner, false) => (
+            far_x * std::f32::consts::SQRT_2,
+            far_y * std::f32::consts::SQRT_2,
+        ),
+    }
+}
+
+fn resolve_stops(stops: &[ColorStop], length: f32) -> Vec<(f32, Color)> {
+    let last = stops.len() - 1;
+    let mut positions: Vec<Option<f32>> = stops
+        .iter()
+        .enumerate()
+        .map(|(index, stop)| match stop.position {
+            Some(Dimension::Percent(percent)) => Some(percent / 100.0),
+            Some(Dimension::Px(px)) if length > 0.0 => Some(px / length),
+            Some(_) => Some(0.0),
+            None if index == 0 => Some(0.0),
+            None if index == last => Some(1.0),
+            None => None,
+        })
+        .collect();
+
+    let mut highest = f32::NEG_INFINITY;
+    for position in positions.iter_mut().flatten() {
+        highest = highest.max(*position);
+        *position = highest;
+    }
+
+    let mut index = 1;
+    while index < last {
+        if positions[index].is_some() {
+            index += 1;
+            continue;
+        }
+        let start = index - 1;
+        let mut end = index;
+        while positions[end].is_none() {
+            end += 1;
+        }
+        let (from, to) = (
+            positions[start].unwrap_or(0.0),
+            positions[end].unwrap_or(1.0),
+        );
+        for (offset, position) in positions[index..end].iter_mut().enumerate() {
+            let fraction = (offset + 1) as f32 / (end - start) as f32;
+            *position = Some(from + (to - from) * fraction);
+        }
+        index = end;
+    }
+
+    positions
+        .into_iter()
+        .zip(stops)
+        .map(|(position, stop)| (position.unwrap_or(0.0), stop.color))
+        .collect()
+}
+
+pub(super) fn mix(from: Color, to: Color, t: f32) -> Color {
+    let (fa, ta) = (from.a as f32 / 255.0, to.a as f32 / 255.0);
+    let alpha = fa + (ta - fa) * t;
+    if alpha <= 0.0 {
+        return Color::TRANSPARENT;
+    }
+    let channel = |from: u8, to: u8| {
+        let premultiplied = from as f32 * fa + (to as f32 * ta - from as f32 * fa) * t;
+        (premultiplied / alpha).round().clamp(0.0, 255.0) as u8
+    };
+    Color::rgba(
+        channel(from.r, to.r),
+        channel(from.g, to.g),
+        channel(from.b, to.b),
+        (alpha * 255.0).round() as u8,
+    )
+}
+
+fn parse_angle(value: &str) -> Option<f32> {
+    if value == "0" {
+        return Some(0.0);
+    }
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    units.iter().find_map(|(unit, scale)| {
+        let number: f32 = value.strip_suffix(unit)?.parse().ok()?;
+        Some(number * scale)
+    })
+}
+
+fn parse_linear_direction(value: &str) -> Option<LinearDirection> {
+    if let Some(angle) = parse_angle(value) {
+        return Some(LinearDirection::Angle(angle));
+    }
+    let sides: Vec<&str> = value.strip_prefix("to ")?.split_whitespace().collect();
+    let horizontal = sides.iter().find(|side| matches!(**side, "left" | "right"));
+    let vertical = sides.iter().find(|side| matches!(**side, "top" | "bottom"));
+    if sides.is_empty()
+        || sides.len() != horizontal.is_some() as usize + vertical.is_some() as usize
+    {
+        return None;
+    }
+    let direction = match (horizontal.copied(), vertical.copied()) {
+        (Some(x), Some(y)) => LinearDirection::Corner {
+            right: x == "right",
+            bottom: y == "bottom",
+        },
+        (Some("left"), None) => LinearDirection::Angle(270.0),
+        (Some(_), None) => LinearDirection::Angle(90.0),
+        (None, Some("top")) => LinearDirection::Angle(0.0),
+        _ => LinearDirection::Angle(180.0),
+    };
+    Some(direction)
+}
+
+fn parse_radial_shape(value: &str, font_size: f32) -> Option<GradientShape> {
+    let (shape, position) = match value.split_once("at ") {
+        Some((shape, position)) => (shape.trim(), Some(position.trim())),
+        None => (value.trim(), None),
+    };
+    let mut circle = None;
+    let mut extent = None;
+    let mut lengths = Vec::new();
+    for token in shape.split_whitespace() {
+        match token {
+            "circle" => circle = Some(true),
+            "ellipse" => circle = Some(false),
+            "closest-side" => extent = Some(RadialExtent::ClosestSide),
+            "closest-corner" => extent = Some(RadialExtent::ClosestCorner),
+            "farthest-side" => extent = Some(RadialExtent::FarthestSide),
+            "farthest-corner" => extent = Some(RadialExtent::FarthestCorner),
+            _ => match resolve_length(token, font_size)? {
+                Dimension::Auto => return None,
+                length => lengths.push(length),
+            },
+        }
+    }
+    match lengths.as_slice() {
+        [] => {}
+        [radius] if circle != Some(false) && !matches!(radius, Dimension::Percent(_)) => {
+            circle = Some(true);
+            extent = Some(RadialExtent::Explicit(*radius, *radius));
+        }
+        [x, y] if circle != Some(true) => {
+            circle = Some(false);
+            extent = Some(RadialExtent::Explicit(*x, *y));
+        }
+        _ => return None,
+    }
+    let center = match position {
+        Some(position) => parse_position(position, font_size)?,
+        None if circle.is_none() && extent.is_none() => return None,
+        None => (Dimension::Percent(50.0), Dimension::Percent(50.0)),
+    };
+    Some(GradientShape::Radial {
+        circle: circle.unwrap_or(false),
+        extent: extent.unwrap_or(RadialExtent::FarthestCorner),
+        center,
+    })
+}
+
+fn parse_position(value: &str, font_size: f32) -> Option<(Dimension, Dimension)> {
+    let keyword = |token: &str| match token {
+        "left" | "top" => Some(Dimension::Percent(0.0)),
+        "center" => Some(Dimension::Percent(50.0)),
+        "right" | "bottom" => Some(Dimension::Percent(100.0)),
+        _ => None,
+    };
+    let component = |token: &str| match keyword(token) {
+        Some(keyword) => Some(keyword),
+        None => match resolve_length(token, font_size)? {
+            Dimension::Auto => None,
+            length => Some(length),
+        },
+    };
+    let tokens: Vec<&str> = value.split_whitespace().collect();
+    let center = Dimension::Percent(50.0);
+    match tokens.as_slice() {
+        [single] if matches!(*single, "top" | "bottom") => Some((center, component(single)?)),
+        [single] => Some((component(single)?, center)),
+        [first, second]
+            if matches!(*first, "top" | "bottom") || matches!(*second, "left" | "right") =>
+        {
+            Some((component(second)?, component(first)?))
+        }
+        [first, second] => Some((component(first)?, component(second)?)),
+        _ => None,
+    }
+}
+
+impl std::fmt::Display for Gradient {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.repeating {
+            f.write_str("repeating-")?;
+        }
+        match self.shape {
+            GradientShape::Linear(LinearDirection::Angle(angle)) => {
+                write!(f, "linear-gradient({}deg", angle)?
+            }
+            GradientShape::Linear(LinearDirection::Corner { right, bottom }) => write!(
+                f,
+                "linear-gradient(to {} {}",
+                if bottom { "bottom" } else { "top" },
+                if right { "right" } else { "left" }
+            )?,
+            GradientShape::Radial {
+                circle,
+                extent,
+                center,
+            } => {
+                f.write_str("radial-gradient(")?;
+                f.write_str(if circle { "circle" } else { "ellipse" })?;
+                match extent {
+                    RadialExtent::ClosestSide => f.write_str(" closest-side")?,
+                    RadialExtent::ClosestCorner => f.write_str(" closest-corner")?,
+                    RadialExtent::FarthestSide => f.write_str(" farthest-side")?,
+                    RadialExtent::FarthestCorner => f.write_str(" farthest-corner")?,
+                    RadialExtent::Explicit(x, _) if circle => write!(f, " {}", x)?,
+                    RadialExtent::Explicit(x, y) => write!(f, " {} {}", x, y)?,
+                }
+                write!(f, " at {} {}", center.0, center.1)?;
+            }
+        }
+        for stop in &self.stops {
+            write!(f, ", {}", stop.color.to_hex())?;
+            if let Some(position) = stop.position {
+                write!(f, " {}", position)?;
+            }
+        }
+        f.write_str(")")
+    }
+}
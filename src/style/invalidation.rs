@@ -0,0 +1,255 @@
+use html5ever::{LocalName, local_name};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::css::Stylesheet;
+use crate::css::selector::{Combinator, Compound, PseudoClass, Selector};
+use crate::dom::{Node, NodeData};
+
+const ELEMENT_ATTRIBUTES: &[&str] = &["style", "href", "hidden"];
+
+#[derive(Debug, Clone, Default)]
+pub struct PendingRestyle {
+    pub subtree: bool,
+    pub descendants: bool,
+    pub animation: bool,
+    pub children: bool,
+    pub attributes: Vec<(LocalName, Option<String>)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    None,
+    Element,
+    Subtree,
+    Siblings,
+}
+
+#[derive(Debug, Default)]
+pub struct InvalidationMap {
+    ids: HashMap<String, Scope>,
+    classes: HashMap<String, Scope>,
+    attributes: HashMap<String, Scope>,
+    structural: bool,
+}
+
+impl InvalidationMap {
+    pub fn new(stylesheets: &[Stylesheet]) -> Self {
+        let mut map = InvalidationMap::default();
+        for stylesheet in stylesheets {
+            for rule in &stylesheet.rules {
+                for selector in &rule.selectors {
+                    map.add_selector(selector, Scope::Element);
+                }
+            }
+        }
+        map
+    }
+
+    fn add_selector(&mut self, selector: &Selector, scope: Scope) {
+        self.add(&selector.subject, scope);
+        for (combinator, compound) in &selector.ancestors {
+            let scope = match combinator {
+                Combinator::Descendant | Combinator::Child => Scope::Subtree,
+                Combinator::NextSibling | Combinator::SubsequentSibling => {
+                    self.structural = true;
+                    Scope::Siblings
+                }
+            };
+            self.add(compound, scope);
+        }
+    }
+
+    fn add(&mut self, compound: &Compound, scope: Scope) {
+        let insert = |map: &mut HashMap<String, Scope>, token: String| {
+            let entry = map.entry(token).or_insert(scope);
+            *entry = (*entry).max(scope);
+        };
+        if let Some(id) = &compound.id {
+            insert(&mut self.ids, id.to_string());
+        }
+        for class in &compound.classes {
+            insert(&mut self.classes, class.to_string());
+        }
+        for attribute in &compound.attributes {
+            insert(
+                &mut self.attributes,
+                attribute.name.to_string().to_ascii_lowercase(),
+            );
+        }
+        self.structural |= compound
+            .pseudo_classes
+            .iter()
+            .any(PseudoClass::is_structural);
+        for selector in compound
+            .pseudo_classes
+            .iter()
+            .flat_map(PseudoClass::arguments)
+        {
+            self.add_selector(selector, scope);
+        }
+    }
+
+    pub fn children_scope(&self) -> Scope {
+        if self.structural {
+            Scope::Subtree
+        } else {
+            Scope::None
+        }
+    }
+
+    pub fn scope(&self, node: &Node, attributes: &[(LocalName, Option<String>)]) -> Scope {
+        attributes
+            .iter()
+            .map(|(name, old_value)| {
+                let new_value = node.with_attribute(name, |value| value.map(String::from));
+                self.attribute_scope(name, old_value.as_deref(), new_value.as_deref())
+            })
+            .max()
+            .unwrap_or(Scope::None)
+    }
+
+    fn attribute_scope(&self, name: &LocalName, old: Option<&str>, new: Option<&str>) -> Scope {
+        if old == new {
+            return Scope::None;
+        }
+        let name_scope = token_scope(
+            std::iter::once(&*name.to_ascii_lowercase()),
+            &self.attributes,
+        );
+        self.value_scope(name, old, new).max(name_scope)
+    }
+
+    fn value_scope(&self, name: &LocalName, old: Option<&str>, new: Option<&str>) -> Scope {
+        if *name == local_name!("id") {
+            let changed = [old, new].into_iter().flatten();
+            return token_scope(changed, &self.ids);
+        }
+        if *name == local_name!("class") {
+            let old: Vec<&str> = old.unwrap_or_default().split_ascii_whitespace().collect();
+            let new: Vec<&str> = new.unwrap_or_default().split_ascii_whitespace().collect();
+            let changed = old
+                .iter()
+                .filter(|class| !new.contains(class))
+                .chain(new.iter().filter(|class| !old.contains(class)))
+                .copied();
+            return token_scope(changed, &self.classes);
+        }
+        if ELEMENT_ATTRIBUTES.contains(&&**name) {
+            return Scope::Element;
+        }
+        Scope::None
+    }
+}
+
+fn token_scope<'a>(tokens: impl Iterator<Item = &'a str>, map: &HashMap<String, Scope>) -> Scope {
+    tokens
+        .filter_map(|token| map.get(token).copied())
+        .max()
+        .unwrap_or(Scope::None)
+}
+
+pub fn stylesheet_generation(node: &Rc<Node>) -> u64 {
+    match Node::document_root(node).as_deref() {
+        Some(Node {
+            data: NodeData::Document { generations },
+            ..
+        }) => generations.stylesheets.get(),
+        _ => 0,
+    }
+}
+
+fn stylesheet_changed(node: &Rc<Node>) {
+    Node::bump_generation(node, |generations| &generations.stylesheets);
+}
+
+fn is_style_element(node: &Node) -> bool {
+    node.element_name() == Some("style")
+}
+
+pub(crate) fn text_changed(node: &Rc<Node>) {
+    if node
+        .parent
+        .borrow()
+        .upgrade()
+        .is_some_and(|parent| is_style_element(&parent))
+    {
+        stylesheet_changed(node);
+    }
+}
+
+pub(crate) fn child_list_changed(parent: &Rc<Node>, child: &Node) {
+    let mut touches_stylesheet = is_style_element(parent);
+    if !touches_stylesheet {
+        child.walk(&mut |node| touches_stylesheet |= is_style_element(node));
+    }
+    if touches_stylesheet {
+        stylesheet_changed(parent);
+    }
+}
+
+pub(crate) fn attribute_changed(node: &Rc<Node>, name: &LocalName, old_value: Option<&str>) {
+    if is_style_element(node) {
+        stylesheet_changed(node);
+    }
+    {
+        let mut pending = node.restyle.borrow_mut();
+        if pending
+            .attributes
+            .iter()
+            .any(|(changed, _)| changed == name)
+        {
+            return;
+        }
+        pending
+            .attributes
+            .push((name.clone(), old_value.map(String::from)));
+    }
+    mark_ancestors(node);
+}
+
+pub(crate) fn node_inserted(node: &Rc<Node>) {
+    node.restyle.borrow_mut().subtree = true;
+    mark_ancestors(node);
+}
+
+pub(crate) fn children_changed(parent: &Rc<Node>) {
+    parent.restyle.borrow_mut().children = true;
+    mark_ancestors(parent);
+}
+
+pub(crate) fn following_siblings_changed(node: &Rc<Node>) {
+    let Some(parent) = node.parent.borrow().upgrade() else {
+        return;
+    };
+    for sibling in parent
+        .children
+        .borrow()
+        .iter()
+        .skip_while(|sibling| !Rc::ptr_eq(sibling, node))
+        .skip(1)
+    {
+        sibling.restyle.borrow_mut().subtree = true;
+    }
+}
+
+pub(crate) fn animation_ticked(node: &Rc<Node>) {
+    node.restyle.borrow_mut().animation = true;
+    mark_ancestors(node);
+}
+
+fn mark_ancestors(node: &Rc<Node>) {
+    let mut current = Node::flat_tree_parent(node);
+    while let Some(ancestor) = current {
+        {
+            let mut pending = ancestor.restyle.borrow_mut();
+            if pending.descendants {
+                return;
+            }
+            pending.descendants = true;
+        }
+        current = Node::flat_tree_parent(&ancestor);
+    }
+}
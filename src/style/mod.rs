@@ -0,0 +1,2272 @@
+pub mod animation;
+pub mod counters;
+pub mod gradient;
+pub mod invalidation;
+pub mod rule_map;
+pub mod ua;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::css::bloom::AncestorFilter;
+use crate::css::keyframes::Keyframes;
+use crate::css::media::{ColorScheme, MediaFeatures, MediaType, ReducedMotion};
+use crate::css::selector::{PseudoElement, Selector, Specificity, parent_element, split_top_level};
+use crate::css::values::{Color, Length};
+use crate::css::{Declaration, Origin, Stylesheet, parser};
+use crate::dom::{Document, Node, NodeData, NodeId};
+use crate::layout;
+use crate::settings::Settings;
+use animation::{AnimationList, AnimationState};
+use counters::ContentItem;
+use gradient::Gradient;
+use invalidation::{InvalidationMap, Scope};
+use rule_map::{RuleEntry, RuleMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Display {
+    None,
+    Block,
+    Inline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhiteSpace {
+    Normal,
+    Pre,
+    PreWrap,
+    PreLine,
+    NoWrap,
+}
+
+impl WhiteSpace {
+    pub fn collapses_spaces(&self) -> bool {
+        matches!(
+            self,
+            WhiteSpace::Normal | WhiteSpace::PreLine | WhiteSpace::NoWrap
+        )
+    }
+
+    pub fn preserves_newlines(&self) -> bool {
+        matches!(
+            self,
+            WhiteSpace::Pre | WhiteSpace::PreWrap | WhiteSpace::PreLine
+        )
+    }
+
+    pub fn wraps(&self) -> bool {
+        !matches!(self, WhiteSpace::Pre | WhiteSpace::NoWrap)
+    }
+}
+
+impl std::fmt::Display for WhiteSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            WhiteSpace::Normal => "normal",
+            WhiteSpace::Pre => "pre",
+            WhiteSpace::PreWrap => "pre-wrap",
+            WhiteSpace::PreLine => "pre-line",
+            WhiteSpace::NoWrap => "nowrap",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowWrap {
+    Normal,
+    BreakWord,
+    Anywhere,
+}
+
+impl std::fmt::Display for OverflowWrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            OverflowWrap::Normal => "normal",
+            OverflowWrap::BreakWord => "break-word",
+            OverflowWrap::Anywhere => "anywhere",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordBreak {
+    Normal,
+    BreakAll,
+    KeepAll,
+}
+
+impl std::fmt::Display for WordBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            WordBreak::Normal => "normal",
+            WordBreak::BreakAll => "break-all",
+            WordBreak::KeepAll => "keep-all",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hyphens {
+    None,
+    Manual,
+    Auto,
+}
+
+impl std::fmt::Display for Hyphens {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Hyphens::None => "none",
+            Hyphens::Manual => "manual",
+            Hyphens::Auto => "auto",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextDecoration {
+    pub underline: bool,
+    pub overline: bool,
+    pub line_through: bool,
+}
+
+impl TextDecoration {
+    pub fn is_none(&self) -> bool {
+        *self == TextDecoration::default()
+    }
+}
+
+impl std::fmt::Display for TextDecoration {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_none() {
+            return f.write_str("none");
+        }
+        let lines: Vec<&str> = [
+            (self.underline, "underline"),
+            (self.overline, "overline"),
+            (self.line_through, "line-through"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        f.write_str(&lines.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    Capitalize,
+}
+
+impl TextTransform {
+    pub fn apply(&self, text: &str, word_start: bool) -> String {
+        match self {
+            TextTransform::None => text.to_string(),
+            TextTransform::Uppercase => text.to_uppercase(),
+            TextTransform::Lowercase => text.to_lowercase(),
+            TextTransform::Capitalize => {
+                let mut out = String::with_capacity(text.len());
+                let mut start = word_start;
+                for c in text.chars() {
+                    if start && c.is_alphanumeric() {
+                        out.extend(c.to_uppercase());
+                    } else {
+                        out.push(c);
+                    }
+                    start = c.is_whitespace() || (start && !c.is_alphanumeric());
+                }
+                out
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for TextTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            TextTransform::None => "none",
+            TextTransform::Uppercase => "uppercase",
+            TextTransform::Lowercase => "lowercase",
+            TextTransform::Capitalize => "capitalize",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+    Justify,
+}
+
+impl TextAlign {
+    pub fn resolve(self, direction: Direction) -> TextAlign {
+        match (self, direction) {
+            (TextAlign::Start, Direction::Ltr) | (TextAlign::End, Direction::Rtl) => {
+                TextAlign::Left
+            }
+            (TextAlign::Start, Direction::Rtl) | (TextAlign::End, Direction::Ltr) => {
+                TextAlign::Right
+            }
+            (align, _) => align,
+        }
+    }
+}
+
+impl std::fmt::Display for TextAlign {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            TextAlign::Start => "start",
+            TextAlign::End => "end",
+            TextAlign::Left => "left",
+            TextAlign::Right => "right",
+            TextAlign::Center => "center",
+            TextAlign::Justify => "justify",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    pub fn level(self) -> u8 {
+        match self {
+            Direction::Ltr => 0,
+            Direction::Rtl => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Direction::Ltr => f.write_str("ltr"),
+            Direction::Rtl => f.write_str("rtl"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerticalAlign {
+    Baseline,
+    Sub,
+    Super,
+    TextTop,
+    TextBottom,
+    Middle,
+    Top,
+    Bottom,
+    Px(f32),
+    Percent(f32),
+}
+
+impl std::fmt::Display for VerticalAlign {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VerticalAlign::Baseline => f.write_str("baseline"),
+            VerticalAlign::Sub => f.write_str("sub"),
+            VerticalAlign::Super => f.write_str("super"),
+            VerticalAlign::TextTop => f.write_str("text-top"),
+            VerticalAlign::TextBottom => f.write_str("text-bottom"),
+            VerticalAlign::Middle => f.write_str("middle"),
+            VerticalAlign::Top => f.write_str("top"),
+            VerticalAlign::Bottom => f.write_str("bottom"),
+            VerticalAlign::Px(px) => write!(f, "{}px", px),
+            VerticalAlign::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Visible,
+    Hidden,
+    Collapse,
+}
+
+impl std::fmt::Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Visibility::Visible => f.write_str("visible"),
+            Visibility::Hidden => f.write_str("hidden"),
+            Visibility::Collapse => f.write_str("collapse"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxShadow {
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub blur: f32,
+    pub spread: f32,
+    pub color: Color,
+    pub inset: bool,
+}
+
+impl std::fmt::Display for BoxShadow {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}px {}px {}px {}px {}",
+            self.offset_x,
+            self.offset_y,
+            self.blur,
+            self.spread,
+            self.color.to_hex()
+        )?;
+        if self.inset {
+            f.write_str(" inset")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxSizing {
+    ContentBox,
+    BorderBox,
+}
+
+impl std::fmt::Display for BoxSizing {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BoxSizing::ContentBox => f.write_str("content-box"),
+            BoxSizing::BorderBox => f.write_str("border-box"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    Static,
+    Sticky,
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Position::Static => f.write_str("static"),
+            Position::Sticky => f.write_str("sticky"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AspectRatio {
+    pub auto: bool,
+    pub ratio: Option<(f32, f32)>,
+}
+
+impl AspectRatio {
+    pub fn preferred(&self, natural: Option<f32>) -> Option<f32> {
+        match natural {
+            Some(natural) if self.auto => Some(natural),
+            _ => self
+                .ratio
+                .filter(|(width, height)| *width > 0.0 && *height > 0.0)
+                .map(|(width, height)| width / height),
+        }
+    }
+}
+
+impl Default for AspectRatio {
+    fn default() -> Self {
+        AspectRatio {
+            auto: true,
+            ratio: None,
+        }
+    }
+}
+
+impl std::fmt::Display for AspectRatio {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.auto, self.ratio) {
+            (true, None) => f.write_str("auto"),
+            (true, Some((width, height))) => write!(f, "auto {} / {}", width, height),
+            (false, Some((width, height))) => write!(f, "{} / {}", width, height),
+            (false, None) => f.write_str("auto"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectFit {
+    Fill,
+    Contain,
+    Cover,
+    None,
+    ScaleDown,
+}
+
+impl std::fmt::Display for ObjectFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ObjectFit::Fill => f.write_str("fill"),
+            ObjectFit::Contain => f.write_str("contain"),
+            ObjectFit::Cover => f.write_str("cover"),
+            ObjectFit::None => f.write_str("none"),
+            ObjectFit::ScaleDown => f.write_str("scale-down"),
+        }
+    }
+}
+
+/// Upper bound for computed line heights in pixels, so a huge value can't
+/// overflow layout or painting.
+pub const MAX_LINE_HEIGHT: f32 = 10_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineHeight {
+    Normal,
+    Number(f32),
+    Px(f32),
+}
+
+impl std::fmt::Display for LineHeight {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LineHeight::Normal => f.write_str("normal"),
+            LineHeight::Number(number) => write!(f, "{}", number),
+            LineHeight::Px(px) => write!(f, "{}px", px),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    None,
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl std::fmt::Display for BorderStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            BorderStyle::None => "none",
+            BorderStyle::Solid => "solid",
+            BorderStyle::Dashed => "dashed",
+            BorderStyle::Dotted => "dotted",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Auto,
+    Px(f32),
+    Percent(f32),
+}
+
+impl Dimension {
+    pub fn resolve(&self, base: f32) -> Option<f32> {
+        match self {
+            Dimension::Auto => None,
+            Dimension::Px(px) => Some(*px),
+            Dimension::Percent(percent) => Some(base * percent / 100.0),
+        }
+    }
+
+    pub fn resolve_or_zero(&self, base: f32) -> f32 {
+        self.resolve(base).unwrap_or(0.0)
+    }
+}
+
+impl std::fmt::Display for Dimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Dimension::Auto => write!(f, "auto"),
+            Dimension::Px(px) => write!(f, "{}px", px),
+            Dimension::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Sides<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+impl<T: Copy> Sides<T> {
+    pub fn all(value: T) -> Self {
+        Sides {
+            top: value,
+            right: value,
+            bottom: value,
+            left: value,
+        }
+    }
+
+    pub fn map<U>(&self, f: impl Fn(T) -> U) -> Sides<U> {
+        Sides {
+            top: f(self.top),
+            right: f(self.right),
+            bottom: f(self.bottom),
+            left: f(self.left),
+        }
+    }
+
+    pub fn side(&self, side: &str) -> Option<T> {
+        match side {
+            "top" => Some(self.top),
+            "right" => Some(self.right),
+            "bottom" => Some(self.bottom),
+            "left" => Some(self.left),
+            _ => None,
+        }
+    }
+
+    fn side_mut(&mut self, side: &str) -> Option<&mut T> {
+        match side {
+            "top" => Some(&mut self.top),
+            "right" => Some(&mut self.right),
+            "bottom" => Some(&mut self.bottom),
+            "left" => Some(&mut self.left),
+            _ => None,
+        }
+    }
+}
+
+impl Sides<f32> {
+    pub fn horizontal(&self) -> f32 {
+        self.left + self.right
+    }
+
+    pub fn vertical(&self) -> f32 {
+        self.top + self.bottom
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedStyle {
+    pub display: Display,
+    pub visibility: Visibility,
+    pub color: Color,
+    pub background_color: Color,
+    pub background_image: Vec<Gradient>,
+    pub font_size: f32,
+    pub font_weight: u16,
+    pub italic: bool,
+    pub font_family: Vec<String>,
+    pub line_height: LineHeight,
+    pub white_space: WhiteSpace,
+    pub overflow_wrap: OverflowWrap,
+    pub word_break: WordBreak,
+    pub hyphens: Hyphens,
+    pub text_decoration: TextDecoration,
+    pub text_transform: TextTransform,
+    pub letter_spacing: f32,
+    pub word_spacing: f32,
+    pub text_align: TextAlign,
+    pub direction: Direction,
+    pub vertical_align: VerticalAlign,
+    pub position: Position,
+    pub inset: Sides<Dimension>,
+    pub margin: Sides<Dimension>,
+    pub padding: Sides<Dimension>,
+    pub border_width: Sides<f32>,
+    pub border_style: Sides<BorderStyle>,
+    pub border_color: Sides<Color>,
+    pub box_shadow: Vec<BoxShadow>,
+    pub box_sizing: BoxSizing,
+    pub aspect_ratio: AspectRatio,
+    pub object_fit: ObjectFit,
+    pub animation: AnimationList,
+    pub width: Dimension,
+    pub height: Dimension,
+    pub min_width: Dimension,
+    pub max_width: Dimension,
+    pub min_height: Dimension,
+    pub max_height: Dimension,
+    pub break_before: bool,
+    pub break_after: bool,
+    pub break_inside_avoid: bool,
+    pub content: Vec<ContentItem>,
+    pub counter_reset: Vec<(String, i32)>,
+    pub counter_increment: Vec<(String, i32)>,
+    pub counter_set: Vec<(String, i32)>,
+}
+
+impl Default for ComputedStyle {
+    fn default() -> Self {
+        ComputedStyle {
+            display: Display::Inline,
+            visibility: Visibility::Visible,
+            color: Color::BLACK,
+            background_color: Color::TRANSPARENT,
+            background_image: Vec::new(),
+            font_size: 16.0,
+            font_weight: 400,
+            italic: false,
+            font_family: Vec::new(),
+            line_height: LineHeight::Normal,
+            white_space: WhiteSpace::Normal,
+            overflow_wrap: OverflowWrap::Normal,
+            word_break: WordBreak::Normal,
+            hyphens: Hyphens::Manual,
+            text_decoration: TextDecoration::default(),
+            text_transform: TextTransform::None,
+            letter_spacing: 0.0,
+            word_spacing: 0.0,
+            text_align: TextAlign::Start,
+            direction: Direction::Ltr,
+            vertical_align: VerticalAlign::Baseline,
+            position: Position::Static,
+            inset: Sides::all(Dimension::Auto),
+            margin: Sides::all(Dimension::Px(0.0)),
+            padding: Sides::all(Dimension::Px(0.0)),
+            border_width: Sides::all(0.0),
+            border_style: Sides::all(BorderStyle::None),
+            border_color: Sides::all(Color::BLACK),
+            box_shadow: Vec::new(),
+            box_sizing: BoxSizing::ContentBox,
+            aspect_ratio: AspectRatio::default(),
+            object_fit: ObjectFit::Fill,
+            animation: AnimationList::default(),
+            width: Dimension::Auto,
+            height: Dimension::Auto,
+            min_width: Dimension::Auto,
+            max_width: Dimension::Auto,
+            min_height: Dimension::Auto,
+            max_height: Dimension::Auto,
+            break_before: false,
+            break_after: false,
+            break_inside_avoid: false,
+            content: Vec::new(),
+            counter_reset: Vec::new(),
+            counter_increment: Vec::new(),
+            counter_set: Vec::new(),
+        }
+    }
+}
+
+impl ComputedStyle {
+    pub fn inherit(parent: &ComputedStyle) -> Self {
+        ComputedStyle {
+            visibility: parent.visibility,
+            color: parent.color,
+            font_size: parent.font_size,
+            font_weight: parent.font_weight,
+            italic: parent.italic,
+            font_family: parent.font_family.clone(),
+            line_height: parent.line_height,
+            white_space: parent.white_space,
+            overflow_wrap: parent.overflow_wrap,
+            word_break: parent.word_break,
+            hyphens: parent.hyphens,
+            text_decoration: parent.text_decoration,
+            text_transform: parent.text_transform,
+            letter_spacing: parent.letter_spacing,
+            word_spacing: parent.word_spacing,
+            text_align: parent.text_align,
+            direction: parent.direction,
+            ..ComputedStyle::default()
+        }
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.font_weight >= 600
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visibility == Visibility::Visible
+    }
+
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        let sides = |sides: &Sides<Dimension>| {
+            format!(
+                "{} {} {} {}",
+                sides.top, sides.right, sides.bottom, sides.left
+            )
+        };
+        let display = match self.display {
+            Display::None => "none",
+            Display::Block => "block",
+            Display::Inline => "inline",
+        };
+
+        let mut properties = vec![
+            ("display", display.to_string()),
+            ("visibility", self.visibility.to_string()),
+            ("color", self.color.to_hex()),
+            ("background-color", self.background_color.to_hex()),
+            ("background-image", background_image(&self.background_image)),
+            ("font-size", format!("{}px", self.font_size)),
+            ("font-weight", self.font_weight.to_string()),
+            (
+                "font-style",
+                if self.italic { "italic" } else { "normal" }.to_string(),
+            ),
+            ("font-family", font_family(&self.font_family)),
+            ("line-height", self.line_height.to_string()),
+            ("white-space", self.white_space.to_string()),
+            ("overflow-wrap", self.overflow_wrap.to_string()),
+            ("word-break", self.word_break.to_string()),
+            ("hyphens", self.hyphens.to_string()),
+            ("text-decoration", self.text_decoration.to_string()),
+            ("text-transform", self.text_transform.to_string()),
+            ("letter-spacing", format!("{}px", self.letter_spacing)),
+            ("word-spacing", format!("{}px", self.word_spacing)),
+            ("text-align", self.text_align.to_string()),
+            ("direction", self.direction.to_string()),
+            ("vertical-align", self.vertical_align.to_string()),
+            ("box-shadow", box_shadow(&self.box_shadow)),
+            ("box-sizing", self.box_sizing.to_string()),
+            ("aspect-ratio", self.aspect_ratio.to_string()),
+            ("object-fit", self.object_fit.to_string()),
+            ("width", self.width.to_string()),
+            ("height", self.height.to_string()),
+            ("min-width", self.min_width.to_string()),
+            ("max-width", max_size(&self.max_width)),
+            ("min-height", self.min_height.to_string()),
+            ("max-height", max_size(&self.max_height)),
+            ("position", self.position.to_string()),
+            ("top", self.inset.top.to_string()),
+            ("right", self.inset.right.to_string()),
+            ("bottom", self.inset.bottom.to_string()),
+            ("left", self.inset.left.to_string()),
+            ("margin", sides(&self.margin)),
+            ("padding", sides(&self.padding)),
+            (
+                "border-width",
+                format!(
+                    "{}px {}px {}px {}px",
+                    self.border_width.top,
+                    self.border_width.right,
+                    self.border_width.bottom,
+                    self.border_width.left
+                ),
+            ),
+            ("border-color", self.border_color.top.to_hex()),
+            ("break-before", page_break(self.break_before).to_string()),
+            ("break-after", page_break(self.break_after).to_string()),
+            (
+                "break-inside",
+                String::from(if self.break_inside_avoid {
+                    "avoid"
+                } else {
+                    "auto"
+                }),
+            ),
+            ("content", content(&self.content)),
+            (
+                "counter-reset",
+                counters::format_counter_list(&self.counter_reset),
+            ),
+            (
+                "counter-increment",
+                counters::format_counter_list(&self.counter_increment),
+            ),
+            (
+                "counter-set",
+                counters::format_counter_list(&self.counter_set),
+            ),
+        ];
+        properties.extend(self.animation.properties());
+        properties
+    }
+
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map: BTreeMap<String, String> = self
+            .properties()
+            .into_iter()
+            .filter(|(name, _)| {
+                !matches!(
+                    *name,
+                    "margin" | "padding" | "border-width" | "border-color"
+                )
+            })
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        for (side, margin, padding, width, style, color) in [
+            (
+                "top",
+                self.margin.top,
+                self.padding.top,
+                self.border_width.top,
+                self.border_style.top,
+                self.border_color.top,
+            ),
+            (
+                "right",
+                self.margin.right,
+                self.padding.right,
+                self.border_width.right,
+                self.border_style.right,
+                self.border_color.right,
+            ),
+            (
+                "bottom",
+                self.margin.bottom,
+                self.padding.bottom,
+                self.border_width.bottom,
+                self.border_style.bottom,
+                self.border_color.bottom,
+            ),
+            (
+                "left",
+                self.margin.left,
+                self.padding.left,
+                self.border_width.left,
+                self.border_style.left,
+                self.border_color.left,
+            ),
+        ] {
+            map.insert(format!("margin-{}", side), margin.to_string());
+            map.insert(format!("padding-{}", side), padding.to_string());
+            map.insert(format!("border-{}-width", side), format!("{}px", width));
+            map.insert(format!("border-{}-style", side), style.to_string());
+            map.insert(format!("border-{}-color", side), color.to_hex());
+        }
+        map
+    }
+}
+
+pub struct Stylist {
+    stylesheets: Vec<Stylesheet>,
+    rule_map: RuleMap,
+    invalidation: InvalidationMap,
+    styled_elements: Cell<usize>,
+    matched_rules: Cell<usize>,
+    fast_rejected: Cell<usize>,
+    initial: ComputedStyle,
+    media: MediaFeatures,
+    pseudo_elements: bool,
+    time: Cell<f64>,
+    generation: u64,
+    settings: Settings,
+    shadow_stylists: RefCell<HashMap<NodeId, ShadowStylist>>,
+}
+
+struct ShadowStylist {
+    generation: u64,
+    stylist: Rc<Stylist>,
+}
+
+struct MatchedDeclaration {
+    important: bool,
+    origin: Origin,
+    inline: bool,
+    specificity: Specificity,
+    order: usize,
+    declaration: Declaration,
+}
+
+impl Stylist {
+    pub fn new(stylesheets: Vec<Stylesheet>) -> Self {
+        let pseudo_elements = stylesheets
+            .iter()
+            .flat_map(|stylesheet| &stylesheet.rules)
+            .flat_map(|rule| &rule.selectors)
+            .any(|selector| selector.pseudo_element().is_some());
+        Stylist {
+            rule_map: RuleMap::new(&stylesheets),
+            invalidation: InvalidationMap::new(&stylesheets),
+            stylesheets,
+            styled_elements: Cell::new(0),
+            matched_rules: Cell::new(0),
+            fast_rejected: Cell::new(0),
+            initial: ComputedStyle::default(),
+            media: MediaFeatures {
+                media_type: MediaType::Screen,
+                color_scheme: ColorScheme::Light,
+                reduced_motion: ReducedMotion::NoPreference,
+                scripting: true,
+            },
+            pseudo_elements,
+            time: Cell::new(0.0),
+            generation: 0,
+            settings: Settings::default(),
+            shadow_stylists: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_scripting(mut self, scripting: bool) -> Self {
+        self.media.scripting = scripting;
+        self
+    }
+
+    pub fn with_media_type(mut self, media_type: MediaType) -> Self {
+        self.media.media_type = media_type;
+        self
+    }
+
+    pub fn with_settings(mut self, settings: &Settings) -> Self {
+        self.initial.color = settings.palette().text;
+        self.initial.font_size = settings.fonts.size;
+        self.media.color_scheme = settings.color_scheme.resolve();
+        self.media.reduced_motion = settings.reduced_motion.resolve();
+        self.settings = settings.clone();
+        self
+    }
+
+    pub fn with_generation(mut self, generation: u64) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    pub fn page_margin(&self) -> Sides<Dimension> {
+        let mut page = ComputedStyle {
+            margin: Sides::all(Dimension::Auto),
+            ..ComputedStyle::default()
+        };
+        let declarations: Vec<&Declaration> = self
+            .stylesheets
+            .iter()
+            .flat_map(|stylesheet| &stylesheet.pages)
+            .filter(|rule| rule.applies(&self.media))
+            .flat_map(|rule| &rule.declarations)
+            .collect();
+        let (important, normal): (Vec<_>, Vec<_>) = declarations
+            .into_iter()
+            .partition(|declaration| declaration.important);
+        for declaration in normal.into_iter().chain(important) {
+            if declaration.name.starts_with("margin-") {
+                apply_declaration(&mut page, &self.initial, declaration);
+            }
+        }
+        page.margin
+    }
+
+    pub fn styled_elements(&self) -> usize {
+        self.styled_elements.get()
+    }
+
+    pub fn matched_rules(&self) -> usize {
+        self.matched_rules.get()
+    }
+
+    pub fn fast_rejected(&self) -> usize {
+        self.fast_rejected.get()
+    }
+
+    pub fn stylesheets(&self) -> &[Stylesheet] {
+        &self.stylesheets
+    }
+
+    pub fn set_time(&self, time: f64) {
+        self.time.set(time);
+    }
+
+    fn keyframes(&self, name: &str) -> Option<&Keyframes> {
+        self.stylesheets
+            .iter()
+            .rev()
+            .flat_map(|stylesheet| stylesheet.keyframes.iter().rev())
+            .find(|keyframes| keyframes.name == name && keyframes.applies(&self.media))
+    }
+
+    fn reset_counters(&self) {
+        self.styled_elements.set(0);
+        self.matched_rules.set(0);
+        self.fast_rejected.set(0);
+    }
+
+    fn rejected(&self, selector: &Selector, filter: Option<&AncestorFilter>) -> bool {
+        let rejected = filter.is_some_and(|filter| selector.rejected_by(filter));
+        if rejected {
+            self.fast_rejected.set(self.fast_rejected.get() + 1);
+        }
+        rejected
+    }
+
+    fn shadow_stylist(&self, shadow_root: &Rc<Node>) -> Rc<Stylist> {
+        let generation = invalidation::stylesheet_generation(shadow_root);
+        let mut cache = self.shadow_stylists.borrow_mut();
+        if let Some(cached) = cache.get(&shadow_root.id)
+            && cached.generation == generation
+        {
+            return Rc::clone(&cached.stylist);
+        }
+        let mut sources = Vec::new();
+        shadow_root.walk_rc(&mut |node| {
+            if is_stylesheet(node) {
+                sources.push(node.get_text_content());
+            }
+        });
+
+        let mut stylesheets: Vec<Stylesheet> = self
+            .stylesheets
+            .iter()
+            .filter(|stylesheet| stylesheet.origin == Origin::UserAgent)
+            .cloned()
+            .collect();
+        stylesheets.extend(
+            sources
+                .iter()
+                .map(|source| Stylesheet::parse(source, Origin::Author)),
+        );
+        let mut stylist = Stylist::new(stylesheets);
+        stylist.initial = self.initial.clone();
+        stylist.media = self.media;
+        let stylist = Rc::new(stylist);
+        cache.insert(
+            shadow_root.id,
+            ShadowStylist {
+                generation,
+                stylist: Rc::clone(&stylist),
+            },
+        );
+        stylist
+    }
+
+    fn style_shadow_tree(&self, host: &Rc<Node>, host_style: &ComputedStyle) -> bool {
+        let Some(shadow_root) = host.shadow_root.borrow().clone() else {
+            return false;
+        };
+        let stylist = self.shadow_stylist(&shadow_root);
+        stylist.reset_counters();
+        stylist.set_time(self.time.get());
+        *shadow_root.restyle.borrow_mut() = Default::default();
+        let children = shadow_root.children.borrow().clone();
+        for child in &children {
+            stylist.style_filtered(child, Some(host_style), &mut AncestorFilter::new());
+        }
+        self.styled_elements
+            .set(self.styled_elements.get() + stylist.styled_elements());
+        self.matched_rules
+            .set(self.matched_rules.get() + stylist.matched_rules());
+        self.fast_rejected
+            .set(self.fast_rejected.get() + stylist.fast_rejected());
+        true
+    }
+
+    pub fn for_document(document: &Document) -> Self {
+        Stylist::new(document.stylesheets())
+            .with_scripting(document.scripting)
+            .with_settings(&document.settings)
+    }
+
+    fn matched_declarations(
+        &self,
+        element: &Rc<Node>,
+        pseudo: Option<PseudoElement>,
+        filter: Option<&AncestorFilter>,
+    ) -> Vec<Declaration> {
+        let mut matched = Vec::new();
+        let mut matched_rules: Vec<RuleEntry> = Vec::new();
+
+        for entry in self.rule_map.candidates(element) {
+            let stylesheet = &self.stylesheets[entry.sheet];
+            let rule = &stylesheet.rules[entry.rule];
+            if !rule.applies(&self.media) {
+                continue;
+            }
+            let selector = &rule.selectors[entry.selector];
+            if self.rejected(selector, filter) || !selector.matches_pseudo(element, pseudo) {
+                continue;
+            }
+            match matched_rules.last_mut() {
+                Some(last) if last.order == entry.order => {
+                    last.specificity = last.specificity.max(entry.specificity);
+                }
+                _ => matched_rules.push(entry),
+            }
+        }
+
+        self.matched_rules
+            .set(self.matched_rules.get() + matched_rules.len());
+        for entry in &matched_rules {
+            let stylesheet = &self.stylesheets[entry.sheet];
+            for declaration in &stylesheet.rules[entry.rule].declarations {
+                matched.push(MatchedDeclaration {
+                    important: declaration.important,
+                    origin: stylesheet.origin,
+                    inline: false,
+                    specificity: entry.specificity,
+                    order: entry.order,
+                    declaration: declaration.clone(),
+                });
+            }
+        }
+
+        if pseudo.is_none() && element.has_attribute("hidden") {
+            matched.push(MatchedDeclaration {
+                important: false,
+                origin: Origin::UserAgent,
+                inline: false,
+                specificity: Specificity(0, 1, 0),
+                order: 0,
+                declaration: Declaration {
+                    name: String::from("display"),
+                    value: String::from("none"),
+                    important: false,
+                },
+            });
+        }
+
+        if pseudo.is_none()
+            && let Some(direction) = dir_attribute(element)
+        {
+            matched.push(MatchedDeclaration {
+                important: false,
+                origin: Origin::UserAgent,
+                inline: false,
+                specificity: Specificity::default(),
+                order: 0,
+                declaration: Declaration {
+                    name: String::from("direction"),
+                    value: direction.to_string(),
+                    important: false,
+                },
+            });
+        }
+
+        if pseudo.is_none()
+            && let Some(style) = element.get_attribute("style")
+        {
+            for declaration in parser::parse_declarations(&style) {
+                matched.push(MatchedDeclaration {
+                    important: declaration.important,
+                    origin: Origin::Author,
+                    inline: true,
+                    specificity: Specificity::default(),
+                    order: usize::MAX,
+                    declaration,
+                });
+            }
+        }
+
+        matched.sort_by(|a, b| {
+            (a.important, a.origin, a.inline, a.specificity, a.order).cmp(&(
+                b.important,
+                b.origin,
+                b.inline,
+                b.specificity,
+                b.order,
+            ))
+        });
+        matched.into_iter().map(|m| m.declaration).collect()
+    }
+
+    pub fn compute(&self, element: &Rc<Node>, parent: Option<&ComputedStyle>) -> ComputedStyle {
+        self.compute_filtered(element, parent, None)
+    }
+
+    fn compute_filtered(
+        &self,
+        element: &Rc<Node>,
+        parent: Option<&ComputedStyle>,
+        filter: Option<&AncestorFilter>,
+    ) -> ComputedStyle {
+        let parent = parent.unwrap_or(&self.initial);
+        let mut declarations = self.matched_declarations(element, None, filter);
+        let style = cascade(parent, &declarations);
+        if style.animation.is_none() && element.animations.borrow().is_empty() {
+            return style;
+        }
+        let animated = self.animate(element, &style);
+        if animated.is_empty() {
+            return style;
+        }
+        let important = declarations.partition_point(|declaration| !declaration.important);
+        declarations.splice(important..important, animated);
+        cascade(parent, &declarations)
+    }
+
+    fn animate(&self, element: &Rc<Node>, style: &ComputedStyle) -> Vec<Declaration> {
+        let now = self.time.get();
+        let reduced = self.media.reduced_motion == ReducedMotion::Reduce;
+        let previous = std::mem::take(&mut *element.animations.borrow_mut());
+        let mut states = Vec::new();
+        let mut declarations = Vec::new();
+        let mut underlying = None;
+        for animation in style.animation.animations() {
+            let Some(keyframes) = self.keyframes(&animation.name) else {
+                continue;
+            };
+            let mut state = previous
+                .iter()
+                .find(|state| state.name == animation.name)
+                .cloned()
+                .unwrap_or_else(|| AnimationState::new(&animation.name, now));
+            let mut elapsed = state.update(&animation, now);
+            if reduced {
+                state.finished = true;
+                elapsed = f32::INFINITY;
+            }
+            states.push(state);
+            if let Some(progress) = animation.progress(elapsed) {
+                let underlying = underlying.get_or_insert_with(|| style.to_map());
+                declarations.extend(animation::sample(
+                    keyframes,
+                    animation.timing_function,
+                    progress,
+                    underlying,
+                ));
+            }
+        }
+        *element.animations.borrow_mut() = states;
+        declarations
+    }
+
+    pub fn compute_pseudo(
+        &self,
+        element: &Rc<Node>,
+        pseudo: PseudoElement,
+        parent: &ComputedStyle,
+    ) -> Option<ComputedStyle> {
+        if !self.pseudo_elements {
+            return None;
+        }
+        let declarations = self.matched_declarations(element, Some(pseudo), None);
+        if declarations.is_empty() {
+            return None;
+        }
+        Some(cascade(parent, &declarations))
+    }
+
+    pub fn style_subtree(&self, node: &Rc<Node>, parent: Option<&ComputedStyle>) {
+        let mut ancestors = Vec::new();
+        let mut current = parent_element(node);
+        while let Some(ancestor) = current {
+            current = parent_element(&ancestor);
+            ancestors.push(ancestor);
+        }
+
+        let mut filter = AncestorFilter::new();
+        for ancestor in ancestors.iter().rev() {
+            filter.push(ancestor);
+        }
+        self.style_filtered(node, parent, &mut filter);
+    }
+
+    fn style_filtered(
+        &self,
+        node: &Rc<Node>,
+        parent: Option<&ComputedStyle>,
+        filter: &mut AncestorFilter,
+    ) {
+        *node.restyle.borrow_mut() = Default::default();
+        let style = match node.data {
+            NodeData::Element { .. } => {
+                let style = Rc::new(self.compute_filtered(node, parent, Some(filter)));
+                self.styled_elements.set(self.styled_elements.get() + 1);
+                if node.style.borrow().as_deref() != Some(&*style) {
+                    layout::mark_dirty(node);
+                }
+                *node.style.borrow_mut() = Some(Rc::clone(&style));
+                self.style_shadow_tree(node, &style);
+                Some(style)
+            }
+            _ => None,
+        };
+        let inherited = style.as_deref().or(parent);
+
+        let children = node.children.borrow().clone();
+        if style.is_some() {
+            filter.push(node);
+        }
+        for child in &children {
+            let slot = slot_style(child);
+            self.style_filtered(child, slot.as_deref().or(inherited), filter);
+        }
+        if style.is_some() {
+            filter.pop();
+        }
+    }
+
+    pub fn restyle_dirty(
+        &self,
+        node: &Rc<Node>,
+        parent: Option<&ComputedStyle>,
+        filter: &mut AncestorFilter,
+        parent_changed: bool,
+    ) {
+        let pending = std::mem::take(&mut *node.restyle.borrow_mut());
+        if pending.subtree {
+            self.style_filtered(node, parent, filter);
+            return;
+        }
+
+        let mut style = node.style.borrow().clone();
+        let mut changed = parent_changed;
+        if let NodeData::Element { .. } = node.data {
+            let scope = match &style {
+                None => Scope::Subtree,
+                Some(_) if pending.animation => self
+                    .invalidation
+                    .scope(node, &pending.attributes)
+                    .max(Scope::Element),
+                Some(_) => self.invalidation.scope(node, &pending.attributes),
+            };
+            let scope = if pending.children {
+                scope.max(self.invalidation.children_scope())
+            } else {
+                scope
+            };
+            if scope == Scope::Siblings {
+                invalidation::following_siblings_changed(node);
+            }
+            if scope >= Scope::Subtree {
+                self.style_filtered(node, parent, filter);
+                return;
+            }
+            if scope == Scope::Element || parent_changed {
+                let computed = Rc::new(self.compute_filtered(node, parent, Some(filter)));
+                self.styled_elements.set(self.styled_elements.get() + 1);
+                changed = style.as_deref() != Some(&*computed);
+                if changed {
+                    layout::mark_dirty(node);
+                }
+                *node.style.borrow_mut() = Some(Rc::clone(&computed));
+                style = Some(computed);
+            }
+        }
+        if !pending.descendants && !changed {
+            return;
+        }
+
+        let shadow_pending = node
+            .shadow_root
+            .borrow()
+            .as_ref()
+            .is_some_and(|shadow_root| shadow_root.restyle.borrow().descendants);
+        let shadow_changed = match &style {
+            Some(style) if changed || shadow_pending => self.style_shadow_tree(node, style),
+            _ => false,
+        };
+
+        let inherited = style.as_deref().or(parent);
+        let children = node.children.borrow().clone();
+        if style.is_some() {
+            filter.push(node);
+        }
+        for child in &children {
+            let slot = slot_style(child);
+            let inherited = slot.as_deref().or(inherited);
+            self.restyle_dirty(child, inherited, filter, changed || shadow_changed);
+        }
+        if style.is_some() {
+            filter.pop();
+        }
+    }
+}
+
+fn cascade(parent: &ComputedStyle, declarations: &[Declaration]) -> ComputedStyle {
+    let mut style = ComputedStyle::inherit(parent);
+    let (early, late): (Vec<_>, Vec<_>) = declarations
+        .iter()
+        .partition(|d| d.name == "font-size" || d.name == "color");
+    for declaration in early {
+        apply_declaration(&mut style, parent, declaration);
+    }
+    style.border_color = Sides::all(style.color);
+    for declaration in late {
+        apply_declaration(&mut style, parent, declaration);
+    }
+
+    let styles = style.border_style;
+    for (width, border_style) in [
+        (&mut style.border_width.top, styles.top),
+        (&mut style.border_width.right, styles.right),
+        (&mut style.border_width.bottom, styles.bottom),
+        (&mut style.border_width.left, styles.left),
+    ] {
+        if border_style == BorderStyle::None {
+            *width = 0.0;
+        }
+    }
+    style
+}
+
+fn resolve_length(value: &str, font_size: f32) -> Option<Dimension> {
+    match Length::parse(value)? {
+        Length::Auto => Some(Dimension::Auto),
+        Length::Px(px) => Some(Dimension::Px(px)),
+        Length::Em(em) => Some(Dimension::Px(em * font_size)),
+        Length::Rem(rem) => Some(Dimension::Px(rem * 16.0)),
+        Length::Percent(percent) => Some(Dimension::Percent(percent)),
+    }
+}
+
+fn parse_box_shadow(value: &str, font_size: f32, current: Color) -> Option<Vec<BoxShadow>> {
+    if value == "none" {
+        return Some(Vec::new());
+    }
+    let mut shadows = Vec::new();
+    for layer in split_top_level(value, ',') {
+        let mut lengths = Vec::new();
+        let mut color = None;
+        let mut inset = false;
+        for token in split_top_level(layer.trim(), ' ') {
+            let token = token.trim();
+            if token.is_empty() {
+                continue;
+            }
+            if token == "inset" && !inset {
+                inset = true;
+            } else if let Some(Dimension::Px(px)) = resolve_length(token, font_size) {
+                lengths.push(px);
+            } else if color.is_none() {
+                color = Some(parse_color(token, current)?);
+            } else {
+                return None;
+            }
+        }
+        if !(2..=4).contains(&lengths.len()) || lengths.get(2).is_some_and(|blur| *blur < 0.0) {
+            return None;
+        }
+        shadows.push(BoxShadow {
+            offset_x: lengths[0],
+            offset_y: lengths[1],
+            blur: lengths.get(2).copied().unwrap_or(0.0),
+            spread: lengths.get(3).copied().unwrap_or(0.0),
+            color: color.unwrap_or(current),
+            inset,
+        });
+    }
+    Some(shadows)
+}
+
+fn parse_aspect_ratio(value: &str) -> Option<AspectRatio> {
+    let mut auto = false;
+    let mut ratio = None;
+    let value = value.replace('/', " / ");
+    let mut tokens = value.split_whitespace().peekable();
+    while let Some(token) = tokens.next() {
+        if token == "auto" && !auto {
+            auto = true;
+            continue;
+        }
+        if ratio.is_some() {
+            return None;
+        }
+        let width: f32 = token.parse().ok().filter(|width: &f32| *width >= 0.0)?;
+        let height = if tokens.next_if_eq(&"/").is_some() {
+            tokens
+                .next()?
+                .parse()
+                .ok()
+                .filter(|height: &f32| *height >= 0.0)?
+        } else {
+            1.0
+        };
+        ratio = Some((width, height));
+    }
+    if !auto && ratio.is_none() {
+        return None;
+    }
+    Some(AspectRatio {
+        auto: auto || ratio.is_none_or(|(width, height)| width == 0.0 || height == 0.0),
+        ratio,
+    })
+}
+
+fn parse_background_image(value: &str, font_size: f32, current: Color) -> Option<Vec<Gradient>> {
+    let mut layers = Vec::new();
+    for layer in split_top_level(value, ',') {
+        let layer = layer.trim();
+        if layer == "none" || layer.starts_with("url(") {
+            continue;
+        }
+        layers.push(Gradient::parse(layer, font_size, current)?);
+    }
+    Some(layers)
+}
+
+fn background_image(layers: &[Gradient]) -> String {
+    if layers.is_empty() {
+        return String::from("none");
+    }
+    layers
+        .iter()
+        .map(Gradient::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn box_shadow(shadows: &[BoxShadow]) -> String {
+    if shadows.is_empty() {
+        return String::from("none");
+    }
+    shadows
+        .iter()
+        .map(BoxShadow::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn parse_max_size(value: &str, font_size: f32) -> Option<Dimension> {
+    match value {
+        "none" => Some(Dimension::Auto),
+        "auto" => None,
+        _ => resolve_length(value, font_size),
+    }
+}
+
+fn max_size(size: &Dimension) -> String {
+    match size {
+        Dimension::Auto => String::from("none"),
+        size => size.to_string(),
+    }
+}
+
+fn parse_font_size(value: &str, parent: f32) -> Option<f32> {
+    let size = match value {
+        "xx-small" => 9.0,
+        "x-small" => 10.0,
+        "small" => 13.0,
+        "medium" => 16.0,
+        "large" => 18.0,
+        "x-large" => 24.0,
+        "xx-large" => 32.0,
+        "smaller" => parent / 1.2,
+        "larger" => parent * 1.2,
+        _ => match Length::parse(value)? {
+            Length::Px(px) => px,
+            Length::Em(em) => em * parent,
+            Length::Rem(rem) => rem * 16.0,
+            Length::Percent(percent) => parent * percent / 100.0,
+            Length::Auto => return None,
+        },
+    };
+    Some(size.max(0.0))
+}
+
+fn parse_font_weight(value: &str, parent: u16) -> Option<u16> {
+    match value {
+        "normal" => Some(400),
+        "bold" => Some(700),
+        "bolder" => Some(if parent < 600 { 700 } else { 900 }),
+        "lighter" => Some(if parent > 500 { 400 } else { 100 }),
+        _ => value
+            .parse::<u16>()
+            .ok()
+            .filter(|weight| (1..=1000).contains(weight)),
+    }
+}
+
+fn parse_border_width(value: &str, font_size: f32) -> Option<f32> {
+    match value {
+        "thin" => Some(1.0),
+        "medium" => Some(3.0),
+        "thick" => Some(5.0),
+        _ => match resolve_length(value, font_size)? {
+            Dimension::Px(px) => Some(px.max(0.0)),
+            _ => None,
+        },
+    }
+}
+
+fn dir_attribute(element: &Rc<Node>) -> Option<Direction> {
+    match element
+        .get_attribute("dir")?
+        .trim()
+        .to_ascii_lowercase()
+        .as_str()
+    {
+        "ltr" => Some(Direction::Ltr),
+        "rtl" => Some(Direction::Rtl),
+        "auto" => {
+            let text = element.get_text_content();
+            Some(layout::bidi::first_strong(&text).unwrap_or_default())
+        }
+        _ => None,
+    }
+}
+
+fn parse_color(value: &str, current: Color) -> Option<Color> {
+    if value.eq_ignore_ascii_case("currentcolor") {
+        return Some(current);
+    }
+    Color::parse(value)
+}
+
+fn apply_declaration(style: &mut ComputedStyle, parent: &ComputedStyle, declaration: &Declaration) {
+    let physical = physical_name(&declaration.name, style.direction);
+    let name = physical.as_deref().unwrap_or(&declaration.name);
+    let value = declaration.value.trim().to_ascii_lowercase();
+    let value = value.as_str();
+    let initial = ComputedStyle::default();
+
+    if value == "inherit" || value == "initial" || value == "unset" {
+        let source = if value == "initial" { &initial } else { parent };
+        copy_property(style, source, name);
+        return;
+    }
+
+    match name {
+        "display" => {
+            if let Some(display) = parse_display(value) {
+                style.display = display;
+            }
+        }
+        "color" => {
+            if let Some(color) = parse_color(value, parent.color) {
+                style.color = color;
+            }
+        }
+        "background-color" => {
+            if let Some(color) = parse_color(value, style.color) {
+                style.background_color = color;
+            }
+        }
+        "background-image" => {
+            if let Some(layers) = parse_background_image(value, style.font_size, style.color) {
+                style.background_image = layers;
+            }
+        }
+        "white-space" => {
+            if let Some(white_space) = parse_white_space(value) {
+                style.white_space = white_space;
+            }
+        }
+        "overflow-wrap" | "word-wrap" => match value {
+            "normal" => style.overflow_wrap = OverflowWrap::Normal,
+            "break-word" => style.overflow_wrap = OverflowWrap::BreakWord,
+            "anywhere" => style.overflow_wrap = OverflowWrap::Anywhere,
+            _ => {}
+        },
+        "word-break" => match value {
+            "normal" => style.word_break = WordBreak::Normal,
+            "break-all" => style.word_break = WordBreak::BreakAll,
+            "keep-all" => style.word_break = WordBreak::KeepAll,
+            "break-word" => {
+                style.word_break = WordBreak::Normal;
+                style.overflow_wrap = OverflowWrap::Anywhere;
+            }
+            _ => {}
+        },
+        "hyphens" | "-webkit-hyphens" => match value {
+            "none" => style.hyphens = Hyphens::None,
+            "manual" => style.hyphens = Hyphens::Manual,
+            "auto" => style.hyphens = Hyphens::Auto,
+            _ => {}
+        },
+        "font-size" => {
+            if let Some(size) = parse_font_size(value, parent.font_size) {
+                style.font_size = size;
+            }
+        }
+        "font-weight" => {
+            if let Some(weight) = parse_font_weight(value, parent.font_weight) {
+                style.font_weight = weight;
+            }
+        }
+        "font-style" => style.italic = matches!(value, "italic" | "oblique"),
+        "font-family" => {
+            if let Some(families) = parse_font_family(&declaration.value) {
+                style.font_family = families;
+            }
+        }
+        "text-decoration" | "text-decoration-line" => {
+            if let Some(decoration) = parse_text_decoration(value) {
+                style.text_decoration = decoration;
+            }
+        }
+        "text-transform" => {
+            if let Some(transform) = parse_text_transform(value) {
+                style.text_transform = transform;
+            }
+        }
+        "text-align" => {
+            if let Some(align) = parse_text_align(value) {
+                style.text_align = align;
+            }
+        }
+        "direction" => match value {
+            "ltr" => style.direction = Direction::Ltr,
+            "rtl" => style.direction = Direction::Rtl,
+            _ => {}
+        },
+        "line-height" => {
+            if let Some(line_height) = parse_line_height(value, style.font_size) {
+                style.line_height = line_height;
+            }
+        }
+        "vertical-align" => {
+            if let Some(align) = parse_vertical_align(value, style.font_size) {
+                style.vertical_align = align;
+            }
+        }
+        "letter-spacing" => {
+            if let Some(spacing) = parse_spacing(value, style.font_size) {
+                style.letter_spacing = spacing;
+            }
+        }
+        "word-spacing" => {
+            if let Some(spacing) = parse_spacing(value, style.font_size) {
+                style.word_spacing = spacing;
+            }
+        }
+        "break-before" | "page-break-before" => {
+            if let Some(forced) = parse_page_break(value) {
+                style.break_before = forced;
+            }
+        }
+        "break-after" | "page-break-after" => {
+            if let Some(forced) = parse_page_break(value) {
+                style.break_after = forced;
+            }
+        }
+        "break-inside" | "page-break-inside" => match value {
+            "avoid" | "avoid-page" => style.break_inside_avoid = true,
+            "auto" | "avoid-column" => style.break_inside_avoid = false,
+            _ => {}
+        },
+        "width" => {
+            if let Some(width) = resolve_length(value, style.font_size) {
+                style.width = width;
+            }
+        }
+        "height" => {
+            if let Some(height) = resolve_length(value, style.font_size) {
+                style.height = height;
+            }
+        }
+        "box-shadow" => {
+            if let Some(shadows) = parse_box_shadow(value, style.font_size, style.color) {
+                style.box_shadow = shadows;
+            }
+        }
+        "visibility" => match value {
+            "visible" => style.visibility = Visibility::Visible,
+            "hidden" => style.visibility = Visibility::Hidden,
+            "collapse" => style.visibility = Visibility::Collapse,
+            _ => {}
+        },
+        "box-sizing" | "-webkit-box-sizing" | "-moz-box-sizing" => match value {
+            "content-box" => style.box_sizing = BoxSizing::ContentBox,
+            "border-box" => style.box_sizing = BoxSizing::BorderBox,
+            _ => {}
+        },
+        "position" => match value {
+            "static" | "relative" | "absolute" | "fixed" => style.position = Position::Static,
+            "sticky" | "-webkit-sticky" => style.position = Position::Sticky,
+            _ => {}
+        },
+        "top" | "right" | "bottom" | "left" => {
+            if let Some(length) = resolve_length(value, style.font_size)
+                && let Some(slot) = style.inset.side_mut(name)
+            {
+                *slot = length;
+            }
+        }
+        "aspect-ratio" => {
+            if let Some(ratio) = parse_aspect_ratio(value) {
+                style.aspect_ratio = ratio;
+            }
+        }
+        "object-fit" => match value {
+            "fill" => style.object_fit = ObjectFit::Fill,
+            "contain" => style.object_fit = ObjectFit::Contain,
+            "cover" => style.object_fit = ObjectFit::Cover,
+            "none" => style.object_fit = ObjectFit::None,
+            "scale-down" => style.object_fit = ObjectFit::ScaleDown,
+            _ => {}
+        },
+        "min-width" => {
+            if let Some(width) = resolve_length(value, style.font_size) {
+                style.min_width = width;
+            }
+        }
+        "max-width" => {
+            if let Some(width) = parse_max_size(value, style.font_size) {
+                style.max_width = width;
+            }
+        }
+        "min-height" => {
+            if let Some(height) = resolve_length(value, style.font_size) {
+                style.min_height = height;
+            }
+        }
+        "max-height" => {
+            if let Some(height) = parse_max_size(value, style.font_size) {
+                style.max_height = height;
+            }
+        }
+        "content" => {
+            if let Some(content) = counters::parse_content(&declaration.value) {
+                style.content = content;
+            }
+        }
+        "counter-reset" => {
+            if let Some(list) = counters::parse_counter_list(&declaration.value, 0) {
+                style.counter_reset = list;
+            }
+        }
+        "counter-increment" => {
+            if let Some(list) = counters::parse_counter_list(&declaration.value, 1) {
+                style.counter_increment = list;
+            }
+        }
+        "counter-set" => {
+            if let Some(list) = counters::parse_counter_list(&declaration.value, 0) {
+                style.counter_set = list;
+            }
+        }
+        "animation-name"
+        | "animation-duration"
+        | "animation-timing-function"
+        | "animation-delay"
+        | "animation-iteration-count"
+        | "animation-direction"
+        | "animation-fill-mode"
+        | "animation-play-state" => style.animation.apply(name, &declaration.value),
+        _ => {
+            if let Some(side) = name.strip_prefix("margin-")
+                && let Some(length) = resolve_length(value, style.font_size)
+                && let Some(slot) = style.margin.side_mut(side)
+            {
+                *slot = length;
+            } else if let Some(side) = name.strip_prefix("padding-")
+                && let Some(length) = resolve_length(value, style.font_size)
+                && length != Dimension::Auto
+                && let Some(slot) = style.padding.side_mut(side)
+            {
+                *slot = length;
+            } else if let Some(rest) = name.strip_prefix("border-")
+                && let Some((side, property)) = rest.split_once('-')
+            {
+                apply_border(style, side, property, value);
+            }
+        }
+    }
+}
+
+fn physical_name(name: &str, direction: Direction) -> Option<String> {
+    if !name.contains("-inline-") && !name.contains("-block-") {
+        return None;
+    }
+    let (start, end) = match direction {
+        Direction::Ltr => ("left", "right"),
+        Direction::Rtl => ("right", "left"),
+    };
+    Some(
+        name.replace("inline-start", start)
+            .replace("inline-end", end)
+            .replace("block-start", "top")
+            .replace("block-end", "bottom"),
+    )
+}
+
+fn apply_border(style: &mut ComputedStyle, side: &str, property: &str, value: &str) {
+    match property {
+        "width" => {
+            if let Some(width) = parse_border_width(value, style.font_size)
+                && let Some(slot) = style.border_width.side_mut(side)
+            {
+                *slot = width;
+            }
+        }
+        "style" => {
+            let border_style = match value {
+                "none" | "hidden" => BorderStyle::None,
+                "dashed" => BorderStyle::Dashed,
+                "dotted" => BorderStyle::Dotted,
+                _ if parser::BORDER_STYLES.contains(&value) => BorderStyle::Solid,
+                _ => return,
+            };
+            if let Some(slot) = style.border_style.side_mut(side) {
+                *slot = border_style;
+            }
+            if border_style != BorderStyle::None
+                && let Some(width) = style.border_width.side_mut(side)
+                && *width == 0.0
+            {
+                *width = 3.0;
+            }
+        }
+        "color" => {
+            let current = style.color;
+            if let Some(color) = parse_color(value, current)
+                && let Some(slot) = style.border_color.side_mut(side)
+            {
+                *slot = color;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_display(value: &str) -> Option<Display> {
+    match value {
+        "none" => Some(Display::None),
+        "inline" | "inline-block" | "inline-flex" | "inline-grid" | "inline-table" | "contents" => {
+            Some(Display::Inline)
+        }
+        "block" | "list-item" | "flex" | "grid" | "flow-root" | "table" | "table-row"
+        | "table-cell" | "table-row-group" | "table-header-group" | "table-footer-group"
+        | "table-caption" => Some(Display::Block),
+        _ => None,
+    }
+}
+
+fn parse_white_space(value: &str) -> Option<WhiteSpace> {
+    match value {
+        "normal" => Some(WhiteSpace::Normal),
+        "pre" => Some(WhiteSpace::Pre),
+        "pre-wrap" | "break-spaces" => Some(WhiteSpace::PreWrap),
+        "pre-line" => Some(WhiteSpace::PreLine),
+        "nowrap" => Some(WhiteSpace::NoWrap),
+        _ => None,
+    }
+}
+
+fn parse_text_decoration(value: &str) -> Option<TextDecoration> {
+    let mut decoration = TextDecoration::default();
+    for part in value.split_whitespace() {
+        match part {
+            "none" => {}
+            "underline" => decoration.underline = true,
+            "overline" => decoration.overline = true,
+            "line-through" => decoration.line_through = true,
+            "solid" | "double" | "dotted" | "dashed" | "wavy" => {}
+            _ if Color::parse(part).is_some() || part == "currentcolor" => {}
+            _ => return None,
+        }
+    }
+    Some(decoration)
+}
+
+fn parse_text_transform(value: &str) -> Option<TextTransform> {
+    match value {
+        "none" => Some(TextTransform::None),
+        "uppercase" => Some(TextTransform::Uppercase),
+        "lowercase" => Some(TextTransform::Lowercase),
+        "capitalize" => Some(TextTransform::Capitalize),
+        _ => None,
+    }
+}
+
+fn parse_text_align(value: &str) -> Option<TextAlign> {
+    match value {
+        "start" => Some(TextAlign::Start),
+        "end" => Some(TextAlign::End),
+        "left" => Some(TextAlign::Left),
+        "right" => Some(TextAlign::Right),
+        "center" | "-webkit-center" => Some(TextAlign::Center),
+        "justify" => Some(TextAlign::Justify),
+        _ => None,
+    }
+}
+
+fn parse_line_height(value: &str, font_size: f32) -> Option<LineHeight> {
+    if value == "normal" {
+        return Some(LineHeight::Normal);
+    }
+    if let Ok(number) = value.parse::<f32>() {
+        return (number >= 0.0).then(|| LineHeight::Number(number.min(MAX_LINE_HEIGHT)));
+    }
+    let px = match resolve_length(value, font_size)? {
+        Dimension::Px(px) if px >= 0.0 => px,
+        Dimension::Percent(percent) if percent >= 0.0 => font_size * percent / 100.0,
+        _ => return None,
+    };
+    Some(LineHeight::Px(px.min(MAX_LINE_HEIGHT)))
+}
+
+fn parse_vertical_align(value: &str, font_size: f32) -> Option<VerticalAlign> {
+    let align = match value {
+        "baseline" => VerticalAlign::Baseline,
+        "sub" => VerticalAlign::Sub,
+        "super" => VerticalAlign::Super,
+        "text-top" => VerticalAlign::TextTop,
+        "text-bottom" => VerticalAlign::TextBottom,
+        "middle" => VerticalAlign::Middle,
+        "top" => VerticalAlign::Top,
+        "bottom" => VerticalAlign::Bottom,
+        _ => match resolve_length(value, font_size)? {
+            Dimension::Px(px) => VerticalAlign::Px(px),
+            Dimension::Percent(percent) => VerticalAlign::Percent(percent),
+            Dimension::Auto => return None,
+        },
+    };
+    Some(align)
+}
+
+fn parse_spacing(value: &str, font_size: f32) -> Option<f32> {
+    if value == "normal" {
+        return Some(0.0);
+    }
+    match resolve_length(value, font_size)? {
+        Dimension::Px(px) => Some(px),
+        _ => None,
+    }
+}
+
+fn parse_font_family(value: &str) -> Option<Vec<String>> {
+    let mut families = Vec::new();
+    for family in value.split(',') {
+        let family = family.trim();
+        let unquoted = family
+            .strip_prefix('"')
+            .and_then(|family| family.strip_suffix('"'))
+            .or_else(|| {
+                family
+                    .strip_prefix('\'')
+                    .and_then(|family| family.strip_suffix('\''))
+            });
+        let family = match unquoted {
+            Some(name) => name.to_string(),
+            None => {
+                let name = family.split_whitespace().collect::<Vec<_>>().join(" ");
+                match name.to_ascii_lowercase().as_str() {
+                    "" => return None,
+                    generic @ ("serif" | "sans-serif" | "monospace" | "cursive" | "fantasy"
+                    | "system-ui" | "ui-serif" | "ui-sans-serif" | "ui-monospace") => {
+                        generic.to_string()
+                    }
+                    _ => name,
+                }
+            }
+        };
+        families.push(family);
+    }
+    Some(families)
+}
+
+fn parse_page_break(value: &str) -> Option<bool> {
+    match value {
+        "page" | "always" | "left" | "right" | "recto" | "verso" => Some(true),
+        "auto" | "avoid" | "avoid-page" | "column" | "avoid-column" => Some(false),
+        _ => None,
+    }
+}
+
+fn content(items: &[ContentItem]) -> String {
+    if items.is_empty() {
+        return String::from("none");
+    }
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn font_family(families: &[String]) -> String {
+    if families.is_empty() {
+        return String::from("monospace");
+    }
+    families
+        .iter()
+        .map(|family| {
+            if family.contains(' ') {
+                format!("\"{}\"", family)
+            } else {
+                family.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn page_break(forced: bool) -> &'static str {
+    if forced { "page" } else { "auto" }
+}
+
+fn copy_property(style: &mut ComputedStyle, source: &ComputedStyle, name: &str) {
+    match name {
+        "white-space" => style.white_space = source.white_space,
+        "overflow-wrap" | "word-wrap" => style.overflow_wrap = source.overflow_wrap,
+        "word-break" => style.word_break = source.word_break,
+        "hyphens" | "-webkit-hyphens" => style.hyphens = source.hyphens,
+        "display" => style.display = source.display,
+        "visibility" => style.visibility = source.visibility,
+        "box-shadow" => style.box_shadow = source.box_shadow.clone(),
+        "color" => style.color = source.color,
+        "background-color" => style.background_color = source.background_color,
+        "background-image" => style.background_image = source.background_image.clone(),
+        "font-size" => style.font_size = source.font_size,
+        "font-weight" => style.font_weight = source.font_weight,
+        "font-style" => style.italic = source.italic,
+        "font-family" => style.font_family = source.font_family.clone(),
+        "line-height" => style.line_height = source.line_height,
+        "text-decoration" | "text-decoration-line" => {
+            style.text_decoration = source.text_decoration
+        }
+        "text-transform" => style.text_transform = source.text_transform,
+        "letter-spacing" => style.letter_spacing = source.letter_spacing,
+        "text-align" => style.text_align = source.text_align,
+        "direction" => style.direction = source.direction,
+        "vertical-align" => style.vertical_align = source.vertical_align,
+        "word-spacing" => style.word_spacing = source.word_spacing,
+        "box-sizing" | "-webkit-box-sizing" | "-moz-box-sizing" => {
+            style.box_sizing = source.box_sizing
+        }
+        "position" => style.position = source.position,
+        "top" | "right" | "bottom" | "left" => copy_side(&mut style.inset, &source.inset, name),
+        "aspect-ratio" => style.aspect_ratio = source.aspect_ratio,
+        "object-fit" => style.object_fit = source.object_fit,
+        "width" => style.width = source.width,
+        "height" => style.height = source.height,
+        "min-width" => style.min_width = source.min_width,
+        "max-width" => style.max_width = source.max_width,
+        "min-height" => style.min_height = source.min_height,
+        "max-height" => style.max_height = source.max_height,
+        "break-before" | "page-break-before" => style.break_before = source.break_before,
+        "break-after" | "page-break-after" => style.break_after = source.break_after,
+        "break-inside" | "page-break-inside" => {
+            style.break_inside_avoid = source.break_inside_avoid
+        }
+        "content" => style.content = source.content.clone(),
+        "counter-reset" => style.counter_reset = source.counter_reset.clone(),
+        "counter-increment" => style.counter_increment = source.counter_increment.clone(),
+        "counter-set" => style.counter_set = source.counter_set.clone(),
+        "animation-name"
+        | "animation-duration"
+        | "animation-timing-function"
+        | "animation-delay"
+        | "animation-iteration-count"
+        | "animation-direction"
+        | "animation-fill-mode"
+        | "animation-play-state" => style.animation.copy_from(&source.animation, name),
+        _ => {
+            if let Some(side) = name.strip_prefix("margin-") {
+                copy_side(&mut style.margin, &source.margin, side);
+            } else if let Some(side) = name.strip_prefix("padding-") {
+                copy_side(&mut style.padding, &source.padding, side);
+            } else if let Some(rest) = name.strip_prefix("border-")
+                && let Some((side, property)) = rest.split_once('-')
+            {
+                match property {
+                    "width" => copy_side(&mut style.border_width, &source.border_width, side),
+                    "style" => copy_side(&mut style.border_style, &source.border_style, side),
+                    "color" => copy_side(&mut style.border_color, &source.border_color, side),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn copy_side<T: Copy>(target: &mut Sides<T>, source: &Sides<T>, side: &str) {
+    if let (Some(slot), Some(value)) = (target.side_mut(side), source.side(side)) {
+        *slot = value;
+    }
+}
+
+fn is_stylesheet(node: &Node) -> bool {
+    node.element_name() == Some("style")
+        && node
+            .get_attribute("type")
+            .is_none_or(|mime| mime.is_empty() || mime.eq_ignore_ascii_case("text/css"))
+}
+
+fn slot_style(node: &Rc<Node>) -> Option<Rc<ComputedStyle>> {
+    let parent = node.parent.borrow().upgrade()?;
+    if parent.shadow_root.borrow().is_none() {
+        return None;
+    }
+    let slot = Node::assigned_slot(node)?;
+    slot.style.borrow().clone()
+}
+
+impl Document {
+    pub fn stylesheets(&self) -> Vec<Stylesheet> {
+        let mut stylesheets = vec![ua::stylesheet(&self.settings)];
+        let author = self
+            .get_elements_by_tag_name("style")
+            .into_iter()
+            .filter(|style| is_stylesheet(style))
+            .map(|style| Stylesheet::parse(&style.get_text_content(), Origin::Author));
+        stylesheets.extend(author);
+        stylesheets
+    }
+
+    pub fn restyle(&self) {
+        let span = tracing::info_span!(
+            "style",
+            elements = tracing::field::Empty,
+            rules_matched = tracing::field::Empty,
+            bloom_rejected = tracing::field::Empty
+        )
+        .entered();
+        let generation = invalidation::stylesheet_generation(&self.root);
+        let cached = self.stylist.borrow().clone().filter(|stylist| {
+            stylist.generation == generation
+                && stylist.media.media_type == self.media_type.get()
+                && stylist.media.scripting == self.scripting
+                && stylist.settings == *self.settings
+        });
+        let stylist = match cached {
+            Some(stylist) => {
+                stylist.reset_counters();
+                stylist.set_time(self.timeline.current_time());
+                stylist.restyle_dirty(&self.root, None, &mut AncestorFilter::new(), false);
+                stylist
+            }
+            None => {
+                let stylist = Rc::new(
+                    Stylist::new(self.stylesheets())
+                        .with_generation(generation)
+                        .with_media_type(self.media_type.get())
+                        .with_scripting(self.scripting)
+                        .with_settings(&self.settings),
+                );
+                stylist.set_time(self.timeline.current_time());
+                stylist.style_subtree(&self.root, None);
+                *self.stylist.borrow_mut() = Some(Rc::clone(&stylist));
+                stylist
+            }
+        };
+        counters::generate_content(&self.root, &stylist);
+        span.record("elements", stylist.styled_elements());
+        span.record("rules_matched", stylist.matched_rules());
+        span.record("bloom_rejected", stylist.fast_rejected());
+    }
+
+    pub fn advance_animations(&self, time: f64) -> bool {
+        self.timeline.set_current_time(time);
+        let mut running = false;
+        self.root.walk_rc(&mut |node| {
+            if node
+                .animations
+                .borrow()
+                .iter()
+                .any(AnimationState::is_running)
+            {
+                invalidation::animation_ticked(node);
+                running = true;
+            }
+        });
+        running
+    }
+
+    pub fn animations_running(&self) -> bool {
+        let mut running = false;
+        self.root.walk(&mut |node| {
+            running |= node
+                .animations
+                .borrow()
+                .iter()
+                .any(AnimationState::is_running);
+        });
+        running
+    }
+}
+
+pub fn style_of(node: &Rc<Node>) -> Rc<ComputedStyle> {
+    if let Some(style) = node.style.borrow().as_ref() {
+        return Rc::clone(style);
+    }
+    match node.parent.borrow().upgrade() {
+        Some(parent) => style_of(&parent),
+        None => Rc::new(ComputedStyle::default()),
+    }
+}
@@ -0,0 +1,106 @@
+use html5ever::{LocalName, local_name};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::css::Stylesheet;
+use crate::css::selector::{Selector, Specificity};
+use crate::dom::{Node, NodeData};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RuleEntry {
+    pub sheet: usize,
+    pub rule: usize,
+    pub selector: usize,
+    pub order: usize,
+    pub specificity: Specificity,
+}
+
+#[derive(Default)]
+pub struct RuleMap {
+    ids: HashMap<String, Vec<RuleEntry>>,
+    classes: HashMap<String, Vec<RuleEntry>>,
+    tags: HashMap<LocalName, Vec<RuleEntry>>,
+    universal: Vec<RuleEntry>,
+}
+
+impl RuleMap {
+    pub fn new(stylesheets: &[Stylesheet]) -> Self {
+        let mut map = RuleMap::default();
+        let mut order = 0;
+        for (sheet, stylesheet) in stylesheets.iter().enumerate() {
+            for (rule, css_rule) in stylesheet.rules.iter().enumerate() {
+                order += 1;
+                for (index, selector) in css_rule.selectors.iter().enumerate() {
+                    map.insert(
+                        selector,
+                        RuleEntry {
+                            sheet,
+                            rule,
+                            selector: index,
+                            order,
+                            specificity: selector.specificity(),
+                        },
+                    );
+                }
+            }
+        }
+        map
+    }
+
+    fn insert(&mut self, selector: &Selector, entry: RuleEntry) {
+        let subject = &selector.subject;
+        let bucket = if let Some(id) = &subject.id {
+            self.ids.entry(id.to_string()).or_default()
+        } else if let Some(class) = subject.classes.first() {
+            self.classes.entry(class.to_string()).or_default()
+        } else if let Some(tag) = &subject.tag {
+            self.tags
+                .entry(LocalName::from(tag.to_ascii_lowercase()))
+                .or_default()
+        } else {
+            &mut self.universal
+        };
+        bucket.push(entry);
+    }
+
+    pub fn candidates(&self, element: &Rc<Node>) -> Vec<RuleEntry> {
+        let NodeData::Element { name, attrs } = &element.data else {
+            return Vec::new();
+        };
+
+        let mut candidates = self.universal.clone();
+        if let Some(entries) = self.tags.get(&name.local) {
+            candidates.extend_from_slice(entries);
+        } else if name.local.chars().any(|c| c.is_ascii_uppercase())
+            && let Some(entries) = self
+                .tags
+                .get(&LocalName::from(name.local.to_ascii_lowercase()))
+        {
+            candidates.extend_from_slice(entries);
+        }
+
+        for attr in attrs.borrow().iter() {
+            if attr.name.local == local_name!("id") {
+                if let Some(entries) = self.ids.get(attr.value.as_str()) {
+                    candidates.extend_from_slice(entries);
+                }
+            } else if attr.name.local == local_name!("class") {
+                let mut seen: Vec<&str> = Vec::new();
+                for class in attr.value.split_ascii_whitespace() {
+                    if seen.contains(&class) {
+                        continue;
+                    }
+                    seen.push(class);
+                    if let Some(entries) = self.classes.get(class) {
+                        candidates.extend_from_slice(entries);
+                    }
+                }
+            }
+        }
+
+        candidates.sort_by_key(|entry| (entry.order, entry.selector));
+        candidates
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::OnceLock;
+
+use crate::css::values::Color;
+use crate::css::{Origin, Stylesheet};
+use crate::settings::Settings;
+
+pub const DEFAULT_LINK_COLOR: Color = Color::rgb(0, 0, 238);
+
+const STYLESHEET: &str = r#"
+head, link, meta, script, style, template, title, area, base, datalist, param, rp {
+    display: none
+}
+@media (scripting: enabled) {
+    noscript { display: none }
+}
+
+html, body, address, article, aside, blockquote, center, dd, details, dialog, dir, div,
+dl, dt, fieldset, figcaption, figure, footer, form, h1, h2, h3, h4, h5, h6, header,
+hgroup, hr, legend, listing, main, menu, nav, ol, p, plaintext, pre, search, section,
+summary, ul, xmp {
+    display: block
+}
+li { display: list-item }
+iframe { display: block; width: 300px; height: 150px; border: 2px inset gray }
+table { display: table }
+caption { display: table-caption }
+thead { display: table-header-group }
+tbody { display: table-row-group }
+tfoot { display: table-footer-group }
+tr { display: table-row }
+td, th { display: table-cell }
+
+body { margin: 8px }
+p, dl { margin-top: 1em; margin-bottom: 1em }
+blockquote, figure { margin: 1em 40px }
+dd { margin-inline-start: 40px }
+ul, ol, menu, dir { margin-top: 1em; margin-bottom: 1em; padding-inline-start: 40px }
+ul ul, ul ol, ol ul, ol ol, ul menu, ol menu, menu ul, menu ol {
+    margin-top: 0;
+    margin-bottom: 0
+}
+hr { margin: 0.5em 0; border: 1px inset gray }
+fieldset { margin: 0 2px; padding: 0.35em 0.75em 0.625em; border: 2px groove gray }
+td, th { padding: 1px }
+
+h1 { font-size: 2em; margin-top: 0.67em; margin-bottom: 0.67em }
+h2 { font-size: 1.5em; margin-top: 0.83em; margin-bottom: 0.83em }
+h3 { font-size: 1.17em; margin-top: 1em; margin-bottom: 1em }
+h4 { margin-top: 1.33em; margin-bottom: 1.33em }
+h5 { font-size: 0.83em; margin-top: 1.67em; margin-bottom: 1.67em }
+h6 { font-size: 0.67em; margin-top: 2.33em; margin-bottom: 2.33em }
+h1, h2, h3, h4, h5, h6, b, strong, th, dt, summary, legend { font-weight: bold }
+i, em, cite, var, dfn, address { font-style: italic }
+u, ins { text-decoration: underline }
+s, strike, del { text-decoration: line-through }
+small, sub, sup { font-size: smaller }
+sub { vertical-align: sub }
+sup { vertical-align: super }
+center, th { text-align: center }
+big { font-size: larger }
+
+pre, listing, xmp, plaintext { white-space: pre }
+textarea { white-space: pre-wrap }
+nobr { white-space: nowrap }
+"#;
+
+fn base() -> &'static Stylesheet {
+    static BASE: OnceLock<Stylesheet> = OnceLock::new();
+    BASE.get_or_init(|| Stylesheet::parse(STYLESHEET, Origin::UserAgent))
+}
+
+pub fn stylesheet(settings: &Settings) -> Stylesheet {
+    let link = settings.palette().link.unwrap_or(DEFAULT_LINK_COLOR);
+    let mut sheet = base().clone();
+    sheet.rules.extend(
+        Stylesheet::parse(
+            &format!(
+                ":link {{ color: {}; text-decoration: underline }}",
+                link.to_hex()
+            ),
+            Origin::UserAgent,
+        )
+        .rules,
+    );
+    sheet
+}
@@ -0,0 +1,579 @@
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::dom::{Document, Node, NodeData};
+use crate::html::parser::parse_html;
+use crate::layout::inline::image_placeholder_label;
+use crate::render::tty::DEFAULT_COLUMNS;
+
+const SKIPPED_ELEMENTS: &[&str] = &["head", "script", "style", "template", "title"];
+
+const PARAGRAPH_ELEMENTS: &[&str] = &[
+    "address",
+    "blockquote",
+    "dl",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "ol",
+    "p",
+    "pre",
+    "table",
+    "ul",
+];
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "article",
+    "aside",
+    "body",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "footer",
+    "form",
+    "header",
+    "hgroup",
+    "html",
+    "legend",
+    "li",
+    "main",
+    "nav",
+    "section",
+    "summary",
+    "tr",
+];
+
+const MIN_COLUMN_WIDTH: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkStyle {
+    #[default]
+    Footnotes,
+    Inline,
+    Hidden,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TableStyle {
+    #[default]
+    Ascii,
+    Plain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextOptions {
+    pub width: usize,
+    pub links: LinkStyle,
+    pub tables: TableStyle,
+    pub emphasis: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            width: DEFAULT_COLUMNS,
+            links: LinkStyle::default(),
+            tables: TableStyle::default(),
+            emphasis: false,
+        }
+    }
+}
+
+pub fn to_text(html: &str, options: TextOptions) -> String {
+    parse_html(html).to_text(options)
+}
+
+impl Document {
+    pub fn to_text(&self, options: TextOptions) -> String {
+        let mut converter = Converter {
+            document: self,
+            options,
+            out: String::new(),
+            inline: String::new(),
+            indents: Vec::new(),
+            gap: false,
+            cell: false,
+            links: Vec::new(),
+        };
+        converter.collect(&self.root);
+        converter.flush();
+        if !converter.links.is_empty() {
+            converter.gap = true;
+            let links = std::mem::take(&mut converter.links);
+            for (index, link) in links.iter().enumerate() {
+                converter.line(&format!("[{}] {}", index + 1, link));
+            }
+        }
+        let mut text = converter.out.trim_end().to_string();
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+struct Indent {
+    first: String,
+    rest: String,
+    used: bool,
+}
+
+struct Converter<'a> {
+    document: &'a Document,
+    options: TextOptions,
+    out: String,
+    inline: String,
+    indents: Vec<Indent>,
+    gap: bool,
+    cell: bool,
+    links: Vec<String>,
+}
+
+impl Converter<'_> {
+    fn collect(&mut self, node: &Rc<Node>) {
+        match &node.data {
+            NodeData::Text { contents } => self.push_text(&contents.borrow()),
+            NodeData::Element { .. } => self.element(node),
+            NodeData::Document { .. } | NodeData::ShadowRoot { .. } => self.children(node),
+            _ => {}
+        }
+    }
+
+    fn children(&mut self, node: &Rc<Node>) {
+        let children = node.children.borrow().clone();
+        for child in &children {
+            self.collect(child);
+        }
+    }
+
+    fn element(&mut self, node: &Rc<Node>) {
+        let name = node.element_name().unwrap_or_default();
+        if SKIPPED_ELEMENTS.contains(&name) || node.has_attribute("hidden") {
+            return;
+        }
+        if self.cell {
+            match name {
+                "br" => self.push_text(" "),
+                "img" => self.image(node),
+                "a" => self.link(node),
+                _ if PARAGRAPH_ELEMENTS.contains(&name) || BLOCK_ELEMENTS.contains(&name) => {
+                    self.push_text(" ");
+                    self.children(node);
+                    self.push_text(" ");
+                }
+                _ => self.children(node),
+            }
+            return;
+        }
+        match name {
+            "br" => self.flush(),
+            "img" => self.image(node),
+            "a" => self.link(node),
+            "em" | "i" if self.options.emphasis => self.wrap_inline(node, "_"),
+            "strong" | "b" if self.options.emphasis => self.wrap_inline(node, "*"),
+            "hr" => {
+                self.paragraph();
+                let width = self.available_width().max(MIN_COLUMN_WIDTH);
+                self.line(&"-".repeat(width));
+                self.gap = true;
+            }
+            "pre" => {
+                self.paragraph();
+                let text = node.get_text_content();
+                for line in text.trim_end_matches('\n').lines() {
+                    self.line(line.trim_end());
+                }
+                self.gap = true;
+            }
+            "h1" | "h2" => {
+                self.paragraph();
+                self.children(node);
+                let start = self.out.len();
+                self.flush();
+                let width = self.out[start..]
+                    .lines()
+                    .map(|line| line.chars().count().saturating_sub(self.prefix_width()))
+                    .max()
+                    .unwrap_or(0);
+                if width > 0 {
+                    let rule = if name == "h1" { "=" } else { "-" };
+                    self.line(&rule.repeat(width));
+                }
+                self.gap = true;
+            }
+            "blockquote" => self.indented(node, "> ", "> ", true),
+            "li" => {
+                let marker = list_marker(node);
+                let rest = " ".repeat(marker.chars().count());
+                self.indented(node, &marker, &rest, false);
+            }
+            "dd" => self.indented(node, "    ", "    ", false),
+            "table" => {
+                self.paragraph();
+                self.table(node);
+                self.gap = true;
+            }
+            _ if PARAGRAPH_ELEMENTS.contains(&name) => {
+                let nested = matches!(name, "ul" | "ol" | "dl") && self.in_list(node);
+                if nested {
+                    self.flush();
+                } else {
+                    self.paragraph();
+                }
+                self.children(node);
+                if nested {
+                    self.flush();
+                } else {
+                    self.paragraph();
+                }
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                self.flush();
+                self.children(node);
+                self.flush();
+            }
+            _ => self.children(node),
+        }
+    }
+
+    fn indented(&mut self, node: &Rc<Node>, first: &str, rest: &str, paragraph: bool) {
+        if paragraph {
+            self.paragraph();
+        } else {
+            self.flush();
+        }
+        self.indents.push(Indent {
+            first: first.to_string(),
+            rest: rest.to_string(),
+            used: false,
+        });
+        self.children(node);
+        self.flush();
+        self.indents.pop();
+        if paragraph {
+            self.gap = true;
+        }
+    }
+
+    fn in_list(&self, node: &Rc<Node>) -> bool {
+        let mut current = node.parent.borrow().upgrade();
+        while let Some(parent) = current {
+            if matches!(parent.element_name(), Some("li" | "dd")) {
+                return true;
+            }
+            current = parent.parent.borrow().upgrade();
+        }
+        false
+    }
+
+    fn wrap_inline(&mut self, node: &Rc<Node>, marker: &str) {
+        self.push_word(marker);
+        self.children(node);
+        let trailing = self.inline.ends_with(' ');
+        self.inline.truncate(self.inline.trim_end().len());
+        self.inline.push_str(marker);
+        if trailing {
+            self.inline.push(' ');
+        }
+    }
+
+    fn image(&mut self, node: &Rc<Node>) {
+        let alt = node.get_attribute("alt").unwrap_or_default();
+        let alt = alt.trim();
+        if !alt.is_empty() {
+            self.push_word(&image_placeholder_label(alt));
+        }
+    }
+
+    fn link(&mut self, node: &Rc<Node>) {
+        let start = self.inline.len();
+        self.children(node);
+        let Some(href) = node.get_attribute("href") else {
+            return;
+        };
+        let href = href.trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            return;
+        }
+        let url = self
+            .document
+            .resolve_url(href)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|| href.to_string());
+        let text = self.inline[start..].trim().to_string();
+        let trailing = self.inline.ends_with(' ');
+        self.inline.truncate(self.inline.trim_end().len());
+        match self.options.links {
+            LinkStyle::Hidden => {}
+            LinkStyle::Inline if text == url || text == href => {}
+            LinkStyle::Inline => self.inline.push_str(&format!(" <{}>", url)),
+            LinkStyle::Footnotes => {
+                let index = match self.links.iter().position(|link| *link == url) {
+                    Some(index) => index,
+                    None => {
+                        self.links.push(url);
+                        self.links.len() - 1
+                    }
+                };
+                self.inline.push_str(&format!("[{}]", index + 1));
+            }
+        }
+        if trailing {
+            self.inline.push(' ');
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_whitespace() && c != '\u{a0}' {
+                if !self.inline.is_empty() && !self.inline.ends_with(' ') {
+                    self.inline.push(' ');
+                }
+            } else {
+                self.inline.push(c);
+            }
+        }
+    }
+
+    fn push_word(&mut self, word: &str) {
+        if !self.inline.is_empty() && !self.inline.ends_with(' ') && !word.starts_with(['_', '*']) {
+            self.inline.push(' ');
+        }
+        self.inline.push_str(word);
+    }
+
+    fn paragraph(&mut self) {
+        self.flush();
+        self.gap = true;
+    }
+
+    fn prefix_width(&self) -> usize {
+        self.indents
+            .iter()
+            .map(|indent| indent.first.chars().count())
+            .sum()
+    }
+
+    fn available_width(&self) -> usize {
+        match self.options.width {
+            0 => usize::MAX,
+            width => width.saturating_sub(self.prefix_width()).max(1),
+        }
+    }
+
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.inline);
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        for line in wrap(text, self.available_width()) {
+            self.line(&line);
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        if self.gap && !self.out.is_empty() {
+            let blank: String = self
+                .indents
+                .iter()
+                .filter(|indent| indent.used)
+                .map(|indent| indent.rest.as_str())
+                .collect();
+            self.out.push_str(blank.trim_end());
+            self.out.push('\n');
+        }
+        self.gap = false;
+        for indent in &mut self.indents {
+            if indent.used {
+                self.out.push_str(&indent.rest);
+            } else {
+                self.out.push_str(&indent.first);
+                indent.used = true;
+            }
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn cell_text(&mut self, cell: &Rc<Node>) -> String {
+        let saved = std::mem::take(&mut self.inline);
+        let was_cell = std::mem::replace(&mut self.cell, true);
+        self.children(cell);
+        self.cell = was_cell;
+        let text = std::mem::replace(&mut self.inline, saved);
+        text.trim().to_string()
+    }
+
+    fn table(&mut self, table: &Rc<Node>) {
+        let mut rows: Vec<(bool, Vec<String>)> = Vec::new();
+        for row in table_rows(table) {
+            let cells: Vec<Rc<Node>> = row
+                .children
+                .borrow()
+                .iter()
+                .filter(|cell| matches!(cell.element_name(), Some("td" | "th")))
+                .cloned()
+                .collect();
+            if cells.is_empty() {
+                continue;
+            }
+            let header = cells.iter().all(|cell| cell.element_name() == Some("th"));
+            let texts = cells.iter().map(|cell| self.cell_text(cell)).collect();
+            rows.push((header, texts));
+        }
+        let columns = rows.iter().map(|(_, cells)| cells.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let mut widths = vec![0; columns];
+        for (_, cells) in &rows {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let (separator, padding) = match self.options.tables {
+            TableStyle::Ascii => (3, 4),
+            TableStyle::Plain => (2, 0),
+        };
+        let available = self.available_width();
+        while widths.iter().sum::<usize>() + separator * (columns - 1) + padding > available {
+            let Some(width) = widths.iter_mut().rev().max_by_key(|width| **width) else {
+                break;
+            };
+            if *width <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            *width -= 1;
+        }
+
+        let rule = |fill: &str| {
+            let cells: Vec<String> = widths.iter().map(|width| fill.repeat(width + 2)).collect();
+            format!("+{}+", cells.join("+"))
+        };
+        let ascii = self.options.tables == TableStyle::Ascii;
+        if ascii {
+            self.line(&rule("-"));
+        }
+        for (index, (header, cells)) in rows.iter().enumerate() {
+            let wrapped: Vec<Vec<String>> = widths
+                .iter()
+                .enumerate()
+                .map(|(column, width)| {
+                    cells
+                        .get(column)
+                        .map(|cell| wrap(cell, *width))
+                        .unwrap_or_default()
+                })
+                .collect();
+            let height = wrapped.iter().map(Vec::len).max().unwrap_or(0).max(1);
+            for line in 0..height {
+                let parts: Vec<String> = wrapped
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| {
+                        let text = cell.get(line).map(String::as_str).unwrap_or("");
+                        format!("{:<width$}", text, width = *width)
+                    })
+                    .collect();
+                let text = if ascii {
+                    format!("| {} |", parts.join(" | "))
+                } else {
+                    parts.join("  ").trim_end().to_string()
+                };
+                self.line(&text);
+            }
+            let last = index + 1 == rows.len();
+            if ascii && (last || *header) {
+                self.line(&rule(if *header && !last { "=" } else { "-" }));
+            }
+        }
+    }
+}
+
+fn table_rows(table: &Rc<Node>) -> Vec<Rc<Node>> {
+    let mut rows = Vec::new();
+    for child in table.children.borrow().iter() {
+        match child.element_name() {
+            Some("tr") => rows.push(Rc::clone(child)),
+            Some("thead" | "tbody" | "tfoot") => rows.extend(
+                child
+                    .children
+                    .borrow()
+                    .iter()
+                    .filter(|row| row.element_name() == Some("tr"))
+                    .cloned(),
+            ),
+            _ => {}
+        }
+    }
+    rows
+}
+
+fn list_marker(item: &Rc<Node>) -> String {
+    let Some(list) = item.parent.borrow().upgrade() else {
+        return String::from("* ");
+    };
+    if list.element_name() != Some("ol") {
+        return String::from("* ");
+    }
+    let start: i64 = list
+        .get_attribute("start")
+        .and_then(|start| start.trim().parse().ok())
+        .unwrap_or(1);
+    let mut number = start;
+    for sibling in list.children.borrow().iter() {
+        if Rc::ptr_eq(sibling, item) {
+            break;
+        }
+        if sibling.element_name() == Some("li") {
+            number += 1;
+        }
+    }
+    format!("{}. ", number)
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut length = 0;
+    for mut word in text.split(' ').filter(|word| !word.is_empty()) {
+        let mut word_length = word.chars().count();
+        if length > 0 && length + 1 + word_length > width {
+            lines.push(std::mem::take(&mut line));
+            length = 0;
+        }
+        // A word wider than the whole line is broken wherever it hits the
+        // edge.
+        while word_length > width {
+            let split = word
+                .char_indices()
+                .nth(width)
+                .map_or(word.len(), |(index, _)| index);
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+            word_length -= width;
+        }
+        if length > 0 {
+            line.push(' ');
+            length += 1;
+        }
+        line.push_str(word);
+        length += word_length;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
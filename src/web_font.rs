@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use flate2::read::ZlibDecoder;
+use std::collections::HashSet;
+use std::io::Read;
+use std::string::String;
+use std::sync::Arc;
+use std::vec::Vec;
+use url::Url;
+
+use crate::css::font_face::{FontFace, FontSource};
+use crate::dom::Document;
+use crate::net;
+use crate::settings::Settings;
+use crate::woff2;
+
+const SUPPORTED_FORMATS: &[&str] = &["truetype", "opentype", "woff", "woff2", "collection"];
+
+// Table lengths come from the font itself, so the decoded size is capped
+// before anything is allocated for it. Real fonts stay far below this.
+pub(crate) const MAX_FONT_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct WebFont {
+    pub family: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub data: Arc<Vec<u8>>,
+}
+
+#[derive(Default)]
+pub struct FontLoader {
+    requested: HashSet<(String, bool, bool)>,
+}
+
+impl FontLoader {
+    pub fn new() -> Self {
+        FontLoader::default()
+    }
+
+    pub fn load(&mut self, document: &Document) -> Vec<WebFont> {
+        let span = tracing::info_span!("fonts", loaded = tracing::field::Empty).entered();
+        let mut loaded = Vec::new();
+        for sheet in document.stylesheets() {
+            for face in &sheet.font_faces {
+                let key = (face.family.to_lowercase(), face.bold, face.italic);
+                if !self.requested.insert(key) {
+                    continue;
+                }
+                match load_face(document, face) {
+                    Some(data) => loaded.push(WebFont {
+                        family: face.family.clone(),
+                        bold: face.bold,
+                        italic: face.italic,
+                        data: Arc::new(data),
+                    }),
+                    None => log::warn!("no usable source for font family {:?}", face.family),
+                }
+            }
+        }
+        span.record("loaded", loaded.len());
+        loaded
+    }
+}
+
+fn load_face(document: &Document, face: &FontFace) -> Option<Vec<u8>> {
+    face.sources.iter().find_map(|source| {
+        let FontSource::Url { url, format } = source else {
+            return None;
+        };
+        if format
+            .as_deref()
+            .is_some_and(|format| !SUPPORTED_FORMATS.contains(&format))
+        {
+            return None;
+        }
+        let url = document.resolve_url(url)?;
+        match fetch_font(&url, &document.settings) {
+            Ok(data) => Some(data),
+            Err(err) => {
+                log::warn!("failed to load font {}: {:#}", url, err);
+                None
+            }
+        }
+    })
+}
+
+pub fn fetch_font(url: &Url, settings: &Settings) -> Result<Vec<u8>> {
+    let response = net::fetch(url, settings)?;
+    if !response.info().is_success() {
+        anyhow::bail!("HTTP {}", response.status);
+    }
+    decode(&response.read_body()?)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<u8>> {
+    match bytes.get(..4).context("truncated font")? {
+        b"wOFF" => decode_woff(bytes),
+        b"wOF2" => woff2::decode(bytes),
+        [0, 1, 0, 0] | b"OTTO" | b"true" | b"ttcf" => Ok(bytes.to_vec()),
+        _ => anyhow::bail!("unrecognized font format"),
+    }
+}
+
+fn decode_woff(bytes: &[u8]) -> Result<Vec<u8>> {
+    let flavor = read_u32(bytes, 4)?;
+    let count = read_u16(bytes, 12)?;
+    let mut tables = Vec::with_capacity(count as usize);
+    let mut total = 0usize;
+    for index in 0..count as usize {
+        let entry = 44 + index * 20;
+        let tag = read_u32(bytes, entry)?;
+        let offset = read_u32(bytes, entry + 4)? as usize;
+        let stored_length = read_u32(bytes, entry + 8)? as usize;
+        let length = read_u32(bytes, entry + 12)? as usize;
+        let checksum = read_u32(bytes, entry + 16)?;
+        total = total.saturating_add(length);
+        if total > MAX_FONT_SIZE {
+            anyhow::bail!("WOFF font is too large");
+        }
+        let stored = offset
+            .checked_add(stored_length)
+            .and_then(|end| bytes.get(offset..end))
+            .context("truncated WOFF table")?;
+        let data = if stored_length < length {
+            let mut data = Vec::new();
+            ZlibDecoder::new(stored)
+                .take(length as u64 + 1)
+                .read_to_end(&mut data)
+                .context("inflating WOFF table")?;
+            data
+        } else {
+            stored.to_vec()
+        };
+        if data.len() != length {
+            anyhow::bail!("WOFF table has the wrong length");
+        }
+        tables.push((tag, checksum, data));
+    }
+    Ok(write_sfnt(flavor, &tables))
+}
+
+/// Lays out `tables` (tag, checksum, data), already sorted by tag, as an
+/// sfnt font with a table directory in front.
+pub(crate) fn write_sfnt(flavor: u32, tables: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = count.max(1).ilog2() as u16;
+    let search_range = (1u16 << entry_selector).saturating_mul(16);
+    let range_shift = count.saturating_mul(16).saturating_sub(search_range);
+    let mut sfnt = Vec::new();
+    sfnt.extend_from_slice(&flavor.to_be_bytes());
+    for value in [count, search_range, entry_selector, range_shift] {
+        sfnt.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, checksum, data) in tables {
+        for value in [*tag, *checksum, offset as u32, data.len() as u32] {
+            sfnt.extend_from_slice(&value.to_be_bytes());
+        }
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, _, data) in tables {
+        sfnt.extend_from_slice(data);
+        sfnt.resize(sfnt.len().next_multiple_of(4), 0);
+    }
+    sfnt
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Result<u16> {
+    let bytes = bytes.get(at..at + 2).context("truncated font header")?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Result<u32> {
+    let bytes = bytes.get(at..at + 4).context("truncated font header")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
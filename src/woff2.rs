@@ -0,0 +1,453 @@
+use anyhow::{Context, Result, bail, ensure};
+use brotli_decompressor::Decompressor;
+use std::io::Read;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::web_font::{MAX_FONT_SIZE, write_sfnt};
+
+const HEADER_SIZE: usize = 48;
+const TTC_FLAVOR: u32 = u32::from_be_bytes(*b"ttcf");
+const GLYF: u32 = u32::from_be_bytes(*b"glyf");
+const LOCA: u32 = u32::from_be_bytes(*b"loca");
+
+// Tag indices 0..=62 of the table directory's flags byte; 63 means the tag
+// follows explicitly.
+const KNOWN_TAGS: [&[u8; 4]; 63] = [
+    b"cmap", b"head", b"hhea", b"hmtx", b"maxp", b"name", b"OS/2", b"post", b"cvt ", b"fpgm",
+    b"glyf", b"loca", b"prep", b"CFF ", b"VORG", b"EBDT", b"EBLC", b"gasp", b"hdmx", b"kern",
+    b"LTSH", b"PCLT", b"VDMX", b"vhea", b"vmtx", b"BASE", b"GDEF", b"GPOS", b"GSUB", b"EBSC",
+    b"JSTF", b"MATH", b"CBDT", b"CBLC", b"COLR", b"CPAL", b"SVG ", b"sbix", b"acnt", b"avar",
+    b"bdat", b"bloc", b"bsln", b"cvar", b"fdsc", b"feat", b"fmtx", b"fvar", b"gvar", b"hsty",
+    b"just", b"lcar", b"mort", b"morx", b"opbd", b"prop", b"trak", b"Zapf", b"Silf", b"Glat",
+    b"Gloc", b"Feat", b"Sill",
+];
+
+struct TableEntry {
+    tag: u32,
+    transformed: bool,
+    length: usize,
+    stored_length: usize,
+}
+
+/// Decodes a WOFF2 font into a plain sfnt. Transformed `glyf` and `loca`
+/// tables are rebuilt; a transformed `hmtx` and font collections are not
+/// supported.
+pub fn decode(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut header = Reader::new(bytes);
+    ensure!(
+        header.u32()? == u32::from_be_bytes(*b"wOF2"),
+        "not a WOFF2 font"
+    );
+    let flavor = header.u32()?;
+    if flavor == TTC_FLAVOR {
+        bail!("WOFF2 font collections are not supported");
+    }
+    header.skip(4)?;
+    let count = header.u16()?;
+    header.skip(6)?;
+    let compressed_length = header.u32()? as usize;
+
+    let mut directory = Reader::new(bytes.get(HEADER_SIZE..).context("truncated WOFF2 header")?);
+    let mut entries = Vec::with_capacity(count as usize);
+    let mut total = 0usize;
+    for _ in 0..count {
+        let flags = directory.u8()?;
+        let tag = match flags & 0x3f {
+            63 => directory.u32()?,
+            index => u32::from_be_bytes(*KNOWN_TAGS[index as usize]),
+        };
+        let version = flags >> 6;
+        // For glyf and loca version 0 is the transform and 3 the null
+        // transform; every other table has it the other way round.
+        let transformed = if tag == GLYF || tag == LOCA {
+            version != 3
+        } else {
+            version != 0
+        };
+        let length = directory.base128()? as usize;
+        let stored_length = if transformed {
+            directory.base128()? as usize
+        } else {
+            length
+        };
+        total = total.saturating_add(stored_length).saturating_add(length);
+        ensure!(total <= MAX_FONT_SIZE, "WOFF2 font is too large");
+        entries.push(TableEntry {
+            tag,
+            transformed,
+            length,
+            stored_length,
+        });
+    }
+
+    let start = HEADER_SIZE + directory.position;
+    let compressed = start
+        .checked_add(compressed_length)
+        .and_then(|end| bytes.get(start..end))
+        .context("truncated WOFF2 data")?;
+    let expected: usize = entries.iter().map(|entry| entry.stored_length).sum();
+    let mut data = Vec::new();
+    Decompressor::new(compressed, 4096)
+        .take(expected as u64 + 1)
+        .read_to_end(&mut data)
+        .context("decompressing WOFF2 data")?;
+    ensure!(data.len() == expected, "WOFF2 data has the wrong length");
+
+    let mut offset = 0;
+    let mut stored = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        stored.push(&data[offset..offset + entry.stored_length]);
+        offset += entry.stored_length;
+    }
+
+    let glyf = entries.iter().position(|entry| entry.tag == GLYF);
+    let loca = entries.iter().position(|entry| entry.tag == LOCA);
+    let mut rebuilt = None;
+    if let Some(glyf) = glyf.filter(|&glyf| entries[glyf].transformed) {
+        let loca = loca
+            .filter(|&loca| entries[loca].transformed)
+            .context("WOFF2 glyf is transformed but loca isn't")?;
+        let (glyf_data, loca_data) = rebuild_glyf(stored[glyf])?;
+        ensure!(
+            loca_data.len() == entries[loca].length,
+            "rebuilt WOFF2 loca has the wrong length"
+        );
+        rebuilt = Some((glyf_data, loca_data));
+    }
+
+    let mut tables = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let table = match &rebuilt {
+            Some((glyf, _)) if entry.tag == GLYF => glyf.clone(),
+            Some((_, loca)) if entry.tag == LOCA => loca.clone(),
+            _ if entry.transformed => bail!(
+                "WOFF2 transform of the {} table is not supported",
+                String::from_utf8_lossy(&entry.tag.to_be_bytes())
+            ),
+            _ => stored[index].to_vec(),
+        };
+        tables.push((entry.tag, checksum(&table), table));
+    }
+    tables.sort_by_key(|(tag, _, _)| *tag);
+    Ok(write_sfnt(flavor, &tables))
+}
+
+fn checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+/// Rebuilds `glyf` and `loca` from the transformed glyf table, which splits
+/// the glyph data into separate streams (section 5.1 of the WOFF2 spec).
+fn rebuild_glyf(table: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut header = Reader::new(table);
+    header.skip(2)?;
+    let options = header.u16()?;
+    let glyph_count = header.u16()? as usize;
+    let index_format = header.u16()?;
+    let mut sizes = [0usize; 7];
+    for size in &mut sizes {
+        *size = header.u32()? as usize;
+    }
+
+    let mut offset = header.position;
+    let mut stream = |size: usize| -> Result<Reader> {
+        let stream = offset
+            .checked_add(size)
+            .and_then(|end| table.get(offset..end))
+            .context("truncated WOFF2 glyf stream")?;
+        offset += size;
+        Ok(Reader::new(stream))
+    };
+    let mut contours = stream(sizes[0])?;
+    let mut points = stream(sizes[1])?;
+    let mut flags = stream(sizes[2])?;
+    let mut glyphs = stream(sizes[3])?;
+    let mut composites = stream(sizes[4])?;
+    let bboxes = stream(sizes[5])?.data;
+    let mut instructions = stream(sizes[6])?;
+    let overlaps = if options & 1 != 0 {
+        stream(glyph_count.div_ceil(8))?.data
+    } else {
+        &[]
+    };
+
+    let bitmap_length = glyph_count.div_ceil(32) * 4;
+    let bbox_bitmap = bboxes
+        .get(..bitmap_length)
+        .context("truncated WOFF2 bbox bitmap")?;
+    let mut bboxes = Reader::new(&bboxes[bitmap_length..]);
+    let has_bit = |bitmap: &[u8], index: usize| bitmap[index / 8] & (0x80 >> (index % 8)) != 0;
+
+    let mut glyf = Vec::new();
+    let mut offsets = Vec::with_capacity(glyph_count + 1);
+    for index in 0..glyph_count {
+        offsets.push(glyf.len());
+        let contour_count = contours.u16()? as i16;
+        let explicit_bbox = has_bit(bbox_bitmap, index);
+
+        if contour_count == 0 {
+            ensure!(!explicit_bbox, "empty WOFF2 glyph has a bounding box");
+        } else if contour_count < 0 {
+            ensure!(explicit_bbox, "composite WOFF2 glyph has no bounding box");
+            glyf.extend_from_slice(&contour_count.to_be_bytes());
+            glyf.extend_from_slice(bboxes.take(8)?);
+            let start = composites.position;
+            let mut has_instructions = false;
+            loop {
+                let flags = composites.u16()?;
+                has_instructions |= flags & WE_HAVE_INSTRUCTIONS != 0;
+                let mut size = 2;
+                size += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                    4
+                } else {
+                    2
+                };
+                if flags & WE_HAVE_A_SCALE != 0 {
+                    size += 2;
+                } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                    size += 4;
+                } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                    size += 8;
+                }
+                composites.skip(size)?;
+                if flags & MORE_COMPONENTS == 0 {
+                    break;
+                }
+            }
+            glyf.extend_from_slice(&composites.data[start..composites.position]);
+            if has_instructions {
+                let length = glyphs.u255()?;
+                glyf.extend_from_slice(&length.to_be_bytes());
+                glyf.extend_from_slice(instructions.take(length as usize)?);
+            }
+        } else {
+            let mut end_points = Vec::with_capacity(contour_count as usize);
+            let mut point_count = 0usize;
+            for _ in 0..contour_count {
+                point_count += points.u255()? as usize;
+                ensure!(
+                    (1..=0x10000).contains(&point_count),
+                    "WOFF2 glyph has a bad point count"
+                );
+                end_points.push((point_count - 1) as u16);
+            }
+            let on_curve = flags.take(point_count)?;
+            let mut coordinates = Vec::with_capacity(point_count);
+            let (mut x, mut y) = (0i32, 0i32);
+            for &flag in on_curve {
+                let (dx, dy) = triplet(flag & 0x7f, &mut glyphs)?;
+                x += dx;
+                y += dy;
+                coordinates.push((x, y, flag >> 7 == 0));
+            }
+            let instruction_length = glyphs.u255()?;
+
+            glyf.extend_from_slice(&contour_count.to_be_bytes());
+            if explicit_bbox {
+                glyf.extend_from_slice(bboxes.take(8)?);
+            } else {
+                let bbox = coordinates.iter().fold(
+                    (i32::MAX, i32::MAX, i32::MIN, i32::MIN),
+                    |(x_min, y_min, x_max, y_max), &(x, y, _)| {
+                        (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+                    },
+                );
+                for value in [bbox.0, bbox.1, bbox.2, bbox.3] {
+                    glyf.extend_from_slice(&(value as i16).to_be_bytes());
+                }
+            }
+            for end in end_points {
+                glyf.extend_from_slice(&end.to_be_bytes());
+            }
+            glyf.extend_from_slice(&instruction_length.to_be_bytes());
+            glyf.extend_from_slice(instructions.take(instruction_length as usize)?);
+            let overlap = !overlaps.is_empty() && has_bit(overlaps, index);
+            write_points(&mut glyf, &coordinates, overlap);
+        }
+        glyf.resize(glyf.len().next_multiple_of(4), 0);
+    }
+    offsets.push(glyf.len());
+
+    let mut loca = Vec::with_capacity(offsets.len() * 4);
+    for offset in offsets {
+        if index_format == 0 {
+            ensure!(
+                offset / 2 <= u16::MAX as usize,
+                "glyf is too large for short loca"
+            );
+            loca.extend_from_slice(&((offset / 2) as u16).to_be_bytes());
+        } else {
+            loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+    Ok((glyf, loca))
+}
+
+/// Decodes one point's delta from the glyph stream using the triplet
+/// encoding table (section 5.2 of the WOFF2 spec).
+fn triplet(flag: u8, glyphs: &mut Reader) -> Result<(i32, i32)> {
+    let with_sign = |flag: u8, value: i32| if flag & 1 != 0 { value } else { -value };
+    let flag_value = flag as i32;
+    Ok(match flag {
+        0..=9 => {
+            let b0 = glyphs.u8()? as i32;
+            (0, with_sign(flag, ((flag_value & 14) << 7) + b0))
+        }
+        10..=19 => {
+            let b0 = glyphs.u8()? as i32;
+            (with_sign(flag, (((flag_value - 10) & 14) << 7) + b0), 0)
+        }
+        20..=83 => {
+            let base = flag_value - 20;
+            let b1 = glyphs.u8()? as i32;
+            (
+                with_sign(flag, 1 + (base & 0x30) + (b1 >> 4)),
+                with_sign(flag >> 1, 1 + ((base & 0x0c) << 2) + (b1 & 0x0f)),
+            )
+        }
+        84..=119 => {
+            let base = flag_value - 84;
+            let b1 = glyphs.u8()? as i32;
+            let b2 = glyphs.u8()? as i32;
+            (
+                with_sign(flag, 1 + ((base / 12) << 8) + b1),
+                with_sign(flag >> 1, 1 + (((base % 12) >> 2) << 8) + b2),
+            )
+        }
+        120..=123 => {
+            let b1 = glyphs.u8()? as i32;
+            let b2 = glyphs.u8()? as i32;
+            let b3 = glyphs.u8()? as i32;
+            (
+                with_sign(flag, (b1 << 4) + (b2 >> 4)),
+                with_sign(flag >> 1, ((b2 & 0x0f) << 8) + b3),
+            )
+        }
+        _ => {
+            let dx = glyphs.u16()? as i32;
+            let dy = glyphs.u16()? as i32;
+            (with_sign(flag, dx), with_sign(flag >> 1, dy))
+        }
+    })
+}
+
+// Writes the flags and coordinate arrays of a simple glyph, using the short
+// forms where a delta fits in a byte. Flags aren't run-length compressed.
+fn write_points(glyf: &mut Vec<u8>, points: &[(i32, i32, bool)], overlap: bool) {
+    let mut flags = Vec::with_capacity(points.len());
+    let mut xs = Vec::new();
+    let mut ys = Vec::new();
+    let (mut last_x, mut last_y) = (0, 0);
+    for (index, &(x, y, on_curve)) in points.iter().enumerate() {
+        let mut flag = if on_curve { ON_CURVE } else { 0 };
+        if index == 0 && overlap {
+            flag |= OVERLAP_SIMPLE;
+        }
+        let (dx, dy) = (x - last_x, y - last_y);
+        (last_x, last_y) = (x, y);
+        flag |= write_delta(&mut xs, dx, X_SHORT, X_SAME_OR_POSITIVE);
+        flag |= write_delta(&mut ys, dy, Y_SHORT, Y_SAME_OR_POSITIVE);
+        flags.push(flag);
+    }
+    glyf.extend_from_slice(&flags);
+    glyf.extend_from_slice(&xs);
+    glyf.extend_from_slice(&ys);
+}
+
+fn write_delta(out: &mut Vec<u8>, delta: i32, short: u8, same_or_positive: u8) -> u8 {
+    if delta == 0 {
+        same_or_positive
+    } else if delta.abs() < 256 {
+        out.push(delta.unsigned_abs() as u8);
+        short | if delta > 0 { same_or_positive } else { 0 }
+    } else {
+        out.extend_from_slice(&(delta as i16).to_be_bytes());
+        0
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, position: 0 }
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .position
+            .checked_add(length)
+            .and_then(|end| self.data.get(self.position..end))
+            .context("truncated WOFF2 data")?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<()> {
+        self.take(length).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// `UIntBase128`: big-endian groups of seven bits, at most five bytes.
+    fn base128(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for index in 0..5 {
+            let byte = self.u8()?;
+            ensure!(
+                !(index == 0 && byte == 0x80),
+                "UIntBase128 has a leading zero"
+            );
+            ensure!(value & 0xfe00_0000 == 0, "UIntBase128 overflows");
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("UIntBase128 is longer than five bytes")
+    }
+
+    /// `255UInt16`: one byte, or a marker byte followed by one or two more.
+    fn u255(&mut self) -> Result<u16> {
+        Ok(match self.u8()? {
+            253 => self.u16()?,
+            254 => 506 + self.u8()? as u16,
+            255 => 253 + self.u8()? as u16,
+            code => code as u16,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use icarus::html::parser::parse_html;
+
+#[test]
+fn hidden_elements_stay_out_of_the_tree() {
+    let document = parse_html(
+        "<button>Shown</button>
+         <button hidden>Attribute</button>
+         <button aria-hidden=true>Aria</button>
+         <button style='display: none'>Display</button>
+         <button style='visibility: hidden'>Visibility</button>
+         <style>.gone { display: none }</style>
+         <div class=gone><button>Nested</button></div>",
+    );
+    let dump = document.accessibility_tree().dump();
+    assert!(dump.contains("button \"Shown\""));
+    for hidden in ["Attribute", "Aria", "Display", "Visibility", "Nested"] {
+        assert!(!dump.contains(hidden), "{} in {}", hidden, dump);
+    }
+}
+
+#[test]
+fn hidden_content_is_left_out_of_accessible_names() {
+    let document =
+        parse_html("<a href=/x>Visible <span style='display: none'>secret</span>text</a>");
+    let dump = document.accessibility_tree().dump();
+    assert!(dump.contains("link \"Visible text\""), "{}", dump);
+}
@@ -0,0 +1,101 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::layout::Rect;
+use icarus::settings::Settings;
+use icarus::shell::address_bar::{AddressBar, resolve};
+use icarus::shell::pipeline::Event;
+use icarus::shell::tab::{COMMITTED, Tab};
+use icarus::storage::StorageContext;
+
+mod common;
+
+#[test]
+fn typing_replaces_the_focused_url_then_edits_in_place() {
+    let mut bar = AddressBar::default();
+    bar.focus("http://example.com/");
+    assert!(bar.focused && bar.selected);
+    assert_eq!(bar.cursor(), bar.text.len());
+
+    bar.insert('e');
+    assert_eq!(bar.text, "e");
+    for c in "xämple.org".chars() {
+        bar.insert(c);
+    }
+    bar.home();
+    bar.delete();
+    bar.insert('E');
+    bar.end();
+    bar.backspace();
+    bar.move_left();
+    bar.move_left();
+    bar.insert('_');
+    assert_eq!(bar.text, "Exämple._or");
+    assert_eq!(bar.cursor(), "Exämple._".len());
+
+    bar.focus("x");
+    bar.backspace();
+    assert_eq!(bar.text, "");
+    bar.blur();
+    assert!(!bar.focused && !bar.selected);
+}
+
+#[test]
+fn input_resolves_to_urls_or_searches() {
+    assert_eq!(resolve("  "), None);
+    assert_eq!(
+        resolve("https://example.com/a b").as_deref(),
+        Some("https://example.com/a%20b")
+    );
+    assert_eq!(resolve("about:blank").as_deref(), Some("about:blank"));
+    assert_eq!(
+        resolve("example.com/path").as_deref(),
+        Some("http://example.com/path")
+    );
+    assert_eq!(
+        resolve("localhost:8080").as_deref(),
+        Some("http://localhost:8080/")
+    );
+    assert_eq!(
+        resolve("rust borrow checker").as_deref(),
+        Some("https://duckduckgo.com/html/?q=rust+borrow+checker")
+    );
+}
+
+#[test]
+fn committed_url_follows_redirects() {
+    let port = common::serve_with(|request, mut stream| {
+        let response = if request.starts_with("GET /old ") {
+            "HTTP/1.1 302 Found\r\nLocation: /new\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        } else {
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 7\r\nConnection: close\r\n\r\n<p>new\n"
+        };
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+
+    let mut tab = Tab::open(
+        &format!("http://127.0.0.1:{}/old", port),
+        Rect::new(0.0, 0.0, 400.0, 300.0),
+        Arc::new(Settings::default()),
+        StorageContext::default(),
+    )
+    .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let committed = 'wait: loop {
+        for event in tab.pipeline.poll().unwrap() {
+            if let Event::Committed(url) = event {
+                break 'wait url;
+            }
+        }
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for the commit"
+        );
+        thread::sleep(Duration::from_millis(5));
+    };
+    tab.commit(committed);
+    assert_eq!(tab.url(), format!("http://127.0.0.1:{}/new", port));
+    assert_eq!(tab.progress, Some(COMMITTED));
+}
@@ -0,0 +1,43 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::render::tty;
+
+fn placeholders(html: &str) -> Vec<String> {
+    let document = parse_html(html);
+    LayoutTree::build(&document, LayoutOptions::default())
+        .dump()
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("placeholder"))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn tty_shows_the_alt_text_label() {
+    let document = parse_html("<p>a <img src=missing.png alt=' A cat '> b</p>");
+    let text = tty::render(&document, 40);
+    assert!(text.contains("[image: A cat]"), "{:?}", text);
+}
+
+#[test]
+fn placeholder_width_comes_from_the_label_or_attributes() {
+    let lines = placeholders("<p><img alt=cat></p>");
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+    assert!(lines[0].starts_with("placeholder \"cat\""), "{}", lines[0]);
+
+    let wide = placeholders("<p><img alt=cat width=300 height=50></p>");
+    assert!(wide[0].ends_with(" 300x50"), "{}", wide[0]);
+
+    let short = placeholders("<p><img alt=cat width=120 height=2></p>");
+    assert!(short[0].ends_with(" 120x19.2"), "{}", short[0]);
+}
+
+#[test]
+fn decorative_images_take_no_space() {
+    assert!(placeholders("<p>x<img alt=''>y</p>").is_empty());
+    assert_eq!(
+        placeholders("<p><img alt='' width=10 height=30></p>").len(),
+        1
+    );
+}
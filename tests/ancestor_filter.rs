@@ -0,0 +1,165 @@
+use std::rc::Rc;
+
+use icarus::css::bloom::{AncestorFilter, element_hashes, hash_name};
+use icarus::css::values::Color;
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+const BODY: &str = "<div id=main class='wide page'>
+      <section class=intro><p>one <em>two</em></p><span class=note><em>three</em></span></section>
+      <article><div class=page><p class=lead><b>four</b></p></div><ul><li><em>five</em></li></ul></article>
+    </div>
+    <p><em>six</em></p>";
+
+const SELECTORS: &[&str] = &[
+    "div p",
+    "div > p",
+    "#main em",
+    ".wide section > p em",
+    "article div.page > p b",
+    "section > .note em",
+    "div > section em",
+    "body > p em",
+    "article > p",
+    ".page .page p",
+    "ul li > em",
+    "#main > article li em",
+    "html em",
+    "span em",
+];
+
+const SIBLING_SELECTORS: &[&str] = &[
+    "section + article p",
+    ".intro ~ article li em",
+    "section.intro + article div.page > p b",
+    "#main section + article > ul em",
+    "div > section ~ article em",
+];
+
+fn elements(root: &Rc<Node>) -> Vec<Rc<Node>> {
+    let mut elements = Vec::new();
+    root.walk_rc(&mut |node| {
+        if node.element_name().is_some() {
+            elements.push(Rc::clone(node));
+        }
+    });
+    elements
+}
+
+#[test]
+fn filtered_styling_agrees_with_matches() {
+    let red = Color::rgb(255, 0, 0);
+    for selector in SELECTORS {
+        let document = parse_html(&format!(
+            "<style>{} {{ background-color: red }}</style>{}",
+            selector, BODY
+        ));
+        document.restyle();
+        for element in elements(&document.root) {
+            let matched = element.matches(selector).unwrap();
+            let styled = style_of(&element).background_color == red;
+            assert_eq!(
+                styled,
+                matched,
+                "{} on <{}>",
+                selector,
+                element.element_name().unwrap_or_default()
+            );
+        }
+    }
+}
+
+#[test]
+fn sibling_of_ancestor_is_not_filtered() {
+    let red = Color::rgb(255, 0, 0);
+    for selector in SIBLING_SELECTORS {
+        let document = parse_html(&format!(
+            "<style>{} {{ background-color: red }}</style>{}",
+            selector, BODY
+        ));
+        document.restyle();
+        let mut styled_any = false;
+        for element in elements(&document.root) {
+            let matched = element.matches(selector).unwrap();
+            let styled = style_of(&element).background_color == red;
+            styled_any |= styled;
+            assert_eq!(
+                styled,
+                matched,
+                "{} on <{}>",
+                selector,
+                element.element_name().unwrap_or_default()
+            );
+        }
+        assert!(styled_any, "{} styled nothing", selector);
+    }
+}
+
+#[test]
+fn counters_balance_across_push_and_pop() {
+    let document = parse_html(BODY);
+    let chain: Vec<Rc<Node>> = document
+        .get_elements_by_tag_name("b")
+        .first()
+        .map(|b| {
+            let mut chain = Vec::new();
+            let mut current = Some(Rc::clone(b));
+            while let Some(node) = current {
+                current = node.parent.borrow().upgrade();
+                if node.element_name().is_some() {
+                    chain.push(node);
+                }
+            }
+            chain.reverse();
+            chain
+        })
+        .unwrap();
+
+    let mut filter = AncestorFilter::new();
+    for node in &chain {
+        filter.push(node);
+    }
+    assert_eq!(filter.depth(), chain.len());
+    for node in &chain {
+        for hash in element_hashes(node) {
+            assert!(filter.might_contain(hash));
+        }
+    }
+
+    for _ in 0..3 {
+        filter.pop();
+    }
+    for node in &chain[..chain.len() - 3] {
+        for hash in element_hashes(node) {
+            assert!(filter.might_contain(hash));
+        }
+    }
+
+    for _ in 0..chain.len() + 2 {
+        filter.pop();
+    }
+    assert_eq!(filter.depth(), 0);
+    for node in &chain {
+        for hash in element_hashes(node) {
+            assert!(!filter.might_contain(hash));
+        }
+    }
+}
+
+#[test]
+fn saturated_counters_never_reject() {
+    let document = parse_html("<div class=deep></div>");
+    let div = document.get_elements_by_tag_name("div")[0].clone();
+    let hash = hash_name("deep", false);
+
+    let mut filter = AncestorFilter::new();
+    for _ in 0..300 {
+        filter.push(&div);
+    }
+    for _ in 0..299 {
+        filter.pop();
+    }
+    assert_eq!(filter.depth(), 1);
+    assert!(filter.might_contain(hash));
+}
@@ -0,0 +1,171 @@
+use icarus::css::{Origin, Stylesheet};
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::style::animation::{StepPosition, TimingFunction, interpolate, parse_timing_function};
+use icarus::style::style_of;
+
+const KEYFRAMES: &str = "@keyframes grow {
+    from { width: 100px; background-color: #000000 }
+    to { width: 200px; background-color: #ffffff }
+  }";
+
+fn page(animation: &str) -> Document {
+    let document = parse_html(&format!(
+        "<style>{} #box {{ animation: {} }}</style><div id=box></div>",
+        KEYFRAMES, animation
+    ));
+    document.restyle();
+    document
+}
+
+fn property_at(document: &Document, time: f64, property: &str) -> String {
+    document.advance_animations(time);
+    document.restyle();
+    let node = document.query_selector("#box").unwrap().unwrap();
+    style_of(&node).to_map()[property].clone()
+}
+
+#[test]
+fn keyframes_rules_are_collected() {
+    let sheet = Stylesheet::parse(
+        "@keyframes fade { from { color: red } 50%, 75% { color: blue !important; width: 5px } to { color: green } }
+         @-webkit-keyframes 'quoted' { 0% { top: 0 } 100% { top: 10px } }
+         @media print { @keyframes printed { to { top: 0 } } }
+         @keyframes none { to { top: 0 } }
+         p { color: red }",
+        Origin::Author,
+    );
+    assert_eq!(sheet.rules.len(), 1);
+    let names: Vec<&str> = sheet.keyframes.iter().map(|k| k.name.as_str()).collect();
+    assert_eq!(names, ["fade", "quoted", "printed"]);
+    let fade = &sheet.keyframes[0];
+    let offsets: Vec<f32> = fade.frames.iter().map(|frame| frame.offset).collect();
+    assert_eq!(offsets, [0.0, 0.5, 0.75, 1.0]);
+    assert_eq!(fade.frames[1].declarations.len(), 1);
+    assert_eq!(fade.frames[1].declarations[0].name, "width");
+    assert_eq!(sheet.keyframes[2].media.len(), 1);
+}
+
+#[test]
+fn animation_shorthand_expands_to_longhands() {
+    let document = page("grow 2s ease-in 500ms infinite alternate both paused, Other 1s");
+    let node = document.query_selector("#box").unwrap().unwrap();
+    let map = style_of(&node).to_map();
+    assert_eq!(map["animation-name"], "grow, Other");
+    assert_eq!(map["animation-duration"], "2s, 1s");
+    assert_eq!(map["animation-timing-function"], "ease-in, ease");
+    assert_eq!(map["animation-delay"], "0.5s, 0s");
+    assert_eq!(map["animation-iteration-count"], "infinite, 1");
+    assert_eq!(map["animation-direction"], "alternate, normal");
+    assert_eq!(map["animation-fill-mode"], "both, none");
+    assert_eq!(map["animation-play-state"], "paused, running");
+
+    let plain = parse_html("<p>x</p>");
+    plain.restyle();
+    let p = plain.query_selector("p").unwrap().unwrap();
+    assert_eq!(style_of(&p).to_map()["animation-name"], "none");
+}
+
+#[test]
+fn keyframes_are_interpolated_on_the_timeline() {
+    let document = page("grow 2s linear");
+    assert_eq!(property_at(&document, 0.0, "width"), "100px");
+    assert_eq!(property_at(&document, 1.0, "width"), "150px");
+    assert_eq!(property_at(&document, 1.0, "background-color"), "#808080");
+    assert!(document.animations_running());
+    assert_eq!(property_at(&document, 2.5, "width"), "auto");
+    assert!(!document.animations_running());
+    assert!(!document.advance_animations(3.0));
+}
+
+#[test]
+fn iterations_and_directions() {
+    let document = page("grow 1s linear 3 alternate forwards");
+    assert_eq!(property_at(&document, 0.25, "width"), "125px");
+    assert_eq!(property_at(&document, 1.25, "width"), "175px");
+    assert_eq!(property_at(&document, 2.25, "width"), "125px");
+    assert_eq!(property_at(&document, 10.0, "width"), "200px");
+
+    let document = page("grow 1s linear infinite reverse");
+    assert_eq!(property_at(&document, 7.25, "width"), "175px");
+    assert!(document.animations_running());
+
+    let document = page("grow 1s linear 1.5 alternate-reverse forwards");
+    assert_eq!(property_at(&document, 0.25, "width"), "175px");
+    assert_eq!(property_at(&document, 4.0, "width"), "150px");
+}
+
+#[test]
+fn fill_modes_and_delays() {
+    let document = page("grow 1s linear 2s");
+    assert_eq!(property_at(&document, 1.0, "width"), "auto");
+    assert_eq!(property_at(&document, 2.5, "width"), "150px");
+    assert_eq!(property_at(&document, 3.5, "width"), "auto");
+
+    let document = page("grow 1s linear 2s backwards");
+    assert_eq!(property_at(&document, 1.0, "width"), "100px");
+    assert_eq!(property_at(&document, 3.5, "width"), "auto");
+
+    let document = page("grow 1s linear -500ms both");
+    assert_eq!(property_at(&document, 0.0, "width"), "150px");
+    assert_eq!(property_at(&document, 5.0, "width"), "200px");
+}
+
+#[test]
+fn paused_animations_hold_their_progress() {
+    let document = page("grow 2s linear");
+    assert_eq!(property_at(&document, 0.5, "width"), "125px");
+    let node = document.query_selector("#box").unwrap().unwrap();
+    Node::set_attribute(&node, "style", "animation-play-state: paused");
+    assert_eq!(property_at(&document, 0.5, "width"), "125px");
+    assert!(!document.animations_running());
+    assert_eq!(property_at(&document, 1.5, "width"), "125px");
+    Node::set_attribute(&node, "style", "animation-play-state: running");
+    assert_eq!(property_at(&document, 2.0, "width"), "125px");
+    assert_eq!(property_at(&document, 2.5, "width"), "150px");
+}
+
+#[test]
+fn missing_keyframes_use_the_underlying_value() {
+    let document = parse_html(
+        "<style>@keyframes slide { to { margin-left: 100px } }
+           #box { margin-left: 20px; animation: slide 1s linear }
+           #important { margin-left: 5px !important; animation: slide 1s linear }</style>
+         <div id=box></div><div id=important></div>",
+    );
+    document.restyle();
+    assert_eq!(property_at(&document, 0.5, "margin-left"), "60px");
+    let important = document.query_selector("#important").unwrap().unwrap();
+    assert_eq!(style_of(&important).to_map()["margin-left"], "5px");
+}
+
+#[test]
+fn timing_functions() {
+    let linear = parse_timing_function("linear").unwrap();
+    assert_eq!(linear.at(0.3), 0.3);
+    let ease_in_out = parse_timing_function("ease-in-out").unwrap();
+    assert!((ease_in_out.at(0.5) - 0.5).abs() < 1e-3);
+    assert!(ease_in_out.at(0.25) < 0.25);
+    assert_eq!(
+        parse_timing_function("steps(4, start)"),
+        Some(TimingFunction::Steps(4, StepPosition::Start))
+    );
+    let steps = parse_timing_function("steps(2)").unwrap();
+    assert_eq!(
+        (steps.at(0.3), steps.at(0.6), steps.at(1.0)),
+        (0.0, 0.5, 1.0)
+    );
+    let jump_start = parse_timing_function("step-start").unwrap();
+    assert_eq!(jump_start.at(0.1), 1.0);
+    assert_eq!(parse_timing_function("cubic-bezier(2, 0, 0, 1)"), None);
+    assert_eq!(parse_timing_function("steps(1, jump-none)"), None);
+}
+
+#[test]
+fn values_interpolate_by_component() {
+    assert_eq!(interpolate("0", "10px", 0.5), "5px");
+    assert_eq!(interpolate("1px 2em", "3px 4em", 0.5), "2px 3em");
+    assert_eq!(interpolate("red", "blue", 0.5), "#800080");
+    assert_eq!(interpolate("10px", "2em", 0.25), "10px");
+    assert_eq!(interpolate("block", "none", 0.75), "none");
+}
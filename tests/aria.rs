@@ -0,0 +1,82 @@
+use icarus::diagnostics::Severity;
+use icarus::html::parser::parse_html;
+
+fn codes(html: &str) -> Vec<(&'static str, Severity, String)> {
+    parse_html(html)
+        .validate_aria()
+        .into_iter()
+        .map(|diagnostic| {
+            let id = diagnostic
+                .node
+                .and_then(|node| node.get_attribute("id"))
+                .unwrap_or_default();
+            (diagnostic.code, diagnostic.severity, id)
+        })
+        .collect()
+}
+
+#[test]
+fn valid_aria_has_no_diagnostics() {
+    let html = r#"
+        <nav aria-label="Main"><a href="/">Home</a></nav>
+        <div role="checkbox" aria-checked="mixed" tabindex="0">Pick</div>
+        <h2 id="title">Title</h2>
+        <section aria-labelledby="title" aria-busy="false"></section>
+        <input type="checkbox" role="switch" aria-checked="true">
+        <div aria-hidden="true"><span>decoration</span></div>
+    "#;
+    assert!(codes(html).is_empty(), "{:?}", codes(html));
+}
+
+#[test]
+fn roles_are_validated() {
+    assert_eq!(
+        codes(
+            "<div id=a role=buton></div><div id=b role=' '></div><div role='fancy button'></div>"
+        ),
+        [
+            ("aria-unknown-role", Severity::Error, String::from("a")),
+            ("aria-empty-role", Severity::Warning, String::from("b")),
+        ]
+    );
+    assert_eq!(
+        codes("<div id=s role=slider></div><h2 role=heading></h2>"),
+        [("aria-missing-required", Severity::Error, String::from("s"))]
+    );
+}
+
+#[test]
+fn attributes_values_and_references_are_validated() {
+    assert_eq!(
+        codes(
+            "<p id=a aria-colour=red></p>\
+             <p id=b aria-busy=yes aria-pressed=mixed aria-checked=maybe></p>\
+             <p id=c aria-describedby='c missing'></p>"
+        ),
+        [
+            ("aria-unknown-attribute", Severity::Error, String::from("a")),
+            ("aria-invalid-value", Severity::Error, String::from("b")),
+            ("aria-invalid-value", Severity::Error, String::from("b")),
+            (
+                "aria-broken-reference",
+                Severity::Warning,
+                String::from("c")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn focusable_content_inside_aria_hidden_is_reported() {
+    assert_eq!(
+        codes("<div aria-hidden=true><span tabindex=-1></span><a id=link href=/x>x</a></div>"),
+        [(
+            "aria-hidden-focusable",
+            Severity::Error,
+            String::from("link")
+        )]
+    );
+    assert!(
+        codes("<div aria-hidden=true><button disabled>x</button><a>no href</a></div>").is_empty()
+    );
+}
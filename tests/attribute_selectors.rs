@@ -0,0 +1,82 @@
+use icarus::css::selector::{Specificity, parse_selector};
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+mod common;
+
+use common::ids;
+
+const PAGE: &str = "<a id=secure href='https://example.com/doc.PDF' lang=en-US>1</a>
+    <a id=plain href='http://example.com/page.html' lang=en>2</a>
+    <a id=bare>3</a>
+    <p id=tags data-tags='news featured' DATA-Kind=Alert>4</p>
+    <svg><rect id=box viewBox='0 0 1 1'/></svg>";
+
+#[test]
+fn presence_and_equality() {
+    assert_eq!(ids(PAGE, "a[href]"), ["secure", "plain"]);
+    assert_eq!(ids(PAGE, "[ lang = en ]"), ["plain"]);
+    assert_eq!(ids(PAGE, "[lang=\"en-US\"]"), ["secure"]);
+    assert_eq!(ids(PAGE, "[data-kind=Alert]"), ["tags"]);
+    assert_eq!(ids(PAGE, "[DATA-KIND]"), ["tags"]);
+    assert_eq!(ids(PAGE, "rect[viewBox]"), ["box"]);
+    assert!(ids(PAGE, "rect[viewbox]").is_empty());
+}
+
+#[test]
+fn substring_operators() {
+    assert_eq!(ids(PAGE, "[href^='https:']"), ["secure"]);
+    assert_eq!(ids(PAGE, "[href$='.html']"), ["plain"]);
+    assert_eq!(ids(PAGE, "[href*=example]"), ["secure", "plain"]);
+    assert_eq!(ids(PAGE, "[data-tags~=featured]"), ["tags"]);
+    assert!(ids(PAGE, "[data-tags~='news featured']").is_empty());
+    assert_eq!(ids(PAGE, "[lang|=en]"), ["secure", "plain"]);
+    assert!(ids(PAGE, "[href^='']").is_empty());
+}
+
+#[test]
+fn case_sensitivity_flags() {
+    assert!(ids(PAGE, "[href$='.pdf']").is_empty());
+    assert_eq!(ids(PAGE, "[href$='.pdf' i]"), ["secure"]);
+    assert_eq!(ids(PAGE, "[data-kind=alert I]"), ["tags"]);
+    assert!(ids(PAGE, "[data-kind=alert s]").is_empty());
+}
+
+#[test]
+fn invalid_attribute_selectors_are_rejected() {
+    assert!(parse_selector("[]").is_none());
+    assert!(parse_selector("[href").is_none());
+    assert!(parse_selector("[href=]").is_none());
+    assert!(parse_selector("[href!=x]").is_none());
+    assert!(parse_selector("[href='x' q]").is_none());
+    assert!(parse_selector("[href='x]").is_none());
+    assert_eq!(
+        parse_selector("a[href][lang]").unwrap().specificity(),
+        Specificity(0, 2, 1)
+    );
+}
+
+#[test]
+fn attribute_changes_restyle_matching_elements() {
+    let document = parse_html(
+        "<style>[aria-expanded=true], [data-state=open] { color: red }
+                [data-theme=dark] span { color: white }</style>
+         <div id=panel data-state=closed><span id=label>x</span></div>",
+    );
+    document.restyle();
+    let color = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()["color"].clone()
+    };
+    assert_eq!(color("#panel"), "#000000");
+
+    let panel = document.query_selector("#panel").unwrap().unwrap();
+    Node::set_attribute(&panel, "data-state", "open");
+    document.restyle();
+    assert_eq!(color("#panel"), "#ff0000");
+
+    Node::set_attribute(&panel, "data-theme", "dark");
+    document.restyle();
+    assert_eq!(color("#label"), "#ffffff");
+}
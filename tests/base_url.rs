@@ -0,0 +1,96 @@
+use std::rc::Rc;
+use std::time::Instant;
+
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::net::{ResponseInfo, Timing};
+use url::Url;
+
+fn loaded(html: &str, url: &str) -> Document {
+    let mut document = parse_html(html);
+    document.response = Some(ResponseInfo {
+        url: Url::parse(url).unwrap(),
+        status: 200,
+        headers: Vec::new(),
+        timing: Timing::started(Instant::now()),
+    });
+    document
+}
+
+fn resolved(document: &Document, relative: &str) -> String {
+    document.resolve_url(relative).unwrap().to_string()
+}
+
+#[test]
+fn relative_base_resolves_against_the_request_url() {
+    let document = loaded(
+        "<head><base href=' ../assets/ '></head><img src=x.png>",
+        "http://example.com/docs/guide/page.html",
+    );
+    assert_eq!(
+        document.base_url().unwrap().as_str(),
+        "http://example.com/docs/assets/"
+    );
+    assert_eq!(
+        resolved(&document, "img/x.png"),
+        "http://example.com/docs/assets/img/x.png"
+    );
+    assert_eq!(resolved(&document, "/top"), "http://example.com/top");
+}
+
+#[test]
+fn first_base_with_href_wins_and_unsafe_schemes_are_ignored() {
+    let document = loaded(
+        "<base target=_blank><base href=/first/><base href=/second/>",
+        "http://example.com/page",
+    );
+    assert_eq!(resolved(&document, "a"), "http://example.com/first/a");
+
+    let document = loaded(
+        "<base href='javascript:void(0)'>",
+        "http://example.com/d/page",
+    );
+    assert_eq!(resolved(&document, "a"), "http://example.com/d/a");
+
+    let document = parse_html("<base href=https://cdn.example.org/lib/>");
+    assert_eq!(
+        resolved(&document, "a.js"),
+        "https://cdn.example.org/lib/a.js"
+    );
+}
+
+#[test]
+fn cached_base_follows_base_mutations() {
+    let document = loaded("<head></head><p>x</p>", "http://example.com/a/page");
+    assert_eq!(resolved(&document, "b"), "http://example.com/a/b");
+
+    let head = document.get_elements_by_tag_name("head")[0].clone();
+    let base = parse_html("<base href=/inserted/>").get_elements_by_tag_name("base")[0].clone();
+    Node::append_child(&head, Rc::clone(&base));
+    assert_eq!(resolved(&document, "b"), "http://example.com/inserted/b");
+
+    Node::set_attribute(&base, "href", "/changed/");
+    assert_eq!(resolved(&document, "b"), "http://example.com/changed/b");
+
+    Node::remove_attribute(&base, "href");
+    assert_eq!(resolved(&document, "b"), "http://example.com/a/b");
+
+    Node::set_attribute(&base, "href", "/again/");
+    assert_eq!(resolved(&document, "b"), "http://example.com/again/b");
+
+    let wrapper = parse_html("<div></div>").get_elements_by_tag_name("div")[0].clone();
+    Node::append_child(&wrapper, Rc::clone(&base));
+    assert_eq!(resolved(&document, "b"), "http://example.com/a/b");
+
+    Node::set_attribute(&base, "href", "/detached/");
+    Node::append_child(&head, wrapper);
+    assert_eq!(resolved(&document, "b"), "http://example.com/detached/b");
+}
+
+#[test]
+fn cached_base_follows_the_response_url() {
+    let mut document = loaded("<base href=sub/>", "http://example.com/one/page");
+    assert_eq!(resolved(&document, "x"), "http://example.com/one/sub/x");
+    document.response.as_mut().unwrap().url = Url::parse("http://example.org/two/").unwrap();
+    assert_eq!(resolved(&document, "x"), "http://example.org/two/sub/x");
+}
@@ -0,0 +1,72 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::bidi::{resolve_levels, shape_arabic, visual_order};
+use icarus::style::style_of;
+
+mod common;
+
+use common::{fragment_text, fragments, layout};
+
+#[test]
+fn levels_follow_the_bidi_algorithm() {
+    assert_eq!(resolve_levels("ab אב", 0), [0, 0, 0, 1, 1]);
+    assert_eq!(resolve_levels("אב 12", 1), [1, 1, 1, 2, 2]);
+    assert_eq!(resolve_levels("ab ", 1), [2, 2, 1]);
+    assert_eq!(resolve_levels("א (ב) c", 0), [1, 1, 1, 1, 0, 0, 0]);
+
+    assert_eq!(visual_order(&[0, 1, 1, 0]), [0, 2, 1, 3]);
+    assert_eq!(visual_order(&[1, 2, 2, 1]), [3, 1, 2, 0]);
+}
+
+#[test]
+fn arabic_letters_take_joined_forms() {
+    assert_eq!(shape_arabic("سلام"), "\u{feb3}\u{fefc}\u{fee1}");
+    assert_eq!(shape_arabic("بيت"), "\u{fe91}\u{fef4}\u{fe96}");
+    assert_eq!(shape_arabic("plain"), "plain");
+}
+
+#[test]
+fn mixed_runs_are_reordered_per_line() {
+    let tree = layout("<p>abc אבג (ד) def</p>");
+    let fragments = fragments(&tree.root);
+    let texts: Vec<&str> = fragments
+        .iter()
+        .map(|fragment| fragment_text(fragment))
+        .collect();
+    assert_eq!(texts, ["abc ", "ד) גבא", ") def"]);
+    for pair in fragments.windows(2) {
+        assert_eq!(pair[0].rect.right(), pair[1].rect.x);
+    }
+}
+
+#[test]
+fn rtl_blocks_start_at_the_right() {
+    let tree = layout(
+        "<body style='margin: 0' dir=rtl><p>שלום world</p>
+         <div style='width: 100px; margin-right: 10px'>x</div>
+         <ul><li>item</li></ul></body>",
+    );
+    let fragments = fragments(&tree.root);
+    assert_eq!(fragment_text(fragments[0]), "world");
+    assert_eq!(fragment_text(fragments[1]), " םולש");
+    assert_eq!(fragments[1].rect.right(), 800.0);
+    assert_eq!(fragments[2].rect.right(), 790.0);
+    assert_eq!(fragments[3].rect.right(), 760.0);
+}
+
+#[test]
+fn direction_comes_from_the_dir_attribute() {
+    let document = parse_html(
+        "<div dir=RTL><p id=a>x</p></div><p dir=auto id=b>... مرحبا</p>\
+         <p dir=ltr id=c style='text-align: end'>y</p>",
+    );
+    document.restyle();
+    let map = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()
+    };
+    assert_eq!(map("#a")["direction"], "rtl");
+    assert_eq!(map("#a")["text-align"], "start");
+    assert_eq!(map("#b")["direction"], "rtl");
+    assert_eq!(map("#c")["direction"], "ltr");
+    assert_eq!(map("#c")["text-align"], "end");
+}
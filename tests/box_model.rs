@@ -0,0 +1,51 @@
+use icarus::layout::Rect;
+
+mod common;
+
+use common::{find, layout};
+
+#[test]
+fn padding_border_and_margin_wrap_the_content_box() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=a style='margin: 10px; border: 2px solid black; padding: 5px; height: 20px'></div>
+         </body>",
+    );
+    let a = find(&tree.root, "a").expect("box");
+    assert_eq!(a.rect, Rect::new(17.0, 17.0, 766.0, 20.0));
+    assert_eq!(a.padding_box(), Rect::new(12.0, 12.0, 776.0, 30.0));
+    assert_eq!(a.border_box(), Rect::new(10.0, 10.0, 780.0, 34.0));
+    assert_eq!(a.margin_box(), Rect::new(0.0, 0.0, 800.0, 54.0));
+}
+
+#[test]
+fn blocks_stack_and_widths_resolve_against_the_container() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=a style='width: 50%; height: 10px'></div>
+         <div id=b style='width: 100px; height: 30px'></div>
+         <div id=c style='display: none; height: 40px'></div>
+         <div id=d>text</div></body>",
+    );
+    let a = find(&tree.root, "a").expect("box");
+    let b = find(&tree.root, "b").expect("box");
+    assert_eq!((a.rect.width, a.rect.y), (400.0, 0.0));
+    assert_eq!((b.rect.width, b.rect.y), (100.0, 10.0));
+    assert!(find(&tree.root, "c").is_none());
+    let d = find(&tree.root, "d").expect("box");
+    assert_eq!(d.rect.y, 40.0);
+    assert!(d.rect.height > 0.0);
+}
+
+#[test]
+fn hit_testing_finds_the_innermost_element() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=outer style='padding: 20px'><div id=inner style='height: 10px'></div></div>
+         </body>",
+    );
+    let hit = tree.hit_test(30.0, 25.0).expect("hit");
+    assert_eq!(hit.node.get_attribute("id").as_deref(), Some("inner"));
+    let hit = tree.hit_test(5.0, 5.0).expect("hit");
+    assert_eq!(hit.node.get_attribute("id").as_deref(), Some("outer"));
+}
@@ -0,0 +1,82 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::raster::Canvas;
+use icarus::paint::{DisplayItem, DisplayList};
+use icarus::render::svg::to_svg;
+use icarus::style::{BoxShadow, style_of};
+
+const CARD: &str = "<body style='margin: 0'><div style='margin: 20px; width: 40px; height: 40px;
+    box-shadow: 4px 4px 0 red, inset 0 0 0 5px rgb(0, 0, 255)'></div></body>";
+
+fn pixel(canvas: &Canvas, x: usize, y: usize) -> u32 {
+    canvas.pixels[y * canvas.width + x] & 0xffffff
+}
+
+#[test]
+fn shadows_are_parsed() {
+    let document = parse_html(
+        "<p id=a style='color: green; box-shadow: inset 1px 2px 3px 4px rgba(0, 0, 0, 0.5), 0 0 1em'>x</p>
+         <p id=b style='box-shadow: 1px red'>y</p>",
+    );
+    document.restyle();
+    let a = document.query_selector("#a").unwrap().unwrap();
+    let shadows = style_of(&a).box_shadow.clone();
+    assert_eq!(
+        shadows,
+        [
+            BoxShadow {
+                offset_x: 1.0,
+                offset_y: 2.0,
+                blur: 3.0,
+                spread: 4.0,
+                color: Color::rgba(0, 0, 0, 128),
+                inset: true,
+            },
+            BoxShadow {
+                offset_x: 0.0,
+                offset_y: 0.0,
+                blur: 16.0,
+                spread: 0.0,
+                color: Color::rgb(0, 128, 0),
+                inset: false,
+            },
+        ]
+    );
+    let b = document.query_selector("#b").unwrap().unwrap();
+    assert_eq!(style_of(&b).to_map()["box-shadow"], "none");
+}
+
+#[test]
+fn shadows_paint_outside_and_inside_the_box() {
+    let tree = LayoutTree::build(&parse_html(CARD), LayoutOptions::default());
+    let list = DisplayList::build(&tree);
+    assert!(list.items.iter().any(|item| matches!(
+        item,
+        DisplayItem::BoxShadow { rect, .. } if *rect == Rect::new(20.0, 20.0, 40.0, 40.0)
+    )));
+
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&list, None, 0.0);
+    assert_eq!(pixel(&canvas, 62, 62), 0xff0000);
+    assert_eq!(pixel(&canvas, 22, 40), 0x0000ff);
+    assert_eq!(pixel(&canvas, 40, 40), 0xffffff);
+    assert_eq!(pixel(&canvas, 18, 18), 0xffffff);
+}
+
+#[test]
+fn blurred_shadows_fade_out() {
+    let html = "<body style='margin: 0'><div style='margin: 30px; width: 40px; height: 40px;
+        box-shadow: 0 0 20px black'></div></body>";
+    let tree = LayoutTree::build(&parse_html(html), LayoutOptions::default());
+    let list = DisplayList::build(&tree);
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&list, None, 0.0);
+    let darkness = |x| 255 - (pixel(&canvas, x, 50) & 0xff);
+    assert!(darkness(29) > darkness(20));
+    assert!(darkness(20) > darkness(12));
+    assert_eq!(darkness(5), 0);
+
+    let svg = to_svg(&list, 100, 100, Color::WHITE);
+    assert!(svg.contains("feGaussianBlur stdDeviation=\"10\""));
+}
@@ -0,0 +1,43 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::style::{Dimension, Display, style_of};
+
+#[test]
+fn cascade_orders_by_importance_specificity_and_source() {
+    let document = parse_html(
+        "<style>
+           p { color: red; margin: 4px }
+           #one { color: blue }
+           p { color: green }
+           .loud { color: purple !important }
+           div { font-size: 20px; border: 2px solid black }
+         </style>
+         <div id=box><p id=one>a</p><p id=two>b</p><p id=three class=loud style='color: orange'>c</p></div>",
+    );
+    document.restyle();
+    let style = |id: &str| style_of(&document.get_element_by_id(id).unwrap());
+
+    assert_eq!(style("one").color, Color::parse("blue").unwrap());
+    assert_eq!(style("two").color, Color::parse("green").unwrap());
+    assert_eq!(style("three").color, Color::parse("purple").unwrap());
+    assert_eq!(style("two").margin.top, Dimension::Px(4.0));
+    assert_eq!(style("box").border_width.left, 2.0);
+    assert_eq!(style("two").border_width.left, 0.0);
+}
+
+#[test]
+fn inherited_and_user_agent_values() {
+    let document = parse_html(
+        "<style>div { font-size: 20px; color: #123456 } em { font-size: 2em }</style>
+         <div id=box><p id=para>x <em id=em>y</em></p><span id=span>z</span></div>",
+    );
+    document.restyle();
+    let style = |id: &str| style_of(&document.get_element_by_id(id).unwrap());
+
+    assert_eq!(style("para").font_size, 20.0);
+    assert_eq!(style("para").color, Color::parse("#123456").unwrap());
+    assert_eq!(style("em").font_size, 40.0);
+    assert_eq!(style("para").display, Display::Block);
+    assert_eq!(style("span").display, Display::Inline);
+    assert_eq!(style("box").margin.top, Dimension::Px(0.0));
+}
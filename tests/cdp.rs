@@ -0,0 +1,195 @@
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+
+use icarus::cdp::{Server, Session};
+use icarus::html::parser::parse_html;
+use icarus::settings::Settings;
+use icarus::storage::{LocalStorage, StorageContext};
+
+mod common;
+
+fn call(session: &mut Session, method: &str, params: Value) -> Value {
+    let request = json!({ "id": 7, "method": method, "params": params });
+    session.dispatch(&request.to_string()).0
+}
+
+fn error_code(reply: &Value) -> i64 {
+    assert_eq!(reply["id"], 7, "{}", reply);
+    reply["error"]["code"].as_i64().unwrap()
+}
+
+#[test]
+fn successful_commands_echo_the_request_id() {
+    let mut session = Session::new(parse_html("<p id=x>hi</p>"));
+    let reply = call(&mut session, "DOM.getDocument", json!({ "depth": -1 }));
+    assert_eq!(reply["id"], 7);
+    assert!(reply.get("error").is_none());
+    assert_eq!(reply["result"]["root"]["nodeName"], "#document");
+
+    let id = reply["result"]["root"]["nodeId"].as_u64().unwrap();
+    let reply = call(&mut session, "DOM.describeNode", json!({ "nodeId": id }));
+    assert_eq!(reply["result"]["node"]["nodeType"], 9);
+}
+
+#[test]
+fn dom_storage_uses_the_page_storage_across_navigations() {
+    let storage = StorageContext::new(LocalStorage::in_memory());
+    let mut session = Session::default().with_storage(storage.clone());
+    let id = json!({ "securityOrigin": "http://example.com", "isLocalStorage": false });
+    call(
+        &mut session,
+        "DOMStorage.setDOMStorageItem",
+        json!({ "storageId": id, "key": "k", "value": "v" }),
+    );
+    call(
+        &mut session,
+        "Page.navigate",
+        json!({ "url": "about:blank" }),
+    );
+
+    let reply = call(
+        &mut session,
+        "DOMStorage.getDOMStorageItems",
+        json!({ "storageId": id }),
+    );
+    assert_eq!(reply["result"]["entries"], json!([["k", "v"]]));
+    let url = url::Url::parse("http://example.com/").unwrap();
+    let value = storage.session(&url, |area| area.get_item("k").map(str::to_string));
+    assert_eq!(value.unwrap().as_deref(), Some("v"));
+}
+
+#[test]
+fn errors_use_json_rpc_codes() {
+    let mut session = Session::default();
+    assert_eq!(
+        error_code(&call(&mut session, "Page.teleport", json!({}))),
+        -32601
+    );
+    assert_eq!(
+        error_code(&call(&mut session, "Page.navigate", json!({}))),
+        -32602
+    );
+    assert_eq!(
+        error_code(&call(&mut session, "DOM.describeNode", json!({}))),
+        -32602
+    );
+    assert_eq!(
+        error_code(&call(
+            &mut session,
+            "Page.captureScreenshot",
+            json!({ "format": "gif" })
+        )),
+        -32602
+    );
+    assert_eq!(
+        error_code(&call(
+            &mut session,
+            "DOMStorage.getDOMStorageItems",
+            json!({ "storageId": { "securityOrigin": "not a url", "isLocalStorage": false } })
+        )),
+        -32602
+    );
+
+    let missing = call(
+        &mut session,
+        "DOM.describeNode",
+        json!({ "nodeId": u64::MAX }),
+    );
+    assert_eq!(error_code(&missing), -32000);
+    assert!(
+        missing["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("no node with id")
+    );
+}
+
+#[test]
+fn malformed_requests_are_parse_errors() {
+    let (reply, events) = Session::default().dispatch("{ not json");
+    assert_eq!(reply["error"]["code"], -32700);
+    assert_eq!(reply["id"], Value::Null);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn navigation_uses_the_session_settings() {
+    let (sender, received) = mpsc::channel();
+    let port = common::serve_with(move |head, mut stream| {
+        sender.send(head.to_string()).unwrap();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 2\r\n\r\nhi")
+            .unwrap();
+    });
+    let settings = Settings {
+        user_agent: String::from("Inspector/1.0"),
+        ..Settings::default()
+    };
+    let mut session = Session::default().with_settings(settings);
+    let url = format!("http://127.0.0.1:{}/", port);
+    let reply = call(&mut session, "Page.navigate", json!({ "url": url }));
+    assert!(reply["result"].get("errorText").is_none(), "{}", reply);
+    assert!(
+        received
+            .recv()
+            .unwrap()
+            .contains("\r\nUser-Agent: Inspector/1.0\r\n")
+    );
+}
+
+fn server(origins: &[&str]) -> u16 {
+    let origins: Vec<String> = origins.iter().map(|origin| origin.to_string()).collect();
+    let (sender, port) = mpsc::channel();
+    thread::spawn(move || {
+        let mut server = Server::bind(0, Session::default())
+            .unwrap()
+            .allow_origins(origins);
+        sender.send(server.port().unwrap()).unwrap();
+        let _ = server.run();
+    });
+    port.recv().unwrap()
+}
+
+fn status_line(port: u16, host: &str, origin: Option<&str>) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let origin = origin
+        .map(|origin| format!("Origin: {}\r\n", origin))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "GET /devtools/page/icarus-page HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\n\
+         Connection: Upgrade\r\nSec-WebSocket-Version: 13\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n{}\r\n",
+        host, origin
+    )
+    .unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line.trim_end().to_string()
+}
+
+#[test]
+fn foreign_origins_and_hosts_are_refused() {
+    let port = server(&["http://localhost:3000"]);
+    let local = format!("127.0.0.1:{}", port);
+
+    assert_eq!(
+        status_line(port, &local, Some("https://evil.example")),
+        "HTTP/1.1 403 Forbidden"
+    );
+    assert_eq!(
+        status_line(port, &format!("evil.example:{}", port), None),
+        "HTTP/1.1 403 Forbidden"
+    );
+    assert_eq!(
+        status_line(port, &local, Some("http://localhost:3000")),
+        "HTTP/1.1 101 Switching Protocols"
+    );
+    assert_eq!(
+        status_line(port, &format!("localhost:{}", port), None),
+        "HTTP/1.1 101 Switching Protocols"
+    );
+}
@@ -0,0 +1,28 @@
+use icarus::clipboard::copy_selection;
+use icarus::html::parser::parse_html;
+
+#[test]
+fn empty_selections_are_not_copied() {
+    let document = parse_html("<p id=a>hello world</p>");
+    assert!(!copy_selection(&document).unwrap());
+
+    let text = document.get_element_by_id("a").unwrap().children.borrow()[0].clone();
+    document.selection.borrow_mut().collapse(&text, 3);
+    assert!(!copy_selection(&document).unwrap());
+}
+
+#[test]
+fn copied_text_follows_the_selection() {
+    let document = parse_html("<p id=a>hello <b>bold</b> world</p>");
+    let p = document.get_element_by_id("a").unwrap();
+    document.selection.borrow_mut().select_all_children(&p);
+    assert_eq!(document.selected_text(), "hello bold world");
+
+    let bold = document.query_selector("b").unwrap().unwrap();
+    let text = bold.children.borrow()[0].clone();
+    let mut selection = document.selection.borrow_mut();
+    selection.collapse(&text, 1);
+    selection.extend(&text, 3);
+    drop(selection);
+    assert_eq!(document.selected_text(), "ol");
+}
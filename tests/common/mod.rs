@@ -0,0 +1,131 @@
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::thread;
+
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+use icarus::layout::inline::{Fragment, FragmentKind, LineBox};
+use icarus::layout::{LayoutBox, LayoutOptions, LayoutTree};
+
+/// Accepts connections on a fresh local port and hands each one to `handler`
+/// together with its request head (request line and headers, without the
+/// terminating blank line). Returns the port.
+pub fn serve_with<F>(mut handler: F) -> u16
+where
+    F: FnMut(&str, TcpStream) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                head.push_str(&line);
+                line.clear();
+            }
+            handler(&head, stream);
+        }
+    });
+    port
+}
+
+/// Answers one connection per response, in order, writing each response
+/// verbatim.
+pub fn serve<R>(responses: Vec<R>) -> u16
+where
+    R: AsRef<[u8]> + Send + 'static,
+{
+    let mut responses = responses.into_iter();
+    serve_with(move |_, mut stream| {
+        if let Some(response) = responses.next() {
+            stream.write_all(response.as_ref()).unwrap();
+        }
+    })
+}
+
+/// A local port with nothing listening on it.
+pub fn closed_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// A detached text node holding `value`.
+pub fn text_node(value: &str) -> Rc<Node> {
+    Node::new(NodeData::Text {
+        contents: RefCell::new(value.to_string()),
+    })
+}
+
+pub fn layout(html: &str) -> LayoutTree {
+    LayoutTree::build(&parse_html(html), LayoutOptions::default())
+}
+
+pub fn find<'a>(layout_box: &'a LayoutBox, id: &str) -> Option<&'a LayoutBox> {
+    if layout_box
+        .node
+        .as_ref()
+        .is_some_and(|node| node.get_attribute("id").as_deref() == Some(id))
+    {
+        return Some(layout_box);
+    }
+    layout_box.children.iter().find_map(|child| find(child, id))
+}
+
+/// The ids of the elements in `html` matching `selector`, in document order.
+pub fn ids(html: &str, selector: &str) -> Vec<String> {
+    parse_html(html)
+        .query_selector_all(selector)
+        .unwrap()
+        .iter()
+        .filter_map(|node| node.get_attribute("id"))
+        .collect()
+}
+
+pub fn fragments(layout_box: &LayoutBox) -> Vec<&Fragment> {
+    let mut out: Vec<&Fragment> = layout_box
+        .lines
+        .iter()
+        .flat_map(|line| &line.fragments)
+        .collect();
+    for child in &layout_box.children {
+        out.extend(fragments(child));
+    }
+    out
+}
+
+/// Every line box under `layout_box`, in tree order.
+pub fn line_boxes(layout_box: &LayoutBox) -> Vec<&LineBox> {
+    let mut out: Vec<&LineBox> = layout_box.lines.iter().collect();
+    for child in &layout_box.children {
+        out.extend(line_boxes(child));
+    }
+    out
+}
+
+/// The text in `fragment`, or an empty string for images and other boxes.
+pub fn fragment_text(fragment: &Fragment) -> &str {
+    match &fragment.kind {
+        FragmentKind::Text(text) => text,
+        _ => "",
+    }
+}
+
+/// The text on `line`, leaving out images and other non-text fragments.
+pub fn line_text(line: &LineBox) -> String {
+    line.fragments.iter().map(fragment_text).collect()
+}
+
+/// The text of every line under `layout_box`.
+pub fn line_texts(layout_box: &LayoutBox) -> Vec<String> {
+    line_boxes(layout_box).into_iter().map(line_text).collect()
+}
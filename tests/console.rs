@@ -0,0 +1,110 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use icarus::console::{ConsoleLevel, ConsoleValue, format_arguments};
+use icarus::html::parser::parse_html;
+
+fn values(args: &[&str]) -> Vec<ConsoleValue> {
+    args.iter().map(|arg| ConsoleValue::from(*arg)).collect()
+}
+
+#[test]
+fn format_specifiers() {
+    let object = ConsoleValue::Object(vec![
+        (String::from("a"), ConsoleValue::from(1i64)),
+        (String::from("b"), ConsoleValue::Array(vec!["x".into()])),
+    ]);
+    let (text, styles) = format_arguments(&[
+        "%cuser %s is %d (%f) %o 100%% %x".into(),
+        "color: red".into(),
+        "ann".into(),
+        "42.9px".into(),
+        ConsoleValue::from(1.5),
+        object,
+        ConsoleValue::Null,
+    ]);
+    assert_eq!(
+        text,
+        "user ann is 42 (1.5) { a: 1, b: [ 'x' ] } 100% %x null"
+    );
+    assert_eq!(styles, ["color: red"]);
+
+    let (text, _) = format_arguments(&["%d %s".into(), "nope".into()]);
+    assert_eq!(text, "NaN %s");
+    let (text, _) = format_arguments(&[ConsoleValue::from(true), "a".into()]);
+    assert_eq!(text, "true a");
+}
+
+#[test]
+fn messages_are_captured_with_levels_and_groups() {
+    let document = parse_html("<p>x</p>");
+    let console = &document.console;
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&seen);
+    console.subscribe(move |message| log.borrow_mut().push(message.level));
+
+    console.log(&values(&["outer"]));
+    console.group(&values(&["group"]));
+    console.warn(&values(&["inner"]));
+    console.group_end();
+    console.group_end();
+    console.error(&values(&["after"]));
+    console.assert(true, &values(&["hidden"]));
+    console.assert(false, &values(&["shown"]));
+    console.count(None);
+    console.count(None);
+
+    let messages = console.messages();
+    let summary: Vec<_> = messages
+        .iter()
+        .map(|message| (message.level, message.group_depth, message.text.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (ConsoleLevel::Log, 0, "outer"),
+            (ConsoleLevel::Log, 0, "group"),
+            (ConsoleLevel::Warn, 1, "inner"),
+            (ConsoleLevel::Error, 0, "after"),
+            (ConsoleLevel::Error, 0, "Assertion failed: shown"),
+            (ConsoleLevel::Info, 0, "default: 1"),
+            (ConsoleLevel::Info, 0, "default: 2"),
+        ]
+    );
+    assert_eq!(
+        *seen.borrow(),
+        messages.iter().map(|m| m.level).collect::<Vec<_>>()
+    );
+    assert_eq!(messages[2].to_string(), "  inner");
+    assert_eq!(messages[2].to_colored_string(), "\x1b[33m  inner\x1b[0m");
+    assert_eq!(messages[0].to_colored_string(), "outer");
+}
+
+#[test]
+fn table_lists_rows_and_columns() {
+    let row = |name: &str, age: i64| {
+        ConsoleValue::Object(vec![
+            (String::from("name"), name.into()),
+            (String::from("age"), ConsoleValue::from(age)),
+        ])
+    };
+    let document = parse_html("");
+    document.console.table(
+        &ConsoleValue::Array(vec![row("ann", 30), row("bo", 4)]),
+        None,
+    );
+    assert_eq!(
+        document.console.messages()[0].text,
+        "\
+┌─────────┬───────┬─────┐
+│ (index) │ name  │ age │
+├─────────┼───────┼─────┤
+│    0    │ 'ann' │ 30  │
+│    1    │ 'bo'  │  4  │
+└─────────┴───────┴─────┘"
+    );
+
+    document.console.clear();
+    document.console.table(&ConsoleValue::from("plain"), None);
+    assert_eq!(document.console.messages()[0].text, "plain");
+}
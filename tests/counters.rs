@@ -0,0 +1,107 @@
+use icarus::css::selector::parse_selector;
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::style::counters::{CounterStyle, parse_content};
+
+mod common;
+
+use common::{layout, line_texts};
+
+fn lines(html: &str) -> Vec<String> {
+    line_texts(&layout(html).root)
+}
+
+#[test]
+fn nested_lists_number_with_counters() {
+    let lines = lines(
+        "<style>
+            ol { counter-reset: item }
+            li { display: block }
+            li::before { counter-increment: item; content: counters(item, \".\") \". \" }
+        </style>
+        <ol><li>One</li><li>Two<ol><li>Inner</li><li>Again</li></ol></li><li>Three</li></ol>",
+    );
+    assert_eq!(
+        lines,
+        ["1. One", "2. Two", "2.1. Inner", "2.2. Again", "3. Three"]
+    );
+}
+
+#[test]
+fn headings_are_numbered_with_styles() {
+    let lines = lines(
+        "<style>
+            body { counter-reset: chapter }
+            h1 { counter-increment: chapter; counter-reset: section }
+            h1:before { content: \"Chapter \" counter(chapter, upper-roman) \": \" }
+            h2 { counter-increment: section 2 }
+            h2::before { content: counter(chapter) \".\" counter(section, lower-alpha) \" \" }
+            h2::after { content: \" [\" attr(data-tag) \"]\" }
+        </style>
+        <h1>Intro</h1><h2 data-tag=x>First</h2><h2>Second</h2>
+        <h1>Body</h1><h2>Third</h2>",
+    );
+    assert_eq!(
+        lines,
+        [
+            "Chapter I: Intro",
+            "1.b First [x]",
+            "1.d Second []",
+            "Chapter II: Body",
+            "2.b Third []",
+        ]
+    );
+}
+
+#[test]
+fn hidden_elements_do_not_increment_counters() {
+    let lines = lines(
+        "<style>
+            p { counter-increment: n }
+            p::before { content: counter(n) \" \" }
+            .skip { display: none }
+        </style>
+        <p>a</p><p class=skip>b</p><p>c</p>",
+    );
+    assert_eq!(lines, ["1 a", "2 c"]);
+}
+
+#[test]
+fn generated_content_updates_after_mutation() {
+    let document = parse_html(
+        "<style>li { display: block; counter-increment: n } li::before { content: counter(n) \")\" }</style>
+         <ul><li>a</li><li id=b>b</li></ul>",
+    );
+    let mut tree = LayoutTree::build(&document, LayoutOptions::default());
+    let b = document.query_selector("#b").unwrap().unwrap();
+    let ul = b.parent.borrow().upgrade().unwrap();
+    let first = ul.children.borrow()[0].clone();
+    Node::remove_child(&ul, &first);
+    tree.update(&document, LayoutOptions::default());
+
+    assert_eq!(line_texts(&tree.root), ["1)b"]);
+}
+
+#[test]
+fn pseudo_element_selectors() {
+    let selector = parse_selector("ul > li.a::before").unwrap();
+    assert!(selector.pseudo_element().is_some());
+    assert!(parse_selector("p::before span").is_none());
+    assert!(parse_selector("p::before.a").is_none());
+    assert!(parse_selector("p::marker").is_none());
+
+    let document = parse_html("<p>x</p>");
+    assert!(document.query_selector("p::after").unwrap().is_none());
+}
+
+#[test]
+fn content_values() {
+    let items = parse_content("\"\\201C\" counters(a, \").\", upper-alpha) attr(title)").unwrap();
+    assert_eq!(items.len(), 3);
+    assert!(parse_content("counter(a, bogus)").is_none());
+    assert_eq!(parse_content("none"), Some(Vec::new()));
+    assert_eq!(CounterStyle::LowerRoman.format(1994), "mcmxciv");
+    assert_eq!(CounterStyle::UpperAlpha.format(28), "AB");
+    assert_eq!(CounterStyle::DecimalLeadingZero.format(7), "07");
+}
@@ -0,0 +1,59 @@
+use icarus::css::selector::{Specificity, parse_selector};
+use icarus::css::values::{Color, Length};
+use icarus::css::{Origin, Stylesheet};
+
+#[test]
+fn rules_and_declarations_are_parsed() {
+    let sheet = Stylesheet::parse(
+        "/* comment */ @charset \"utf-8\";
+         p, .note { color: red !important; font-size: 12px }
+         p > { color: blue }
+         #main em { margin: 1px 2px }",
+        Origin::Author,
+    );
+    assert_eq!(sheet.rules.len(), 2);
+    let first = &sheet.rules[0];
+    assert_eq!(first.selectors.len(), 2);
+    assert_eq!(first.declarations[0].name, "color");
+    assert_eq!(first.declarations[0].value, "red");
+    assert!(first.declarations[0].important);
+    assert!(!first.declarations[1].important);
+
+    let margins: Vec<(&str, &str)> = sheet.rules[1]
+        .declarations
+        .iter()
+        .map(|declaration| (declaration.name.as_str(), declaration.value.as_str()))
+        .collect();
+    assert_eq!(
+        margins,
+        [
+            ("margin-top", "1px"),
+            ("margin-right", "2px"),
+            ("margin-bottom", "1px"),
+            ("margin-left", "2px"),
+        ]
+    );
+}
+
+#[test]
+fn selector_specificity() {
+    let specificity = |selector: &str| parse_selector(selector).unwrap().specificity();
+    assert_eq!(specificity("p"), Specificity(0, 0, 1));
+    assert_eq!(specificity("#main .note > em"), Specificity(1, 1, 1));
+    assert_eq!(specificity("div.a.b"), Specificity(0, 2, 1));
+    assert!(specificity("#a") > specificity(".a.b.c"));
+    assert!(parse_selector("p >").is_none());
+}
+
+#[test]
+fn values_are_parsed() {
+    assert_eq!(Color::parse("red").unwrap().to_hex(), "#ff0000");
+    assert_eq!(Color::parse("#0f0").unwrap().to_hex(), "#00ff00");
+    assert_eq!(Color::parse("rgb(0, 0, 255)").unwrap().to_hex(), "#0000ff");
+    assert!(Color::parse("transparent").unwrap().is_transparent());
+    assert!(Color::parse("nonsense").is_none());
+    assert_eq!(Length::parse("2em"), Some(Length::Em(2.0)));
+    assert_eq!(Length::parse("50%"), Some(Length::Percent(50.0)));
+    assert_eq!(Length::parse("auto"), Some(Length::Auto));
+    assert_eq!(Length::parse("wide"), None);
+}
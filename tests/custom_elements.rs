@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use icarus::custom_elements::{CustomElementDefinition, is_valid_custom_element_name};
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+fn logging(name: &str, log: &Log) -> CustomElementDefinition {
+    let mut definition = CustomElementDefinition::new(name);
+    definition.observed_attributes = vec![String::from("state")];
+    let connected = Rc::clone(log);
+    definition.connected_callback = Some(Box::new(move |element| {
+        connected.borrow_mut().push(format!(
+            "connected {}",
+            element.get_attribute("id").unwrap_or_default()
+        ))
+    }));
+    let disconnected = Rc::clone(log);
+    definition.disconnected_callback = Some(Box::new(move |element| {
+        disconnected.borrow_mut().push(format!(
+            "disconnected {}",
+            element.get_attribute("id").unwrap_or_default()
+        ))
+    }));
+    let changed = Rc::clone(log);
+    definition.attribute_changed_callback = Some(Box::new(move |_, name, old, new| {
+        changed
+            .borrow_mut()
+            .push(format!("{} {:?} -> {:?}", name, old, new))
+    }));
+    definition
+}
+
+#[test]
+fn names_must_be_valid_custom_element_names() {
+    assert!(is_valid_custom_element_name("my-widget"));
+    assert!(is_valid_custom_element_name("x-é"));
+    for invalid in ["widget", "My-widget", "1-widget", "font-face", "my-Widget"] {
+        assert!(!is_valid_custom_element_name(invalid), "{}", invalid);
+    }
+
+    let document = parse_html("");
+    assert!(
+        document
+            .define_custom_element(CustomElementDefinition::new("plain"))
+            .is_err()
+    );
+    document
+        .define_custom_element(CustomElementDefinition::new("my-widget"))
+        .unwrap();
+    assert!(
+        document
+            .define_custom_element(CustomElementDefinition::new("my-widget"))
+            .is_err()
+    );
+}
+
+#[test]
+fn defining_upgrades_parsed_elements_and_runs_callbacks() {
+    let document =
+        parse_html("<my-toggle id=a state=on></my-toggle><div><my-toggle id=b></my-toggle></div>");
+    let log = Log::default();
+    document
+        .define_custom_element(logging("my-toggle", &log))
+        .unwrap();
+    assert_eq!(
+        *log.borrow(),
+        ["state None -> Some(\"on\")", "connected a", "connected b"]
+    );
+    log.borrow_mut().clear();
+
+    let a = document.get_element_by_id("a").unwrap();
+    Node::set_attribute(&a, "state", "off");
+    Node::set_attribute(&a, "title", "ignored");
+    Node::remove_attribute(&a, "state");
+    assert_eq!(
+        *log.borrow(),
+        [
+            "state Some(\"on\") -> Some(\"off\")",
+            "state Some(\"off\") -> None"
+        ]
+    );
+    log.borrow_mut().clear();
+
+    let b = document.get_element_by_id("b").unwrap();
+    let div = b.parent.borrow().upgrade().unwrap();
+    Node::detach(&div);
+    let body = document.get_elements_by_tag_name("body")[0].clone();
+    Node::append_child(&body, Rc::clone(&div));
+    assert_eq!(*log.borrow(), ["disconnected b", "connected b"]);
+}
@@ -0,0 +1,110 @@
+use icarus::css::values::Color;
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::DisplayList;
+use icarus::paint::damage::Damage;
+use icarus::paint::raster::Canvas;
+
+const PAGE: &str = "<body style='margin: 0'>
+    <div style='height: 20px; background-color: #ff0000'></div>
+    <div id=changed style='height: 20px; background-color: #00ff00'></div>
+    <div style='height: 20px; background-color: #0000ff'></div></body>";
+
+fn options() -> LayoutOptions {
+    LayoutOptions {
+        viewport_width: 100.0,
+        ..LayoutOptions::default()
+    }
+}
+
+#[test]
+fn rects_are_snapped_and_merged() {
+    let mut damage = Damage::new();
+    assert!(damage.is_empty());
+    damage.add(Rect::new(10.5, 10.5, 5.0, 5.0));
+    assert_eq!(damage.rects(), [Rect::new(9.0, 9.0, 8.0, 8.0)]);
+    damage.add(Rect::new(0.0, 0.0, 0.0, 40.0));
+    assert_eq!(damage.rects().len(), 1);
+
+    damage.add(Rect::new(14.0, 14.0, 4.0, 4.0));
+    damage.add(Rect::new(50.0, 50.0, 2.0, 2.0));
+    assert_eq!(
+        damage.rects(),
+        [
+            Rect::new(9.0, 9.0, 10.0, 10.0),
+            Rect::new(49.0, 49.0, 4.0, 4.0)
+        ]
+    );
+    assert_eq!(damage.bounds(), Some(Rect::new(9.0, 9.0, 44.0, 44.0)));
+
+    let clipped = damage.offset(-40.0).clip(Rect::new(0.0, 0.0, 100.0, 100.0));
+    assert_eq!(clipped.rects(), [Rect::new(49.0, 9.0, 4.0, 4.0)]);
+
+    for i in 0..20 {
+        damage.add(Rect::new(i as f32 * 10.0, 200.0, 2.0, 2.0));
+        assert!(damage.rects().len() <= 16);
+    }
+    assert_eq!(damage.bounds(), Some(Rect::new(-1.0, 9.0, 194.0, 194.0)));
+}
+
+#[test]
+fn changed_items_damage_only_their_bounds() {
+    let document = parse_html(PAGE);
+    let old = DisplayList::build(&LayoutTree::build(&document, options()));
+    assert!(Damage::between(&old, &old).is_empty());
+
+    let changed = document.get_element_by_id("changed").unwrap();
+    Node::set_attribute(&changed, "style", "height: 20px; background-color: #ffff00");
+    let new = DisplayList::build(&LayoutTree::build(&document, options()));
+    let damage = Damage::between(&old, &new);
+    let bounds = damage.bounds().unwrap();
+    assert!(bounds.y <= 20.0 && bounds.bottom() >= 40.0, "{:?}", bounds);
+    assert!(bounds.y > 10.0 && bounds.bottom() < 50.0, "{:?}", bounds);
+
+    let mut partial = Canvas::new(100, 60, Color::WHITE);
+    partial.paint(&old, None, 0.0);
+    for rect in damage.rects() {
+        partial.clip = Some(*rect);
+        partial.fill_rect(*rect, Color::WHITE);
+        partial.paint(&new, None, 0.0);
+    }
+    partial.clip = None;
+
+    let mut full = Canvas::new(100, 60, Color::WHITE);
+    full.paint(&new, None, 0.0);
+    assert_eq!(partial.pixels, full.pixels);
+    assert_eq!(
+        partial.pixel(50, 30),
+        Some(Color::rgb(255, 255, 0).to_u32())
+    );
+}
+
+#[test]
+fn scrolling_shifts_pixels_and_clears_the_exposed_strip() {
+    let mut canvas = Canvas::new(2, 4, Color::WHITE);
+    canvas.fill_rect(Rect::new(0.0, 3.0, 2.0, 1.0), Color::BLACK);
+    canvas.scroll(2, Color::WHITE);
+    assert_eq!(canvas.pixel(0, 1), Some(Color::BLACK.to_u32()));
+    assert_eq!(canvas.pixel(0, 3), Some(Color::WHITE.to_u32()));
+    canvas.scroll(-1, Color::WHITE);
+    assert_eq!(canvas.pixel(1, 2), Some(Color::BLACK.to_u32()));
+    assert_eq!(canvas.pixel(1, 0), Some(Color::WHITE.to_u32()));
+}
+
+#[test]
+fn clipped_damage_stays_inside_the_viewport() {
+    let mut damage = Damage::new();
+    damage.add(Rect::new(0.0, 0.0, 100.0, 10.0));
+    damage.add(Rect::new(20.0, 90.0, 10.0, 20.0));
+    let viewport = Rect::new(0.0, 0.0, 100.0, 100.0);
+    let clipped = damage.clip(viewport);
+    assert_eq!(
+        clipped.rects(),
+        [
+            Rect::new(0.0, 0.0, 100.0, 11.0),
+            Rect::new(19.0, 89.0, 12.0, 11.0)
+        ]
+    );
+    assert!(clipped.clip(Rect::new(200.0, 0.0, 10.0, 10.0)).is_empty());
+}
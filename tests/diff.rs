@@ -0,0 +1,165 @@
+use std::rc::Rc;
+
+use icarus::diff::{DomPatch, diff};
+use icarus::dom::{Document, Node, NodeData};
+use icarus::html::parser::parse_html;
+use icarus::html::serialize::outer_html;
+
+fn node_at(document: &Document, path: &[usize]) -> Rc<Node> {
+    let mut node = Rc::clone(&document.root);
+    for index in path {
+        let child = Rc::clone(&node.children.borrow()[*index]);
+        node = child;
+    }
+    node
+}
+
+fn parse_node(html: &str) -> Rc<Node> {
+    let document = parse_html(&format!("<body>{}", html));
+    let body = document.get_elements_by_tag_name("body")[0].clone();
+    let node = Rc::clone(&body.children.borrow()[0]);
+    Node::detach(&node);
+    node
+}
+
+fn apply(document: &Document, patches: &[DomPatch]) {
+    for patch in patches {
+        let (parent_path, index) = patch.path().split_at(patch.path().len() - 1);
+        match patch {
+            DomPatch::Added { html, .. } => {
+                let parent = node_at(document, parent_path);
+                let sibling = parent.children.borrow().get(index[0]).cloned();
+                match sibling {
+                    Some(sibling) => Node::insert_before(&parent, parse_node(html), &sibling),
+                    None => Node::append_child(&parent, parse_node(html)),
+                }
+            }
+            DomPatch::Removed { html, .. } => {
+                let node = node_at(document, patch.path());
+                let described = match &node.data {
+                    NodeData::Text { contents } => contents.borrow().clone(),
+                    _ => outer_html(&node),
+                };
+                assert_eq!(&described, html, "{}", patch);
+                Node::detach(&node);
+            }
+            DomPatch::TextChanged { old, new, .. } => {
+                let node = node_at(document, patch.path());
+                assert_eq!(&node.get_text_content(), old, "{}", patch);
+                Node::set_character_data(&node, new);
+            }
+            DomPatch::AttributeAdded { name, value, .. }
+            | DomPatch::AttributeChanged {
+                name, new: value, ..
+            } => Node::set_attribute(&node_at(document, patch.path()), name, value),
+            DomPatch::AttributeRemoved { name, .. } => {
+                Node::remove_attribute(&node_at(document, patch.path()), name)
+            }
+        }
+    }
+}
+
+fn replays(old: &str, new: &str) -> Vec<DomPatch> {
+    let (a, b) = (parse_html(old), parse_html(new));
+    let patches = diff(&a, &b);
+    apply(&a, &patches);
+    assert_eq!(outer_html(&a.root), outer_html(&b.root), "{:#?}", patches);
+    patches
+}
+
+#[test]
+fn identical_documents_have_no_patches() {
+    let html = "<div id=a><p class=x>one</p><!-- c --><ul><li>1<li>2</ul></div>";
+    assert!(replays(html, html).is_empty());
+}
+
+#[test]
+fn added_and_removed_nodes() {
+    let patches = replays(
+        "<div><p>one</p><p>two</p></div>",
+        "<div><p>one</p><span>new</span><p>two</p><p>three</p></div>",
+    );
+    assert_eq!(
+        patches
+            .iter()
+            .filter(|patch| matches!(patch, DomPatch::Added { .. }))
+            .count(),
+        2
+    );
+    assert!(
+        patches
+            .iter()
+            .all(|patch| matches!(patch, DomPatch::Added { .. }))
+    );
+
+    let patches = replays(
+        "<div><p>one</p><p>two</p><p>three</p></div>",
+        "<div><p>three</p></div>",
+    );
+    assert!(
+        patches
+            .iter()
+            .all(|patch| matches!(patch, DomPatch::Removed { .. }))
+    );
+    assert_eq!(patches[0].path(), patches[1].path());
+}
+
+#[test]
+fn attribute_and_text_changes() {
+    let patches = replays(
+        "<p id=x class=old title=gone>hello</p>",
+        "<p id=x class=new lang=en>goodbye</p>",
+    );
+    let path = patches[0].path().to_vec();
+    assert!(patches.contains(&DomPatch::AttributeChanged {
+        path: path.clone(),
+        name: String::from("class"),
+        old: String::from("old"),
+        new: String::from("new"),
+    }));
+    assert!(patches.contains(&DomPatch::AttributeRemoved {
+        path: path.clone(),
+        name: String::from("title"),
+        value: String::from("gone"),
+    }));
+    assert!(patches.contains(&DomPatch::AttributeAdded {
+        path: path.clone(),
+        name: String::from("lang"),
+        value: String::from("en"),
+    }));
+    let mut text_path = path.clone();
+    text_path.push(0);
+    assert!(patches.contains(&DomPatch::TextChanged {
+        path: text_path,
+        old: String::from("hello"),
+        new: String::from("goodbye"),
+    }));
+}
+
+#[test]
+fn reordered_children_replay_in_sequence() {
+    replays(
+        "<ul><li id=a>a</li><li id=b>b</li><li id=c>c</li><li id=d>d</li></ul>",
+        "<ul><li id=d>d</li><li id=b>b</li><li id=a>a</li><li id=c>c</li></ul>",
+    );
+    replays(
+        "<div><p>1</p><section><p>x</p><p>y</p></section><p>2</p></div>",
+        "<div><p>0</p><p>2</p><section><p>y</p><p>z</p></section></div>",
+    );
+}
+
+#[test]
+fn deep_change_is_a_single_leaf_patch() {
+    let depth = 200;
+    let nested = |leaf: &str| {
+        format!(
+            "{}{}{}",
+            "<div>".repeat(depth),
+            leaf,
+            "</div>".repeat(depth)
+        )
+    };
+    let patches = replays(&nested("old"), &nested("new"));
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches[0].path().len(), depth + 3);
+}
@@ -0,0 +1,72 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::raster::Canvas;
+use icarus::paint::{DisplayItem, DisplayList};
+
+fn display_list(html: &str) -> DisplayList {
+    DisplayList::build(&LayoutTree::build(
+        &parse_html(html),
+        LayoutOptions::default(),
+    ))
+}
+
+#[test]
+fn backgrounds_borders_and_text_are_painted_in_order() {
+    let list = display_list(
+        "<body style='margin: 0'>
+         <div style='background-color: #ff0000; border: 4px solid #0000ff; color: #00ff00'>hi</div>
+         </body>",
+    );
+    let solids: Vec<(Rect, Color)> = list
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::SolidColor { rect, color } => Some((*rect, *color)),
+            _ => None,
+        })
+        .collect();
+    let height = solids[0].0.height;
+    assert_eq!(
+        solids[0],
+        (Rect::new(0.0, 0.0, 800.0, height), Color::rgb(255, 0, 0))
+    );
+    assert_eq!(solids.len(), 5);
+    assert!(
+        solids[1..]
+            .iter()
+            .all(|(_, color)| *color == Color::rgb(0, 0, 255))
+    );
+
+    let text = list.items.iter().position(|item| {
+        matches!(item, DisplayItem::Text { text, color, .. } if text == "hi" && *color == Color::rgb(0, 255, 0))
+    });
+    let last_border = list
+        .items
+        .iter()
+        .rposition(|item| matches!(item, DisplayItem::SolidColor { .. }));
+    assert!(text.expect("text item") > last_border.expect("border item"));
+}
+
+#[test]
+fn transparent_backgrounds_emit_no_items() {
+    let list = display_list("<body style='margin: 0'><div>plain</div></body>");
+    assert!(
+        !list
+            .items
+            .iter()
+            .any(|item| matches!(item, DisplayItem::SolidColor { .. }))
+    );
+}
+
+#[test]
+fn solid_rects_are_rasterized_and_clipped_to_the_canvas() {
+    let white = Color::rgb(255, 255, 255);
+    let red = Color::rgb(255, 0, 0);
+    let mut canvas = Canvas::new(10, 10, white);
+    canvas.fill_rect(Rect::new(5.0, 5.0, 20.0, 20.0), red);
+    assert_eq!(canvas.pixels[0], white.to_u32());
+    assert_eq!(canvas.pixels[4 * 10 + 4], white.to_u32());
+    assert_eq!(canvas.pixels[5 * 10 + 5], red.to_u32());
+    assert_eq!(canvas.pixels[9 * 10 + 9], red.to_u32());
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Instant;
+
+use icarus::net::download::{self, filename_from_content_disposition, percent_decode};
+use icarus::net::{Response, Timing, http};
+use icarus::settings::Settings;
+use url::Url;
+
+mod common;
+
+#[test]
+fn content_disposition_filenames() {
+    let name = filename_from_content_disposition;
+    assert_eq!(
+        name("attachment; filename=\"report.pdf\"").as_deref(),
+        Some("report.pdf")
+    );
+    assert_eq!(
+        name("attachment; FILENAME=plain.txt").as_deref(),
+        Some("plain.txt")
+    );
+    assert_eq!(
+        name("attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve%20file.txt")
+            .as_deref(),
+        Some("naïve file.txt")
+    );
+    assert_eq!(
+        name("attachment; filename=\"../../etc/passwd\"").as_deref(),
+        Some("passwd")
+    );
+    assert_eq!(
+        name("attachment; filename=\".hidden\"").as_deref(),
+        Some("hidden")
+    );
+    assert_eq!(name("attachment; filename=\"\""), None);
+    assert_eq!(name("inline"), None);
+}
+
+#[test]
+fn invalid_percent_escapes_pass_through() {
+    assert_eq!(percent_decode("a%20b").as_deref(), Some("a b"));
+    assert_eq!(percent_decode("100%").as_deref(), Some("100%"));
+    assert_eq!(percent_decode("%zz%2").as_deref(), Some("%zz%2"));
+    assert_eq!(percent_decode("%+1x").as_deref(), Some("%+1x"));
+    assert_eq!(percent_decode("%ff"), None);
+}
+
+#[test]
+fn redirects_to_other_schemes_are_refused() {
+    let port = common::serve(vec![
+        "HTTP/1.1 302 Found\r\nLocation: file:///etc/passwd\r\nContent-Length: 0\r\n\r\n",
+    ]);
+
+    let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+    let err = http::get(&url, &Settings::default())
+        .err()
+        .expect("redirect refused");
+    assert!(err.to_string().contains("unsupported scheme"), "{}", err);
+}
+
+#[test]
+fn host_header_keeps_an_explicit_port() {
+    let (sender, received) = mpsc::channel();
+    let port = common::serve_with(move |head, mut stream| {
+        sender.send(head.to_string()).unwrap();
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+    });
+
+    let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+    http::get(&url, &Settings::default()).unwrap();
+    let head = received.recv().unwrap();
+    assert!(
+        head.contains(&format!("\r\nHost: 127.0.0.1:{}\r\n", port)),
+        "{}",
+        head
+    );
+}
+
+fn response(body: impl Read + Send + 'static) -> Response {
+    Response {
+        url: Url::parse("http://example.com/files/report.txt").unwrap(),
+        status: 200,
+        headers: Vec::new(),
+        timing: Timing::started(Instant::now()),
+        body: Box::new(body),
+    }
+}
+
+fn download_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("icarus-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn entries(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn saves_never_overwrite_existing_files() {
+    let dir = download_dir("downloads");
+    fs::write(dir.join("report.txt"), "kept").unwrap();
+
+    let first = download::save(response(&b"one"[..]), &dir, |_, _| {}).unwrap();
+    let second = download::save(response(&b"two"[..]), &dir, |_, _| {}).unwrap();
+    assert_eq!(first, dir.join("report (1).txt"));
+    assert_eq!(second, dir.join("report (2).txt"));
+    assert_eq!(fs::read_to_string(dir.join("report.txt")).unwrap(), "kept");
+    assert_eq!(fs::read_to_string(&second).unwrap(), "two");
+    assert_eq!(
+        entries(&dir),
+        ["report (1).txt", "report (2).txt", "report.txt"]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+struct Interrupted(bool);
+
+impl Read for Interrupted {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if std::mem::replace(&mut self.0, true) {
+            return Err(io::Error::new(ErrorKind::ConnectionReset, "reset"));
+        }
+        buffer[..4].copy_from_slice(b"half");
+        Ok(4)
+    }
+}
+
+#[test]
+fn failed_downloads_leave_no_file_behind() {
+    let dir = download_dir("interrupted");
+    assert!(download::save(response(Interrupted(false)), &dir, |_, _| {}).is_err());
+    assert!(entries(&dir).is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::layout::Rect;
+use icarus::loader;
+use icarus::net::error::LoadError;
+use icarus::settings::Settings;
+use icarus::shell::pipeline::{Event, Pipeline};
+use icarus::storage::StorageContext;
+
+mod common;
+
+use common::closed_port;
+
+fn classify(url: &str) -> LoadError {
+    let error = match loader::open(url, &Settings::default()) {
+        Ok(_) => panic!("{} loaded", url),
+        Err(error) => error,
+    };
+    LoadError::classify(url, &error)
+}
+
+fn rendered_source(url: &str) -> (Option<String>, String) {
+    let pipeline = Pipeline::spawn(
+        url,
+        Rect::new(0.0, 0.0, 400.0, 300.0),
+        Arc::new(Settings::default()),
+        StorageContext::default(),
+    )
+    .unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut committed = None;
+    loop {
+        for event in pipeline.poll().unwrap() {
+            match event {
+                Event::Committed(url) => committed = Some(url),
+                Event::Source(source) => return (committed.unwrap(), source),
+                Event::Failed(message) => panic!("no error page: {}", message),
+                _ => {}
+            }
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for the page");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn failures_are_classified() {
+    let port = closed_port();
+    assert_eq!(
+        classify(&format!("http://127.0.0.1:{}/", port)),
+        LoadError::ConnectionRefused
+    );
+    let missing = std::env::temp_dir().join("icarus-no-such-file.html");
+    assert_eq!(
+        classify(url::Url::from_file_path(&missing).unwrap().as_str()),
+        LoadError::FileNotFound
+    );
+    let other = anyhow::anyhow!("boom");
+    assert_eq!(
+        LoadError::classify("https://example.com/", &other),
+        LoadError::SecureConnection
+    );
+    assert_eq!(
+        LoadError::classify("gopher://example.com/", &other),
+        LoadError::UnsupportedScheme
+    );
+    assert_eq!(
+        LoadError::classify("http://example.com/", &other),
+        LoadError::Other
+    );
+}
+
+#[test]
+fn codes_round_trip_and_statuses_get_titles() {
+    for kind in [
+        LoadError::NameNotResolved,
+        LoadError::ConnectionRefused,
+        LoadError::TimedOut,
+        LoadError::FileNotFound,
+        LoadError::HttpStatus(404),
+        LoadError::NotHtml,
+        LoadError::Other,
+    ] {
+        assert_eq!(LoadError::from_code(&kind.code()), kind);
+    }
+    assert_eq!(LoadError::from_code("bogus"), LoadError::Other);
+    assert_eq!(LoadError::HttpStatus(404).title(), "404 Not Found");
+    assert_eq!(LoadError::HttpStatus(599).title(), "HTTP error 599");
+    assert_ne!(
+        LoadError::HttpStatus(404).advice(),
+        LoadError::HttpStatus(500).advice()
+    );
+}
+
+#[test]
+fn refused_connections_render_an_error_page() {
+    let url = format!("http://127.0.0.1:{}/page", closed_port());
+    let (committed, source) = rendered_source(&url);
+    assert_eq!(committed, None);
+    assert!(
+        source.contains("<title>Connection refused</title>"),
+        "{}",
+        source
+    );
+    assert!(source.contains(&format!("<a href=\"{}\">Try again</a>", url)));
+}
+
+#[test]
+fn empty_http_errors_render_an_error_page() {
+    let port = common::serve(vec![
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Type: text/html\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+    let (_, source) = rendered_source(&format!("http://127.0.0.1:{}/", port));
+    assert!(
+        source.contains("<title>503 Service Unavailable</title>"),
+        "{}",
+        source
+    );
+}
@@ -0,0 +1,83 @@
+use icarus::html::escape::{CharacterReferences, escape_text, escape_xml_text, unescape_numeric};
+use icarus::html::parser::parse_html;
+use icarus::html::serialize::{inner_html, outer_html};
+use icarus::html::{escape, unescape};
+
+#[test]
+fn escape_round_trips() {
+    let text = r#"<a href="x">Tom & Jerry's</a>"#;
+    assert_eq!(
+        escape(text),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+    );
+    assert_eq!(unescape(&escape(text)), text);
+}
+
+#[test]
+fn unescape_follows_the_tokenizer() {
+    assert_eq!(unescape("&lt;&gt;&amp;&quot;&apos;"), "<>&\"'");
+    assert_eq!(unescape("&#65;&#x42;&#X43"), "ABC");
+    assert_eq!(unescape("&#128;&#0;&#x110000;"), "\u{20ac}\u{fffd}\u{fffd}");
+    assert_eq!(
+        unescape("&notit; &amp &unknown; & &#;"),
+        "¬it; & &unknown; & &#;"
+    );
+    assert_eq!(unescape_numeric("&amp;&#169;"), "&amp;©");
+}
+
+#[test]
+fn serialization_escapes_text_and_attributes() {
+    let document = parse_html(
+        r#"<p title="a &quot;b&quot; <c> &amp; 'd'">1 &lt; 2 &amp;&nbsp;3 > 0 "q" 'q'</p><script>if (a < b && c) {}</script>"#,
+    );
+    let p = &document.get_elements_by_tag_name("p")[0];
+    assert_eq!(
+        outer_html(p),
+        r#"<p title="a &quot;b&quot; &lt;c&gt; &amp; 'd'">1 &lt; 2 &amp;&nbsp;3 &gt; 0 "q" 'q'</p>"#
+    );
+    let script = &document.get_elements_by_tag_name("script")[0];
+    assert_eq!(inner_html(script), "if (a < b && c) {}");
+    assert_eq!(
+        outer_html(&parse_html("<br><img alt=x>").root),
+        r#"<html><head></head><body><br><img alt="x"></body></html>"#
+    );
+}
+
+#[test]
+fn attribute_text_can_keep_or_decode_references() {
+    let document = parse_html(r#"<a title="&amp;#169; 2024">x</a>"#);
+    let a = &document.get_elements_by_tag_name("a")[0];
+    assert_eq!(
+        a.attribute_text("title", CharacterReferences::Keep)
+            .as_deref(),
+        Some("&amp;#169; 2024")
+    );
+    assert_eq!(
+        a.attribute_text("title", CharacterReferences::Decode)
+            .as_deref(),
+        Some("&#169; 2024")
+    );
+}
+
+#[test]
+fn attribute_references_are_decoded_once() {
+    let document = parse_html(r#"<a title="&amp;#65; &lt;b&gt; &#169;&nbsp;x">x</a>"#);
+    let a = &document.get_elements_by_tag_name("a")[0];
+    assert_eq!(
+        a.attribute_text("title", CharacterReferences::Decode)
+            .as_deref(),
+        Some("&#65; <b> ©\u{a0}x")
+    );
+    assert_eq!(
+        a.attribute_text("title", CharacterReferences::Keep)
+            .as_deref(),
+        Some("&amp;#65; &lt;b&gt; ©&nbsp;x")
+    );
+    assert_eq!(a.attribute_text("href", CharacterReferences::Keep), None);
+}
+
+#[test]
+fn xml_text_has_no_html_only_references() {
+    assert_eq!(escape_xml_text("a\u{a0}<b>\u{1}&"), "a&#160;&lt;b&gt;&amp;");
+    assert_eq!(escape_text("a\u{a0}b"), "a&nbsp;b");
+}
@@ -0,0 +1,81 @@
+use icarus::extract::PropertyValue;
+use icarus::html::parser::parse_html;
+use serde_json::json;
+
+#[test]
+fn microdata_items_nest_and_follow_itemref() {
+    let document = parse_html(
+        r#"<div itemscope itemtype="https://schema.org/Movie" itemref="rating">
+             <h1 itemprop="name">Avatar</h1>
+             <a itemprop="url" href="https://example.com/avatar">site</a>
+             <div itemprop="director" itemscope itemtype="https://schema.org/Person">
+               <span itemprop="name">James Cameron</span>
+               <time itemprop="birthDate" datetime="1954-08-16">August 16, 1954</time>
+             </div>
+             <meta itemprop="genre alternateGenre" content="Science fiction">
+           </div>
+           <p id="rating">Rated <data itemprop="rating" value="8">eight</data></p>"#,
+    );
+    let items = document.microdata();
+    assert_eq!(items.len(), 1);
+    let movie = &items[0];
+    assert_eq!(movie.types, ["https://schema.org/Movie"]);
+    assert_eq!(
+        movie.get("name").and_then(PropertyValue::as_str),
+        Some("Avatar")
+    );
+    assert_eq!(
+        movie.get("url"),
+        Some(&PropertyValue::Url(String::from(
+            "https://example.com/avatar"
+        )))
+    );
+    assert_eq!(
+        movie.get("alternateGenre").and_then(PropertyValue::as_str),
+        Some("Science fiction")
+    );
+    assert_eq!(
+        movie.get("rating").and_then(PropertyValue::as_str),
+        Some("8")
+    );
+    let Some(PropertyValue::Item(director)) = movie.get("director") else {
+        panic!("director should be an item");
+    };
+    assert_eq!(
+        director.get("birthDate").and_then(PropertyValue::as_str),
+        Some("1954-08-16")
+    );
+    assert!(director.get("url").is_none());
+    assert_eq!(
+        movie.to_json()["properties"]["director"][0]["properties"]["name"],
+        json!(["James Cameron"])
+    );
+}
+
+#[test]
+fn json_ld_and_social_meta_tags() {
+    let document = parse_html(
+        r#"<head>
+             <script type="application/ld+json">{"@type": "Article", "headline": "Hi"}</script>
+             <script type="application/ld+json">[{"@type": "A"}, {"@type": "B"}]</script>
+             <script type="application/ld+json">{ not json</script>
+             <meta property="og:title" content="Open title">
+             <meta property="og:image" content="https://example.com/a.png">
+             <meta property="og:image:width" content="640">
+             <meta property="og:image" content="https://example.com/b.png">
+             <meta property="og:image:alt" content="Second">
+             <meta name="twitter:card" content="summary_large_image">
+             <meta name="twitter:site" content="@icarus">
+           </head>"#,
+    );
+    let data = document.structured_data();
+    assert_eq!(data.json_ld.len(), 3);
+    assert_eq!(data.json_ld[0]["headline"], json!("Hi"));
+    assert_eq!(data.open_graph.title.as_deref(), Some("Open title"));
+    assert_eq!(data.open_graph.images.len(), 2);
+    assert_eq!(data.open_graph.images[0].width, Some(640));
+    assert_eq!(data.open_graph.images[1].alt.as_deref(), Some("Second"));
+    assert_eq!(data.twitter.card.as_deref(), Some("summary_large_image"));
+    assert_eq!(data.twitter.site.as_deref(), Some("@icarus"));
+    assert!(data.microdata.is_empty());
+}
@@ -0,0 +1,82 @@
+use icarus::custom_elements::CustomElementDefinition;
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+use icarus::html::serialize::outer_html;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn clone_subtree_is_independent() {
+    let document = parse_html("<div id=a class=x><p>one</p><p>two</p></div>");
+    let original = document.get_element_by_id("a").unwrap();
+    let copy = original.clone_subtree();
+
+    assert_eq!(outer_html(&copy), outer_html(&original));
+    assert!(copy.parent.borrow().upgrade().is_none());
+    assert_ne!(copy.id, original.id);
+
+    Node::set_attribute(&copy, "class", "y");
+    let first = Rc::clone(&copy.children.borrow()[0]);
+    Node::detach(&first);
+    assert_eq!(original.get_attribute("class").as_deref(), Some("x"));
+    assert_eq!(original.children.borrow().len(), 2);
+}
+
+#[test]
+fn clone_subtree_copies_shadow_roots() {
+    let document = parse_html(
+        "<div id=host><template shadowrootmode=open><b>inside</b></template>light</div>",
+    );
+    let host = document.get_element_by_id("host").unwrap();
+    let copy = host.clone_subtree();
+    let shadow = copy.open_shadow_root().unwrap();
+    assert!(!Rc::ptr_eq(&shadow, &host.open_shadow_root().unwrap()));
+    assert!(Rc::ptr_eq(&shadow.host().unwrap(), &copy));
+    assert_eq!(shadow.get_text_content(), "inside");
+}
+
+#[test]
+fn extract_builds_a_document_from_matches() {
+    let document = parse_html(
+        "<nav>menu</nav><article><h1>One</h1><article>nested</article></article><aside><article>Two</article></aside>",
+    );
+    let extracted = document.extract("article").unwrap();
+    assert_eq!(
+        outer_html(&extracted.root),
+        "<html><head></head><body><article><h1>One</h1><article>nested</article></article><article>Two</article></body></html>"
+    );
+
+    let first = extracted.query_selector("article").unwrap().unwrap();
+    Node::detach(&first);
+    assert_eq!(document.query_selector_all("article").unwrap().len(), 3);
+    assert!(matches!(extracted.root.data, NodeData::Document { .. }));
+}
+
+#[test]
+fn extract_rejects_invalid_selectors() {
+    let document = parse_html("<p>x</p>");
+    assert!(document.extract("p[").is_err());
+}
+
+#[test]
+fn extracted_custom_elements_are_upgraded_again() {
+    let connected = Rc::new(Cell::new(0));
+    let definition = || {
+        let mut definition = CustomElementDefinition::new("x-card");
+        let connected = Rc::clone(&connected);
+        definition.connected_callback = Some(Box::new(move |_| connected.set(connected.get() + 1)));
+        definition
+    };
+    let document = parse_html("<x-card id=a>card</x-card>");
+    document.define_custom_element(definition()).unwrap();
+    assert_eq!(connected.get(), 1);
+
+    let extracted = document.extract("x-card").unwrap();
+    let copy = extracted.get_element_by_id("a").unwrap();
+    assert!(copy.custom_definition.borrow().is_none());
+    assert_eq!(connected.get(), 1);
+
+    extracted.define_custom_element(definition()).unwrap();
+    assert!(copy.custom_definition.borrow().is_some());
+    assert_eq!(connected.get(), 2);
+}
@@ -0,0 +1,120 @@
+use std::io::Write;
+use std::time::Instant;
+
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::image::Image;
+use icarus::metadata::PageMetadata;
+use icarus::net::{ResponseInfo, Timing};
+use url::Url;
+
+mod common;
+
+fn ico() -> Vec<u8> {
+    let mut bytes = vec![0, 0, 1, 0, 1, 0];
+    bytes.extend([2, 2, 0, 0, 1, 0, 32, 0]);
+    bytes.extend(64u32.to_le_bytes());
+    bytes.extend(22u32.to_le_bytes());
+    for value in [40u32, 2, 4] {
+        bytes.extend(value.to_le_bytes());
+    }
+    bytes.extend([1, 0, 32, 0]);
+    bytes.extend([0; 24]);
+    // Rows are stored bottom-up as BGRA.
+    bytes.extend([0, 0, 255, 255, 0, 255, 0, 255]);
+    bytes.extend([255, 0, 0, 255, 0, 0, 0, 0]);
+    bytes.extend([0; 8]);
+    bytes
+}
+
+fn serve() -> u16 {
+    common::serve_with(|request, mut stream| {
+        let (status, body) = if request.starts_with("GET /favicon.ico ") {
+            ("200 OK", ico())
+        } else {
+            ("404 Not Found", Vec::new())
+        };
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: image/x-icon\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(&body).unwrap();
+    })
+}
+
+fn page(html: &str, url: &str) -> icarus::dom::Document {
+    let mut document = parse_html(html);
+    document.response = Some(ResponseInfo {
+        url: Url::parse(url).unwrap(),
+        status: 200,
+        headers: Vec::new(),
+        timing: Timing::started(Instant::now()),
+    });
+    document
+}
+
+#[test]
+fn ico_files_decode_their_largest_bitmap() {
+    let icon = Image::decode(&ico()).unwrap();
+    assert_eq!((icon.width, icon.height), (2, 2));
+    assert_eq!(
+        icon.pixels,
+        [
+            Color::rgb(0, 0, 255),
+            Color::rgba(0, 0, 0, 0),
+            Color::rgb(255, 0, 0),
+            Color::rgb(0, 255, 0),
+        ]
+    );
+    assert!(Image::decode(&ico()[..30]).is_err());
+    assert!(Image::decode(b"GIF89a").is_err());
+}
+
+#[test]
+fn icon_links_come_before_the_favicon_fallback() {
+    let document = page(
+        "<title>  A   page </title><link rel='shortcut ICON' href=/a.png sizes=16x16>
+         <link rel=stylesheet href=s.css><link rel=icon href=b.png>",
+        "http://example.com/dir/page.html",
+    );
+    let metadata = PageMetadata::from_document(&document);
+    assert_eq!(metadata.title.as_deref(), Some("A page"));
+    assert_eq!(metadata.icons[0].sizes.as_deref(), Some("16x16"));
+    let candidates: Vec<String> = metadata
+        .favicon_candidates(&document)
+        .iter()
+        .map(Url::to_string)
+        .collect();
+    assert_eq!(
+        candidates,
+        [
+            "http://example.com/a.png",
+            "http://example.com/dir/b.png",
+            "http://example.com/favicon.ico"
+        ]
+    );
+
+    let local = page("<link rel=icon href=i.png>", "file:///tmp/page.html");
+    assert_eq!(
+        PageMetadata::from_document(&local)
+            .favicon_candidates(&local)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn load_falls_back_to_favicon_ico() {
+    let port = serve();
+    let document = page(
+        "<link rel=icon href=/missing.png>",
+        &format!("http://127.0.0.1:{}/page", port),
+    );
+    let metadata = PageMetadata::load(&document);
+    let (url, icon) = metadata.favicon.clone().unwrap();
+    assert_eq!(url.path(), "/favicon.ico");
+    assert_eq!((icon.width, icon.height), (2, 2));
+    assert!(metadata.to_string().contains("/favicon.ico (2x2)"));
+}
@@ -0,0 +1,108 @@
+use icarus::feed::{Feed, FeedKind};
+use icarus::html::parser::parse_html;
+use icarus::internal;
+use url::Url;
+
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Example News</title>
+    <link>/</link>
+    <description>All the &lt;b&gt;news&lt;/b&gt;</description>
+    <item>
+      <title>First post</title>
+      <link>/posts/1</link>
+      <guid>post-1</guid>
+      <dc:creator>Ada</dc:creator>
+      <pubDate>Mon, 05 Oct 2026 10:00:00 GMT</pubDate>
+      <description><![CDATA[<p>Hello <em>world</em></p>]]></description>
+    </item>
+    <item><title>Second</title></item>
+  </channel>
+</rss>"#;
+
+const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Log</title>
+  <subtitle>Notes</subtitle>
+  <link rel="self" href="https://example.org/feed.xml"/>
+  <link href="https://example.org/"/>
+  <updated>2026-10-01T00:00:00Z</updated>
+  <entry>
+    <title type="html">A &amp;amp; B</title>
+    <link rel="alternate" href="entries/1"/>
+    <id>urn:uuid:1</id>
+    <author><name>Grace</name></author>
+    <updated>2026-10-01T00:00:00Z</updated>
+    <summary>Short</summary>
+  </entry>
+</feed>"#;
+
+#[test]
+fn parses_rss() {
+    let base = Url::parse("http://example.com/feed.rss").unwrap();
+    let feed = Feed::parse(RSS, Some(&base)).unwrap();
+    assert_eq!(feed.kind, FeedKind::Rss);
+    assert_eq!(feed.title.as_deref(), Some("Example News"));
+    assert_eq!(feed.link.as_deref(), Some("http://example.com/"));
+    assert_eq!(feed.entries.len(), 2);
+    let first = &feed.entries[0];
+    assert_eq!(first.link.as_deref(), Some("http://example.com/posts/1"));
+    assert_eq!(first.author.as_deref(), Some("Ada"));
+    assert_eq!(first.id.as_deref(), Some("post-1"));
+    assert_eq!(
+        first.summary.as_deref(),
+        Some("<p>Hello <em>world</em></p>")
+    );
+    assert_eq!(feed.entries[1].link, None);
+}
+
+#[test]
+fn parses_atom_and_rdf() {
+    let base = Url::parse("https://example.org/feed.xml").unwrap();
+    let feed = Feed::parse(ATOM, Some(&base)).unwrap();
+    assert_eq!(feed.kind, FeedKind::Atom);
+    assert_eq!(feed.link.as_deref(), Some("https://example.org/"));
+    assert_eq!(feed.description.as_deref(), Some("Notes"));
+    let entry = &feed.entries[0];
+    assert_eq!(entry.title.as_deref(), Some("A &amp; B"));
+    assert_eq!(entry.link.as_deref(), Some("https://example.org/entries/1"));
+    assert_eq!(entry.author.as_deref(), Some("Grace"));
+
+    let rdf = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/">
+      <channel><title>RDF</title></channel>
+      <item><title>One</title><link>http://a.example/1</link></item>
+    </rdf:RDF>"#;
+    let feed = Feed::parse(rdf, None).unwrap();
+    assert_eq!(feed.title.as_deref(), Some("RDF"));
+    assert_eq!(feed.entries[0].link.as_deref(), Some("http://a.example/1"));
+
+    assert!(Feed::parse("<html></html>", None).is_err());
+    assert!(Feed::parse("<rss><channel>", None).is_err());
+}
+
+#[test]
+fn discovers_feed_links_and_renders_them() {
+    let document = parse_html(
+        r#"<head>
+             <link rel="alternate" type="application/rss+xml" title="News" href="http://example.com/feed.rss">
+             <link rel="alternate" type="application/atom+xml; charset=utf-8" href="http://example.com/atom.xml">
+             <link rel="alternate" hreflang="fr" href="/fr/">
+             <link rel="stylesheet" type="application/rss+xml" href="/nope">
+           </head>"#,
+    );
+    let links = document.feed_links();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].kind, FeedKind::Rss);
+    assert_eq!(links[0].title.as_deref(), Some("News"));
+    assert_eq!(links[1].kind, FeedKind::Atom);
+
+    let feed = Feed::parse(RSS, Some(&Url::parse("http://example.com/").unwrap())).unwrap();
+    let page = parse_html(&internal::feed_page("http://example.com/feed.rss", &feed));
+    let text = page.root.get_text_content();
+    assert!(text.contains("Example News"));
+    assert!(text.contains("All the news"));
+    assert!(text.contains("Hello world"));
+    assert!(text.contains("(untitled)") || text.contains("Second"));
+    assert_eq!(page.get_elements_by_tag_name("h2").len(), 2);
+}
@@ -0,0 +1,61 @@
+use icarus::find::{FindOptions, Finder};
+use icarus::html::parser::parse_html;
+use icarus::shell::find_bar::FindBar;
+
+#[test]
+fn matches_do_not_cross_block_boundaries() {
+    let document = parse_html("<p>a</p><p>b</p><div>c<br>d</div>");
+    assert!(document.find("ab", FindOptions::default()).is_empty());
+    assert!(document.find("cd", FindOptions::default()).is_empty());
+    assert_eq!(document.find("b", FindOptions::default()).len(), 1);
+}
+
+#[test]
+fn matches_cross_inline_elements() {
+    let document = parse_html("<p>hel<b>lo</b> <span style='display: block'>wor</span>ld</p>");
+    let matches = document.find("hello", FindOptions::default());
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].to_text(), "hello");
+    assert!(document.find("world", FindOptions::default()).is_empty());
+}
+
+#[test]
+fn matching_ignores_case_unless_asked() {
+    let document = parse_html("<p>Rust and rust</p>");
+    assert_eq!(document.find("RUST", FindOptions::default()).len(), 2);
+    let options = FindOptions {
+        case_sensitive: true,
+    };
+    assert_eq!(document.find("Rust", options).len(), 1);
+}
+
+#[test]
+fn finder_cycles_through_matches_and_selects_them() {
+    let document = parse_html("<p>one</p><p>two one</p>");
+    let mut finder = Finder::new(&document, "one", FindOptions::default());
+    assert_eq!(finder.matches.len(), 2);
+    finder.next(&document);
+    finder.next(&document);
+    assert_eq!(finder.current().map(|(index, _)| index), Some(1));
+    assert_eq!(document.selected_text(), "one");
+    finder.next(&document);
+    assert_eq!(finder.current().map(|(index, _)| index), Some(0));
+    finder.previous(&document);
+    assert_eq!(finder.current().map(|(index, _)| index), Some(1));
+}
+
+#[test]
+fn find_bar_reports_its_position() {
+    let mut bar = FindBar::default();
+    bar.open();
+    assert!(bar.open && bar.input.focused);
+    assert_eq!(bar.status(), "");
+    bar.input.insert('x');
+    assert_eq!(bar.status(), "0/0");
+    bar.current = Some(2);
+    bar.total = 5;
+    assert_eq!(bar.status(), "3/5");
+    bar.close();
+    assert!(!bar.open);
+    assert_eq!(bar.query(), "x");
+}
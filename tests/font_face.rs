@@ -0,0 +1,155 @@
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use std::io::Write;
+
+use icarus::css::font_face::FontSource;
+use icarus::css::{Origin, Stylesheet};
+use icarus::loader::{self, Load};
+use icarus::web_font::{FontLoader, decode};
+
+const TABLES: [(&[u8; 4], &[u8]); 2] = [
+    (b"cmap", b"abcabcabcabcabcabcabcabcabcabcabc"),
+    (b"head", b"xyz"),
+];
+
+fn sfnt() -> Vec<u8> {
+    let mut out = vec![0, 1, 0, 0, 0, 2, 0, 32, 0, 1, 0, 0];
+    let mut offset = 12 + 16 * TABLES.len();
+    for (index, (tag, data)) in TABLES.iter().enumerate() {
+        out.extend_from_slice(*tag);
+        out.extend_from_slice(&(index as u32).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in TABLES {
+        out.extend_from_slice(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+fn woff() -> Vec<u8> {
+    let stored: Vec<Vec<u8>> = TABLES
+        .iter()
+        .map(|(_, data)| {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+            encoder.write_all(data).unwrap();
+            let compressed = encoder.finish().unwrap();
+            if compressed.len() < data.len() {
+                compressed
+            } else {
+                data.to_vec()
+            }
+        })
+        .collect();
+    let mut out = b"wOFF".to_vec();
+    out.extend_from_slice(&[0, 1, 0, 0]);
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(TABLES.len() as u16).to_be_bytes());
+    out.resize(44, 0);
+    let mut offset = 44 + 20 * TABLES.len();
+    for (index, ((tag, data), stored)) in TABLES.iter().zip(&stored).enumerate() {
+        out.extend_from_slice(*tag);
+        for value in [offset, stored.len(), data.len(), index] {
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        offset += stored.len().next_multiple_of(4);
+    }
+    for stored in &stored {
+        out.extend_from_slice(stored);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+#[test]
+fn font_face_rules_are_collected() {
+    let sheet = Stylesheet::parse(
+        "@font-face{font-family: 'Web Sans'; font-weight: 700; font-style: italic;
+           src: url(a.woff2) format('woff2'), url(\"a.woff\") format(\"woff\"), local(Web Sans)}
+         @font-face { src: url(nameless.ttf) }
+         p { color: red }",
+        Origin::Author,
+    );
+    assert_eq!(sheet.rules.len(), 1);
+    assert_eq!(sheet.font_faces.len(), 1);
+    let face = &sheet.font_faces[0];
+    assert_eq!(face.family, "Web Sans");
+    assert!(face.bold && face.italic);
+    assert_eq!(
+        face.sources,
+        [
+            FontSource::Url {
+                url: "a.woff2".to_string(),
+                format: Some("woff2".to_string()),
+            },
+            FontSource::Url {
+                url: "a.woff".to_string(),
+                format: Some("woff".to_string()),
+            },
+            FontSource::Local("Web Sans".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn woff_fonts_are_decoded_to_sfnt() {
+    assert_eq!(decode(&sfnt()).unwrap(), sfnt());
+    assert_eq!(decode(&woff()).unwrap(), sfnt());
+    assert!(decode(b"wOF2\0\0\0\0").is_err());
+    assert!(decode(b"<html>").is_err());
+}
+
+#[test]
+fn woff2_fonts_are_decoded_to_sfnt() {
+    let font = decode(include_bytes!("fonts/open-sans.woff2")).unwrap();
+    assert_eq!(font[..4], 0x0001_0000u32.to_be_bytes());
+    let count = u16::from_be_bytes([font[4], font[5]]) as usize;
+    let tags: Vec<&[u8]> = (0..count)
+        .map(|index| &font[12 + index * 16..16 + index * 16])
+        .collect();
+    assert!(tags.is_sorted());
+    assert!(tags.contains(&&b"glyf"[..]) && tags.contains(&&b"loca"[..]));
+
+    let font = fontdue::Font::from_bytes(font, fontdue::FontSettings::default()).unwrap();
+    let (metrics, bitmap) = font.rasterize('A', 32.0);
+    assert!(metrics.width > 0 && metrics.height > 0);
+    assert!(bitmap.iter().any(|&coverage| coverage > 0));
+}
+
+#[test]
+fn oversized_woff_tables_are_refused() {
+    let mut font = woff();
+    // Claim the first table inflates to 4 GiB.
+    font[44 + 12..44 + 16].copy_from_slice(&u32::MAX.to_be_bytes());
+    let err = decode(&font).unwrap_err();
+    assert!(err.to_string().contains("too large"), "{}", err);
+}
+
+#[test]
+fn fonts_are_fetched_relative_to_the_document() {
+    let dir = std::env::temp_dir().join(format!("icarus-fonts-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("face.woff"), woff()).unwrap();
+    std::fs::write(
+        dir.join("page.html"),
+        "<style>
+           @font-face { font-family: Shown; src: url(face.woff2) format('woff2'), url(face.woff) }
+           @font-face { font-family: Missing; src: url(missing.ttf) }
+         </style><p style='font-family: Shown'>x</p>",
+    )
+    .unwrap();
+    let url = url::Url::from_file_path(dir.join("page.html")).unwrap();
+    let Ok(Load::Document(document)) = loader::load(url.as_str()) else {
+        panic!("document");
+    };
+
+    let mut fonts = FontLoader::new();
+    let loaded = fonts.load(&document);
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].family, "Shown");
+    assert_eq!(*loaded[0].data, sfnt());
+    assert!(fonts.load(&document).is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,56 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::{FontMetrics, char_columns};
+use icarus::paint::font::FontSet;
+use icarus::settings::FontSettings;
+use icarus::style::style_of;
+use std::path::Path;
+
+const SANS: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+#[test]
+fn font_family_lists_are_inherited() {
+    let document = parse_html(
+        "<div style='font-family: \"Noto Sans\", Droid  Sans, SERIF'><p>x</p></div>
+         <p id=plain>y</p>",
+    );
+    document.restyle();
+    let p = document.query_selector("div p").unwrap().unwrap();
+    assert_eq!(
+        style_of(&p).font_family,
+        ["Noto Sans", "Droid Sans", "serif"]
+    );
+    assert_eq!(
+        style_of(&p).to_map()["font-family"],
+        "\"Noto Sans\", \"Droid Sans\", serif"
+    );
+    let plain = document.query_selector("#plain").unwrap().unwrap();
+    assert_eq!(style_of(&plain).to_map()["font-family"], "monospace");
+}
+
+#[test]
+fn wide_characters_take_two_columns() {
+    assert_eq!(char_columns('a'), 1);
+    assert_eq!(char_columns('漢'), 2);
+    assert_eq!(char_columns('한'), 2);
+    assert_eq!(char_columns('😀'), 2);
+    assert_eq!(char_columns('\u{301}'), 0);
+    let metrics = FontMetrics::for_size(10.0);
+    assert_eq!(metrics.text_width("a漢字"), 5.0 * 6.0);
+}
+
+#[test]
+fn missing_glyphs_fall_back_per_character() {
+    let Some(fonts) = FontSet::load(&FontSettings::default()) else {
+        return;
+    };
+    let chain = fonts.chain(&[], false, false);
+    assert_eq!(fonts.resolve(&chain, 'a'), chain[0]);
+    let chess = fonts.resolve(&chain, '♔');
+    if !fonts.font(chain[0]).has_glyph('♔') && Path::new(SANS).exists() {
+        assert_ne!(chess, chain[0]);
+        assert!(fonts.font(chess).has_glyph('♔'));
+    }
+
+    let named = fonts.chain(&[String::from("Nonexistent Font")], false, false);
+    assert_eq!(named, chain);
+}
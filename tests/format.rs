@@ -0,0 +1,65 @@
+use icarus::html::format::SerializeOptions;
+use icarus::html::parser::parse_html;
+
+#[test]
+fn minified_output_collapses_whitespace_and_quotes() {
+    let document = parse_html(
+        "<!DOCTYPE html>
+         <html>
+           <head>
+             <title>Hello</title>
+           </head>
+           <body>
+             <!-- note -->
+             <div   class=\"box\" title=\"two words\" hidden=\"\">
+               <p>One   <b>two</b> <i>three</i></p>
+             </div>
+             <pre>  keep
+   this  </pre>
+           </body>
+         </html>",
+    );
+    assert_eq!(
+        document.serialize_with(SerializeOptions::minified()),
+        "<!DOCTYPE html><html><head><title>Hello</title></head><body>\
+         <div class=box title=\"two words\" hidden><p>One <b>two</b> <i>three</i></p></div>\
+         <pre>  keep\n   this  </pre></body></html>"
+    );
+}
+
+#[test]
+fn pretty_output_indents_blocks() {
+    let document = parse_html(
+        "<ul><li>One <em>two</em></li><li><p>Three</p></li></ul><script>if (a < b) {}</script>",
+    );
+    assert_eq!(
+        document.serialize_with(SerializeOptions::pretty()),
+        "<html>
+  <head></head>
+  <body>
+    <ul>
+      <li>One <em>two</em></li>
+      <li>
+        <p>Three</p>
+      </li>
+    </ul>
+    <script>if (a < b) {}</script>
+  </body>
+</html>
+"
+    );
+}
+
+#[test]
+fn minified_output_reparses_to_the_same_text() {
+    let source = "<p>a &amp; b <a href=\"/x?y=1&amp;z=2\">link</a></p>";
+    let document = parse_html(source);
+    let minified = document.serialize_with(SerializeOptions::minified());
+    assert_eq!(
+        parse_html(&minified).root.get_text_content(),
+        document.root.get_text_content()
+    );
+    assert!(
+        minified.contains("href=\"/x?y=1&amp;z=2\"") || minified.contains("href=/x?y=1&amp;z=2")
+    );
+}
@@ -0,0 +1,131 @@
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+use icarus::html::serialize::{inner_html, outer_html};
+use icarus::mutation::{MutationObserver, MutationObserverInit, MutationRecord, notify_observers};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod common;
+
+use common::text_node;
+
+fn element(document_html: &str, id: &str) -> Rc<Node> {
+    parse_html(document_html).get_element_by_id(id).unwrap()
+}
+
+#[test]
+fn append_fragment_moves_every_child() {
+    let target = element("<div id=t><i>first</i></div>", "t");
+    let fragment = Node::new_fragment();
+    let bold = element("<b id=b>bold</b>", "b");
+    Node::append_child(&fragment, bold);
+    Node::append_child(&fragment, text_node(" tail"));
+
+    Node::append_fragment(&target, &fragment);
+
+    assert!(fragment.children.borrow().is_empty());
+    assert_eq!(inner_html(&target), "<i>first</i><b id=\"b\">bold</b> tail");
+    for child in target.children.borrow().iter() {
+        assert!(Rc::ptr_eq(
+            &child.parent.borrow().upgrade().unwrap(),
+            &target
+        ));
+    }
+}
+
+#[test]
+fn appending_a_fragment_inserts_its_children() {
+    let target = element("<ul id=t></ul>", "t");
+    let fragment = Node::new_fragment();
+    for _ in 0..3 {
+        Node::append_child(&fragment, element("<li id=i>item</li>", "i"));
+    }
+
+    Node::append_child(&target, Rc::clone(&fragment));
+
+    assert_eq!(target.children.borrow().len(), 3);
+    assert!(fragment.parent.borrow().upgrade().is_none());
+    assert!(fragment.children.borrow().is_empty());
+}
+
+#[test]
+fn inserting_a_fragment_splices_its_children_before_the_reference() {
+    let target = element("<div id=t><i id=i>last</i></div>", "t");
+    let reference = target.children.borrow()[0].clone();
+    let fragment = Node::new_fragment();
+    Node::append_child(&fragment, element("<b id=b>bold</b>", "b"));
+    Node::append_child(&fragment, text_node(" mid "));
+
+    let records: Rc<RefCell<Vec<MutationRecord>>> = Rc::default();
+    let sink = Rc::clone(&records);
+    let observer = MutationObserver::new(move |batch: Vec<MutationRecord>, _| {
+        sink.borrow_mut().extend(batch);
+    });
+    observer.observe(
+        &target,
+        MutationObserverInit {
+            child_list: true,
+            ..MutationObserverInit::default()
+        },
+    );
+    Node::insert_before(&target, Rc::clone(&fragment), &reference);
+    notify_observers();
+
+    assert!(fragment.children.borrow().is_empty());
+    assert_eq!(
+        inner_html(&target),
+        "<b id=\"b\">bold</b> mid <i id=\"i\">last</i>"
+    );
+    for child in target.children.borrow().iter() {
+        assert!(Rc::ptr_eq(
+            &child.parent.borrow().upgrade().unwrap(),
+            &target
+        ));
+    }
+    let records = records.borrow();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].added_nodes.len(), 2);
+    assert!(records[0].previous_sibling.is_none());
+    assert!(Rc::ptr_eq(
+        records[0].next_sibling.as_ref().unwrap(),
+        &reference
+    ));
+}
+
+#[test]
+fn template_contents_live_in_a_fragment() {
+    let document = parse_html("<template id=t><p class=inner>hidden</p></template>");
+    let template = document.get_element_by_id("t").unwrap();
+    let contents = template.template_content().unwrap();
+
+    assert!(matches!(contents.data, NodeData::DocumentFragment));
+    assert!(template.children.borrow().is_empty());
+    assert_eq!(contents.children.borrow().len(), 1);
+    assert!(document.query_selector(".inner").unwrap().is_none());
+    assert_eq!(
+        outer_html(&template),
+        "<template id=\"t\"><p class=\"inner\">hidden</p></template>"
+    );
+}
+
+#[test]
+fn cloning_a_template_clones_its_contents() {
+    let template = element("<template id=t><b>x</b></template>", "t");
+    let copy = template.clone_subtree();
+    let contents = copy.template_content().unwrap();
+    assert!(!Rc::ptr_eq(
+        &contents,
+        &template.template_content().unwrap()
+    ));
+    assert_eq!(outer_html(&copy), outer_html(&template));
+
+    let shallow = Node::clone_node(&template, false);
+    assert!(
+        shallow
+            .template_content()
+            .unwrap()
+            .children
+            .borrow()
+            .is_empty()
+    );
+}
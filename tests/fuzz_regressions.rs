@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use icarus::css::selector::parse_selector_list;
+use icarus::css::{Origin, Stylesheet};
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::loader::decode;
+
+fn inputs(target: &str) -> Vec<Vec<u8>> {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fuzz/regressions")
+        .join(target);
+    let mut paths: Vec<_> = std::fs::read_dir(&directory)
+        .unwrap_or_else(|err| panic!("reading {}: {}", directory.display(), err))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| std::fs::read(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn parse_html_regressions() {
+    for data in inputs("parse_html") {
+        let document = parse_html(&decode(&data, None));
+        LayoutTree::build(&document, LayoutOptions::default());
+    }
+}
+
+#[test]
+fn parse_css_regressions() {
+    for data in inputs("parse_css") {
+        let source = decode(&data, None);
+        parse_selector_list(&source);
+        Stylesheet::parse(&source, Origin::Author);
+
+        let document = parse_html(&format!(
+            "<style>{}</style><div class=a><p id=b>x</p></div>",
+            source
+        ));
+        document.restyle();
+    }
+}
@@ -0,0 +1,50 @@
+use icarus::paint::font::FontSet;
+use icarus::paint::glyph_cache::{GlyphAtlas, GlyphCache, GlyphKey};
+use icarus::settings::FontSettings;
+
+fn key(fonts: &FontSet, c: char, size: f32) -> GlyphKey {
+    GlyphKey {
+        face: 0,
+        size: size.to_bits(),
+        glyph: fonts.font(0).lookup_glyph_index(c),
+    }
+}
+
+#[test]
+fn repeated_glyphs_hit_the_cache() {
+    let Some(fonts) = FontSet::load(&FontSettings::default()) else {
+        return;
+    };
+    let mut cache = GlyphCache::new();
+    let first = cache.glyph(&fonts.font(0), key(&fonts, 'a', 16.0));
+    let second = cache.glyph(&fonts.font(0), key(&fonts, 'a', 16.0));
+    assert!(first.is_cached());
+    assert_eq!((first.width, first.height), (second.width, second.height));
+    assert_eq!((cache.hits, cache.misses), (1, 1));
+    assert_eq!(cache.atlas.len(), 1);
+}
+
+#[test]
+fn oversize_glyphs_are_drawn_uncached() {
+    let Some(fonts) = FontSet::load(&FontSettings::default()) else {
+        return;
+    };
+    let mut atlas = GlyphAtlas::new();
+    let small = atlas.insert(key(&fonts, 'a', 16.0), &fonts.font(0));
+    let huge = atlas.insert(key(&fonts, 'W', 3000.0), &fonts.font(0));
+    assert!(huge.width > 1024);
+    assert!(!huge.is_cached());
+
+    let mut inked = false;
+    for row in 0..huge.height {
+        for column in 0..huge.width {
+            inked |= atlas.coverage(&huge, column, row) > 0;
+        }
+    }
+    assert!(inked);
+
+    // The oversize glyph must not evict what is already in the atlas.
+    assert_eq!(atlas.len(), 1);
+    assert!(atlas.get(&key(&fonts, 'a', 16.0)).is_some());
+    assert!(small.is_cached());
+}
@@ -0,0 +1,107 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::raster::Canvas;
+use icarus::paint::{DisplayItem, DisplayList};
+use icarus::style::gradient::{ColorStop, Gradient, GradientShape, LinearDirection};
+use icarus::style::{Dimension, style_of};
+
+fn parse(value: &str) -> Gradient {
+    Gradient::parse(value, 16.0, Color::BLACK).expect("gradient")
+}
+
+fn render(style: &str) -> Canvas {
+    let html = format!(
+        "<body style='margin: 0'><div style='width: 100px; height: 100px; {}'></div></body>",
+        style
+    );
+    let tree = LayoutTree::build(&parse_html(&html), LayoutOptions::default());
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&DisplayList::build(&tree), None, 0.0);
+    canvas
+}
+
+fn pixel(canvas: &Canvas, x: usize, y: usize) -> u32 {
+    canvas.pixels[y * canvas.width + x] & 0xffffff
+}
+
+#[test]
+fn gradients_are_parsed() {
+    let gradient = parse("linear-gradient(to right, red, blue 80%)");
+    assert_eq!(
+        gradient.shape,
+        GradientShape::Linear(LinearDirection::Angle(90.0))
+    );
+    assert_eq!(
+        gradient.stops,
+        [
+            ColorStop {
+                color: Color::rgb(255, 0, 0),
+                position: None,
+            },
+            ColorStop {
+                color: Color::rgb(0, 0, 255),
+                position: Some(Dimension::Percent(80.0)),
+            },
+        ]
+    );
+    assert_eq!(
+        parse("linear-gradient(0.25turn, red, blue)").shape,
+        GradientShape::Linear(LinearDirection::Angle(90.0))
+    );
+    assert!(parse("repeating-radial-gradient(circle 10px at top left, red, blue 5px)").repeating);
+    assert_eq!(parse("radial-gradient(red 10px 20px, blue)").stops.len(), 3);
+    assert!(Gradient::parse("linear-gradient(red)", 16.0, Color::BLACK).is_none());
+    assert!(Gradient::parse("conic-gradient(red, blue)", 16.0, Color::BLACK).is_none());
+
+    let document = parse_html(
+        "<p id=a style='background: url(x.png), linear-gradient(red, blue) white'>x</p>
+         <p id=b style='background: white'>y</p>",
+    );
+    document.restyle();
+    let a = document.query_selector("#a").unwrap().unwrap();
+    assert_eq!(style_of(&a).background_image.len(), 1);
+    assert_eq!(style_of(&a).background_color, Color::WHITE);
+    let b = document.query_selector("#b").unwrap().unwrap();
+    assert_eq!(style_of(&b).to_map()["background-image"], "none");
+}
+
+#[test]
+fn linear_gradients_interpolate_between_stops() {
+    let smooth = render("background-image: linear-gradient(to right, red, blue)");
+    assert_eq!(pixel(&smooth, 0, 50), 0xfe0001);
+    assert_eq!(pixel(&smooth, 99, 50), 0x0100fe);
+    // Pixels are sampled at their centers, so x=50 sits at t=0.505 rather than 0.5.
+    assert_eq!(pixel(&smooth, 50, 10), 0x7e0081);
+
+    let hard = render("background: linear-gradient(black 50%, white 50%)");
+    assert_eq!(pixel(&hard, 50, 49), 0x000000);
+    assert_eq!(pixel(&hard, 50, 50), 0xffffff);
+}
+
+#[test]
+fn radial_and_repeating_gradients() {
+    let radial = render("background: radial-gradient(circle closest-side, black, white)");
+    assert!(pixel(&radial, 50, 50) < 0x101010);
+    assert_eq!(pixel(&radial, 2, 2), 0xffffff);
+
+    let stripes =
+        render("background: repeating-linear-gradient(90deg, black 0 10px, white 10px 20px)");
+    assert_eq!(pixel(&stripes, 5, 0), 0x000000);
+    assert_eq!(pixel(&stripes, 15, 0), 0xffffff);
+    assert_eq!(pixel(&stripes, 45, 0), 0x000000);
+    assert_eq!(pixel(&stripes, 95, 0), 0xffffff);
+}
+
+#[test]
+fn gradients_cover_the_border_box() {
+    let html = "<body style='margin: 0'><div style='width: 40px; height: 40px; padding: 5px;
+        border: 5px solid transparent; background-image: linear-gradient(red, red)'></div></body>";
+    let tree = LayoutTree::build(&parse_html(html), LayoutOptions::default());
+    let list = DisplayList::build(&tree);
+    assert!(list.items.iter().any(|item| matches!(
+        item,
+        DisplayItem::Gradient { rect, origin, .. }
+            if *rect == Rect::new(0.0, 0.0, 60.0, 60.0) && *origin == Rect::new(5.0, 5.0, 50.0, 50.0)
+    )));
+}
@@ -0,0 +1,152 @@
+use serde_json::Value;
+use std::time::{Duration, SystemTime};
+
+use icarus::net::fetch;
+use icarus::net::log::{NetworkLog, RequestEntry};
+use icarus::settings::Settings;
+use url::Url;
+
+mod common;
+
+fn serve(responses: &'static [&'static str]) -> Url {
+    let port = common::serve(responses.to_vec());
+    Url::parse(&format!("http://127.0.0.1:{}/start?q=1&lang=en", port)).unwrap()
+}
+
+fn entry(started_at: SystemTime) -> RequestEntry {
+    RequestEntry {
+        url: Url::parse("http://example.com/").unwrap(),
+        method: String::from("GET"),
+        status: Some(200),
+        headers: Vec::new(),
+        size: 0,
+        started_at,
+        wait: Duration::from_millis(5),
+        total: Duration::from_millis(12),
+        from_cache: false,
+        error: None,
+    }
+}
+
+fn object<'a>(value: &'a Value, fields: &[&str]) -> &'a Value {
+    for field in fields {
+        assert!(value.get(field).is_some(), "missing {} in {}", field, value);
+    }
+    value
+}
+
+#[test]
+fn started_date_time_is_iso_8601() {
+    let at = |secs: u64, millis: u64| {
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_millis(secs * 1000 + millis);
+        entry(started_at).to_har()["startedDateTime"].clone()
+    };
+    assert_eq!(at(0, 0), "1970-01-01T00:00:00.000Z");
+    assert_eq!(at(946_684_799, 999), "1999-12-31T23:59:59.999Z");
+    assert_eq!(at(1_709_210_096, 789), "2024-02-29T12:34:56.789Z");
+    assert_eq!(at(4_107_542_400, 7), "2100-03-01T00:00:00.007Z");
+}
+
+#[test]
+fn time_is_the_sum_of_timings() {
+    let har = entry(SystemTime::now()).to_har();
+    let timings = &har["timings"];
+    let sum: f64 = [
+        "blocked", "dns", "connect", "ssl", "send", "wait", "receive",
+    ]
+    .iter()
+    .map(|name| timings[name].as_f64().unwrap())
+    .filter(|time| *time >= 0.0)
+    .sum();
+    assert!((har["time"].as_f64().unwrap() - sum).abs() < 1e-9);
+    assert_eq!(timings["wait"].as_f64(), Some(5.0));
+}
+
+#[test]
+fn saved_har_round_trips_with_the_required_fields() {
+    let url = serve(&[
+        "HTTP/1.1 301 Moved Permanently\r\nLocation: /final\r\nContent-Length: 0\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: 4\r\n\r\ndone",
+    ]);
+    let log = NetworkLog::new();
+    log.start();
+    fetch(&url, &Settings::default())
+        .unwrap()
+        .read_body()
+        .unwrap();
+    log.stop();
+
+    let path = std::env::temp_dir().join(format!("icarus-har-{}.har", std::process::id()));
+    log.save_har(&path).unwrap();
+    let har: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let root = object(&har["log"], &["version", "creator", "entries"]);
+    assert_eq!(root["version"], "1.2");
+    let creator = object(&root["creator"], &["name", "version"]);
+    assert_eq!(creator["name"], "Icarus");
+
+    let entries = root["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in entries {
+        object(
+            entry,
+            &[
+                "startedDateTime",
+                "time",
+                "request",
+                "response",
+                "cache",
+                "timings",
+            ],
+        );
+        object(
+            &entry["request"],
+            &[
+                "method",
+                "url",
+                "httpVersion",
+                "cookies",
+                "headers",
+                "queryString",
+                "headersSize",
+                "bodySize",
+            ],
+        );
+        object(
+            &entry["response"],
+            &[
+                "status",
+                "statusText",
+                "httpVersion",
+                "cookies",
+                "headers",
+                "content",
+                "redirectURL",
+                "headersSize",
+                "bodySize",
+            ],
+        );
+        object(&entry["response"]["content"], &["size", "mimeType"]);
+        let timings = object(&entry["timings"], &["send", "wait", "receive"]);
+        assert!(timings["wait"].as_f64().unwrap() <= entry["time"].as_f64().unwrap());
+    }
+
+    let (redirect, page) = (&entries[0], &entries[1]);
+    assert_eq!(redirect["response"]["status"], 301);
+    assert_eq!(redirect["response"]["statusText"], "Moved Permanently");
+    assert_eq!(redirect["response"]["redirectURL"], "/final");
+    assert_eq!(
+        redirect["request"]["queryString"],
+        serde_json::json!([{ "name": "q", "value": "1" }, { "name": "lang", "value": "en" }])
+    );
+    assert_eq!(page["response"]["status"], 200);
+    assert_eq!(
+        page["response"]["content"]["mimeType"],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(page["response"]["content"]["size"], 4);
+    assert!(
+        page["startedDateTime"].as_str().unwrap() >= redirect["startedDateTime"].as_str().unwrap()
+    );
+}
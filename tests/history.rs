@@ -0,0 +1,64 @@
+use icarus::history::{SessionHistory, Traversal};
+use serde_json::json;
+
+#[test]
+fn push_state_traverses_without_loading() {
+    let mut history = SessionHistory::new("http://example.com/app");
+    let url = history
+        .push_state(json!({"page": 2}), Some("page/2?q=1"))
+        .unwrap();
+    assert_eq!(url, "http://example.com/page/2?q=1");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.state(), Some(&json!({"page": 2})));
+
+    assert_eq!(
+        history.go(-1),
+        Some(Traversal::PopState {
+            url: String::from("http://example.com/app"),
+            state: None,
+        })
+    );
+    assert_eq!(
+        history.go(1),
+        Some(Traversal::PopState {
+            url: String::from("http://example.com/page/2?q=1"),
+            state: Some(json!({"page": 2})),
+        })
+    );
+    assert_eq!(history.go(1), None);
+
+    history.navigate("http://example.org/");
+    assert_eq!(history.go(-1), Some(Traversal::Load));
+}
+
+#[test]
+fn replace_state_keeps_length() {
+    let mut history = SessionHistory::new("http://example.com/");
+    history.replace_state(json!("first"), Some("#top")).unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.current().url, "http://example.com/#top");
+    assert_eq!(history.state(), Some(&json!("first")));
+
+    history.replace_state(json!(null), None).unwrap();
+    assert_eq!(history.current().url, "http://example.com/#top");
+}
+
+#[test]
+fn cross_origin_urls_are_rejected() {
+    let mut history = SessionHistory::new("http://example.com/");
+    assert!(
+        history
+            .push_state(json!(1), Some("http://evil.example/"))
+            .is_err()
+    );
+    assert!(
+        history
+            .push_state(json!(1), Some("https://example.com/"))
+            .is_err()
+    );
+    assert_eq!(history.len(), 1);
+
+    let mut history = SessionHistory::new("file:///tmp/a.html");
+    history.push_state(json!(1), Some("b.html")).unwrap();
+    assert_eq!(history.current().url, "file:///tmp/b.html");
+}
@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use html5ever::ns;
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+
+const SUITE_DIRECTORY: &str = "tests/html5lib-tests/tree-construction";
+const LOCAL_DIRECTORY: &str = "tests/html5lib";
+const KNOWN_FAILURES: &str = "tests/html5lib/known_failures.txt";
+
+struct TestCase {
+    file: String,
+    index: usize,
+    data: String,
+    document: String,
+    fragment: Option<String>,
+    script_off: bool,
+}
+
+impl TestCase {
+    fn name(&self) -> String {
+        format!("{}#{}", self.file, self.index)
+    }
+}
+
+fn parse_dat(file: &str, source: &str) -> Vec<TestCase> {
+    let mut cases = Vec::new();
+    let source = source.strip_prefix("#data\n").unwrap_or(source);
+    for (index, block) in source.split("\n\n#data\n").enumerate() {
+        let mut sections: Vec<(&str, Vec<&str>)> = vec![("#data", Vec::new())];
+        for line in block.lines() {
+            match line {
+                "#errors" | "#new-errors" | "#document-fragment" | "#script-off" | "#script-on"
+                | "#document" => sections.push((line, Vec::new())),
+                _ => sections.last_mut().unwrap().1.push(line),
+            }
+        }
+        let section = |name: &str| {
+            sections
+                .iter()
+                .find(|(header, _)| *header == name)
+                .map(|(_, lines)| lines.join("\n"))
+        };
+        cases.push(TestCase {
+            file: file.to_string(),
+            index,
+            data: section("#data").unwrap_or_default(),
+            document: section("#document")
+                .unwrap_or_default()
+                .trim_end_matches('\n')
+                .to_string(),
+            fragment: section("#document-fragment").map(|name| name.trim().to_string()),
+            script_off: section("#script-off").is_some(),
+        });
+    }
+    cases
+}
+
+fn dump(node: &Rc<Node>, depth: usize, out: &mut Vec<String>) {
+    let indent = format!("| {}", "  ".repeat(depth));
+    match &node.data {
+        NodeData::Document { .. } | NodeData::DocumentFragment | NodeData::ShadowRoot { .. } => {}
+        NodeData::Doctype {
+            name,
+            public_id,
+            system_id,
+        } => {
+            if public_id.is_empty() && system_id.is_empty() {
+                out.push(format!("{}<!DOCTYPE {}>", indent, name));
+            } else {
+                out.push(format!(
+                    "{}<!DOCTYPE {} \"{}\" \"{}\">",
+                    indent, name, public_id, system_id
+                ));
+            }
+        }
+        NodeData::Comment { contents } => {
+            out.push(format!("{}<!-- {} -->", indent, contents.borrow()))
+        }
+        NodeData::Text { contents } => out.push(format!("{}\"{}\"", indent, contents.borrow())),
+        NodeData::Element { name, attrs } => {
+            let prefix = match name.ns {
+                ns!(svg) => "svg ",
+                ns!(mathml) => "math ",
+                _ => "",
+            };
+            out.push(format!("{}<{}{}>", indent, prefix, name.local));
+
+            let mut attrs: Vec<String> = attrs
+                .borrow()
+                .iter()
+                .map(|attr| {
+                    let prefix = match attr.name.ns {
+                        ns!(xlink) => "xlink ",
+                        ns!(xml) => "xml ",
+                        ns!(xmlns) => "xmlns ",
+                        _ => "",
+                    };
+                    format!("{}{}=\"{}\"", prefix, attr.name.local, attr.value)
+                })
+                .collect();
+            attrs.sort();
+            for attr in attrs {
+                out.push(format!("| {}{}", "  ".repeat(depth + 1), attr));
+            }
+
+            if name.ns == ns!(html) && &*name.local == "template" {
+                out.push(format!("| {}content", "  ".repeat(depth + 1)));
+                let contents = node.template_content().expect("template without contents");
+                for child in contents.children.borrow().iter() {
+                    dump(child, depth + 2, out);
+                }
+                return;
+            }
+        }
+    }
+
+    let depth = match node.data {
+        NodeData::Document { .. } | NodeData::DocumentFragment | NodeData::ShadowRoot { .. } => {
+            depth
+        }
+        _ => depth + 1,
+    };
+    for child in node.children.borrow().iter() {
+        dump(child, depth, out);
+    }
+}
+
+fn dat_files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "dat"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn tree_construction() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let suite = std::env::var_os("HTML5LIB_TESTS")
+        .map(|path| PathBuf::from(path).join("tree-construction"))
+        .unwrap_or_else(|| root.join(SUITE_DIRECTORY));
+    assert!(
+        !dat_files(&suite).is_empty(),
+        "no html5lib-tests tree-construction files found at {}",
+        suite.display()
+    );
+    let known_failures = std::fs::read_to_string(root.join(KNOWN_FAILURES)).unwrap_or_default();
+    let known_failures: Vec<&str> = known_failures
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+
+    let (mut passed, mut skipped) = (0, 0);
+    let mut failures = Vec::new();
+    let mut fixed = Vec::new();
+    for path in dat_files(&root.join(LOCAL_DIRECTORY))
+        .into_iter()
+        .chain(dat_files(&suite))
+    {
+        let file = path.file_name().unwrap().to_string_lossy().into_owned();
+        let source = std::fs::read_to_string(&path).unwrap();
+        for case in parse_dat(&file, &source) {
+            if case.fragment.is_some() || case.script_off {
+                skipped += 1;
+                continue;
+            }
+
+            let document = parse_html(&case.data);
+            let mut lines = Vec::new();
+            dump(&document.root, 0, &mut lines);
+            let actual = lines.join("\n");
+
+            let name = case.name();
+            let known = known_failures.contains(&name.as_str());
+            if actual == case.document {
+                passed += 1;
+                if known {
+                    fixed.push(name);
+                }
+            } else if !known {
+                failures.push(format!(
+                    "{}\n#data\n{}\n#expected\n{}\n#actual\n{}\n",
+                    name, case.data, case.document, actual
+                ));
+            }
+        }
+    }
+
+    eprintln!(
+        "html5lib: {} passed, {} failed, {} known failures, {} skipped",
+        passed,
+        failures.len(),
+        known_failures.len() - fixed.len(),
+        skipped
+    );
+    for name in &fixed {
+        eprintln!("{} now passes, remove it from {}", name, KNOWN_FAILURES);
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
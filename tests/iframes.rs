@@ -0,0 +1,77 @@
+use icarus::css::values::Color;
+use icarus::frame::Frame;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::raster::Canvas;
+use icarus::paint::{DisplayItem, DisplayList};
+
+fn framed(child: &str) -> Frame {
+    let mut frame = Frame::new(parse_html(&format!(
+        "<body style='margin: 0'><p style='margin: 0; height: 20px'>top</p>\
+         <iframe style='border: 0; width: 200px; height: 100px' srcdoc=\"{}\"></iframe></body>",
+        child
+    )));
+    frame.load_subframes();
+    frame
+}
+
+#[test]
+fn child_documents_are_painted_inside_a_clip() {
+    let frame = framed("<body style='margin: 0'><p id=inner style='margin: 0'>inside</p></body>");
+    let tree = LayoutTree::build(&frame.document, LayoutOptions::default());
+    let list = DisplayList::build(&tree);
+
+    let push = list
+        .items
+        .iter()
+        .position(|item| matches!(item, DisplayItem::PushClip { .. }))
+        .expect("clip");
+    assert_eq!(
+        list.items[push],
+        DisplayItem::PushClip {
+            rect: Rect::new(0.0, 20.0, 200.0, 100.0)
+        }
+    );
+    let text = list
+        .items
+        .iter()
+        .position(|item| matches!(item, DisplayItem::Text { text, .. } if text == "inside"))
+        .expect("child text");
+    let pop = list
+        .items
+        .iter()
+        .position(|item| matches!(item, DisplayItem::PopClip))
+        .expect("pop");
+    assert!(push < text && text < pop);
+    assert!(list.items[text].bounds().y >= 20.0);
+}
+
+#[test]
+fn hits_inside_the_iframe_reach_the_child_document() {
+    let frame = framed("<body style='margin: 0'><p id=inner style='margin: 0'>inside</p></body>");
+    let tree = LayoutTree::build(&frame.document, LayoutOptions::default());
+
+    let hit = tree.hit_test(5.0, 25.0).expect("hit");
+    assert_eq!(hit.node.get_attribute("id").as_deref(), Some("inner"));
+    assert!(hit.rect.y >= 20.0);
+    let owner = frame.frame_for_node(&hit.node).expect("owning frame");
+    assert!(owner.element.is_some());
+
+    let hit = tree.hit_test(5.0, 5.0).expect("hit");
+    assert_eq!(hit.node.element_name(), Some("p"));
+    assert!(frame.frame_for_node(&hit.node).unwrap().element.is_none());
+}
+
+#[test]
+fn iframe_content_is_clipped_when_rasterized() {
+    let frame = framed("<body style='margin: 0; background-color: #ff0000; height: 500px'></body>");
+    let tree = LayoutTree::build(&frame.document, LayoutOptions::default());
+    let list = DisplayList::build(&tree);
+    let mut canvas = Canvas::new(400, 300, Color::WHITE);
+    canvas.paint(&list, None, 0.0);
+
+    let red = Color::rgb(255, 0, 0).to_u32();
+    assert_eq!(canvas.pixel(100, 50), Some(red));
+    assert_eq!(canvas.pixel(100, 150), Some(Color::WHITE.to_u32()));
+    assert_eq!(canvas.pixel(250, 50), Some(Color::WHITE.to_u32()));
+}
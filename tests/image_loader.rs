@@ -0,0 +1,83 @@
+use std::rc::Rc;
+
+use icarus::css::values::Color;
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::image::{Image, ImageLoader};
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use url::Url;
+
+const VIEWPORT: Rect = Rect {
+    x: 0.0,
+    y: 0.0,
+    width: 800.0,
+    height: 600.0,
+};
+
+fn png_url(name: &str) -> Url {
+    let path = std::env::temp_dir().join(format!("icarus-{}-{}.png", name, std::process::id()));
+    let image = Image {
+        width: 2,
+        height: 2,
+        pixels: vec![Color::rgb(0, 128, 255); 4],
+    };
+    std::fs::write(&path, image.to_png().unwrap()).unwrap();
+    Url::from_file_path(&path).unwrap()
+}
+
+fn load(loader: &mut ImageLoader, document: &Document, viewport: Rect) -> usize {
+    document.restyle();
+    let tree = LayoutTree::build(document, LayoutOptions::default());
+    loader.load_visible(document, &tree, viewport)
+}
+
+#[test]
+fn images_are_requested_once_per_node() {
+    let url = png_url("requested-once");
+    let document = parse_html(&format!(
+        "<img id=eager src='{url}'><div style='height: 5000px'></div><img id=lazy loading=lazy width=10 height=10 src='{url}'>"
+    ));
+    let mut loader = ImageLoader::new();
+
+    assert_eq!(load(&mut loader, &document, VIEWPORT), 1);
+    let eager = document.get_element_by_id("eager").unwrap();
+    assert_eq!(eager.image.borrow().as_ref().unwrap().width, 2);
+    assert!(
+        document
+            .get_element_by_id("lazy")
+            .unwrap()
+            .image
+            .borrow()
+            .is_none()
+    );
+    assert_eq!(load(&mut loader, &document, VIEWPORT), 0);
+
+    let scrolled = Rect {
+        y: 4800.0,
+        ..VIEWPORT
+    };
+    assert_eq!(load(&mut loader, &document, scrolled), 1);
+    assert_eq!(load(&mut loader, &document, scrolled), 0);
+}
+
+#[test]
+fn replaced_image_nodes_are_loaded_again() {
+    let url = png_url("replaced");
+    let document = parse_html(&format!("<p><img src='{url}'></p>"));
+    let mut loader = ImageLoader::new();
+    assert_eq!(load(&mut loader, &document, VIEWPORT), 1);
+
+    for _ in 0..3 {
+        let old = document.query_selector("img").unwrap().unwrap();
+        let parent = old.parent.borrow().upgrade().unwrap();
+        Node::detach(&old);
+        drop(old);
+        let fresh = parse_html(&format!("<img src='{url}'>"))
+            .query_selector("img")
+            .unwrap()
+            .unwrap();
+        Node::append_child(&parent, Rc::clone(&fresh));
+        assert_eq!(load(&mut loader, &document, VIEWPORT), 1);
+        assert!(fresh.image.borrow().is_some());
+    }
+}
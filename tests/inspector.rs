@@ -0,0 +1,33 @@
+use icarus::html::parser::parse_html;
+use icarus::shell::inspector::{describe, dom_path, selector_for};
+
+#[test]
+fn selectors_include_id_and_classes() {
+    let document = parse_html("<div id=main class='wide  dark'><p>text</p></div>");
+    let main = document.get_element_by_id("main").unwrap();
+    assert_eq!(selector_for(&main), "div#main.wide.dark");
+
+    let p = main.children.borrow()[0].clone();
+    assert_eq!(selector_for(&p), "p");
+    let text = p.children.borrow()[0].clone();
+    assert_eq!(selector_for(&text), "#text");
+}
+
+#[test]
+fn dom_path_lists_element_ancestors() {
+    let document = parse_html("<section class=intro><p id=lead>hello</p></section>");
+    let lead = document.get_element_by_id("lead").unwrap();
+    assert_eq!(dom_path(&lead), "html > body > section.intro > p#lead");
+}
+
+#[test]
+fn describe_prints_the_computed_style() {
+    let document = parse_html("<p id=lead style='color: red'>hello</p>");
+    document.restyle();
+    let lead = document.get_element_by_id("lead").unwrap();
+    let description = describe(&lead);
+    assert!(description.starts_with("html > body > p#lead {\n"));
+    assert!(description.contains("  display: block;\n"));
+    assert!(description.contains("  color: #ff0000;\n"));
+    assert!(description.ends_with('}'));
+}
@@ -0,0 +1,97 @@
+use icarus::internal;
+use icarus::loader::{self, Load};
+use icarus::net::error::LoadError;
+use icarus::settings::Settings;
+use url::Url;
+
+fn load(url: &str, settings: &Settings) -> icarus::dom::Document {
+    match loader::load_with(url, settings).unwrap() {
+        Load::Document(document) => document,
+        Load::Download(_) => panic!("expected a document for {}", url),
+    }
+}
+
+fn title(document: &icarus::dom::Document) -> String {
+    document.get_elements_by_tag_name("title")[0].get_text_content()
+}
+
+#[test]
+fn built_in_pages_load_as_documents() {
+    let settings = Settings::default();
+    let version = load("icarus://version", &settings);
+    assert_eq!(
+        title(&version),
+        format!("Icarus {}", env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(version.response.as_ref().unwrap().status, 200);
+    assert_eq!(
+        version.url().map(|url| url.to_string()).as_deref(),
+        Some("icarus://version")
+    );
+
+    let settings = Settings {
+        javascript: false,
+        user_agent: String::from("Agent <b>&</b>"),
+        ..Settings::default()
+    };
+    let config = load("icarus://config", &settings);
+    assert_eq!(title(&config), "Configuration");
+    let text = config.root.get_text_content();
+    assert!(text.contains("JavaScriptoff"), "{}", text);
+    assert!(text.contains("Agent <b>&</b>"));
+    assert!(config.get_elements_by_tag_name("b").is_empty());
+
+    let blank = load("about:blank", &settings);
+    assert!(
+        blank.get_elements_by_tag_name("body")[0]
+            .children
+            .borrow()
+            .is_empty()
+    );
+    assert!(loader::load_with("icarus://nope", &settings).is_err());
+}
+
+#[test]
+fn history_lists_recorded_visits_but_not_internal_pages() {
+    let page = Url::parse("http://example.com/visited?a=1&b=2").unwrap();
+    internal::record_visit(&page, Some(String::from("Visited <page>")));
+    internal::record_visit(&Url::parse("icarus://version").unwrap(), None);
+    assert!(
+        internal::visits()
+            .iter()
+            .all(|visit| !internal::is_internal(&visit.url))
+    );
+
+    let history = load("icarus://history", &Settings::default());
+    let link = history
+        .get_elements_by_tag_name("a")
+        .into_iter()
+        .find(|link| link.get_attribute("href").as_deref() == Some(page.as_str()))
+        .unwrap();
+    assert_eq!(link.get_text_content(), "Visited <page>");
+}
+
+#[test]
+fn error_pages_round_trip_their_parameters() {
+    let url = internal::error_url(
+        "http://down.example/",
+        LoadError::HttpStatus(503),
+        "first line\n\nsecond & last",
+    );
+    assert!(internal::is_internal(&url));
+    let document = load(url.as_str(), &Settings::default());
+    assert_eq!(title(&document), LoadError::HttpStatus(503).title());
+    let messages: Vec<String> = document
+        .get_elements_by_tag_name("p")
+        .iter()
+        .filter(|p| p.has_class("message"))
+        .map(|p| p.get_text_content())
+        .collect();
+    assert_eq!(messages, ["first line", "second & last"]);
+    let retry = document
+        .get_elements_by_tag_name("a")
+        .last()
+        .unwrap()
+        .get_attribute("href");
+    assert_eq!(retry.as_deref(), Some("http://down.example/"));
+}
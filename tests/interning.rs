@@ -0,0 +1,56 @@
+use html5ever::{LocalName, local_name, ns};
+use icarus::css::selector::parse_selector;
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+use string_cache::DefaultAtom as Atom;
+
+#[test]
+fn parsed_names_are_atoms() {
+    let document = parse_html("<DIV Class='a b' data-X=1></DIV><svg viewBox='0 0 1 1'></svg>");
+    let div = document.get_elements_by_tag_name("div")[0].clone();
+    let NodeData::Element { name, attrs } = &div.data else {
+        panic!("expected an element");
+    };
+    assert_eq!(name.local, local_name!("div"));
+    assert_eq!(name.ns, ns!(html));
+    let names: Vec<LocalName> = attrs
+        .borrow()
+        .iter()
+        .map(|a| a.name.local.clone())
+        .collect();
+    assert_eq!(names, [local_name!("class"), LocalName::from("data-x")]);
+
+    let svg = document.get_elements_by_tag_name("svg")[0].clone();
+    assert!(
+        svg.with_attribute(&LocalName::from("viewBox"), |value| value
+            == Some("0 0 1 1"))
+    );
+    assert!(svg.with_attribute(&LocalName::from("viewbox"), |value| value.is_none()));
+}
+
+#[test]
+fn selectors_store_atoms() {
+    let selector = parse_selector("div#main.a.b").unwrap();
+    assert_eq!(selector.subject.tag, Some(local_name!("div")));
+    assert_eq!(selector.subject.id, Some(Atom::from("main")));
+    assert_eq!(selector.subject.classes, [Atom::from("a"), Atom::from("b")]);
+}
+
+#[test]
+fn class_and_id_matching_follows_mutations() {
+    let document = parse_html("<p id=x class='one  two'>t</p>");
+    let p = document.get_element_by_id("x").unwrap();
+    let matches = |selector: &str| parse_selector(selector).unwrap().matches(&p);
+
+    assert!(p.has_class("two"));
+    assert!(!p.has_class("on"));
+    assert!(matches("p.one.two#x"));
+    assert!(!matches(".one.three"));
+
+    Node::set_attribute(&p, "CLASS", "three");
+    assert!(matches(".three") && !matches(".one"));
+    Node::set_attribute(&p, "id", "y");
+    assert!(matches("#y") && !matches("#x"));
+    Node::remove_attribute(&p, "class");
+    assert!(!p.has_class("three") && !matches(".three"));
+}
@@ -0,0 +1,28 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+
+#[test]
+fn dump_lists_boxes_lines_and_box_model_edges() {
+    let document = parse_html(
+        "<div style=\"padding: 4px 2px; border-top: 1px solid red\"><p>Hello <b>there</b></p></div>",
+    );
+    let tree = LayoutTree::build(
+        &document,
+        LayoutOptions {
+            viewport_width: 200.0,
+            ..LayoutOptions::default()
+        },
+    );
+    assert_eq!(
+        tree.dump(),
+        "#document block (0, 0) 200x76.2
+  html block (0, 0) 200x76.2
+    body block (8, 8) 184x60.2 margin=8
+      div block (10, 13) 180x51.2 border=1 0 0 0 padding=4 2 4 2
+        p block (10, 29) 180x19.2 margin=16 0 16 0
+          line (10, 29) 180x19.2
+            text \"Hello \" (10, 29) 57.6x19.2
+            text \"there\" (67.6, 29) 48x19.2
+"
+    );
+}
@@ -0,0 +1,82 @@
+use icarus::layout::hyphenate::Hyphenator;
+
+mod common;
+
+use common::{layout, line_boxes, line_text};
+
+fn lines(html: &str) -> Vec<(String, f32)> {
+    line_boxes(&layout(html).root)
+        .into_iter()
+        .map(|line| {
+            let right = line
+                .fragments
+                .iter()
+                .map(|fragment| fragment.rect.right())
+                .fold(0.0, f32::max);
+            (line_text(line), right - line.rect.x)
+        })
+        .collect()
+}
+
+fn texts(html: &str) -> Vec<String> {
+    lines(html).into_iter().map(|(text, _)| text).collect()
+}
+
+#[test]
+fn long_words_overflow_unless_allowed_to_break() {
+    let url = "https://example.com/a/long/path";
+    let normal = lines(&format!("<div style='width: 100px'>{}</div>", url));
+    assert_eq!(normal.len(), 1);
+    assert!(normal[0].1 > 100.0);
+
+    for style in [
+        "overflow-wrap: break-word",
+        "word-wrap: anywhere",
+        "word-break: break-word",
+    ] {
+        let broken = lines(&format!(
+            "<div style='width: 100px; {}'>{}</div>",
+            style, url
+        ));
+        assert!(broken.len() > 1);
+        assert!(broken.iter().all(|(_, width)| *width <= 100.0));
+        let joined: String = broken.into_iter().map(|(text, _)| text).collect();
+        assert_eq!(joined, url);
+    }
+}
+
+#[test]
+fn break_all_fills_each_line() {
+    assert_eq!(
+        texts("<div style='width: 100px; word-break: break-all'>ab cdefghijklmnop</div>"),
+        ["ab cdefghi", "jklmnop"]
+    );
+}
+
+#[test]
+fn soft_hyphens_mark_break_points() {
+    let html = "<div style='width: 80px'>Hy\u{ad}phen\u{ad}ation ex\u{ad}tra\u{ad}ordinary</div>";
+    assert_eq!(texts(html), ["Hyphen-", "ation", "extra-", "ordinary"]);
+    let none = html.replace("80px'", "80px; hyphens: none'");
+    assert_eq!(texts(&none), ["Hyphenation", "extraordinary"]);
+}
+
+#[test]
+fn wide_characters_break_between_each_other() {
+    let html = "<div style='width: 60px'>漢字かな交じり文</div>";
+    assert_eq!(texts(html), ["漢字か", "な交じ", "り文"]);
+    let keep = html.replace("60px'", "60px; word-break: keep-all'");
+    assert_eq!(texts(&keep).len(), 1);
+}
+
+#[test]
+fn patterns_hyphenate_words() {
+    let hyphenator = Hyphenator::parse(
+        "UTF-8\nLEFTHYPHENMIN 2\nRIGHTHYPHENMIN 3\n\
+         hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n\nta-ble % exception",
+    );
+    assert_eq!(hyphenator.hyphenate("hyphenation"), [2, 6]);
+    assert_eq!(hyphenator.hyphenate("Hyphenation"), [2, 6]);
+    assert_eq!(hyphenator.hyphenate("table"), [2]);
+    assert!(hyphenator.hyphenate("hyp").is_empty());
+}
@@ -0,0 +1,131 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::inline::Fragment;
+use icarus::layout::{FontMetrics, FontProportions};
+use icarus::paint::DisplayList;
+use icarus::paint::font::FontSet;
+use icarus::paint::raster::Canvas;
+use icarus::settings::FontSettings;
+use icarus::style::style_of;
+
+mod common;
+
+use common::{layout, line_boxes};
+
+fn near(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.01
+}
+
+#[test]
+fn line_height_values_are_computed() {
+    let document = parse_html(
+        "<div style='line-height: 1.5; font-size: 10px'><p id=a style='font-size: 20px'>x</p></div>
+         <p id=b style='font-size: 20px; line-height: 150%'>y</p>
+         <p id=c style='line-height: 2em; font-size: 12px'>z</p>
+         <p id=d style='line-height: -1'>w</p>",
+    );
+    document.restyle();
+    let map = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()
+    };
+    assert_eq!(map("#a")["line-height"], "1.5");
+    assert_eq!(map("#b")["line-height"], "30px");
+    assert_eq!(map("#c")["line-height"], "24px");
+    assert_eq!(map("#d")["line-height"], "normal");
+}
+
+#[test]
+fn huge_line_heights_are_clamped() {
+    let document = parse_html(
+        "<p id=a style='line-height: 1e30'>x</p><p id=b style='line-height: 1000000px'>y</p>",
+    );
+    document.restyle();
+    let map = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()
+    };
+    assert_eq!(map("#a")["line-height"], "10000");
+    assert_eq!(map("#b")["line-height"], "10000px");
+
+    let tree = layout("<p style='line-height: 1e30'>x</p><p>y</p>");
+    assert!(tree.height() <= 10_100.0, "{}", tree.height());
+    let Some(fonts) = FontSet::load(&FontSettings::default()) else {
+        return;
+    };
+    let mut canvas = Canvas::new(50, 50, Color::WHITE);
+    canvas.paint(&DisplayList::build(&tree), Some(&fonts), 0.0);
+    canvas.draw_text(
+        &fonts,
+        0.0,
+        1e30,
+        "x",
+        16.0,
+        Color::BLACK,
+        false,
+        false,
+        None,
+    );
+    canvas.draw_text(
+        &fonts,
+        -1e30,
+        -1e30,
+        "x",
+        16.0,
+        Color::BLACK,
+        false,
+        false,
+        None,
+    );
+}
+
+#[test]
+fn line_boxes_use_the_line_height() {
+    let tree = layout("<p style='line-height: 2'>a</p><p style='line-height: 30px'>b</p><p>c</p>");
+    let heights: Vec<f32> = line_boxes(&tree.root)
+        .iter()
+        .map(|line| line.rect.height)
+        .collect();
+    assert_eq!(heights.len(), 3);
+    assert!(near(heights[0], 32.0));
+    assert!(near(heights[1], 30.0));
+    assert!(near(heights[2], 19.2));
+}
+
+#[test]
+fn mixed_sizes_share_a_baseline() {
+    let tree = layout("<p>a<span style='font-size: 32px'>B</span><small>c</small></p>");
+    let lines = line_boxes(&tree.root);
+    let fragments: &[Fragment] = &lines[0].fragments;
+    assert_eq!(fragments.len(), 3);
+    let baselines: Vec<f32> = fragments
+        .iter()
+        .map(|fragment| {
+            let metrics = FontMetrics::for_size(fragment.style.font_size);
+            fragment.rect.y + metrics.baseline()
+        })
+        .collect();
+    assert!(near(baselines[0], baselines[1]));
+    assert!(near(baselines[0], baselines[2]));
+}
+
+#[test]
+fn the_block_font_sets_a_minimum_line() {
+    let tree = layout("<p><span style='font-size: 8px'>x</span></p>");
+    assert!(near(line_boxes(&tree.root)[0].rect.height, 19.2));
+}
+
+#[test]
+fn font_proportions_drive_normal_line_height() {
+    let proportions = FontProportions {
+        ascent: 0.9,
+        descent: 0.3,
+        line_gap: 0.0,
+    };
+    let metrics = FontMetrics::new(10.0, proportions, None);
+    assert!(near(metrics.line_height, 12.0));
+    assert!(near(metrics.baseline(), 9.0));
+    let spaced = FontMetrics::new(10.0, proportions, Some(20.0));
+    assert!(near(spaced.baseline(), 13.0));
+    assert!(near(spaced.below(), 7.0));
+}
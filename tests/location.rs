@@ -0,0 +1,68 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::location::{Location, Navigation, fragment_target};
+
+#[test]
+fn exposes_url_components() {
+    let location = Location::parse("http://user@example.com:8080/a/b?q=1#top").unwrap();
+    assert_eq!(location.protocol(), "http:");
+    assert_eq!(location.host(), "example.com:8080");
+    assert_eq!(location.hostname(), "example.com");
+    assert_eq!(location.port(), "8080");
+    assert_eq!(location.pathname(), "/a/b");
+    assert_eq!(location.search(), "?q=1");
+    assert_eq!(location.hash(), "#top");
+    assert_eq!(location.origin(), "http://example.com:8080");
+}
+
+#[test]
+fn hash_only_changes_stay_in_the_document() {
+    let location = Location::parse("http://example.com/page?x=1").unwrap();
+    assert_eq!(
+        location.assign("#intro").unwrap(),
+        Navigation::Fragment {
+            url: String::from("http://example.com/page?x=1#intro"),
+            fragment: String::from("intro"),
+        }
+    );
+    assert_eq!(
+        location.set_hash("more"),
+        Navigation::Fragment {
+            url: String::from("http://example.com/page?x=1#more"),
+            fragment: String::from("more"),
+        }
+    );
+    assert_eq!(
+        location.assign("other#intro").unwrap(),
+        Navigation::Load {
+            url: String::from("http://example.com/other#intro"),
+            replace: false,
+        }
+    );
+    assert_eq!(
+        location.replace("?x=2").unwrap(),
+        Navigation::Load {
+            url: String::from("http://example.com/page?x=2"),
+            replace: true,
+        }
+    );
+    assert!(location.assign("javascript:void(0)").is_err());
+}
+
+#[test]
+fn fragments_resolve_to_ids_and_named_anchors() {
+    let document = parse_html(
+        "<p style='height: 500px'>top</p><h2 id='caf%C3%A9'>id</h2><p><a name='old'>named</a></p>",
+    );
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+
+    let named = fragment_target(&document, "old").unwrap();
+    assert_eq!(named.element_name(), Some("a"));
+    let heading = fragment_target(&document, "caf%C3%A9").unwrap();
+    assert_eq!(heading.element_name(), Some("h2"));
+    assert!(fragment_target(&document, "missing").is_none());
+
+    let heading_y = tree.rect_of(&heading).unwrap().y;
+    assert!(heading_y >= 500.0);
+    assert!(tree.rect_of(&named).unwrap().y > heading_y);
+}
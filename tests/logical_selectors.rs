@@ -0,0 +1,68 @@
+use icarus::css::selector::{Specificity, parse_selector};
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+mod common;
+
+use common::ids;
+
+const PAGE: &str =
+    "<nav><a id=home class=current href=/>Home</a><a id=docs href=/docs>Docs</a></nav>
+    <main><h1 id=title>Title</h1><h2 id=sub class=muted>Sub</h2><p id=text>Text</p></main>";
+
+fn specificity(selector: &str) -> Specificity {
+    parse_selector(selector).unwrap().specificity()
+}
+
+#[test]
+fn logical_pseudo_classes_match() {
+    assert_eq!(ids(PAGE, "a:not(.current)"), ["docs"]);
+    assert_eq!(ids(PAGE, "main :not(h1, .muted)"), ["text"]);
+    assert_eq!(ids(PAGE, ":is(h1, h2):not(:first-child)"), ["sub"]);
+    assert_eq!(
+        ids(PAGE, ":where(nav, main) > :is(#home, p)"),
+        ["home", "text"]
+    );
+    assert_eq!(ids(PAGE, ":is(nav a):last-child"), ["docs"]);
+    assert_eq!(ids(PAGE, "main > :not(:is(h1, h2))"), ["text"]);
+}
+
+#[test]
+fn is_and_where_are_forgiving() {
+    assert_eq!(ids(PAGE, ":is(h1, !bogus)"), ["title"]);
+    assert!(ids(PAGE, ":where()").is_empty());
+    assert!(parse_selector(":not(!bogus)").is_none());
+    assert!(parse_selector(":not()").is_none());
+    assert!(parse_selector(":is(p::before)").is_some());
+    assert!(ids(PAGE, ":is(p::before)").is_empty());
+    assert!(parse_selector(":not(p").is_none());
+}
+
+#[test]
+fn specificity_follows_the_arguments() {
+    assert_eq!(specificity(":where(#a, .b) p"), Specificity(0, 0, 1));
+    assert_eq!(specificity(":is(#a, .b) p"), Specificity(1, 0, 1));
+    assert_eq!(specificity("a:not(.x, p span)"), Specificity(0, 1, 1));
+    assert_eq!(specificity(":is()"), Specificity(0, 0, 0));
+    assert_eq!(specificity("li:first-child"), Specificity(0, 1, 1));
+}
+
+#[test]
+fn logical_specificity_decides_the_cascade() {
+    let document = parse_html(
+        "<style>p { color: blue } :where(main) p { color: red }
+                :is(main, #none) p { font-size: 20px } main p { font-size: 10px }
+                p.alert { color: green } </style>
+         <main><p id=text>Text</p></main>",
+    );
+    document.restyle();
+    let node = document.query_selector("#text").unwrap().unwrap();
+    let map = style_of(&node).to_map();
+    assert_eq!(map["color"], "#ff0000");
+    assert_eq!(map["font-size"], "20px");
+
+    Node::set_attribute(&node, "class", "alert");
+    document.restyle();
+    assert_eq!(style_of(&node).to_map()["color"], "#008000");
+}
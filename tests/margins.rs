@@ -0,0 +1,64 @@
+mod common;
+
+use common::{find, layout};
+
+#[test]
+fn auto_margins_center_and_push_blocks() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=a style='max-width: 600px; margin: 0 auto'>x</div>
+         <div id=b style='width: 100px; margin-left: auto'>y</div>
+         <div id=c style='margin: 0 auto'>z</div></body>",
+    );
+    let a = find(&tree.root, "a").unwrap();
+    assert_eq!((a.rect.x, a.rect.width), (100.0, 600.0));
+    assert_eq!((a.margin.left, a.margin.right), (100.0, 100.0));
+    assert_eq!(find(&tree.root, "b").unwrap().rect.x, 700.0);
+    assert_eq!(find(&tree.root, "c").unwrap().rect.x, 0.0);
+}
+
+#[test]
+fn adjacent_sibling_margins_collapse() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=a style='height: 10px; margin-bottom: 20px'></div>
+         <div id=b style='height: 10px; margin-top: 30px; margin-bottom: -5px'></div>
+         <div id=c style='height: 10px; margin-top: 15px'></div>
+         <div id=d style='margin: 40px 0'></div>
+         <div id=e style='height: 10px; margin-top: 10px'></div></body>",
+    );
+    let top = |id| find(&tree.root, id).unwrap().rect.y;
+    assert_eq!(top("b"), 40.0);
+    assert_eq!(top("c"), 60.0);
+    assert_eq!(top("e"), 110.0);
+}
+
+#[test]
+fn first_and_last_child_margins_collapse_with_the_parent() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <div id=outer style='margin-top: 10px'><p id=inner style='margin: 25px 0'>x</p></div>
+         <div id=next style='margin-top: 5px'>y</div>
+         <div id=padded style='padding-top: 1px'><p id=kept style='margin-top: 25px'>z</p></div>
+         </body>",
+    );
+    let outer = find(&tree.root, "outer").unwrap();
+    assert_eq!(outer.rect.y, 25.0);
+    assert_eq!(find(&tree.root, "inner").unwrap().rect.y, 25.0);
+    assert_eq!(outer.rect.height, 19.2);
+    assert_eq!(find(&tree.root, "next").unwrap().rect.y, 25.0 + 19.2 + 25.0);
+    let padded = find(&tree.root, "padded").unwrap();
+    assert_eq!(
+        find(&tree.root, "kept").unwrap().rect.y,
+        padded.rect.y + 25.0
+    );
+}
+
+#[test]
+fn root_element_margins_do_not_collapse() {
+    let tree =
+        layout("<html style='margin-top: 5px'><body style='margin-top: 8px'>x</body></html>");
+    let html = &tree.root.children[0];
+    assert_eq!(html.rect.y, 5.0);
+    assert_eq!(html.children[0].rect.y, 13.0);
+}
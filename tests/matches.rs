@@ -0,0 +1,42 @@
+use std::rc::Rc;
+
+use icarus::html::parser::parse_html;
+
+const PAGE: &str = "<section class=card id=outer>
+    <div class=card id=inner><p><a id=link href=/x>go</a> text</p></div>
+    </section>";
+
+#[test]
+fn matches_tests_the_element_against_a_selector_list() {
+    let document = parse_html(PAGE);
+    let link = document.get_element_by_id("link").unwrap();
+    assert!(link.matches("a").unwrap());
+    assert!(link.matches("p > a[href]").unwrap());
+    assert!(link.matches("span, .card a").unwrap());
+    assert!(!link.matches("section > a").unwrap());
+    assert!(link.matches("a[").is_err());
+
+    let text = Rc::clone(&link.children.borrow()[0]);
+    assert!(!text.matches("*").unwrap());
+}
+
+#[test]
+fn closest_starts_at_the_element_and_walks_up() {
+    let document = parse_html(PAGE);
+    let link = document.get_element_by_id("link").unwrap();
+    let id = |selector: &str| {
+        link.closest(selector)
+            .unwrap()
+            .and_then(|node| node.get_attribute("id"))
+    };
+    assert_eq!(id("a"), Some(String::from("link")));
+    assert_eq!(id(".card"), Some(String::from("inner")));
+    assert_eq!(id("section.card"), Some(String::from("outer")));
+    assert_eq!(id("body > .card"), Some(String::from("outer")));
+    assert_eq!(id("ul"), None);
+    assert!(link.closest("::").is_err());
+
+    let text = Rc::clone(&link.children.borrow()[0]);
+    assert!(text.closest("p").unwrap().is_none());
+    assert!(document.root.closest("*").unwrap().is_none());
+}
@@ -0,0 +1,103 @@
+use icarus::layout::inline::{Fragment, FragmentKind};
+use icarus::layout::math::MathBox;
+use icarus::paint::{DisplayItem, DisplayList};
+
+mod common;
+
+use common::{fragments, layout};
+
+fn math(fragment: &Fragment) -> Option<&MathBox> {
+    match &fragment.kind {
+        FragmentKind::Math(math) => Some(math),
+        _ => None,
+    }
+}
+
+fn run_baseline(fragment: &Fragment, text: &str) -> f32 {
+    let run = math(fragment)
+        .unwrap()
+        .runs
+        .iter()
+        .find(|run| run.text == text)
+        .unwrap();
+    fragment.rect.y + run.rect.y + run.rect.height * 0.75
+}
+
+#[test]
+fn math_is_laid_out_as_one_inline_fragment() {
+    let tree = layout("<p>a <math><mi>x</mi><mo>+</mo><mn>1</mn></math> b</p>");
+    let fragments = fragments(&tree.root);
+    let math_fragment = fragments.iter().find(|f| math(f).is_some()).unwrap();
+    let math = math(math_fragment).unwrap();
+
+    assert_eq!(math.text(), "x+1");
+    assert!(math.runs[0].italic);
+    assert!(!math.runs[2].italic);
+    assert!(math.runs[1].rect.x > math.runs[0].rect.right());
+    assert!(math.runs[2].rect.x > math.runs[1].rect.right());
+
+    let text = fragments
+        .iter()
+        .find(|f| matches!(&f.kind, FragmentKind::Text(t) if t.starts_with('a')))
+        .unwrap();
+    let text_baseline = text.rect.y + text.rect.height * 0.75;
+    assert!((run_baseline(math_fragment, "x") - text_baseline).abs() < 0.01);
+}
+
+#[test]
+fn fractions_stack_around_a_rule() {
+    let tree = layout("<p><math><mfrac><mi>a</mi><mi>b</mi></mfrac></math></p>");
+    let fragments = fragments(&tree.root);
+    let math = math(fragments[0]).unwrap();
+    let numerator = math.runs.iter().find(|run| run.text == "a").unwrap();
+    let denominator = math.runs.iter().find(|run| run.text == "b").unwrap();
+    let rule = math.rules[0];
+
+    assert!(numerator.font_size < 16.0);
+    assert!(numerator.rect.bottom() <= rule.y + numerator.rect.height * 0.25 + 0.01);
+    assert!(denominator.rect.y > rule.y);
+    assert!((numerator.rect.x - denominator.rect.x).abs() < 0.01);
+    assert!(math.height() > 19.2);
+}
+
+#[test]
+fn scripts_are_raised_lowered_and_smaller() {
+    let tree = layout(
+        "<p><math><msup><mi>x</mi><mn>2</mn></msup><msub><mi>y</mi><mn>0</mn></msub></math></p>",
+    );
+    let fragments = fragments(&tree.root);
+    let fragment = fragments[0];
+    let base = run_baseline(fragment, "x");
+    let superscript = run_baseline(fragment, "2");
+    let subscript = run_baseline(fragment, "0");
+
+    assert!(superscript < base);
+    assert!(subscript > base);
+    let math = math(fragment).unwrap();
+    assert!(
+        math.runs
+            .iter()
+            .find(|run| run.text == "2")
+            .unwrap()
+            .font_size
+            < 16.0
+    );
+}
+
+#[test]
+fn math_paints_text_and_rules() {
+    let tree = layout("<p><math><mfrac><mn>1</mn><mn>2</mn></mfrac></math></p>");
+    let list = DisplayList::build(&tree);
+    let texts = list
+        .items
+        .iter()
+        .filter(|item| matches!(item, DisplayItem::Text { .. }))
+        .count();
+    let rules = list
+        .items
+        .iter()
+        .filter(|item| matches!(item, DisplayItem::SolidColor { .. }))
+        .count();
+    assert_eq!(texts, 2);
+    assert_eq!(rules, 1);
+}
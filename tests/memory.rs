@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::image::Image;
+
+const PAGE: &str = "<!DOCTYPE html><p id=a class='x y'>hi</p><!--c-->\
+    <div id=host><template shadowrootmode=open><b>s</b></template></div>\
+    <template><i title=t>tt</i></template>";
+
+#[test]
+fn counts_nodes_text_and_attributes() {
+    let stats = parse_html(PAGE).memory_stats();
+    assert_eq!(stats.documents, 1);
+    assert_eq!(stats.doctypes, 1);
+    assert_eq!(stats.shadow_roots, 1);
+    assert_eq!(stats.fragments, 1);
+    assert_eq!(stats.elements, 8);
+    assert_eq!(stats.text_nodes, 3);
+    assert_eq!(stats.comments, 1);
+    assert_eq!(stats.nodes(), 16);
+    assert_eq!(
+        stats.text_bytes,
+        "hi".len() + "s".len() + "tt".len() + "c".len()
+    );
+    assert_eq!(stats.attributes, 4);
+    assert_eq!(
+        stats.attribute_bytes,
+        "ida".len() + "classx y".len() + "idhost".len() + "titlet".len()
+    );
+    assert_eq!(stats.computed_styles, 0);
+    assert!(stats.heap_bytes > 0);
+}
+
+#[test]
+fn generated_content_images_and_animations_add_to_the_heap() {
+    let plain = parse_html("<p id=a>x</p><p id=b>y</p>");
+    plain.restyle();
+    let before = plain.memory_stats();
+
+    let document = parse_html(
+        "<style>#a::before { content: 'gen' } @keyframes fade { to { opacity: 0 } }\
+         #b { animation: fade 1s }</style><p id=a>x</p><p id=b>y</p>",
+    );
+    document.restyle();
+    let image = Arc::new(Image {
+        width: 2,
+        height: 2,
+        pixels: vec![Color::BLACK; 4],
+    });
+    for id in ["a", "b"] {
+        let node = document.get_element_by_id(id).unwrap();
+        *node.image.borrow_mut() = Some(Arc::clone(&image));
+    }
+    let stats = document.memory_stats();
+
+    assert_eq!(stats.pseudo_elements, 1);
+    assert_eq!(stats.images, 1);
+    assert_eq!(stats.animations, 1);
+    // Only the <style> element and its text are new DOM nodes.
+    assert_eq!(stats.elements, before.elements + 1);
+    assert_eq!(stats.text_nodes, before.text_nodes + 1);
+    assert!(stats.heap_bytes > before.heap_bytes + 4 * std::mem::size_of::<Color>());
+    assert!(stats.summary().contains("pseudo-elements"));
+}
@@ -0,0 +1,120 @@
+use std::cell::RefCell;
+use std::io::Read;
+use std::rc::Rc;
+
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::loader::{self, Open};
+use icarus::mutation::{
+    MutationKind, MutationObserver, MutationObserverInit, MutationRecord, notify_observers,
+};
+use icarus::settings::Settings;
+
+type Batches = Rc<RefCell<Vec<Vec<MutationKind>>>>;
+
+fn recording_observer() -> (MutationObserver, Batches) {
+    let batches: Batches = Rc::default();
+    let sink = Rc::clone(&batches);
+    let observer = MutationObserver::new(move |records: Vec<MutationRecord>, _| {
+        sink.borrow_mut()
+            .push(records.iter().map(|record| record.kind).collect());
+    });
+    (observer, batches)
+}
+
+fn everything() -> MutationObserverInit {
+    MutationObserverInit {
+        child_list: true,
+        attributes: true,
+        subtree: true,
+        ..MutationObserverInit::default()
+    }
+}
+
+#[test]
+fn records_are_batched_until_notification() {
+    let document = parse_html("<div id=root><p id=a></p><p id=b></p></div>");
+    let root = document.get_element_by_id("root").unwrap();
+    let a = document.get_element_by_id("a").unwrap();
+    let b = document.get_element_by_id("b").unwrap();
+    let (observer, batches) = recording_observer();
+    observer.observe(&root, everything());
+
+    Node::set_attribute(&a, "class", "one");
+    Node::remove_child(&root, &b);
+    Node::set_attribute(&a, "class", "two");
+    assert!(batches.borrow().is_empty());
+
+    notify_observers();
+    assert_eq!(
+        *batches.borrow(),
+        vec![vec![
+            MutationKind::Attributes,
+            MutationKind::ChildList,
+            MutationKind::Attributes
+        ]]
+    );
+
+    notify_observers();
+    assert_eq!(batches.borrow().len(), 1);
+}
+
+#[test]
+fn take_records_empties_the_queue() {
+    let document = parse_html("<div id=root></div>");
+    let root = document.get_element_by_id("root").unwrap();
+    let (observer, batches) = recording_observer();
+    observer.observe(&root, everything());
+
+    Node::set_attribute(&root, "title", "x");
+    Node::set_attribute(&root, "title", "y");
+    let records = observer.take_records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].attribute_name.as_deref(), Some("title"));
+    assert!(observer.take_records().is_empty());
+
+    notify_observers();
+    assert!(batches.borrow().is_empty());
+}
+
+#[test]
+fn pending_notifications_do_not_keep_observers_alive() {
+    let document = parse_html("<div id=root></div>");
+    let root = document.get_element_by_id("root").unwrap();
+    let (observer, batches) = recording_observer();
+    observer.observe(&root, everything());
+
+    Node::set_attribute(&root, "title", "x");
+    drop(observer);
+    assert_eq!(Rc::strong_count(&batches), 1);
+
+    notify_observers();
+    assert!(batches.borrow().is_empty());
+}
+
+#[test]
+fn finishing_a_load_delivers_pending_records() {
+    let path = std::env::temp_dir().join(format!("icarus-mutations-{}.html", std::process::id()));
+    std::fs::write(&path, "<p>parsed</p>").unwrap();
+    let mut stream = match loader::open(path.to_str().unwrap(), &Settings::default()).unwrap() {
+        Open::Document(stream) => stream,
+        Open::Download(_) => panic!("expected a document"),
+    };
+    let (observer, batches) = recording_observer();
+    observer.observe(&stream.document().root, everything());
+
+    let mut body = String::new();
+    stream
+        .take_body()
+        .unwrap()
+        .read_to_string(&mut body)
+        .unwrap();
+    stream.push(body.as_bytes());
+    assert!(batches.borrow().is_empty());
+
+    let document = stream.finish();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(document.get_elements_by_tag_name("p").len(), 1);
+    assert!(!batches.borrow().is_empty());
+    assert!(observer.take_records().is_empty());
+}
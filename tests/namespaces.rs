@@ -0,0 +1,49 @@
+use icarus::html::parser::parse_html;
+use icarus::metadata::PageMetadata;
+
+const PAGE: &str = "<title>Page</title><svg><title>Icon</title><foreignObject><p>inside</p></foreignObject><rect/></svg><math><mi>x</mi></math><div>html</div>";
+
+#[test]
+fn tag_name_queries_respect_namespaces() {
+    let document = parse_html(PAGE);
+    let svg = "http://www.w3.org/2000/svg";
+    let html = "http://www.w3.org/1999/xhtml";
+
+    assert_eq!(document.get_elements_by_tag_name("title").len(), 2);
+    assert_eq!(document.get_elements_by_tag_name_ns(html, "title").len(), 1);
+    assert_eq!(document.get_elements_by_tag_name_ns(svg, "title").len(), 1);
+    assert_eq!(document.get_elements_by_tag_name_ns(svg, "*").len(), 4);
+    assert_eq!(document.get_elements_by_tag_name("foreignObject").len(), 1);
+    assert!(
+        document
+            .get_elements_by_tag_name("foreignobject")
+            .is_empty()
+    );
+    assert_eq!(document.get_elements_by_tag_name("DIV").len(), 1);
+}
+
+#[test]
+fn selectors_accept_namespace_prefixes() {
+    let document = parse_html(PAGE);
+    let count = |selector: &str| document.query_selector_all(selector).unwrap().len();
+
+    assert_eq!(count("title"), 2);
+    assert_eq!(count("svg|title"), 1);
+    assert_eq!(count("html|title"), 1);
+    assert_eq!(count("*|title"), 2);
+    assert_eq!(count("math|*"), 2);
+    assert_eq!(count("svg|*"), 4);
+    assert_eq!(count("foreignObject > p"), 1);
+    assert_eq!(count("svg|foreignobject"), 0);
+    assert_eq!(count("DIV"), 1);
+    assert!(document.query_selector_all("unknown|p").is_err());
+}
+
+#[test]
+fn document_title_ignores_svg_titles() {
+    let document = parse_html("<svg><title>Icon</title></svg><title>Real</title>");
+    assert_eq!(
+        PageMetadata::from_document(&document).title.as_deref(),
+        Some("Real")
+    );
+}
@@ -0,0 +1,103 @@
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use icarus::net::fetch;
+use icarus::net::log::NetworkLog;
+use icarus::settings::Settings;
+use url::Url;
+
+mod common;
+
+const REDIRECT_DELAY: Duration = Duration::from_millis(200);
+
+fn serve(responses: Vec<(Duration, String)>) -> Url {
+    let mut responses = responses.into_iter();
+    let port = common::serve_with(move |_, mut stream| {
+        if let Some((delay, response)) = responses.next() {
+            thread::sleep(delay);
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    Url::parse(&format!("http://127.0.0.1:{}/start", port)).unwrap()
+}
+
+fn ok(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+#[test]
+fn requests_are_logged_with_status_type_and_size() {
+    let url = serve(vec![(Duration::ZERO, ok("<p>hello</p>"))]);
+    let log = NetworkLog::new();
+    log.start();
+    fetch(&url, &Settings::default())
+        .unwrap()
+        .read_body()
+        .unwrap();
+    log.stop();
+
+    let entries = log.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].status, Some(200));
+    assert_eq!(entries[0].content_type().as_deref(), Some("text/html"));
+    assert_eq!(entries[0].size, 12);
+    assert_eq!(log.total_bytes(), 12);
+    assert!(entries[0].wait <= entries[0].total);
+    assert!(log.summary().contains("1 request(s), 12 B transferred"));
+}
+
+#[test]
+fn redirect_hops_do_not_share_timing() {
+    let url = serve(vec![
+        (
+            REDIRECT_DELAY,
+            String::from("HTTP/1.1 302 Found\r\nLocation: /final\r\nContent-Length: 0\r\n\r\n"),
+        ),
+        (Duration::ZERO, ok("done")),
+    ]);
+    let log = NetworkLog::new();
+    log.start();
+    let response = fetch(&url, &Settings::default()).unwrap();
+    assert!(response.timing.time_to_first_byte() < REDIRECT_DELAY);
+    response.read_body().unwrap();
+    log.stop();
+
+    let entries = log.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].status, Some(302));
+    assert_eq!(entries[1].status, Some(200));
+    assert_eq!(entries[1].url.path(), "/final");
+
+    assert!(entries[0].total >= REDIRECT_DELAY);
+    assert!(entries[1].total < REDIRECT_DELAY);
+    assert!(entries[1].wait < REDIRECT_DELAY);
+    let gap = entries[1]
+        .started_at
+        .duration_since(entries[0].started_at)
+        .unwrap();
+    assert!(gap >= REDIRECT_DELAY);
+}
+
+#[test]
+fn failures_and_stopped_logs() {
+    let log = NetworkLog::new();
+    log.start();
+    let missing = Url::parse("file:///nonexistent/icarus-network-log").unwrap();
+    assert!(fetch(&missing, &Settings::default()).is_err());
+    log.stop();
+    assert!(fetch(&missing, &Settings::default()).is_err());
+
+    let entries = log.entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].status, None);
+    assert!(entries[0].error.is_some());
+    assert!(log.summary().contains("failed"));
+
+    log.clear();
+    assert!(log.is_empty());
+}
@@ -0,0 +1,34 @@
+use icarus::dom::{Node, NodeData};
+use icarus::html::parser::parse_html;
+
+#[test]
+fn nodes_have_unique_ids_that_resolve_back() {
+    let document = parse_html("<ul><li>one</li><li>two</li></ul>");
+    let items = document.get_elements_by_tag_name("li");
+    assert_ne!(items[0].id, items[1].id);
+    let found = document.node_by_id(items[1].id).unwrap();
+    assert!(std::rc::Rc::ptr_eq(&found, &items[1]));
+
+    let detached = Node::new(NodeData::Text {
+        contents: "loose".to_string().into(),
+    });
+    assert!(document.node_by_id(detached.id).is_none());
+}
+
+#[test]
+fn paths_disambiguate_siblings() {
+    let document = parse_html("<div><p>a</p><span>b</span><p>c <!-- note --></p></div>");
+    let paragraphs = document.get_elements_by_tag_name("p");
+    assert_eq!(paragraphs[1].path(), "html > body > div > p:nth-child(3)");
+    let span = &document.get_elements_by_tag_name("span")[0];
+    assert_eq!(span.path(), "html > body > div > span");
+    let comment = paragraphs[1].children.borrow()[1].clone();
+    assert_eq!(
+        comment.path(),
+        "html > body > div > p:nth-child(3) > #comment[1]"
+    );
+
+    let path = Node::index_path(&comment);
+    let resolved = document.node_at_path(&path).unwrap();
+    assert!(std::rc::Rc::ptr_eq(&resolved, &comment));
+}
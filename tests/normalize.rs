@@ -0,0 +1,54 @@
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use std::rc::Rc;
+
+mod common;
+
+use common::text_node;
+
+fn child_texts(node: &Rc<Node>) -> Vec<Option<String>> {
+    node.children
+        .borrow()
+        .iter()
+        .map(|child| child.text_content())
+        .collect()
+}
+
+#[test]
+fn merges_adjacent_text_and_drops_empty_nodes() {
+    let document = parse_html("<div id=a>one<b>bold</b></div>");
+    let div = document.get_element_by_id("a").unwrap();
+    let bold = document.query_selector("b").unwrap().unwrap();
+    Node::append_child(&div, text_node(" two"));
+    Node::append_child(&div, text_node(""));
+    Node::append_child(&div, text_node(" three"));
+    Node::insert_before(&div, text_node(""), &bold);
+    Node::append_child(&bold, text_node(""));
+    Node::append_child(&bold, text_node("er"));
+
+    div.normalize();
+
+    assert_eq!(
+        child_texts(&div),
+        [
+            Some("one".to_string()),
+            None,
+            Some(" two three".to_string())
+        ]
+    );
+    assert_eq!(child_texts(&bold), [Some("bolder".to_string())]);
+}
+
+#[test]
+fn keeps_the_first_node_of_a_run() {
+    let document = parse_html("<p id=p>a</p>");
+    let p = document.get_element_by_id("p").unwrap();
+    let first = Rc::clone(&p.children.borrow()[0]);
+    Node::append_child(&p, text_node("b"));
+
+    p.normalize();
+
+    assert_eq!(p.children.borrow().len(), 1);
+    assert!(Rc::ptr_eq(&p.children.borrow()[0], &first));
+    assert_eq!(first.text_content().as_deref(), Some("ab"));
+}
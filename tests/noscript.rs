@@ -0,0 +1,47 @@
+use icarus::html::parser::{ParseOptions, parse_html_with};
+use icarus::loader::{self, Load};
+use icarus::render::tty;
+use icarus::settings::Settings;
+
+const PAGE: &str = "<head><noscript><style>p { color: red }</style></noscript></head>
+    <body><p>always</p><noscript><p id=fallback>enable <b>scripts</b></p></noscript></body>";
+
+#[test]
+fn scripting_parses_noscript_as_raw_text_and_hides_it() {
+    let document = parse_html_with(PAGE, ParseOptions { scripting: true });
+    assert!(document.scripting);
+    assert!(document.get_element_by_id("fallback").is_none());
+    let text = tty::render(&document, 40);
+    assert!(text.contains("always"));
+    assert!(!text.contains("enable"), "{}", text);
+}
+
+#[test]
+fn disabled_scripting_renders_noscript_content() {
+    let document = parse_html_with(PAGE, ParseOptions { scripting: false });
+    assert!(!document.scripting);
+    let fallback = document.get_element_by_id("fallback").unwrap();
+    assert_eq!(fallback.get_text_content(), "enable scripts");
+    assert_eq!(document.get_elements_by_tag_name("style").len(), 1);
+    let text = tty::render(&document, 40);
+    assert!(text.contains("enable scripts"), "{}", text);
+}
+
+#[test]
+fn no_script_flag_reaches_the_loader() {
+    let mut args = vec![String::from("--no-script"), String::from("page.html")];
+    let mut settings = Settings::default();
+    settings.apply_args(&mut args).unwrap();
+    assert_eq!(args, ["page.html"]);
+    assert!(!settings.parse_options().scripting);
+
+    let path = std::env::temp_dir().join(format!("icarus-noscript-{}.html", std::process::id()));
+    std::fs::write(&path, PAGE).unwrap();
+    let url = url::Url::from_file_path(&path).unwrap();
+    let load = loader::load_with(url.as_str(), &settings);
+    std::fs::remove_file(&path).unwrap();
+    let Load::Document(document) = load.unwrap() else {
+        panic!("expected a document");
+    };
+    assert!(document.get_element_by_id("fallback").is_some());
+}
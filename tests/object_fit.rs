@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use icarus::css::values::Color;
+use icarus::dom::Document;
+use icarus::html::parser::parse_html;
+use icarus::image::Image;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::raster::Canvas;
+use icarus::paint::{DisplayItem, DisplayList};
+use icarus::style::style_of;
+
+fn with_image(html: &str) -> Document {
+    let document = parse_html(html);
+    let img = document.query_selector("img").unwrap().unwrap();
+    let mut pixels = vec![Color::rgb(255, 0, 0); 400 * 100];
+    for row in pixels.chunks_mut(400) {
+        row[200..].fill(Color::rgb(0, 0, 255));
+    }
+    *img.image.borrow_mut() = Some(Arc::new(Image {
+        width: 400,
+        height: 100,
+        pixels,
+    }));
+    document
+}
+
+fn image_item(document: &Document) -> (Rect, Rect) {
+    let tree = LayoutTree::build(document, LayoutOptions::default());
+    DisplayList::build(&tree)
+        .items
+        .into_iter()
+        .find_map(|item| match item {
+            DisplayItem::Image { rect, source, .. } => Some((rect, source)),
+            _ => None,
+        })
+        .expect("image item")
+}
+
+#[test]
+fn aspect_ratio_is_parsed() {
+    let document = parse_html(
+        "<p id=a style='aspect-ratio: 16/9'>x</p>
+         <p id=b style='aspect-ratio: auto 2'>y</p>
+         <p id=c style='aspect-ratio: 1 / 0; object-fit: cover'>z</p>",
+    );
+    document.restyle();
+    let map = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()
+    };
+    assert_eq!(map("#a")["aspect-ratio"], "16 / 9");
+    assert_eq!(map("#b")["aspect-ratio"], "auto 2 / 1");
+    assert_eq!(map("#c")["aspect-ratio"], "auto 1 / 0");
+    assert_eq!(map("#c")["object-fit"], "cover");
+}
+
+#[test]
+fn aspect_ratio_sizes_blocks_and_images() {
+    let document = with_image(
+        "<body style='margin: 0'><div id=a style='aspect-ratio: 16 / 9'></div>
+         <div id=b style='width: 100px; aspect-ratio: 2; padding: 10px; box-sizing: border-box'></div>
+         <img style='width: 200px; aspect-ratio: 1'></body>",
+    );
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let a = document.query_selector("#a").unwrap().unwrap();
+    assert_eq!(tree.rect_of(&a).unwrap().height, 450.0);
+    let b = document.query_selector("#b").unwrap().unwrap();
+    assert_eq!(tree.rect_of(&b).unwrap().height, 50.0);
+    let (rect, _) = image_item(&document);
+    assert_eq!((rect.width, rect.height), (200.0, 200.0));
+}
+
+#[test]
+fn object_fit_contains_and_covers() {
+    let html = |fit: &str| {
+        format!(
+            "<body style='margin: 0'><img style='width: 100px; height: 100px; object-fit: {}'>",
+            fit
+        )
+    };
+    let (rect, source) = image_item(&with_image(&html("fill")));
+    assert_eq!(rect, Rect::new(0.0, 0.0, 100.0, 100.0));
+    assert_eq!(source, Rect::new(0.0, 0.0, 400.0, 100.0));
+
+    let (rect, source) = image_item(&with_image(&html("contain")));
+    assert_eq!(rect, Rect::new(0.0, 37.5, 100.0, 25.0));
+    assert_eq!(source, Rect::new(0.0, 0.0, 400.0, 100.0));
+
+    let document = with_image(&html("cover"));
+    let (rect, source) = image_item(&document);
+    assert_eq!(rect, Rect::new(0.0, 0.0, 100.0, 100.0));
+    assert_eq!(source, Rect::new(150.0, 0.0, 100.0, 100.0));
+
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&DisplayList::build(&tree), None, 0.0);
+    let pixel = |x: usize, y: usize| canvas.pixels[y * 100 + x] & 0xffffff;
+    assert_eq!(pixel(10, 50), 0xff0000);
+    assert_eq!(pixel(90, 50), 0x0000ff);
+}
@@ -0,0 +1,37 @@
+use icarus::html::parser::parse_html;
+use icarus::outline;
+
+#[test]
+fn headings_nest_by_level() {
+    let document = parse_html(
+        "<h1>Title</h1>
+         <h2>Intro</h2><p>text</p>
+         <h3>Detail  <em>one</em></h3>
+         <h2>Usage</h2>
+         <h4>Skipped a level</h4>
+         <template><h2>Inert</h2></template>
+         <section hidden><h2>Hidden</h2></section>
+         <h1>Appendix</h1>",
+    );
+    let headings = document.outline();
+    assert_eq!(headings.len(), 2);
+    assert_eq!(headings[0].children.len(), 2);
+    assert_eq!(headings[0].children[0].children[0].text, "Detail one");
+    assert_eq!(
+        headings[0].children[1].children[0].node.element_name(),
+        Some("h4")
+    );
+    assert_eq!(
+        outline::dump(&headings),
+        "h1 Title\n  h2 Intro\n    h3 Detail one\n  h2 Usage\n    h4 Skipped a level\nh1 Appendix\n"
+    );
+}
+
+#[test]
+fn leading_subheadings_stay_top_level() {
+    let document = parse_html("<h3>Deep</h3><h2>Shallower</h2><h3>Child</h3>");
+    let headings = document.outline();
+    assert_eq!(headings.len(), 2);
+    assert_eq!(headings[0].text, "Deep");
+    assert_eq!(headings[1].children[0].text, "Child");
+}
@@ -0,0 +1,58 @@
+use icarus::dom::NodeData;
+use icarus::html::parser::{FilterAction, ParseFilter, parse_html_with_filter};
+use icarus::html::serialize::outer_html;
+
+#[test]
+fn skips_filtered_subtrees() {
+    let document = parse_html_with_filter(
+        "<p>a<script>x()</script><svg><g><text>t</text></g></svg>b</p>",
+        ParseFilter::skip(&["script", "svg"]),
+    );
+    assert_eq!(
+        outer_html(&document.root),
+        "<html><head></head><body><p>ab</p></body></html>"
+    );
+}
+
+#[test]
+fn keeps_only_selected_subtrees() {
+    let document = parse_html_with_filter(
+        "<title>T</title><nav>menu</nav><article><h1>One</h1><p>x</p></article><p>footer</p><article>Two</article>",
+        ParseFilter::only(&["article"]),
+    );
+    assert_eq!(
+        outer_html(&document.root),
+        "<article><h1>One</h1><p>x</p></article><article>Two</article>"
+    );
+}
+
+#[test]
+fn custom_filters_see_the_kept_parent() {
+    let document = parse_html_with_filter(
+        "<div class=ad><p>buy</p></div><div><p class=ad>no</p><p>yes</p></div>",
+        ParseFilter::new(|element, parent| {
+            let ad = element.get_attribute("class").as_deref() == Some("ad");
+            if ad && matches!(parent.data, NodeData::Element { .. }) {
+                FilterAction::Skip
+            } else {
+                FilterAction::Keep
+            }
+        }),
+    );
+    assert_eq!(
+        outer_html(&document.root),
+        "<html><head></head><body><div><p>yes</p></div></body></html>"
+    );
+}
+
+#[test]
+fn filtered_tables_survive_foster_parenting() {
+    let document = parse_html_with_filter(
+        "<table><tr><td>cell</td></tr>stray<b>bold</b></table>",
+        ParseFilter::skip(&["table"]),
+    );
+    assert_eq!(
+        outer_html(&document.root),
+        "<html><head></head><body>stray<b>bold</b></body></html>"
+    );
+}
@@ -0,0 +1,40 @@
+use icarus::loader::{self, Load};
+use icarus::render::tty;
+use icarus::settings::Settings;
+
+#[test]
+fn milestones_are_ordered() {
+    let path = std::env::temp_dir().join(format!("icarus-timing-{}.html", std::process::id()));
+    std::fs::write(&path, "<title>t</title><p>hello <b>world</b></p>").unwrap();
+    let url = url::Url::from_file_path(&path).unwrap();
+    let Load::Document(document) = loader::load_with(url.as_str(), &Settings::default()).unwrap()
+    else {
+        panic!("expected a document");
+    };
+    std::fs::remove_file(&path).unwrap();
+
+    let parsed = document.performance.metrics();
+    assert!(parsed.dom_parsed.is_some());
+    assert!(parsed.first_paint.is_none());
+    assert!(parsed.load_complete.is_none());
+
+    tty::render(&document, 40);
+    let metrics = document.performance.metrics();
+    let milestones = [
+        metrics.fetch_start.unwrap(),
+        metrics.dom_parsed.unwrap(),
+        metrics.first_paint.unwrap(),
+        metrics.load_complete.unwrap(),
+    ];
+    assert!(metrics.navigation_start <= milestones[0]);
+    assert!(
+        milestones.windows(2).all(|pair| pair[0] <= pair[1]),
+        "{}",
+        metrics.summary()
+    );
+
+    tty::render(&document, 40);
+    let again = document.performance.metrics();
+    assert_eq!(again.first_paint, metrics.first_paint);
+    assert_eq!(again.load_complete, metrics.load_complete);
+}
@@ -0,0 +1,136 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::layout::Rect;
+use icarus::paint::DisplayItem;
+use icarus::settings::Settings;
+use icarus::shell::pipeline::{Command, Event, Pipeline};
+use icarus::storage::StorageContext;
+
+mod common;
+
+fn spawn(url: &str) -> Pipeline {
+    Pipeline::spawn(
+        url,
+        Rect::new(0.0, 0.0, 400.0, 300.0),
+        Arc::new(Settings::default()),
+        StorageContext::default(),
+    )
+    .unwrap()
+}
+
+fn wait_for<T>(pipeline: &Pipeline, mut pick: impl FnMut(Event) -> Option<T>) -> T {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        for event in pipeline.poll().unwrap() {
+            if let Event::Failed(message) = &event {
+                panic!("pipeline failed: {}", message);
+            }
+            if let Some(value) = pick(event) {
+                return value;
+            }
+        }
+        assert!(
+            Instant::now() < deadline,
+            "timed out waiting for the pipeline"
+        );
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn texts(items: &[DisplayItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, .. } => Some(text.trim().to_string()),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+#[test]
+fn worker_sends_frames_and_answers_commands() {
+    let path = std::env::temp_dir().join(format!("icarus-pipeline-{}.html", std::process::id()));
+    std::fs::write(&path, "<p>hello <a href=next.html>next</a></p>").unwrap();
+    let url = url::Url::from_file_path(&path).unwrap();
+    let pipeline = spawn(url.as_str());
+
+    let committed = wait_for(&pipeline, |event| match event {
+        Event::Committed(url) => Some(url),
+        _ => None,
+    });
+    assert_eq!(committed.as_deref(), Some(url.as_str()));
+    let source = wait_for(&pipeline, |event| match event {
+        Event::Source(source) => Some(source),
+        _ => None,
+    });
+    std::fs::remove_file(&path).unwrap();
+    assert!(source.contains("next.html"));
+
+    pipeline.send(Command::Resize(200));
+    let (display_list, height) = wait_for(&pipeline, |event| match event {
+        Event::Frame {
+            display_list,
+            height,
+        } => Some((display_list, height)),
+        _ => None,
+    });
+    assert_eq!(texts(&display_list.items), ["hello", "next"]);
+    assert!(height > 0.0);
+
+    let link = display_list
+        .items
+        .iter()
+        .find_map(|item| match item {
+            DisplayItem::Text { rect, text, .. } if text == "next" => Some(*rect),
+            _ => None,
+        })
+        .unwrap();
+    pipeline.send(Command::Click {
+        x: link.x + 1.0,
+        y: link.y + 1.0,
+    });
+    let followed = wait_for(&pipeline, |event| match event {
+        Event::Link(url) => Some(url),
+        _ => None,
+    });
+    assert!(followed.ends_with("/next.html"), "{}", followed);
+}
+
+#[test]
+fn ui_side_never_blocks_on_a_slow_body() {
+    let (release, released) = mpsc::channel::<()>();
+    let port = common::serve_with(move |_, mut stream| {
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nConnection: close\r\n\r\n<p>first</p>")
+            .unwrap();
+        stream.flush().unwrap();
+        let _ = released.recv();
+        let _ = stream.write_all(b"<p>second</p>");
+    });
+
+    let pipeline = spawn(&format!("http://127.0.0.1:{}/slow", port));
+    wait_for(&pipeline, |event| match event {
+        Event::Committed(_) => Some(()),
+        _ => None,
+    });
+
+    let start = Instant::now();
+    for _ in 0..10 {
+        for event in pipeline.poll().unwrap() {
+            assert!(!matches!(event, Event::Source(_)));
+        }
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    release.send(()).unwrap();
+    let source = wait_for(&pipeline, |event| match event {
+        Event::Source(source) => Some(source),
+        _ => None,
+    });
+    assert_eq!(source, "<p>first</p><p>second</p>");
+}
@@ -0,0 +1,105 @@
+use icarus::html::parser::parse_html;
+use icarus::render::pdf::{self, PageSize, PrintOptions};
+use icarus::style::style_of;
+
+fn page_count(pdf: &[u8]) -> usize {
+    String::from_utf8_lossy(pdf).matches("/Type /Page ").count()
+}
+
+#[test]
+fn prints_selectable_text_into_a_pdf() {
+    let document = parse_html("<title>Report (draft)</title><p>Hello, world</p>");
+    let pdf = pdf::print(&document, &PrintOptions::default());
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-1.4"));
+    assert!(text.ends_with("%%EOF\n"));
+    assert!(text.contains("(Hello, world) Tj"));
+    assert!(text.contains("/Title (Report \\(draft\\))"));
+    assert_eq!(page_count(&pdf), 1);
+}
+
+#[test]
+fn long_documents_flow_onto_more_pages() {
+    let paragraphs = "<p>line</p>".repeat(120);
+    let document = parse_html(&paragraphs);
+    let options = PrintOptions {
+        page: PageSize::A5,
+        ..PrintOptions::default()
+    };
+    let pdf = pdf::print(&document, &options);
+    assert!(page_count(&pdf) > 3);
+    let text = String::from_utf8_lossy(&pdf);
+    assert_eq!(text.matches("(line) Tj").count(), 120);
+}
+
+#[test]
+fn forced_breaks_start_new_pages() {
+    let document = parse_html(
+        "<style>h1 { break-before: page } .end { page-break-after: always }</style>
+         <h1>One</h1><p class=end>Two</p><p>Three</p>",
+    );
+    let pdf = pdf::print(&document, &PrintOptions::default());
+    assert_eq!(page_count(&pdf), 2);
+}
+
+#[test]
+fn huge_line_heights_still_paginate() {
+    let document = parse_html("<p style='line-height:1e30'>x</p><p>y</p>");
+    let pdf = pdf::print(&document, &PrintOptions::default());
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("(x) Tj") && text.contains("(y) Tj"));
+    assert!(page_count(&pdf) <= 10_000);
+}
+
+#[test]
+fn print_media_rules_apply_only_while_printing() {
+    let document = parse_html(
+        "<style>@media print { .screen { display: none } }
+                @media screen { .print { display: none } }</style>
+         <p class=screen>On screen</p><p class=print>On paper</p>",
+    );
+    let pdf = pdf::print(&document, &PrintOptions::default());
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("(On paper) Tj"));
+    assert!(!text.contains("(On screen) Tj"));
+
+    let screen = document.query_selector(".screen").unwrap().unwrap();
+    assert_eq!(style_of(&screen).to_map()["display"], "block");
+}
+
+#[test]
+fn page_rules_set_the_page_margins() {
+    let document = parse_html(
+        "<style>@page { margin: 0 10% } @media screen { @page { margin: 100px } }
+                body { margin: 0 }</style><p>Edge</p>",
+    );
+    let pdf = pdf::print(&document, &PrintOptions::default());
+    let text = String::from_utf8_lossy(&pdf);
+    let margin = 79.37 * pdf::POINTS_PER_PX;
+    assert!(text.contains(&format!("q {:.2} 0.00 ", margin)));
+}
+
+#[test]
+fn break_inside_avoid_moves_blocks_to_the_next_page() {
+    let html = |keep: &str| {
+        format!(
+            "<style>.keep {{ page-break-inside: {} }}</style>
+             <div style='height: 600px'></div>
+             <div class=keep><p>first</p>{}</div>",
+            keep,
+            "<p>more</p>".repeat(5)
+        )
+    };
+    let options = PrintOptions {
+        page: PageSize::A5,
+        ..PrintOptions::default()
+    };
+    let second_page = |keep: &str| {
+        let pdf = pdf::print(&parse_html(&html(keep)), &options);
+        let text = String::from_utf8_lossy(&pdf).into_owned();
+        let first = text.find("(first) Tj").unwrap();
+        text[..first].matches("re W n").count() == 2
+    };
+    assert!(!second_page("auto"));
+    assert!(second_page("avoid"));
+}
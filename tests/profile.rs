@@ -0,0 +1,23 @@
+use icarus::html::parser::parse_html;
+use icarus::profile::Profiler;
+use tracing_subscriber::Registry;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[test]
+fn profiled_parse_records_the_node_count() {
+    let html = "<!doctype html><title>t</title><p>one<!-- c --><b>two</b></p>";
+    let document = parse_html(html);
+    let mut expected = 0;
+    document.root.walk(&mut |_| expected += 1);
+
+    let profiler = Profiler::new();
+    let subscriber = Registry::default().with(profiler.clone());
+    tracing::subscriber::with_default(subscriber, || parse_html(html));
+
+    let stages = profiler.stages();
+    let parse = stages.iter().find(|stage| stage.name == "parse").unwrap();
+    assert_eq!(parse.calls, 1);
+    assert!(parse.counts.contains(&("nodes", expected)));
+    assert!(parse.counts.contains(&("bytes", html.len() as u64)));
+    assert!(profiler.report().contains(&format!("nodes={}", expected)));
+}
@@ -0,0 +1,102 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::range::{BoundaryPoint, Range, Selection};
+
+fn text_of(node: &Rc<Node>) -> Rc<Node> {
+    node.children.borrow()[0].clone()
+}
+
+#[test]
+fn boundary_points_compare_in_tree_order() {
+    let document = parse_html("<p id=a>one</p><p id=b>two</p>");
+    let a = document.get_element_by_id("a").unwrap();
+    let b = document.get_element_by_id("b").unwrap();
+    let one = text_of(&a);
+    let two = text_of(&b);
+
+    let start = BoundaryPoint::new(&one, 1);
+    assert_eq!(start.compare(&BoundaryPoint::new(&one, 2)), Ordering::Less);
+    assert_eq!(start.compare(&BoundaryPoint::new(&one, 1)), Ordering::Equal);
+    assert_eq!(start.compare(&BoundaryPoint::new(&two, 0)), Ordering::Less);
+    assert_eq!(
+        BoundaryPoint::new(&a, 1).compare(&BoundaryPoint::new(&one, 3)),
+        Ordering::Greater
+    );
+    assert_eq!(BoundaryPoint::new(&one, 10).offset, 3);
+}
+
+#[test]
+fn ranges_order_their_end_points() {
+    let document = parse_html("<p id=a>hello world</p>");
+    let text = text_of(&document.get_element_by_id("a").unwrap());
+    let range = Range::from_points(BoundaryPoint::new(&text, 8), BoundaryPoint::new(&text, 2));
+    assert_eq!((range.start.offset, range.end.offset), (2, 8));
+
+    let mut range = Range::new(&text);
+    range.set_end(&text, 5);
+    range.set_start(&text, 7);
+    assert!(range.collapsed());
+    assert_eq!(range.end.offset, 7);
+}
+
+#[test]
+fn extraction_splits_partially_selected_nodes() {
+    let document =
+        parse_html("<div id=root><p id=a>alpha</p><p id=b>beta</p><p id=c>gamma</p></div>");
+    let root = document.get_element_by_id("root").unwrap();
+    let alpha = text_of(&document.get_element_by_id("a").unwrap());
+    let gamma = text_of(&document.get_element_by_id("c").unwrap());
+
+    let mut range =
+        Range::from_points(BoundaryPoint::new(&alpha, 2), BoundaryPoint::new(&gamma, 3));
+    let extracted = range.extract_contents();
+
+    let names: Vec<_> = extracted
+        .iter()
+        .map(|node| node.element_name().unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(names, ["p", "p", "p"]);
+    let texts: Vec<_> = extracted
+        .iter()
+        .map(|node| node.get_text_content())
+        .collect();
+    assert_eq!(texts, ["pha", "beta", "gam"]);
+    assert!(range.collapsed());
+    assert_eq!(root.get_text_content(), "alma");
+    assert_eq!(root.children.borrow().len(), 2);
+}
+
+#[test]
+fn clone_contents_leaves_the_tree_alone() {
+    let document = parse_html("<p id=a>hello <b>bold</b> world</p>");
+    let p = document.get_element_by_id("a").unwrap();
+    let mut range = Range::new(&p);
+    range.select_node_contents(&p);
+    assert_eq!(range.clone_contents().len(), 3);
+    assert_eq!(p.get_text_content(), "hello bold world");
+}
+
+#[test]
+fn text_serialization_covers_the_selected_characters() {
+    let document = parse_html("<p id=a>hello <b>bold</b> world</p>");
+    let p = document.get_element_by_id("a").unwrap();
+    let hello = text_of(&p);
+    let world = p.children.borrow()[2].clone();
+
+    let range = Range::from_points(BoundaryPoint::new(&hello, 3), BoundaryPoint::new(&world, 3));
+    assert_eq!(range.to_text(), "lo bold wo");
+
+    let mut selection = Selection::new();
+    assert!(selection.is_collapsed());
+    selection.collapse(&world, 3);
+    selection.extend(&hello, 3);
+    assert_eq!(selection.to_text(), "lo bold wo");
+    assert_eq!(selection.anchor().unwrap().offset, 3);
+    assert!(Rc::ptr_eq(&selection.focus().unwrap().node, &hello));
+
+    selection.remove_all_ranges();
+    assert_eq!(selection.to_text(), "");
+}
@@ -0,0 +1,43 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::reader::extract_article;
+
+const PAGE: &str = "<title>A &lt;long&gt; story</title>
+    <nav class=menu><a href=/a>Home</a> <a href=/b>About</a></nav>
+    <div class=content>
+      <p>The first paragraph is long enough, with commas, to count as article text.</p>
+      <p>The second paragraph keeps going so the container scores well, again and again.</p>
+      <p><a href=more.html>Read more</a> of this story elsewhere, if you have the time.</p>
+    </div>
+    <footer>Copyright</footer>";
+
+#[test]
+fn reader_page_keeps_the_article_and_drops_the_chrome() {
+    let document = parse_html(PAGE);
+    let article = extract_article(&document).expect("article");
+    assert_eq!(article.title, "A <long> story");
+
+    let page = article.page(&document);
+    let heading = page.get_elements_by_tag_name("h1");
+    assert_eq!(heading[0].get_text_content(), "A <long> story");
+    assert_eq!(page.get_elements_by_tag_name("p").len(), 3);
+    assert!(page.get_elements_by_tag_name("nav").is_empty());
+    assert!(page.get_elements_by_tag_name("footer").is_empty());
+}
+
+#[test]
+fn reader_page_lays_out_in_a_narrow_column() {
+    let document = parse_html(PAGE);
+    let page = extract_article(&document).expect("article").page(&document);
+    let tree = LayoutTree::build(
+        &page,
+        LayoutOptions {
+            viewport_width: 1600.0,
+            ..LayoutOptions::default()
+        },
+    );
+    let heading = page.get_elements_by_tag_name("h1")[0].clone();
+    let rect = tree.rect_of(&heading).expect("heading box");
+    assert!(rect.x > 100.0);
+    assert!(rect.width < 1000.0);
+}
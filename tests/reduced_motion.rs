@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use icarus::css::media::{ColorScheme, MediaFeatures, MediaQueryList, MediaType, ReducedMotion};
+use icarus::dom::Document;
+use icarus::html::parser::parse_html;
+use icarus::settings::Settings;
+use icarus::style::style_of;
+
+fn page(reduced_motion: ReducedMotion) -> Document {
+    let mut document = parse_html(
+        "<style>
+           @keyframes grow { from { width: 100px } to { width: 200px } }
+           #box { animation: grow 2s linear forwards }
+           #spinner { animation: grow 1s infinite }
+           @media (prefers-reduced-motion) { #box { height: 5px } }
+         </style>
+         <div id=box></div><div id=spinner></div>",
+    );
+    document.settings = Arc::new(Settings {
+        reduced_motion,
+        ..Settings::default()
+    });
+    document.restyle();
+    document
+}
+
+fn property(document: &Document, selector: &str, property: &str) -> String {
+    let node = document.query_selector(selector).unwrap().unwrap();
+    style_of(&node).to_map()[property].clone()
+}
+
+#[test]
+fn media_queries_follow_the_motion_preference() {
+    let features = |reduced_motion| MediaFeatures {
+        media_type: MediaType::Screen,
+        color_scheme: ColorScheme::Light,
+        reduced_motion,
+        scripting: true,
+    };
+    let reduce = features(ReducedMotion::Reduce);
+    let motion = features(ReducedMotion::NoPreference);
+    let query = MediaQueryList::parse("(prefers-reduced-motion)");
+    assert!(query.matches(&reduce));
+    assert!(!query.matches(&motion));
+    let query = MediaQueryList::parse("(prefers-reduced-motion: no-preference)");
+    assert!(!query.matches(&reduce));
+    assert!(query.matches(&motion));
+    assert!(MediaQueryList::parse("(prefers-reduced-motion: reduce)").matches(&reduce));
+    assert!(!MediaQueryList::parse("(prefers-reduced-motion: system)").matches(&reduce));
+}
+
+#[test]
+fn reduced_motion_is_configurable() {
+    let settings = Settings::from_toml("reduced-motion = \"reduce\"").unwrap();
+    assert_eq!(settings.reduced_motion, ReducedMotion::Reduce);
+    assert_eq!(
+        Settings::from_toml("").unwrap().reduced_motion,
+        ReducedMotion::System
+    );
+    assert!(Settings::from_toml("reduced-motion = \"less\"").is_err());
+
+    let mut settings = Settings::default();
+    assert_eq!(settings.reduced_motion, ReducedMotion::NoPreference);
+    let mut args = vec![
+        String::from("--reduced-motion"),
+        String::from("reduce"),
+        String::from("about:blank"),
+    ];
+    settings.apply_args(&mut args).unwrap();
+    assert_eq!(settings.reduced_motion, ReducedMotion::Reduce);
+    assert_eq!(args, ["about:blank"]);
+}
+
+#[test]
+fn animations_run_without_a_preference() {
+    let document = page(ReducedMotion::NoPreference);
+    assert_eq!(property(&document, "#box", "width"), "100px");
+    assert_eq!(property(&document, "#box", "height"), "auto");
+    assert!(document.animations_running());
+}
+
+#[test]
+fn reduced_motion_skips_to_the_end_of_animations() {
+    let document = page(ReducedMotion::Reduce);
+    assert_eq!(property(&document, "#box", "width"), "200px");
+    assert_eq!(property(&document, "#box", "height"), "5px");
+    assert_eq!(property(&document, "#spinner", "width"), "auto");
+    assert!(!document.animations_running());
+    assert!(!document.advance_animations(0.5));
+}
@@ -0,0 +1,137 @@
+use std::time::{Duration, Instant};
+
+use icarus::html::parser::parse_html;
+use icarus::loader::{self, Load};
+use icarus::location::Navigation;
+use icarus::net::{ResponseInfo, Timing};
+use icarus::refresh::{Refresh, RefreshTimer, parse_refresh};
+use icarus::settings::Settings;
+use url::Url;
+
+mod common;
+
+fn serve(response: &'static [u8]) -> Url {
+    let port = common::serve(vec![response]);
+    Url::parse(&format!("http://127.0.0.1:{}/page", port)).unwrap()
+}
+
+#[test]
+fn refresh_content_is_parsed() {
+    let secs = Duration::from_secs;
+    assert_eq!(parse_refresh("5"), Some((secs(5), None)));
+    assert_eq!(
+        parse_refresh("0; url=next.html"),
+        Some((secs(0), Some(String::from("next.html"))))
+    );
+    assert_eq!(
+        parse_refresh(" 3 , URL = 'quoted.html' trailing"),
+        Some((secs(3), Some(String::from("quoted.html"))))
+    );
+    assert_eq!(
+        parse_refresh("2.9;other.html"),
+        Some((secs(2), Some(String::from("other.html"))))
+    );
+    assert_eq!(parse_refresh("1; url="), Some((secs(1), None)));
+    assert_eq!(parse_refresh("soon"), None);
+    assert_eq!(parse_refresh(".5; url=x"), None);
+}
+
+#[test]
+fn meta_refresh_wins_over_the_header_and_resolves_relative_urls() {
+    let mut document =
+        parse_html("<meta http-equiv=' Refresh ' content='4; url=../next.html'><p>wait</p>");
+    document.response = Some(ResponseInfo {
+        url: Url::parse("http://example.com/a/b/page.html").unwrap(),
+        status: 200,
+        headers: vec![(String::from("Refresh"), String::from("1; url=header.html"))],
+        timing: Timing::started(Instant::now()),
+    });
+    let refresh = Refresh::from_document(&document).unwrap();
+    assert_eq!(refresh.delay, Duration::from_secs(4));
+    assert_eq!(
+        refresh.url.unwrap().as_str(),
+        "http://example.com/a/next.html"
+    );
+}
+
+#[test]
+fn refresh_header_from_http_response() {
+    let url = serve(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nRefresh: 2; url=/after\r\nContent-Length: 9\r\n\r\n<p>hi</p>",
+    );
+    let Load::Document(document) = loader::load_with(url.as_str(), &Settings::default()).unwrap()
+    else {
+        panic!("expected a document");
+    };
+    let refresh = Refresh::from_document(&document).unwrap();
+    assert_eq!(refresh.delay, Duration::from_secs(2));
+    assert_eq!(refresh.url.unwrap().path(), "/after");
+}
+
+#[test]
+fn refresh_navigates_by_replacing_or_reloading() {
+    let refresh = Refresh {
+        delay: Duration::ZERO,
+        url: Some(Url::parse("http://example.com/next").unwrap()),
+    };
+    assert!(matches!(
+        refresh.navigation("http://example.com/"),
+        Navigation::Load { ref url, replace: true } if url == "http://example.com/next"
+    ));
+    assert!(matches!(
+        refresh.navigation("http://example.com/next"),
+        Navigation::Reload
+    ));
+    let reload = Refresh {
+        delay: Duration::ZERO,
+        url: None,
+    };
+    assert!(matches!(
+        reload.navigation("http://example.com/"),
+        Navigation::Reload
+    ));
+}
+
+#[test]
+fn timer_fires_once_after_the_delay() {
+    let now = Instant::now();
+    let refresh = Refresh {
+        delay: Duration::from_secs(3),
+        url: None,
+    };
+    let mut timer = RefreshTimer::default();
+    assert!(timer.take_due(now).is_none());
+
+    timer.schedule(refresh.clone(), now);
+    assert!(timer.is_pending());
+    assert!(timer.take_due(now + Duration::from_secs(2)).is_none());
+    assert_eq!(timer.take_due(now + Duration::from_secs(3)), Some(refresh));
+    assert!(!timer.is_pending());
+    assert!(timer.take_due(now + Duration::from_secs(10)).is_none());
+}
+
+#[test]
+fn cancelled_timer_never_fires() {
+    let now = Instant::now();
+    let mut timer = RefreshTimer::default();
+    timer.schedule(
+        Refresh {
+            delay: Duration::ZERO,
+            url: None,
+        },
+        now,
+    );
+    timer.cancel();
+    assert!(timer.take_due(now + Duration::from_secs(1)).is_none());
+}
+
+#[test]
+fn refresh_can_be_disabled() {
+    assert!(Settings::default().refresh);
+    assert!(!Settings::from_toml("refresh = false").unwrap().refresh);
+    let mut args = vec![String::from("--no-refresh"), String::from("page.html")];
+    let mut settings = Settings::default();
+    settings.apply_args(&mut args).unwrap();
+    assert_eq!(args, ["page.html"]);
+    assert!(!settings.refresh);
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::paint::DisplayList;
+use icarus::paint::raster::Canvas;
+
+const DIRECTORY: &str = "tests/reftests";
+const WIDTH: usize = 400;
+const HEIGHT: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Relation {
+    Equal,
+    NotEqual,
+}
+
+struct Reftest {
+    relation: Relation,
+    test: String,
+    reference: String,
+    max_difference: u8,
+    max_pixels: usize,
+}
+
+fn parse_manifest(source: &str) -> Vec<Reftest> {
+    let mut tests = Vec::new();
+    for line in source.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace().peekable();
+        let (mut max_difference, mut max_pixels) = (0, 0);
+        if let Some(fuzzy) = parts
+            .peek()
+            .and_then(|part| part.strip_prefix("fuzzy("))
+            .and_then(|part| part.strip_suffix(')'))
+        {
+            let (difference, pixels) = fuzzy.split_once(',').expect("fuzzy(<diff>,<pixels>)");
+            max_difference = difference.trim().parse().expect("fuzzy difference");
+            max_pixels = pixels.trim().parse().expect("fuzzy pixel count");
+            parts.next();
+        }
+        let relation = match parts.next() {
+            Some("==") => Relation::Equal,
+            Some("!=") => Relation::NotEqual,
+            other => panic!("bad reftest relation {:?} in {:?}", other, line),
+        };
+        let (Some(test), Some(reference)) = (parts.next(), parts.next()) else {
+            panic!("reftest line needs a test and a reference: {:?}", line);
+        };
+        tests.push(Reftest {
+            relation,
+            test: test.to_string(),
+            reference: reference.to_string(),
+            max_difference,
+            max_pixels,
+        });
+    }
+    tests
+}
+
+fn render(path: &Path) -> Canvas {
+    let source = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("reading {}: {}", path.display(), err));
+    let document = parse_html(&source);
+    let tree = LayoutTree::build(
+        &document,
+        LayoutOptions {
+            viewport_width: WIDTH as f32,
+            ..LayoutOptions::default()
+        },
+    );
+    let mut canvas = Canvas::new(WIDTH, HEIGHT, Color::WHITE);
+    canvas.paint(&DisplayList::build(&tree), None, 0.0);
+    canvas
+}
+
+fn reference(path: &Path, test: &Canvas) -> Canvas {
+    if path.extension().is_some_and(|ext| ext == "png") {
+        if std::env::var_os("UPDATE_REFTESTS").is_some() {
+            std::fs::write(path, test.to_png().unwrap()).unwrap();
+        }
+        let bytes = std::fs::read(path).unwrap_or_else(|err| {
+            panic!(
+                "reading {}: {} (run with UPDATE_REFTESTS=1 to create it)",
+                path.display(),
+                err
+            )
+        });
+        Canvas::from_png(&bytes).unwrap()
+    } else {
+        render(path)
+    }
+}
+
+fn compare(a: &Canvas, b: &Canvas) -> (u8, usize) {
+    if (a.width, a.height) != (b.width, b.height) {
+        return (u8::MAX, usize::MAX);
+    }
+    let mut max_difference = 0;
+    let mut differing = 0;
+    for (a, b) in a.pixels.iter().zip(&b.pixels) {
+        let difference = [16, 8, 0]
+            .iter()
+            .map(|shift| ((a >> shift) as u8).abs_diff((b >> shift) as u8))
+            .max()
+            .unwrap_or_default();
+        if difference > 0 {
+            differing += 1;
+            max_difference = max_difference.max(difference);
+        }
+    }
+    (max_difference, differing)
+}
+
+#[test]
+fn reftests() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(DIRECTORY);
+    let manifest = std::fs::read_to_string(directory.join("reftest.list")).unwrap();
+
+    let mut failures = Vec::new();
+    let mut failed = 0;
+    let tests = parse_manifest(&manifest);
+    for test in &tests {
+        let actual = render(&directory.join(&test.test));
+        let expected = reference(&directory.join(&test.reference), &actual);
+        let (difference, pixels) = compare(&actual, &expected);
+        let matches =
+            pixels == 0 || (difference <= test.max_difference && pixels <= test.max_pixels);
+
+        if matches != (test.relation == Relation::Equal) {
+            failed += 1;
+            failures.push(format!(
+                "{} {} {}: {} pixels differ, max channel difference {}",
+                test.test,
+                if test.relation == Relation::Equal {
+                    "=="
+                } else {
+                    "!="
+                },
+                test.reference,
+                pixels,
+                difference
+            ));
+            if let Ok(png) = actual.to_png() {
+                let output = std::env::temp_dir().join(test.test.replace(".html", "-actual.png"));
+                if std::fs::write(&output, png).is_ok() {
+                    failures.push(format!(
+                        "  actual rendering written to {}",
+                        output.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    eprintln!("reftests: {} run, {} failed", tests.len(), failed);
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+
+const PAGE: &str = "<div id=a><p id=first>one</p><p id=second>two</p></div>
+    <div id=b style='padding: 4px'><p>three</p><p id=last>four</p></div>";
+
+fn options(viewport_width: f32) -> LayoutOptions {
+    LayoutOptions {
+        viewport_width,
+        ..LayoutOptions::default()
+    }
+}
+
+fn assert_relayout_matches(document: &Document, tree: &mut LayoutTree, width: f32) {
+    tree.update(document, options(width));
+    assert_eq!(
+        tree.dump(),
+        LayoutTree::build(document, options(width)).dump()
+    );
+}
+
+#[test]
+fn mutations_mark_the_node_and_its_ancestors() {
+    let document = parse_html(PAGE);
+    LayoutTree::build(&document, options(400.0));
+    let first = document.get_element_by_id("first").unwrap();
+    let a = document.get_element_by_id("a").unwrap();
+    let b = document.get_element_by_id("b").unwrap();
+    assert!(first.layout_dirty.get().is_clean());
+
+    Node::set_attribute(&first, "title", "x");
+    assert!(first.layout_dirty.get().this);
+    assert!(a.layout_dirty.get().descendants && !a.layout_dirty.get().this);
+    assert!(document.root.layout_dirty.get().descendants);
+    assert!(b.layout_dirty.get().is_clean());
+
+    LayoutTree::build(&document, options(400.0));
+    assert!(document.root.layout_dirty.get().is_clean());
+    assert!(first.layout_dirty.get().is_clean());
+}
+
+#[test]
+fn updated_layout_matches_a_fresh_build() {
+    let document = parse_html(PAGE);
+    let mut tree = LayoutTree::build(&document, options(400.0));
+    assert_relayout_matches(&document, &mut tree, 400.0);
+
+    let first = document.get_element_by_id("first").unwrap();
+    let text = Rc::clone(&first.children.borrow()[0]);
+    Node::set_character_data(&text, &"a much longer paragraph ".repeat(20));
+    assert_relayout_matches(&document, &mut tree, 400.0);
+
+    let second = document.get_element_by_id("second").unwrap();
+    Node::set_attribute(&second, "style", "margin-top: 40px");
+    assert_relayout_matches(&document, &mut tree, 400.0);
+
+    let a = document.get_element_by_id("a").unwrap();
+    Node::detach(&second);
+    Node::append_child(&document.get_element_by_id("b").unwrap(), second);
+    assert!(!a.layout_dirty.get().is_clean());
+    assert_relayout_matches(&document, &mut tree, 400.0);
+
+    assert_relayout_matches(&document, &mut tree, 250.0);
+}
@@ -0,0 +1,73 @@
+use icarus::dom::Document;
+use icarus::loader::{self, Load};
+use icarus::settings::Settings;
+use url::Url;
+
+mod common;
+
+fn serve(responses: Vec<Vec<u8>>) -> Url {
+    let port = common::serve(responses);
+    Url::parse(&format!("http://127.0.0.1:{}/start", port)).unwrap()
+}
+
+fn load(url: &Url) -> Document {
+    match loader::load_with(url.as_str(), &Settings::default()).unwrap() {
+        Load::Document(document) => document,
+        Load::Download(_) => panic!("expected a document"),
+    }
+}
+
+#[test]
+fn document_keeps_the_final_response() {
+    let mut page = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=ISO-8859-1\r\n\
+        Set-Cookie: a=1\r\nx-served-by: test\r\nSet-Cookie: b=2\r\nContent-Length: 10\r\n\r\n<p>caf"
+        .to_vec();
+    page.extend_from_slice(b"\xe9</p>");
+    let url = serve(vec![
+        b"HTTP/1.1 302 Found\r\nLocation: /final\r\nContent-Length: 0\r\n\r\n".to_vec(),
+        page,
+    ]);
+    let document = load(&url);
+
+    let info = document.response.as_ref().unwrap();
+    assert_eq!(document.url().unwrap().path(), "/final");
+    assert_eq!(info.status, 200);
+    assert!(info.is_success());
+    assert_eq!(document.header("X-Served-By"), Some("test"));
+    assert_eq!(
+        info.header_all("set-cookie").collect::<Vec<_>>(),
+        ["a=1", "b=2"]
+    );
+    assert_eq!(info.content_type().as_deref(), Some("text/html"));
+    assert_eq!(info.charset().as_deref(), Some("ISO-8859-1"));
+    assert_eq!(
+        document
+            .query_selector("p")
+            .unwrap()
+            .unwrap()
+            .get_text_content(),
+        "café"
+    );
+
+    let timing = info.timing;
+    assert!(timing.fetch_start <= timing.response_start);
+    assert!(
+        timing
+            .response_end
+            .is_some_and(|end| end >= timing.response_start)
+    );
+    assert!(timing.total().unwrap() >= timing.time_to_first_byte());
+}
+
+#[test]
+fn error_pages_are_still_documents() {
+    let url = serve(vec![
+        b"HTTP/1.1 404 Not Found\r\nContent-Type: text/html\r\nContent-Length: 11\r\n\r\n<p>gone</p>"
+            .to_vec(),
+    ]);
+    let document = load(&url);
+    let info = document.response.as_ref().unwrap();
+    assert_eq!(info.status, 404);
+    assert!(!info.is_success());
+    assert_eq!(document.url(), Some(&url));
+}
@@ -0,0 +1,96 @@
+use std::rc::Rc;
+
+use icarus::css::{Origin, Stylesheet};
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::rule_map::RuleMap;
+
+fn candidate_rules(map: &RuleMap, element: &Rc<Node>) -> Vec<usize> {
+    map.candidates(element)
+        .iter()
+        .map(|entry| entry.rule)
+        .collect()
+}
+
+#[test]
+fn selectors_land_in_their_most_specific_bucket() {
+    let sheet = Stylesheet::parse(
+        "p.note#lead { color: red }
+         p.note { color: red }
+         p { color: red }
+         * { color: red }
+         [title] { color: red }",
+        Origin::Author,
+    );
+    let map = RuleMap::new(&[sheet]);
+    let document = parse_html(
+        "<p id=lead class=note>a</p><p class=note>b</p><p>c</p><div id=lead>d</div><span>e</span>",
+    );
+    let paragraphs = document.get_elements_by_tag_name("p");
+    assert_eq!(candidate_rules(&map, &paragraphs[0]), [0, 1, 2, 3, 4]);
+    assert_eq!(candidate_rules(&map, &paragraphs[1]), [1, 2, 3, 4]);
+    assert_eq!(candidate_rules(&map, &paragraphs[2]), [2, 3, 4]);
+
+    let div = document.get_elements_by_tag_name("div")[0].clone();
+    assert_eq!(candidate_rules(&map, &div), [0, 3, 4]);
+    let span = document.get_elements_by_tag_name("span")[0].clone();
+    assert_eq!(candidate_rules(&map, &span), [3, 4]);
+}
+
+#[test]
+fn mixed_case_tags_fall_back_to_the_lowercase_bucket() {
+    let sheet = Stylesheet::parse(
+        "foreignObject { display: block } lineargradient { display: none }",
+        Origin::Author,
+    );
+    let map = RuleMap::new(&[sheet]);
+    let document =
+        parse_html("<svg><foreignObject></foreignObject><linearGradient></linearGradient></svg>");
+    let foreign = document.get_elements_by_tag_name("foreignObject")[0].clone();
+    assert_eq!(foreign.element_name(), Some("foreignObject"));
+    assert_eq!(candidate_rules(&map, &foreign), [0]);
+    let gradient = document.get_elements_by_tag_name("linearGradient")[0].clone();
+    assert_eq!(candidate_rules(&map, &gradient), [1]);
+}
+
+#[test]
+fn merged_buckets_keep_source_order() {
+    let author = Stylesheet::parse(
+        ".b { color: red }
+         p { color: red }
+         #x { color: red }
+         * { color: red }
+         .a, p, #x { color: red }
+         .a.b { color: red }",
+        Origin::Author,
+    );
+    let second = Stylesheet::parse("p { color: blue }", Origin::Author);
+    let map = RuleMap::new(&[author, second]);
+    let document = parse_html("<p id=x class='a b a'>text</p>");
+    let p = document.get_element_by_id("x").unwrap();
+
+    let entries = map.candidates(&p);
+    let order: Vec<(usize, usize, usize)> = entries
+        .iter()
+        .map(|entry| (entry.sheet, entry.rule, entry.selector))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            (0, 0, 0),
+            (0, 1, 0),
+            (0, 2, 0),
+            (0, 3, 0),
+            (0, 4, 0),
+            (0, 4, 1),
+            (0, 4, 2),
+            (0, 5, 0),
+            (1, 0, 0),
+        ]
+    );
+    assert!(
+        entries
+            .windows(2)
+            .all(|pair| pair[0].order <= pair[1].order)
+    );
+}
@@ -0,0 +1,65 @@
+use icarus::sanitize;
+use icarus::sanitize::Policy;
+
+#[test]
+fn removes_scripts_handlers_and_unsafe_urls() {
+    let policy = Policy::default();
+    assert_eq!(
+        sanitize(
+            r#"<p onclick="steal()" class="x" title="t">Hi <script>alert(1)</script><b>there</b></p>"#,
+            &policy
+        ),
+        r#"<p title="t">Hi <b>there</b></p>"#
+    );
+    assert_eq!(
+        sanitize(
+            r#"<a href="jav&#x09;ascript:alert(1)">x</a><a href="/ok?a=b:c">y</a><a href="HTTPS://e.com">z</a>"#,
+            &policy
+        ),
+        r#"<a>x</a><a href="/ok?a=b:c">y</a><a href="HTTPS://e.com">z</a>"#
+    );
+    assert_eq!(
+        sanitize(
+            "<img src=x onerror=alert(1)><svg><script>x</script></svg><!-- note -->",
+            &policy
+        ),
+        r#"<img src="x">"#
+    );
+}
+
+#[test]
+fn unwraps_disallowed_elements_but_keeps_text() {
+    let policy = Policy::default();
+    assert_eq!(
+        sanitize(
+            "<form><label>Name <input name=n></label></form><custom-tag>kept</custom-tag>",
+            &policy
+        ),
+        "Name kept"
+    );
+    assert_eq!(
+        sanitize("<p>a &lt;b&gt; &amp; c</p><style>p{}</style>", &policy),
+        "<p>a &lt;b&gt; &amp; c</p>"
+    );
+}
+
+#[test]
+fn policies_are_configurable() {
+    let policy = Policy::text_only();
+    assert_eq!(sanitize("<h1>Title</h1><p>Body</p>", &policy), "TitleBody");
+
+    let policy = Policy::default()
+        .deny_elements(&["img"])
+        .allow_elements(&["iframe"])
+        .allow_attributes("iframe", &["src"])
+        .allow_attributes("*", &["class"])
+        .allow_url_schemes(&["data"])
+        .allow_comments(true);
+    assert_eq!(
+        sanitize(
+            r#"<p class="lead">x<img src="a.png"></p><iframe src="data:text/html,hi"></iframe><!--c-->"#,
+            &policy
+        ),
+        r#"<p class="lead">x</p><iframe src="data:text/html,hi"></iframe><!--c-->"#
+    );
+}
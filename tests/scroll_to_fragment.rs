@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::layout::Rect;
+use icarus::location::Navigation;
+use icarus::paint::DisplayItem;
+use icarus::settings::Settings;
+use icarus::shell::pipeline::{Command, Event};
+use icarus::shell::tab::Tab;
+use icarus::storage::StorageContext;
+
+const VIEWPORT: Rect = Rect {
+    x: 0.0,
+    y: 0.0,
+    width: 400.0,
+    height: 200.0,
+};
+
+const PAGE: &str = "<body style='margin: 0'><p><a href=#end>jump</a></p>
+    <div style='height: 1000px'></div>
+    <h2 id=end>End</h2><p><a name=named>named</a></p>";
+
+fn next<T>(tab: &Tab, mut pick: impl FnMut(Event) -> Option<T>) -> T {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        for event in tab.pipeline.poll().unwrap() {
+            if let Some(value) = pick(event) {
+                return value;
+            }
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for the page");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn scroll_to(event: Event) -> Option<f32> {
+    match event {
+        Event::ScrollTo(y) => Some(y),
+        _ => None,
+    }
+}
+
+#[test]
+fn fragments_scroll_on_load_and_on_in_page_clicks() {
+    let path = std::env::temp_dir().join(format!("icarus-fragment-{}.html", std::process::id()));
+    std::fs::write(&path, PAGE).unwrap();
+    let url = url::Url::from_file_path(&path).unwrap();
+    let settings = Arc::new(Settings::default());
+
+    let tab = Tab::open(
+        &format!("{}#end", url),
+        VIEWPORT,
+        Arc::clone(&settings),
+        StorageContext::default(),
+    )
+    .unwrap();
+    let on_load = next(&tab, scroll_to);
+    assert!(on_load >= 1000.0, "{}", on_load);
+
+    let mut tab = Tab::open(url.as_str(), VIEWPORT, settings, StorageContext::default()).unwrap();
+    let link = next(&tab, |event| match event {
+        Event::Frame { display_list, .. } => {
+            display_list.items.iter().find_map(|item| match item {
+                DisplayItem::Text { rect, text, .. } if text == "jump" => Some(*rect),
+                _ => None,
+            })
+        }
+        _ => None,
+    });
+    std::fs::remove_file(&path).unwrap();
+
+    tab.pipeline.send(Command::Click {
+        x: link.x + 1.0,
+        y: link.y + 1.0,
+    });
+    let href = next(&tab, |event| match event {
+        Event::Link(url) => Some(url),
+        _ => None,
+    });
+    assert_eq!(href, format!("{}#end", url));
+
+    let navigation = tab.location().unwrap().assign(&href).unwrap();
+    assert!(matches!(navigation, Navigation::Fragment { ref fragment, .. } if fragment == "end"));
+    tab.follow(navigation, VIEWPORT).unwrap();
+    assert_eq!(next(&tab, scroll_to), on_load);
+    assert_eq!(tab.url(), href);
+    assert_eq!(tab.history().len(), 2);
+
+    tab.pipeline
+        .send(Command::ScrollToFragment(String::from("named")));
+    assert!(next(&tab, scroll_to) > on_load);
+    tab.pipeline
+        .send(Command::ScrollToFragment(String::from("top")));
+    assert_eq!(next(&tab, scroll_to), 0.0);
+}
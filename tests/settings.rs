@@ -0,0 +1,109 @@
+use icarus::css::media::{ColorScheme, MediaFeatures, MediaQueryList, MediaType, ReducedMotion};
+use icarus::css::values::Color;
+use icarus::image::Loading;
+use icarus::settings::{ColorSettings, Settings};
+use std::path::PathBuf;
+
+#[test]
+fn config_file_overrides_defaults() {
+    let settings = Settings::from_toml(
+        r##"
+user-agent = "Test/1.0"
+javascript = false
+images = "lazy"
+proxy = "http://localhost:3128"
+
+[fonts]
+size = 18
+fallbacks = ["/opt/fonts/NotoSansCJK-Regular.ttc"]
+
+[colors]
+background = "#000"
+link = "red"
+"##,
+    )
+    .unwrap();
+    assert_eq!(settings.user_agent, "Test/1.0");
+    assert!(!settings.javascript);
+    assert!(!settings.parse_options().scripting);
+    assert_eq!(settings.images, Loading::Lazy);
+    assert_eq!(settings.proxy.unwrap().port(), Some(3128));
+    assert_eq!(settings.fonts.size, 18.0);
+    assert_eq!(
+        settings.fonts.fallbacks,
+        [PathBuf::from("/opt/fonts/NotoSansCJK-Regular.ttc")]
+    );
+    assert_eq!(settings.colors.text, Color::BLACK);
+    assert_eq!(settings.colors.background, Color::BLACK);
+    assert_eq!(settings.colors.link, Some(Color::rgb(255, 0, 0)));
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    assert!(Settings::from_toml("javascript = \"yes\"").is_err());
+    assert!(Settings::from_toml("images = \"sometimes\"").is_err());
+    assert!(Settings::from_toml("proxy = \"socks5://localhost\"").is_err());
+    assert!(Settings::from_toml("[colors]\ntext = \"not a color\"").is_err());
+    assert!(Settings::from_toml("[fonts]\nfallbacks = \"font.ttf\"").is_err());
+}
+
+#[test]
+fn command_line_flags_are_consumed() {
+    let mut args: Vec<String> = ["--no-script", "view", "--user-agent", "Cli/2", "page.html"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let mut settings = Settings::default();
+    settings.apply_args(&mut args).unwrap();
+    assert_eq!(args, ["view", "page.html"]);
+    assert!(!settings.javascript);
+    assert_eq!(settings.user_agent, "Cli/2");
+    assert!(
+        settings
+            .apply_args(&mut vec![String::from("--proxy")])
+            .is_err()
+    );
+}
+
+#[test]
+fn dark_scheme_keeps_custom_colors() {
+    let mut settings =
+        Settings::from_toml("color-scheme = \"dark\"\n[colors]\ntext = \"yellow\"").unwrap();
+    let palette = settings.palette();
+    assert_eq!(palette.text, Color::rgb(255, 255, 0));
+    assert_eq!(palette.background, ColorSettings::dark().background);
+
+    settings.color_scheme = ColorScheme::Light;
+    assert_eq!(settings.palette().background, Color::WHITE);
+}
+
+#[test]
+fn media_queries_follow_the_color_scheme() {
+    let dark = MediaFeatures {
+        media_type: MediaType::Screen,
+        color_scheme: ColorScheme::Dark,
+        reduced_motion: ReducedMotion::NoPreference,
+        scripting: true,
+    };
+    let light = MediaFeatures {
+        media_type: MediaType::Screen,
+        color_scheme: ColorScheme::Light,
+        reduced_motion: ReducedMotion::NoPreference,
+        scripting: true,
+    };
+    let query = MediaQueryList::parse("screen and (prefers-color-scheme: dark)");
+    assert!(query.matches(&dark));
+    assert!(!query.matches(&light));
+    assert!(MediaQueryList::parse("print, (prefers-color-scheme: light)").matches(&light));
+    assert!(MediaQueryList::parse("not print").matches(&light));
+    assert!(!MediaQueryList::parse("(min-resolution: 2dppx)").matches(&light));
+}
+
+#[test]
+fn color_scheme_defaults_to_the_system() {
+    assert_eq!(Settings::default().color_scheme, ColorScheme::System);
+    assert_eq!(
+        Settings::from_toml("").unwrap().color_scheme,
+        Settings::default().color_scheme
+    );
+}
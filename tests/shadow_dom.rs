@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use icarus::css::values::Color;
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::style::style_of;
+
+mod common;
+
+use common::find;
+
+const PAGE: &str = "<style>p { color: blue } .slotted { font-size: 20px }</style>\
+    <div id=host><template shadowrootmode=open>\
+    <style>p { color: red } span { color: green }</style>\
+    <p id=inner>shadow</p><section id=wrapper style='font-size: 30px'><slot></slot></section>\
+    <slot name=named><em id=fallback>fallback</em></slot>\
+    </template><span id=light class=slotted>light</span></div><p id=outside>outside</p>";
+
+fn shadow_element(document: &Document, id: &str) -> Rc<Node> {
+    let host = document.get_element_by_id("host").unwrap();
+    let shadow_root = host.shadow_root.borrow().clone().unwrap();
+    let mut found = None;
+    shadow_root.walk_rc(&mut |node| {
+        if node.get_attribute("id").as_deref() == Some(id) {
+            found = Some(Rc::clone(node));
+        }
+    });
+    found.expect("shadow element")
+}
+
+#[test]
+fn shadow_styles_are_scoped_to_their_tree() {
+    let document = parse_html(PAGE);
+    document.restyle();
+
+    let inner = shadow_element(&document, "inner");
+    assert_eq!(style_of(&inner).color, Color::parse("red").unwrap());
+    let outside = document.get_element_by_id("outside").unwrap();
+    assert_eq!(style_of(&outside).color, Color::parse("blue").unwrap());
+
+    let light = document.get_element_by_id("light").unwrap();
+    let style = style_of(&light);
+    assert_ne!(style.color, Color::parse("green").unwrap());
+    assert_eq!(style.font_size, 20.0);
+}
+
+#[test]
+fn slotted_content_inherits_from_its_slot() {
+    let document = parse_html(
+        "<div id=host><template shadowrootmode=open>\
+         <section style='color: purple'><slot></slot></section></template>\
+         <span id=light>light</span></div>",
+    );
+    document.restyle();
+    let light = document.get_element_by_id("light").unwrap();
+    assert_eq!(style_of(&light).color, Color::parse("purple").unwrap());
+}
+
+#[test]
+fn the_flattened_tree_is_laid_out() {
+    let document = parse_html(PAGE);
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+
+    let host = find(&tree.root, "host").expect("host box");
+    assert!(find(host, "inner").is_some());
+    let wrapper = find(host, "wrapper").expect("wrapper box");
+    let text = tree.dump();
+    assert!(text.contains("shadow"), "{}", text);
+    assert!(text.contains("light"), "{}", text);
+    assert!(text.contains("fallback"), "{}", text);
+    assert!(wrapper.rect.height > 0.0);
+
+    let light = document.get_element_by_id("light").unwrap();
+    let rect = tree.rect_of(&light).expect("slotted content is laid out");
+    assert!(rect.y >= wrapper.rect.y && rect.bottom() <= wrapper.rect.bottom());
+}
+
+#[test]
+fn changes_inside_a_shadow_tree_are_restyled() {
+    let document = parse_html(PAGE);
+    document.restyle();
+    let inner = shadow_element(&document, "inner");
+    Node::set_attribute(&inner, "style", "color: orange");
+    document.restyle();
+    assert_eq!(style_of(&inner).color, Color::parse("orange").unwrap());
+}
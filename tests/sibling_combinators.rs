@@ -0,0 +1,62 @@
+use icarus::css::selector::parse_selector;
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+mod common;
+
+use common::ids;
+
+const PAGE: &str = "<div><h2 id=h>Title</h2>text<!-- c --><p id=p1>1</p><p id=p2 class=x>2</p>
+    <span id=s>3</span><p id=p3>4</p></div><section><p id=p4>5</p></section>";
+
+#[test]
+fn adjacent_siblings_skip_text_and_comments() {
+    assert_eq!(ids(PAGE, "h2 + p"), ["p1"]);
+    assert_eq!(ids(PAGE, "p+p"), ["p2"]);
+    assert_eq!(ids(PAGE, ".x + span + p"), ["p3"]);
+    assert!(ids(PAGE, "h2 + span").is_empty());
+}
+
+#[test]
+fn general_siblings_follow_in_document_order() {
+    assert_eq!(ids(PAGE, "h2 ~ p"), ["p1", "p2", "p3"]);
+    assert_eq!(ids(PAGE, ".x ~ *"), ["s", "p3"]);
+    assert_eq!(ids(PAGE, "div > h2 ~ p:last-child"), ["p3"]);
+    assert_eq!(ids(PAGE, "div ~ section p"), ["p4"]);
+    assert!(ids(PAGE, "section ~ div").is_empty());
+}
+
+#[test]
+fn dangling_combinators_are_rejected() {
+    assert!(parse_selector("+ p").is_none());
+    assert!(parse_selector("h2 ~").is_none());
+    assert!(parse_selector("h2 + > p").is_none());
+    assert!(parse_selector("h2 ~ + p").is_none());
+    assert!(parse_selector("h2 + p").is_some());
+}
+
+#[test]
+fn sibling_styles_follow_dom_and_attribute_changes() {
+    let document = parse_html(
+        "<style>.open + dd { display: none } dt ~ dd.last { color: red }</style>
+         <dl><dt id=term>a</dt><dd id=first>b</dd><dd id=last class=last>c</dd></dl>",
+    );
+    document.restyle();
+    let map = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()
+    };
+    assert_eq!(map("#first")["display"], "block");
+    assert_eq!(map("#last")["color"], "#ff0000");
+
+    let term = document.query_selector("#term").unwrap().unwrap();
+    Node::set_attribute(&term, "class", "open");
+    document.restyle();
+    assert_eq!(map("#first")["display"], "none");
+
+    Node::detach(&term);
+    document.restyle();
+    assert_eq!(map("#first")["display"], "block");
+    assert_eq!(map("#last")["color"], "#000000");
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::image::Image;
+use icarus::layout::inline::FragmentKind;
+use icarus::layout::{LayoutBox, LayoutOptions, LayoutTree, Rect};
+
+mod common;
+
+use common::{find, layout};
+
+fn rect(html: &str, id: &str) -> Rect {
+    let tree = layout(html);
+    find(&tree.root, id).expect("box").rect
+}
+
+fn image_rect(layout_box: &LayoutBox) -> Option<Rect> {
+    layout_box
+        .lines
+        .iter()
+        .flat_map(|line| &line.fragments)
+        .find(|fragment| matches!(fragment.kind, FragmentKind::Image { .. }))
+        .map(|fragment| fragment.rect)
+        .or_else(|| layout_box.children.iter().find_map(image_rect))
+}
+
+#[test]
+fn widths_are_clamped() {
+    let width = |style: &str| {
+        let html = format!(
+            "<body style='margin: 0'><div id=a style='{}'>x</div>",
+            style
+        );
+        rect(&html, "a").width
+    };
+    assert_eq!(width("max-width: 300px"), 300.0);
+    assert_eq!(width("max-width: 25%"), 200.0);
+    assert_eq!(width("width: 50px; min-width: 120px"), 120.0);
+    assert_eq!(width("max-width: 100px; min-width: 150px"), 150.0);
+    assert_eq!(width("max-width: none"), 800.0);
+}
+
+#[test]
+fn heights_are_clamped() {
+    assert_eq!(
+        rect("<div id=a style='min-height: 100px'>x</div>", "a").height,
+        100.0
+    );
+    assert_eq!(
+        rect("<div id=a style='max-height: 30px'>x<br>y<br>z</div>", "a").height,
+        30.0
+    );
+    assert_eq!(
+        rect(
+            "<div id=a style='height: 10px; min-height: 20px'>x</div>",
+            "a"
+        )
+        .height,
+        20.0
+    );
+}
+
+#[test]
+fn images_shrink_to_max_width_keeping_their_ratio() {
+    let document = parse_html(
+        "<body style='margin: 0'><div style='width: 200px'>\
+         <img style='max-width: 100%'></div></body>",
+    );
+    let img = document.query_selector("img").unwrap().unwrap();
+    *img.image.borrow_mut() = Some(Arc::new(Image {
+        width: 400,
+        height: 100,
+        pixels: vec![Color::BLACK; 400 * 100],
+    }));
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let image = image_rect(&tree.root).expect("image fragment");
+    assert_eq!((image.width, image.height), (200.0, 50.0));
+}
+
+#[test]
+fn border_box_sizes_include_padding_and_border() {
+    let html = "<body style='margin: 0'>
+        <style>.b { box-sizing: border-box }</style>
+        <div id=a class=b style='width: 200px; height: 50px; padding: 10px; border: 5px solid'>x</div>
+        <div id=b style='width: 200px; height: 50px; padding: 10px; border: 5px solid'>y</div>
+        <div id=c class=b style='max-width: 100px; padding: 0 20px'>z</div>
+        <div id=d class=b style='width: 10px; padding: 0 20px'>w</div>";
+    let tree = layout(html);
+    let size = |id| {
+        let rect = find(&tree.root, id).expect("box").rect;
+        (rect.width, rect.height)
+    };
+    assert_eq!(size("a"), (170.0, 20.0));
+    assert_eq!(size("b"), (200.0, 50.0));
+    assert_eq!(size("c").0, 60.0);
+    assert_eq!(size("d").0, 0.0);
+    let a = find(&tree.root, "a").expect("box");
+    assert_eq!(a.border_box().width, 200.0);
+}
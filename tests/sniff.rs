@@ -0,0 +1,44 @@
+use icarus::net::sniff::sniff;
+
+#[test]
+fn missing_type_is_sniffed_from_content() {
+    assert_eq!(sniff(None, false, b"  <!DOCTYPE html><p>hi"), "text/html");
+    assert_eq!(sniff(None, false, b"\x89PNG\r\n\x1a\n...."), "image/png");
+    assert_eq!(sniff(None, false, b"%PDF-1.7"), "application/pdf");
+    assert_eq!(sniff(None, false, b"just some words"), "text/plain");
+    assert_eq!(
+        sniff(None, false, b"\x00\x01\x02"),
+        "application/octet-stream"
+    );
+    assert_eq!(sniff(Some("*/*"), false, b"<html>"), "text/html");
+}
+
+#[test]
+fn mislabeled_types_are_corrected() {
+    assert_eq!(
+        sniff(Some("text/plain"), false, b"<html><body>"),
+        "text/html"
+    );
+    assert_eq!(
+        sniff(Some("text/plain"), false, b"GIF89a\x01\x00"),
+        "image/gif"
+    );
+    assert_eq!(
+        sniff(Some("image/png"), false, b"\xff\xd8\xff\xe0"),
+        "image/jpeg"
+    );
+    assert_eq!(
+        sniff(Some("text/plain; charset=utf-16"), false, b"<html>"),
+        "text/plain"
+    );
+    assert_eq!(sniff(Some("text/css"), false, b"<html>"), "text/css");
+}
+
+#[test]
+fn no_sniff_keeps_the_supplied_type() {
+    assert_eq!(
+        sniff(Some("text/plain"), true, b"<html><body>"),
+        "text/plain"
+    );
+    assert_eq!(sniff(None, true, b"<html><body>"), "text/plain");
+}
@@ -0,0 +1,76 @@
+use icarus::loader::{self, Load};
+use icarus::paint::DisplayItem;
+use icarus::settings::Settings;
+use icarus::source::{self, TokenKind};
+
+#[test]
+fn tokens_cover_the_source_exactly() {
+    let html = "<!DOCTYPE html>\n<!-- hi --><a href=\"x\" data-on>Tom &amp; Jerry</a><script>if (a<b) {}</script>";
+    let tokens = source::tokenize(html);
+    assert_eq!(
+        tokens.iter().map(|(_, text)| *text).collect::<String>(),
+        html
+    );
+
+    let kind_of = |needle: &str| {
+        tokens
+            .iter()
+            .find(|(_, text)| text.contains(needle))
+            .map(|(kind, _)| *kind)
+    };
+    assert_eq!(kind_of("DOCTYPE"), Some(TokenKind::Doctype));
+    assert_eq!(kind_of("hi"), Some(TokenKind::Comment));
+    assert_eq!(kind_of("href"), Some(TokenKind::AttributeName));
+    assert_eq!(kind_of("\"x\""), Some(TokenKind::AttributeValue));
+    assert_eq!(kind_of("&amp;"), Some(TokenKind::Entity));
+    assert_eq!(kind_of("Tom"), Some(TokenKind::Text));
+    assert_eq!(kind_of("a<b"), Some(TokenKind::Text));
+}
+
+#[test]
+fn ansi_output_has_line_numbers_and_colors() {
+    let html = "<p>\n".repeat(9) + "<b>x</b>\n";
+    let plain = source::to_ansi(&html, false);
+    let lines: Vec<&str> = plain.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], " 1  <p>");
+    assert_eq!(lines[9], "10  <b>x</b>");
+
+    let colored = source::to_ansi("<b>x</b>", true);
+    assert_eq!(
+        colored,
+        "\x1b[90m1\x1b[0m  \x1b[35m<b>\x1b[0mx\x1b[35m</b>\x1b[0m\n"
+    );
+}
+
+#[test]
+fn display_list_puts_each_line_below_the_last() {
+    let (list, height) = source::display_list("<p>\n\tone</p>\n", 16.0);
+    let items: Vec<(f32, String)> = list
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { rect, text, .. } => Some((rect.y, text.clone())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(items[0].1, "1");
+    assert!(items.iter().any(|(_, text)| text == "    one"));
+    let second = items.iter().find(|(_, text)| text == "2").unwrap().0;
+    assert!(second > items[0].0);
+    assert!(height > second);
+}
+
+#[test]
+fn loaded_documents_keep_the_fetched_source() {
+    let html = "<!doctype html><title>t</title>\n<p class=a>hello";
+    let path = std::env::temp_dir().join(format!("icarus-source-{}.html", std::process::id()));
+    std::fs::write(&path, html).unwrap();
+    let url = url::Url::from_file_path(&path).unwrap();
+    let Load::Document(document) = loader::load_with(url.as_str(), &Settings::default()).unwrap()
+    else {
+        panic!("expected a document");
+    };
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(document.source.as_deref(), Some(html));
+}
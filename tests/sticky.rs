@@ -0,0 +1,68 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree, Rect};
+use icarus::paint::DisplayList;
+use icarus::paint::raster::Canvas;
+use icarus::style::{Position, style_of};
+
+const PAGE: &str = "<body style='margin: 0'>
+    <div id=section style='height: 300px'>
+      <div id=header style='position: sticky; top: 0; height: 20px; background: red'></div>
+      <div style='height: 100px; background: blue'></div>
+    </div>
+    <div id=footer style='position: -webkit-sticky; bottom: 10px; height: 20px; background: lime'></div>
+    <div style='height: 1000px'></div></body>";
+
+fn build() -> DisplayList {
+    let tree = LayoutTree::build(&parse_html(PAGE), LayoutOptions::default());
+    DisplayList::build(&tree)
+}
+
+#[test]
+fn sticky_position_and_insets_are_parsed() {
+    let document = parse_html(PAGE);
+    document.restyle();
+    let header = document.query_selector("#header").unwrap().unwrap();
+    assert_eq!(style_of(&header).position, Position::Sticky);
+    let map = style_of(&header).to_map();
+    assert_eq!(
+        (map["top"].as_str(), map["bottom"].as_str()),
+        ("0px", "auto")
+    );
+    let section = document.query_selector("#section").unwrap().unwrap();
+    assert_eq!(style_of(&section).to_map()["position"], "static");
+}
+
+#[test]
+fn sticky_boxes_follow_the_scroll_within_their_container() {
+    let list = build();
+    assert_eq!(list.sticky.len(), 2);
+    let header = &list.sticky[0];
+    assert_eq!(header.rect, Rect::new(0.0, 0.0, 800.0, 20.0));
+    assert_eq!(header.offset(0.0, 100.0), 0.0);
+    assert_eq!(header.offset(150.0, 100.0), 150.0);
+    assert_eq!(header.offset(500.0, 100.0), 280.0);
+
+    let footer = &list.sticky[1];
+    assert_eq!(footer.offset(0.0, 100.0), -230.0);
+    assert_eq!(footer.offset(250.0, 100.0), 0.0);
+    assert_eq!(footer.offset(0.0, 10.0), -300.0);
+}
+
+#[test]
+fn sticky_boxes_paint_above_later_content() {
+    let list = build();
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&list, None, 150.0);
+    let pixel = |x: usize, y: usize| canvas.pixels[y * 100 + x] & 0xffffff;
+    assert_eq!(pixel(5, 5), 0xff0000);
+    assert_eq!(pixel(5, 75), 0x00ff00);
+    assert_eq!(pixel(5, 95), 0xffffff);
+
+    let mut canvas = Canvas::new(100, 100, Color::WHITE);
+    canvas.paint(&list, None, 10.0);
+    assert_eq!(canvas.pixels[15 * 100 + 5] & 0xffffff, 0xff0000);
+
+    let bounds = list.sticky_bounds(150.0, 100.0);
+    assert_eq!(bounds[0], Rect::new(0.0, 0.0, 800.0, 20.0));
+}
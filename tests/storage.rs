@@ -0,0 +1,112 @@
+use icarus::loader::{self, Load};
+use icarus::settings::Settings;
+use icarus::storage::{LocalStorage, QUOTA, SessionStorage, StorageContext, origin_of};
+use url::Url;
+
+#[test]
+fn areas_are_partitioned_by_origin() {
+    let mut storage = SessionStorage::new();
+    let a = Url::parse("http://example.com/a").unwrap();
+    let b = Url::parse("http://example.com:8080/b").unwrap();
+    storage.area(&a).unwrap().set_item("k", "one").unwrap();
+    storage.area(&b).unwrap().set_item("k", "two").unwrap();
+    assert_eq!(storage.area(&a).unwrap().get_item("k"), Some("one"));
+    assert_eq!(storage.area(&b).unwrap().get_item("k"), Some("two"));
+    assert_eq!(
+        origin_of(&Url::parse("http://example.com/other/page").unwrap()).as_deref(),
+        Some("http://example.com")
+    );
+    assert!(
+        storage
+            .area(&Url::parse("data:text/html,hi").unwrap())
+            .is_err()
+    );
+}
+
+#[test]
+fn quota_is_enforced() {
+    let mut storage = SessionStorage::new();
+    let area = storage
+        .area(&Url::parse("http://example.com/").unwrap())
+        .unwrap();
+    let big = "x".repeat(QUOTA / 2 - 8);
+    area.set_item("a", &big).unwrap();
+    assert!(area.set_item("b", &big).is_err());
+    area.set_item("a", "small").unwrap();
+    area.set_item("b", &big).unwrap();
+    assert_eq!(area.len(), 2);
+    assert_eq!(area.key(1), Some("b"));
+}
+
+#[test]
+fn local_storage_persists() {
+    let path = std::env::temp_dir().join(format!("icarus-storage-{}.json", std::process::id()));
+    let url = Url::parse("http://example.com/").unwrap();
+    let mut storage = LocalStorage::open(&path).unwrap();
+    storage
+        .area(&url)
+        .unwrap()
+        .set_item("theme", "dark")
+        .unwrap();
+    storage.save().unwrap();
+
+    let mut reopened = LocalStorage::open(&path).unwrap();
+    assert_eq!(reopened.area(&url).unwrap().get_item("theme"), Some("dark"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn over_quota_areas_are_skipped_when_loading() {
+    let path = std::env::temp_dir().join(format!("icarus-quota-{}.json", std::process::id()));
+    let big = "x".repeat(QUOTA);
+    let saved = serde_json::json!({
+        "http://big.example": [["k", big]],
+        "http://small.example": [["k", "v"]],
+    });
+    std::fs::write(&path, saved.to_string()).unwrap();
+
+    let mut storage = LocalStorage::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(storage.origins(), ["http://small.example"]);
+    let small = Url::parse("http://small.example/").unwrap();
+    assert_eq!(storage.area(&small).unwrap().get_item("k"), Some("v"));
+}
+
+#[test]
+fn documents_reach_their_storage_areas() {
+    let path = std::env::temp_dir().join(format!("icarus-storage-{}.html", std::process::id()));
+    std::fs::write(&path, "<p>page</p>").unwrap();
+    let load = |storage: &StorageContext| {
+        let Ok(Load::Document(mut document)) =
+            loader::load_with(path.to_str().unwrap(), &Settings::default())
+        else {
+            panic!("{} didn't load", path.display());
+        };
+        document.storage = storage.clone();
+        document
+    };
+    let tab = StorageContext::new(LocalStorage::in_memory());
+    let document = load(&tab);
+    document
+        .local_storage(|area| area.set_item("a", "local"))
+        .unwrap()
+        .unwrap();
+    document
+        .session_storage(|area| area.set_item("b", "session"))
+        .unwrap()
+        .unwrap();
+
+    let other = load(&tab.new_tab());
+    std::fs::remove_file(&path).unwrap();
+    let get =
+        |area: &mut icarus::storage::Storage, key: &str| area.get_item(key).map(str::to_string);
+    assert_eq!(
+        other
+            .local_storage(|area| get(area, "a"))
+            .unwrap()
+            .as_deref(),
+        Some("local")
+    );
+    assert_eq!(other.session_storage(|area| get(area, "b")).unwrap(), None);
+    assert!(icarus::dom::Document::new().local_storage(|_| ()).is_err());
+}
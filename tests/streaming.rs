@@ -0,0 +1,59 @@
+use icarus::loader::{self, Decoder, Open};
+use icarus::net::multipart::{Multipart, Part};
+use icarus::settings::Settings;
+
+const BODY: &[u8] = b"preamble\r\n--frame\r\nContent-Type: text/html\r\n\r\n<p>one</p>\r\n--frame\r\nContent-Type: text/plain\r\n\r\ntwo\r\n--frame--\r\n";
+
+fn collect(parts: Vec<Part>, bodies: &mut Vec<Vec<u8>>) {
+    for part in parts {
+        match part {
+            Part::Headers(_) => bodies.push(Vec::new()),
+            Part::Data(data) => bodies.last_mut().unwrap().extend(data),
+            Part::End => {}
+        }
+    }
+}
+
+#[test]
+fn multipart_parts_survive_any_chunking() {
+    for size in [1, 3, 7, BODY.len()] {
+        let mut multipart =
+            Multipart::for_content_type("multipart/x-mixed-replace; boundary=\"frame\"").unwrap();
+        let mut bodies = Vec::new();
+        for chunk in BODY.chunks(size) {
+            collect(multipart.push(chunk), &mut bodies);
+        }
+        collect(multipart.finish(), &mut bodies);
+        assert_eq!(
+            bodies,
+            vec![b"<p>one</p>".to_vec(), b"two".to_vec()],
+            "chunk size {}",
+            size
+        );
+    }
+}
+
+#[test]
+fn decoder_holds_back_split_characters() {
+    let mut decoder = Decoder::new(Some("utf-8"));
+    assert_eq!(decoder.decode(b"caf\xc3"), "caf");
+    assert_eq!(decoder.decode(b"\xa9 \xe2\x82"), "\u{e9} ");
+    assert_eq!(decoder.decode(b"\xac"), "\u{20ac}");
+    assert_eq!(decoder.finish(), "");
+}
+
+#[test]
+fn blank_page_streams_into_a_real_document() {
+    let stream = match loader::open("about:blank", &Settings::default()).unwrap() {
+        Open::Document(stream) => stream,
+        Open::Download(_) => panic!("expected a document"),
+    };
+    let document = stream.finish();
+    assert_eq!(document.get_elements_by_tag_name("html").len(), 1);
+    assert!(
+        document.get_elements_by_tag_name("body")[0]
+            .children
+            .borrow()
+            .is_empty()
+    );
+}
@@ -0,0 +1,116 @@
+use icarus::css::selector::{Nth, parse_selector};
+use icarus::dom::Node;
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+mod common;
+
+use common::ids;
+
+const LIST: &str = "<ul><li id=a>1</li><!-- note --><li id=b>2</li><li id=c>3</li>
+    <li id=d>4</li><li id=e>5</li></ul>
+    <div id=mixed><p id=p1></p><span id=s1> </span><p id=p2><!-- only --></p><span id=s2>x</span></div>
+    <section><em id=only></em></section>";
+
+#[test]
+fn nth_expressions_parse() {
+    let nth = |text: &str| Nth::parse(text).map(|nth| (nth.a, nth.b));
+    assert_eq!(nth("odd"), Some((2, 1)));
+    assert_eq!(nth("EVEN"), Some((2, 0)));
+    assert_eq!(nth("3"), Some((0, 3)));
+    assert_eq!(nth("-n+3"), Some((-1, 3)));
+    assert_eq!(nth("+n"), Some((1, 0)));
+    assert_eq!(nth(" 2n - 1 "), Some((2, -1)));
+    assert_eq!(nth("n2"), None);
+    assert_eq!(nth("2n+"), None);
+    assert_eq!(nth("foo"), None);
+    assert!(parse_selector("li:nth-child(2n+1)").is_some());
+    assert!(parse_selector("li:nth-child(bogus)").is_none());
+    assert!(parse_selector("li:nth-child(2").is_none());
+    assert!(parse_selector("li:first-child(2)").is_none());
+}
+
+#[test]
+fn nth_whitespace_is_only_allowed_around_the_sign() {
+    let nth = |text: &str| Nth::parse(text).map(|nth| (nth.a, nth.b));
+    assert_eq!(nth("2n+ 3"), Some((2, 3)));
+    assert_eq!(nth("2n +3"), Some((2, 3)));
+    assert_eq!(nth("-n- 2"), Some((-1, -2)));
+    assert_eq!(nth("\t3n\n"), Some((3, 0)));
+    for invalid in [
+        "- n+2", "+ n", "2 n", "2n+-3", "2n + +3", "2n - ", "+ 3", "- 3", "o dd", "1 0",
+    ] {
+        assert_eq!(nth(invalid), None, "{:?}", invalid);
+    }
+    assert!(parse_selector("li:nth-child(- n+2)").is_none());
+    assert!(parse_selector("li:nth-child(2 n)").is_none());
+    assert!(parse_selector("li:nth-child( 2n + 1 )").is_some());
+}
+
+#[test]
+fn child_positions() {
+    assert_eq!(ids(LIST, "li:first-child"), ["a"]);
+    assert_eq!(ids(LIST, "li:last-child"), ["e"]);
+    assert_eq!(ids(LIST, "li:nth-child(odd)"), ["a", "c", "e"]);
+    assert_eq!(ids(LIST, "li:nth-child(2n)"), ["b", "d"]);
+    assert_eq!(ids(LIST, "li:nth-child(-n+2)"), ["a", "b"]);
+    assert_eq!(ids(LIST, "li:nth-last-child(2)"), ["d"]);
+    assert_eq!(ids(LIST, ":only-child"), ["only"]);
+}
+
+#[test]
+fn extreme_nth_values_do_not_overflow() {
+    let all = ["a", "b", "c", "d", "e"];
+    assert_eq!(ids(LIST, "li:nth-child(n-2147483647)"), all);
+    assert_eq!(ids(LIST, "li:nth-child(-n+2147483647)"), all);
+    assert_eq!(ids(LIST, "li:nth-child(2147483647n+1)"), ["a"]);
+    assert_eq!(
+        ids(LIST, "li:nth-last-child(-2147483648n+2147483647)"),
+        Vec::<String>::new()
+    );
+    assert!(ids(LIST, "li:nth-child(-n-2147483647)").is_empty());
+    assert!(!Nth { a: -1, b: i32::MIN }.matches(0));
+    assert!(Nth { a: 1, b: i32::MIN }.matches(i32::MAX));
+}
+
+#[test]
+fn type_positions() {
+    assert_eq!(ids(LIST, "#mixed :first-of-type"), ["p1", "s1"]);
+    assert_eq!(ids(LIST, "#mixed :last-of-type"), ["p2", "s2"]);
+    assert_eq!(ids(LIST, "#mixed span:nth-of-type(2)"), ["s2"]);
+    assert_eq!(ids(LIST, "#mixed p:nth-last-of-type(2)"), ["p1"]);
+    assert_eq!(ids(LIST, "em:only-of-type"), ["only"]);
+    assert!(ids(LIST, "p:only-of-type").is_empty());
+}
+
+#[test]
+fn empty_elements() {
+    assert_eq!(ids(LIST, "#mixed :empty"), ["p1", "p2"]);
+    assert_eq!(ids(LIST, "em:empty"), ["only"]);
+}
+
+#[test]
+fn structural_styles_follow_dom_changes() {
+    let document = parse_html(
+        "<style>tr:nth-child(even) { color: red } td:empty { display: none }</style>
+         <table><tr id=one><td>a</td></tr><tr id=two><td id=cell>b</td></tr></table>",
+    );
+    document.restyle();
+    let color = |id: &str| {
+        let node = document.query_selector(id).unwrap().unwrap();
+        style_of(&node).to_map()["color"].clone()
+    };
+    assert_eq!(color("#one"), "#000000");
+    assert_eq!(color("#two"), "#ff0000");
+
+    let one = document.query_selector("#one").unwrap().unwrap();
+    Node::detach(&one);
+    document.restyle();
+    assert_eq!(color("#two"), "#000000");
+
+    let cell = document.query_selector("#cell").unwrap().unwrap();
+    let text = cell.children.borrow()[0].clone();
+    Node::remove_child(&cell, &text);
+    document.restyle();
+    assert_eq!(style_of(&cell).to_map()["display"], "none");
+}
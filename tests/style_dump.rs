@@ -0,0 +1,37 @@
+use icarus::html::parser::parse_html;
+use icarus::style::style_of;
+
+#[test]
+fn to_map_reports_resolved_longhands() {
+    let document = parse_html(
+        "<style>.box { margin: 4px 8px; border-left: 2px dashed red; color: blue }</style>
+         <div class=box><p class=box id=inner>text</p></div><p>other</p>",
+    );
+    document.restyle();
+    let boxes = document.query_selector_all(".box").unwrap();
+    assert_eq!(boxes.len(), 2);
+    assert_eq!(
+        document
+            .query_selector("#inner")
+            .unwrap()
+            .map(|node| node.element_name().map(str::to_string)),
+        Some(Some(String::from("p")))
+    );
+
+    let map = style_of(&boxes[0]).to_map();
+    assert_eq!(map["display"], "block");
+    assert_eq!(map["color"], "#0000ff");
+    assert_eq!(map["margin-top"], "4px");
+    assert_eq!(map["margin-right"], "8px");
+    assert_eq!(map["border-left-width"], "2px");
+    assert_eq!(map["border-left-style"], "dashed");
+    assert_eq!(map["border-left-color"], "#ff0000");
+    assert_eq!(map["border-top-style"], "none");
+    assert!(!map.contains_key("margin"));
+}
+
+#[test]
+fn invalid_selectors_are_errors() {
+    let document = parse_html("<p>x</p>");
+    assert!(document.query_selector_all("p[").is_err());
+}
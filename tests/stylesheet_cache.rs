@@ -0,0 +1,97 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use icarus::css::values::Color;
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::settings::Settings;
+use icarus::style::style_of;
+
+fn stylist(document: &Document) -> Rc<icarus::style::Stylist> {
+    document.restyle();
+    document.stylist.borrow().clone().expect("stylist")
+}
+
+fn color_of(document: &Document, id: &str) -> Color {
+    document.restyle();
+    style_of(&document.get_element_by_id(id).unwrap()).color
+}
+
+#[test]
+fn unchanged_stylesheets_reuse_the_stylist() {
+    let document = parse_html("<style>p { color: red }</style><p id=a>text</p>");
+    let first = stylist(&document);
+    let p = document.get_element_by_id("a").unwrap();
+    Node::set_attribute(&p, "class", "other");
+    assert!(Rc::ptr_eq(&first, &stylist(&document)));
+}
+
+#[test]
+fn editing_style_text_reparses() {
+    let document = parse_html("<style>p { color: red }</style><p id=a>text</p>");
+    assert_eq!(color_of(&document, "a"), Color::rgb(255, 0, 0));
+    let first = stylist(&document);
+
+    let style = document.get_elements_by_tag_name("style")[0].clone();
+    let text = style.children.borrow()[0].clone();
+    Node::set_character_data(&text, "p { color: blue }");
+    assert_eq!(color_of(&document, "a"), Color::rgb(0, 0, 255));
+    assert!(!Rc::ptr_eq(&first, &stylist(&document)));
+}
+
+#[test]
+fn inserting_removing_and_retyping_style_elements_reparses() {
+    let document = parse_html("<p id=a>text</p>");
+    let black = color_of(&document, "a");
+
+    let source = parse_html("<style>p { color: red }</style>");
+    let style = source.get_elements_by_tag_name("style")[0].clone();
+    Node::detach(&style);
+    let body = document.get_elements_by_tag_name("body")[0].clone();
+    Node::append_child(&body, Rc::clone(&style));
+    assert_eq!(color_of(&document, "a"), Color::rgb(255, 0, 0));
+
+    Node::set_attribute(&style, "type", "text/plain");
+    assert_eq!(color_of(&document, "a"), black);
+    Node::remove_attribute(&style, "type");
+    assert_eq!(color_of(&document, "a"), Color::rgb(255, 0, 0));
+
+    Node::detach(&style);
+    assert_eq!(color_of(&document, "a"), black);
+}
+
+#[test]
+fn scripting_and_settings_changes_rebuild_the_stylist() {
+    let mut document =
+        parse_html("<style>@media (scripting: none) { p { color: red } }</style><p id=a>text</p>");
+    let first = stylist(&document);
+    assert_ne!(color_of(&document, "a"), Color::rgb(255, 0, 0));
+
+    document.scripting = false;
+    assert_eq!(color_of(&document, "a"), Color::rgb(255, 0, 0));
+    let second = stylist(&document);
+    assert!(!Rc::ptr_eq(&first, &second));
+
+    let mut settings = Settings::default();
+    settings.fonts.size = 30.0;
+    document.settings = Arc::new(settings);
+    let third = stylist(&document);
+    assert!(!Rc::ptr_eq(&second, &third));
+    let p = document.get_element_by_id("a").unwrap();
+    assert_eq!(style_of(&p).font_size, 30.0);
+}
+
+#[test]
+fn style_changes_only_invalidate_their_own_document() {
+    let first = parse_html("<style>p { color: red }</style><p id=a>text</p>");
+    let second = parse_html("<style>p { color: green }</style><p id=a>text</p>");
+    let kept = stylist(&first);
+    let replaced = stylist(&second);
+
+    let style = second.get_elements_by_tag_name("style")[0].clone();
+    let text = style.children.borrow()[0].clone();
+    Node::set_character_data(&text, "p { color: blue }");
+    assert!(Rc::ptr_eq(&kept, &stylist(&first)));
+    assert!(!Rc::ptr_eq(&replaced, &stylist(&second)));
+    assert_eq!(color_of(&second, "a"), Color::rgb(0, 0, 255));
+}
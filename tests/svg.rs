@@ -0,0 +1,40 @@
+use icarus::css::values::Color;
+use icarus::html::parser::parse_html;
+use icarus::render::svg;
+
+#[test]
+fn svg_contains_text_and_boxes() {
+    let document = parse_html(
+        "<div style=\"background-color: rgba(255, 0, 0, 0.5); height: 20px\"></div>\
+         <p><b>Tom &amp; Jerry</b> <i>&lt;3</i></p><img alt=\"logo\" width=40 height=20>",
+    );
+    let output = svg::render(&document, 400, Some(300));
+    assert!(output.starts_with(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"400\" height=\"300\" viewBox=\"0 0 400 300\">"
+    ));
+    assert!(output.contains("fill=\"#ff0000\" fill-opacity=\"0.502\""));
+    assert!(output.contains("font-weight=\"bold\""));
+    assert!(output.contains(">Tom &amp; Jerry</text>"));
+    assert!(output.contains("font-style=\"italic\""));
+    assert!(output.contains(">&lt;3</text>"));
+    assert!(output.contains(">[image: logo]</text>"));
+    assert!(output.ends_with("</svg>\n"));
+}
+
+#[test]
+fn empty_display_list_is_just_the_background() {
+    let output = svg::to_svg(&Default::default(), 10, 10, Color::rgb(255, 255, 255));
+    assert_eq!(
+        output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\" viewBox=\"0 0 10 10\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n</svg>\n"
+    );
+}
+
+#[test]
+fn svg_text_stays_well_formed_xml() {
+    let document = parse_html("<p>a\u{7}b</p>");
+    let output = svg::render(&document, 400, Some(100));
+    assert!(output.contains(">ab</text>"), "{}", output);
+    assert!(roxmltree::Document::parse(&output).is_ok());
+}
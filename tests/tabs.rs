@@ -0,0 +1,158 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::layout::Rect;
+use icarus::settings::Settings;
+use icarus::shell::chrome::{TAB_STRIP_HEIGHT, tab_at, tab_width};
+use icarus::shell::pipeline::Event;
+use icarus::shell::tab::{BLANK_URL, COMMITTED, STARTED, Tab};
+use icarus::storage::{LocalStorage, StorageContext};
+
+const VIEWPORT: Rect = Rect {
+    x: 0.0,
+    y: 0.0,
+    width: 400.0,
+    height: 300.0,
+};
+
+struct Page(std::path::PathBuf);
+
+impl Page {
+    fn new(name: &str, html: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("icarus-tab-{}-{}.html", name, std::process::id()));
+        std::fs::write(&path, html).unwrap();
+        Page(path)
+    }
+
+    fn url(&self) -> String {
+        url::Url::from_file_path(&self.0).unwrap().to_string()
+    }
+}
+
+impl Drop for Page {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn source_of(tab: &Tab) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        for event in tab.pipeline.poll().unwrap() {
+            if let Event::Source(source) = event {
+                return source;
+            }
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for the page");
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn tab_strip_geometry() {
+    assert_eq!(tab_width(2, 800), 200.0);
+    assert_eq!(tab_width(8, 800), 100.0);
+    assert_eq!(tab_width(0, 800), 200.0);
+    assert_eq!(tab_at(250.0, 10.0, 3, 800), Some(1));
+    assert_eq!(tab_at(650.0, 10.0, 3, 800), None);
+    assert_eq!(tab_at(10.0, TAB_STRIP_HEIGHT as f32, 3, 800), None);
+}
+
+#[test]
+fn tabs_load_and_keep_history_independently() {
+    let first = Page::new("first", "<p>first</p>");
+    let second = Page::new("second", "<p>second</p>");
+    let settings = Arc::new(Settings::default());
+
+    let mut a = Tab::open(
+        &first.url(),
+        VIEWPORT,
+        Arc::clone(&settings),
+        StorageContext::default(),
+    )
+    .unwrap();
+    let b = Tab::open(
+        &second.url(),
+        VIEWPORT,
+        Arc::clone(&settings),
+        StorageContext::default(),
+    )
+    .unwrap();
+    assert_eq!(source_of(&a), "<p>first</p>");
+    assert_eq!(source_of(&b), "<p>second</p>");
+    assert_eq!(a.label(), first.url());
+    assert_eq!(a.progress, Some(STARTED));
+    a.commit(None);
+    assert_eq!(a.progress, Some(COMMITTED));
+
+    a.scroll_y = 120.0;
+    a.navigate(&second.url(), VIEWPORT).unwrap();
+    assert_eq!(a.scroll_y, 0.0);
+    assert_eq!(a.progress, Some(STARTED));
+    assert_eq!(source_of(&a), "<p>second</p>");
+    assert_eq!(a.history().len(), 2);
+    assert_eq!(b.history().len(), 1);
+
+    assert!(a.go_back(VIEWPORT).unwrap());
+    assert_eq!(a.url(), first.url());
+    assert_eq!(a.pending_scroll, Some(120.0));
+    assert_eq!(source_of(&a), "<p>first</p>");
+    assert!(!a.go_back(VIEWPORT).unwrap());
+    assert!(a.go_forward(VIEWPORT).unwrap());
+    assert_eq!(a.url(), second.url());
+    assert_eq!(b.url(), second.url());
+}
+
+#[test]
+fn blank_tabs_are_labelled_new_tab() {
+    let tab = Tab::open(
+        BLANK_URL,
+        VIEWPORT,
+        Arc::new(Settings::default()),
+        StorageContext::default(),
+    )
+    .unwrap();
+    assert_eq!(tab.label(), "New Tab");
+}
+
+#[test]
+fn session_storage_belongs_to_the_tab() {
+    let first = Page::new("storage-first", "<p>first</p>");
+    let second = Page::new("storage-second", "<p>second</p>");
+    let origin = url::Url::parse(&first.url()).unwrap();
+    let settings = Arc::new(Settings::default());
+    let browser = StorageContext::new(LocalStorage::in_memory());
+
+    let mut a = Tab::open(
+        &first.url(),
+        VIEWPORT,
+        Arc::clone(&settings),
+        browser.new_tab(),
+    )
+    .unwrap();
+    a.storage()
+        .session(&origin, |area| area.set_item("draft", "one"))
+        .unwrap()
+        .unwrap();
+    a.storage()
+        .local(&origin, |area| area.set_item("theme", "dark"))
+        .unwrap()
+        .unwrap();
+    a.navigate(&second.url(), VIEWPORT).unwrap();
+    let draft = |tab: &Tab| {
+        tab.storage()
+            .session(&origin, |area| area.get_item("draft").map(str::to_string))
+            .unwrap()
+    };
+    assert_eq!(draft(&a).as_deref(), Some("one"));
+
+    let b = Tab::open(&first.url(), VIEWPORT, settings, browser.new_tab()).unwrap();
+    assert_eq!(draft(&b), None);
+    let theme = b
+        .storage()
+        .local(&origin, |area| area.get_item("theme").map(str::to_string))
+        .unwrap();
+    assert_eq!(theme.as_deref(), Some("dark"));
+}
@@ -0,0 +1,87 @@
+use icarus::{LinkStyle, TableStyle, TextOptions, to_text};
+
+#[test]
+fn paragraphs_wrap_and_links_become_footnotes() {
+    let text = to_text(
+        "<h1>Title</h1>
+         <p>Read the <a href=\"https://example.com/docs\">documentation</a> before
+            filing an issue, or ask on the <a href=\"https://example.com/forum\">forum</a>.</p>
+         <script>ignored()</script>",
+        TextOptions {
+            width: 30,
+            ..TextOptions::default()
+        },
+    );
+    assert_eq!(
+        text,
+        "Title\n=====\n\nRead the documentation[1]\nbefore filing an issue, or ask\non the forum[2].\n\n\
+         [1] https://example.com/docs\n[2] https://example.com/forum\n"
+    );
+}
+
+#[test]
+fn lists_quotes_and_inline_links() {
+    let text = to_text(
+        "<ol start=2><li>two</li><li>three <a href=\"https://example.com/\">here</a></li></ol>
+         <blockquote><p>quoted</p><p>twice</p></blockquote>",
+        TextOptions {
+            links: LinkStyle::Inline,
+            ..TextOptions::default()
+        },
+    );
+    assert_eq!(
+        text,
+        "2. two\n3. three here <https://example.com/>\n\n> quoted\n>\n> twice\n"
+    );
+}
+
+#[test]
+fn tables_render_as_ascii_or_plain_columns() {
+    let html = "<table><tr><th>Name</th><th>Size</th></tr>\
+                <tr><td>a.txt</td><td>12</td></tr><tr><td>b</td><td>3400</td></tr></table>";
+    assert_eq!(
+        to_text(html, TextOptions::default()),
+        "+-------+------+\n| Name  | Size |\n+=======+======+\n| a.txt | 12   |\n| b     | 3400 |\n+-------+------+\n"
+    );
+    assert_eq!(
+        to_text(
+            html,
+            TextOptions {
+                tables: TableStyle::Plain,
+                ..TextOptions::default()
+            }
+        ),
+        "Name   Size\na.txt  12\nb      3400\n"
+    );
+}
+
+#[test]
+fn long_words_are_broken_to_fit_table_columns() {
+    let text = to_text(
+        "<table><tr><th>Link</th><th>Note</th></tr>\
+         <tr><td>https://example.com/a/very/long/path</td><td>see it</td></tr></table>",
+        TextOptions {
+            width: 24,
+            ..TextOptions::default()
+        },
+    );
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(
+        lines.iter().all(|line| line.chars().count() == 24),
+        "{}",
+        text
+    );
+    assert!(
+        lines.iter().all(|line| line.ends_with(['|', '+'])),
+        "{}",
+        text
+    );
+    assert_eq!(
+        text.lines()
+            .filter_map(|line| line.strip_prefix("| "))
+            .skip(1)
+            .map(|line| line.split(" | ").next().unwrap().trim_end())
+            .collect::<String>(),
+        "https://example.com/a/very/long/path"
+    );
+}
@@ -0,0 +1,83 @@
+use icarus::layout::LayoutBox;
+use icarus::layout::inline::Fragment;
+
+mod common;
+
+use common::{fragment_text, fragments, layout};
+
+fn line_box(layout_box: &LayoutBox) -> Option<&LayoutBox> {
+    if !layout_box.lines.is_empty() {
+        return Some(layout_box);
+    }
+    layout_box.children.iter().find_map(line_box)
+}
+
+fn baseline(fragment: &Fragment) -> f32 {
+    fragment.rect.y + fragment.rect.height * 0.75
+}
+
+#[test]
+fn horizontal_alignment_offsets_lines() {
+    let tree = layout(
+        "<body style='margin: 0'>
+         <p style='text-align: right; width: 100px'>ab</p>
+         <p style='text-align: center; width: 100px'>ab</p>
+         <center>x</center></body>",
+    );
+    let fragments = fragments(&tree.root);
+    assert_eq!(fragments[0].rect.right(), 100.0);
+    assert_eq!(fragments[1].rect.x, (100.0 - 19.2) / 2.0);
+    assert_eq!(fragments[2].rect.x, (800.0 - 9.6) / 2.0);
+}
+
+#[test]
+fn justify_spreads_wrapped_lines_only() {
+    let tree = layout(
+        "<p style='text-align: justify; width: 150px; margin: 0'>aaa bb c dddd eeeee ff</p>",
+    );
+    let layout_box = line_box(&tree.root).unwrap();
+    let lines = &layout_box.lines;
+    assert!(lines.len() >= 2);
+
+    let first = &lines[0].fragments;
+    assert!(first.len() > 1);
+    assert_eq!(first.last().unwrap().rect.right(), layout_box.rect.right());
+    let joined: String = first.iter().map(fragment_text).collect();
+    assert!(joined.starts_with("aaa bb"));
+
+    let last = lines.last().unwrap();
+    assert_eq!(last.fragments.len(), 1);
+    assert_eq!(last.fragments[0].rect.x, layout_box.rect.x);
+}
+
+#[test]
+fn vertical_align_shifts_from_baseline() {
+    let tree = layout(
+        "<p>x<sup>2</sup><sub>i</sub><span style='vertical-align: 5px'>up</span>\
+         <img width=10 height=40 style='vertical-align: top'></p>",
+    );
+    let fragments = fragments(&tree.root);
+    let base = baseline(fragments[0]);
+    let by_text = |wanted: &str| {
+        fragments
+            .iter()
+            .find(|fragment| fragment_text(fragment) == wanted)
+            .copied()
+            .unwrap()
+    };
+    assert!(baseline(by_text("2")) < base);
+    assert!(baseline(by_text("i")) > base);
+    assert_eq!(baseline(by_text("up")), base - 5.0);
+
+    let image = fragments.last().unwrap();
+    let line = &line_box(&tree.root).unwrap().lines[0];
+    assert_eq!(image.rect.y, line.rect.y);
+    assert!(line.rect.height >= 40.0);
+}
+
+#[test]
+fn images_sit_on_the_baseline() {
+    let tree = layout("<p>x<img width=10 height=30></p>");
+    let fragments = fragments(&tree.root);
+    assert_eq!(fragments[1].rect.bottom(), baseline(fragments[0]));
+}
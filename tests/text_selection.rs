@@ -0,0 +1,59 @@
+use std::rc::Rc;
+
+use icarus::html::parser::parse_html;
+use icarus::layout::selection::{position_at, selection_rects};
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::range::{BoundaryPoint, Range};
+
+#[test]
+fn positions_map_back_to_text_offsets() {
+    let document =
+        parse_html("<body style='margin: 0'><p id=a style='margin: 0'>  hello   world</p>");
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let p = document.get_element_by_id("a").unwrap();
+    let text = p.children.borrow()[0].clone();
+    let rect = tree.rect_of(&text).unwrap();
+
+    let start = position_at(&tree, rect.x - 5.0, rect.y + 1.0).unwrap();
+    assert!(Rc::ptr_eq(&start.node, &text));
+    assert_eq!(start.offset, 2);
+
+    let end = position_at(&tree, rect.right() + 50.0, rect.y + 1.0).unwrap();
+    assert_eq!(end.offset, 15);
+
+    let below = position_at(&tree, 0.0, tree.height() + 100.0).unwrap();
+    assert!(Rc::ptr_eq(&below.node, &text));
+}
+
+#[test]
+fn selection_rects_cover_the_selected_words() {
+    let document = parse_html(
+        "<body style='margin: 0'><p id=a style='margin: 0'>hello world</p><p id=b style='margin: 0'>second line</p>",
+    );
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let hello = document.get_element_by_id("a").unwrap().children.borrow()[0].clone();
+    let second = document.get_element_by_id("b").unwrap().children.borrow()[0].clone();
+    let line = tree.rect_of(&hello).unwrap();
+
+    let word = Range::from_points(
+        BoundaryPoint::new(&hello, 6),
+        BoundaryPoint::new(&hello, 11),
+    );
+    let rects = selection_rects(&tree, &word);
+    assert_eq!(rects.len(), 1);
+    assert!(rects[0].x > line.x);
+    assert!((rects[0].right() - line.right()).abs() < 0.01);
+
+    let across = Range::from_points(
+        BoundaryPoint::new(&hello, 6),
+        BoundaryPoint::new(&second, 6),
+    );
+    let rects = selection_rects(&tree, &across);
+    assert_eq!(rects.len(), 2);
+    assert!(rects[1].y > rects[0].y);
+    assert_eq!(rects[1].x, 0.0);
+
+    let collapsed =
+        Range::from_points(BoundaryPoint::new(&hello, 3), BoundaryPoint::new(&hello, 3));
+    assert!(selection_rects(&tree, &collapsed).is_empty());
+}
@@ -0,0 +1,89 @@
+use icarus::layout::LayoutTree;
+use icarus::layout::inline::FragmentKind;
+use icarus::paint::{DisplayItem, DisplayList};
+
+mod common;
+
+use common::{fragments, layout};
+
+fn texts(tree: &LayoutTree) -> Vec<String> {
+    fragments(&tree.root)
+        .into_iter()
+        .filter_map(|fragment| match &fragment.kind {
+            FragmentKind::Text(text) => Some(text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn text_transform_applies_to_runs() {
+    let tree = layout(
+        "<p style='text-transform: uppercase'>shout it</p>
+         <p style='text-transform: capitalize'>one (two) <b>three</b>four</p>
+         <p style='text-transform: lowercase'>QUIET</p>",
+    );
+    assert_eq!(
+        texts(&tree),
+        ["SHOUT IT", "One (Two) ", "Three", "four", "quiet"]
+    );
+}
+
+#[test]
+fn spacing_widens_measured_text() {
+    let tree = layout(
+        "<p style='letter-spacing: 2px'>abc</p>
+         <p style='word-spacing: 0.5em'>a b c</p>",
+    );
+    let widths: Vec<f32> = fragments(&tree.root)
+        .iter()
+        .map(|fragment| fragment.rect.width)
+        .collect();
+    assert_eq!(widths, [3.0 * 9.6 + 6.0, 5.0 * 9.6 + 16.0]);
+}
+
+#[test]
+fn letter_spacing_paints_each_glyph() {
+    let list = DisplayList::build(&layout("<p style='letter-spacing: 10px'>ab c</p>"));
+    let glyphs: Vec<(String, f32)> = list
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, rect, .. } => Some((text.clone(), rect.x)),
+            _ => None,
+        })
+        .collect();
+    let step = 9.6 + 10.0;
+    assert_eq!(
+        glyphs,
+        [
+            (String::from("a"), 8.0),
+            (String::from("b"), 8.0 + step),
+            (String::from("c"), 8.0 + 3.0 * step),
+        ]
+    );
+}
+
+#[test]
+fn decorations_paint_lines() {
+    let tree = layout(
+        "<p><a href=/x>link</a> <del>gone</del> <span style='text-decoration: overline'>o</span></p>",
+    );
+    let list = DisplayList::build(&tree);
+    let lines: Vec<_> = list
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::SolidColor { rect, .. } => Some(*rect),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(lines.len(), 3);
+
+    let text = fragments(&tree.root)[0].rect;
+    let baseline = text.y + text.height * 0.75;
+    assert!(lines[0].y > baseline);
+    assert!(lines[1].y < baseline && lines[1].y > text.y);
+    assert!(lines[2].y < lines[1].y);
+    assert_eq!(lines[0].width, 4.0 * 9.6);
+}
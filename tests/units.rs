@@ -0,0 +1,12 @@
+use icarus::units::format_bytes;
+
+#[test]
+fn bytes_use_binary_units() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1024), "1.0 KiB");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(1_048_575), "1024.0 KiB");
+    assert_eq!(format_bytes(1_048_576), "1.0 MiB");
+    assert_eq!(format_bytes(5 * 1_048_576 + 524_288), "5.5 MiB");
+}
@@ -0,0 +1,62 @@
+use std::rc::Rc;
+
+use icarus::html::parser::parse_html;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::paint::{DisplayItem, DisplayList};
+
+const PAGE: &str = "<body style='margin: 0'>
+    <div id=hidden style='visibility: hidden; background: red; height: 40px'>
+      gone <span id=shown style='visibility: visible'>shown</span>
+    </div>
+    <p id=after style='margin: 0'>after</p>
+    <div style='display: none'>none</div></body>";
+
+fn texts(list: &DisplayList) -> Vec<&str> {
+    list.items
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn hidden_boxes_keep_their_space_but_are_not_painted() {
+    let document = parse_html(PAGE);
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let after = document.query_selector("#after").unwrap().unwrap();
+    assert_eq!(tree.rect_of(&after).unwrap().y, 40.0);
+
+    let list = DisplayList::build(&tree);
+    assert_eq!(texts(&list), ["shown", "after"]);
+    assert!(
+        !list
+            .items
+            .iter()
+            .any(|item| matches!(item, DisplayItem::SolidColor { .. }))
+    );
+}
+
+#[test]
+fn hidden_boxes_are_not_hit() {
+    let document = parse_html(PAGE);
+    let tree = LayoutTree::build(&document, LayoutOptions::default());
+    let hidden = document.query_selector("#hidden").unwrap().unwrap();
+    let shown = document.query_selector("#shown").unwrap().unwrap();
+    let gone = tree.rect_of(&hidden).unwrap();
+    let hit = tree.hit_test(gone.x + 1.0, gone.y + 1.0);
+    assert!(hit.is_none_or(|hit| !Rc::ptr_eq(&hit.node, &hidden)));
+
+    let rect = tree.rect_of(&shown).unwrap();
+    let hit = tree.hit_test(rect.x + 1.0, rect.y + 1.0).unwrap();
+    assert!(Rc::ptr_eq(&hit.node, &shown));
+}
+
+#[test]
+fn hidden_text_is_left_out_of_inner_text() {
+    let document = parse_html(PAGE);
+    document.restyle();
+    let hidden = document.query_selector("#hidden").unwrap().unwrap();
+    assert_eq!(hidden.inner_text().trim(), "shown");
+}
@@ -0,0 +1,104 @@
+use icarus::html::visitor::ParseVisitor;
+use std::cell::RefCell;
+use std::string::String;
+use std::vec::Vec;
+
+fn events(html: &str) -> Vec<String> {
+    let events = RefCell::new(Vec::new());
+    ParseVisitor::new()
+        .on_start_tag(|tag| events.borrow_mut().push(format!("<{}>", tag.local_name())))
+        .on_end_tag(|tag| events.borrow_mut().push(format!("</{}>", tag.local_name())))
+        .on_text(|text| events.borrow_mut().push(format!("{:?}", text)))
+        .on_comment(|text| events.borrow_mut().push(format!("<!--{}-->", text)))
+        .parse(html);
+    events.into_inner()
+}
+
+#[test]
+fn emits_implied_and_explicit_tags_in_order() {
+    assert_eq!(
+        events("<p>Hello <b>world</b><br>!<!--x-->"),
+        [
+            "<html>",
+            "<head>",
+            "</head>",
+            "<body>",
+            "<p>",
+            "\"Hello \"",
+            "<b>",
+            "\"world\"",
+            "</b>",
+            "<br>",
+            "</br>",
+            "\"!\"",
+            "<!--x-->",
+            "</p>",
+            "</body>",
+            "</html>"
+        ]
+    );
+}
+
+#[test]
+fn closes_implicitly_ended_elements() {
+    assert_eq!(
+        events("<ul><li>a<li>b</ul>")[4..],
+        [
+            "<ul>", "<li>", "\"a\"", "</li>", "<li>", "\"b\"", "</li>", "</ul>", "</body>",
+            "</html>"
+        ]
+    );
+}
+
+#[test]
+fn coalesces_text_across_chunks() {
+    let texts = RefCell::new(Vec::new());
+    let mut parser = ParseVisitor::new()
+        .on_text(|text| texts.borrow_mut().push(text.to_string()))
+        .start();
+    for chunk in ["<p>one ", "two", " three</p><p>four"] {
+        parser.feed(chunk);
+    }
+    parser.finish();
+    assert_eq!(texts.into_inner(), ["one two three", "four"]);
+}
+
+#[test]
+fn extracts_attributes_without_building_a_tree() {
+    let mut links = Vec::new();
+    ParseVisitor::new()
+        .on_start_tag(|tag| {
+            if tag.local_name() == "a"
+                && let Some(href) = tag.get_attribute("href")
+            {
+                links.push(href.to_string());
+            }
+        })
+        .parse(r#"<a href="/one">1</a><div><a href="/two">2</a><a>3</a></div>"#);
+    assert_eq!(links, ["/one", "/two"]);
+}
+
+#[test]
+fn reports_depth_and_doctype() {
+    let doctype = RefCell::new(String::new());
+    let depths = RefCell::new(Vec::new());
+    ParseVisitor::new()
+        .on_doctype(|name| *doctype.borrow_mut() = name.to_string())
+        .on_start_tag(|tag| {
+            depths
+                .borrow_mut()
+                .push((tag.local_name().to_string(), tag.depth))
+        })
+        .parse("<!DOCTYPE html><div><span></span></div>");
+    assert_eq!(doctype.into_inner(), "html");
+    assert_eq!(
+        depths.into_inner(),
+        [
+            ("html".to_string(), 0),
+            ("head".to_string(), 1),
+            ("body".to_string(), 1),
+            ("div".to_string(), 2),
+            ("span".to_string(), 3)
+        ]
+    );
+}
@@ -0,0 +1,38 @@
+use icarus::css::values::Color;
+use icarus::layout::Rect;
+use icarus::paint::raster::Canvas;
+
+#[test]
+fn identical_canvases_are_fully_similar() {
+    let a = Canvas::new(8, 8, Color::WHITE);
+    let diff = a.diff(&Canvas::new(8, 8, Color::WHITE), 0);
+    assert_eq!(diff.differing, 0);
+    assert_eq!(diff.similarity(), 1.0);
+    assert_eq!(diff.image.pixel(3, 3), Some(Color::WHITE.to_u32()));
+}
+
+#[test]
+fn changed_pixels_are_highlighted_and_counted() {
+    let a = Canvas::new(10, 10, Color::WHITE);
+    let mut b = Canvas::new(10, 10, Color::WHITE);
+    b.fill_rect(Rect::new(0.0, 0.0, 5.0, 2.0), Color::rgb(250, 250, 250));
+    b.fill_rect(Rect::new(5.0, 5.0, 5.0, 5.0), Color::BLACK);
+
+    let diff = a.diff(&b, 0);
+    assert_eq!(diff.differing, 35);
+    assert_eq!(diff.max_difference, 255);
+    assert_eq!(diff.similarity(), 0.65);
+    assert_eq!(diff.image.pixel(9, 9), Some(Color::rgb(255, 0, 0).to_u32()));
+
+    let tolerant = a.diff(&b, 8);
+    assert_eq!(tolerant.differing, 25);
+}
+
+#[test]
+fn size_mismatches_count_as_differences() {
+    let a = Canvas::new(4, 4, Color::WHITE);
+    let b = Canvas::new(4, 6, Color::WHITE);
+    let diff = a.diff(&b, 0);
+    assert_eq!((diff.image.width, diff.image.height), (4, 6));
+    assert_eq!(diff.differing, 8);
+}
@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::net::TcpListener;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use icarus::dom::{Document, Node};
+use icarus::html::parser::parse_html;
+use icarus::net::websocket::{MessageData, ReadyState, WebSocketEvent};
+use icarus::net::{ResponseInfo, Timing};
+use tungstenite::Message;
+use url::Url;
+
+mod common;
+
+fn echo_server() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        loop {
+            match socket.read() {
+                Ok(Message::Text(text)) => socket
+                    .send(Message::text(format!("echo: {}", text)))
+                    .unwrap(),
+                Ok(Message::Binary(bytes)) => socket.send(Message::binary(bytes)).unwrap(),
+                Ok(_) => {}
+                Err(_) => return,
+            }
+        }
+    });
+    port
+}
+
+fn page(port: u16) -> Document {
+    let mut document = parse_html("<ul id=log></ul>");
+    document.response = Some(ResponseInfo {
+        url: Url::parse(&format!("http://127.0.0.1:{}/chat/room.html", port)).unwrap(),
+        status: 200,
+        headers: Vec::new(),
+        timing: Timing::started(Instant::now()),
+    });
+    document
+}
+
+fn dispatch_until(document: &Document, done: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for events");
+        document.dispatch_websocket_events();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn page_socket_dispatches_messages_into_the_document() {
+    let port = echo_server();
+    let document = Rc::new(page(port));
+    let socket = document.open_websocket("socket", &[]).unwrap();
+    assert_eq!(
+        socket.url().as_str(),
+        format!("ws://127.0.0.1:{}/chat/socket", port)
+    );
+    assert!(document.has_websockets());
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&events);
+    socket.set_onopen(move |socket, _| {
+        log.borrow_mut().push(String::from("open"));
+        socket.send("hello").unwrap();
+    });
+    let page = Rc::downgrade(&document);
+    socket.set_onmessage(move |socket, event| {
+        let WebSocketEvent::Message(MessageData::Text(text)) = event else {
+            panic!("unexpected {:?}", event);
+        };
+        let document = page.upgrade().unwrap();
+        let list = document.get_element_by_id("log").unwrap();
+        let item = parse_html(&format!("<li>{}", text)).get_elements_by_tag_name("li")[0].clone();
+        Node::append_child(&list, item);
+        socket.close(None, "bye").unwrap();
+    });
+    let log = Rc::clone(&events);
+    socket.set_onclose(move |_, event| {
+        let WebSocketEvent::Close {
+            code, was_clean, ..
+        } = event
+        else {
+            panic!("unexpected {:?}", event);
+        };
+        log.borrow_mut()
+            .push(format!("close {} {}", code, was_clean));
+    });
+
+    dispatch_until(&document, || !document.has_websockets());
+    assert_eq!(*events.borrow(), ["open", "close 1000 true"]);
+    assert_eq!(
+        document
+            .get_element_by_id("log")
+            .unwrap()
+            .get_text_content(),
+        "echo: hello"
+    );
+    assert_eq!(socket.ready_state(), ReadyState::Closed);
+    assert!(!document.dispatch_websocket_events());
+}
+
+#[test]
+fn failed_connections_report_an_error_then_close() {
+    let port = common::closed_port();
+    let document = page(port);
+    let socket = document
+        .open_websocket(&format!("http://127.0.0.1:{}/", port), &[])
+        .unwrap();
+    assert_eq!(socket.url().scheme(), "ws");
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&events);
+    socket.set_onerror(move |_, _| log.borrow_mut().push("error"));
+    let log = Rc::clone(&events);
+    socket.set_onclose(move |_, _| log.borrow_mut().push("close"));
+
+    dispatch_until(&document, || !document.has_websockets());
+    assert_eq!(*events.borrow(), ["error", "close"]);
+    assert!(document.open_websocket("ftp://example.com/", &[]).is_err());
+}
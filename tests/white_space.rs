@@ -0,0 +1,85 @@
+use icarus::html::parser::parse_html;
+use icarus::layout::inline::expand_tabs;
+use icarus::layout::{LayoutOptions, LayoutTree};
+use icarus::style::{WhiteSpace, style_of};
+
+fn text_lines(html: &str, viewport_width: f32) -> Vec<String> {
+    let document = parse_html(html);
+    let dump = LayoutTree::build(
+        &document,
+        LayoutOptions {
+            viewport_width,
+            ..LayoutOptions::default()
+        },
+    )
+    .dump();
+    let mut lines = Vec::new();
+    for line in dump.lines().map(str::trim) {
+        if line.starts_with("line ") {
+            lines.push(String::new());
+        } else if let Some(text) = line.strip_prefix("text ") {
+            let quoted = &text[..text.rfind(" (").unwrap()];
+            let text: String = serde_json::from_str(quoted).unwrap();
+            lines.last_mut().unwrap().push_str(&text);
+        }
+    }
+    lines
+}
+
+#[test]
+fn preformatted_defaults_and_inheritance() {
+    let document = parse_html(
+        "<pre><code>x</code></pre><textarea>t</textarea><p style='white-space: pre-line'>p</p>",
+    );
+    document.restyle();
+    let style = |tag: &str| style_of(&document.get_elements_by_tag_name(tag)[0]).white_space;
+    assert_eq!(style("pre"), WhiteSpace::Pre);
+    assert_eq!(style("code"), WhiteSpace::Pre);
+    assert_eq!(style("textarea"), WhiteSpace::PreWrap);
+    assert_eq!(style("p"), WhiteSpace::PreLine);
+}
+
+#[test]
+fn pre_keeps_spaces_newlines_and_tabs() {
+    let lines = text_lines(
+        "<pre>\nfn main() {\n\tlet  x = 1;\n\n}</pre><p>  a \n  b  </p>",
+        800.0,
+    );
+    assert_eq!(
+        lines,
+        ["fn main() {", "        let  x = 1;", "", "}", "a b"]
+    );
+    assert_eq!(expand_tabs("ab\tc\t"), "ab      c       ");
+}
+
+#[test]
+fn only_pre_wrap_and_pre_line_wrap_long_lines() {
+    let long = "word ".repeat(30);
+    let pre = text_lines(&format!("<pre>{}</pre>", long), 200.0);
+    assert_eq!(pre.len(), 1);
+
+    let wrapped = text_lines(
+        &format!("<div style='white-space: pre-wrap'>{}</div>", long),
+        200.0,
+    );
+    assert!(wrapped.len() > 1);
+    assert_eq!(wrapped.concat(), long);
+
+    let pre_line = text_lines(
+        "<div style='white-space: pre-line'>  one   two \n three</div>",
+        800.0,
+    );
+    assert_eq!(pre_line, ["one two", "three"]);
+}
+
+#[test]
+fn inner_text_follows_white_space() {
+    let document = parse_html(
+        "<p>  hello   <b>big</b>\n world </p><pre>  a\n    b  </pre><div>x<br>  y</div>",
+    );
+    document.restyle();
+    assert_eq!(
+        document.root.inner_text(),
+        "hello big world\n  a\n    b  \nx\ny"
+    );
+}
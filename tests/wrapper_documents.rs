@@ -0,0 +1,84 @@
+use icarus::css::values::Color;
+use icarus::image::Image;
+use icarus::loader::{self, DocumentKind, Load};
+use icarus::settings::Settings;
+use url::Url;
+
+struct TempFile(std::path::PathBuf);
+
+impl TempFile {
+    fn new(name: &str, bytes: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("icarus-{}-{}", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        TempFile(path)
+    }
+
+    fn load(&self) -> icarus::dom::Document {
+        let url = Url::from_file_path(&self.0).unwrap();
+        match loader::load_with(url.as_str(), &Settings::default()).unwrap() {
+            Load::Document(document) => document,
+            Load::Download(_) => panic!("expected a document"),
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn content_types_map_to_document_kinds() {
+    assert_eq!(DocumentKind::of(None), Some(DocumentKind::Html));
+    assert_eq!(
+        DocumentKind::of(Some("application/xhtml+xml")),
+        Some(DocumentKind::Html)
+    );
+    assert_eq!(
+        DocumentKind::of(Some("application/json")),
+        Some(DocumentKind::Text)
+    );
+    assert_eq!(
+        DocumentKind::of(Some("image/x-icon")),
+        Some(DocumentKind::Image)
+    );
+    assert_eq!(DocumentKind::of(Some("image/webp")), None);
+    assert!(!loader::can_render(Some("application/zip")));
+}
+
+#[test]
+fn plain_text_is_wrapped_in_a_pre_and_escaped() {
+    let text = "fn main() {\n    if a < b && c > d {}\n}\n";
+    let document = TempFile::new("notes.txt", text.as_bytes()).load();
+    let pre = document.get_elements_by_tag_name("pre");
+    assert_eq!(pre.len(), 1);
+    assert_eq!(pre[0].get_text_content(), text);
+    assert!(document.get_elements_by_tag_name("b").is_empty());
+    assert_eq!(document.source.as_deref(), Some(text));
+
+    document.restyle();
+    assert_eq!(
+        document.root.inner_text(),
+        "fn main() {\n    if a < b && c > d {}\n}"
+    );
+}
+
+#[test]
+fn images_are_wrapped_in_a_titled_img() {
+    let image = Image {
+        width: 1,
+        height: 1,
+        pixels: vec![Color::rgb(1, 2, 3)],
+    };
+    let file = TempFile::new("my photo.png", &image.to_png().unwrap());
+    let document = file.load();
+    let title = document.get_elements_by_tag_name("title")[0].get_text_content();
+    assert_eq!(title, format!("icarus-{}-my photo.png", std::process::id()));
+    let img = &document.get_elements_by_tag_name("img")[0];
+    assert_eq!(
+        img.get_attribute("src").as_deref(),
+        Some(Url::from_file_path(&file.0).unwrap().as_str())
+    );
+    assert_eq!(img.get_attribute("alt"), Some(title));
+}